@@ -0,0 +1,21 @@
+//! Parses arbitrary bytes as the body of a request creating a user, as the handler does,
+//! checking that the username of a valid request is the one given, trimmed and normalized.
+#![no_main]
+
+use crowdsource::{
+    domain::crowdsrc::models::user::UserNamePolicy,
+    inbound::http::models::CreateUserHttpRequestBody,
+};
+use libfuzzer_sys::fuzz_target;
+use unicode_normalization::UnicodeNormalization;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(body) = serde_json::from_slice::<CreateUserHttpRequestBody>(data) else {
+        return;
+    };
+    let username = body.username.clone();
+    if let Ok(req) = body.try_into_domain(&UserNamePolicy::default()) {
+        let normalized: String = username.trim().nfc().collect();
+        assert_eq!(req.username().to_string(), normalized);
+    }
+});
@@ -0,0 +1,21 @@
+//! Parses arbitrary strings as email addresses, checking that valid ones round-trip and that
+//! their canonical form is stable.
+#![no_main]
+
+use crowdsource::domain::crowdsrc::models::user::{EmailAddress, EmailCanonicalization};
+use libfuzzer_sys::fuzz_target;
+
+const FOLD_ALL: EmailCanonicalization = EmailCanonicalization {
+    fold_plus_aliases: true,
+    fold_gmail_dots: true,
+};
+
+fuzz_target!(|raw: &str| {
+    if let Ok(email) = EmailAddress::new(raw) {
+        assert!(EmailAddress::new(email.as_str()).is_ok_and(|reparsed| reparsed == email));
+        let canonical = email.canonical(&FOLD_ALL);
+        if let Ok(canonical_email) = EmailAddress::new(&canonical) {
+            assert_eq!(canonical_email.canonical(&FOLD_ALL), canonical);
+        }
+    }
+});
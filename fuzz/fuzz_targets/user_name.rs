@@ -0,0 +1,17 @@
+//! Parses arbitrary strings as usernames, checking that valid ones round-trip through their
+//! display form and hold no whitespace or control characters.
+#![no_main]
+
+use crowdsource::domain::crowdsrc::models::user::UserName;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|raw: &str| {
+    if let Ok(name) = UserName::new(raw) {
+        let displayed = name.to_string();
+        assert!(
+            !displayed.contains(|c: char| c.is_whitespace() || c.is_control()),
+            "accepted {raw:?}"
+        );
+        assert_eq!(UserName::new(&displayed).ok(), Some(name));
+    }
+});
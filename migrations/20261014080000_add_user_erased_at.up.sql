@@ -0,0 +1,2 @@
+-- Mark users whose personal data has been erased
+ALTER TABLE users ADD COLUMN erased_at timestamptz NULL;
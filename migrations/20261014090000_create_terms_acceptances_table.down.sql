@@ -0,0 +1 @@
+DROP TABLE terms_acceptances;
@@ -0,0 +1,9 @@
+-- Create Terms Acceptances Table
+CREATE TABLE terms_acceptances(
+id uuid NOT NULL,
+PRIMARY KEY (id),
+user_id uuid NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+terms_version TEXT NOT NULL,
+accepted_at timestamptz NOT NULL
+);
+CREATE INDEX terms_acceptances_user_id_idx ON terms_acceptances (user_id, accepted_at);
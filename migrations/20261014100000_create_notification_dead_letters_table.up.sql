@@ -0,0 +1,12 @@
+-- Create Notification Dead Letters Table
+CREATE TABLE notification_dead_letters(
+id uuid NOT NULL,
+PRIMARY KEY (id),
+event_type TEXT NOT NULL,
+user_id uuid NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+failure_reason TEXT NOT NULL,
+retry_count INTEGER NOT NULL DEFAULT 0,
+created_at timestamptz NOT NULL,
+last_failed_at timestamptz NOT NULL
+);
+CREATE INDEX notification_dead_letters_created_at_idx ON notification_dead_letters (created_at);
@@ -0,0 +1,8 @@
+-- Create Maintenance Mode Table
+CREATE TABLE maintenance_mode(
+id BOOLEAN NOT NULL DEFAULT TRUE CHECK (id),
+PRIMARY KEY (id),
+enabled BOOLEAN NOT NULL,
+updated_at timestamptz NOT NULL
+);
+INSERT INTO maintenance_mode (id, enabled, updated_at) VALUES (TRUE, FALSE, now());
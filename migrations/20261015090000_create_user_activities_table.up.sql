@@ -0,0 +1,10 @@
+-- Create User Activities Table
+CREATE TABLE user_activities(
+seq BIGSERIAL NOT NULL,
+PRIMARY KEY (seq),
+user_id uuid NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+activity_type TEXT NOT NULL,
+terms_version TEXT,
+occurred_at timestamptz NOT NULL
+);
+CREATE INDEX user_activities_user_id_seq_idx ON user_activities (user_id, seq DESC);
@@ -0,0 +1,7 @@
+-- Create Runtime Config Table
+CREATE TABLE runtime_config(
+name TEXT NOT NULL,
+PRIMARY KEY (name),
+value TEXT NOT NULL,
+updated_at timestamptz NOT NULL
+);
@@ -0,0 +1,2 @@
+-- Look up users by a keyed hash of their email, since encrypted emails differ each time
+ALTER TABLE users ADD COLUMN email_index TEXT NULL UNIQUE;
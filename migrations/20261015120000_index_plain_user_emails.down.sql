@@ -0,0 +1,2 @@
+-- The indexes are kept, since they can't be told apart from those set by the application.
+SELECT 1;
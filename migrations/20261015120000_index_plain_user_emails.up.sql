@@ -0,0 +1,18 @@
+-- Index the plain-text emails of users by their canonical form with the domain lowercased,
+-- skipping emails that are already taken by that form. Other canonical forms, and encrypted
+-- emails, are indexed by `crowdsource-admin reencrypt`.
+WITH canonical AS (
+    SELECT id, email_index, row_number() OVER (PARTITION BY email_index ORDER BY created_at, id) AS rank
+    FROM (
+        SELECT id, created_at,
+            substring(email FROM '^(.*)@[^@]*$') || '@' || lower(substring(email FROM '@([^@]*)$')) AS email_index
+        FROM users
+        WHERE email_index IS NULL AND email NOT LIKE 'enc:%'
+    ) AS plain
+)
+UPDATE users
+SET email_index = canonical.email_index
+FROM canonical
+WHERE users.id = canonical.id
+    AND canonical.rank = 1
+    AND NOT EXISTS (SELECT 1 FROM users AS other WHERE other.email_index = canonical.email_index);
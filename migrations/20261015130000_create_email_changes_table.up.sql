@@ -0,0 +1,9 @@
+-- Create Email Changes Table, holding at most one pending email change per user
+CREATE TABLE email_changes(
+user_id uuid NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+PRIMARY KEY (user_id),
+new_email TEXT NOT NULL,
+token_hash TEXT NOT NULL,
+requested_at timestamptz NOT NULL,
+expires_at timestamptz NOT NULL
+);
@@ -0,0 +1,8 @@
+-- Create Schedules Table, holding when each scheduled job runs next
+CREATE TABLE schedules(
+name TEXT NOT NULL,
+PRIMARY KEY (name),
+cron TEXT NOT NULL,
+next_run_at timestamptz NOT NULL,
+last_run_at timestamptz
+);
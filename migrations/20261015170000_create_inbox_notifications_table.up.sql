@@ -0,0 +1,12 @@
+-- Create Inbox Notifications Table, holding the notifications shown to each user in the app
+CREATE TABLE inbox_notifications(
+id uuid NOT NULL,
+PRIMARY KEY (id),
+user_id uuid NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+kind TEXT NOT NULL,
+created_at timestamptz NOT NULL,
+read_at timestamptz
+);
+CREATE INDEX inbox_notifications_user_id_created_at_idx
+ON inbox_notifications (user_id, created_at DESC);
+CREATE INDEX inbox_notifications_created_at_idx ON inbox_notifications (created_at);
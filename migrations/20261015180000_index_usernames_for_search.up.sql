@@ -0,0 +1,3 @@
+-- Index usernames by trigram, so that searching them by substring doesn't scan all users
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+CREATE INDEX users_username_trgm_idx ON users USING gin (username gin_trgm_ops);
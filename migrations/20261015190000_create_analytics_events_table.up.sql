@@ -0,0 +1,13 @@
+-- Events reported by clients for studying annotation behavior, partitioned by the month they
+-- occurred in, so that old months can be detached or dropped whole. Partitions are created as
+-- events of a month arrive.
+CREATE TABLE analytics_events (
+    user_id uuid NOT NULL,
+    task_id text NOT NULL,
+    kind text NOT NULL,
+    duration_ms bigint,
+    occurred_at timestamptz NOT NULL,
+    received_at timestamptz NOT NULL
+) PARTITION BY RANGE (occurred_at);
+CREATE INDEX analytics_events_task_id_idx ON analytics_events (task_id, occurred_at);
+CREATE INDEX analytics_events_user_id_idx ON analytics_events (user_id);
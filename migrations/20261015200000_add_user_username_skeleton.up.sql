@@ -0,0 +1,4 @@
+-- Look up users by the skeleton of their username, so that usernames that look alike can be
+-- refused. Skeletons are only stored while confusable usernames are refused, and are filled in
+-- for the users created before by `crowdsource-admin reencrypt`.
+ALTER TABLE users ADD COLUMN username_skeleton TEXT NULL UNIQUE;
@@ -0,0 +1,2 @@
+-- Whether users have proven that they own their email, by confirming a change to it
+ALTER TABLE users ADD COLUMN email_verified BOOLEAN NOT NULL DEFAULT false;
@@ -0,0 +1,84 @@
+use std::path::Path;
+
+use anyhow::Context;
+use crowdsource::{
+    backup::BackupSummary, bootstrap, configuration::get_configuration,
+    domain::crowdsrc::models::access_token::Principal, telemetry,
+};
+
+const USAGE: &str = "usage: crowdsource-admin backup <archive> | restore <archive> | reencrypt | reindex | issue-token admin | issue-token user <id>";
+
+enum Command<'a> {
+    Backup(&'a Path),
+    Restore(&'a Path),
+    Reencrypt,
+    Reindex,
+    IssueToken(Principal),
+}
+
+/// Runs an administrative command against the configured database:
+///
+/// - `backup <archive>` dumps all data to an NDJSON archive.
+/// - `restore <archive>` loads an archive into an empty database.
+/// - `reencrypt` encrypts personal data under the current key, after rotating keys, and
+///   reindexes emails, after changing how they are canonicalized.
+/// - `reindex` rebuilds the search index from the users in the database.
+/// - `issue-token admin` and `issue-token user <id>` print an access token for an admin or the
+///   user with the given id.
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let command = match args.as_slice() {
+        ["backup", archive] => Command::Backup(Path::new(archive)),
+        ["restore", archive] => Command::Restore(Path::new(archive)),
+        ["reencrypt"] => Command::Reencrypt,
+        ["reindex"] => Command::Reindex,
+        ["issue-token", "admin"] => Command::IssueToken(Principal::Admin),
+        ["issue-token", "user", id] => Command::IssueToken(Principal::User(
+            uuid::Uuid::parse_str(id).with_context(|| format!("invalid user id '{id}'"))?,
+        )),
+        _ => anyhow::bail!(USAGE),
+    };
+    let settings = get_configuration().context("failed to read configuration")?;
+    telemetry::init_logging(&settings.log)?;
+
+    match command {
+        Command::Backup(archive) => {
+            print_summary("backup", bootstrap::backup(&settings, archive).await?)
+        }
+        Command::Restore(archive) => {
+            print_summary("restore", bootstrap::restore(&settings, archive).await?)
+        }
+        Command::Reencrypt => {
+            let summary = bootstrap::reencrypt(&settings).await?;
+            println!(
+                "reencrypt: {} users, {} re-encrypted, {} reindexed, {} duplicates, {} confusables",
+                summary.users,
+                summary.resealed,
+                summary.reindexed,
+                summary.duplicates,
+                summary.confusables
+            );
+        }
+        Command::Reindex => {
+            println!("reindex: {} users", bootstrap::reindex(&settings).await?);
+        }
+        Command::IssueToken(principal) => {
+            println!("{}", bootstrap::issue_access_token(&settings, principal)?);
+        }
+    }
+    Ok(())
+}
+
+fn print_summary(command: &str, summary: BackupSummary) {
+    println!(
+        "{command}: {} users, {} terms acceptances, {} activities, {} notifications, \
+        {} email changes",
+        summary.users,
+        summary.terms_acceptances,
+        summary.activities,
+        summary.notifications,
+        summary.email_changes
+    );
+}
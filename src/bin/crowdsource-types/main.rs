@@ -0,0 +1,22 @@
+use anyhow::Context;
+use crowdsource::inbound::http::typescript;
+
+const USAGE: &str = "usage: crowdsource-types [<types.d.ts>]";
+
+/// Writes the TypeScript declarations of the HTTP API to the given file, or to stdout:
+///
+/// ```sh
+/// cargo run --features typescript --bin crowdsource-types -- frontend/src/types.d.ts
+/// ```
+fn main() -> anyhow::Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let bundle = typescript::bundle();
+    match args.as_slice() {
+        [] => print!("{bundle}"),
+        [path] => {
+            std::fs::write(path, bundle).with_context(|| format!("failed to write {path}"))?
+        }
+        _ => anyhow::bail!(USAGE),
+    }
+    Ok(())
+}
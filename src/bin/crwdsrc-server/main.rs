@@ -1,3 +1,39 @@
-fn main() {
-    println!("Hello, world!");
+use anyhow::Context;
+use crowdsource::{bootstrap, configuration::get_configuration, telemetry};
+
+/// Runs the server, or with `--check` only the startup checks, exiting non-zero if any fails, or
+/// with `--migrate` only the pending migrations, or with `--seed` only the development data.
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let settings = get_configuration().context("failed to read configuration")?;
+    telemetry::init_logging(&settings.log)?;
+
+    if std::env::args().skip(1).any(|arg| arg == "--check") {
+        let results = bootstrap::check(&settings).await;
+        for result in &results {
+            match &result.outcome {
+                Ok(()) => println!("ok      {}", result.name),
+                Err(e) => println!("FAILED  {}: {:#}", result.name, e),
+            }
+        }
+        if !results.iter().all(bootstrap::CheckResult::is_ok) {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    if std::env::args().skip(1).any(|arg| arg == "--migrate") {
+        return bootstrap::migrate(&settings).await;
+    }
+
+    if std::env::args().skip(1).any(|arg| arg == "--seed") {
+        let summary = bootstrap::seed(&settings).await?;
+        println!(
+            "created {} users ({} erased), {} already existed",
+            summary.created, summary.erased, summary.existing
+        );
+        return Ok(());
+    }
+
+    bootstrap::run(settings).await
 }
@@ -0,0 +1,347 @@
+//! Module `backup` dumps the data held in a [UserRepository] to a versioned NDJSON archive and
+//! restores it into an empty one, independently of the database behind it.
+//!
+//! An archive starts with a header line holding its format version, followed by one line per
+//! [User], with their terms acceptances and whether their email is verified, each followed by
+//! the lines of their activity feed, oldest first. The inbox notifications of all users follow, oldest first, and then the pending
+//! email changes, with the hashes of their tokens so that the tokens already sent stay valid.
+//! Dead letters are not archived, since they only matter to the deployment that failed to
+//! deliver them.
+//!
+//! Erased users are archived with their placeholders, so they stay erased once restored, but
+//! when they were erased is not kept.
+
+use std::io::{BufRead, Write};
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use futures::{StreamExt, TryStreamExt};
+use uuid::Uuid;
+
+use crate::domain::crowdsrc::{
+    models::{
+        activity::{Activity, ActivityKind, ActivityQuery},
+        email_change::{EmailChangeTokenHash, PendingEmailChange},
+        inbox::{InboxKind, InboxNotification},
+        terms::{TermsAcceptance, TermsVersion},
+        user::{EmailAddress, User, UserName},
+    },
+    ports::UserRepository,
+};
+
+/// The version of the archive format, bumped on breaking changes.
+pub const FORMAT_VERSION: u32 = 2;
+
+/// The oldest version of the archive format that can still be restored. Archives of version 1
+/// hold no inbox notifications or pending email changes.
+const OLDEST_FORMAT_VERSION: u32 = 1;
+
+/// What was backed up or restored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BackupSummary {
+    pub users: usize,
+    pub terms_acceptances: usize,
+    pub activities: usize,
+    pub notifications: usize,
+    pub email_changes: usize,
+}
+
+/// A line of an archive.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Record {
+    Header {
+        format_version: u32,
+        created_at: DateTime<Utc>,
+    },
+    User {
+        id: Uuid,
+        username: String,
+        email: String,
+        created_at: DateTime<Utc>,
+        /// Missing from archives made before emails were verified.
+        #[serde(default)]
+        email_verified: bool,
+        terms_acceptances: Vec<TermsAcceptanceRecord>,
+    },
+    Activity {
+        user_id: Uuid,
+        activity: ActivityRecord,
+        occurred_at: DateTime<Utc>,
+    },
+    Notification {
+        id: Uuid,
+        user_id: Uuid,
+        kind: String,
+        created_at: DateTime<Utc>,
+        read_at: Option<DateTime<Utc>>,
+    },
+    EmailChange {
+        user_id: Uuid,
+        new_email: String,
+        token_hash: String,
+        requested_at: DateTime<Utc>,
+        expires_at: DateTime<Utc>,
+    },
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+struct TermsAcceptanceRecord {
+    terms_version: String,
+    accepted_at: DateTime<Utc>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum ActivityRecord {
+    SignedUp,
+    AcceptedTerms { terms_version: String },
+}
+
+/// Writes all data of `repo` to `out` as an archive.
+pub async fn backup<R: UserRepository>(
+    repo: &R,
+    mut out: impl Write,
+) -> anyhow::Result<BackupSummary> {
+    write_record(
+        &mut out,
+        &Record::Header {
+            format_version: FORMAT_VERSION,
+            created_at: Utc::now(),
+        },
+    )?;
+
+    let mut summary = BackupSummary::default();
+    let mut users = std::pin::pin!(repo.stream_users());
+    while let Some(user) = users.try_next().await? {
+        let terms_acceptances = repo.list_terms_acceptances(user.id()).await?;
+        summary.users += 1;
+        summary.terms_acceptances += terms_acceptances.len();
+        write_record(&mut out, &user_record(&user, &terms_acceptances))?;
+
+        let activities = list_all_activity(repo, user.id()).await?;
+        summary.activities += activities.len();
+        for activity in activities.iter().rev() {
+            write_record(&mut out, &activity_record(activity))?;
+        }
+    }
+
+    let mut notifications = std::pin::pin!(repo.stream_notifications());
+    while let Some(notification) = notifications.try_next().await? {
+        summary.notifications += 1;
+        write_record(&mut out, &notification_record(&notification))?;
+    }
+
+    let mut email_changes = std::pin::pin!(repo.stream_email_changes());
+    while let Some((change, token_hash)) = email_changes.try_next().await? {
+        summary.email_changes += 1;
+        write_record(&mut out, &email_change_record(&change, &token_hash))?;
+    }
+    out.flush().context("failed to write archive")?;
+    Ok(summary)
+}
+
+/// Restores the archive read from `input` into `repo`, which must hold no users.
+///
+/// # Errors
+///
+/// Fails if `repo` holds users, or if the archive is malformed or of an unsupported format
+/// version. Users restored before the failure are kept.
+pub async fn restore<R: UserRepository>(
+    repo: &R,
+    input: impl BufRead,
+) -> anyhow::Result<BackupSummary> {
+    anyhow::ensure!(
+        std::pin::pin!(repo.stream_users()).next().await.is_none(),
+        "refusing to restore into a repository that already holds users"
+    );
+
+    let mut lines = input.lines().enumerate();
+    let Some((_, header)) = lines.next() else {
+        anyhow::bail!("archive is empty");
+    };
+    match parse_record(&header.context("failed to read archive")?, 1)? {
+        Record::Header { format_version, .. }
+            if (OLDEST_FORMAT_VERSION..=FORMAT_VERSION).contains(&format_version) => {}
+        Record::Header { format_version, .. } => {
+            anyhow::bail!("unsupported archive format version {format_version}")
+        }
+        _ => anyhow::bail!("archive doesn't start with a header"),
+    }
+
+    let mut summary = BackupSummary::default();
+    for (index, line) in lines {
+        let line = line.context("failed to read archive")?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match parse_record(&line, index + 1)? {
+            Record::Header { .. } => anyhow::bail!("unexpected header on line {}", index + 1),
+            Record::User {
+                id,
+                username,
+                email,
+                created_at,
+                email_verified,
+                terms_acceptances,
+            } => {
+                let user = User::new(
+                    id,
+                    UserName::from_persisted(&username),
+                    EmailAddress::from_persisted(&email)?,
+                    created_at,
+                )
+                .with_email_verified(email_verified);
+                let terms_acceptances = terms_acceptances
+                    .into_iter()
+                    .map(|record| {
+                        Ok(TermsAcceptance::new(
+                            id,
+                            TermsVersion::new(&record.terms_version)?,
+                            record.accepted_at,
+                        ))
+                    })
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+                repo.import_user(&user, &terms_acceptances)
+                    .await
+                    .with_context(|| format!("failed to restore user {id}"))?;
+                summary.users += 1;
+                summary.terms_acceptances += terms_acceptances.len();
+            }
+            Record::Activity {
+                user_id,
+                activity,
+                occurred_at,
+            } => {
+                let kind = match activity {
+                    ActivityRecord::SignedUp => ActivityKind::SignedUp,
+                    ActivityRecord::AcceptedTerms { terms_version } => {
+                        ActivityKind::AcceptedTerms {
+                            version: TermsVersion::new(&terms_version)?,
+                        }
+                    }
+                };
+                repo.record_activity(&Activity::new(user_id, kind, occurred_at))
+                    .await
+                    .with_context(|| format!("failed to restore activity of user {user_id}"))?;
+                summary.activities += 1;
+            }
+            Record::Notification {
+                id,
+                user_id,
+                kind,
+                created_at,
+                read_at,
+            } => {
+                let kind = InboxKind::parse(&kind)
+                    .with_context(|| format!("unknown notification kind '{kind}'"))?;
+                repo.save_notification(&InboxNotification::new(
+                    id, user_id, kind, created_at, read_at,
+                ))
+                .await
+                .with_context(|| format!("failed to restore notification {id}"))?;
+                summary.notifications += 1;
+            }
+            Record::EmailChange {
+                user_id,
+                new_email,
+                token_hash,
+                requested_at,
+                expires_at,
+            } => {
+                let change = PendingEmailChange::new(
+                    user_id,
+                    EmailAddress::from_persisted(&new_email)?,
+                    requested_at,
+                    expires_at,
+                );
+                repo.import_email_change(&change, &EmailChangeTokenHash::new(&token_hash))
+                    .await
+                    .with_context(|| format!("failed to restore email change of user {user_id}"))?;
+                summary.email_changes += 1;
+            }
+        }
+    }
+    Ok(summary)
+}
+
+/// The whole activity feed of the [User] with the given id, newest first.
+async fn list_all_activity<R: UserRepository>(
+    repo: &R,
+    user_id: &Uuid,
+) -> anyhow::Result<Vec<Activity>> {
+    let mut activities = Vec::new();
+    let mut after = None;
+    loop {
+        let page = repo
+            .list_activity(
+                user_id,
+                &ActivityQuery::new(ActivityQuery::MAX_LIMIT, after),
+            )
+            .await?;
+        activities.extend_from_slice(page.activities());
+        match page.next() {
+            Some(next) => after = Some(next),
+            None => return Ok(activities),
+        }
+    }
+}
+
+fn user_record(user: &User, terms_acceptances: &[TermsAcceptance]) -> Record {
+    Record::User {
+        id: *user.id(),
+        username: user.username().to_string(),
+        email: user.email().to_string(),
+        created_at: *user.created_at(),
+        email_verified: user.is_email_verified(),
+        terms_acceptances: terms_acceptances
+            .iter()
+            .map(|acceptance| TermsAcceptanceRecord {
+                terms_version: acceptance.terms_version().to_string(),
+                accepted_at: *acceptance.accepted_at(),
+            })
+            .collect(),
+    }
+}
+
+fn activity_record(activity: &Activity) -> Record {
+    Record::Activity {
+        user_id: *activity.user_id(),
+        activity: match activity.kind() {
+            ActivityKind::SignedUp => ActivityRecord::SignedUp,
+            ActivityKind::AcceptedTerms { version } => ActivityRecord::AcceptedTerms {
+                terms_version: version.to_string(),
+            },
+        },
+        occurred_at: *activity.occurred_at(),
+    }
+}
+
+fn notification_record(notification: &InboxNotification) -> Record {
+    Record::Notification {
+        id: *notification.id(),
+        user_id: *notification.user_id(),
+        kind: notification.kind().as_str().to_string(),
+        created_at: *notification.created_at(),
+        read_at: notification.read_at().copied(),
+    }
+}
+
+fn email_change_record(change: &PendingEmailChange, token_hash: &EmailChangeTokenHash) -> Record {
+    Record::EmailChange {
+        user_id: *change.user_id(),
+        new_email: change.new_email().to_string(),
+        token_hash: token_hash.as_str().to_string(),
+        requested_at: *change.requested_at(),
+        expires_at: *change.expires_at(),
+    }
+}
+
+fn write_record(out: &mut impl Write, record: &Record) -> anyhow::Result<()> {
+    serde_json::to_writer(&mut *out, record).context("failed to serialize archive record")?;
+    out.write_all(b"\n").context("failed to write archive")
+}
+
+fn parse_record(line: &str, line_number: usize) -> anyhow::Result<Record> {
+    serde_json::from_str(line).with_context(|| format!("invalid record on line {line_number}"))
+}
@@ -0,0 +1,884 @@
+//! Module `bootstrap` assembles the application from its [Settings] and checks, before the
+//! listener is bound, that everything it depends on is in place, so that problems show up at
+//! startup instead of on the first request.
+
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::Context;
+use sqlx::{PgPool, postgres::PgPoolOptions};
+use tokio::sync::watch;
+
+use crate::{
+    backup,
+    configuration::{self, AnalyticsSettings, CONFIGURATION_FILE, EmailDeliverySettings, Settings},
+    dev_seed,
+    domain::crowdsrc::{
+        analytics_buffer::{AnalyticsBuffer, BufferPolicy},
+        caching_service::{CachePolicy, CachingCrowdSrcService},
+        indexed_service::{self, IndexedCrowdSrcService},
+        models::{
+            access_token::{AccessToken, Principal},
+            runtime_config::{LogLevel, RuntimeConfig},
+            schedule::CronExpression,
+            signed_url::SigningKey,
+            terms::TermsVersion,
+            throttle::ThrottlePolicy,
+            user::{EmailAddress, EmailCanonicalization, UserNamePolicy},
+        },
+        ports::{CrowdSrcService, RuntimeConfigStore, UserNotifier, UserRepository},
+        service::Service,
+    },
+    inbound::http::{HttpServer, HttpServerConfig, RouteLimits, RuntimeConfigControl},
+    migrations,
+    outbound::{
+        clickhouse_analytics_sink::ClickHouseAnalyticsSink,
+        composite_user_notifier::CompositeUserNotifier,
+        config_feature_flags::ConfigFeatureFlags,
+        email_templates::EmailTemplates,
+        email_user_notifier::EmailUserNotifier,
+        field_cipher::FieldCipher,
+        http_client::HttpClient,
+        in_memory_submission_throttle::InMemorySubmissionThrottle,
+        meilisearch_index::MeilisearchIndex,
+        proof_of_work_challenge::ProofOfWorkChallenge,
+        remote_feature_flags::RemoteFeatureFlags,
+        retry::RetryPolicy,
+        retrying_repository::RetryingRepository,
+        sendgrid_user_notifier::SendGridUserNotifier,
+        ses_user_notifier::SesUserNotifier,
+        sqlx_advisory_lock::SqlxAdvisoryLock,
+        sqlx_analytics_sink::SqlxAnalyticsSink,
+        sqlx_maintenance_switch::SqlxMaintenanceSwitch,
+        sqlx_runtime_config_store::SqlxRuntimeConfigStore,
+        sqlx_schedule_store::SqlxScheduleStore,
+        sqlx_transaction::SqlxTransactionManager,
+        sqlx_user_repository::{ReencryptionSummary, SqlxUserRepository},
+        timed_repository::TimedRepository,
+    },
+    scheduler::Scheduler,
+    shutdown, telemetry,
+};
+
+#[cfg(feature = "error-reporting")]
+use crate::error_reporting;
+
+/// How long to wait for Postgres before giving up on a check.
+const DATABASE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long flags fetched from [Settings::feature_flags_url] are used before refetching them.
+const FEATURE_FLAGS_TTL: Duration = Duration::from_secs(60);
+
+/// How long clients are told to wait while in maintenance mode.
+const MAINTENANCE_RETRY_AFTER: Duration = Duration::from_secs(300);
+
+/// How many signups a single source may make, unless tuned at runtime.
+const SUBMISSION_THROTTLE_POLICY: ThrottlePolicy = ThrottlePolicy {
+    max_submissions: 10,
+    window: Duration::from_secs(3600),
+};
+
+/// How often the configuration file is checked for changes.
+const CONFIGURATION_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How often the runtime config is reloaded, to pick up changes made through other instances.
+const RUNTIME_CONFIG_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How many milliseconds repository calls may take before being logged as slow, unless
+/// configured.
+const DEFAULT_SLOW_QUERY_THRESHOLD_MS: u64 = 250;
+
+/// How many times reads failing with transient errors are retried, unless configured.
+const DEFAULT_DATABASE_MAX_RETRIES: u32 = 3;
+
+/// How many milliseconds to wait before retrying a read the first time, unless configured.
+const DEFAULT_DATABASE_RETRY_DELAY_MS: u64 = 50;
+
+/// How many milliseconds requests may take before being logged as slow, unless configured.
+const DEFAULT_REQUEST_LATENCY_BUDGET_MS: u64 = 1000;
+
+/// How long in-flight requests may take to complete once shutdown is requested.
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How long access tokens issued by [issue_access_token] are valid.
+const ACCESS_TOKEN_TTL: chrono::TimeDelta = chrono::TimeDelta::days(30);
+
+/// How long, and how many, looked up users are cached.
+const USER_CACHE_POLICY: CachePolicy = CachePolicy {
+    ttl: Duration::from_secs(30),
+    max_entries: 10_000,
+};
+
+/// How many users are re-encrypted per transaction.
+const REENCRYPTION_BATCH_SIZE: u32 = 500;
+
+/// How many users are sent to the search index per request when it is rebuilt.
+const REINDEX_BATCH_SIZE: usize = 1000;
+
+/// The uid of the Meilisearch index users are kept in, unless configured otherwise.
+const DEFAULT_SEARCH_INDEX: &str = "users";
+
+/// How many analytics events are buffered, and when they are written.
+const ANALYTICS_BUFFER_POLICY: BufferPolicy = BufferPolicy {
+    capacity: 10_000,
+    max_batch: 500,
+    flush_interval: Duration::from_secs(5),
+};
+
+/// The ClickHouse table analytics events are inserted into, unless configured otherwise.
+const DEFAULT_ANALYTICS_TABLE: &str = "analytics_events";
+
+/// How often the scheduler checks whether jobs are due.
+const SCHEDULER_TICK: Duration = Duration::from_secs(30);
+
+/// The job discarding email changes that were never confirmed.
+const PURGE_EXPIRED_EMAIL_CHANGES: &str = "purge_expired_email_changes";
+/// The job discarding inbox notifications past their retention.
+const PURGE_OLD_NOTIFICATIONS: &str = "purge_old_notifications";
+
+/// The scheduled jobs and the cron expressions they run on, unless configured in
+/// [Settings::schedules].
+const SCHEDULED_JOBS: [(&str, &str); 2] = [
+    (PURGE_EXPIRED_EMAIL_CHANGES, "0 * * * *"),
+    (PURGE_OLD_NOTIFICATIONS, "30 3 * * *"),
+];
+
+/// The outcome of one startup check.
+#[derive(Debug)]
+pub struct CheckResult {
+    pub name: &'static str,
+    pub outcome: anyhow::Result<()>,
+}
+
+impl CheckResult {
+    pub fn is_ok(&self) -> bool {
+        self.outcome.is_ok()
+    }
+}
+
+/// Runs all startup checks against `settings`.
+///
+/// The migration check is only run if the database is reachable.
+pub async fn check(settings: &Settings) -> Vec<CheckResult> {
+    let mut results = vec![CheckResult {
+        name: "configuration",
+        outcome: check_configuration(settings),
+    }];
+    match connect(settings).await {
+        Ok(db_pool) => {
+            results.push(CheckResult {
+                name: "database",
+                outcome: Ok(()),
+            });
+            results.push(CheckResult {
+                name: "migrations",
+                outcome: check_migrations(&db_pool).await,
+            });
+        }
+        Err(e) => results.push(CheckResult {
+            name: "database",
+            outcome: Err(e),
+        }),
+    }
+    results
+}
+
+/// Checks the application, then serves it until the server fails.
+///
+/// Pending migrations are applied first if [Settings::auto_migrate] is set, and development
+/// data is seeded if [Settings::dev_seed] is set.
+///
+/// # Errors
+///
+/// Fails without binding the listener if any startup check fails.
+pub async fn run(settings: Settings) -> anyhow::Result<()> {
+    CrowdsourceApp::new(settings).run().await
+}
+
+/// Builds a component of the application from the [Settings] and the database it connects to.
+type ComponentFactory<T> = Box<dyn FnOnce(&Settings, &PgPool) -> anyhow::Result<T> + Send>;
+
+/// The [CrowdSrcService] the shipped server serves: the [Service] over a timed `R` retrying
+/// reads, indexed for search if configured, cached.
+pub type DefaultCrowdSrcService<R, N> = CachingCrowdSrcService<IndexedService<R, N>>;
+
+/// The [DefaultCrowdSrcService] before caching.
+type IndexedService<R, N> =
+    IndexedCrowdSrcService<Service<RetryingRepository<TimedRepository<R>>, N>, MeilisearchIndex>;
+
+/// Composes the application from its [Settings], as the shipped server does, so that it can be
+/// embedded in other binaries.
+///
+/// Every component defaults to the one the shipped server uses, and the `with_` methods
+/// replace one, keeping the others. The [UserRepository] given is still timed and retried, and the
+/// resulting [Service] indexed and cached, as configured. Decorators added by
+/// [CrowdsourceApp::with_service_decorator] then wrap the [DefaultCrowdSrcService], the last
+/// one added being the outermost.
+///
+/// ```no_run
+/// # async fn example(settings: crowdsource::configuration::Settings) -> anyhow::Result<()> {
+/// use crowdsource::{CrowdsourceApp, outbound::collecting_user_notifier::CollectingUserNotifier};
+///
+/// CrowdsourceApp::new(settings)
+///     .with_user_notifier(|_, _| Ok(CollectingUserNotifier::new(Default::default())))
+///     .run()
+///     .await
+/// # }
+/// ```
+pub struct CrowdsourceApp<
+    R: UserRepository = SqlxUserRepository,
+    N: UserNotifier = CompositeUserNotifier,
+    CS = DefaultCrowdSrcService<R, N>,
+> {
+    settings: Settings,
+    user_repository: ComponentFactory<R>,
+    user_notifier: ComponentFactory<N>,
+    decorate: Box<dyn FnOnce(DefaultCrowdSrcService<R, N>) -> CS + Send>,
+}
+
+impl CrowdsourceApp {
+    pub fn new(settings: Settings) -> Self {
+        Self {
+            settings,
+            decorate: Box::new(|service| service),
+            user_repository: Box::new(|settings, db_pool| {
+                user_repository(settings, db_pool.clone())
+            }),
+            user_notifier: Box::new(|settings, _| user_notifier(settings)),
+        }
+    }
+}
+
+impl<R: UserRepository, N: UserNotifier> CrowdsourceApp<R, N> {
+    /// Stores users in the [UserRepository] built by `factory` instead of in Postgres.
+    pub fn with_user_repository<R2: UserRepository>(
+        self,
+        factory: impl FnOnce(&Settings, &PgPool) -> anyhow::Result<R2> + Send + 'static,
+    ) -> CrowdsourceApp<R2, N> {
+        CrowdsourceApp {
+            settings: self.settings,
+            user_repository: Box::new(factory),
+            user_notifier: self.user_notifier,
+            decorate: Box::new(|service| service),
+        }
+    }
+
+    /// Notifies users through the [UserNotifier] built by `factory` instead of by email.
+    pub fn with_user_notifier<N2: UserNotifier>(
+        self,
+        factory: impl FnOnce(&Settings, &PgPool) -> anyhow::Result<N2> + Send + 'static,
+    ) -> CrowdsourceApp<R, N2> {
+        CrowdsourceApp {
+            settings: self.settings,
+            user_repository: self.user_repository,
+            user_notifier: Box::new(factory),
+            decorate: Box::new(|service| service),
+        }
+    }
+}
+
+impl<R: UserRepository, N: UserNotifier, CS: CrowdSrcService> CrowdsourceApp<R, N, CS> {
+    /// Serves the [CrowdSrcService] returned by `decorator`, given the one built so far, such as
+    /// an [ObservedCrowdSrcService](crate::domain::crowdsrc::observed_service::ObservedCrowdSrcService)
+    /// adding cross-cutting behavior around it.
+    pub fn with_service_decorator<CS2: CrowdSrcService>(
+        self,
+        decorator: impl FnOnce(CS) -> CS2 + Send + 'static,
+    ) -> CrowdsourceApp<R, N, CS2> {
+        let decorate = self.decorate;
+        CrowdsourceApp {
+            settings: self.settings,
+            user_repository: self.user_repository,
+            user_notifier: self.user_notifier,
+            decorate: Box::new(move |service| decorator(decorate(service))),
+        }
+    }
+
+    /// Checks the application, then serves it until a shutdown signal is received.
+    ///
+    /// # Errors
+    ///
+    /// Fails without binding the listener if any startup check fails.
+    pub async fn run(self) -> anyhow::Result<()> {
+        self.build().await?.run_until(shutdown::signal()).await
+    }
+
+    /// Checks the application and starts its background jobs, returning the [HttpServer] to
+    /// run.
+    ///
+    /// Pending migrations are applied first if [Settings::auto_migrate] is set, and development
+    /// data is seeded if [Settings::dev_seed] is set.
+    ///
+    /// # Errors
+    ///
+    /// Fails without binding the listener if any startup check fails, or if a component can't
+    /// be built.
+    pub async fn build(self) -> anyhow::Result<HttpServer> {
+        let Self {
+            settings,
+            user_repository,
+            user_notifier,
+            decorate,
+        } = self;
+        install_error_reporting(&settings)?;
+        if settings.auto_migrate {
+            migrate(&settings).await?;
+        }
+        let failures: Vec<String> = check(&settings)
+            .await
+            .into_iter()
+            .filter_map(|result| {
+                let e = result.outcome.err()?;
+                Some(format!("{}: {:#}", result.name, e))
+            })
+            .collect();
+        anyhow::ensure!(
+            failures.is_empty(),
+            "startup checks failed:\n{}",
+            failures.join("\n")
+        );
+
+        let db_pool = connect(&settings).await?;
+        let crwdsrc_service = crwdsrc_service(
+            &settings,
+            &db_pool,
+            user_repository(&settings, &db_pool)?,
+            user_notifier(&settings, &db_pool)?,
+        )?;
+        if settings.dev_seed {
+            let summary = dev_seed::seed(
+                &crwdsrc_service,
+                &TermsVersion::new(&settings.terms_version)?,
+            )
+            .await?;
+            tracing::info!(?summary, "seeded development data");
+        }
+        // Challenges only need to outlive the process that issued them, so a key per process will do.
+        let challenge_key = uuid::Uuid::new_v4();
+        let abuse_challenge =
+            ProofOfWorkChallenge::new(challenge_key.as_bytes(), 16, Duration::from_secs(300));
+        let submission_throttle = InMemorySubmissionThrottle::new(SUBMISSION_THROTTLE_POLICY);
+
+        let signed_url_key = match &settings.signed_url_secret {
+            Some(secret) => secret.as_bytes().to_vec(),
+            None => {
+                tracing::warn!("signed_url_secret is not set, shared links expire on restart");
+                uuid::Uuid::new_v4().as_bytes().to_vec()
+            }
+        };
+        let access_token_key = match &settings.access_token_secret {
+            Some(secret) => secret.as_bytes().to_vec(),
+            None => {
+                tracing::warn!(
+                    "access_token_secret is not set, no access token is accepted across restarts"
+                );
+                uuid::Uuid::new_v4().as_bytes().to_vec()
+            }
+        };
+
+        let crwdsrc_service = decorate(CachingCrowdSrcService::new(
+            crwdsrc_service,
+            USER_CACHE_POLICY,
+        ));
+        let mut scheduler = Scheduler::new(
+            SqlxScheduleStore::new(db_pool.clone()),
+            SqlxAdvisoryLock::new(db_pool.clone()),
+            SCHEDULER_TICK,
+        );
+        let service = crwdsrc_service.clone();
+        scheduler
+            .register(
+                PURGE_EXPIRED_EMAIL_CHANGES,
+                &schedule(&settings, PURGE_EXPIRED_EMAIL_CHANGES)?,
+                move || {
+                    let service = service.clone();
+                    async move {
+                        service.purge_expired_email_changes().await?;
+                        Ok(())
+                    }
+                },
+            )
+            .await?;
+        let service = crwdsrc_service.clone();
+        scheduler
+            .register(
+                PURGE_OLD_NOTIFICATIONS,
+                &schedule(&settings, PURGE_OLD_NOTIFICATIONS)?,
+                move || {
+                    let service = service.clone();
+                    async move {
+                        service.purge_old_notifications().await?;
+                        Ok(())
+                    }
+                },
+            )
+            .await?;
+        tokio::spawn(scheduler.run());
+
+        let route_limits = RouteLimits {
+            latency_budget: Some(Duration::from_millis(
+                settings
+                    .request_latency_budget_ms
+                    .unwrap_or(DEFAULT_REQUEST_LATENCY_BUDGET_MS),
+            )),
+            max_concurrent_requests: settings.max_concurrent_requests,
+            max_p99_latency: settings.max_p99_latency_ms.map(Duration::from_millis),
+            ..RouteLimits::default()
+        };
+
+        let port = settings.application_port.to_string();
+        let config = HttpServerConfig {
+            port: &port,
+            static_dir: settings.static_dir.as_deref().map(Path::new),
+            challenged_routes: &[],
+            throttle_allowlist: &[],
+            api_limits: route_limits,
+            admin_limits: route_limits,
+            maintenance_mode: settings.maintenance_mode,
+            maintenance_retry_after: MAINTENANCE_RETRY_AFTER,
+            signed_url_key: &signed_url_key,
+            access_token_key: &access_token_key,
+            username_policy: UserNamePolicy {
+                max_graphemes: settings
+                    .usernames
+                    .max_length
+                    .unwrap_or(UserNamePolicy::DEFAULT_MAX_GRAPHEMES),
+            },
+            reuse_port: settings.reuse_port,
+            drain_timeout: DRAIN_TIMEOUT,
+        };
+        let maintenance_switch = SqlxMaintenanceSwitch::new(db_pool.clone());
+        let runtime_config_store = SqlxRuntimeConfigStore::new(db_pool.clone());
+        let runtime_config = runtime_config_store.load().await.unwrap_or_else(|e| {
+            tracing::warn!("using configured settings, runtime config could not be read: {e:#}");
+            RuntimeConfig::default()
+        });
+        let (runtime_config_updates, runtime_config_changes) = watch::channel(runtime_config);
+        tokio::spawn(apply_runtime_config(
+            runtime_config_changes,
+            submission_throttle.clone(),
+        ));
+        tokio::spawn(refresh_runtime_config(
+            runtime_config_store.clone(),
+            runtime_config_updates.clone(),
+        ));
+        let runtime_config = RuntimeConfigControl {
+            store: runtime_config_store,
+            updates: runtime_config_updates,
+        };
+        let transaction_manager = SqlxTransactionManager::new(db_pool);
+        let feature_flags = ConfigFeatureFlags::new(settings.feature_flags.clone());
+        let (settings_updates, settings_changes) = watch::channel(settings.clone());
+        tokio::spawn(configuration::watch_configuration(
+            PathBuf::from(CONFIGURATION_FILE),
+            settings_updates,
+            CONFIGURATION_POLL_INTERVAL,
+        ));
+        tokio::spawn(apply_configuration(settings_changes, feature_flags.clone()));
+        let server = match &settings.feature_flags_url {
+            Some(url) => {
+                HttpServer::new(
+                    crwdsrc_service,
+                    transaction_manager,
+                    abuse_challenge,
+                    submission_throttle,
+                    RemoteFeatureFlags::new(
+                        http_client(&settings)?,
+                        url.clone(),
+                        FEATURE_FLAGS_TTL,
+                        feature_flags,
+                    ),
+                    maintenance_switch,
+                    runtime_config,
+                    config,
+                )
+                .await?
+            }
+            None => {
+                HttpServer::new(
+                    crwdsrc_service,
+                    transaction_manager,
+                    abuse_challenge,
+                    submission_throttle,
+                    feature_flags,
+                    maintenance_switch,
+                    runtime_config,
+                    config,
+                )
+                .await?
+            }
+        };
+        Ok(server)
+    }
+}
+
+/// Applies the reloaded [Settings] that can change while running, whenever they change.
+///
+/// Other settings, such as the database or the port, only apply once restarted.
+async fn apply_configuration(
+    mut changes: watch::Receiver<Settings>,
+    feature_flags: ConfigFeatureFlags,
+) {
+    while changes.changed().await.is_ok() {
+        let settings = changes.borrow_and_update().clone();
+        if let Err(e) = telemetry::reload(&settings.log) {
+            tracing::warn!("failed to apply reloaded log settings: {e:#}");
+        }
+        feature_flags.set_flags(settings.feature_flags);
+    }
+}
+
+/// Applies the [RuntimeConfig] to the components that can change while running, initially and
+/// whenever it changes.
+async fn apply_runtime_config(
+    mut changes: watch::Receiver<RuntimeConfig>,
+    submission_throttle: InMemorySubmissionThrottle,
+) {
+    loop {
+        let config = changes.borrow_and_update().clone();
+        if let Err(e) = telemetry::set_level(config.log_level.as_ref().map(LogLevel::as_str)) {
+            tracing::warn!("failed to apply runtime log level: {e:#}");
+        }
+        submission_throttle.set_policy(ThrottlePolicy {
+            max_submissions: config
+                .max_submissions
+                .unwrap_or(SUBMISSION_THROTTLE_POLICY.max_submissions),
+            ..SUBMISSION_THROTTLE_POLICY
+        });
+        if changes.changed().await.is_err() {
+            return;
+        }
+    }
+}
+
+/// Reloads the [RuntimeConfig] from `store` every [RUNTIME_CONFIG_REFRESH_INTERVAL], publishing
+/// it through `updates` if it was changed elsewhere.
+async fn refresh_runtime_config(
+    store: SqlxRuntimeConfigStore,
+    updates: watch::Sender<RuntimeConfig>,
+) {
+    let mut interval = tokio::time::interval(RUNTIME_CONFIG_REFRESH_INTERVAL);
+    interval.tick().await;
+    loop {
+        interval.tick().await;
+        match store.load().await {
+            Ok(config) => {
+                updates.send_if_modified(|current| {
+                    let modified = *current != config;
+                    *current = config;
+                    modified
+                });
+            }
+            Err(e) => tracing::warn!("failed to reload runtime config: {e:#}"),
+        }
+    }
+}
+
+/// Seeds the database of `settings` with development data.
+pub async fn seed(settings: &Settings) -> anyhow::Result<dev_seed::SeedSummary> {
+    let db_pool = connect(settings).await?;
+    let crwdsrc_service = crwdsrc_service(
+        settings,
+        &db_pool,
+        user_repository(settings, db_pool.clone())?,
+        user_notifier(settings)?,
+    )?;
+    dev_seed::seed(
+        &crwdsrc_service,
+        &TermsVersion::new(&settings.terms_version)?,
+    )
+    .await
+}
+
+/// Backs up the database of `settings` to the archive at `path`.
+pub async fn backup(settings: &Settings, path: &Path) -> anyhow::Result<backup::BackupSummary> {
+    let repo = user_repository(settings, connect(settings).await?)?;
+    let file = std::fs::File::create(path)
+        .with_context(|| format!("failed to create {}", path.display()))?;
+    backup::backup(&repo, std::io::BufWriter::new(file)).await
+}
+
+/// Restores the archive at `path` into the database of `settings`, applying pending
+/// migrations first, so that a freshly created database can be restored into.
+pub async fn restore(settings: &Settings, path: &Path) -> anyhow::Result<backup::BackupSummary> {
+    let db_pool = connect(settings).await?;
+    migrations::run(&db_pool).await?;
+    let file =
+        std::fs::File::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+    backup::restore(
+        &user_repository(settings, db_pool)?,
+        std::io::BufReader::new(file),
+    )
+    .await
+}
+
+/// Re-encrypts the personal data in the database of `settings` that is stored in plain text or
+/// under a retired key, so that the key can be removed from [Settings::field_encryption], and
+/// reindexes emails and usernames after [Settings::email_canonicalization] or
+/// [Settings::usernames] is changed.
+pub async fn reencrypt(settings: &Settings) -> anyhow::Result<ReencryptionSummary> {
+    let db_pool = connect(settings).await?;
+    user_repository(settings, db_pool)?
+        .reencrypt_users(REENCRYPTION_BATCH_SIZE)
+        .await
+}
+
+/// Issues an [AccessToken] for `principal`, signed with [Settings::access_token_secret].
+pub fn issue_access_token(
+    settings: &Settings,
+    principal: Principal,
+) -> anyhow::Result<AccessToken> {
+    let secret = settings
+        .access_token_secret
+        .as_ref()
+        .context("access_token_secret is not set")?;
+    Ok(AccessToken::issue(
+        &SigningKey::new(secret.as_bytes()),
+        principal,
+        chrono::Utc::now() + ACCESS_TOKEN_TTL,
+    ))
+}
+
+/// Rebuilds the search index of [Settings::search] from the users in the database of
+/// `settings`, returning how many were indexed.
+pub async fn reindex(settings: &Settings) -> anyhow::Result<u64> {
+    let index = search_index(settings)?.context("search is not configured")?;
+    let repo = user_repository(settings, connect(settings).await?)?;
+    Ok(indexed_service::reindex(repo.stream_users(), &index, REINDEX_BATCH_SIZE).await?)
+}
+
+/// The [SqlxUserRepository] of `settings`, encrypting personal data if configured to.
+fn user_repository(settings: &Settings, db_pool: PgPool) -> anyhow::Result<SqlxUserRepository> {
+    let canonicalization = &settings.email_canonicalization;
+    let repo = SqlxUserRepository::new(db_pool)
+        .with_email_canonicalization(EmailCanonicalization {
+            fold_plus_aliases: canonicalization.fold_plus_aliases,
+            fold_gmail_dots: canonicalization.fold_gmail_dots,
+        })
+        .with_confusable_usernames_rejected(settings.usernames.reject_confusables);
+    Ok(match field_cipher(settings)? {
+        Some(cipher) => repo.with_cipher(cipher),
+        None => repo,
+    })
+}
+
+fn field_cipher(settings: &Settings) -> anyhow::Result<Option<FieldCipher>> {
+    settings
+        .field_encryption
+        .as_ref()
+        .map(|encryption| {
+            FieldCipher::from_hex(
+                &encryption.keys,
+                &encryption.current_key,
+                &encryption.index_key,
+            )
+            .context("invalid field_encryption")
+        })
+        .transpose()
+}
+
+/// The [HttpClient] third-party services are called with, as configured in
+/// [Settings::outbound_http].
+fn http_client(settings: &Settings) -> anyhow::Result<HttpClient> {
+    HttpClient::new(&settings.outbound_http)
+}
+
+/// The [AnalyticsBuffer] writing to the sink of [Settings::analytics].
+fn analytics_buffer(settings: &Settings, db_pool: &PgPool) -> anyhow::Result<AnalyticsBuffer> {
+    Ok(match &settings.analytics {
+        AnalyticsSettings::Postgres => AnalyticsBuffer::spawn(
+            SqlxAnalyticsSink::new(db_pool.clone()),
+            ANALYTICS_BUFFER_POLICY,
+        ),
+        AnalyticsSettings::ClickHouse(clickhouse) => AnalyticsBuffer::spawn(
+            clickhouse_analytics_sink(settings, clickhouse)?,
+            ANALYTICS_BUFFER_POLICY,
+        ),
+    })
+}
+
+fn clickhouse_analytics_sink(
+    settings: &Settings,
+    clickhouse: &configuration::ClickHouseSettings,
+) -> anyhow::Result<ClickHouseAnalyticsSink> {
+    reqwest::Url::parse(&clickhouse.url).context("invalid analytics.url")?;
+    Ok(ClickHouseAnalyticsSink::new(
+        http_client(settings)?,
+        &clickhouse.url,
+        clickhouse.user.clone(),
+        clickhouse.password.clone(),
+        clickhouse
+            .table
+            .as_deref()
+            .unwrap_or(DEFAULT_ANALYTICS_TABLE),
+    ))
+}
+
+/// The [MeilisearchIndex] of [Settings::search], if configured.
+fn search_index(settings: &Settings) -> anyhow::Result<Option<MeilisearchIndex>> {
+    settings
+        .search
+        .as_ref()
+        .map(|search| {
+            reqwest::Url::parse(&search.url).context("invalid search.url")?;
+            Ok(MeilisearchIndex::new(
+                http_client(settings)?,
+                &search.url,
+                search.api_key.clone(),
+                search.index.as_deref().unwrap_or(DEFAULT_SEARCH_INDEX),
+            ))
+        })
+        .transpose()
+}
+
+/// The [UserNotifier] delivering emails through the provider of [Settings::email_delivery].
+fn user_notifier(settings: &Settings) -> anyhow::Result<CompositeUserNotifier> {
+    let templates = EmailTemplates::from_dir(&settings.email_templates_dir)?;
+    let from = |from: &str| EmailAddress::new(from).context("invalid email_delivery.from address");
+    let notifier = CompositeUserNotifier::new();
+    Ok(match &settings.email_delivery {
+        EmailDeliverySettings::Log => notifier.with(EmailUserNotifier::new(templates)),
+        EmailDeliverySettings::SendGrid(sendgrid) => notifier.with(SendGridUserNotifier::new(
+            templates,
+            http_client(settings)?,
+            sendgrid.api_key.clone(),
+            from(&sendgrid.from)?,
+            sendgrid.sandbox,
+        )),
+        EmailDeliverySettings::Ses(ses) => notifier.with(SesUserNotifier::new(
+            templates,
+            http_client(settings)?,
+            ses.region.clone(),
+            ses.access_key_id.clone(),
+            ses.secret_access_key.clone(),
+            from(&ses.from)?,
+            ses.sandbox,
+        )),
+    })
+}
+
+/// The [CronExpression] the scheduled job `name` runs on, as configured or by default.
+fn schedule(settings: &Settings, name: &str) -> anyhow::Result<CronExpression> {
+    let (_, default) = SCHEDULED_JOBS
+        .iter()
+        .find(|(job, _)| *job == name)
+        .with_context(|| format!("unknown scheduled job {name}"))?;
+    let expression = settings
+        .schedules
+        .get(name)
+        .map_or(*default, String::as_str);
+    CronExpression::parse(expression).with_context(|| format!("invalid schedules.{name}"))
+}
+
+/// The [Service] of `settings` over `user_repo`, timed and retried, and `user_notifier`,
+/// recording analytics into the sink of `settings` and indexing users in its search index, if
+/// any.
+fn crwdsrc_service<R: UserRepository, N: UserNotifier>(
+    settings: &Settings,
+    db_pool: &PgPool,
+    user_repo: R,
+    user_notifier: N,
+) -> anyhow::Result<IndexedService<R, N>> {
+    let terms_version = TermsVersion::new(&settings.terms_version)?;
+    let retries = &settings.database_retries;
+    let service = Service::new(
+        RetryingRepository::new(
+            TimedRepository::new(
+                user_repo,
+                Duration::from_millis(
+                    settings
+                        .slow_query_threshold_ms
+                        .unwrap_or(DEFAULT_SLOW_QUERY_THRESHOLD_MS),
+                ),
+            ),
+            RetryPolicy::new(
+                retries.max_retries.unwrap_or(DEFAULT_DATABASE_MAX_RETRIES),
+                Duration::from_millis(
+                    retries
+                        .initial_delay_ms
+                        .unwrap_or(DEFAULT_DATABASE_RETRY_DELAY_MS),
+                ),
+            ),
+        ),
+        user_notifier,
+        terms_version,
+    )
+    .with_analytics(analytics_buffer(settings, db_pool)?);
+    Ok(IndexedCrowdSrcService::new(
+        service,
+        search_index(settings)?,
+    ))
+}
+
+#[cfg(feature = "error-reporting")]
+fn install_error_reporting(settings: &Settings) -> anyhow::Result<()> {
+    if let Some(dsn) = &settings.error_reporting_dsn {
+        error_reporting::install(error_reporting::ErrorReporter::from_dsn(
+            dsn,
+            http_client(settings)?,
+        )?)?;
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "error-reporting"))]
+fn install_error_reporting(settings: &Settings) -> anyhow::Result<()> {
+    if settings.error_reporting_dsn.is_some() {
+        tracing::warn!("error_reporting_dsn is ignored, built without the error-reporting feature");
+    }
+    Ok(())
+}
+
+/// Applies all pending migrations to the database of `settings`.
+pub async fn migrate(settings: &Settings) -> anyhow::Result<()> {
+    migrations::run(&connect(settings).await?).await
+}
+
+fn check_configuration(settings: &Settings) -> anyhow::Result<()> {
+    TermsVersion::new(&settings.terms_version).context("invalid terms_version")?;
+    field_cipher(settings)?;
+    http_client(settings)?;
+    user_notifier(settings)?;
+    search_index(settings)?;
+    if let AnalyticsSettings::ClickHouse(clickhouse) = &settings.analytics {
+        clickhouse_analytics_sink(settings, clickhouse)?;
+    }
+    for name in settings.schedules.keys() {
+        schedule(settings, name)?;
+    }
+    if let Some(static_dir) = &settings.static_dir {
+        anyhow::ensure!(
+            Path::new(static_dir).is_dir(),
+            "static_dir {} is not a directory",
+            static_dir
+        );
+    }
+    Ok(())
+}
+
+async fn connect(settings: &Settings) -> anyhow::Result<PgPool> {
+    let db_pool = PgPoolOptions::new()
+        .acquire_timeout(DATABASE_TIMEOUT)
+        .connect_with(settings.database.connection_options())
+        .await
+        .with_context(|| {
+            format!(
+                "failed to connect to Postgres at {}:{}",
+                settings.database.host, settings.database.port
+            )
+        })?;
+    Ok(db_pool)
+}
+
+/// Fails if any migration of the crate has not been applied to the database.
+async fn check_migrations(db_pool: &PgPool) -> anyhow::Result<()> {
+    let pending = migrations::pending(db_pool).await?;
+    anyhow::ensure!(
+        pending.is_empty(),
+        "pending migrations: {}",
+        pending.join(", ")
+    );
+    Ok(())
+}
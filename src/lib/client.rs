@@ -0,0 +1,319 @@
+/*!
+   Module `client` provides a typed client for the HTTP API, so that Rust consumers can call it
+   with the same request and response bodies the server uses.
+*/
+
+use reqwest::{Method, RequestBuilder, StatusCode};
+use serde::{Serialize, de::DeserializeOwned};
+use uuid::Uuid;
+
+pub use crate::inbound::http::models;
+use crate::inbound::http::models::*;
+
+/// The header carrying the solution to an abuse challenge, for the routes requiring one.
+const CHALLENGE_SOLUTION_HEADER: &str = "x-challenge-solution";
+
+/// A client of the HTTP API served at a base URL, such as `https://crowdsource.example.com`.
+///
+/// Each method sends one request, and returns the `data` of a successful response.
+#[derive(Debug, Clone)]
+pub struct CrowdsourceClient {
+    http: reqwest::Client,
+    base_url: String,
+    access_token: Option<String>,
+}
+
+impl CrowdsourceClient {
+    pub fn new(base_url: &str) -> Self {
+        Self::with_http_client(reqwest::Client::new(), base_url)
+    }
+
+    /// A client sending its requests through `http`, such as one with timeouts or a proxy set.
+    pub fn with_http_client(http: reqwest::Client, base_url: &str) -> Self {
+        Self {
+            http,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            access_token: None,
+        }
+    }
+
+    /// This client, authenticating its requests with `access_token`, as issued by
+    /// `crowdsource-admin issue-token`.
+    pub fn with_access_token(mut self, access_token: &str) -> Self {
+        self.access_token = Some(access_token.to_string());
+        self
+    }
+
+    /// Issue an abuse challenge, to solve before [CrowdsourceClient::create_user] when challenges
+    /// are required.
+    pub async fn issue_challenge(&self) -> Result<ChallengeData, ClientError> {
+        self.send(self.request(Method::GET, "/api/challenge")).await
+    }
+
+    /// Create a user, with the solution to an abuse challenge if challenges are required.
+    pub async fn create_user(
+        &self,
+        body: &CreateUserHttpRequestBody,
+        challenge_solution: Option<&str>,
+    ) -> Result<CreateUserResponseData, ClientError> {
+        let mut request = self.request(Method::POST, "/api/users").json(body);
+        if let Some(solution) = challenge_solution {
+            request = request.header(CHALLENGE_SOLUTION_HEADER, solution);
+        }
+        self.send(request).await
+    }
+
+    pub async fn get_user(&self, user_id: Uuid) -> Result<UserResponseData, ClientError> {
+        let path = format!("/api/users/{user_id}");
+        self.send(self.request(Method::GET, &path)).await
+    }
+
+    pub async fn accept_terms(
+        &self,
+        user_id: Uuid,
+        body: &AcceptTermsHttpRequestBody,
+    ) -> Result<AcceptTermsResponseData, ClientError> {
+        let path = format!("/api/users/{user_id}/terms-acceptance");
+        self.send(self.request(Method::POST, &path).json(body))
+            .await
+    }
+
+    pub async fn request_email_change(
+        &self,
+        user_id: Uuid,
+        body: &RequestEmailChangeHttpRequestBody,
+    ) -> Result<EmailChangeResponseData, ClientError> {
+        let path = format!("/api/users/{user_id}/email-change");
+        self.send(self.request(Method::POST, &path).json(body))
+            .await
+    }
+
+    pub async fn confirm_email_change(
+        &self,
+        user_id: Uuid,
+        body: &ConfirmEmailChangeHttpRequestBody,
+    ) -> Result<ConfirmEmailChangeResponseData, ClientError> {
+        let path = format!("/api/users/{user_id}/email-change/confirmation");
+        self.send(self.request(Method::POST, &path).json(body))
+            .await
+    }
+
+    pub async fn cancel_email_change(
+        &self,
+        user_id: Uuid,
+    ) -> Result<CancelEmailChangeResponseData, ClientError> {
+        let path = format!("/api/users/{user_id}/email-change");
+        self.send(self.request(Method::DELETE, &path)).await
+    }
+
+    pub async fn erase_user(&self, user_id: Uuid) -> Result<EraseUserResponseData, ClientError> {
+        let path = format!("/api/users/{user_id}/erasure");
+        self.send(self.request(Method::POST, &path)).await
+    }
+
+    pub async fn export_user_data(
+        &self,
+        user_id: Uuid,
+    ) -> Result<UserDataExportResponseData, ClientError> {
+        let path = format!("/api/users/{user_id}/data-export");
+        self.send(self.request(Method::GET, &path)).await
+    }
+
+    /// Create a link through which the data of a user can be exported without the API.
+    pub async fn share_user_data_export(
+        &self,
+        user_id: Uuid,
+    ) -> Result<SharedLinkData, ClientError> {
+        let path = format!("/api/users/{user_id}/data-export/share");
+        self.send(self.request(Method::POST, &path)).await
+    }
+
+    /// Export the data of a user through a link created by
+    /// [CrowdsourceClient::share_user_data_export].
+    pub async fn export_shared_user_data(
+        &self,
+        link: &SharedLinkData,
+    ) -> Result<UserDataExportResponseData, ClientError> {
+        self.send(self.http.get(&link.url)).await
+    }
+
+    pub async fn list_user_activity(
+        &self,
+        user_id: Uuid,
+        params: &ListUserActivityParams,
+    ) -> Result<ActivityPageData, ClientError> {
+        let path = format!("/api/users/{user_id}/activity");
+        self.send(self.request(Method::GET, &path).query(params))
+            .await
+    }
+
+    pub async fn list_notifications(
+        &self,
+        user_id: Uuid,
+        params: &ListNotificationsParams,
+    ) -> Result<InboxData, ClientError> {
+        let path = format!("/api/users/{user_id}/notifications");
+        self.send(self.request(Method::GET, &path).query(params))
+            .await
+    }
+
+    pub async fn count_unread_notifications(
+        &self,
+        user_id: Uuid,
+    ) -> Result<UnreadCountData, ClientError> {
+        let path = format!("/api/users/{user_id}/notifications/unread-count");
+        self.send(self.request(Method::GET, &path)).await
+    }
+
+    pub async fn mark_notification_read(
+        &self,
+        user_id: Uuid,
+        notification_id: Uuid,
+    ) -> Result<(), ClientError> {
+        let path = format!("/api/users/{user_id}/notifications/{notification_id}/read");
+        self.send_without_content(self.request(Method::POST, &path))
+            .await
+    }
+
+    pub async fn mark_all_notifications_read(
+        &self,
+        user_id: Uuid,
+    ) -> Result<MarkedReadData, ClientError> {
+        let path = format!("/api/users/{user_id}/notifications/read");
+        self.send(self.request(Method::POST, &path)).await
+    }
+
+    pub async fn record_analytics_events(
+        &self,
+        body: &RecordAnalyticsEventsHttpRequestBody,
+    ) -> Result<RecordedAnalyticsEventsData, ClientError> {
+        self.send(
+            self.request(Method::POST, "/api/analytics/events")
+                .json(body),
+        )
+        .await
+    }
+
+    /// The names of the feature flags enabled for a tenant, or globally.
+    pub async fn list_features(
+        &self,
+        query: &ListFeaturesQuery,
+    ) -> Result<Vec<String>, ClientError> {
+        self.send(self.request(Method::GET, "/api/features").query(query))
+            .await
+    }
+
+    pub async fn list_dead_letters(&self) -> Result<Vec<DeadLetterData>, ClientError> {
+        self.send(self.request(Method::GET, "/api/admin/dead-letters"))
+            .await
+    }
+
+    pub async fn redrive_dead_letter(
+        &self,
+        id: Uuid,
+    ) -> Result<RedriveDeadLetterResponseData, ClientError> {
+        let path = format!("/api/admin/dead-letters/{id}/redrive");
+        self.send(self.request(Method::POST, &path)).await
+    }
+
+    pub async fn search_users(
+        &self,
+        params: &SearchUsersParams,
+    ) -> Result<Vec<UserSearchHitData>, ClientError> {
+        self.send(
+            self.request(Method::GET, "/api/admin/users/search")
+                .query(params),
+        )
+        .await
+    }
+
+    /// Export users as CSV, returned as is.
+    pub async fn export_users_csv(
+        &self,
+        params: &ExportUsersCsvParams,
+    ) -> Result<String, ClientError> {
+        let response = self
+            .request(Method::GET, "/api/admin/users/export.csv")
+            .query(params)
+            .send()
+            .await?;
+        Ok(error_for_status(response).await?.text().await?)
+    }
+
+    pub async fn get_maintenance(&self) -> Result<MaintenanceData, ClientError> {
+        self.send(self.request(Method::GET, "/api/admin/maintenance"))
+            .await
+    }
+
+    pub async fn set_maintenance(
+        &self,
+        body: &SetMaintenanceHttpRequestBody,
+    ) -> Result<MaintenanceData, ClientError> {
+        self.send(
+            self.request(Method::PUT, "/api/admin/maintenance")
+                .json(body),
+        )
+        .await
+    }
+
+    pub async fn get_runtime_config(&self) -> Result<RuntimeConfigData, ClientError> {
+        self.send(self.request(Method::GET, "/api/admin/config"))
+            .await
+    }
+
+    pub async fn patch_runtime_config(
+        &self,
+        body: &PatchRuntimeConfigHttpRequestBody,
+    ) -> Result<RuntimeConfigData, ClientError> {
+        self.send(self.request(Method::PATCH, "/api/admin/config").json(body))
+            .await
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        let request = self
+            .http
+            .request(method, format!("{}{path}", self.base_url));
+        match &self.access_token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+
+    /// Sends `request`, returning the `data` of the response.
+    async fn send<T>(&self, request: RequestBuilder) -> Result<T, ClientError>
+    where
+        T: Serialize + DeserializeOwned + PartialEq,
+    {
+        let response = error_for_status(request.send().await?).await?;
+        let body: ApiResponseBody<T> = response.json().await?;
+        Ok(body.data)
+    }
+
+    /// Sends `request`, expecting a response without content.
+    async fn send_without_content(&self, request: RequestBuilder) -> Result<(), ClientError> {
+        error_for_status(request.send().await?).await?;
+        Ok(())
+    }
+}
+
+/// Turns an error response into [ClientError::Api].
+async fn error_for_status(response: reqwest::Response) -> Result<reqwest::Response, ClientError> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    // Errors not raised by the API itself, such as from a proxy, may have another body.
+    let message = match response.json::<ApiResponseBody<ApiErrorData>>().await {
+        Ok(body) => body.data.message,
+        Err(_) => status.canonical_reason().unwrap_or_default().to_string(),
+    };
+    Err(ClientError::Api { status, message })
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    #[error("the API responded with {status}: {message}")]
+    Api { status: StatusCode, message: String },
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+}
@@ -1,9 +1,260 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
 use sqlx::postgres::PgConnectOptions;
+use tokio::sync::watch;
+
+/// The file the [Settings] are read from, relative to the working directory.
+pub const CONFIGURATION_FILE: &str = "configuration.yaml";
 
-#[derive(serde::Deserialize)]
+#[derive(serde::Deserialize, Clone)]
 pub struct Settings {
     pub database: DatabaseSettings,
     pub application_port: u16,
+    /// The terms of service version users must accept.
+    pub terms_version: String,
+    /// The directory the email templates are loaded from.
+    pub email_templates_dir: String,
+    /// The directory static files, such as the frontend bundle, are served from, if any.
+    pub static_dir: Option<String>,
+    /// Whether the server applies pending migrations at startup.
+    #[serde(default)]
+    pub auto_migrate: bool,
+    /// Whether the server seeds development data at startup. Never set this in production.
+    #[serde(default)]
+    pub dev_seed: bool,
+    /// Whether maintenance mode is kept on, refusing requests that would write, regardless of
+    /// the switch admins can toggle.
+    #[serde(default)]
+    pub maintenance_mode: bool,
+    /// The secret shared links are signed with. Links only stay valid across restarts and
+    /// replicas if it is set.
+    pub signed_url_secret: Option<String>,
+    /// The secret access tokens are signed with. Tokens only stay valid across restarts and
+    /// replicas if it is set, and can only be issued with `crowdsource-admin` if it is.
+    pub access_token_secret: Option<String>,
+    /// Whether the server listens with `SO_REUSEPORT`, so that a restarted server can start
+    /// listening before the old one has drained.
+    #[serde(default)]
+    pub reuse_port: bool,
+    /// The Sentry DSN panics and internal server errors are reported to. Only used if built
+    /// with the `error-reporting` feature.
+    pub error_reporting_dsn: Option<String>,
+    /// The features that are switched on or off, by name.
+    #[serde(default)]
+    pub feature_flags: HashMap<String, FeatureFlagSettings>,
+    /// Where to fetch feature flags from, overriding `feature_flags`, if anywhere.
+    pub feature_flags_url: Option<String>,
+    /// How logs are written.
+    #[serde(default)]
+    pub log: LogSettings,
+    /// Repository calls taking longer than this many milliseconds are logged as slow.
+    pub slow_query_threshold_ms: Option<u64>,
+    /// Requests taking longer than this many milliseconds are logged as slow.
+    pub request_latency_budget_ms: Option<u64>,
+    /// How reads failing with transient database errors are retried.
+    #[serde(default)]
+    pub database_retries: DatabaseRetrySettings,
+    /// How many requests are handled at once before further ones are shed, if limited.
+    pub max_concurrent_requests: Option<usize>,
+    /// The 99th percentile latency in milliseconds above which requests are shed, if limited.
+    pub max_p99_latency_ms: Option<u64>,
+    /// How personal data is encrypted before it is stored, if it is.
+    pub field_encryption: Option<FieldEncryptionSettings>,
+    /// Which variants of an email address are considered the same, so that they can't be used
+    /// for more than one account.
+    #[serde(default)]
+    pub email_canonicalization: EmailCanonicalizationSettings,
+    /// How the usernames of new users are validated.
+    #[serde(default)]
+    pub usernames: UserNameSettings,
+    /// The cron expressions scheduled jobs run on, by job name, overriding their defaults.
+    #[serde(default)]
+    pub schedules: HashMap<String, String>,
+    /// How third-party services, such as the feature flag provider, are called.
+    #[serde(default)]
+    pub outbound_http: OutboundHttpSettings,
+    /// How emails to users are delivered. Defaults to only logging them.
+    #[serde(default)]
+    pub email_delivery: EmailDeliverySettings,
+    /// The Meilisearch instance users are searched in. Without it, users are searched in the
+    /// database.
+    #[serde(default)]
+    pub search: Option<MeilisearchSettings>,
+    /// Where analytics events reported by clients are written. Defaults to the database.
+    #[serde(default)]
+    pub analytics: AnalyticsSettings,
+}
+
+/// The store analytics events are written to.
+#[derive(serde::Deserialize, Clone, Default)]
+#[serde(tag = "sink", rename_all = "lowercase")]
+pub enum AnalyticsSettings {
+    /// Events are written to a table of the database, partitioned by month.
+    #[default]
+    Postgres,
+    /// Events are inserted into a ClickHouse table.
+    ClickHouse(ClickHouseSettings),
+}
+
+#[derive(serde::Deserialize, Clone)]
+pub struct ClickHouseSettings {
+    /// The URL of the HTTP interface of the server.
+    pub url: String,
+    pub user: String,
+    pub password: String,
+    /// The table events are inserted into. Defaults to `analytics_events`.
+    pub table: Option<String>,
+}
+
+#[derive(serde::Deserialize, Clone)]
+pub struct MeilisearchSettings {
+    pub url: String,
+    /// The key requests are authenticated with, if the instance requires one.
+    pub api_key: Option<String>,
+    /// The uid of the index users are kept in. Defaults to `users`.
+    pub index: Option<String>,
+}
+
+/// The provider emails to users are delivered through.
+#[derive(serde::Deserialize, Clone, Default)]
+#[serde(tag = "provider", rename_all = "lowercase")]
+pub enum EmailDeliverySettings {
+    /// Emails are composed and logged, but not delivered.
+    #[default]
+    Log,
+    /// Emails are delivered through the SendGrid v3 API.
+    SendGrid(SendGridSettings),
+    /// Emails are delivered through the Amazon SES v2 API.
+    Ses(SesSettings),
+}
+
+#[derive(serde::Deserialize, Clone)]
+pub struct SendGridSettings {
+    pub api_key: String,
+    /// The verified sender address emails are sent from.
+    pub from: String,
+    /// Whether SendGrid only validates emails, without delivering them.
+    #[serde(default)]
+    pub sandbox: bool,
+}
+
+#[derive(serde::Deserialize, Clone)]
+pub struct SesSettings {
+    /// The AWS region, such as `eu-north-1`.
+    pub region: String,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    /// The verified identity emails are sent from.
+    pub from: String,
+    /// Whether emails are sent to the SES mailbox simulator instead of to users.
+    #[serde(default)]
+    pub sandbox: bool,
+}
+
+/// How requests to third-party services are made.
+#[derive(serde::Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct OutboundHttpSettings {
+    /// How many milliseconds requests may take. Defaults to 10 seconds.
+    pub timeout_ms: Option<u64>,
+    /// How many milliseconds connecting may take. Defaults to 5 seconds.
+    pub connect_timeout_ms: Option<u64>,
+    /// How many times failed requests are retried, if retrying is harmless. Defaults to 2.
+    pub max_retries: Option<u32>,
+    /// The proxy all requests go through, such as `http://proxy.internal:3128`, if any.
+    pub proxy: Option<String>,
+}
+
+/// How reads failing with transient database errors, such as a dropped connection, are retried.
+/// Writes, and reads within a request that writes, are never retried.
+#[derive(serde::Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DatabaseRetrySettings {
+    /// How many times a failed read is retried. Defaults to 3, and 0 disables retrying.
+    pub max_retries: Option<u32>,
+    /// How many milliseconds to wait before the first retry, doubling for each further one.
+    /// Defaults to 50.
+    pub initial_delay_ms: Option<u64>,
+}
+
+/// Which variants of an email address are folded into one, besides the case of the domain.
+#[derive(serde::Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EmailCanonicalizationSettings {
+    /// Whether `user+alias@example.com` is considered the same as `user@example.com`.
+    #[serde(default)]
+    pub fold_plus_aliases: bool,
+    /// Whether dots and case in the local part of Gmail addresses are ignored.
+    #[serde(default)]
+    pub fold_gmail_dots: bool,
+}
+
+/// How the usernames of new users are validated, besides being free of whitespace, control and
+/// format characters.
+#[derive(serde::Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct UserNameSettings {
+    /// How many characters usernames may have, counting an accented letter or an emoji as one.
+    /// Defaults to 64.
+    pub max_length: Option<usize>,
+    /// Whether usernames that look like that of another user, such as `admin` spelled with a
+    /// Cyrillic `а`, are refused.
+    #[serde(default)]
+    pub reject_confusables: bool,
+}
+
+/// The keys personal data is encrypted with, all hex encoded.
+#[derive(serde::Deserialize, Clone)]
+pub struct FieldEncryptionSettings {
+    /// The id of the key new values are encrypted under.
+    pub current_key: String,
+    /// The 32 byte keys by id, including retired keys values may still be encrypted under.
+    pub keys: HashMap<String, String>,
+    /// The key values are hashed with to look them up.
+    pub index_key: String,
+}
+
+/// How logs are written, and which are.
+#[derive(serde::Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct LogSettings {
+    #[serde(default)]
+    pub format: LogFormat,
+    /// The level logged at, such as `info`, unless overridden in `targets`. Defaults to `info`.
+    pub level: Option<String>,
+    /// The levels logged at by target, such as `sqlx: warn`, also applying to the modules
+    /// within the target.
+    #[serde(default)]
+    pub targets: HashMap<String, String>,
+}
+
+#[derive(serde::Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Human-readable lines.
+    #[default]
+    Pretty,
+    /// One JSON object per line.
+    Json,
+}
+
+/// Whether a feature is switched on, globally and per tenant.
+#[derive(serde::Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct FeatureFlagSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Overrides `enabled` for the given tenants.
+    #[serde(default)]
+    pub tenants: HashMap<String, bool>,
+}
+
+impl FeatureFlagSettings {
+    pub fn is_enabled(&self, tenant: Option<&str>) -> bool {
+        tenant
+            .and_then(|tenant| self.tenants.get(tenant))
+            .copied()
+            .unwrap_or(self.enabled)
+    }
 }
 
 #[derive(serde::Deserialize, Clone, Debug)]
@@ -27,11 +278,47 @@ impl DatabaseSettings {
 }
 
 pub fn get_configuration() -> Result<Settings, config::ConfigError> {
+    get_configuration_from(Path::new(CONFIGURATION_FILE))
+}
+
+/// Reads the [Settings] from the YAML file at `path`.
+pub fn get_configuration_from(path: &Path) -> Result<Settings, config::ConfigError> {
     let settings = config::Config::builder()
-        .add_source(config::File::new(
-            "configuration.yaml",
-            config::FileFormat::Yaml,
-        ))
+        .add_source(config::File::from(path).format(config::FileFormat::Yaml))
         .build()?;
     settings.try_deserialize::<Settings>()
 }
+
+/// Re-reads the [Settings] from the file at `path` whenever it is modified, as checked every
+/// `interval`, publishing them through `updates`.
+///
+/// Files that can't be read are logged and skipped, keeping the last valid [Settings]
+/// published.
+pub async fn watch_configuration(
+    path: PathBuf,
+    updates: watch::Sender<Settings>,
+    interval: Duration,
+) {
+    let modified_at = || {
+        fs::metadata(&path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    };
+    let mut last_modified_at = modified_at();
+    let mut interval = tokio::time::interval(interval);
+    loop {
+        interval.tick().await;
+        let modified = modified_at();
+        if modified == last_modified_at {
+            continue;
+        }
+        last_modified_at = modified;
+        match get_configuration_from(&path) {
+            Ok(settings) => {
+                tracing::info!("reloading configuration from {}", path.display());
+                updates.send_replace(settings);
+            }
+            Err(e) => tracing::warn!("ignoring invalid configuration {}: {e}", path.display()),
+        }
+    }
+}
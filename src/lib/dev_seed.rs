@@ -0,0 +1,76 @@
+//! Module `dev_seed` populates a database with a small, varied dataset for local development.
+//!
+//! Everything is created through the [CrowdSrcService], so seeded data is as valid as data
+//! created through the API. Seeding again skips the users that already exist; erased users are
+//! created anew, since erasure leaves nothing to recognize them by.
+
+use crate::domain::crowdsrc::{
+    models::{
+        terms::TermsVersion,
+        user::{CreateUserOutcome, CreateUserRequest, EmailAddress, UserName},
+    },
+    ports::CrowdSrcService,
+};
+
+/// Users that accepted the current terms of service.
+const USERS: [&str; 4] = ["alice", "bob", "carol", "dave"];
+
+/// Users that accepted terms that have been superseded since.
+const USERS_WITH_OLD_TERMS: [&str; 1] = ["erin"];
+
+/// Users whose personal data is erased right after being created.
+const ERASED_USERS: [&str; 1] = ["frank"];
+
+/// The version of the superseded terms of [USERS_WITH_OLD_TERMS].
+const OLD_TERMS_VERSION: &str = "2025-01-01";
+
+/// What seeding did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SeedSummary {
+    /// The users that were created.
+    pub created: usize,
+    /// The users that were skipped, since they already exist.
+    pub existing: usize,
+    /// The created users whose personal data was erased.
+    pub erased: usize,
+}
+
+/// Seeds users in varied states through `service`, new users having accepted `current_terms`.
+pub async fn seed<CS: CrowdSrcService>(
+    service: &CS,
+    current_terms: &TermsVersion,
+) -> anyhow::Result<SeedSummary> {
+    let old_terms = TermsVersion::new(OLD_TERMS_VERSION)?;
+    let reqs = USERS
+        .iter()
+        .chain(&ERASED_USERS)
+        .map(|name| user_request(name, current_terms))
+        .chain(
+            USERS_WITH_OLD_TERMS
+                .iter()
+                .map(|name| user_request(name, &old_terms)),
+        )
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let mut summary = SeedSummary::default();
+    for outcome in service.create_users(&reqs).await? {
+        let CreateUserOutcome::Created(user) = outcome else {
+            summary.existing += 1;
+            continue;
+        };
+        summary.created += 1;
+        if ERASED_USERS.contains(&user.username().to_string().as_str()) {
+            service.erase_user(user.id()).await?;
+            summary.erased += 1;
+        }
+    }
+    Ok(summary)
+}
+
+fn user_request(name: &str, terms: &TermsVersion) -> anyhow::Result<CreateUserRequest> {
+    Ok(CreateUserRequest::new(
+        UserName::new(name)?,
+        EmailAddress::new(&format!("{name}@example.com"))?,
+        terms.clone(),
+    ))
+}
@@ -1,3 +1,8 @@
+pub mod after_commit;
+pub mod analytics_buffer;
+pub mod caching_service;
+pub mod indexed_service;
 pub mod models;
+pub mod observed_service;
 pub mod ports;
 pub mod service;
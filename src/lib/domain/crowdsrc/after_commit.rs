@@ -0,0 +1,118 @@
+/*!
+   Module `after_commit` defers side effects that can't be rolled back, such as notifications,
+   until the transaction of the operation causing them is committed.
+
+   Effects [deferred](defer) within the [scope](AfterCommit::scope) of an [AfterCommit] are only
+   run once it is [run](AfterCommit::run), after the commit, and are discarded if it is dropped
+   instead, after a rollback. Outside of any scope, they run right away.
+*/
+
+use std::{
+    pin::Pin,
+    sync::{Arc, Mutex},
+};
+
+tokio::task_local! {
+    static CURRENT: AfterCommit;
+}
+
+type Effect = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// The effects deferred until a transaction is committed, in the order they were deferred.
+///
+/// Clones share the same effects.
+#[derive(Clone, Default)]
+pub struct AfterCommit {
+    effects: Arc<Mutex<Vec<Effect>>>,
+}
+
+impl AfterCommit {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Runs `f`, with the effects deferred within it queued in this [AfterCommit].
+    pub async fn scope<F: Future>(&self, f: F) -> F::Output {
+        CURRENT.scope(self.clone(), f).await
+    }
+
+    /// Runs the queued effects, one after the other.
+    pub async fn run(self) {
+        let effects = std::mem::take(&mut *self.effects.lock().expect("lock is not poisoned"));
+        for effect in effects {
+            effect.await;
+        }
+    }
+}
+
+impl std::fmt::Debug for AfterCommit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let queued = self.effects.lock().map_or(0, |effects| effects.len());
+        f.debug_struct("AfterCommit")
+            .field("queued", &queued)
+            .finish()
+    }
+}
+
+/// Runs `effect` once the transaction in scope is committed, or right away if there is none.
+pub async fn defer(effect: impl Future<Output = ()> + Send + 'static) {
+    match CURRENT.try_with(Clone::clone) {
+        Ok(current) => current
+            .effects
+            .lock()
+            .expect("lock is not poisoned")
+            .push(Box::pin(effect)),
+        Err(_) => effect.await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    fn counting_effect(count: &Arc<AtomicUsize>) -> impl Future<Output = ()> + Send + 'static {
+        let count = count.clone();
+        async move {
+            count.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[tokio::test]
+    async fn deferred_effects_run_when_run() {
+        let count = Arc::new(AtomicUsize::new(0));
+        let after_commit = AfterCommit::new();
+
+        after_commit
+            .scope(async {
+                defer(counting_effect(&count)).await;
+                defer(counting_effect(&count)).await;
+            })
+            .await;
+        assert_eq!(count.load(Ordering::SeqCst), 0);
+
+        after_commit.run().await;
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn deferred_effects_are_discarded_when_dropped() {
+        let count = Arc::new(AtomicUsize::new(0));
+        let after_commit = AfterCommit::new();
+
+        after_commit.scope(defer(counting_effect(&count))).await;
+        drop(after_commit);
+
+        assert_eq!(count.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn effects_run_right_away_outside_of_a_scope() {
+        let count = Arc::new(AtomicUsize::new(0));
+
+        defer(counting_effect(&count)).await;
+
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,212 @@
+/*!
+   Module `analytics_buffer` provides the buffer [AnalyticsEvent]s are recorded into, and written
+   from to an [AnalyticsSink] in batches, so that clients don't wait for the sink.
+*/
+
+use std::time::Duration;
+
+use tokio::sync::mpsc::{self, error::TrySendError};
+
+use crate::domain::crowdsrc::models::analytics::{AnalyticsError, AnalyticsEvent};
+use crate::domain::crowdsrc::ports::AnalyticsSink;
+
+/// How many [AnalyticsEvent]s an [AnalyticsBuffer] holds, and when it writes them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BufferPolicy {
+    /// How many events may await writing before more are refused.
+    pub capacity: usize,
+    /// How many events are written at once, as soon as that many are buffered.
+    pub max_batch: usize,
+    /// How long events may await writing, however few are buffered.
+    pub flush_interval: Duration,
+}
+
+/// A handle to a buffer of [AnalyticsEvent]s, written to an [AnalyticsSink] by a background
+/// task.
+///
+/// Analytics are best effort: events failing to be written are logged and dropped, as are those
+/// still buffered when the process exits. Once all handles are dropped, the task writes the
+/// events left and stops.
+#[derive(Debug, Clone)]
+pub struct AnalyticsBuffer {
+    sender: mpsc::Sender<AnalyticsEvent>,
+}
+
+impl AnalyticsBuffer {
+    /// Spawns the task writing the events buffered to `sink` as `policy` says.
+    ///
+    /// # Panics
+    ///
+    /// If called outside a Tokio runtime, or `policy` has a zero capacity or batch size.
+    pub fn spawn<S: AnalyticsSink>(sink: S, policy: BufferPolicy) -> Self {
+        assert!(policy.max_batch > 0, "max_batch must be positive");
+        let (sender, receiver) = mpsc::channel(policy.capacity);
+        tokio::spawn(write_events(sink, receiver, policy));
+        Self { sender }
+    }
+
+    /// Buffers `events`, all or none of them.
+    ///
+    /// # Errors
+    ///
+    /// - [AnalyticsError::Overloaded] if the buffer has no room for the events.
+    /// - [AnalyticsError::Unknown] if the task writing the events has stopped.
+    pub fn record(&self, events: Vec<AnalyticsEvent>) -> Result<(), AnalyticsError> {
+        let permits = self
+            .sender
+            .try_reserve_many(events.len())
+            .map_err(|err| match err {
+                TrySendError::Full(()) => AnalyticsError::Overloaded,
+                TrySendError::Closed(()) => {
+                    AnalyticsError::Unknown(anyhow::anyhow!("analytics buffer is closed"))
+                }
+            })?;
+        for (permit, event) in permits.zip(events) {
+            permit.send(event);
+        }
+        Ok(())
+    }
+}
+
+/// Writes the events received to `sink` in batches of at most [BufferPolicy::max_batch], until
+/// all senders are dropped.
+async fn write_events<S: AnalyticsSink>(
+    sink: S,
+    mut receiver: mpsc::Receiver<AnalyticsEvent>,
+    policy: BufferPolicy,
+) {
+    let mut interval = tokio::time::interval(policy.flush_interval);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    // The first tick completes right away.
+    interval.tick().await;
+    let mut batch = Vec::with_capacity(policy.max_batch);
+    loop {
+        let room = policy.max_batch - batch.len();
+        tokio::select! {
+            received = receiver.recv_many(&mut batch, room) => {
+                if received == 0 {
+                    flush(&sink, &mut batch).await;
+                    return;
+                }
+                if batch.len() >= policy.max_batch {
+                    flush(&sink, &mut batch).await;
+                }
+            }
+            _ = interval.tick() => flush(&sink, &mut batch).await,
+        }
+    }
+}
+
+async fn flush<S: AnalyticsSink>(sink: &S, batch: &mut Vec<AnalyticsEvent>) {
+    if batch.is_empty() {
+        return;
+    }
+    if let Err(err) = sink.write_events(batch).await {
+        tracing::warn!(
+            events = batch.len(),
+            "dropped analytics events that could not be written: {err:?}"
+        );
+    }
+    batch.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use chrono::Utc;
+
+    use super::*;
+    use crate::domain::crowdsrc::models::analytics::AnalyticsEventKind;
+
+    /// An [AnalyticsSink] keeping the batches written.
+    #[derive(Debug, Clone, Default)]
+    struct CollectingSink {
+        batches: Arc<Mutex<Vec<usize>>>,
+    }
+
+    impl AnalyticsSink for CollectingSink {
+        async fn write_events(&self, events: &[AnalyticsEvent]) -> Result<(), AnalyticsError> {
+            self.batches.lock().unwrap().push(events.len());
+            Ok(())
+        }
+    }
+
+    impl CollectingSink {
+        /// Waits until `batches` have been written, failing after a second.
+        async fn written(&self, batches: &[usize]) {
+            let wait = async {
+                while *self.batches.lock().unwrap() != batches {
+                    tokio::time::sleep(Duration::from_millis(5)).await;
+                }
+            };
+            if tokio::time::timeout(Duration::from_secs(1), wait)
+                .await
+                .is_err()
+            {
+                panic!(
+                    "expected batches {batches:?}, written {:?}",
+                    self.batches.lock().unwrap()
+                );
+            }
+        }
+    }
+
+    fn events(count: usize) -> Vec<AnalyticsEvent> {
+        (0..count)
+            .map(|_| {
+                AnalyticsEvent::new(
+                    uuid::Uuid::new_v4(),
+                    "task-1",
+                    AnalyticsEventKind::TaskViewed,
+                    Utc::now(),
+                    Utc::now(),
+                )
+                .unwrap()
+            })
+            .collect()
+    }
+
+    fn policy(flush_interval: Duration) -> BufferPolicy {
+        BufferPolicy {
+            capacity: 4,
+            max_batch: 2,
+            flush_interval,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_full_batches_are_written_and_rest_on_drop() {
+        let sink = CollectingSink::default();
+        let buffer = AnalyticsBuffer::spawn(sink.clone(), policy(Duration::from_secs(3600)));
+
+        buffer.record(events(3)).unwrap();
+        sink.written(&[2]).await;
+        drop(buffer);
+
+        sink.written(&[2, 1]).await;
+    }
+
+    #[tokio::test]
+    async fn test_partial_batch_is_written_on_interval() {
+        let sink = CollectingSink::default();
+        let buffer = AnalyticsBuffer::spawn(sink.clone(), policy(Duration::from_millis(20)));
+
+        buffer.record(events(1)).unwrap();
+
+        sink.written(&[1]).await;
+    }
+
+    #[tokio::test]
+    async fn test_record_is_refused_without_room_for_all_events() {
+        let sink = CollectingSink::default();
+        let buffer = AnalyticsBuffer::spawn(sink.clone(), policy(Duration::from_secs(3600)));
+
+        let result = buffer.record(events(5));
+
+        assert!(matches!(result, Err(AnalyticsError::Overloaded)));
+        drop(buffer);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(sink.batches.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,542 @@
+/*!
+   Module `caching_service` provides a [CrowdSrcService] decorator that keeps recently looked up
+   [User]s in memory, so that hot-path lookups don't reach the repository.
+*/
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use futures::Stream;
+use moka::sync::Cache;
+
+use crate::domain::crowdsrc::after_commit;
+
+use crate::domain::crowdsrc::models::activity::{ActivityError, ActivityPage, ActivityQuery};
+use crate::domain::crowdsrc::models::analytics::{AnalyticsError, AnalyticsEvent};
+use crate::domain::crowdsrc::models::dead_letter::{DeadLetter, DeadLetterError, RedriveOutcome};
+use crate::domain::crowdsrc::models::email_change::{
+    EmailChangeError, EmailChangeToken, PendingEmailChange,
+};
+use crate::domain::crowdsrc::models::inbox::{Inbox, InboxError, InboxQuery};
+use crate::domain::crowdsrc::models::search::{SearchError, UserSearchQuery};
+use crate::domain::crowdsrc::models::terms::{AcceptTermsError, TermsAcceptance, TermsVersion};
+use crate::domain::crowdsrc::models::user::{
+    CreateUserError, CreateUserOutcome, CreateUserRequest, CreateUsersError, EmailAddress,
+    EraseUserError, GetUserError, ListUsersError, User, UserDataExport,
+};
+use crate::domain::crowdsrc::ports::CrowdSrcService;
+
+/// How long, and how many, [User]s a [CachingCrowdSrcService] keeps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CachePolicy {
+    pub ttl: Duration,
+    pub max_entries: usize,
+}
+
+/// A [CrowdSrcService] decorator caching the [User]s returned by [CrowdSrcService::get_user]
+/// for [CachePolicy::ttl], evicting the least likely to be looked up again once
+/// [CachePolicy::max_entries] are cached.
+///
+/// [User]s are only cached once the transaction they were read in is committed, so that a
+/// rolled back change is never served. A [User] changed through this service, such as by
+/// erasure, is dropped from the cache right away and again once the change is committed, and a
+/// [User] read before that isn't cached, however late its transaction commits. The cache is per
+/// process, so with several replicas a change made through another one is only seen once the
+/// cached [User] expires.
+#[derive(Debug, Clone)]
+pub struct CachingCrowdSrcService<CS: CrowdSrcService> {
+    inner: CS,
+    users: Cache<uuid::Uuid, User>,
+    /// Counts the invalidations, so that a [User] read before one isn't cached after it.
+    generation: Arc<Mutex<u64>>,
+}
+
+impl<CS: CrowdSrcService> CachingCrowdSrcService<CS> {
+    pub fn new(inner: CS, policy: CachePolicy) -> Self {
+        Self {
+            inner,
+            users: Cache::builder()
+                .max_capacity(policy.max_entries as u64)
+                .time_to_live(policy.ttl)
+                .build(),
+            generation: Arc::new(Mutex::new(0)),
+        }
+    }
+
+    /// The current generation, to be passed to [Self::cache_user_after_commit] for a [User]
+    /// read after this call.
+    fn generation(&self) -> u64 {
+        *self.generation.lock().expect("generation lock poisoned")
+    }
+
+    /// Caches `user`, read at `generation`, once the transaction in scope, if any, is committed,
+    /// unless a [User] was invalidated since.
+    async fn cache_user_after_commit(&self, user: &User, generation: u64) {
+        let (users, current, user) = (self.users.clone(), self.generation.clone(), user.clone());
+        after_commit::defer(async move {
+            let current = current.lock().expect("generation lock poisoned");
+            if *current == generation {
+                users.insert(*user.id(), user);
+            }
+        })
+        .await;
+    }
+
+    /// Drops the [User] with the given id from the cache, and again once the transaction in
+    /// scope, if any, is committed.
+    async fn invalidate_after_commit(&self, id: &uuid::Uuid) {
+        self.invalidate(id);
+        let (users, generation, id) = (self.users.clone(), self.generation.clone(), *id);
+        after_commit::defer(async move { invalidate(&users, &generation, &id) }).await;
+    }
+
+    /// Drops the [User] with the given id from the cache.
+    pub fn invalidate(&self, id: &uuid::Uuid) {
+        invalidate(&self.users, &self.generation, id);
+    }
+}
+
+/// Drops the [User] with the given id from `users`, moving on to the next `generation`.
+fn invalidate(users: &Cache<uuid::Uuid, User>, generation: &Mutex<u64>, id: &uuid::Uuid) {
+    let mut generation = generation.lock().expect("generation lock poisoned");
+    *generation += 1;
+    users.invalidate(id);
+}
+
+impl<CS: CrowdSrcService> CrowdSrcService for CachingCrowdSrcService<CS> {
+    async fn create_user(&self, req: &CreateUserRequest) -> Result<User, CreateUserError> {
+        self.inner.create_user(req).await
+    }
+
+    async fn create_users(
+        &self,
+        reqs: &[CreateUserRequest],
+    ) -> Result<Vec<CreateUserOutcome>, CreateUsersError> {
+        self.inner.create_users(reqs).await
+    }
+
+    fn stream_users(&self) -> impl Stream<Item = Result<User, ListUsersError>> + Send {
+        self.inner.stream_users()
+    }
+
+    /// Retrieve the [User] from the cache, or from the decorated service if it isn't cached or
+    /// has expired.
+    async fn get_user(&self, id: &uuid::Uuid) -> Result<User, GetUserError> {
+        if let Some(user) = self.users.get(id) {
+            return Ok(user);
+        }
+        let generation = self.generation();
+        let user = self.inner.get_user(id).await?;
+        self.cache_user_after_commit(&user, generation).await;
+        Ok(user)
+    }
+
+    async fn export_user_data(&self, id: &uuid::Uuid) -> Result<UserDataExport, GetUserError> {
+        self.inner.export_user_data(id).await
+    }
+
+    /// Erase the [User] through the decorated service, dropping it from the cache.
+    async fn erase_user(&self, id: &uuid::Uuid) -> Result<User, EraseUserError> {
+        self.invalidate_after_commit(id).await;
+        self.inner.erase_user(id).await
+    }
+
+    async fn accept_terms(
+        &self,
+        user_id: &uuid::Uuid,
+        version: &TermsVersion,
+    ) -> Result<TermsAcceptance, AcceptTermsError> {
+        self.inner.accept_terms(user_id, version).await
+    }
+
+    async fn check_current_terms(&self, user_id: &uuid::Uuid) -> Result<(), AcceptTermsError> {
+        self.inner.check_current_terms(user_id).await
+    }
+
+    async fn list_dead_letters(&self) -> Result<Vec<DeadLetter>, DeadLetterError> {
+        self.inner.list_dead_letters().await
+    }
+
+    async fn redrive_dead_letter(
+        &self,
+        id: &uuid::Uuid,
+    ) -> Result<RedriveOutcome, DeadLetterError> {
+        self.inner.redrive_dead_letter(id).await
+    }
+
+    async fn list_user_activity(
+        &self,
+        user_id: &uuid::Uuid,
+        query: &ActivityQuery,
+    ) -> Result<ActivityPage, ActivityError> {
+        self.inner.list_user_activity(user_id, query).await
+    }
+
+    async fn request_email_change(
+        &self,
+        user_id: &uuid::Uuid,
+        new_email: &EmailAddress,
+    ) -> Result<PendingEmailChange, EmailChangeError> {
+        self.inner.request_email_change(user_id, new_email).await
+    }
+
+    /// Confirm the email change through the decorated service, dropping the [User] from the
+    /// cache.
+    async fn confirm_email_change(
+        &self,
+        user_id: &uuid::Uuid,
+        token: &EmailChangeToken,
+    ) -> Result<User, EmailChangeError> {
+        self.invalidate_after_commit(user_id).await;
+        self.inner.confirm_email_change(user_id, token).await
+    }
+
+    async fn cancel_email_change(&self, user_id: &uuid::Uuid) -> Result<(), EmailChangeError> {
+        self.inner.cancel_email_change(user_id).await
+    }
+
+    async fn purge_expired_email_changes(&self) -> Result<u64, EmailChangeError> {
+        self.inner.purge_expired_email_changes().await
+    }
+
+    async fn list_notifications(
+        &self,
+        user_id: &uuid::Uuid,
+        query: &InboxQuery,
+    ) -> Result<Inbox, InboxError> {
+        self.inner.list_notifications(user_id, query).await
+    }
+
+    async fn count_unread_notifications(&self, user_id: &uuid::Uuid) -> Result<u64, InboxError> {
+        self.inner.count_unread_notifications(user_id).await
+    }
+
+    async fn mark_notification_read(
+        &self,
+        user_id: &uuid::Uuid,
+        notification_id: &uuid::Uuid,
+    ) -> Result<(), InboxError> {
+        self.inner
+            .mark_notification_read(user_id, notification_id)
+            .await
+    }
+
+    async fn mark_all_notifications_read(&self, user_id: &uuid::Uuid) -> Result<u64, InboxError> {
+        self.inner.mark_all_notifications_read(user_id).await
+    }
+
+    async fn purge_old_notifications(&self) -> Result<u64, InboxError> {
+        self.inner.purge_old_notifications().await
+    }
+
+    async fn search_users(&self, query: &UserSearchQuery) -> Result<Vec<User>, SearchError> {
+        self.inner.search_users(query).await
+    }
+
+    async fn record_analytics_events(
+        &self,
+        events: Vec<AnalyticsEvent>,
+    ) -> Result<(), AnalyticsError> {
+        self.inner.record_analytics_events(events).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use chrono::Utc;
+
+    use crate::domain::crowdsrc::{after_commit::AfterCommit, models::user::UserName};
+
+    use super::*;
+
+    /// Counts the calls to `get_user`, returning the same [User] each time until erased.
+    #[derive(Clone)]
+    struct CountingCrowdSrcService {
+        user: Arc<Mutex<User>>,
+        get_user_calls: Arc<AtomicU32>,
+    }
+
+    impl CountingCrowdSrcService {
+        fn new() -> Self {
+            Self {
+                user: Arc::new(Mutex::new(User::new(
+                    uuid::Uuid::new_v4(),
+                    UserName::new("Kristoffer").unwrap(),
+                    EmailAddress::new("kristoffer@example.com").unwrap(),
+                    Utc::now(),
+                ))),
+                get_user_calls: Arc::new(AtomicU32::new(0)),
+            }
+        }
+
+        fn id(&self) -> uuid::Uuid {
+            *self.user.lock().unwrap().id()
+        }
+    }
+
+    impl CrowdSrcService for CountingCrowdSrcService {
+        async fn create_user(&self, _: &CreateUserRequest) -> Result<User, CreateUserError> {
+            unimplemented!()
+        }
+
+        async fn create_users(
+            &self,
+            _: &[CreateUserRequest],
+        ) -> Result<Vec<CreateUserOutcome>, CreateUsersError> {
+            unimplemented!()
+        }
+
+        fn stream_users(&self) -> impl Stream<Item = Result<User, ListUsersError>> + Send {
+            futures::stream::empty()
+        }
+
+        async fn get_user(&self, _: &uuid::Uuid) -> Result<User, GetUserError> {
+            self.get_user_calls.fetch_add(1, Ordering::SeqCst);
+            Ok(self.user.lock().unwrap().clone())
+        }
+
+        async fn export_user_data(&self, _: &uuid::Uuid) -> Result<UserDataExport, GetUserError> {
+            unimplemented!()
+        }
+
+        async fn erase_user(&self, id: &uuid::Uuid) -> Result<User, EraseUserError> {
+            let mut user = self.user.lock().unwrap();
+            *user = User::new(
+                *id,
+                UserName::erased(id),
+                EmailAddress::erased(id),
+                *user.created_at(),
+            );
+            Ok(user.clone())
+        }
+
+        async fn accept_terms(
+            &self,
+            _: &uuid::Uuid,
+            _: &TermsVersion,
+        ) -> Result<TermsAcceptance, AcceptTermsError> {
+            unimplemented!()
+        }
+
+        async fn check_current_terms(&self, _: &uuid::Uuid) -> Result<(), AcceptTermsError> {
+            unimplemented!()
+        }
+
+        async fn list_dead_letters(&self) -> Result<Vec<DeadLetter>, DeadLetterError> {
+            unimplemented!()
+        }
+
+        async fn redrive_dead_letter(
+            &self,
+            _: &uuid::Uuid,
+        ) -> Result<RedriveOutcome, DeadLetterError> {
+            unimplemented!()
+        }
+
+        async fn list_user_activity(
+            &self,
+            _: &uuid::Uuid,
+            _: &ActivityQuery,
+        ) -> Result<ActivityPage, ActivityError> {
+            unimplemented!()
+        }
+
+        async fn request_email_change(
+            &self,
+            _: &uuid::Uuid,
+            _: &EmailAddress,
+        ) -> Result<PendingEmailChange, EmailChangeError> {
+            unimplemented!()
+        }
+
+        async fn confirm_email_change(
+            &self,
+            _: &uuid::Uuid,
+            _: &EmailChangeToken,
+        ) -> Result<User, EmailChangeError> {
+            let mut user = self.user.lock().unwrap();
+            *user = User::new(
+                *user.id(),
+                user.username().clone(),
+                EmailAddress::new("changed@example.com").unwrap(),
+                *user.created_at(),
+            );
+            Ok(user.clone())
+        }
+
+        async fn cancel_email_change(&self, _: &uuid::Uuid) -> Result<(), EmailChangeError> {
+            unimplemented!()
+        }
+
+        async fn purge_expired_email_changes(&self) -> Result<u64, EmailChangeError> {
+            unimplemented!()
+        }
+
+        async fn list_notifications(
+            &self,
+            _: &uuid::Uuid,
+            _: &InboxQuery,
+        ) -> Result<Inbox, InboxError> {
+            unimplemented!()
+        }
+
+        async fn count_unread_notifications(&self, _: &uuid::Uuid) -> Result<u64, InboxError> {
+            unimplemented!()
+        }
+
+        async fn mark_notification_read(
+            &self,
+            _: &uuid::Uuid,
+            _: &uuid::Uuid,
+        ) -> Result<(), InboxError> {
+            unimplemented!()
+        }
+
+        async fn mark_all_notifications_read(&self, _: &uuid::Uuid) -> Result<u64, InboxError> {
+            unimplemented!()
+        }
+
+        async fn purge_old_notifications(&self) -> Result<u64, InboxError> {
+            unimplemented!()
+        }
+
+        async fn search_users(&self, _: &UserSearchQuery) -> Result<Vec<User>, SearchError> {
+            unimplemented!()
+        }
+
+        async fn record_analytics_events(
+            &self,
+            _: Vec<AnalyticsEvent>,
+        ) -> Result<(), AnalyticsError> {
+            unimplemented!()
+        }
+    }
+
+    fn policy(ttl: Duration) -> CachePolicy {
+        CachePolicy {
+            ttl,
+            max_entries: 10,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_user_is_served_from_cache_until_expired() {
+        let inner = CountingCrowdSrcService::new();
+        let service = CachingCrowdSrcService::new(inner.clone(), policy(Duration::from_secs(60)));
+        let expiring = CachingCrowdSrcService::new(inner.clone(), policy(Duration::ZERO));
+
+        service.get_user(&inner.id()).await.unwrap();
+        service.get_user(&inner.id()).await.unwrap();
+        expiring.get_user(&inner.id()).await.unwrap();
+        expiring.get_user(&inner.id()).await.unwrap();
+
+        assert_eq!(inner.get_user_calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_get_user_is_cached_only_once_committed() {
+        let inner = CountingCrowdSrcService::new();
+        let service = CachingCrowdSrcService::new(inner.clone(), policy(Duration::from_secs(60)));
+        let rolled_back = AfterCommit::new();
+        let committed = AfterCommit::new();
+
+        rolled_back
+            .scope(service.get_user(&inner.id()))
+            .await
+            .unwrap();
+        drop(rolled_back);
+        committed
+            .scope(service.get_user(&inner.id()))
+            .await
+            .unwrap();
+        committed
+            .scope(service.get_user(&inner.id()))
+            .await
+            .unwrap();
+        committed.run().await;
+        service.get_user(&inner.id()).await.unwrap();
+
+        assert_eq!(inner.get_user_calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_erase_user_is_not_cached_when_rolled_back() {
+        let inner = CountingCrowdSrcService::new();
+        let service = CachingCrowdSrcService::new(inner.clone(), policy(Duration::from_secs(60)));
+        let id = inner.id();
+        service.get_user(&id).await.unwrap();
+        let rolled_back = AfterCommit::new();
+
+        rolled_back.scope(service.erase_user(&id)).await.unwrap();
+        drop(rolled_back);
+        service.get_user(&id).await.unwrap();
+
+        assert_eq!(inner.get_user_calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_erase_user_drops_cached_user() {
+        let inner = CountingCrowdSrcService::new();
+        let service = CachingCrowdSrcService::new(inner.clone(), policy(Duration::from_secs(60)));
+        let id = inner.id();
+        service.get_user(&id).await.unwrap();
+
+        service.erase_user(&id).await.unwrap();
+        let actual = service.get_user(&id).await.unwrap();
+
+        assert_eq!(actual.username(), &UserName::erased(&id));
+        assert_eq!(inner.get_user_calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_user_read_before_erasure_is_not_cached_after_it() {
+        let inner = CountingCrowdSrcService::new();
+        let service = CachingCrowdSrcService::new(inner.clone(), policy(Duration::from_secs(60)));
+        let id = inner.id();
+        let reading = AfterCommit::new();
+        let erasing = AfterCommit::new();
+
+        reading.scope(service.get_user(&id)).await.unwrap();
+        erasing.scope(service.erase_user(&id)).await.unwrap();
+        erasing.run().await;
+        reading.run().await;
+        let actual = service.get_user(&id).await.unwrap();
+
+        assert_eq!(actual.username(), &UserName::erased(&id));
+        assert_eq!(inner.get_user_calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_confirm_email_change_drops_cached_user() {
+        let inner = CountingCrowdSrcService::new();
+        let service = CachingCrowdSrcService::new(inner.clone(), policy(Duration::from_secs(60)));
+        let id = inner.id();
+        service.get_user(&id).await.unwrap();
+
+        service
+            .confirm_email_change(&id, &EmailChangeToken::new("token"))
+            .await
+            .unwrap();
+        let actual = service.get_user(&id).await.unwrap();
+
+        assert_eq!(actual.email().as_str(), "changed@example.com");
+        assert_eq!(inner.get_user_calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_invalidate_drops_cached_user() {
+        let inner = CountingCrowdSrcService::new();
+        let service = CachingCrowdSrcService::new(inner.clone(), policy(Duration::from_secs(60)));
+        let id = inner.id();
+        service.get_user(&id).await.unwrap();
+
+        service.invalidate(&id);
+        service.get_user(&id).await.unwrap();
+
+        assert_eq!(inner.get_user_calls.load(Ordering::SeqCst), 2);
+    }
+}
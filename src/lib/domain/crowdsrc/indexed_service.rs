@@ -0,0 +1,256 @@
+/*!
+   Module `indexed_service` provides a [CrowdSrcService] decorator keeping a [SearchIndex] in
+   sync with the [User]s created and erased through it, and searching users in the index.
+*/
+
+use futures::{Stream, StreamExt};
+
+use crate::domain::crowdsrc::models::activity::{ActivityError, ActivityPage, ActivityQuery};
+use crate::domain::crowdsrc::models::analytics::{AnalyticsError, AnalyticsEvent};
+use crate::domain::crowdsrc::models::dead_letter::{DeadLetter, DeadLetterError, RedriveOutcome};
+use crate::domain::crowdsrc::models::email_change::{
+    EmailChangeError, EmailChangeToken, PendingEmailChange,
+};
+use crate::domain::crowdsrc::models::inbox::{Inbox, InboxError, InboxQuery};
+use crate::domain::crowdsrc::models::search::{SearchError, UserSearchQuery};
+use crate::domain::crowdsrc::models::terms::{AcceptTermsError, TermsAcceptance, TermsVersion};
+use crate::domain::crowdsrc::models::user::{
+    CreateUserError, CreateUserOutcome, CreateUserRequest, CreateUsersError, EmailAddress,
+    EraseUserError, GetUserError, ListUsersError, User, UserDataExport,
+};
+
+use crate::domain::crowdsrc::ports::{CrowdSrcService, SearchIndex};
+
+/// A [CrowdSrcService] decorator indexing [User]s in a [SearchIndex] as they are created, and
+/// removing them as they are erased.
+///
+/// The [User]s are the source of truth, so failing to update the index is logged rather than
+/// failing the change, and leaves the index stale until it is rebuilt with [reindex]. Without an
+/// index, users are searched through the decorated service.
+#[derive(Debug, Clone)]
+pub struct IndexedCrowdSrcService<CS: CrowdSrcService, I: SearchIndex> {
+    inner: CS,
+    index: Option<I>,
+}
+
+impl<CS: CrowdSrcService, I: SearchIndex> IndexedCrowdSrcService<CS, I> {
+    pub fn new(inner: CS, index: Option<I>) -> Self {
+        Self { inner, index }
+    }
+
+    async fn index(&self, users: &[User]) {
+        let Some(index) = &self.index else {
+            return;
+        };
+        if users.is_empty() {
+            return;
+        }
+        if let Err(err) = index.index_users(users).await {
+            tracing::warn!("failed to index {} users: {err:?}", users.len());
+        }
+    }
+}
+
+impl<CS: CrowdSrcService, I: SearchIndex> CrowdSrcService for IndexedCrowdSrcService<CS, I> {
+    /// Create the [User] through the decorated service, and index it.
+    async fn create_user(&self, req: &CreateUserRequest) -> Result<User, CreateUserError> {
+        let user = self.inner.create_user(req).await?;
+        self.index(std::slice::from_ref(&user)).await;
+        Ok(user)
+    }
+
+    async fn create_users(
+        &self,
+        reqs: &[CreateUserRequest],
+    ) -> Result<Vec<CreateUserOutcome>, CreateUsersError> {
+        let outcomes = self.inner.create_users(reqs).await?;
+        let created: Vec<User> = outcomes
+            .iter()
+            .filter_map(|outcome| match outcome {
+                CreateUserOutcome::Created(user) => Some(user.clone()),
+                _ => None,
+            })
+            .collect();
+        self.index(&created).await;
+        Ok(outcomes)
+    }
+
+    fn stream_users(&self) -> impl Stream<Item = Result<User, ListUsersError>> + Send {
+        self.inner.stream_users()
+    }
+
+    async fn get_user(&self, id: &uuid::Uuid) -> Result<User, GetUserError> {
+        self.inner.get_user(id).await
+    }
+
+    async fn export_user_data(&self, id: &uuid::Uuid) -> Result<UserDataExport, GetUserError> {
+        self.inner.export_user_data(id).await
+    }
+
+    /// Erase the [User] through the decorated service, and remove it from the index.
+    async fn erase_user(&self, id: &uuid::Uuid) -> Result<User, EraseUserError> {
+        let user = self.inner.erase_user(id).await?;
+        if let Some(index) = &self.index
+            && let Err(err) = index.remove_user(id).await
+        {
+            tracing::warn!("failed to remove erased user {id} from search index: {err:?}");
+        }
+        Ok(user)
+    }
+
+    async fn accept_terms(
+        &self,
+        user_id: &uuid::Uuid,
+        version: &TermsVersion,
+    ) -> Result<TermsAcceptance, AcceptTermsError> {
+        self.inner.accept_terms(user_id, version).await
+    }
+
+    async fn check_current_terms(&self, user_id: &uuid::Uuid) -> Result<(), AcceptTermsError> {
+        self.inner.check_current_terms(user_id).await
+    }
+
+    async fn list_dead_letters(&self) -> Result<Vec<DeadLetter>, DeadLetterError> {
+        self.inner.list_dead_letters().await
+    }
+
+    async fn redrive_dead_letter(
+        &self,
+        id: &uuid::Uuid,
+    ) -> Result<RedriveOutcome, DeadLetterError> {
+        self.inner.redrive_dead_letter(id).await
+    }
+
+    async fn list_user_activity(
+        &self,
+        user_id: &uuid::Uuid,
+        query: &ActivityQuery,
+    ) -> Result<ActivityPage, ActivityError> {
+        self.inner.list_user_activity(user_id, query).await
+    }
+
+    async fn request_email_change(
+        &self,
+        user_id: &uuid::Uuid,
+        new_email: &EmailAddress,
+    ) -> Result<PendingEmailChange, EmailChangeError> {
+        self.inner.request_email_change(user_id, new_email).await
+    }
+
+    async fn confirm_email_change(
+        &self,
+        user_id: &uuid::Uuid,
+        token: &EmailChangeToken,
+    ) -> Result<User, EmailChangeError> {
+        self.inner.confirm_email_change(user_id, token).await
+    }
+
+    async fn cancel_email_change(&self, user_id: &uuid::Uuid) -> Result<(), EmailChangeError> {
+        self.inner.cancel_email_change(user_id).await
+    }
+
+    async fn purge_expired_email_changes(&self) -> Result<u64, EmailChangeError> {
+        self.inner.purge_expired_email_changes().await
+    }
+
+    async fn list_notifications(
+        &self,
+        user_id: &uuid::Uuid,
+        query: &InboxQuery,
+    ) -> Result<Inbox, InboxError> {
+        self.inner.list_notifications(user_id, query).await
+    }
+
+    async fn count_unread_notifications(&self, user_id: &uuid::Uuid) -> Result<u64, InboxError> {
+        self.inner.count_unread_notifications(user_id).await
+    }
+
+    async fn mark_notification_read(
+        &self,
+        user_id: &uuid::Uuid,
+        notification_id: &uuid::Uuid,
+    ) -> Result<(), InboxError> {
+        self.inner
+            .mark_notification_read(user_id, notification_id)
+            .await
+    }
+
+    async fn mark_all_notifications_read(&self, user_id: &uuid::Uuid) -> Result<u64, InboxError> {
+        self.inner.mark_all_notifications_read(user_id).await
+    }
+
+    async fn purge_old_notifications(&self) -> Result<u64, InboxError> {
+        self.inner.purge_old_notifications().await
+    }
+
+    /// Search the [User]s in the [SearchIndex], falling back to the decorated service if there
+    /// is no index or it fails.
+    ///
+    /// The index lags behind the users, so each hit is looked up through the decorated service,
+    /// and those since erased are left out.
+    async fn search_users(&self, query: &UserSearchQuery) -> Result<Vec<User>, SearchError> {
+        let Some(index) = &self.index else {
+            return self.inner.search_users(query).await;
+        };
+        let ids = match index.search_users(query).await {
+            Ok(ids) => ids,
+            Err(err) => {
+                tracing::warn!("search index failed, searching the repository: {err:?}");
+                return self.inner.search_users(query).await;
+            }
+        };
+        let mut users = Vec::with_capacity(ids.len());
+        for id in ids {
+            match self.inner.get_user(&id).await {
+                Ok(user) if !user.is_erased() => users.push(user),
+                Ok(_) | Err(GetUserError::NotFound { .. }) => {
+                    tracing::debug!(user_id = %id, "dropped stale search hit")
+                }
+                Err(GetUserError::Unknown(cause)) => return Err(cause.into()),
+            }
+        }
+        Ok(users)
+    }
+
+    async fn record_analytics_events(
+        &self,
+        events: Vec<AnalyticsEvent>,
+    ) -> Result<(), AnalyticsError> {
+        self.inner.record_analytics_events(events).await
+    }
+}
+
+/// Rebuilds `index` from `users`, in batches of `batch_size`, returning how many users were
+/// indexed. Erased users are left out.
+///
+/// # Errors
+///
+/// - [SearchError::Unknown] if the users could not be read or indexed. The index is then
+///   incomplete until rebuilt again.
+pub async fn reindex<I: SearchIndex>(
+    users: impl Stream<Item = Result<User, ListUsersError>>,
+    index: &I,
+    batch_size: usize,
+) -> Result<u64, SearchError> {
+    index.clear().await?;
+    let mut users = std::pin::pin!(users);
+    let mut batch = Vec::with_capacity(batch_size);
+    let mut indexed = 0;
+    while let Some(user) = users.next().await {
+        let user = user.map_err(anyhow::Error::from)?;
+        if user.is_erased() {
+            continue;
+        }
+        batch.push(user);
+        if batch.len() == batch_size {
+            index.index_users(&batch).await?;
+            indexed += batch.len() as u64;
+            batch.clear();
+        }
+    }
+    if !batch.is_empty() {
+        index.index_users(&batch).await?;
+        indexed += batch.len() as u64;
+    }
+    Ok(indexed)
+}
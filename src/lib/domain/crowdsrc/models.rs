@@ -1,2 +1,21 @@
 //! Module `models` specifies the canonical data structures comprising the domain.
+pub mod abuse_challenge;
+pub mod access_token;
+pub mod activity;
+pub mod analytics;
+pub mod dead_letter;
+pub mod email_change;
+pub mod feature_flag;
+pub mod inbox;
+pub mod lock;
+pub mod maintenance;
+pub mod redacted;
+pub mod runtime_config;
+pub mod schedule;
+pub mod search;
+pub mod signed_url;
+#[cfg(test)]
+pub(crate) mod strategies;
+pub mod terms;
+pub mod throttle;
 pub mod user;
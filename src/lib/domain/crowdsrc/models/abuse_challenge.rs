@@ -0,0 +1,21 @@
+/// A challenge a client must solve before an unauthenticated submission is accepted, to keep
+/// bots out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Challenge {
+    /// Find a `nonce` such that the SHA-256 hash of `"{challenge}:{nonce}"` starts with
+    /// `difficulty` zero bits. The solution is `"{challenge}:{nonce}"`.
+    ProofOfWork { challenge: String, difficulty: u8 },
+    /// Solve the CAPTCHA widget of `provider` configured with `site_key`. The solution is the
+    /// token returned by the widget.
+    Captcha { provider: String, site_key: String },
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum AbuseChallengeError {
+    #[error("no challenge solution was given")]
+    Missing,
+    #[error("challenge solution was rejected")]
+    Rejected,
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
@@ -0,0 +1,202 @@
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use hmac::Mac;
+
+use crate::domain::crowdsrc::models::signed_url::SigningKey;
+
+/// Who an [AccessToken] was issued to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Principal {
+    /// An administrator, allowed to act on behalf of any [User](super::user::User).
+    Admin,
+    /// The [User](super::user::User) with the given id.
+    User(uuid::Uuid),
+}
+
+impl Principal {
+    /// Whether this principal may act on behalf of the [User](super::user::User) with the given
+    /// id, being that user or an admin.
+    pub fn may_act_for(&self, user_id: &uuid::Uuid) -> bool {
+        match self {
+            Self::Admin => true,
+            Self::User(id) => id == user_id,
+        }
+    }
+
+    fn subject(&self) -> String {
+        match self {
+            Self::Admin => "admin".to_string(),
+            Self::User(id) => format!("user:{id}"),
+        }
+    }
+
+    fn parse(subject: &str) -> Option<Self> {
+        match subject.split_once(':') {
+            None if subject == "admin" => Some(Self::Admin),
+            Some(("user", id)) => uuid::Uuid::parse_str(id).ok().map(Self::User),
+            _ => None,
+        }
+    }
+}
+
+/// A bearer token proving that its holder is a [Principal], until it expires.
+///
+/// Tokens are formatted as `<subject>.<expiry>.<signature>`, the signature covering the
+/// subject and the expiry, so that neither can be changed without invalidating it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccessToken {
+    principal: Principal,
+    expires_at: i64,
+    signature: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum AccessTokenError {
+    #[error("no access token was given")]
+    Missing,
+    #[error("the access token is invalid")]
+    Invalid,
+    #[error("the access token expired at {expires_at}")]
+    Expired { expires_at: DateTime<Utc> },
+}
+
+impl AccessToken {
+    /// Issues a token for `principal` signed with `key`, valid until `expires_at`.
+    pub fn issue(key: &SigningKey, principal: Principal, expires_at: DateTime<Utc>) -> Self {
+        let expires_at = expires_at.timestamp();
+        let signature = hex::encode(
+            key.mac(&Self::signed_subject(&principal), expires_at)
+                .finalize()
+                .into_bytes(),
+        );
+        Self {
+            principal,
+            expires_at,
+            signature,
+        }
+    }
+
+    /// Parses a formatted token, without verifying it.
+    pub fn parse(token: &str) -> Result<Self, AccessTokenError> {
+        let mut parts = token.splitn(3, '.');
+        let (Some(subject), Some(expires_at), Some(signature)) =
+            (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(AccessTokenError::Invalid);
+        };
+        Ok(Self {
+            principal: Principal::parse(subject).ok_or(AccessTokenError::Invalid)?,
+            expires_at: expires_at.parse().map_err(|_| AccessTokenError::Invalid)?,
+            signature: signature.to_string(),
+        })
+    }
+
+    /// Checks that this token was issued with `key` and has not expired at `now`, returning
+    /// who it was issued to.
+    ///
+    /// # Errors
+    ///
+    /// - [AccessTokenError::Invalid] if the subject, expiry or signature were tampered with.
+    /// - [AccessTokenError::Expired] if the token expired before `now`.
+    pub fn verify(
+        &self,
+        key: &SigningKey,
+        now: DateTime<Utc>,
+    ) -> Result<Principal, AccessTokenError> {
+        let signature = hex::decode(&self.signature).map_err(|_| AccessTokenError::Invalid)?;
+        key.mac(&Self::signed_subject(&self.principal), self.expires_at)
+            .verify_slice(&signature)
+            .map_err(|_| AccessTokenError::Invalid)?;
+        let expires_at = self.expires_at();
+        if now > expires_at {
+            return Err(AccessTokenError::Expired { expires_at });
+        }
+        Ok(self.principal)
+    }
+
+    pub fn principal(&self) -> Principal {
+        self.principal
+    }
+
+    pub fn expires_at(&self) -> DateTime<Utc> {
+        DateTime::from_timestamp(self.expires_at, 0).unwrap_or(DateTime::<Utc>::MIN_UTC)
+    }
+
+    /// What is signed for the subject, never starting with `/`, so that no token signature is
+    /// valid for a [SignedUrl](super::signed_url::SignedUrl) made with the same key.
+    fn signed_subject(principal: &Principal) -> String {
+        format!("access-token:{}", principal.subject())
+    }
+}
+
+impl fmt::Display for AccessToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}.{}.{}",
+            self.principal.subject(),
+            self.expires_at,
+            self.signature
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeDelta;
+
+    use super::*;
+
+    fn key() -> SigningKey {
+        SigningKey::new(b"key")
+    }
+
+    #[test]
+    fn access_token_round_trips_until_it_expires() {
+        let now = Utc::now();
+        let user_id = uuid::Uuid::new_v4();
+        let token = AccessToken::issue(&key(), Principal::User(user_id), now + TimeDelta::hours(1));
+
+        let parsed = AccessToken::parse(&token.to_string()).unwrap();
+
+        assert_eq!(parsed, token);
+        assert_eq!(parsed.verify(&key(), now), Ok(Principal::User(user_id)));
+        assert!(matches!(
+            parsed.verify(&key(), now + TimeDelta::hours(2)),
+            Err(AccessTokenError::Expired { .. })
+        ));
+    }
+
+    #[test]
+    fn tampered_access_token_is_rejected() {
+        let now = Utc::now();
+        let token = AccessToken::issue(
+            &key(),
+            Principal::User(uuid::Uuid::new_v4()),
+            now + TimeDelta::hours(1),
+        );
+        let formatted = token.to_string();
+        let (_, rest) = formatted.split_once('.').unwrap();
+        let promoted = AccessToken::parse(&format!("admin.{rest}")).unwrap();
+
+        assert_eq!(promoted.verify(&key(), now), Err(AccessTokenError::Invalid));
+        assert_eq!(
+            token.verify(&SigningKey::new(b"other key"), now),
+            Err(AccessTokenError::Invalid)
+        );
+        assert_eq!(
+            AccessToken::parse("user:not-a-uuid.0.00"),
+            Err(AccessTokenError::Invalid)
+        );
+    }
+
+    #[test]
+    fn only_admins_and_the_user_act_for_a_user() {
+        let user_id = uuid::Uuid::new_v4();
+
+        assert!(Principal::Admin.may_act_for(&user_id));
+        assert!(Principal::User(user_id).may_act_for(&user_id));
+        assert!(!Principal::User(uuid::Uuid::new_v4()).may_act_for(&user_id));
+    }
+}
@@ -0,0 +1,158 @@
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+
+use crate::domain::crowdsrc::models::terms::TermsVersion;
+
+/// What a [User](super::user::User) did, as shown in their activity feed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ActivityKind {
+    SignedUp,
+    AcceptedTerms { version: TermsVersion },
+}
+
+/// An entry of the activity feed of a [User](super::user::User).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Activity {
+    user_id: uuid::Uuid,
+    kind: ActivityKind,
+    occurred_at: DateTime<Utc>,
+}
+
+impl Activity {
+    pub fn new(user_id: uuid::Uuid, kind: ActivityKind, occurred_at: DateTime<Utc>) -> Self {
+        Self {
+            user_id,
+            kind,
+            occurred_at,
+        }
+    }
+
+    pub fn user_id(&self) -> &uuid::Uuid {
+        &self.user_id
+    }
+
+    pub fn kind(&self) -> &ActivityKind {
+        &self.kind
+    }
+
+    pub fn occurred_at(&self) -> &DateTime<Utc> {
+        &self.occurred_at
+    }
+}
+
+/// Marks where a page of [Activity] ended, so that the next page starts after it.
+///
+/// Cursors are opaque to clients, who pass them back as received.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ActivityCursor(i64);
+
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("activity cursor '{invalid_cursor}' is invalid")]
+pub struct ActivityCursorError {
+    pub invalid_cursor: String,
+}
+
+impl ActivityCursor {
+    pub fn new(position: i64) -> Self {
+        Self(position)
+    }
+
+    pub fn parse(raw: &str) -> Result<Self, ActivityCursorError> {
+        raw.parse().map(Self).map_err(|_| ActivityCursorError {
+            invalid_cursor: raw.to_string(),
+        })
+    }
+
+    pub fn position(&self) -> i64 {
+        self.0
+    }
+}
+
+impl fmt::Display for ActivityCursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Which page of an activity feed to list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ActivityQuery {
+    limit: u32,
+    after: Option<ActivityCursor>,
+}
+
+impl ActivityQuery {
+    /// The largest page that may be requested.
+    pub const MAX_LIMIT: u32 = 100;
+
+    /// Lists at most `limit` entries, clamped to `1..=`[Self::MAX_LIMIT], starting after
+    /// `after`, or at the newest entry if `None`.
+    pub fn new(limit: u32, after: Option<ActivityCursor>) -> Self {
+        Self {
+            limit: limit.clamp(1, Self::MAX_LIMIT),
+            after,
+        }
+    }
+
+    pub fn limit(&self) -> u32 {
+        self.limit
+    }
+
+    pub fn after(&self) -> Option<ActivityCursor> {
+        self.after
+    }
+}
+
+/// A page of an activity feed, newest first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActivityPage {
+    activities: Vec<Activity>,
+    next: Option<ActivityCursor>,
+}
+
+impl ActivityPage {
+    pub fn new(activities: Vec<Activity>, next: Option<ActivityCursor>) -> Self {
+        Self { activities, next }
+    }
+
+    pub fn activities(&self) -> &[Activity] {
+        &self.activities
+    }
+
+    /// Where the next page starts, or `None` if this is the last page.
+    pub fn next(&self) -> Option<ActivityCursor> {
+        self.next
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ActivityError {
+    #[error("user with id {id} not found")]
+    UserNotFound { id: uuid::Uuid },
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_limit_is_clamped() {
+        assert_eq!(ActivityQuery::new(0, None).limit(), 1);
+        assert_eq!(ActivityQuery::new(20, None).limit(), 20);
+        assert_eq!(
+            ActivityQuery::new(1000, None).limit(),
+            ActivityQuery::MAX_LIMIT
+        );
+    }
+
+    #[test]
+    fn cursor_round_trips_through_display() {
+        let cursor = ActivityCursor::new(42);
+
+        assert_eq!(ActivityCursor::parse(&cursor.to_string()).unwrap(), cursor);
+        assert!(ActivityCursor::parse("abc").is_err());
+    }
+}
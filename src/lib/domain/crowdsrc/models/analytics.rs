@@ -0,0 +1,189 @@
+use chrono::{DateTime, TimeDelta, Utc};
+
+/// The most events that may be recorded at once.
+pub const MAX_BATCH_SIZE: usize = 100;
+
+/// The longest task id, in characters.
+const MAX_TASK_ID_LEN: usize = 100;
+
+/// How far ahead of the server the clock of a client may be.
+const MAX_CLOCK_SKEW: TimeDelta = TimeDelta::minutes(5);
+
+/// How long after an event occurred it may be recorded, such as after a client was offline.
+const MAX_EVENT_AGE: TimeDelta = TimeDelta::days(7);
+
+/// The longest time that may be spent on a task, in milliseconds.
+const MAX_TIME_ON_TASK_MS: u64 = 24 * 60 * 60 * 1000;
+
+/// What a contributor did with a task, as reported by their client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnalyticsEventKind {
+    /// The task was shown to the contributor.
+    TaskViewed,
+    /// The contributor moved on without contributing to the task.
+    TaskSkipped,
+    /// The contributor spent `duration_ms` milliseconds on the task.
+    TimeOnTask { duration_ms: u64 },
+}
+
+impl AnalyticsEventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::TaskViewed => "task_viewed",
+            Self::TaskSkipped => "task_skipped",
+            Self::TimeOnTask { .. } => "time_on_task",
+        }
+    }
+
+    /// How long the task took, for [AnalyticsEventKind::TimeOnTask].
+    pub fn duration_ms(&self) -> Option<u64> {
+        match self {
+            Self::TimeOnTask { duration_ms } => Some(*duration_ms),
+            Self::TaskViewed | Self::TaskSkipped => None,
+        }
+    }
+}
+
+/// An event reported by the client of a [User](super::user::User), for studying how tasks are
+/// annotated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnalyticsEvent {
+    user_id: uuid::Uuid,
+    task_id: String,
+    kind: AnalyticsEventKind,
+    occurred_at: DateTime<Utc>,
+    received_at: DateTime<Utc>,
+}
+
+impl AnalyticsEvent {
+    /// An event of `kind` on the task with the given id, which occurred on the client at
+    /// `occurred_at` and was received by the server at `received_at`.
+    ///
+    /// # Errors
+    ///
+    /// - [AnalyticsError::InvalidEvent] if the task id is blank or longer than 100 characters,
+    ///   the event occurred more than 5 minutes after it was received or more than 7 days
+    ///   before, or the time on task is zero or longer than a day.
+    pub fn new(
+        user_id: uuid::Uuid,
+        task_id: &str,
+        kind: AnalyticsEventKind,
+        occurred_at: DateTime<Utc>,
+        received_at: DateTime<Utc>,
+    ) -> Result<Self, AnalyticsError> {
+        let invalid = |reason: &str| {
+            Err(AnalyticsError::InvalidEvent {
+                reason: reason.to_string(),
+            })
+        };
+        let task_id = task_id.trim();
+        if task_id.is_empty() || task_id.chars().count() > MAX_TASK_ID_LEN {
+            return invalid("the task id must be 1 to 100 characters");
+        }
+        if occurred_at > received_at + MAX_CLOCK_SKEW {
+            return invalid("the event occurred in the future");
+        }
+        if occurred_at < received_at - MAX_EVENT_AGE {
+            return invalid("the event occurred more than 7 days ago");
+        }
+        if let Some(duration_ms) = kind.duration_ms()
+            && !(1..=MAX_TIME_ON_TASK_MS).contains(&duration_ms)
+        {
+            return invalid("the time on task must be between 1 millisecond and 24 hours");
+        }
+        Ok(Self {
+            user_id,
+            task_id: task_id.to_string(),
+            kind,
+            occurred_at,
+            received_at,
+        })
+    }
+
+    pub fn user_id(&self) -> &uuid::Uuid {
+        &self.user_id
+    }
+
+    /// The id of the task, as known to the client.
+    pub fn task_id(&self) -> &str {
+        &self.task_id
+    }
+
+    pub fn kind(&self) -> AnalyticsEventKind {
+        self.kind
+    }
+
+    pub fn occurred_at(&self) -> &DateTime<Utc> {
+        &self.occurred_at
+    }
+
+    pub fn received_at(&self) -> &DateTime<Utc> {
+        &self.received_at
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum AnalyticsError {
+    #[error("invalid analytics event: {reason}")]
+    InvalidEvent { reason: String },
+    #[error("cannot record more than {max} events at once")]
+    TooManyEvents { max: usize },
+    #[error("user with id {id} not found")]
+    UserNotFound { id: uuid::Uuid },
+    #[error("analytics events are arriving faster than they can be written")]
+    Overloaded,
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(kind: AnalyticsEventKind, occurred_at: DateTime<Utc>) -> Result<(), AnalyticsError> {
+        AnalyticsEvent::new(
+            uuid::Uuid::new_v4(),
+            "task-1",
+            kind,
+            occurred_at,
+            Utc::now(),
+        )
+        .map(|_| ())
+    }
+
+    #[test]
+    fn event_within_bounds_is_valid() {
+        let now = Utc::now();
+
+        assert!(event(AnalyticsEventKind::TaskViewed, now - TimeDelta::days(6)).is_ok());
+        assert!(event(AnalyticsEventKind::TaskSkipped, now + TimeDelta::minutes(4)).is_ok());
+        assert!(event(AnalyticsEventKind::TimeOnTask { duration_ms: 1500 }, now).is_ok());
+    }
+
+    #[test]
+    fn event_out_of_bounds_is_invalid() {
+        let now = Utc::now();
+
+        for result in [
+            event(AnalyticsEventKind::TaskViewed, now - TimeDelta::days(8)),
+            event(AnalyticsEventKind::TaskViewed, now + TimeDelta::hours(1)),
+            event(AnalyticsEventKind::TimeOnTask { duration_ms: 0 }, now),
+            event(
+                AnalyticsEventKind::TimeOnTask {
+                    duration_ms: MAX_TIME_ON_TASK_MS + 1,
+                },
+                now,
+            ),
+            AnalyticsEvent::new(
+                uuid::Uuid::new_v4(),
+                " ",
+                AnalyticsEventKind::TaskViewed,
+                now,
+                now,
+            )
+            .map(|_| ()),
+        ] {
+            assert!(matches!(result, Err(AnalyticsError::InvalidEvent { .. })));
+        }
+    }
+}
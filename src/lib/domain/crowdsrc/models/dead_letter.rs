@@ -0,0 +1,95 @@
+use chrono::{DateTime, Utc};
+
+/// An event [User](super::user::User)s are notified about.
+///
+/// Events only refer to the [User](super::user::User), so that no personal data is kept around
+/// after an erasure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationEvent {
+    UserCreated { user_id: uuid::Uuid },
+}
+
+impl NotificationEvent {
+    pub fn user_id(&self) -> &uuid::Uuid {
+        match self {
+            Self::UserCreated { user_id } => user_id,
+        }
+    }
+}
+
+/// A [NotificationEvent] the [UserNotifier](crate::domain::crowdsrc::ports::UserNotifier) failed
+/// to deliver, kept so that it can be redriven.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeadLetter {
+    id: uuid::Uuid,
+    event: NotificationEvent,
+    failure_reason: String,
+    retry_count: u32,
+    created_at: DateTime<Utc>,
+    last_failed_at: DateTime<Utc>,
+}
+
+impl DeadLetter {
+    pub fn new(
+        id: uuid::Uuid,
+        event: NotificationEvent,
+        failure_reason: String,
+        retry_count: u32,
+        created_at: DateTime<Utc>,
+        last_failed_at: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            id,
+            event,
+            failure_reason,
+            retry_count,
+            created_at,
+            last_failed_at,
+        }
+    }
+
+    pub fn id(&self) -> &uuid::Uuid {
+        &self.id
+    }
+
+    pub fn event(&self) -> &NotificationEvent {
+        &self.event
+    }
+
+    /// Why the latest delivery attempt failed.
+    pub fn failure_reason(&self) -> &str {
+        &self.failure_reason
+    }
+
+    /// How many times redriving the [DeadLetter] has failed.
+    pub fn retry_count(&self) -> u32 {
+        self.retry_count
+    }
+
+    pub fn created_at(&self) -> &DateTime<Utc> {
+        &self.created_at
+    }
+
+    pub fn last_failed_at(&self) -> &DateTime<Utc> {
+        &self.last_failed_at
+    }
+}
+
+/// The result of redriving a [DeadLetter].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RedriveOutcome {
+    /// The event was delivered and the [DeadLetter] removed.
+    Delivered,
+    /// Delivery failed again, the [DeadLetter] is kept with its retry count incremented.
+    Failed(DeadLetter),
+    /// The user the event is about was erased, so the [DeadLetter] was removed undelivered.
+    Discarded,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum DeadLetterError {
+    #[error("dead letter with id {id} not found")]
+    NotFound { id: uuid::Uuid },
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
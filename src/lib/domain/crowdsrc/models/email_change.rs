@@ -0,0 +1,140 @@
+use std::fmt;
+
+use chrono::{DateTime, TimeDelta, Utc};
+
+use crate::domain::crowdsrc::models::redacted::Redacted;
+use crate::domain::crowdsrc::models::user::EmailAddress;
+
+/// How long a requested [PendingEmailChange] may be confirmed.
+pub const EMAIL_CHANGE_TTL: TimeDelta = TimeDelta::hours(24);
+
+/// A change of the email of a [User](super::user::User) that takes effect once confirmed from
+/// the new address with its [EmailChangeToken].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingEmailChange {
+    user_id: uuid::Uuid,
+    new_email: EmailAddress,
+    requested_at: DateTime<Utc>,
+    expires_at: DateTime<Utc>,
+}
+
+impl PendingEmailChange {
+    pub fn new(
+        user_id: uuid::Uuid,
+        new_email: EmailAddress,
+        requested_at: DateTime<Utc>,
+        expires_at: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            user_id,
+            new_email,
+            requested_at,
+            expires_at,
+        }
+    }
+
+    pub fn user_id(&self) -> &uuid::Uuid {
+        &self.user_id
+    }
+
+    /// The address the email is changed to.
+    pub fn new_email(&self) -> &EmailAddress {
+        &self.new_email
+    }
+
+    pub fn requested_at(&self) -> &DateTime<Utc> {
+        &self.requested_at
+    }
+
+    /// When the change can no longer be confirmed.
+    pub fn expires_at(&self) -> &DateTime<Utc> {
+        &self.expires_at
+    }
+}
+
+/// The secret sent to the new address of a [PendingEmailChange], proving that it belongs to
+/// the [User](super::user::User).
+///
+/// The [fmt::Debug] representation is [Redacted], so that tokens don't end up in logs.
+#[derive(Clone, PartialEq, Eq)]
+pub struct EmailChangeToken(String);
+
+impl EmailChangeToken {
+    /// Generates a new token from 244 random bits.
+    pub fn generate() -> Self {
+        Self(format!(
+            "{}{}",
+            uuid::Uuid::new_v4().simple(),
+            uuid::Uuid::new_v4().simple()
+        ))
+    }
+
+    /// Wraps a token given by a client, to be checked against the [PendingEmailChange].
+    pub fn new(raw: &str) -> Self {
+        Self(raw.trim().to_string())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for EmailChangeToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("EmailChangeToken")
+            .field(&Redacted(&self.0))
+            .finish()
+    }
+}
+
+/// An [EmailChangeToken] in the form a [UserRepository](crate::domain::crowdsrc::ports::UserRepository)
+/// persists it in, from which the token can't be recovered, carried over as is when backing up
+/// and restoring pending changes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmailChangeTokenHash(String);
+
+impl EmailChangeTokenHash {
+    pub fn new(hash: &str) -> Self {
+        Self(hash.to_string())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum EmailChangeError {
+    #[error("user with id {id} not found")]
+    UserNotFound { id: uuid::Uuid },
+    #[error("user with id {user_id} has no pending email change")]
+    NotPending { user_id: uuid::Uuid },
+    #[error("email change token is invalid")]
+    InvalidToken,
+    #[error("email change of user with id {user_id} has expired")]
+    Expired { user_id: uuid::Uuid },
+    #[error("user with email {} already exists", Redacted(.email))]
+    DuplicateEmail { email: EmailAddress },
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_tokens_differ() {
+        let token = EmailChangeToken::generate();
+
+        assert_eq!(token.as_str().len(), 64);
+        assert_ne!(token, EmailChangeToken::generate());
+    }
+
+    #[test]
+    fn token_is_redacted_in_debug_output() {
+        let token = EmailChangeToken::generate();
+
+        assert!(!format!("{token:?}").contains(token.as_str()));
+    }
+}
@@ -0,0 +1,160 @@
+use chrono::{DateTime, TimeDelta, Utc};
+
+/// How long [InboxNotification]s are kept, read or not, before they are pruned.
+pub const NOTIFICATION_RETENTION: TimeDelta = TimeDelta::days(90);
+
+/// A notification in the inbox of a [User](super::user::User), shown in the app whether or
+/// not it was also delivered by email.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InboxNotification {
+    id: uuid::Uuid,
+    user_id: uuid::Uuid,
+    kind: InboxKind,
+    created_at: DateTime<Utc>,
+    read_at: Option<DateTime<Utc>>,
+}
+
+impl InboxNotification {
+    pub fn new(
+        id: uuid::Uuid,
+        user_id: uuid::Uuid,
+        kind: InboxKind,
+        created_at: DateTime<Utc>,
+        read_at: Option<DateTime<Utc>>,
+    ) -> Self {
+        Self {
+            id,
+            user_id,
+            kind,
+            created_at,
+            read_at,
+        }
+    }
+
+    pub fn id(&self) -> &uuid::Uuid {
+        &self.id
+    }
+
+    pub fn user_id(&self) -> &uuid::Uuid {
+        &self.user_id
+    }
+
+    pub fn kind(&self) -> InboxKind {
+        self.kind
+    }
+
+    pub fn created_at(&self) -> &DateTime<Utc> {
+        &self.created_at
+    }
+
+    /// When the user read the notification, or `None` if it is unread.
+    pub fn read_at(&self) -> Option<&DateTime<Utc>> {
+        self.read_at.as_ref()
+    }
+}
+
+/// What an [InboxNotification] tells its [User](super::user::User) about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InboxKind {
+    /// The user signed up.
+    Welcome,
+    /// A change of the email of the user awaits confirmation.
+    EmailChangeRequested,
+}
+
+impl InboxKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Welcome => "welcome",
+            Self::EmailChangeRequested => "email_change_requested",
+        }
+    }
+
+    pub fn parse(kind: &str) -> Option<Self> {
+        [Self::Welcome, Self::EmailChangeRequested]
+            .into_iter()
+            .find(|known| known.as_str() == kind)
+    }
+}
+
+/// Which notifications of an inbox to list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InboxQuery {
+    limit: u32,
+    unread_only: bool,
+}
+
+impl InboxQuery {
+    /// The most notifications that may be requested at once.
+    pub const MAX_LIMIT: u32 = 100;
+
+    /// Lists at most `limit` notifications, clamped to `1..=`[Self::MAX_LIMIT], leaving out
+    /// those read if `unread_only`.
+    pub fn new(limit: u32, unread_only: bool) -> Self {
+        Self {
+            limit: limit.clamp(1, Self::MAX_LIMIT),
+            unread_only,
+        }
+    }
+
+    pub fn limit(&self) -> u32 {
+        self.limit
+    }
+
+    pub fn unread_only(&self) -> bool {
+        self.unread_only
+    }
+}
+
+/// The newest notifications of an inbox, and how many of all its notifications are unread.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Inbox {
+    notifications: Vec<InboxNotification>,
+    unread_count: u64,
+}
+
+impl Inbox {
+    pub fn new(notifications: Vec<InboxNotification>, unread_count: u64) -> Self {
+        Self {
+            notifications,
+            unread_count,
+        }
+    }
+
+    pub fn notifications(&self) -> &[InboxNotification] {
+        &self.notifications
+    }
+
+    pub fn unread_count(&self) -> u64 {
+        self.unread_count
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum InboxError {
+    #[error("user with id {id} not found")]
+    UserNotFound { id: uuid::Uuid },
+    #[error("notification with id {id} not found")]
+    NotFound { id: uuid::Uuid },
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kind_round_trips_through_str() {
+        for kind in [InboxKind::Welcome, InboxKind::EmailChangeRequested] {
+            assert_eq!(InboxKind::parse(kind.as_str()), Some(kind));
+        }
+        assert_eq!(InboxKind::parse("unknown"), None);
+    }
+
+    #[test]
+    fn query_limit_is_clamped() {
+        assert_eq!(InboxQuery::new(0, false).limit(), 1);
+        assert_eq!(InboxQuery::new(1000, true).limit(), InboxQuery::MAX_LIMIT);
+    }
+}
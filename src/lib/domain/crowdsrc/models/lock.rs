@@ -0,0 +1,5 @@
+#[derive(Debug, thiserror::Error)]
+pub enum LockError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
@@ -0,0 +1,71 @@
+use std::fmt;
+
+/// Wraps personally identifiable information so it can be passed to `tracing` and `{:?}`
+/// formatting without being written verbatim.
+///
+/// Both [fmt::Debug] and [fmt::Display] print `[redacted]`; the wrapped value is only reachable
+/// through [Redacted::expose].
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Redacted<T>(pub T);
+
+impl<T> Redacted<T> {
+    pub fn expose(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> fmt::Debug for Redacted<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[redacted]")
+    }
+}
+
+impl<T> fmt::Display for Redacted<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[redacted]")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::crowdsrc::models::terms::TermsVersion;
+    use crate::domain::crowdsrc::models::user::{
+        CreateUserError, CreateUserRequest, EmailAddress, UserName,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_redacted_never_formats_inner_value() {
+        let email = EmailAddress::new("kristoffer@example.com").unwrap();
+
+        assert_eq!(format!("{}", Redacted(&email)), "[redacted]");
+        assert_eq!(format!("{:?}", Redacted(&email)), "[redacted]");
+    }
+
+    #[test]
+    fn test_email_address_debug_is_redacted() {
+        let req = CreateUserRequest::new(
+            UserName::new("Kristoffer").unwrap(),
+            EmailAddress::new("kristoffer@example.com").unwrap(),
+            TermsVersion::new("2026-01-30").unwrap(),
+        );
+
+        let debug = format!("{:?}", req);
+
+        assert!(
+            !debug.contains("kristoffer@example.com"),
+            "expected email to be redacted in {debug}"
+        );
+        assert!(debug.contains("Kristoffer"), "expected username in {debug}");
+    }
+
+    #[test]
+    fn test_duplicate_email_error_is_redacted() {
+        let err = CreateUserError::DuplicateEmail {
+            email: EmailAddress::new("kristoffer@example.com").unwrap(),
+        };
+
+        assert_eq!(err.to_string(), "user with email [redacted] already exists");
+    }
+}
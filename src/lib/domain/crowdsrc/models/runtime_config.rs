@@ -0,0 +1,66 @@
+use std::fmt;
+
+/// The levels [RuntimeConfig::log_level] may be set to.
+const LOG_LEVELS: &[&str] = &["off", "error", "warn", "info", "debug", "trace"];
+
+/// Settings that can be tuned while the application runs, overriding those of the
+/// configuration file. `None` keeps the configured value.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RuntimeConfig {
+    /// The level logged at, unless overridden for a target in the configuration file.
+    pub log_level: Option<LogLevel>,
+    /// How many submissions a single source may make per throttle window.
+    pub max_submissions: Option<u32>,
+}
+
+/// A valid log level, such as `info`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogLevel(String);
+
+impl LogLevel {
+    pub fn new(raw: &str) -> Result<Self, RuntimeConfigError> {
+        let level = raw.trim().to_lowercase();
+        if !LOG_LEVELS.contains(&level.as_str()) {
+            return Err(RuntimeConfigError::InvalidLogLevel {
+                invalid_level: raw.to_string(),
+            });
+        }
+        Ok(Self(level))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum RuntimeConfigError {
+    #[error("log level '{invalid_level}' is invalid")]
+    InvalidLogLevel { invalid_level: String },
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn log_level_is_normalized() {
+        assert_eq!(LogLevel::new(" DEBUG ").unwrap().as_str(), "debug");
+    }
+
+    #[test]
+    fn unknown_log_level_is_rejected() {
+        assert!(matches!(
+            LogLevel::new("loud"),
+            Err(RuntimeConfigError::InvalidLogLevel { invalid_level }) if invalid_level == "loud"
+        ));
+    }
+}
@@ -0,0 +1,310 @@
+use std::fmt;
+
+use chrono::{DateTime, Datelike, NaiveDate, TimeDelta, Timelike, Utc};
+
+/// How far ahead [CronExpression::next_after] looks for the next run. Long enough for any
+/// expression that runs at all, such as on February 29, to run within it.
+const HORIZON_DAYS: i64 = 9 * 366;
+
+/// A crontab-style expression of when a job runs, in UTC.
+///
+/// The five fields are the minute (0-59), hour (0-23), day of month (1-31), month (1-12) and day
+/// of week (0-7, both 0 and 7 being Sunday). Each field is `*` or a comma separated list of
+/// values and ranges such as `1-5`, either of which may be followed by a step such as `*/15`.
+///
+/// As in cron, a job whose day of month and day of week are both restricted runs on the days
+/// matching either.
+#[derive(Clone, PartialEq, Eq)]
+pub struct CronExpression {
+    expression: String,
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    any_day_of_month: bool,
+    any_day_of_week: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+#[error("cron expression '{expression}' is invalid: {reason}")]
+pub struct CronExpressionError {
+    pub expression: String,
+    pub reason: String,
+}
+
+impl CronExpression {
+    pub fn parse(expression: &str) -> Result<Self, CronExpressionError> {
+        let error = |reason: String| CronExpressionError {
+            expression: expression.to_string(),
+            reason,
+        };
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minutes, hours, days_of_month, months, days_of_week] = fields[..] else {
+            return Err(error(format!("expected 5 fields, got {}", fields.len())));
+        };
+        let mut days_of_week_mask = parse_field(days_of_week, 0, 7).map_err(&error)?;
+        // 7 is Sunday as well as 0
+        if days_of_week_mask & (1 << 7) != 0 {
+            days_of_week_mask = (days_of_week_mask | 1) & !(1 << 7);
+        }
+        let cron = Self {
+            expression: fields.join(" "),
+            minutes: parse_field(minutes, 0, 59).map_err(&error)?,
+            hours: parse_field(hours, 0, 23).map_err(&error)?,
+            days_of_month: parse_field(days_of_month, 1, 31).map_err(&error)?,
+            months: parse_field(months, 1, 12).map_err(&error)?,
+            days_of_week: days_of_week_mask,
+            any_day_of_month: days_of_month == "*",
+            any_day_of_week: days_of_week == "*",
+        };
+        let reference = DateTime::<Utc>::UNIX_EPOCH;
+        if cron.next_after(reference).is_none() {
+            return Err(error("it never matches".to_string()));
+        }
+        Ok(cron)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.expression
+    }
+
+    /// The first minute after `time` the expression matches, if any within the next years.
+    pub fn next_after(&self, time: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let horizon = time + TimeDelta::days(HORIZON_DAYS);
+        let mut time = time
+            .with_second(0)?
+            .with_nanosecond(0)?
+            .checked_add_signed(TimeDelta::minutes(1))?;
+        while time < horizon {
+            if !has(self.months, time.month()) {
+                let (year, month) = if time.month() == 12 {
+                    (time.year() + 1, 1)
+                } else {
+                    (time.year(), time.month() + 1)
+                };
+                time = NaiveDate::from_ymd_opt(year, month, 1)?
+                    .and_hms_opt(0, 0, 0)?
+                    .and_utc();
+            } else if !self.matches_day(time) {
+                time = (time.date_naive() + TimeDelta::days(1))
+                    .and_hms_opt(0, 0, 0)?
+                    .and_utc();
+            } else if !has(self.hours, time.hour()) {
+                time = time.with_minute(0)? + TimeDelta::hours(1);
+            } else if !has(self.minutes, time.minute()) {
+                time += TimeDelta::minutes(1);
+            } else {
+                return Some(time);
+            }
+        }
+        None
+    }
+
+    fn matches_day(&self, time: DateTime<Utc>) -> bool {
+        let day_of_month = has(self.days_of_month, time.day());
+        let day_of_week = has(self.days_of_week, time.weekday().num_days_from_sunday());
+        match (self.any_day_of_month, self.any_day_of_week) {
+            (false, false) => day_of_month || day_of_week,
+            _ => day_of_month && day_of_week,
+        }
+    }
+}
+
+impl fmt::Display for CronExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.expression)
+    }
+}
+
+impl fmt::Debug for CronExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("CronExpression")
+            .field(&self.expression)
+            .finish()
+    }
+}
+
+fn has(mask: u64, value: u32) -> bool {
+    mask & (1 << value) != 0
+}
+
+/// Parses a field whose values range from `min` to `max` into a mask with a bit per value.
+fn parse_field(field: &str, min: u32, max: u32) -> Result<u64, String> {
+    let mut mask = 0;
+    for item in field.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => (
+                range,
+                step.parse::<u32>()
+                    .ok()
+                    .filter(|step| *step > 0)
+                    .ok_or_else(|| format!("invalid step '{step}'"))?,
+            ),
+            None => (item, 1),
+        };
+        let (start, end) = match range {
+            "*" => (min, max),
+            range => {
+                let value = |value: &str| {
+                    value
+                        .parse::<u32>()
+                        .ok()
+                        .filter(|value| (min..=max).contains(value))
+                        .ok_or_else(|| format!("'{value}' is not between {min} and {max}"))
+                };
+                match range.split_once('-') {
+                    Some((start, end)) => (value(start)?, value(end)?),
+                    None if step > 1 => (value(range)?, max),
+                    None => (value(range)?, value(range)?),
+                }
+            }
+        };
+        if start > end {
+            return Err(format!("range '{range}' is empty"));
+        }
+        for value in (start..=end).step_by(step as usize) {
+            mask |= 1 << value;
+        }
+    }
+    Ok(mask)
+}
+
+/// A named job that runs whenever its [CronExpression] matches.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    name: String,
+    cron: CronExpression,
+    next_run_at: DateTime<Utc>,
+    last_run_at: Option<DateTime<Utc>>,
+}
+
+impl Schedule {
+    pub fn new(
+        name: &str,
+        cron: CronExpression,
+        next_run_at: DateTime<Utc>,
+        last_run_at: Option<DateTime<Utc>>,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            cron,
+            next_run_at,
+            last_run_at,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn cron(&self) -> &CronExpression {
+        &self.cron
+    }
+
+    /// When the job is due next.
+    pub fn next_run_at(&self) -> &DateTime<Utc> {
+        &self.next_run_at
+    }
+
+    /// When the job was last claimed to run, if ever.
+    pub fn last_run_at(&self) -> Option<&DateTime<Utc>> {
+        self.last_run_at.as_ref()
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ScheduleError {
+    #[error("schedule '{name}' not found")]
+    NotFound { name: String },
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(time: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(time).unwrap().to_utc()
+    }
+
+    fn next(expression: &str, time: &str) -> DateTime<Utc> {
+        CronExpression::parse(expression)
+            .unwrap()
+            .next_after(at(time))
+            .unwrap()
+    }
+
+    #[test]
+    fn next_after_finds_next_matching_minute() {
+        assert_eq!(
+            next("*/15 * * * *", "2026-10-15T10:07:30Z"),
+            at("2026-10-15T10:15:00Z")
+        );
+        assert_eq!(
+            next("0 * * * *", "2026-10-15T10:00:00Z"),
+            at("2026-10-15T11:00:00Z")
+        );
+        assert_eq!(
+            next("30 2 * * *", "2026-10-15T10:00:00Z"),
+            at("2026-10-16T02:30:00Z")
+        );
+        assert_eq!(
+            next("0 0 1 1 *", "2026-10-15T10:00:00Z"),
+            at("2027-01-01T00:00:00Z")
+        );
+    }
+
+    #[test]
+    fn next_after_handles_days_of_week() {
+        // 2026-10-15 is a Thursday
+        assert_eq!(
+            next("0 9 * * 1-5", "2026-10-16T10:00:00Z"),
+            at("2026-10-19T09:00:00Z")
+        );
+        assert_eq!(
+            next("0 9 * * 7", "2026-10-15T10:00:00Z"),
+            at("2026-10-18T09:00:00Z")
+        );
+        // either the 1st or a Monday
+        assert_eq!(
+            next("0 0 1 * 1", "2026-10-15T10:00:00Z"),
+            at("2026-10-19T00:00:00Z")
+        );
+    }
+
+    #[test]
+    fn next_after_finds_leap_day() {
+        assert_eq!(
+            next("0 0 29 2 *", "2026-10-15T10:00:00Z"),
+            at("2028-02-29T00:00:00Z")
+        );
+    }
+
+    #[test]
+    fn parse_rejects_invalid_expressions() {
+        for expression in [
+            "* * * *",
+            "60 * * * *",
+            "* 24 * * *",
+            "* * 0 * *",
+            "*/0 * * * *",
+            "5-1 * * * *",
+            "a * * * *",
+            "0 0 30 2 *",
+        ] {
+            assert!(
+                CronExpression::parse(expression).is_err(),
+                "expected '{expression}' to be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn parse_normalizes_whitespace() {
+        let cron = CronExpression::parse(" 0  *  * * * ").unwrap();
+
+        assert_eq!(cron.as_str(), "0 * * * *");
+    }
+}
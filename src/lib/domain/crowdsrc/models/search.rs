@@ -0,0 +1,77 @@
+/// The longest text that may be searched for, in characters.
+const MAX_TEXT_LEN: usize = 100;
+
+/// A search for [User](super::user::User)s by username.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserSearchQuery {
+    text: String,
+    limit: u32,
+}
+
+impl UserSearchQuery {
+    /// The most users that may be found at once.
+    pub const MAX_LIMIT: u32 = 100;
+
+    /// Searches for at most `limit` users, clamped to `1..=`[Self::MAX_LIMIT], whose username
+    /// matches `text`.
+    ///
+    /// # Errors
+    ///
+    /// - [SearchError::InvalidQuery] if `text` is blank or longer than 100 characters.
+    pub fn new(text: &str, limit: u32) -> Result<Self, SearchError> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Err(SearchError::InvalidQuery {
+                reason: "the search text cannot be empty".to_string(),
+            });
+        }
+        if text.chars().count() > MAX_TEXT_LEN {
+            return Err(SearchError::InvalidQuery {
+                reason: format!("the search text cannot be longer than {MAX_TEXT_LEN} characters"),
+            });
+        }
+        Ok(Self {
+            text: text.to_string(),
+            limit: limit.clamp(1, Self::MAX_LIMIT),
+        })
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn limit(&self) -> u32 {
+        self.limit
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SearchError {
+    #[error("invalid search: {reason}")]
+    InvalidQuery { reason: String },
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_text_is_trimmed_and_limit_clamped() {
+        let query = UserSearchQuery::new("  ali ", 1000).unwrap();
+
+        assert_eq!(query.text(), "ali");
+        assert_eq!(query.limit(), UserSearchQuery::MAX_LIMIT);
+    }
+
+    #[test]
+    fn blank_or_long_query_is_invalid() {
+        for text in ["", "   ", &"a".repeat(MAX_TEXT_LEN + 1)] {
+            assert!(matches!(
+                UserSearchQuery::new(text, 10),
+                Err(SearchError::InvalidQuery { .. })
+            ));
+        }
+    }
+}
@@ -0,0 +1,57 @@
+//! Generators of the raw input value objects are parsed from, for property tests.
+//!
+//! The input is biased towards the characters validation must handle, such as whitespace of
+//! all kinds, zero width characters and combining marks, which uniformly random characters
+//! would rarely hit. Each property runs on a fixed seed, so that failures reproduce.
+
+use rand::{Rng, SeedableRng, rngs::StdRng, seq::IndexedRandom};
+
+/// How many inputs a property is checked on.
+pub const CASES: usize = 2000;
+
+/// Characters that are easy to get wrong when validating.
+const EDGE_CHARS: &[char] = &[
+    ' ', '\t', '\n', '\r', '\u{A0}', '\u{1680}', '\u{2003}', '\u{2028}', '\u{3000}', '\u{200B}',
+    '\u{200C}', '\u{200D}', '\u{2060}', '\u{FEFF}', '\u{0301}', '\u{0000}', '\u{007F}', 'é', 'ß',
+    'ǅ', 'а', '🦀', '-', '_', '.', '+', '@', '"', '\\',
+];
+
+/// Runs `property` on [CASES] inputs generated by `generate` from a fixed seed.
+pub fn check<T>(generate: impl Fn(&mut StdRng) -> T, property: impl Fn(T)) {
+    let mut rng = StdRng::seed_from_u64(0x0063_7277_6473_7263);
+    for _ in 0..CASES {
+        property(generate(&mut rng));
+    }
+}
+
+/// A string of up to `max_len` characters, mixing ASCII letters, edge cases and any
+/// character.
+pub fn arbitrary_string(rng: &mut StdRng, max_len: usize) -> String {
+    let len = rng.random_range(0..=max_len);
+    (0..len)
+        .map(|_| match rng.random_range(0..4) {
+            0 | 1 => rng.random_range('a'..='z'),
+            2 => *EDGE_CHARS.choose(rng).expect("edge characters are given"),
+            _ => rng.random::<char>(),
+        })
+        .collect()
+}
+
+/// A raw username, valid more often than not.
+pub fn raw_username(rng: &mut StdRng) -> String {
+    arbitrary_string(rng, 24)
+}
+
+/// A raw email address, with a local part and domain, and often a plus alias or dots.
+pub fn raw_email(rng: &mut StdRng) -> String {
+    let local_part = arbitrary_string(rng, 12);
+    let domain = match rng.random_range(0..4) {
+        0 => "gmail.com".to_string(),
+        1 => "Example.COM".to_string(),
+        _ => format!("{}.se", arbitrary_string(rng, 10)),
+    };
+    match rng.random_range(0..3) {
+        0 => format!("{local_part}+{}@{domain}", arbitrary_string(rng, 6)),
+        _ => format!("{local_part}@{domain}"),
+    }
+}
@@ -0,0 +1,83 @@
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+
+/// The version of the terms of service a [User](super::user::User) accepted, e.g. `2026-01-30`.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TermsVersion(String);
+
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum TermsVersionError {
+    #[error("terms version cannot be empty")]
+    Empty,
+    #[error("terms version cannot contain whitespace: '{invalid_version}'")]
+    WithWhitespace { invalid_version: String },
+}
+
+impl TermsVersion {
+    pub fn new(raw: &str) -> Result<Self, TermsVersionError> {
+        let trimmed = raw.trim();
+        if trimmed.is_empty() {
+            Err(TermsVersionError::Empty)
+        } else if trimmed.contains(|c: char| c.is_whitespace()) {
+            Err(TermsVersionError::WithWhitespace {
+                invalid_version: raw.to_string(),
+            })
+        } else {
+            Ok(Self(trimmed.to_string()))
+        }
+    }
+}
+
+impl fmt::Display for TermsVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// The record of a [User](super::user::User) accepting a [TermsVersion].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TermsAcceptance {
+    user_id: uuid::Uuid,
+    terms_version: TermsVersion,
+    accepted_at: DateTime<Utc>,
+}
+
+impl TermsAcceptance {
+    pub fn new(
+        user_id: uuid::Uuid,
+        terms_version: TermsVersion,
+        accepted_at: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            user_id,
+            terms_version,
+            accepted_at,
+        }
+    }
+
+    pub fn user_id(&self) -> &uuid::Uuid {
+        &self.user_id
+    }
+
+    pub fn terms_version(&self) -> &TermsVersion {
+        &self.terms_version
+    }
+
+    pub fn accepted_at(&self) -> &DateTime<Utc> {
+        &self.accepted_at
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum AcceptTermsError {
+    #[error("user with id {id} not found")]
+    UserNotFound { id: uuid::Uuid },
+    #[error("terms version {accepted} is not the current version {current}")]
+    StaleTermsVersion {
+        accepted: TermsVersion,
+        current: TermsVersion,
+    },
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
@@ -0,0 +1,74 @@
+use std::{fmt, net::IpAddr, time::Duration};
+
+use sha2::{Digest, Sha256};
+
+/// Where a submission originates from, identified by the hash of its IP address and user agent
+/// so that neither is kept around.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct SubmissionSource(String);
+
+impl SubmissionSource {
+    pub fn new(ip: IpAddr, user_agent: &str) -> Self {
+        let mut hasher = Sha256::new();
+        hasher.update(ip.to_string().as_bytes());
+        hasher.update(b"\n");
+        hasher.update(user_agent.as_bytes());
+        Self(hex::encode(hasher.finalize()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for SubmissionSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("SubmissionSource").field(&self.0).finish()
+    }
+}
+
+/// How many submissions a single [SubmissionSource] may make within a window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ThrottlePolicy {
+    pub max_submissions: u32,
+    pub window: Duration,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ThrottleError {
+    #[error("too many submissions from the same source, retry after {retry_after:?}")]
+    TooManySubmissions { retry_after: Duration },
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn source_depends_on_ip_and_user_agent() {
+        let ip: IpAddr = "192.0.2.1".parse().unwrap();
+        let other_ip: IpAddr = "192.0.2.2".parse().unwrap();
+
+        assert_eq!(
+            SubmissionSource::new(ip, "curl"),
+            SubmissionSource::new(ip, "curl")
+        );
+        assert_ne!(
+            SubmissionSource::new(ip, "curl"),
+            SubmissionSource::new(other_ip, "curl")
+        );
+        assert_ne!(
+            SubmissionSource::new(ip, "curl"),
+            SubmissionSource::new(ip, "firefox")
+        );
+    }
+
+    #[test]
+    fn source_does_not_contain_ip() {
+        let source = SubmissionSource::new("192.0.2.1".parse().unwrap(), "curl");
+
+        assert!(!source.as_str().contains("192.0.2.1"));
+    }
+}
@@ -1,6 +1,13 @@
 use std::{fmt, str::FromStr};
 
 use chrono::{DateTime, Utc};
+use unicode_normalization::UnicodeNormalization;
+use unicode_properties::{GeneralCategory, UnicodeGeneralCategory};
+use unicode_security::confusable_detection;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::domain::crowdsrc::models::redacted::Redacted;
+use crate::domain::crowdsrc::models::terms::{TermsAcceptance, TermsVersion};
 
 #[derive(Debug, Clone)]
 pub struct User {
@@ -8,9 +15,11 @@ pub struct User {
     username: UserName,
     email_addr: EmailAddress,
     created_at: DateTime<Utc>,
+    email_verified: bool,
 }
 
 impl User {
+    /// A user whose email is not verified yet.
     pub fn new(
         id: uuid::Uuid,
         username: UserName,
@@ -22,9 +31,16 @@ impl User {
             username,
             email_addr,
             created_at,
+            email_verified: false,
         }
     }
 
+    /// The user, with their email verified or not.
+    pub fn with_email_verified(mut self, email_verified: bool) -> Self {
+        self.email_verified = email_verified;
+        self
+    }
+
     pub fn id(&self) -> &uuid::Uuid {
         &self.id
     }
@@ -36,32 +52,114 @@ impl User {
     pub fn email(&self) -> &EmailAddress {
         &self.email_addr
     }
+
+    pub fn created_at(&self) -> &DateTime<Utc> {
+        &self.created_at
+    }
+
+    /// Whether the user has proven that they own their email.
+    pub fn is_email_verified(&self) -> bool {
+        self.email_verified
+    }
+
+    /// Whether the personal data of the user has been erased.
+    pub fn is_erased(&self) -> bool {
+        self.username == UserName::erased(&self.id)
+    }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Clone, PartialEq, Eq, Hash)]
 /// A valid email address.
+///
+/// The [fmt::Debug] representation is [Redacted], so that emails don't end up in logs.
 pub struct EmailAddress(email_address::EmailAddress);
 
+/// An invalid email address, which is left out so that it doesn't end up in logs.
 #[derive(Debug, Clone, thiserror::Error)]
-#[error("'{invalid_email}' is not a valid email address: {message}")]
+#[error("not a valid email address: {message}")]
 pub struct EmailAddressError {
-    pub invalid_email: String,
     pub message: String,
 }
 
+/// The domain of the placeholder emails of erased [User]s, which can't be given by anyone, so
+/// that no one can pass for an erased user.
+const RESERVED_EMAIL_DOMAIN: &str = "invalid";
+
 impl EmailAddress {
     pub fn new(email: &str) -> Result<Self, EmailAddressError> {
+        let parsed = Self::from_persisted(email)?;
+        let domain = parsed.0.domain().to_lowercase();
+        let domain = domain.trim_end_matches('.');
+        if domain == RESERVED_EMAIL_DOMAIN || domain.ends_with(&format!(".{RESERVED_EMAIL_DOMAIN}"))
+        {
+            return Err(EmailAddressError {
+                message: format!("the domain .{RESERVED_EMAIL_DOMAIN} is reserved"),
+            });
+        }
+        Ok(parsed)
+    }
+
+    /// An email address as it was persisted, without rejecting the reserved domain, so that the
+    /// placeholder emails of erased [User]s can be read.
+    pub fn from_persisted(persisted: &str) -> Result<Self, EmailAddressError> {
         let email =
-            email_address::EmailAddress::from_str(email).map_err(|err| EmailAddressError {
-                invalid_email: email.to_string(),
+            email_address::EmailAddress::from_str(persisted).map_err(|err| EmailAddressError {
                 message: err.to_string(),
             })?;
         Ok(Self(email))
     }
 
+    /// The placeholder email of the erased [User] with the given id.
+    pub fn erased(id: &uuid::Uuid) -> Self {
+        Self::from_persisted(&format!("{}@erased.{RESERVED_EMAIL_DOMAIN}", id.simple()))
+            .expect("erased email address is valid")
+    }
+
     pub fn as_str(&self) -> &str {
         self.0.as_str()
     }
+
+    /// The form of this address under `canonicalization`, equal for addresses that reach the
+    /// same mailbox, so that one mailbox can't hold several accounts.
+    pub fn canonical(&self, canonicalization: &EmailCanonicalization) -> String {
+        let mut domain = self.0.domain().to_lowercase();
+        let mut local_part = self.0.local_part();
+        if canonicalization.fold_plus_aliases
+            && let Some((mailbox, _alias)) = local_part.split_once('+')
+            && !mailbox.is_empty()
+        {
+            local_part = mailbox;
+        }
+        if canonicalization.fold_gmail_dots && GMAIL_DOMAINS.contains(&domain.as_str()) {
+            domain = GMAIL_DOMAINS[0].to_string();
+            return format!("{}@{domain}", local_part.replace('.', "").to_lowercase());
+        }
+        format!("{local_part}@{domain}")
+    }
+}
+
+/// The domains of Gmail, the first being the one the others are folded into.
+const GMAIL_DOMAINS: &[&str] = &["gmail.com", "googlemail.com"];
+
+/// How [EmailAddress]es are folded into their canonical form.
+///
+/// Domains are always compared case-insensitively. The defaults fold nothing else.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EmailCanonicalization {
+    /// Whether `+alias` suffixes of the local part are dropped, as most providers deliver
+    /// `user+alias@example.com` to `user@example.com`.
+    pub fold_plus_aliases: bool,
+    /// Whether dots and case in the local part of Gmail addresses are ignored, as Gmail does,
+    /// and `googlemail.com` is treated as `gmail.com`.
+    pub fold_gmail_dots: bool,
+}
+
+impl fmt::Debug for EmailAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("EmailAddress")
+            .field(&Redacted(self.as_str()))
+            .finish()
+    }
 }
 
 impl fmt::Display for EmailAddress {
@@ -70,6 +168,8 @@ impl fmt::Display for EmailAddress {
     }
 }
 
+/// A username, trimmed and in Unicode normalization form C, so that usernames that are
+/// canonically equivalent, such as `é` and `e` followed by a combining acute accent, are equal.
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct UserName(String);
 
@@ -79,21 +179,111 @@ pub enum UserNameError {
     Empty,
     #[error("username cannot contain whitespace: '{invalid_username}'")]
     WithWhitespace { invalid_username: String },
+    /// The username contains control or format characters, such as zero width spaces and
+    /// bidirectional overrides, which are invisible or change how the username is rendered.
+    #[error("username cannot contain control or format characters: {invalid_username:?}")]
+    WithControlCharacters { invalid_username: String },
+    #[error("username cannot be longer than {max_graphemes} characters")]
+    TooLong { max_graphemes: usize },
+    /// The username starts like the placeholder usernames of erased users, so that no one can
+    /// pass for an erased user.
+    #[error("username cannot start with '{prefix}'")]
+    Reserved { prefix: &'static str },
+}
+
+/// The prefix of the placeholder usernames of erased [User]s.
+const ERASED_USERNAME_PREFIX: &str = "erased-";
+
+/// How [UserName]s are validated, besides being non-empty and free of whitespace, control and
+/// format characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UserNamePolicy {
+    /// How many characters a username may have, counted as grapheme clusters, so that an
+    /// accented letter or a flag counts as one however many code points it is made of.
+    pub max_graphemes: usize,
+}
+
+impl UserNamePolicy {
+    /// The default [UserNamePolicy::max_graphemes], leaving room for the placeholder usernames
+    /// of erased users.
+    pub const DEFAULT_MAX_GRAPHEMES: usize = 64;
+}
+
+impl Default for UserNamePolicy {
+    fn default() -> Self {
+        Self {
+            max_graphemes: Self::DEFAULT_MAX_GRAPHEMES,
+        }
+    }
 }
 
 impl UserName {
+    /// Parses a username under the default [UserNamePolicy].
     pub fn new(raw: &str) -> Result<Self, UserNameError> {
-        let trimmed = raw.trim();
-        if trimmed.is_empty() {
+        Self::with_policy(raw, &UserNamePolicy::default())
+    }
+
+    pub fn with_policy(raw: &str, policy: &UserNamePolicy) -> Result<Self, UserNameError> {
+        let normalized: String = raw.trim().nfc().collect();
+        if normalized.is_empty() {
             Err(UserNameError::Empty)
-        } else if trimmed.contains(|c: char| c.is_whitespace()) {
+        } else if normalized.contains(char::is_whitespace) {
             Err(UserNameError::WithWhitespace {
                 invalid_username: raw.to_string(),
             })
+        } else if normalized.contains(is_control_or_format) {
+            Err(UserNameError::WithControlCharacters {
+                invalid_username: raw.to_string(),
+            })
+        } else if normalized.graphemes(true).count() > policy.max_graphemes {
+            Err(UserNameError::TooLong {
+                max_graphemes: policy.max_graphemes,
+            })
+        } else if normalized
+            .to_lowercase()
+            .starts_with(ERASED_USERNAME_PREFIX)
+        {
+            Err(UserNameError::Reserved {
+                prefix: ERASED_USERNAME_PREFIX,
+            })
         } else {
-            Ok(Self(trimmed.to_string()))
+            Ok(Self(normalized))
         }
     }
+
+    /// A username as it was persisted, once valid, without validating it again, so that
+    /// [User]s created under a looser [UserNamePolicy] than the current one can still be read.
+    pub fn from_persisted(persisted: &str) -> Self {
+        Self(persisted.to_string())
+    }
+
+    /// The placeholder username of the erased [User] with the given id.
+    pub fn erased(id: &uuid::Uuid) -> Self {
+        Self(format!("{ERASED_USERNAME_PREFIX}{}", id.simple()))
+    }
+
+    /// The skeleton of the username, in the sense of Unicode Technical Standard #39: the form
+    /// that usernames which look alike when rendered have in common, so that one user can't
+    /// impersonate another with a lookalike username.
+    ///
+    /// Compatibility characters, such as fullwidth letters, are decomposed first, since only
+    /// some of them are listed as confusables, and then confusables, such as Cyrillic and Greek
+    /// letters looking like Latin ones, `1` and `I` for `l` and `rn` for `m`, are replaced as
+    /// listed by Unicode. Case and diacritics are kept, so `Admin`, `admin` and `ädmin` have
+    /// different skeletons.
+    pub fn skeleton(&self) -> String {
+        let decomposed: String = self.0.nfkd().collect();
+        confusable_detection::skeleton(&decomposed).collect()
+    }
+}
+
+/// Whether `c` is a control character, or a format character such as a zero width space or
+/// joiner, which [char::is_whitespace] misses although they are as invisible.
+fn is_control_or_format(c: char) -> bool {
+    matches!(
+        c.general_category(),
+        GeneralCategory::Control | GeneralCategory::Format
+    )
 }
 
 impl fmt::Display for UserName {
@@ -107,11 +297,16 @@ impl fmt::Display for UserName {
 pub struct CreateUserRequest {
     username: UserName,
     email: EmailAddress,
+    accepted_terms: TermsVersion,
 }
 
 impl CreateUserRequest {
-    pub fn new(username: UserName, email: EmailAddress) -> Self {
-        Self { username, email }
+    pub fn new(username: UserName, email: EmailAddress, accepted_terms: TermsVersion) -> Self {
+        Self {
+            username,
+            email,
+            accepted_terms,
+        }
     }
 
     pub fn username(&self) -> &UserName {
@@ -121,15 +316,337 @@ impl CreateUserRequest {
     pub fn email(&self) -> &EmailAddress {
         &self.email
     }
+
+    /// The [TermsVersion] the new [User] accepted.
+    pub fn accepted_terms(&self) -> &TermsVersion {
+        &self.accepted_terms
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
 pub enum CreateUserError {
     #[error("user with user name {username} already exists")]
     DuplicateUserName { username: UserName },
-    #[error("user with email {email} already exists")]
+    #[error("user with email {} already exists", Redacted(.email))]
     DuplicateEmail { email: EmailAddress },
+    #[error("terms version {accepted} is not the current version {current}")]
+    StaleTermsVersion {
+        accepted: TermsVersion,
+        current: TermsVersion,
+    },
     #[error(transparent)]
     Unknown(#[from] anyhow::Error),
     // to be extended as new error scenarios are introduced
 }
+
+/// The outcome of a single [CreateUserRequest] within a batch creation.
+#[derive(Debug, Clone)]
+pub enum CreateUserOutcome {
+    /// The [User] was created.
+    Created(User),
+    /// An [User] with the same [UserName] already exists.
+    DuplicateUserName { username: UserName },
+    /// An [User] with the same [EmailAddress] already exists.
+    DuplicateEmail { email: EmailAddress },
+}
+
+impl CreateUserOutcome {
+    pub fn is_created(&self) -> bool {
+        matches!(self, Self::Created(_))
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CreateUsersError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ListUsersError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum GetUserError {
+    #[error("user with id {id} not found")]
+    NotFound { id: uuid::Uuid },
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+/// All data held about a single [User], in a form suitable for handing over to them.
+#[derive(Debug, Clone)]
+pub struct UserDataExport {
+    user: User,
+    terms_acceptances: Vec<TermsAcceptance>,
+    exported_at: DateTime<Utc>,
+}
+
+impl UserDataExport {
+    pub fn new(
+        user: User,
+        terms_acceptances: Vec<TermsAcceptance>,
+        exported_at: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            user,
+            terms_acceptances,
+            exported_at,
+        }
+    }
+
+    pub fn user(&self) -> &User {
+        &self.user
+    }
+
+    pub fn terms_acceptances(&self) -> &[TermsAcceptance] {
+        &self.terms_acceptances
+    }
+
+    pub fn exported_at(&self) -> &DateTime<Utc> {
+        &self.exported_at
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum EraseUserError {
+    #[error("user with id {id} not found")]
+    NotFound { id: uuid::Uuid },
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum NotifyUserError {
+    #[error(transparent)]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::crowdsrc::models::strategies::{self, check};
+
+    const FOLD_ALL: EmailCanonicalization = EmailCanonicalization {
+        fold_plus_aliases: true,
+        fold_gmail_dots: true,
+    };
+
+    fn canonical(email: &str, canonicalization: &EmailCanonicalization) -> String {
+        EmailAddress::new(email)
+            .unwrap()
+            .canonical(canonicalization)
+    }
+
+    #[test]
+    fn canonical_form_lowercases_domain_only_by_default() {
+        let default = EmailCanonicalization::default();
+
+        assert_eq!(
+            canonical("Kristoffer+x@Example.COM", &default),
+            "Kristoffer+x@example.com"
+        );
+        assert_eq!(canonical("k.a@gmail.com", &default), "k.a@gmail.com");
+    }
+
+    #[test]
+    fn canonical_form_folds_plus_aliases() {
+        assert_eq!(
+            canonical("user+1@example.com", &FOLD_ALL),
+            "user@example.com"
+        );
+        assert_eq!(
+            canonical("user+a+b@example.com", &FOLD_ALL),
+            "user@example.com"
+        );
+        assert_eq!(
+            canonical("+user@example.com", &FOLD_ALL),
+            "+user@example.com"
+        );
+    }
+
+    #[test]
+    fn canonical_form_folds_gmail_dots() {
+        assert_eq!(
+            canonical("First.Last+news@GoogleMail.com", &FOLD_ALL),
+            "firstlast@gmail.com"
+        );
+        assert_eq!(
+            canonical("first.last@example.com", &FOLD_ALL),
+            "first.last@example.com"
+        );
+    }
+
+    #[test]
+    fn username_is_normalized() {
+        assert_eq!(
+            UserName::new("Kristoffe\u{301}r").unwrap(),
+            UserName::new("Kristoffér").unwrap()
+        );
+        assert_eq!(
+            UserName::new(" Kristoffe\u{301}r ").unwrap().to_string(),
+            "Kristoffér"
+        );
+    }
+
+    #[test]
+    fn username_rejects_control_and_format_characters() {
+        for username in [
+            "a\u{0}b",
+            "a\u{7F}b",
+            "a\u{200B}b",
+            "a\u{200D}b",
+            "a\u{AD}b",
+            "a\u{202E}b",
+            "\u{FEFF}ab",
+        ] {
+            assert!(
+                matches!(
+                    UserName::new(username),
+                    Err(UserNameError::WithControlCharacters { .. })
+                ),
+                "expected {username:?} to be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn username_length_is_counted_in_graphemes() {
+        let policy = UserNamePolicy { max_graphemes: 3 };
+
+        assert!(UserName::with_policy("abc", &policy).is_ok());
+        assert!(UserName::with_policy("a\u{308}o\u{308}u\u{30A}", &policy).is_ok());
+        assert!(UserName::with_policy("🇸🇪🇳🇴🇫🇮", &policy).is_ok());
+        assert!(matches!(
+            UserName::with_policy("abcd", &policy),
+            Err(UserNameError::TooLong { max_graphemes: 3 })
+        ));
+        assert!(UserName::new(&"a".repeat(UserNamePolicy::DEFAULT_MAX_GRAPHEMES)).is_ok());
+        assert!(UserName::new(&"a".repeat(UserNamePolicy::DEFAULT_MAX_GRAPHEMES + 1)).is_err());
+    }
+
+    #[test]
+    fn email_address_error_leaves_out_the_address() {
+        let err = EmailAddress::new("kristoffer.example.com").unwrap_err();
+
+        assert!(!err.to_string().contains("kristoffer"));
+        assert!(!format!("{err:?}").contains("kristoffer"));
+    }
+
+    #[test]
+    fn erased_placeholders_are_reserved() {
+        let id = uuid::Uuid::new_v4();
+        let erased_username = UserName::erased(&id).to_string();
+        let erased_email = EmailAddress::erased(&id).to_string();
+
+        assert!(matches!(
+            UserName::new(&erased_username),
+            Err(UserNameError::Reserved { .. })
+        ));
+        assert!(matches!(
+            UserName::new("Erased-someone"),
+            Err(UserNameError::Reserved { .. })
+        ));
+        assert!(UserName::new("erased").is_ok());
+        assert!(EmailAddress::new(&erased_email).is_err());
+        assert!(EmailAddress::new("someone@example.INVALID").is_err());
+        assert!(EmailAddress::new("someone@invalid.example.com").is_ok());
+        assert_eq!(
+            EmailAddress::from_persisted(&erased_email).ok(),
+            Some(EmailAddress::erased(&id))
+        );
+    }
+
+    #[test]
+    fn persisted_username_is_not_validated_again() {
+        let persisted = "a".repeat(UserNamePolicy::DEFAULT_MAX_GRAPHEMES + 1);
+
+        assert!(UserName::new(&persisted).is_err());
+        assert_eq!(UserName::from_persisted(&persisted).to_string(), persisted);
+    }
+
+    #[test]
+    fn skeleton_folds_confusable_usernames() {
+        let skeleton = |username: &str| UserName::new(username).unwrap().skeleton();
+
+        for lookalike in ["\u{430}dmin", "adm\u{456}n", "adrnin", "ａｄｍｉｎ"] {
+            assert_eq!(
+                skeleton(lookalike),
+                skeleton("admin"),
+                "expected {lookalike:?} to look like admin"
+            );
+        }
+        assert_eq!(skeleton("\u{410}DM\u{399}N"), skeleton("ADMIN"));
+        assert_eq!(skeleton("paypaI"), skeleton("paypal"));
+        assert_eq!(skeleton("paypa1"), skeleton("paypal"));
+        assert_eq!(skeleton("G00GLE"), skeleton("GOOGLE"));
+        assert_ne!(skeleton("Admin"), skeleton("admin"));
+        assert_ne!(skeleton("Kristoffér"), skeleton("Kristoffer"));
+    }
+
+    #[test]
+    fn username_round_trips_through_display() {
+        check(strategies::raw_username, |raw| {
+            if let Ok(name) = UserName::new(&raw) {
+                let reparsed = UserName::new(&name.to_string());
+                assert_eq!(reparsed.ok(), Some(name), "reparsing {raw:?}");
+            }
+        });
+    }
+
+    #[test]
+    fn username_has_no_invisible_separators() {
+        check(strategies::raw_username, |raw| {
+            if let Ok(name) = UserName::new(&raw) {
+                assert!(
+                    !name.to_string().contains(|c: char| c.is_whitespace()
+                        || ['\u{200B}', '\u{200C}', '\u{200D}', '\u{2060}', '\u{FEFF}']
+                            .contains(&c)),
+                    "accepted {raw:?}"
+                );
+            }
+        });
+    }
+
+    #[test]
+    fn username_ignores_surrounding_whitespace() {
+        check(strategies::raw_username, |raw| {
+            assert_eq!(
+                UserName::new(&format!(" {raw}\n")).ok(),
+                UserName::new(&raw).ok(),
+                "padding {raw:?}"
+            );
+        });
+    }
+
+    #[test]
+    fn email_round_trips_through_as_str() {
+        check(strategies::raw_email, |raw| {
+            if let Ok(email) = EmailAddress::new(&raw) {
+                let reparsed = EmailAddress::new(email.as_str());
+                assert!(
+                    reparsed.is_ok_and(|reparsed| reparsed == email),
+                    "reparsing {raw:?}"
+                );
+            }
+        });
+    }
+
+    #[test]
+    fn canonical_form_is_its_own_canonical_form() {
+        check(strategies::raw_email, |raw| {
+            if let Ok(email) = EmailAddress::new(&raw) {
+                let canonical = email.canonical(&FOLD_ALL);
+                if let Ok(canonical_email) = EmailAddress::new(&canonical) {
+                    assert_eq!(
+                        canonical_email.canonical(&FOLD_ALL),
+                        canonical,
+                        "canonicalizing {raw:?}"
+                    );
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,504 @@
+/*!
+   Module `observed_service` provides a [CrowdSrcService] decorator reporting each call to a
+   [ServiceObserver], along with observers that log the calls and that count them, so that
+   cross-cutting concerns can be added around any [CrowdSrcService] without touching it.
+*/
+
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use futures::Stream;
+
+use crate::domain::crowdsrc::models::activity::{ActivityError, ActivityPage, ActivityQuery};
+use crate::domain::crowdsrc::models::analytics::{AnalyticsError, AnalyticsEvent};
+use crate::domain::crowdsrc::models::dead_letter::{DeadLetter, DeadLetterError, RedriveOutcome};
+use crate::domain::crowdsrc::models::email_change::{
+    EmailChangeError, EmailChangeToken, PendingEmailChange,
+};
+use crate::domain::crowdsrc::models::inbox::{Inbox, InboxError, InboxQuery};
+use crate::domain::crowdsrc::models::search::{SearchError, UserSearchQuery};
+use crate::domain::crowdsrc::models::terms::{AcceptTermsError, TermsAcceptance, TermsVersion};
+use crate::domain::crowdsrc::models::user::{
+    CreateUserError, CreateUserOutcome, CreateUserRequest, CreateUsersError, EmailAddress,
+    EraseUserError, GetUserError, ListUsersError, User, UserDataExport,
+};
+use crate::domain::crowdsrc::ports::CrowdSrcService;
+
+/// A completed call to a [CrowdSrcService].
+#[derive(Debug, Clone, Copy)]
+pub struct ServiceCall<'a> {
+    /// The name of the method called, such as `create_user`.
+    pub operation: &'static str,
+    pub elapsed: Duration,
+    /// The error the call failed with, if it failed.
+    pub error: Option<&'a (dyn Error + 'static)>,
+}
+
+/// `ServiceObserver` is told about each call made through an [ObservedCrowdSrcService].
+pub trait ServiceObserver: Send + Sync + Clone + 'static {
+    /// Observe `call`, once it has completed.
+    fn observe(&self, call: &ServiceCall<'_>);
+}
+
+/// A [CrowdSrcService] decorator reporting each completed call to its [ServiceObserver].
+///
+/// Streams are not observed, since how long they take depends on their consumer.
+#[derive(Debug, Clone)]
+pub struct ObservedCrowdSrcService<CS: CrowdSrcService, O: ServiceObserver> {
+    inner: CS,
+    observer: O,
+}
+
+impl<CS: CrowdSrcService, O: ServiceObserver> ObservedCrowdSrcService<CS, O> {
+    pub fn new(inner: CS, observer: O) -> Self {
+        Self { inner, observer }
+    }
+
+    async fn observed<T, E: Error + 'static>(
+        &self,
+        operation: &'static str,
+        call: impl Future<Output = Result<T, E>>,
+    ) -> Result<T, E> {
+        let started_at = Instant::now();
+        let result = call.await;
+        self.observer.observe(&ServiceCall {
+            operation,
+            elapsed: started_at.elapsed(),
+            error: result.as_ref().err().map(|e| e as &(dyn Error + 'static)),
+        });
+        result
+    }
+}
+
+impl<CS: CrowdSrcService, O: ServiceObserver> CrowdSrcService for ObservedCrowdSrcService<CS, O> {
+    async fn create_user(&self, req: &CreateUserRequest) -> Result<User, CreateUserError> {
+        self.observed("create_user", self.inner.create_user(req))
+            .await
+    }
+
+    async fn create_users(
+        &self,
+        reqs: &[CreateUserRequest],
+    ) -> Result<Vec<CreateUserOutcome>, CreateUsersError> {
+        self.observed("create_users", self.inner.create_users(reqs))
+            .await
+    }
+
+    fn stream_users(&self) -> impl Stream<Item = Result<User, ListUsersError>> + Send {
+        self.inner.stream_users()
+    }
+
+    async fn get_user(&self, id: &uuid::Uuid) -> Result<User, GetUserError> {
+        self.observed("get_user", self.inner.get_user(id)).await
+    }
+
+    async fn export_user_data(&self, id: &uuid::Uuid) -> Result<UserDataExport, GetUserError> {
+        self.observed("export_user_data", self.inner.export_user_data(id))
+            .await
+    }
+
+    async fn erase_user(&self, id: &uuid::Uuid) -> Result<User, EraseUserError> {
+        self.observed("erase_user", self.inner.erase_user(id)).await
+    }
+
+    async fn accept_terms(
+        &self,
+        user_id: &uuid::Uuid,
+        version: &TermsVersion,
+    ) -> Result<TermsAcceptance, AcceptTermsError> {
+        self.observed("accept_terms", self.inner.accept_terms(user_id, version))
+            .await
+    }
+
+    async fn check_current_terms(&self, user_id: &uuid::Uuid) -> Result<(), AcceptTermsError> {
+        self.observed(
+            "check_current_terms",
+            self.inner.check_current_terms(user_id),
+        )
+        .await
+    }
+
+    async fn list_dead_letters(&self) -> Result<Vec<DeadLetter>, DeadLetterError> {
+        self.observed("list_dead_letters", self.inner.list_dead_letters())
+            .await
+    }
+
+    async fn redrive_dead_letter(
+        &self,
+        id: &uuid::Uuid,
+    ) -> Result<RedriveOutcome, DeadLetterError> {
+        self.observed("redrive_dead_letter", self.inner.redrive_dead_letter(id))
+            .await
+    }
+
+    async fn list_user_activity(
+        &self,
+        user_id: &uuid::Uuid,
+        query: &ActivityQuery,
+    ) -> Result<ActivityPage, ActivityError> {
+        self.observed(
+            "list_user_activity",
+            self.inner.list_user_activity(user_id, query),
+        )
+        .await
+    }
+
+    async fn request_email_change(
+        &self,
+        user_id: &uuid::Uuid,
+        new_email: &EmailAddress,
+    ) -> Result<PendingEmailChange, EmailChangeError> {
+        self.observed(
+            "request_email_change",
+            self.inner.request_email_change(user_id, new_email),
+        )
+        .await
+    }
+
+    async fn confirm_email_change(
+        &self,
+        user_id: &uuid::Uuid,
+        token: &EmailChangeToken,
+    ) -> Result<User, EmailChangeError> {
+        self.observed(
+            "confirm_email_change",
+            self.inner.confirm_email_change(user_id, token),
+        )
+        .await
+    }
+
+    async fn cancel_email_change(&self, user_id: &uuid::Uuid) -> Result<(), EmailChangeError> {
+        self.observed(
+            "cancel_email_change",
+            self.inner.cancel_email_change(user_id),
+        )
+        .await
+    }
+
+    async fn purge_expired_email_changes(&self) -> Result<u64, EmailChangeError> {
+        self.observed(
+            "purge_expired_email_changes",
+            self.inner.purge_expired_email_changes(),
+        )
+        .await
+    }
+
+    async fn list_notifications(
+        &self,
+        user_id: &uuid::Uuid,
+        query: &InboxQuery,
+    ) -> Result<Inbox, InboxError> {
+        self.observed(
+            "list_notifications",
+            self.inner.list_notifications(user_id, query),
+        )
+        .await
+    }
+
+    async fn count_unread_notifications(&self, user_id: &uuid::Uuid) -> Result<u64, InboxError> {
+        self.observed(
+            "count_unread_notifications",
+            self.inner.count_unread_notifications(user_id),
+        )
+        .await
+    }
+
+    async fn mark_notification_read(
+        &self,
+        user_id: &uuid::Uuid,
+        notification_id: &uuid::Uuid,
+    ) -> Result<(), InboxError> {
+        self.observed(
+            "mark_notification_read",
+            self.inner.mark_notification_read(user_id, notification_id),
+        )
+        .await
+    }
+
+    async fn mark_all_notifications_read(&self, user_id: &uuid::Uuid) -> Result<u64, InboxError> {
+        self.observed(
+            "mark_all_notifications_read",
+            self.inner.mark_all_notifications_read(user_id),
+        )
+        .await
+    }
+
+    async fn purge_old_notifications(&self) -> Result<u64, InboxError> {
+        self.observed(
+            "purge_old_notifications",
+            self.inner.purge_old_notifications(),
+        )
+        .await
+    }
+
+    async fn search_users(&self, query: &UserSearchQuery) -> Result<Vec<User>, SearchError> {
+        self.observed("search_users", self.inner.search_users(query))
+            .await
+    }
+
+    async fn record_analytics_events(
+        &self,
+        events: Vec<AnalyticsEvent>,
+    ) -> Result<(), AnalyticsError> {
+        self.observed(
+            "record_analytics_events",
+            self.inner.record_analytics_events(events),
+        )
+        .await
+    }
+}
+
+/// A [ServiceObserver] logging each call at debug level, and each failed call at warn level,
+/// within the span of the request that made it.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LoggingObserver;
+
+impl ServiceObserver for LoggingObserver {
+    fn observe(&self, call: &ServiceCall<'_>) {
+        let elapsed_ms = call.elapsed.as_millis() as u64;
+        match call.error {
+            Some(e) => tracing::warn!(
+                operation = call.operation,
+                elapsed_ms,
+                "service call failed: {e}"
+            ),
+            None => tracing::debug!(operation = call.operation, elapsed_ms, "service call"),
+        }
+    }
+}
+
+/// The calls made to one method of a [CrowdSrcService], as counted by [ServiceMetrics].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OperationMetrics {
+    pub calls: u64,
+    pub failures: u64,
+    /// How long all the calls took together.
+    pub total_time: Duration,
+}
+
+/// A [ServiceObserver] counting the calls to, and the failures of, each method, and how long
+/// they took.
+///
+/// Clones share their counts, so that a clone kept aside reads the counts of the one given to
+/// an [ObservedCrowdSrcService].
+#[derive(Debug, Clone, Default)]
+pub struct ServiceMetrics {
+    operations: Arc<Mutex<BTreeMap<&'static str, OperationMetrics>>>,
+}
+
+impl ServiceMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The counts so far, by method name.
+    pub fn snapshot(&self) -> BTreeMap<&'static str, OperationMetrics> {
+        self.operations
+            .lock()
+            .expect("service metrics lock poisoned")
+            .clone()
+    }
+}
+
+impl ServiceObserver for ServiceMetrics {
+    fn observe(&self, call: &ServiceCall<'_>) {
+        let mut operations = self
+            .operations
+            .lock()
+            .expect("service metrics lock poisoned");
+        let metrics = operations.entry(call.operation).or_default();
+        metrics.calls += 1;
+        metrics.failures += u64::from(call.error.is_some());
+        metrics.total_time += call.elapsed;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use crate::domain::crowdsrc::models::user::UserName;
+
+    use super::*;
+
+    /// Knows of a single [User], failing to get any other.
+    #[derive(Clone)]
+    struct SingleUserCrowdSrcService {
+        user: User,
+    }
+
+    impl SingleUserCrowdSrcService {
+        fn new() -> Self {
+            Self {
+                user: User::new(
+                    uuid::Uuid::new_v4(),
+                    UserName::new("Kristoffer").unwrap(),
+                    EmailAddress::new("kristoffer@example.com").unwrap(),
+                    Utc::now(),
+                ),
+            }
+        }
+    }
+
+    impl CrowdSrcService for SingleUserCrowdSrcService {
+        async fn create_user(&self, _: &CreateUserRequest) -> Result<User, CreateUserError> {
+            unimplemented!()
+        }
+
+        async fn create_users(
+            &self,
+            _: &[CreateUserRequest],
+        ) -> Result<Vec<CreateUserOutcome>, CreateUsersError> {
+            unimplemented!()
+        }
+
+        fn stream_users(&self) -> impl Stream<Item = Result<User, ListUsersError>> + Send {
+            futures::stream::empty()
+        }
+
+        async fn get_user(&self, id: &uuid::Uuid) -> Result<User, GetUserError> {
+            if id != self.user.id() {
+                return Err(GetUserError::NotFound { id: *id });
+            }
+            Ok(self.user.clone())
+        }
+
+        async fn export_user_data(&self, _: &uuid::Uuid) -> Result<UserDataExport, GetUserError> {
+            unimplemented!()
+        }
+
+        async fn erase_user(&self, _: &uuid::Uuid) -> Result<User, EraseUserError> {
+            unimplemented!()
+        }
+
+        async fn accept_terms(
+            &self,
+            _: &uuid::Uuid,
+            _: &TermsVersion,
+        ) -> Result<TermsAcceptance, AcceptTermsError> {
+            unimplemented!()
+        }
+
+        async fn check_current_terms(&self, _: &uuid::Uuid) -> Result<(), AcceptTermsError> {
+            unimplemented!()
+        }
+
+        async fn list_dead_letters(&self) -> Result<Vec<DeadLetter>, DeadLetterError> {
+            unimplemented!()
+        }
+
+        async fn redrive_dead_letter(
+            &self,
+            _: &uuid::Uuid,
+        ) -> Result<RedriveOutcome, DeadLetterError> {
+            unimplemented!()
+        }
+
+        async fn list_user_activity(
+            &self,
+            _: &uuid::Uuid,
+            _: &ActivityQuery,
+        ) -> Result<ActivityPage, ActivityError> {
+            unimplemented!()
+        }
+
+        async fn request_email_change(
+            &self,
+            _: &uuid::Uuid,
+            _: &EmailAddress,
+        ) -> Result<PendingEmailChange, EmailChangeError> {
+            unimplemented!()
+        }
+
+        async fn confirm_email_change(
+            &self,
+            _: &uuid::Uuid,
+            _: &EmailChangeToken,
+        ) -> Result<User, EmailChangeError> {
+            unimplemented!()
+        }
+
+        async fn cancel_email_change(&self, _: &uuid::Uuid) -> Result<(), EmailChangeError> {
+            unimplemented!()
+        }
+
+        async fn purge_expired_email_changes(&self) -> Result<u64, EmailChangeError> {
+            Ok(0)
+        }
+
+        async fn list_notifications(
+            &self,
+            _: &uuid::Uuid,
+            _: &InboxQuery,
+        ) -> Result<Inbox, InboxError> {
+            unimplemented!()
+        }
+
+        async fn count_unread_notifications(&self, _: &uuid::Uuid) -> Result<u64, InboxError> {
+            unimplemented!()
+        }
+
+        async fn mark_notification_read(
+            &self,
+            _: &uuid::Uuid,
+            _: &uuid::Uuid,
+        ) -> Result<(), InboxError> {
+            unimplemented!()
+        }
+
+        async fn mark_all_notifications_read(&self, _: &uuid::Uuid) -> Result<u64, InboxError> {
+            unimplemented!()
+        }
+
+        async fn purge_old_notifications(&self) -> Result<u64, InboxError> {
+            unimplemented!()
+        }
+
+        async fn search_users(&self, _: &UserSearchQuery) -> Result<Vec<User>, SearchError> {
+            unimplemented!()
+        }
+
+        async fn record_analytics_events(
+            &self,
+            _: Vec<AnalyticsEvent>,
+        ) -> Result<(), AnalyticsError> {
+            unimplemented!()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_service_metrics_count_calls_and_failures() {
+        let inner = SingleUserCrowdSrcService::new();
+        let metrics = ServiceMetrics::new();
+        let service = ObservedCrowdSrcService::new(inner.clone(), metrics.clone());
+
+        service.get_user(inner.user.id()).await.unwrap();
+        service.get_user(&uuid::Uuid::new_v4()).await.unwrap_err();
+        service.purge_expired_email_changes().await.unwrap();
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(
+            snapshot.keys().collect::<Vec<_>>(),
+            vec![&"get_user", &"purge_expired_email_changes"]
+        );
+        assert_eq!(snapshot["get_user"].calls, 2);
+        assert_eq!(snapshot["get_user"].failures, 1);
+        assert_eq!(snapshot["purge_expired_email_changes"].calls, 1);
+        assert_eq!(snapshot["purge_expired_email_changes"].failures, 0);
+    }
+
+    #[tokio::test]
+    async fn test_observed_service_passes_errors_through() {
+        let service =
+            ObservedCrowdSrcService::new(SingleUserCrowdSrcService::new(), LoggingObserver);
+        let id = uuid::Uuid::new_v4();
+
+        let actual = service.get_user(&id).await;
+
+        assert!(matches!(actual, Err(GetUserError::NotFound { id: actual_id }) if actual_id == id));
+    }
+}
@@ -6,14 +6,46 @@
 
    Trait methods are explicitly asynchronous, including `Send` bounds on response types,
    since the application is expected to always run in a multithreaded environment.
+
+   Module `testing` holds the contract test suites implementations are checked against.
 */
 
+pub mod testing;
+
 use std::future::Future;
+use std::net::IpAddr;
+
+use chrono::{DateTime, Utc};
+use futures::Stream;
 
+use crate::domain::crowdsrc::models::abuse_challenge::{AbuseChallengeError, Challenge};
+use crate::domain::crowdsrc::models::activity::{
+    Activity, ActivityError, ActivityPage, ActivityQuery,
+};
+use crate::domain::crowdsrc::models::analytics::{AnalyticsError, AnalyticsEvent};
+use crate::domain::crowdsrc::models::dead_letter::{
+    DeadLetter, DeadLetterError, NotificationEvent, RedriveOutcome,
+};
+use crate::domain::crowdsrc::models::email_change::{
+    EmailChangeError, EmailChangeToken, EmailChangeTokenHash, PendingEmailChange,
+};
+use crate::domain::crowdsrc::models::feature_flag::FeatureFlag;
+use crate::domain::crowdsrc::models::inbox::{Inbox, InboxError, InboxNotification, InboxQuery};
+use crate::domain::crowdsrc::models::lock::LockError;
+use crate::domain::crowdsrc::models::maintenance::MaintenanceError;
+use crate::domain::crowdsrc::models::runtime_config::{RuntimeConfig, RuntimeConfigError};
+use crate::domain::crowdsrc::models::schedule::{CronExpression, Schedule, ScheduleError};
+use crate::domain::crowdsrc::models::search::{SearchError, UserSearchQuery};
+use crate::domain::crowdsrc::models::terms::{AcceptTermsError, TermsAcceptance, TermsVersion};
+use crate::domain::crowdsrc::models::throttle::{SubmissionSource, ThrottleError};
 use crate::domain::crowdsrc::models::user::CreateUserError;
+use crate::domain::crowdsrc::models::user::EmailAddress;
 #[allow(unused_imports)] // UserName is used in doc comments
 use crate::domain::crowdsrc::models::user::UserName;
-use crate::domain::crowdsrc::models::user::{CreateUserRequest, User};
+use crate::domain::crowdsrc::models::user::{
+    CreateUserOutcome, CreateUserRequest, CreateUsersError, EraseUserError, GetUserError,
+    ListUsersError, NotifyUserError, User, UserDataExport,
+};
 
 /// `CrowdSrcService` is the public API for the crowdsrc domain.
 ///
@@ -24,11 +56,256 @@ pub trait CrowdSrcService: Clone + Send + Sync + 'static {
     ///
     /// # Errors
     ///
-    /// - [CreateUserError::Duplicate] if an [User] with the same [UserName] already exists.
+    /// - [CreateUserError::DuplicateUserName] if an [User] with the same [UserName] already
+    ///   exists.
+    /// - [CreateUserError::DuplicateEmail] if an [User] with the same [EmailAddress] already
+    ///   exists.
+    /// - [CreateUserError::StaleTermsVersion] if the accepted [TermsVersion] isn't the current
+    ///   one.
     fn create_user(
         &self,
         req: &CreateUserRequest,
     ) -> impl Future<Output = Result<User, CreateUserError>> + Send;
+
+    /// Asynchronously create a batch of new [User]s.
+    ///
+    /// Returns one [CreateUserOutcome] per request, in the order of `reqs`. Duplicates are
+    /// reported per item and do not fail the batch.
+    ///
+    /// The accepted [TermsVersion]s are recorded as given, since imported users may have
+    /// accepted earlier versions.
+    ///
+    /// # Errors
+    ///
+    /// - [CreateUsersError::Unknown] if the batch could not be processed at all.
+    fn create_users(
+        &self,
+        reqs: &[CreateUserRequest],
+    ) -> impl Future<Output = Result<Vec<CreateUserOutcome>, CreateUsersError>> + Send;
+
+    /// Stream all [User]s, oldest first, without loading them all into memory.
+    ///
+    /// # Errors
+    ///
+    /// - Yields [ListUsersError::Unknown] if a [User] could not be read.
+    fn stream_users(&self) -> impl Stream<Item = Result<User, ListUsersError>> + Send;
+
+    /// Asynchronously retrieve the [User] with the given id.
+    ///
+    /// # Errors
+    ///
+    /// - [GetUserError::NotFound] if no [User] with the given id exists.
+    fn get_user(&self, id: &uuid::Uuid) -> impl Future<Output = Result<User, GetUserError>> + Send;
+
+    /// Asynchronously collect all data held about the [User] with the given id.
+    ///
+    /// # Errors
+    ///
+    /// - [GetUserError::NotFound] if no [User] with the given id exists.
+    fn export_user_data(
+        &self,
+        id: &uuid::Uuid,
+    ) -> impl Future<Output = Result<UserDataExport, GetUserError>> + Send;
+
+    /// Asynchronously erase all personal data of the [User] with the given id.
+    ///
+    /// The [User] itself is kept, with its [UserName] and [EmailAddress] replaced by
+    /// placeholders, so that anything referring to it stays valid. Erasing an already erased
+    /// [User] succeeds.
+    ///
+    /// # Errors
+    ///
+    /// - [EraseUserError::NotFound] if no [User] with the given id exists.
+    fn erase_user(
+        &self,
+        id: &uuid::Uuid,
+    ) -> impl Future<Output = Result<User, EraseUserError>> + Send;
+
+    /// Asynchronously record that the [User] with the given id accepted the current
+    /// [TermsVersion].
+    ///
+    /// # Errors
+    ///
+    /// - [AcceptTermsError::StaleTermsVersion] if `version` isn't the current [TermsVersion].
+    /// - [AcceptTermsError::UserNotFound] if no [User] with the given id exists.
+    fn accept_terms(
+        &self,
+        user_id: &uuid::Uuid,
+        version: &TermsVersion,
+    ) -> impl Future<Output = Result<TermsAcceptance, AcceptTermsError>> + Send;
+
+    /// Asynchronously check that the [User] with the given id last accepted the current
+    /// [TermsVersion], as required before acting under the terms.
+    ///
+    /// # Errors
+    ///
+    /// - [AcceptTermsError::StaleTermsVersion] with the last accepted [TermsVersion] if it isn't
+    ///   the current one.
+    /// - [AcceptTermsError::UserNotFound] if no [User] with the given id accepted any terms.
+    fn check_current_terms(
+        &self,
+        user_id: &uuid::Uuid,
+    ) -> impl Future<Output = Result<(), AcceptTermsError>> + Send;
+
+    /// Asynchronously list the [DeadLetter]s of notifications that could not be delivered,
+    /// oldest first.
+    fn list_dead_letters(
+        &self,
+    ) -> impl Future<Output = Result<Vec<DeadLetter>, DeadLetterError>> + Send;
+
+    /// Asynchronously try to deliver the event of the [DeadLetter] with the given id again.
+    ///
+    /// # Errors
+    ///
+    /// - [DeadLetterError::NotFound] if no [DeadLetter] with the given id exists.
+    fn redrive_dead_letter(
+        &self,
+        id: &uuid::Uuid,
+    ) -> impl Future<Output = Result<RedriveOutcome, DeadLetterError>> + Send;
+
+    /// Asynchronously list a page of the activity feed of the [User] with the given id, newest
+    /// first.
+    ///
+    /// # Errors
+    ///
+    /// - [ActivityError::UserNotFound] if no [User] with the given id exists.
+    fn list_user_activity(
+        &self,
+        user_id: &uuid::Uuid,
+        query: &ActivityQuery,
+    ) -> impl Future<Output = Result<ActivityPage, ActivityError>> + Send;
+
+    /// Asynchronously request that the email of the [User] with the given id is changed to
+    /// `new_email`, sending an [EmailChangeToken] to confirm it with to `new_email` and a
+    /// notice to the current address.
+    ///
+    /// The email is unchanged until confirmed. A new request replaces the pending one.
+    ///
+    /// # Errors
+    ///
+    /// - [EmailChangeError::UserNotFound] if no [User] with the given id exists.
+    /// - [EmailChangeError::DuplicateEmail] if another [User] has `new_email`.
+    fn request_email_change(
+        &self,
+        user_id: &uuid::Uuid,
+        new_email: &EmailAddress,
+    ) -> impl Future<Output = Result<PendingEmailChange, EmailChangeError>> + Send;
+
+    /// Asynchronously confirm the [PendingEmailChange] of the [User] with the given id,
+    /// changing its email.
+    ///
+    /// # Errors
+    ///
+    /// - [EmailChangeError::NotPending] if the [User] has no [PendingEmailChange].
+    /// - [EmailChangeError::InvalidToken] if `token` isn't that of the [PendingEmailChange].
+    /// - [EmailChangeError::Expired] if the [PendingEmailChange] has expired.
+    /// - [EmailChangeError::DuplicateEmail] if another [User] has taken the new email since.
+    fn confirm_email_change(
+        &self,
+        user_id: &uuid::Uuid,
+        token: &EmailChangeToken,
+    ) -> impl Future<Output = Result<User, EmailChangeError>> + Send;
+
+    /// Asynchronously cancel the [PendingEmailChange] of the [User] with the given id.
+    ///
+    /// # Errors
+    ///
+    /// - [EmailChangeError::NotPending] if the [User] has no [PendingEmailChange].
+    fn cancel_email_change(
+        &self,
+        user_id: &uuid::Uuid,
+    ) -> impl Future<Output = Result<(), EmailChangeError>> + Send;
+
+    /// Asynchronously discard all [PendingEmailChange]s that have expired, returning how many
+    /// were discarded.
+    ///
+    /// # Errors
+    ///
+    /// - [EmailChangeError::Unknown] if the expired changes could not be discarded.
+    fn purge_expired_email_changes(
+        &self,
+    ) -> impl Future<Output = Result<u64, EmailChangeError>> + Send;
+
+    /// Asynchronously list the newest [InboxNotification]s of the [User] with the given id
+    /// matching `query`, with how many of their notifications are unread.
+    ///
+    /// # Errors
+    ///
+    /// - [InboxError::UserNotFound] if no [User] with the given id exists.
+    fn list_notifications(
+        &self,
+        user_id: &uuid::Uuid,
+        query: &InboxQuery,
+    ) -> impl Future<Output = Result<Inbox, InboxError>> + Send;
+
+    /// Asynchronously count the unread [InboxNotification]s of the [User] with the given id.
+    ///
+    /// # Errors
+    ///
+    /// - [InboxError::UserNotFound] if no [User] with the given id exists.
+    fn count_unread_notifications(
+        &self,
+        user_id: &uuid::Uuid,
+    ) -> impl Future<Output = Result<u64, InboxError>> + Send;
+
+    /// Asynchronously mark an [InboxNotification] of the [User] with the given id as read.
+    /// Marking a notification read again keeps when it was first read.
+    ///
+    /// # Errors
+    ///
+    /// - [InboxError::NotFound] if the [User] has no [InboxNotification] with
+    ///   `notification_id`.
+    fn mark_notification_read(
+        &self,
+        user_id: &uuid::Uuid,
+        notification_id: &uuid::Uuid,
+    ) -> impl Future<Output = Result<(), InboxError>> + Send;
+
+    /// Asynchronously mark all unread [InboxNotification]s of the [User] with the given id as
+    /// read, returning how many were marked.
+    ///
+    /// # Errors
+    ///
+    /// - [InboxError::UserNotFound] if no [User] with the given id exists.
+    fn mark_all_notifications_read(
+        &self,
+        user_id: &uuid::Uuid,
+    ) -> impl Future<Output = Result<u64, InboxError>> + Send;
+
+    /// Asynchronously discard all [InboxNotification]s older than the retention period,
+    /// returning how many were discarded.
+    ///
+    /// # Errors
+    ///
+    /// - [InboxError::Unknown] if the notifications could not be discarded.
+    fn purge_old_notifications(&self) -> impl Future<Output = Result<u64, InboxError>> + Send;
+
+    /// Asynchronously find the [User]s whose [UserName] matches `query`, best match first,
+    /// leaving out erased users.
+    ///
+    /// # Errors
+    ///
+    /// - [SearchError::Unknown] if the users could not be searched.
+    fn search_users(
+        &self,
+        query: &UserSearchQuery,
+    ) -> impl Future<Output = Result<Vec<User>, SearchError>> + Send;
+
+    /// Asynchronously record `events` reported by the clients of [User]s, to be written to the
+    /// analytics store eventually.
+    ///
+    /// # Errors
+    ///
+    /// - [AnalyticsError::TooManyEvents] if more than
+    ///   [MAX_BATCH_SIZE](crate::domain::crowdsrc::models::analytics::MAX_BATCH_SIZE) events
+    ///   are given.
+    /// - [AnalyticsError::UserNotFound] if no user with the id of an event exists.
+    /// - [AnalyticsError::Overloaded] if events are recorded faster than they are written.
+    /// - [AnalyticsError::Unknown] if the events could not be recorded.
+    fn record_analytics_events(
+        &self,
+        events: Vec<AnalyticsEvent>,
+    ) -> impl Future<Output = Result<(), AnalyticsError>> + Send;
 }
 
 /// `UserRepository` represents a store of user data.
@@ -36,16 +313,313 @@ pub trait CrowdSrcService: Clone + Send + Sync + 'static {
 /// External modules must conform to this contract – the domain is not concerned with the
 /// implementation details or underlying technology of any external code.
 pub trait UserRepository: Send + Sync + Clone + 'static {
-    /// Asynchronously persist a new [User].
+    /// Asynchronously persist a new [User], together with the [TermsAcceptance] of
+    /// [CreateUserRequest::accepted_terms].
     ///
     /// # Errors
     ///
-    /// - MUST return [CreateUserError::Duplicate] if an [User] with the same [UserName]
-    ///   already exists.
+    /// - MUST return [CreateUserError::DuplicateUserName] if an [User] with the same
+    ///   [UserName] already exists, or [CreateUserError::DuplicateEmail] if one with the same
+    ///   [EmailAddress] does, including when created concurrently.
     fn create_user(
         &self,
         req: &CreateUserRequest,
     ) -> impl Future<Output = Result<User, CreateUserError>> + Send;
+
+    /// Asynchronously persist a batch of new [User]s, together with the [TermsAcceptance]s of
+    /// those created.
+    ///
+    /// # Errors
+    ///
+    /// - MUST report duplicates per item as [CreateUserOutcome::DuplicateUserName] or
+    ///   [CreateUserOutcome::DuplicateEmail], in the order of `reqs`, including duplicates
+    ///   within the batch itself.
+    /// - MUST return [CreateUsersError::Unknown] and persist nothing if the batch fails for any
+    ///   other reason.
+    fn create_users(
+        &self,
+        reqs: &[CreateUserRequest],
+    ) -> impl Future<Output = Result<Vec<CreateUserOutcome>, CreateUsersError>> + Send;
+
+    /// Stream all persisted [User]s, oldest first.
+    ///
+    /// Implementations MUST NOT buffer the whole result set.
+    ///
+    /// # Errors
+    ///
+    /// - MUST yield [ListUsersError::Unknown] if a [User] could not be read. The stream may end
+    ///   after yielding an error.
+    fn stream_users(&self) -> impl Stream<Item = Result<User, ListUsersError>> + Send;
+
+    /// Asynchronously retrieve the [User] with the given id.
+    ///
+    /// # Errors
+    ///
+    /// - MUST return [GetUserError::NotFound] if no [User] with the given id exists.
+    fn get_user(&self, id: &uuid::Uuid) -> impl Future<Output = Result<User, GetUserError>> + Send;
+
+    /// Asynchronously replace the personal data of the [User] with the given id by
+    /// [UserName::erased] and [EmailAddress::erased], returning the erased [User].
+    ///
+    /// # Errors
+    ///
+    /// - MUST return [EraseUserError::NotFound] if no [User] with the given id exists.
+    /// - MUST leave the [User] untouched if the erasure fails.
+    fn erase_user(
+        &self,
+        id: &uuid::Uuid,
+    ) -> impl Future<Output = Result<User, EraseUserError>> + Send;
+
+    /// Asynchronously record that the [User] with the given id accepted `version` now.
+    ///
+    /// # Errors
+    ///
+    /// - MUST return [AcceptTermsError::UserNotFound] if no [User] with the given id exists.
+    fn accept_terms(
+        &self,
+        user_id: &uuid::Uuid,
+        version: &TermsVersion,
+    ) -> impl Future<Output = Result<TermsAcceptance, AcceptTermsError>> + Send;
+
+    /// Asynchronously list the [TermsAcceptance]s of the [User] with the given id, oldest first.
+    ///
+    /// Returns an empty list if no [User] with the given id exists.
+    fn list_terms_acceptances(
+        &self,
+        user_id: &uuid::Uuid,
+    ) -> impl Future<Output = Result<Vec<TermsAcceptance>, GetUserError>> + Send;
+
+    /// Asynchronously persist `event` as a [DeadLetter] that failed with `failure_reason`.
+    fn save_dead_letter(
+        &self,
+        event: &NotificationEvent,
+        failure_reason: &str,
+    ) -> impl Future<Output = Result<DeadLetter, DeadLetterError>> + Send;
+
+    /// Asynchronously list all [DeadLetter]s, oldest first.
+    fn list_dead_letters(
+        &self,
+    ) -> impl Future<Output = Result<Vec<DeadLetter>, DeadLetterError>> + Send;
+
+    /// Asynchronously retrieve the [DeadLetter] with the given id.
+    ///
+    /// # Errors
+    ///
+    /// - MUST return [DeadLetterError::NotFound] if no [DeadLetter] with the given id exists.
+    fn get_dead_letter(
+        &self,
+        id: &uuid::Uuid,
+    ) -> impl Future<Output = Result<DeadLetter, DeadLetterError>> + Send;
+
+    /// Asynchronously record that redriving the [DeadLetter] with the given id failed with
+    /// `failure_reason`, incrementing its retry count.
+    ///
+    /// # Errors
+    ///
+    /// - MUST return [DeadLetterError::NotFound] if no [DeadLetter] with the given id exists.
+    fn record_dead_letter_retry(
+        &self,
+        id: &uuid::Uuid,
+        failure_reason: &str,
+    ) -> impl Future<Output = Result<DeadLetter, DeadLetterError>> + Send;
+
+    /// Asynchronously delete the [DeadLetter] with the given id.
+    ///
+    /// # Errors
+    ///
+    /// - MUST return [DeadLetterError::NotFound] if no [DeadLetter] with the given id exists.
+    fn delete_dead_letter(
+        &self,
+        id: &uuid::Uuid,
+    ) -> impl Future<Output = Result<(), DeadLetterError>> + Send;
+
+    /// Asynchronously persist `user` and its `terms_acceptances` as they are, keeping their ids
+    /// and timestamps, such as when restoring a backup.
+    ///
+    /// # Errors
+    ///
+    /// - MUST return [CreateUserError::DuplicateUserName] or [CreateUserError::DuplicateEmail]
+    ///   if another [User] with the same [UserName] or [EmailAddress] already exists.
+    /// - MUST persist nothing if the import fails.
+    fn import_user(
+        &self,
+        user: &User,
+        terms_acceptances: &[TermsAcceptance],
+    ) -> impl Future<Output = Result<(), CreateUserError>> + Send;
+
+    /// Asynchronously append `activity` to the activity feed of its [User].
+    ///
+    /// # Errors
+    ///
+    /// - MUST return [ActivityError::UserNotFound] if the [User] of `activity` doesn't exist.
+    fn record_activity(
+        &self,
+        activity: &Activity,
+    ) -> impl Future<Output = Result<(), ActivityError>> + Send;
+
+    /// Asynchronously list a page of the activity feed of the [User] with the given id, newest
+    /// first.
+    ///
+    /// Returns an empty page if no [User] with the given id exists.
+    fn list_activity(
+        &self,
+        user_id: &uuid::Uuid,
+        query: &ActivityQuery,
+    ) -> impl Future<Output = Result<ActivityPage, ActivityError>> + Send;
+
+    /// Asynchronously persist `change`, to be confirmed with `token`, replacing the pending
+    /// change of its [User], if any.
+    ///
+    /// Implementations MUST discard the change when the [User] is erased.
+    ///
+    /// # Errors
+    ///
+    /// - MUST return [EmailChangeError::UserNotFound] if the [User] of `change` doesn't exist.
+    /// - MUST return [EmailChangeError::DuplicateEmail] if another [User] has the new
+    ///   [EmailAddress].
+    fn save_email_change(
+        &self,
+        change: &PendingEmailChange,
+        token: &EmailChangeToken,
+    ) -> impl Future<Output = Result<(), EmailChangeError>> + Send;
+
+    /// Asynchronously replace the email of the [User] with the given id by that of its
+    /// [PendingEmailChange], if `token` is that of the change, and discard the change,
+    /// returning the changed [User].
+    ///
+    /// Implementations MUST NOT persist `token` in a form it can be recovered from.
+    ///
+    /// # Errors
+    ///
+    /// - MUST return [EmailChangeError::NotPending] if the [User] has no [PendingEmailChange].
+    /// - MUST return [EmailChangeError::InvalidToken] if `token` isn't that of the change.
+    /// - MUST return [EmailChangeError::Expired] and discard the change if it has expired.
+    /// - MUST return [EmailChangeError::DuplicateEmail] if another [User] has the new
+    ///   [EmailAddress].
+    /// - MUST leave the [User] untouched if the change fails.
+    fn confirm_email_change(
+        &self,
+        user_id: &uuid::Uuid,
+        token: &EmailChangeToken,
+    ) -> impl Future<Output = Result<User, EmailChangeError>> + Send;
+
+    /// Stream all [PendingEmailChange]s, expired or not, with the hashes of their tokens, such
+    /// as when taking a backup.
+    fn stream_email_changes(
+        &self,
+    ) -> impl Stream<Item = Result<(PendingEmailChange, EmailChangeTokenHash), EmailChangeError>> + Send;
+
+    /// Asynchronously persist `change` as it is, to be confirmed with the token hashed as
+    /// `token_hash`, replacing the pending change of its [User], if any, such as when restoring
+    /// a backup.
+    ///
+    /// # Errors
+    ///
+    /// - MUST return [EmailChangeError::UserNotFound] if the [User] of `change` doesn't exist.
+    fn import_email_change(
+        &self,
+        change: &PendingEmailChange,
+        token_hash: &EmailChangeTokenHash,
+    ) -> impl Future<Output = Result<(), EmailChangeError>> + Send;
+
+    /// Asynchronously discard the [PendingEmailChange] of the [User] with the given id.
+    ///
+    /// # Errors
+    ///
+    /// - MUST return [EmailChangeError::NotPending] if the [User] has no [PendingEmailChange].
+    fn delete_email_change(
+        &self,
+        user_id: &uuid::Uuid,
+    ) -> impl Future<Output = Result<(), EmailChangeError>> + Send;
+
+    /// Asynchronously discard all [PendingEmailChange]s that expired at or before `now`,
+    /// returning how many were discarded.
+    ///
+    /// # Errors
+    ///
+    /// - MUST return [EmailChangeError::Unknown] if the changes could not be discarded.
+    fn delete_expired_email_changes(
+        &self,
+        now: &DateTime<Utc>,
+    ) -> impl Future<Output = Result<u64, EmailChangeError>> + Send;
+
+    /// Asynchronously persist `notification` in the inbox of its [User].
+    ///
+    /// Implementations MUST discard the notifications when the [User] is erased.
+    ///
+    /// # Errors
+    ///
+    /// - MUST return [InboxError::UserNotFound] if the [User] of `notification` doesn't exist.
+    fn save_notification(
+        &self,
+        notification: &InboxNotification,
+    ) -> impl Future<Output = Result<(), InboxError>> + Send;
+
+    /// Stream the [InboxNotification]s of all [User]s, oldest first, such as when taking a
+    /// backup.
+    fn stream_notifications(
+        &self,
+    ) -> impl Stream<Item = Result<InboxNotification, InboxError>> + Send;
+
+    /// Asynchronously get the newest [InboxNotification]s of the [User] with the given id
+    /// matching `query`, newest first.
+    ///
+    /// # Errors
+    ///
+    /// - MUST return [InboxError::Unknown] if the notifications could not be read.
+    fn list_notifications(
+        &self,
+        user_id: &uuid::Uuid,
+        query: &InboxQuery,
+    ) -> impl Future<Output = Result<Vec<InboxNotification>, InboxError>> + Send;
+
+    /// Asynchronously count the unread [InboxNotification]s of the [User] with the given id.
+    ///
+    /// # Errors
+    ///
+    /// - MUST return [InboxError::Unknown] if the notifications could not be counted.
+    fn count_unread_notifications(
+        &self,
+        user_id: &uuid::Uuid,
+    ) -> impl Future<Output = Result<u64, InboxError>> + Send;
+
+    /// Asynchronously mark the [InboxNotification] with `notification_id` of the [User] with
+    /// the given id, or all of their notifications if `None`, as read at `read_at`, returning
+    /// how many unread notifications were marked.
+    ///
+    /// # Errors
+    ///
+    /// - MUST return [InboxError::NotFound] if the [User] has no notification with
+    ///   `notification_id`.
+    /// - MUST keep when notifications already read were read.
+    fn mark_notifications_read(
+        &self,
+        user_id: &uuid::Uuid,
+        notification_id: Option<&uuid::Uuid>,
+        read_at: &DateTime<Utc>,
+    ) -> impl Future<Output = Result<u64, InboxError>> + Send;
+
+    /// Asynchronously discard all [InboxNotification]s created before `cutoff`, returning how
+    /// many were discarded.
+    ///
+    /// # Errors
+    ///
+    /// - MUST return [InboxError::Unknown] if the notifications could not be discarded.
+    fn delete_notifications_before(
+        &self,
+        cutoff: &DateTime<Utc>,
+    ) -> impl Future<Output = Result<u64, InboxError>> + Send;
+
+    /// Asynchronously find the [User]s whose [UserName] contains the text of `query`, ignoring
+    /// case, leaving out erased users.
+    ///
+    /// # Errors
+    ///
+    /// - MUST return [SearchError::Unknown] if the users could not be searched.
+    fn search_users(
+        &self,
+        query: &UserSearchQuery,
+    ) -> impl Future<Output = Result<Vec<User>, SearchError>> + Send;
 }
 
 /// `UserNotifier` triggers notifications to users.
@@ -59,5 +633,298 @@ pub trait UserRepository: Send + Sync + Clone + 'static {
 /// For others, code coordinating notifications will be complex enough to warrant its own domain.
 /// In this case, an `UserNotifier` adapter will call that domain's `Service`.
 pub trait UserNotifier: Send + Sync + Clone + 'static {
-    fn user_created(&self, user: &User) -> impl Future<Output = ()> + Send;
+    /// Notify that `user` was created.
+    ///
+    /// # Errors
+    ///
+    /// - MUST return [NotifyUserError::Unknown] if the notification could not be delivered.
+    fn user_created(&self, user: &User)
+    -> impl Future<Output = Result<(), NotifyUserError>> + Send;
+
+    /// Notify that `user` requested `change`, sending `token` to the new address, and only
+    /// there, and a notice to the current one.
+    ///
+    /// # Errors
+    ///
+    /// - MUST return [NotifyUserError::Unknown] if either notification could not be delivered.
+    fn email_change_requested(
+        &self,
+        user: &User,
+        change: &PendingEmailChange,
+        token: &EmailChangeToken,
+    ) -> impl Future<Output = Result<(), NotifyUserError>> + Send;
+}
+
+/// `TransactionManager` begins [Transaction]s that group several repository calls, so that an
+/// operation writing several times is atomic as a whole.
+///
+/// External modules must conform to this contract – the domain is not concerned with the
+/// implementation details or underlying technology of any external code.
+pub trait TransactionManager: Send + Sync + Clone + 'static {
+    type Transaction: Transaction;
+
+    /// Asynchronously begin a new [Transaction].
+    fn begin(&self) -> impl Future<Output = anyhow::Result<Self::Transaction>> + Send;
+}
+
+/// `Transaction` is a transaction begun by a [TransactionManager].
+///
+/// Clones share the same transaction. Implementations MUST roll back a transaction that was
+/// dropped without being committed or rolled back.
+pub trait Transaction: Send + Sync + Clone + 'static {
+    /// Asynchronously run `f`, with all repository calls made within it taking part in this
+    /// transaction.
+    fn scope<F>(&self, f: F) -> impl Future<Output = F::Output> + Send
+    where
+        F: Future + Send,
+        F::Output: Send;
+
+    /// Asynchronously commit this transaction.
+    fn commit(self) -> impl Future<Output = anyhow::Result<()>> + Send;
+
+    /// Asynchronously roll back this transaction.
+    fn rollback(self) -> impl Future<Output = anyhow::Result<()>> + Send;
+}
+
+/// `AbuseChallenge` verifies that unauthenticated submissions come from humans, e.g. by a CAPTCHA
+/// or a proof of work.
+///
+/// External modules must conform to this contract – the domain is not concerned with the
+/// implementation details or underlying technology of any external code.
+pub trait AbuseChallenge: Send + Sync + Clone + 'static {
+    /// Asynchronously issue a [Challenge] for a client to solve.
+    fn issue(&self) -> impl Future<Output = Result<Challenge, AbuseChallengeError>> + Send;
+
+    /// Asynchronously verify the `solution` to a [Challenge], given by a client at `remote_ip`.
+    ///
+    /// # Errors
+    ///
+    /// - MUST return [AbuseChallengeError::Rejected] if `solution` does not solve a [Challenge]
+    ///   issued by this [AbuseChallenge].
+    fn verify(
+        &self,
+        solution: &str,
+        remote_ip: Option<IpAddr>,
+    ) -> impl Future<Output = Result<(), AbuseChallengeError>> + Send;
+}
+
+/// `SubmissionThrottle` limits how many submissions, such as signups, may originate from the
+/// same [SubmissionSource], to make creating many accounts from one machine harder.
+///
+/// External modules must conform to this contract – the domain is not concerned with the
+/// implementation details or underlying technology of any external code.
+pub trait SubmissionThrottle: Send + Sync + Clone + 'static {
+    /// Asynchronously record a submission from `source`.
+    ///
+    /// # Errors
+    ///
+    /// - MUST return [ThrottleError::TooManySubmissions] and not record the submission if
+    ///   `source` has used up its allowance.
+    fn record(
+        &self,
+        source: &SubmissionSource,
+    ) -> impl Future<Output = Result<(), ThrottleError>> + Send;
+}
+
+/// `FeatureFlags` tells which features are switched on, globally or for a tenant.
+///
+/// External modules must conform to this contract – the domain is not concerned with the
+/// implementation details or underlying technology of any external code.
+pub trait FeatureFlags: Send + Sync + Clone + 'static {
+    /// Asynchronously list the [FeatureFlag]s switched on for `tenant`, or globally if `None`,
+    /// in alphabetical order.
+    ///
+    /// Implementations MUST treat flags they can't determine as switched off.
+    fn enabled(&self, tenant: Option<&str>) -> impl Future<Output = Vec<FeatureFlag>> + Send;
+
+    /// Asynchronously tell whether `flag` is switched on for `tenant`, or globally if `None`.
+    fn is_enabled(
+        &self,
+        flag: &FeatureFlag,
+        tenant: Option<&str>,
+    ) -> impl Future<Output = bool> + Send {
+        async move { self.enabled(tenant).await.contains(flag) }
+    }
+}
+
+/// `MaintenanceSwitch` persists whether the application is in maintenance mode, during which
+/// requests that would write are refused.
+///
+/// External modules must conform to this contract – the domain is not concerned with the
+/// implementation details or underlying technology of any external code.
+pub trait MaintenanceSwitch: Send + Sync + Clone + 'static {
+    /// Asynchronously tell whether maintenance mode is switched on.
+    ///
+    /// # Errors
+    ///
+    /// - [MaintenanceError::Unknown] if the switch could not be read.
+    fn is_enabled(&self) -> impl Future<Output = Result<bool, MaintenanceError>> + Send;
+
+    /// Asynchronously switch maintenance mode on or off.
+    ///
+    /// # Errors
+    ///
+    /// - [MaintenanceError::Unknown] if the switch could not be written.
+    fn set_enabled(
+        &self,
+        enabled: bool,
+    ) -> impl Future<Output = Result<(), MaintenanceError>> + Send;
+}
+
+/// `RuntimeConfigStore` persists the [RuntimeConfig] admins tune while the application runs.
+///
+/// External modules must conform to this contract – the domain is not concerned with the
+/// implementation details or underlying technology of any external code.
+pub trait RuntimeConfigStore: Send + Sync + Clone + 'static {
+    /// Asynchronously retrieve the persisted [RuntimeConfig], or the default if none was saved.
+    ///
+    /// # Errors
+    ///
+    /// - [RuntimeConfigError::Unknown] if the [RuntimeConfig] could not be read.
+    fn load(&self) -> impl Future<Output = Result<RuntimeConfig, RuntimeConfigError>> + Send;
+
+    /// Asynchronously replace the persisted [RuntimeConfig] by `config`.
+    ///
+    /// # Errors
+    ///
+    /// - [RuntimeConfigError::Unknown] if `config` could not be written, leaving the persisted
+    ///   [RuntimeConfig] untouched.
+    fn save(
+        &self,
+        config: &RuntimeConfig,
+    ) -> impl Future<Output = Result<(), RuntimeConfigError>> + Send;
+}
+
+/// `ScheduleStore` persists the [Schedule]s of jobs, shared by all instances, so that each run
+/// of a job happens on one instance only.
+///
+/// External modules must conform to this contract – the domain is not concerned with the
+/// implementation details or underlying technology of any external code.
+pub trait ScheduleStore: Send + Sync + Clone + 'static {
+    /// Asynchronously persist that the job named `name` runs on `cron`, returning its
+    /// [Schedule].
+    ///
+    /// A job registered with the same [CronExpression] as before keeps its next run, so that
+    /// restarts don't skip or repeat runs. Otherwise, the job next runs when `cron` matches
+    /// after `now`.
+    ///
+    /// # Errors
+    ///
+    /// - MUST return [ScheduleError::Unknown] if the [Schedule] could not be written.
+    fn register(
+        &self,
+        name: &str,
+        cron: &CronExpression,
+        now: DateTime<Utc>,
+    ) -> impl Future<Output = Result<Schedule, ScheduleError>> + Send;
+
+    /// Asynchronously claim the run of the job named `name` that is due at `now`, if any,
+    /// moving its next run to when its [CronExpression] matches after `now`.
+    ///
+    /// Returns whether the run was claimed. Of all instances claiming the same run, only one
+    /// MUST succeed.
+    ///
+    /// # Errors
+    ///
+    /// - MUST return [ScheduleError::NotFound] if no job named `name` is registered.
+    fn claim(
+        &self,
+        name: &str,
+        now: DateTime<Utc>,
+    ) -> impl Future<Output = Result<bool, ScheduleError>> + Send;
+
+    /// Asynchronously retrieve all [Schedule]s, by name.
+    ///
+    /// # Errors
+    ///
+    /// - MUST return [ScheduleError::Unknown] if the [Schedule]s could not be read.
+    fn list(&self) -> impl Future<Output = Result<Vec<Schedule>, ScheduleError>> + Send;
+}
+
+/// `DistributedLock` lets one instance at a time, of all those sharing it, do the work guarded
+/// by a named lock, such as running a job or migrating the database.
+///
+/// External modules must conform to this contract – the domain is not concerned with the
+/// implementation details or underlying technology of any external code.
+pub trait DistributedLock: Send + Sync + Clone + 'static {
+    /// Asynchronously run `work` while holding the lock named `name`, first waiting for any
+    /// other instance holding it to release it.
+    ///
+    /// The lock MUST be released once `work` completes, or is dropped.
+    ///
+    /// # Errors
+    ///
+    /// - MUST return [LockError::Unknown] if the lock could not be taken, without running
+    ///   `work`.
+    fn with_lock<T, W>(
+        &self,
+        name: &str,
+        work: W,
+    ) -> impl Future<Output = Result<T, LockError>> + Send
+    where
+        T: Send,
+        W: Future<Output = T> + Send;
+
+    /// Asynchronously run `work` while holding the lock named `name`, unless another instance
+    /// holds it, returning [None] right away without running `work`.
+    ///
+    /// The lock MUST be released once `work` completes, or is dropped.
+    ///
+    /// # Errors
+    ///
+    /// - MUST return [LockError::Unknown] if the lock could not be taken, without running
+    ///   `work`.
+    fn try_with_lock<T, W>(
+        &self,
+        name: &str,
+        work: W,
+    ) -> impl Future<Output = Result<Option<T>, LockError>> + Send
+    where
+        T: Send,
+        W: Future<Output = T> + Send;
+}
+
+/// `SearchIndex` is a search engine [User]s are indexed in, to be searched faster and with
+/// better relevance, such as tolerating typos, than by the [UserRepository].
+///
+/// The index is kept in sync with the [UserRepository] as users are created and erased, and
+/// lags behind it, so hits may be stale and recent changes missing for a while.
+///
+/// External modules must conform to this contract – the domain is not concerned with the
+/// implementation details or underlying technology of any external code.
+pub trait SearchIndex: Send + Sync + Clone + 'static {
+    /// Asynchronously add `users` to the index, replacing those indexed before.
+    ///
+    /// Implementations MUST NOT index personal data other than the [UserName].
+    fn index_users(&self, users: &[User]) -> impl Future<Output = Result<(), SearchError>> + Send;
+
+    /// Asynchronously remove the [User] with the given id from the index, if indexed.
+    fn remove_user(&self, id: &uuid::Uuid) -> impl Future<Output = Result<(), SearchError>> + Send;
+
+    /// Asynchronously remove all [User]s from the index.
+    fn clear(&self) -> impl Future<Output = Result<(), SearchError>> + Send;
+
+    /// Asynchronously find the ids of the indexed [User]s matching `query`, best match first.
+    fn search_users(
+        &self,
+        query: &UserSearchQuery,
+    ) -> impl Future<Output = Result<Vec<uuid::Uuid>, SearchError>> + Send;
+}
+
+/// `AnalyticsSink` represents a store of [AnalyticsEvent]s researchers query, such as a
+/// partitioned table or a column store.
+///
+/// External modules must conform to this contract – the domain is not concerned with the
+/// implementation details or underlying technology of any external code.
+pub trait AnalyticsSink: Send + Sync + Clone + 'static {
+    /// Asynchronously write `events` to the store.
+    ///
+    /// # Errors
+    ///
+    /// - MUST return [AnalyticsError::Unknown] if the events could not be written, none or only
+    ///   some of them being written.
+    fn write_events(
+        &self,
+        events: &[AnalyticsEvent],
+    ) -> impl Future<Output = Result<(), AnalyticsError>> + Send;
 }
@@ -0,0 +1,302 @@
+/*!
+   Module `testing` provides contract test suites for the ports, so that every implementation,
+   including new backends and decorators, can prove that it satisfies the contract documented on
+   its port.
+
+   A suite panics on the first violation, so it is run from a test of the implementation:
+
+   ```ignore
+   #[tokio::test]
+   async fn my_repository_satisfies_contract() {
+       user_repository_contract(async || MyRepository::new()).await;
+   }
+   ```
+*/
+
+use chrono::{TimeDelta, Utc};
+use futures::{TryStreamExt, future::join_all};
+
+use crate::domain::crowdsrc::{
+    models::{
+        email_change::{EmailChangeError, EmailChangeToken, PendingEmailChange},
+        terms::{AcceptTermsError, TermsVersion},
+        user::{
+            CreateUserError, CreateUserOutcome, CreateUserRequest, EmailAddress, EraseUserError,
+            GetUserError, User, UserName,
+        },
+    },
+    ports::UserRepository,
+};
+
+/// The terms version the users of the suites accept on creation.
+const TERMS_VERSION: &str = "2026-01-30";
+
+/// How far timestamps may drift when persisted, as stores may keep them less precisely.
+const TIMESTAMP_PRECISION: TimeDelta = TimeDelta::milliseconds(1);
+
+/// Runs the contract test suite of [UserRepository] on users, terms acceptances and erasure,
+/// each check against a new, empty repository made by `new_repository`.
+///
+/// # Panics
+///
+/// If a repository violates the contract.
+pub async fn user_repository_contract<R, F>(new_repository: F)
+where
+    R: UserRepository,
+    F: AsyncFn() -> R,
+{
+    created_user_round_trips(&new_repository().await).await;
+    unknown_user_is_not_found(&new_repository().await).await;
+    duplicate_users_are_refused(&new_repository().await).await;
+    batch_reports_duplicates_per_item(&new_repository().await).await;
+    concurrent_duplicates_create_one_user(&new_repository().await).await;
+    users_are_streamed_oldest_first(&new_repository().await).await;
+    terms_acceptances_round_trip(&new_repository().await).await;
+    erasure_replaces_personal_data(&new_repository().await).await;
+}
+
+fn create_user_request(username: &str, email: &str) -> CreateUserRequest {
+    CreateUserRequest::new(
+        UserName::new(username).unwrap(),
+        EmailAddress::new(email).unwrap(),
+        TermsVersion::new(TERMS_VERSION).unwrap(),
+    )
+}
+
+async fn create_user<R: UserRepository>(repo: &R, username: &str) -> User {
+    repo.create_user(&create_user_request(
+        username,
+        &format!("{username}@example.com"),
+    ))
+    .await
+    .unwrap_or_else(|err| panic!("failed to create user {username}: {err:?}"))
+}
+
+async fn usernames<R: UserRepository>(repo: &R) -> Vec<String> {
+    let users: Vec<User> = repo
+        .stream_users()
+        .try_collect()
+        .await
+        .expect("failed to stream users");
+    users
+        .iter()
+        .map(|user| user.username().to_string())
+        .collect()
+}
+
+async fn created_user_round_trips<R: UserRepository>(repo: &R) {
+    let created = create_user(repo, "alice").await;
+
+    let got = repo
+        .get_user(created.id())
+        .await
+        .expect("created user not found");
+
+    assert_eq!(got.id(), created.id());
+    assert_eq!(got.username(), created.username());
+    assert_eq!(got.email(), created.email());
+    assert!(
+        (*got.created_at() - *created.created_at()).abs() < TIMESTAMP_PRECISION,
+        "created_at changed from {} to {}",
+        created.created_at(),
+        got.created_at()
+    );
+    assert!(!got.is_erased());
+    assert!(
+        !got.is_email_verified(),
+        "the email of new users must not be verified"
+    );
+    let acceptances = repo.list_terms_acceptances(created.id()).await.unwrap();
+    assert_eq!(
+        acceptances.len(),
+        1,
+        "the terms accepted on creation must be recorded"
+    );
+    assert_eq!(acceptances[0].user_id(), created.id());
+    assert_eq!(acceptances[0].terms_version().to_string(), TERMS_VERSION);
+}
+
+async fn unknown_user_is_not_found<R: UserRepository>(repo: &R) {
+    let id = uuid::Uuid::new_v4();
+    let version = TermsVersion::new(TERMS_VERSION).unwrap();
+
+    let got = repo.get_user(&id).await;
+    let erased = repo.erase_user(&id).await;
+    let accepted = repo.accept_terms(&id, &version).await;
+    let acceptances = repo.list_terms_acceptances(&id).await;
+
+    assert!(
+        matches!(got, Err(GetUserError::NotFound { id: found }) if found == id),
+        "expected get_user to return NotFound, got {got:?}"
+    );
+    assert!(
+        matches!(erased, Err(EraseUserError::NotFound { .. })),
+        "expected erase_user to return NotFound, got {erased:?}"
+    );
+    assert!(
+        matches!(accepted, Err(AcceptTermsError::UserNotFound { .. })),
+        "expected accept_terms to return UserNotFound, got {accepted:?}"
+    );
+    assert!(
+        acceptances.as_ref().is_ok_and(Vec::is_empty),
+        "expected no terms acceptances, got {acceptances:?}"
+    );
+}
+
+async fn duplicate_users_are_refused<R: UserRepository>(repo: &R) {
+    create_user(repo, "alice").await;
+
+    let same_username = repo
+        .create_user(&create_user_request("alice", "other@example.com"))
+        .await;
+    let same_email = repo
+        .create_user(&create_user_request("other", "alice@example.com"))
+        .await;
+
+    assert!(
+        matches!(&same_username, Err(CreateUserError::DuplicateUserName { username }) if username.to_string() == "alice"),
+        "expected DuplicateUserName, got {same_username:?}"
+    );
+    assert!(
+        matches!(&same_email, Err(CreateUserError::DuplicateEmail { email }) if email.as_str() == "alice@example.com"),
+        "expected DuplicateEmail, got {same_email:?}"
+    );
+    assert_eq!(
+        usernames(repo).await,
+        ["alice"],
+        "refused users must not be persisted"
+    );
+}
+
+async fn batch_reports_duplicates_per_item<R: UserRepository>(repo: &R) {
+    create_user(repo, "alice").await;
+    let reqs = [
+        create_user_request("bob", "alice@example.com"),
+        create_user_request("alice", "bob@example.com"),
+        create_user_request("carol", "carol@example.com"),
+        create_user_request("dave", "carol@example.com"),
+        create_user_request("carol", "dave@example.com"),
+    ];
+
+    let outcomes = repo.create_users(&reqs).await.expect("batch failed");
+
+    assert_eq!(
+        outcomes.len(),
+        reqs.len(),
+        "expected an outcome per request"
+    );
+    assert!(
+        matches!(&outcomes[0], CreateUserOutcome::DuplicateEmail { .. }),
+        "expected duplicate email, got {:?}",
+        outcomes[0]
+    );
+    assert!(
+        matches!(&outcomes[1], CreateUserOutcome::DuplicateUserName { .. }),
+        "expected duplicate username, got {:?}",
+        outcomes[1]
+    );
+    assert!(outcomes[2].is_created(), "got {:?}", outcomes[2]);
+    assert!(
+        matches!(&outcomes[3], CreateUserOutcome::DuplicateEmail { .. }),
+        "expected duplicate email within batch, got {:?}",
+        outcomes[3]
+    );
+    assert!(
+        matches!(&outcomes[4], CreateUserOutcome::DuplicateUserName { .. }),
+        "expected duplicate username within batch, got {:?}",
+        outcomes[4]
+    );
+    assert_eq!(usernames(repo).await, ["alice", "carol"]);
+    let CreateUserOutcome::Created(carol) = &outcomes[2] else {
+        unreachable!()
+    };
+    assert_eq!(
+        repo.list_terms_acceptances(carol.id()).await.unwrap().len(),
+        1,
+        "the terms accepted by users created in a batch must be recorded"
+    );
+}
+
+async fn concurrent_duplicates_create_one_user<R: UserRepository>(repo: &R) {
+    let reqs: Vec<CreateUserRequest> = (0..8)
+        .map(|i| create_user_request("alice", &format!("alice{i}@example.com")))
+        .collect();
+
+    let results = join_all(reqs.iter().map(|req| repo.create_user(req))).await;
+
+    let created = results.iter().filter(|result| result.is_ok()).count();
+    assert_eq!(
+        created, 1,
+        "expected a single user created, got {results:?}"
+    );
+    assert!(
+        results.iter().all(|result| matches!(
+            result,
+            Ok(_) | Err(CreateUserError::DuplicateUserName { .. })
+        )),
+        "expected the others to be duplicates, got {results:?}"
+    );
+    assert_eq!(usernames(repo).await, ["alice"]);
+}
+
+async fn users_are_streamed_oldest_first<R: UserRepository>(repo: &R) {
+    for username in ["carol", "alice", "bob"] {
+        create_user(repo, username).await;
+    }
+
+    assert_eq!(usernames(repo).await, ["carol", "alice", "bob"]);
+}
+
+async fn terms_acceptances_round_trip<R: UserRepository>(repo: &R) {
+    let user = create_user(repo, "alice").await;
+    let version = TermsVersion::new("2026-06-01").unwrap();
+
+    let accepted = repo
+        .accept_terms(user.id(), &version)
+        .await
+        .expect("failed to accept terms");
+
+    assert_eq!(accepted.user_id(), user.id());
+    assert_eq!(accepted.terms_version(), &version);
+    let versions: Vec<String> = repo
+        .list_terms_acceptances(user.id())
+        .await
+        .unwrap()
+        .iter()
+        .map(|acceptance| acceptance.terms_version().to_string())
+        .collect();
+    assert_eq!(versions, [TERMS_VERSION, "2026-06-01"]);
+}
+
+async fn erasure_replaces_personal_data<R: UserRepository>(repo: &R) {
+    let alice = create_user(repo, "alice").await;
+    let bob = create_user(repo, "bob").await;
+    let requested_at = Utc::now();
+    let change = PendingEmailChange::new(
+        *alice.id(),
+        EmailAddress::new("alice@example.org").unwrap(),
+        requested_at,
+        requested_at + TimeDelta::hours(1),
+    );
+    repo.save_email_change(&change, &EmailChangeToken::generate())
+        .await
+        .expect("failed to save email change");
+
+    let erased = repo
+        .erase_user(alice.id())
+        .await
+        .expect("failed to erase user");
+
+    assert!(erased.is_erased());
+    let got = repo.get_user(alice.id()).await.unwrap();
+    assert_eq!(got.username(), &UserName::erased(alice.id()));
+    assert_eq!(got.email(), &EmailAddress::erased(alice.id()));
+    let pending = repo.delete_email_change(alice.id()).await;
+    assert!(
+        matches!(pending, Err(EmailChangeError::NotPending { .. })),
+        "the pending email change must be discarded on erasure, got {pending:?}"
+    );
+    let untouched = repo.get_user(bob.id()).await.unwrap();
+    assert_eq!(untouched.username(), bob.username());
+    assert_eq!(untouched.email(), bob.email());
+}
@@ -3,8 +3,34 @@
    crowdsrc-domain logic is defined here.
 */
 
-use crate::domain::crowdsrc::models::user::CreateUserError;
-use crate::domain::crowdsrc::models::user::{CreateUserRequest, User};
+use std::collections::BTreeSet;
+
+use chrono::Utc;
+use futures::Stream;
+
+use crate::domain::crowdsrc::after_commit;
+use crate::domain::crowdsrc::analytics_buffer::AnalyticsBuffer;
+use crate::domain::crowdsrc::models::activity::{
+    Activity, ActivityError, ActivityKind, ActivityPage, ActivityQuery,
+};
+use crate::domain::crowdsrc::models::analytics::{AnalyticsError, AnalyticsEvent, MAX_BATCH_SIZE};
+use crate::domain::crowdsrc::models::dead_letter::{
+    DeadLetter, DeadLetterError, NotificationEvent, RedriveOutcome,
+};
+use crate::domain::crowdsrc::models::email_change::{
+    EMAIL_CHANGE_TTL, EmailChangeError, EmailChangeToken, PendingEmailChange,
+};
+use crate::domain::crowdsrc::models::inbox::{
+    Inbox, InboxError, InboxKind, InboxNotification, InboxQuery, NOTIFICATION_RETENTION,
+};
+use crate::domain::crowdsrc::models::redacted::Redacted;
+use crate::domain::crowdsrc::models::search::{SearchError, UserSearchQuery};
+use crate::domain::crowdsrc::models::terms::{AcceptTermsError, TermsAcceptance, TermsVersion};
+use crate::domain::crowdsrc::models::user::{CreateUserError, CreateUsersError, ListUsersError};
+use crate::domain::crowdsrc::models::user::{
+    CreateUserOutcome, CreateUserRequest, EmailAddress, User,
+};
+use crate::domain::crowdsrc::models::user::{EraseUserError, GetUserError, UserDataExport};
 use crate::domain::crowdsrc::ports::{CrowdSrcService, UserNotifier, UserRepository};
 
 /// Canonical implementation of the [CrowdSrcService] port, through which the crowdsrc domain API is
@@ -17,6 +43,8 @@ where
 {
     user_repo: R,
     user_notifier: N,
+    current_terms: TermsVersion,
+    analytics: Option<AnalyticsBuffer>,
 }
 
 impl<R, N> Service<R, N>
@@ -24,10 +52,123 @@ where
     R: UserRepository,
     N: UserNotifier,
 {
-    pub fn new(user_repo: R, user_notifier: N) -> Self {
+    /// Creates the service, requiring new users to accept `current_terms`.
+    pub fn new(user_repo: R, user_notifier: N, current_terms: TermsVersion) -> Self {
         Self {
             user_repo,
             user_notifier,
+            current_terms,
+            analytics: None,
+        }
+    }
+
+    /// Records analytics events into `buffer`. Without a buffer, they are dropped.
+    pub fn with_analytics(self, buffer: AnalyticsBuffer) -> Self {
+        Self {
+            analytics: Some(buffer),
+            ..self
+        }
+    }
+
+    fn is_current_terms(&self, version: &TermsVersion) -> bool {
+        *version == self.current_terms
+    }
+
+    /// Notify that `user` was created, once the creation is committed. A failed notification
+    /// doesn't undo the creation, so the event is saved as a [DeadLetter] to be redriven later.
+    async fn notify_user_created(&self, user: &User) {
+        let (service, user) = (self.clone(), user.clone());
+        after_commit::defer(async move {
+            if let Err(err) = service.user_notifier.user_created(&user).await {
+                tracing::warn!("failed to notify creation of user {}: {:?}", user.id(), err);
+                let event = NotificationEvent::UserCreated {
+                    user_id: *user.id(),
+                };
+                service
+                    .save_dead_letter(&event, &format!("{:#}", err))
+                    .await;
+            }
+        })
+        .await;
+    }
+
+    /// Send `token` to confirm `change` by, once the change is committed. Without the token, the
+    /// change can't be confirmed, so it is discarded if the token can't be sent, leaving the
+    /// [User] free to request it again.
+    async fn send_email_change_token(
+        &self,
+        user: &User,
+        change: &PendingEmailChange,
+        token: &EmailChangeToken,
+    ) {
+        let (service, user, change, token) =
+            (self.clone(), user.clone(), change.clone(), token.clone());
+        after_commit::defer(async move {
+            let Err(err) = service
+                .user_notifier
+                .email_change_requested(&user, &change, &token)
+                .await
+            else {
+                return;
+            };
+            tracing::error!(
+                "failed to send email change token to user {}, discarding the change: {:?}",
+                user.id(),
+                err
+            );
+            if let Err(err) = service.user_repo.delete_email_change(user.id()).await {
+                tracing::error!(
+                    "failed to discard email change of user {}: {:?}",
+                    user.id(),
+                    err
+                );
+            }
+        })
+        .await;
+    }
+
+    /// Append `activity` to the activity feed. The feed is a read model, so failing to record
+    /// it doesn't fail the operation it describes.
+    async fn record_activity(&self, activity: Activity) {
+        if let Err(err) = self.user_repo.record_activity(&activity).await {
+            tracing::warn!("failed to record activity {:?}: {:?}", activity, err);
+        }
+    }
+
+    /// Put a notification of `kind` in the inbox of the [User] with the given id. The inbox
+    /// only mirrors what happened, so failing to fill it doesn't fail the operation.
+    async fn add_to_inbox(&self, user_id: &uuid::Uuid, kind: InboxKind) {
+        let notification =
+            InboxNotification::new(uuid::Uuid::new_v4(), *user_id, kind, Utc::now(), None);
+        if let Err(err) = self.user_repo.save_notification(&notification).await {
+            tracing::warn!("failed to add {:?} to inbox: {:?}", notification, err);
+        }
+    }
+
+    async fn save_dead_letter(&self, event: &NotificationEvent, failure_reason: &str) {
+        match self.user_repo.save_dead_letter(event, failure_reason).await {
+            Ok(dead_letter) => {
+                tracing::info!(
+                    "saved undelivered {:?} as dead letter {}",
+                    event,
+                    dead_letter.id()
+                )
+            }
+            Err(err) => tracing::error!("failed to save dead letter for {:?}: {:?}", event, err),
+        }
+    }
+
+    /// Fails with the error `not_found` makes of the id unless the [User] with the given id
+    /// exists.
+    async fn ensure_user_exists<E: From<anyhow::Error>>(
+        &self,
+        user_id: &uuid::Uuid,
+        not_found: impl FnOnce(uuid::Uuid) -> E,
+    ) -> Result<(), E> {
+        match self.user_repo.get_user(user_id).await {
+            Ok(_) => Ok(()),
+            Err(GetUserError::NotFound { id }) => Err(not_found(id)),
+            Err(GetUserError::Unknown(cause)) => Err(cause.into()),
         }
     }
 }
@@ -41,13 +182,499 @@ where
     ///
     /// # Errors
     ///
+    /// - [CreateUserError::StaleTermsVersion] if the accepted [TermsVersion] isn't the current
+    ///   one.
     /// - Propagates any [CreateUserError] returned by the [UserRepository].
+    #[tracing::instrument(
+        skip_all,
+        fields(username = %req.username(), email = %Redacted(req.email()), user_id)
+    )]
     async fn create_user(&self, req: &CreateUserRequest) -> Result<User, CreateUserError> {
+        if !self.is_current_terms(req.accepted_terms()) {
+            tracing::info!(outcome = "stale_terms_version");
+            return Err(CreateUserError::StaleTermsVersion {
+                accepted: req.accepted_terms().clone(),
+                current: self.current_terms.clone(),
+            });
+        }
         let result = self.user_repo.create_user(req).await;
-        if let Ok(user) = result.as_ref() {
-            self.user_notifier.user_created(user).await;
+        match &result {
+            Ok(user) => {
+                tracing::Span::current().record("user_id", tracing::field::display(user.id()));
+                tracing::info!(outcome = "created");
+                self.record_activity(signed_up(user)).await;
+                self.add_to_inbox(user.id(), InboxKind::Welcome).await;
+                self.notify_user_created(user).await;
+            }
+            Err(CreateUserError::DuplicateUserName { .. }) => {
+                tracing::info!(outcome = "duplicate_username")
+            }
+            Err(CreateUserError::DuplicateEmail { .. }) => {
+                tracing::info!(outcome = "duplicate_email")
+            }
+            Err(CreateUserError::StaleTermsVersion { .. }) => {
+                tracing::info!(outcome = "stale_terms_version")
+            }
+            Err(CreateUserError::Unknown(_)) => tracing::warn!(outcome = "failed"),
         }
 
         result
     }
+
+    /// Create the [User]s specified in `reqs` and trigger notifications for those created.
+    ///
+    /// # Errors
+    ///
+    /// - Propagates any [CreateUsersError] returned by the [UserRepository].
+    #[tracing::instrument(skip_all, fields(count = reqs.len()))]
+    async fn create_users(
+        &self,
+        reqs: &[CreateUserRequest],
+    ) -> Result<Vec<CreateUserOutcome>, CreateUsersError> {
+        let outcomes = self
+            .user_repo
+            .create_users(reqs)
+            .await
+            .inspect_err(|_| tracing::warn!(outcome = "failed"))?;
+        let mut created = 0;
+        for outcome in &outcomes {
+            if let CreateUserOutcome::Created(user) = outcome {
+                created += 1;
+                self.record_activity(signed_up(user)).await;
+                self.add_to_inbox(user.id(), InboxKind::Welcome).await;
+                self.notify_user_created(user).await;
+            }
+        }
+        tracing::info!(
+            outcome = "created",
+            created,
+            duplicates = outcomes.len() - created
+        );
+
+        Ok(outcomes)
+    }
+
+    /// Stream all [User]s from the [UserRepository].
+    fn stream_users(&self) -> impl Stream<Item = Result<User, ListUsersError>> + Send {
+        self.user_repo.stream_users()
+    }
+
+    /// Retrieve the [User] from the [UserRepository].
+    ///
+    /// # Errors
+    ///
+    /// - Propagates any [GetUserError] returned by the [UserRepository].
+    async fn get_user(&self, id: &uuid::Uuid) -> Result<User, GetUserError> {
+        self.user_repo.get_user(id).await
+    }
+
+    /// Collect the data of the [User] for export.
+    ///
+    /// # Errors
+    ///
+    /// - Propagates any [GetUserError] returned by the [UserRepository].
+    #[tracing::instrument(skip_all, fields(user_id = %id))]
+    async fn export_user_data(&self, id: &uuid::Uuid) -> Result<UserDataExport, GetUserError> {
+        let user = self.user_repo.get_user(id).await?;
+        let terms_acceptances = self.user_repo.list_terms_acceptances(id).await?;
+        tracing::info!(outcome = "exported");
+        Ok(UserDataExport::new(
+            user,
+            terms_acceptances,
+            chrono::Utc::now(),
+        ))
+    }
+
+    /// Erase the personal data of the [User].
+    ///
+    /// Users are currently the only holders of personal data, so erasing the [User] in the
+    /// [UserRepository] is all there is to it.
+    ///
+    /// # Errors
+    ///
+    /// - Propagates any [EraseUserError] returned by the [UserRepository].
+    #[tracing::instrument(skip_all, fields(user_id = %id))]
+    async fn erase_user(&self, id: &uuid::Uuid) -> Result<User, EraseUserError> {
+        let result = self.user_repo.erase_user(id).await;
+        match &result {
+            Ok(_) => tracing::info!(outcome = "erased"),
+            Err(EraseUserError::NotFound { .. }) => tracing::info!(outcome = "not_found"),
+            Err(EraseUserError::Unknown(_)) => tracing::warn!(outcome = "failed"),
+        }
+        result
+    }
+
+    /// Record that the [User] accepted the current [TermsVersion].
+    ///
+    /// # Errors
+    ///
+    /// - [AcceptTermsError::StaleTermsVersion] if `version` isn't the current [TermsVersion].
+    /// - Propagates any [AcceptTermsError] returned by the [UserRepository].
+    #[tracing::instrument(skip_all, fields(user_id = %user_id, terms_version = %version))]
+    async fn accept_terms(
+        &self,
+        user_id: &uuid::Uuid,
+        version: &TermsVersion,
+    ) -> Result<TermsAcceptance, AcceptTermsError> {
+        if !self.is_current_terms(version) {
+            tracing::info!(outcome = "stale_terms_version");
+            return Err(AcceptTermsError::StaleTermsVersion {
+                accepted: version.clone(),
+                current: self.current_terms.clone(),
+            });
+        }
+        let result = self.user_repo.accept_terms(user_id, version).await;
+        match &result {
+            Ok(acceptance) => {
+                tracing::info!(outcome = "accepted");
+                self.record_activity(Activity::new(
+                    *user_id,
+                    ActivityKind::AcceptedTerms {
+                        version: version.clone(),
+                    },
+                    *acceptance.accepted_at(),
+                ))
+                .await;
+            }
+            Err(AcceptTermsError::Unknown(_)) => tracing::warn!(outcome = "failed"),
+            Err(_) => tracing::info!(outcome = "not_found"),
+        }
+        result
+    }
+
+    /// Check the last [TermsAcceptance] listed by the [UserRepository] against the current
+    /// [TermsVersion].
+    ///
+    /// # Errors
+    ///
+    /// - [AcceptTermsError::StaleTermsVersion] if the last accepted version isn't the current one.
+    /// - [AcceptTermsError::UserNotFound] if the [User] accepted no terms.
+    /// - [AcceptTermsError::Unknown] if the [UserRepository] fails.
+    async fn check_current_terms(&self, user_id: &uuid::Uuid) -> Result<(), AcceptTermsError> {
+        let acceptances = self
+            .user_repo
+            .list_terms_acceptances(user_id)
+            .await
+            .map_err(|e| match e {
+                GetUserError::NotFound { id } => AcceptTermsError::UserNotFound { id },
+                GetUserError::Unknown(cause) => AcceptTermsError::Unknown(cause),
+            })?;
+        let accepted = acceptances
+            .last()
+            .ok_or(AcceptTermsError::UserNotFound { id: *user_id })?
+            .terms_version();
+        if !self.is_current_terms(accepted) {
+            return Err(AcceptTermsError::StaleTermsVersion {
+                accepted: accepted.clone(),
+                current: self.current_terms.clone(),
+            });
+        }
+        Ok(())
+    }
+
+    /// List the [DeadLetter]s from the [UserRepository].
+    ///
+    /// # Errors
+    ///
+    /// - Propagates any [DeadLetterError] returned by the [UserRepository].
+    async fn list_dead_letters(&self) -> Result<Vec<DeadLetter>, DeadLetterError> {
+        self.user_repo.list_dead_letters().await
+    }
+
+    /// Deliver the event of the [DeadLetter] again. The [DeadLetter] is deleted if the delivery
+    /// succeeds, and its retry count incremented otherwise. The event of an erased [User] is not
+    /// delivered, but its [DeadLetter] is deleted.
+    ///
+    /// # Errors
+    ///
+    /// - Propagates any [DeadLetterError] returned by the [UserRepository].
+    #[tracing::instrument(skip_all, fields(dead_letter_id = %id))]
+    async fn redrive_dead_letter(
+        &self,
+        id: &uuid::Uuid,
+    ) -> Result<RedriveOutcome, DeadLetterError> {
+        let dead_letter = self.user_repo.get_dead_letter(id).await?;
+        let result = match dead_letter.event() {
+            NotificationEvent::UserCreated { user_id } => {
+                let user = self
+                    .user_repo
+                    .get_user(user_id)
+                    .await
+                    .map_err(anyhow::Error::from)?;
+                if user.is_erased() {
+                    self.user_repo.delete_dead_letter(id).await?;
+                    tracing::info!(outcome = "discarded");
+                    return Ok(RedriveOutcome::Discarded);
+                }
+                self.user_notifier.user_created(&user).await
+            }
+        };
+        match result {
+            Ok(()) => {
+                self.user_repo.delete_dead_letter(id).await?;
+                tracing::info!(outcome = "delivered");
+                Ok(RedriveOutcome::Delivered)
+            }
+            Err(err) => {
+                let dead_letter = self
+                    .user_repo
+                    .record_dead_letter_retry(id, &format!("{:#}", err))
+                    .await?;
+                tracing::warn!(outcome = "failed", retry_count = dead_letter.retry_count());
+                Ok(RedriveOutcome::Failed(dead_letter))
+            }
+        }
+    }
+
+    /// List a page of the activity feed of the [User] from the [UserRepository].
+    ///
+    /// # Errors
+    ///
+    /// - [ActivityError::UserNotFound] if no [User] with the given id exists.
+    /// - Propagates any [ActivityError] returned by the [UserRepository].
+    async fn list_user_activity(
+        &self,
+        user_id: &uuid::Uuid,
+        query: &ActivityQuery,
+    ) -> Result<ActivityPage, ActivityError> {
+        self.user_repo
+            .get_user(user_id)
+            .await
+            .map_err(|err| match err {
+                GetUserError::NotFound { id } => ActivityError::UserNotFound { id },
+                GetUserError::Unknown(cause) => ActivityError::Unknown(cause),
+            })?;
+        self.user_repo.list_activity(user_id, query).await
+    }
+
+    /// Save a [PendingEmailChange] expiring after [EMAIL_CHANGE_TTL] and send its
+    /// [EmailChangeToken] once the change is committed.
+    ///
+    /// Without the token, the change can't be confirmed, so failing to send it discards the
+    /// change instead of saving a [DeadLetter].
+    ///
+    /// # Errors
+    ///
+    /// - [EmailChangeError::UserNotFound] if no [User] with the given id exists.
+    /// - Propagates any [EmailChangeError] returned by the [UserRepository].
+    #[tracing::instrument(skip_all, fields(user_id = %user_id, new_email = %Redacted(new_email)))]
+    async fn request_email_change(
+        &self,
+        user_id: &uuid::Uuid,
+        new_email: &EmailAddress,
+    ) -> Result<PendingEmailChange, EmailChangeError> {
+        let user = self
+            .user_repo
+            .get_user(user_id)
+            .await
+            .map_err(|err| match err {
+                GetUserError::NotFound { id } => EmailChangeError::UserNotFound { id },
+                GetUserError::Unknown(cause) => EmailChangeError::Unknown(cause),
+            })
+            .inspect_err(log_email_change_outcome)?;
+        let requested_at = Utc::now();
+        let change = PendingEmailChange::new(
+            *user_id,
+            new_email.clone(),
+            requested_at,
+            requested_at + EMAIL_CHANGE_TTL,
+        );
+        let token = EmailChangeToken::generate();
+        self.user_repo
+            .save_email_change(&change, &token)
+            .await
+            .inspect_err(log_email_change_outcome)?;
+        self.send_email_change_token(&user, &change, &token).await;
+        self.add_to_inbox(user_id, InboxKind::EmailChangeRequested)
+            .await;
+        tracing::info!(outcome = "requested");
+        Ok(change)
+    }
+
+    /// Confirm the [PendingEmailChange] in the [UserRepository].
+    ///
+    /// # Errors
+    ///
+    /// - Propagates any [EmailChangeError] returned by the [UserRepository].
+    #[tracing::instrument(skip_all, fields(user_id = %user_id))]
+    async fn confirm_email_change(
+        &self,
+        user_id: &uuid::Uuid,
+        token: &EmailChangeToken,
+    ) -> Result<User, EmailChangeError> {
+        let user = self
+            .user_repo
+            .confirm_email_change(user_id, token)
+            .await
+            .inspect_err(log_email_change_outcome)?;
+        tracing::info!(outcome = "confirmed");
+        Ok(user)
+    }
+
+    /// Discard the [PendingEmailChange] in the [UserRepository].
+    ///
+    /// # Errors
+    ///
+    /// - Propagates any [EmailChangeError] returned by the [UserRepository].
+    #[tracing::instrument(skip_all, fields(user_id = %user_id))]
+    async fn cancel_email_change(&self, user_id: &uuid::Uuid) -> Result<(), EmailChangeError> {
+        self.user_repo
+            .delete_email_change(user_id)
+            .await
+            .inspect_err(log_email_change_outcome)?;
+        tracing::info!(outcome = "cancelled");
+        Ok(())
+    }
+
+    /// Discard the [PendingEmailChange]s that have expired by now in the [UserRepository].
+    ///
+    /// # Errors
+    ///
+    /// - Propagates any [EmailChangeError] returned by the [UserRepository].
+    #[tracing::instrument(skip_all)]
+    async fn purge_expired_email_changes(&self) -> Result<u64, EmailChangeError> {
+        let purged = self
+            .user_repo
+            .delete_expired_email_changes(&Utc::now())
+            .await?;
+        tracing::info!(purged, "purged expired email changes");
+        Ok(purged)
+    }
+
+    /// List the [InboxNotification]s of the [User] from the [UserRepository], with the count of
+    /// those unread.
+    ///
+    /// # Errors
+    ///
+    /// - [InboxError::UserNotFound] if no [User] with the given id exists.
+    /// - Propagates any [InboxError] returned by the [UserRepository].
+    async fn list_notifications(
+        &self,
+        user_id: &uuid::Uuid,
+        query: &InboxQuery,
+    ) -> Result<Inbox, InboxError> {
+        self.ensure_user_exists(user_id, |id| InboxError::UserNotFound { id })
+            .await?;
+        let notifications = self.user_repo.list_notifications(user_id, query).await?;
+        let unread_count = self.user_repo.count_unread_notifications(user_id).await?;
+        Ok(Inbox::new(notifications, unread_count))
+    }
+
+    /// Count the unread [InboxNotification]s of the [User] in the [UserRepository].
+    ///
+    /// # Errors
+    ///
+    /// - [InboxError::UserNotFound] if no [User] with the given id exists.
+    /// - Propagates any [InboxError] returned by the [UserRepository].
+    async fn count_unread_notifications(&self, user_id: &uuid::Uuid) -> Result<u64, InboxError> {
+        self.ensure_user_exists(user_id, |id| InboxError::UserNotFound { id })
+            .await?;
+        self.user_repo.count_unread_notifications(user_id).await
+    }
+
+    /// Mark the [InboxNotification] of the [User] as read in the [UserRepository].
+    ///
+    /// # Errors
+    ///
+    /// - Propagates any [InboxError] returned by the [UserRepository].
+    #[tracing::instrument(skip(self))]
+    async fn mark_notification_read(
+        &self,
+        user_id: &uuid::Uuid,
+        notification_id: &uuid::Uuid,
+    ) -> Result<(), InboxError> {
+        self.user_repo
+            .mark_notifications_read(user_id, Some(notification_id), &Utc::now())
+            .await?;
+        Ok(())
+    }
+
+    /// Mark all [InboxNotification]s of the [User] as read in the [UserRepository].
+    ///
+    /// # Errors
+    ///
+    /// - [InboxError::UserNotFound] if no [User] with the given id exists.
+    /// - Propagates any [InboxError] returned by the [UserRepository].
+    #[tracing::instrument(skip(self))]
+    async fn mark_all_notifications_read(&self, user_id: &uuid::Uuid) -> Result<u64, InboxError> {
+        self.ensure_user_exists(user_id, |id| InboxError::UserNotFound { id })
+            .await?;
+        let marked = self
+            .user_repo
+            .mark_notifications_read(user_id, None, &Utc::now())
+            .await?;
+        tracing::info!(marked, "marked notifications read");
+        Ok(marked)
+    }
+
+    /// Discard the [InboxNotification]s older than [NOTIFICATION_RETENTION] in the
+    /// [UserRepository].
+    ///
+    /// # Errors
+    ///
+    /// - Propagates any [InboxError] returned by the [UserRepository].
+    #[tracing::instrument(skip_all)]
+    async fn purge_old_notifications(&self) -> Result<u64, InboxError> {
+        let purged = self
+            .user_repo
+            .delete_notifications_before(&(Utc::now() - NOTIFICATION_RETENTION))
+            .await?;
+        tracing::info!(purged, "purged old notifications");
+        Ok(purged)
+    }
+
+    /// Search the [User]s in the [UserRepository].
+    ///
+    /// # Errors
+    ///
+    /// - Propagates any [SearchError] returned by the [UserRepository].
+    async fn search_users(&self, query: &UserSearchQuery) -> Result<Vec<User>, SearchError> {
+        self.user_repo.search_users(query).await
+    }
+
+    /// Buffer `events` for the analytics store, once their users are known to exist.
+    ///
+    /// # Errors
+    ///
+    /// - [AnalyticsError::TooManyEvents] if more than [MAX_BATCH_SIZE] events are given.
+    /// - [AnalyticsError::UserNotFound] if no user with the id of an event exists.
+    /// - Propagates any [AnalyticsError] returned by the [AnalyticsBuffer].
+    #[tracing::instrument(skip_all, fields(count = events.len()))]
+    async fn record_analytics_events(
+        &self,
+        events: Vec<AnalyticsEvent>,
+    ) -> Result<(), AnalyticsError> {
+        if events.len() > MAX_BATCH_SIZE {
+            return Err(AnalyticsError::TooManyEvents {
+                max: MAX_BATCH_SIZE,
+            });
+        }
+        let user_ids: BTreeSet<uuid::Uuid> = events.iter().map(|event| *event.user_id()).collect();
+        for user_id in &user_ids {
+            self.ensure_user_exists(user_id, |id| AnalyticsError::UserNotFound { id })
+                .await?;
+        }
+        match &self.analytics {
+            Some(buffer) => buffer.record(events),
+            None => {
+                tracing::debug!("analytics are not recorded, dropped events");
+                Ok(())
+            }
+        }
+    }
+}
+
+fn log_email_change_outcome(err: &EmailChangeError) {
+    match err {
+        EmailChangeError::UserNotFound { .. } => tracing::info!(outcome = "not_found"),
+        EmailChangeError::NotPending { .. } => tracing::info!(outcome = "not_pending"),
+        EmailChangeError::InvalidToken => tracing::info!(outcome = "invalid_token"),
+        EmailChangeError::Expired { .. } => tracing::info!(outcome = "expired"),
+        EmailChangeError::DuplicateEmail { .. } => tracing::info!(outcome = "duplicate_email"),
+        EmailChangeError::Unknown(_) => tracing::warn!(outcome = "failed"),
+    }
+}
+
+fn signed_up(user: &User) -> Activity {
+    Activity::new(*user.id(), ActivityKind::SignedUp, *user.created_at())
 }
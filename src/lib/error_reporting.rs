@@ -0,0 +1,198 @@
+//! Module `error_reporting` sends panics and the causes of internal server errors to Sentry,
+//! with the request being handled when they happened.
+//!
+//! Reporting is best effort: events are sent in the background, and are lost if the process
+//! exits first or Sentry can't be reached.
+
+use std::sync::OnceLock;
+
+use anyhow::Context;
+use chrono::Utc;
+
+use crate::outbound::http_client::HttpClient;
+
+static REPORTER: OnceLock<ErrorReporter> = OnceLock::new();
+
+tokio::task_local! {
+    static CURRENT_REQUEST: RequestContext;
+}
+
+/// The request being handled when an error is reported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestContext {
+    pub method: String,
+    pub url: String,
+}
+
+/// Sends events to the Sentry project of a DSN.
+#[derive(Debug, Clone)]
+pub struct ErrorReporter {
+    client: HttpClient,
+    store_url: String,
+    auth: String,
+}
+
+impl ErrorReporter {
+    /// Parses a DSN of the form `https://<public key>@<host>/<project id>`.
+    pub fn from_dsn(dsn: &str, client: HttpClient) -> anyhow::Result<Self> {
+        let url = reqwest::Url::parse(dsn).context("invalid error reporting DSN")?;
+        let public_key = url.username();
+        anyhow::ensure!(!public_key.is_empty(), "DSN has no public key");
+        let (prefix, project_id) = url
+            .path()
+            .rsplit_once('/')
+            .filter(|(_, project_id)| !project_id.is_empty())
+            .context("DSN has no project id")?;
+        let host = url.host_str().context("DSN has no host")?;
+        let port = url
+            .port()
+            .map(|port| format!(":{port}"))
+            .unwrap_or_default();
+        Ok(Self {
+            client,
+            store_url: format!(
+                "{}://{host}{port}{prefix}/api/{project_id}/store/",
+                url.scheme()
+            ),
+            auth: format!(
+                "Sentry sentry_version=7, sentry_client=crowdsource/{}, sentry_key={public_key}",
+                env!("CARGO_PKG_VERSION")
+            ),
+        })
+    }
+
+    /// Sends an error event with `exception` as its type and `message` as its value.
+    fn send(&self, exception: &str, message: &str, request: Option<RequestContext>) {
+        let event = event(exception, message, request.as_ref());
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            tracing::warn!("not reporting error outside of the async runtime");
+            return;
+        };
+        let client = self.client.clone();
+        let request = client
+            .post(&self.store_url)
+            .header("X-Sentry-Auth", &self.auth)
+            .json(&event);
+        runtime.spawn(async move {
+            if let Err(e) = client
+                .send(request)
+                .await
+                .and_then(reqwest::Response::error_for_status)
+            {
+                tracing::warn!("failed to report error: {e}");
+            }
+        });
+    }
+}
+
+/// Reports to `reporter` from now on, including panics, which are still passed on to the
+/// previously installed panic hook.
+///
+/// # Errors
+///
+/// Fails if a reporter was already installed.
+pub fn install(reporter: ErrorReporter) -> anyhow::Result<()> {
+    REPORTER
+        .set(reporter)
+        .map_err(|_| anyhow::anyhow!("error reporting is already installed"))?;
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let message = info
+            .payload()
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| info.payload().downcast_ref::<String>().map(String::as_str))
+            .unwrap_or("panic");
+        let message = match info.location() {
+            Some(location) => format!("{message} at {location}"),
+            None => message.to_string(),
+        };
+        if let Some(reporter) = REPORTER.get() {
+            reporter.send("panic", &message, current_request());
+        }
+        previous(info);
+    }));
+    Ok(())
+}
+
+/// Reports `cause` with the request being handled, if a reporter is installed.
+pub fn capture_error(cause: &anyhow::Error) {
+    if let Some(reporter) = REPORTER.get() {
+        reporter.send("error", &format!("{cause:#}"), current_request());
+    }
+}
+
+/// Runs `f`, reporting errors during it with `request`.
+pub async fn with_request<F: Future>(request: RequestContext, f: F) -> F::Output {
+    CURRENT_REQUEST.scope(request, f).await
+}
+
+fn current_request() -> Option<RequestContext> {
+    CURRENT_REQUEST.try_with(Clone::clone).ok()
+}
+
+fn event(exception: &str, message: &str, request: Option<&RequestContext>) -> serde_json::Value {
+    let mut event = serde_json::json!({
+        "event_id": uuid::Uuid::new_v4().simple().to_string(),
+        "timestamp": Utc::now().to_rfc3339(),
+        "platform": "other",
+        "level": "error",
+        "release": concat!("crowdsource@", env!("CARGO_PKG_VERSION")),
+        "exception": {
+            "values": [{ "type": exception, "value": message }],
+        },
+    });
+    if let Some(request) = request {
+        event["request"] = serde_json::json!({ "method": request.method, "url": request.url });
+    }
+    event
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dsn_is_parsed_into_store_url_and_auth() {
+        let reporter = ErrorReporter::from_dsn(
+            "https://abc123@o1.ingest.example.com:8443/sentry/42",
+            HttpClient::default(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            reporter.store_url,
+            "https://o1.ingest.example.com:8443/sentry/api/42/store/"
+        );
+        assert!(reporter.auth.ends_with("sentry_key=abc123"));
+    }
+
+    #[test]
+    fn dsn_without_key_or_project_is_rejected() {
+        assert!(
+            ErrorReporter::from_dsn("https://o1.ingest.example.com/42", HttpClient::default())
+                .is_err()
+        );
+        assert!(
+            ErrorReporter::from_dsn(
+                "https://abc123@o1.ingest.example.com/",
+                HttpClient::default()
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn event_includes_request_context() {
+        let request = RequestContext {
+            method: "POST".to_string(),
+            url: "/api/users".to_string(),
+        };
+
+        let event = event("error", "database is down", Some(&request));
+
+        assert_eq!(event["exception"]["values"][0]["value"], "database is down");
+        assert_eq!(event["request"]["method"], "POST");
+        assert_eq!(event["request"]["url"], "/api/users");
+    }
+}
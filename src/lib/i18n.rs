@@ -0,0 +1,467 @@
+//! Module `i18n` resolves user-facing messages in the language of the user.
+//!
+//! Email texts are not part of the catalog, they are rendered from per-locale templates instead.
+//!
+//! Messages are identified by a key and may contain `{name}` placeholders, filled in from the
+//! arguments given to [MessageCatalog::message]. A message missing in the requested [Locale]
+//! falls back to English.
+
+use std::{collections::HashMap, fmt, sync::LazyLock};
+
+/// The languages messages are available in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Locale {
+    #[default]
+    En,
+    Sv,
+}
+
+impl Locale {
+    /// Returns the [Locale] for a language tag such as `sv` or `sv-SE`, if supported.
+    pub fn from_language_tag(tag: &str) -> Option<Self> {
+        let primary = tag.split(['-', '_']).next()?.trim();
+        if primary.eq_ignore_ascii_case("en") {
+            Some(Self::En)
+        } else if primary.eq_ignore_ascii_case("sv") {
+            Some(Self::Sv)
+        } else {
+            None
+        }
+    }
+
+    /// Picks the preferred supported [Locale] from an `Accept-Language` header value, falling
+    /// back to the default.
+    pub fn negotiate(accept_language: &str) -> Self {
+        let mut candidates: Vec<(f32, usize, Self)> = accept_language
+            .split(',')
+            .enumerate()
+            .filter_map(|(position, range)| {
+                let mut parts = range.split(';');
+                let locale = Self::from_language_tag(parts.next()?)?;
+                let quality = parts
+                    .find_map(|param| param.trim().strip_prefix("q="))
+                    .map_or(Some(1.0), |q| q.trim().parse::<f32>().ok())?;
+                (quality > 0.0).then_some((quality, position, locale))
+            })
+            .collect();
+        candidates.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)));
+        candidates
+            .first()
+            .map(|(_, _, locale)| *locale)
+            .unwrap_or_default()
+    }
+
+    pub fn as_language_tag(&self) -> &'static str {
+        match self {
+            Self::En => "en",
+            Self::Sv => "sv",
+        }
+    }
+}
+
+const EN: &[(&str, &str)] = &[
+    ("error.internal", "Internal server error"),
+    (
+        "error.user.duplicate_username",
+        "user with username '{username}' already exists",
+    ),
+    (
+        "error.user.duplicate_email",
+        "user with email '{email}' already exists",
+    ),
+    ("error.user.not_found", "user with id '{id}' not found"),
+    (
+        "error.dead_letter.not_found",
+        "dead letter with id '{id}' not found",
+    ),
+    (
+        "error.email_change.not_pending",
+        "user with id '{id}' has no pending email change",
+    ),
+    (
+        "error.email_change.invalid_token",
+        "the email change token is invalid",
+    ),
+    (
+        "error.email_change.expired",
+        "the email change has expired, request a new one",
+    ),
+    ("error.username.empty", "username can't be empty"),
+    (
+        "error.username.whitespace",
+        "username cannot contain whitespace (got: '{username}')",
+    ),
+    (
+        "error.username.control_characters",
+        "username cannot contain control or formatting characters (got: '{username}')",
+    ),
+    (
+        "error.username.too_long",
+        "username can't be longer than {max_length} characters",
+    ),
+    (
+        "error.username.reserved",
+        "username can't start with '{prefix}'",
+    ),
+    (
+        "error.email.invalid",
+        "the email address is invalid: {reason}",
+    ),
+    ("error.terms_version.empty", "terms version can't be empty"),
+    (
+        "error.terms_version.whitespace",
+        "terms version cannot contain whitespace (got: '{version}')",
+    ),
+    (
+        "error.terms_version.stale",
+        "terms of service version '{current}' must be accepted (got: '{accepted}')",
+    ),
+    (
+        "error.challenge.missing",
+        "a solved challenge is required, get one from /api/challenge",
+    ),
+    (
+        "error.challenge.rejected",
+        "the challenge solution was rejected",
+    ),
+    (
+        "error.throttle.too_many_submissions",
+        "too many submissions, try again later",
+    ),
+    ("error.overloaded", "the server is busy, try again later"),
+    ("error.timeout", "the request took too long"),
+    (
+        "error.maintenance",
+        "the service is under maintenance, only reading is possible",
+    ),
+    ("error.access_token.missing", "authentication is required"),
+    ("error.access_token.invalid", "the access token is invalid"),
+    ("error.access_token.expired", "the access token has expired"),
+    (
+        "error.access_token.forbidden",
+        "you may not access this resource",
+    ),
+    ("error.signed_url.missing", "the link is not signed"),
+    ("error.signed_url.invalid", "the link is invalid"),
+    ("error.signed_url.expired", "the link has expired"),
+    (
+        "error.activity_cursor.invalid",
+        "activity cursor '{cursor}' is invalid",
+    ),
+    (
+        "error.runtime_config.invalid_log_level",
+        "log level '{level}' is invalid",
+    ),
+    (
+        "error.users_export.unknown_column",
+        "column '{column}' is unknown",
+    ),
+    (
+        "error.notification.not_found",
+        "notification with id '{id}' not found",
+    ),
+    (
+        "error.search.invalid_query",
+        "the search is invalid: {reason}",
+    ),
+    (
+        "error.analytics.invalid_event",
+        "the event is invalid: {reason}",
+    ),
+    (
+        "error.analytics.too_many_events",
+        "at most {max} events can be recorded at once",
+    ),
+    (
+        "error.analytics.overloaded",
+        "too many events are being recorded, try again later",
+    ),
+    ("inbox.welcome", "Welcome! Your account is ready."),
+    (
+        "inbox.email_change_requested",
+        "Confirm your new email address from the link we sent to it.",
+    ),
+];
+
+const SV: &[(&str, &str)] = &[
+    ("error.internal", "Internt serverfel"),
+    (
+        "error.user.duplicate_username",
+        "det finns redan en användare med användarnamnet '{username}'",
+    ),
+    (
+        "error.user.duplicate_email",
+        "det finns redan en användare med e-postadressen '{email}'",
+    ),
+    (
+        "error.user.not_found",
+        "det finns ingen användare med id '{id}'",
+    ),
+    (
+        "error.dead_letter.not_found",
+        "det finns inget olevererat meddelande med id '{id}'",
+    ),
+    (
+        "error.email_change.not_pending",
+        "användaren med id '{id}' har inget väntande byte av e-postadress",
+    ),
+    (
+        "error.email_change.invalid_token",
+        "koden för byte av e-postadress är ogiltig",
+    ),
+    (
+        "error.email_change.expired",
+        "bytet av e-postadress har gått ut, begär ett nytt",
+    ),
+    ("error.username.empty", "användarnamnet får inte vara tomt"),
+    (
+        "error.username.whitespace",
+        "användarnamnet får inte innehålla blanksteg (fick: '{username}')",
+    ),
+    (
+        "error.username.control_characters",
+        "användarnamnet får inte innehålla kontroll- eller formateringstecken (fick: '{username}')",
+    ),
+    (
+        "error.username.too_long",
+        "användarnamnet får inte vara längre än {max_length} tecken",
+    ),
+    (
+        "error.username.reserved",
+        "användarnamnet får inte börja med '{prefix}'",
+    ),
+    ("error.email.invalid", "e-postadressen är ogiltig: {reason}"),
+    (
+        "error.terms_version.empty",
+        "villkorsversionen får inte vara tom",
+    ),
+    (
+        "error.terms_version.whitespace",
+        "villkorsversionen får inte innehålla blanksteg (fick: '{version}')",
+    ),
+    (
+        "error.terms_version.stale",
+        "användarvillkoren i version '{current}' måste godkännas (fick: '{accepted}')",
+    ),
+    (
+        "error.challenge.missing",
+        "en löst utmaning krävs, hämta en från /api/challenge",
+    ),
+    (
+        "error.challenge.rejected",
+        "lösningen på utmaningen godtogs inte",
+    ),
+    (
+        "error.throttle.too_many_submissions",
+        "för många inskick, försök igen senare",
+    ),
+    (
+        "error.overloaded",
+        "servern är upptagen, försök igen senare",
+    ),
+    ("error.timeout", "begäran tog för lång tid"),
+    (
+        "error.maintenance",
+        "tjänsten genomgår underhåll, endast läsning är möjlig",
+    ),
+    ("error.access_token.missing", "autentisering krävs"),
+    ("error.access_token.invalid", "åtkomsttoken är ogiltig"),
+    ("error.access_token.expired", "åtkomsttoken har gått ut"),
+    (
+        "error.access_token.forbidden",
+        "du har inte åtkomst till resursen",
+    ),
+    ("error.signed_url.missing", "länken är inte signerad"),
+    ("error.signed_url.invalid", "länken är ogiltig"),
+    ("error.signed_url.expired", "länken har gått ut"),
+    (
+        "error.activity_cursor.invalid",
+        "aktivitetsmarkören '{cursor}' är ogiltig",
+    ),
+    (
+        "error.runtime_config.invalid_log_level",
+        "loggnivån '{level}' är ogiltig",
+    ),
+    (
+        "error.users_export.unknown_column",
+        "kolumnen '{column}' är okänd",
+    ),
+    (
+        "error.notification.not_found",
+        "aviseringen med id '{id}' hittades inte",
+    ),
+    (
+        "error.search.invalid_query",
+        "sökningen är ogiltig: {reason}",
+    ),
+    (
+        "error.analytics.invalid_event",
+        "händelsen är ogiltig: {reason}",
+    ),
+    (
+        "error.analytics.too_many_events",
+        "högst {max} händelser kan registreras åt gången",
+    ),
+    (
+        "error.analytics.overloaded",
+        "för många händelser registreras just nu, försök igen senare",
+    ),
+    ("inbox.welcome", "Välkommen! Ditt konto är klart."),
+    (
+        "inbox.email_change_requested",
+        "Bekräfta din nya e-postadress via länken vi skickade till den.",
+    ),
+];
+
+/// The messages of all [Locale]s.
+#[derive(Debug, Clone)]
+pub struct MessageCatalog {
+    messages: HashMap<Locale, HashMap<&'static str, &'static str>>,
+}
+
+static GLOBAL: LazyLock<MessageCatalog> = LazyLock::new(MessageCatalog::new);
+
+tokio::task_local! {
+    static CURRENT_LOCALE: Locale;
+}
+
+impl MessageCatalog {
+    pub fn new() -> Self {
+        let messages = [(Locale::En, EN), (Locale::Sv, SV)]
+            .into_iter()
+            .map(|(locale, messages)| (locale, messages.iter().copied().collect()))
+            .collect();
+        Self { messages }
+    }
+
+    /// The catalog shared by the whole application.
+    pub fn global() -> &'static Self {
+        &GLOBAL
+    }
+
+    /// Resolves the message `key` in `locale`, filling in `args`.
+    ///
+    /// Falls back to English, and to the key itself for unknown keys.
+    pub fn message(&self, locale: Locale, key: &str, args: &[(&str, &dyn fmt::Display)]) -> String {
+        let template = [locale, Locale::En]
+            .iter()
+            .find_map(|locale| self.messages.get(locale)?.get(key))
+            .copied()
+            .unwrap_or(key);
+        let mut message = String::with_capacity(template.len());
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            message.push_str(&rest[..start]);
+            let placeholder = &rest[start..];
+            let Some(end) = placeholder.find('}') else {
+                rest = placeholder;
+                break;
+            };
+            let name = &placeholder[1..end];
+            match args.iter().find(|(arg, _)| *arg == name) {
+                Some((_, value)) => message.push_str(&value.to_string()),
+                None => message.push_str(&placeholder[..=end]),
+            }
+            rest = &placeholder[end + 1..];
+        }
+        message.push_str(rest);
+        message
+    }
+}
+
+impl Default for MessageCatalog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Runs `f` with `locale` as the [current_locale].
+pub async fn with_locale<F: Future>(locale: Locale, f: F) -> F::Output {
+    CURRENT_LOCALE.scope(locale, f).await
+}
+
+/// The [Locale] of the request being handled, or the default outside of [with_locale].
+pub fn current_locale() -> Locale {
+    CURRENT_LOCALE
+        .try_with(|locale| *locale)
+        .unwrap_or_default()
+}
+
+/// Resolves the message `key` in the [current_locale] through the global [MessageCatalog].
+pub fn message(key: &str, args: &[(&str, &dyn fmt::Display)]) -> String {
+    MessageCatalog::global().message(current_locale(), key, args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negotiate_picks_highest_quality_supported_locale() {
+        assert_eq!(Locale::negotiate("sv-SE,sv;q=0.9,en;q=0.8"), Locale::Sv);
+        assert_eq!(Locale::negotiate("de-DE,en;q=0.5,sv;q=0.7"), Locale::Sv);
+        assert_eq!(Locale::negotiate("sv;q=0,en"), Locale::En);
+        assert_eq!(Locale::negotiate("de, fr"), Locale::En);
+        assert_eq!(Locale::negotiate(""), Locale::En);
+    }
+
+    #[test]
+    fn test_message_fills_in_arguments() {
+        let catalog = MessageCatalog::new();
+
+        let actual = catalog.message(Locale::Sv, "error.user.not_found", &[("id", &"b2d5b8c2")]);
+
+        assert_eq!(actual, "det finns ingen användare med id 'b2d5b8c2'");
+    }
+
+    #[test]
+    fn test_message_does_not_expand_placeholders_in_arguments() {
+        let catalog = MessageCatalog::new();
+
+        let actual = catalog.message(
+            Locale::En,
+            "error.terms_version.stale",
+            &[("current", &"{accepted}"), ("accepted", &"v1")],
+        );
+
+        assert_eq!(
+            actual,
+            "terms of service version '{accepted}' must be accepted (got: 'v1')"
+        );
+    }
+
+    #[test]
+    fn test_message_falls_back_to_english_and_key() {
+        let mut catalog = MessageCatalog::new();
+        catalog
+            .messages
+            .get_mut(&Locale::Sv)
+            .unwrap()
+            .remove("error.internal");
+
+        assert_eq!(
+            catalog.message(Locale::Sv, "error.internal", &[]),
+            "Internal server error"
+        );
+        assert_eq!(
+            catalog.message(Locale::Sv, "no.such.key", &[]),
+            "no.such.key"
+        );
+    }
+
+    #[test]
+    fn test_every_message_is_translated() {
+        let catalog = MessageCatalog::new();
+        let en = &catalog.messages[&Locale::En];
+
+        for locale in [Locale::Sv] {
+            let translated = &catalog.messages[&locale];
+            for key in en.keys() {
+                assert!(
+                    translated.contains_key(key),
+                    "{key} is missing in {locale:?}"
+                );
+            }
+        }
+    }
+}
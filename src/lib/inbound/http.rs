@@ -1,34 +1,129 @@
-use std::sync::Arc;
+use std::{net::IpAddr, path::Path, sync::Arc, time::Duration};
 
 use anyhow::Context;
-use axum::routing::{get, post};
+use axum::extract::FromRef;
+use axum::routing::{delete, get, post};
+use futures::FutureExt;
 use tokio::net;
 
-use crate::domain::crowdsrc::ports::CrowdSrcService;
+use crate::domain::crowdsrc::models::signed_url::SigningKey;
+use crate::domain::crowdsrc::models::user::UserNamePolicy;
+use crate::domain::crowdsrc::ports::{
+    AbuseChallenge, CrowdSrcService, FeatureFlags, MaintenanceSwitch, RuntimeConfigStore,
+    SubmissionThrottle, TransactionManager,
+};
+use crate::inbound::http::handlers::accept_terms::accept_terms;
 use crate::inbound::http::handlers::api_home::api_home;
 use crate::inbound::http::handlers::create_user::create_user;
+use crate::inbound::http::handlers::email_change::{
+    cancel_email_change, confirm_email_change, request_email_change,
+};
+use crate::inbound::http::handlers::erase_user::erase_user;
+use crate::inbound::http::handlers::export_user_data::{
+    export_shared_user_data, export_user_data, share_user_data_export,
+};
+use crate::inbound::http::handlers::export_users_csv::export_users_csv;
+use crate::inbound::http::handlers::get_user::get_user;
+use crate::inbound::http::handlers::list_dead_letters::list_dead_letters;
+use crate::inbound::http::handlers::list_features::list_features;
+use crate::inbound::http::handlers::list_user_activity::list_user_activity;
+use crate::inbound::http::handlers::notifications::{
+    count_unread_notifications, list_notifications, mark_all_notifications_read,
+    mark_notification_read,
+};
+use crate::inbound::http::handlers::record_analytics_events::record_analytics_events;
+use crate::inbound::http::handlers::redrive_dead_letter::redrive_dead_letter;
+use crate::inbound::http::handlers::search_users::search_users;
 
+pub use abuse_challenge::ChallengedRoute;
+pub use limits::RouteLimits;
+pub use runtime_config::RuntimeConfigControl;
+
+mod abuse_challenge;
+mod admin;
+mod auth;
+#[cfg(feature = "error-reporting")]
+mod error_reporting;
 mod handlers;
+mod limits;
+mod listener;
+mod locale;
+mod maintenance;
+#[cfg(feature = "client-models")]
+pub mod models;
 mod responses;
+mod runtime_config;
+mod signed_url;
+mod static_files;
+mod terms;
+mod throttle;
+mod transaction;
+#[cfg(feature = "typescript")]
+pub mod typescript;
 
 pub struct HttpServerConfig<'a> {
     pub port: &'a str,
+    /// The directory static files, such as a frontend bundle, are served from, if any.
+    pub static_dir: Option<&'a Path>,
+    /// The routes that require a solved abuse challenge.
+    pub challenged_routes: &'a [ChallengedRoute],
+    /// The addresses signups are never throttled for.
+    pub throttle_allowlist: &'a [IpAddr],
+    /// The limits on requests to `/api`.
+    pub api_limits: RouteLimits,
+    /// The limits on requests to `/admin`.
+    pub admin_limits: RouteLimits,
+    /// Whether maintenance mode is kept on regardless of the persisted switch.
+    pub maintenance_mode: bool,
+    /// How long clients are told to wait while in maintenance mode.
+    pub maintenance_retry_after: Duration,
+    /// The key shared links are signed with.
+    pub signed_url_key: &'a [u8],
+    /// The key access tokens are verified with.
+    pub access_token_key: &'a [u8],
+    /// How the usernames of new users are validated.
+    pub username_policy: UserNamePolicy,
+    /// Whether to set `SO_REUSEPORT`, so that a new server can listen on the port before the
+    /// old one stops. Ignored if a listener is passed by socket activation.
+    pub reuse_port: bool,
+    /// How long in-flight requests may take to complete once shutdown is requested.
+    pub drain_timeout: Duration,
 }
 
 pub struct HttpServer {
     router: axum::Router,
+    /// Assembles [HttpServer::router] around the routes added to `/api` by extensions.
+    assemble_router: Arc<dyn Fn(axum::Router) -> axum::Router + Send + Sync>,
+    api_extensions: axum::Router,
     listener: net::TcpListener,
+    drain_timeout: Duration,
 }
 
 #[derive(Debug, Clone)]
 /// The global application state shared between all request handlers.
-struct AppState<CS: CrowdSrcService> {
+struct AppState<CS: CrowdSrcService, FF: FeatureFlags> {
     crwdsrc_service: Arc<CS>,
+    feature_flags: Arc<FF>,
+    signing_key: SigningKey,
+    username_policy: UserNamePolicy,
+}
+
+impl<CS: CrowdSrcService, FF: FeatureFlags> FromRef<AppState<CS, FF>> for SigningKey {
+    fn from_ref(state: &AppState<CS, FF>) -> Self {
+        state.signing_key.clone()
+    }
 }
 
 impl HttpServer {
+    #[allow(clippy::too_many_arguments)]
     pub async fn new(
         crwdsrc_service: impl CrowdSrcService,
+        transaction_manager: impl TransactionManager,
+        abuse_challenge: impl AbuseChallenge,
+        submission_throttle: impl SubmissionThrottle,
+        feature_flags: impl FeatureFlags,
+        maintenance_switch: impl MaintenanceSwitch,
+        runtime_config: RuntimeConfigControl<impl RuntimeConfigStore>,
         config: HttpServerConfig<'_>,
     ) -> Result<Self, anyhow::Error> {
         let trace_layer = tower_http::trace::TraceLayer::new_for_http().make_span_with(
@@ -38,27 +133,155 @@ impl HttpServer {
             },
         );
 
+        let maintenance = maintenance::Maintenance::new(
+            maintenance_switch,
+            config.maintenance_mode,
+            config.maintenance_retry_after,
+        );
         let state = AppState {
             crwdsrc_service: Arc::new(crwdsrc_service),
+            feature_flags: Arc::new(feature_flags),
+            signing_key: SigningKey::new(config.signed_url_key),
+            username_policy: config.username_policy,
+        };
+        let throttling = throttle::Throttling {
+            throttle: submission_throttle,
+            allowlist: config.throttle_allowlist.into(),
+        };
+        let authentication = auth::Authentication {
+            key: SigningKey::new(config.access_token_key),
+            challenge: auth::BEARER_CHALLENGE,
+        };
+        let admin_pages_authentication = auth::Authentication {
+            challenge: auth::ADMIN_PAGES_CHALLENGE,
+            ..authentication.clone()
         };
+        let challenged_routes: Arc<[ChallengedRoute]> = config.challenged_routes.into();
+        let static_dir = config.static_dir.map(|dir| Arc::new(dir.to_path_buf()));
+        let (api_limits, admin_limits) = (config.api_limits, config.admin_limits);
 
-        let router = axum::Router::new()
-            .nest("/api", api_routes())
-            .layer(trace_layer)
-            .with_state(state);
-        let listener = net::TcpListener::bind(format!("0.0.0.0:{}", config.port))
-            .await
-            .with_context(|| format!("failed to listen on {}", config.port))?;
+        let assemble_router = move |api_extensions: axum::Router| {
+            let mut router = axum::Router::new()
+                .nest(
+                    "/api",
+                    limits::limited(
+                        under_maintenance(
+                            api_routes(
+                                transaction_manager.clone(),
+                                abuse_challenge.clone(),
+                                throttling.clone(),
+                                authentication.clone(),
+                                state.crwdsrc_service.clone(),
+                                &challenged_routes,
+                            )
+                            .merge(api_extensions.with_state(())),
+                            maintenance.clone(),
+                        )
+                        // Added after the maintenance layer, so that maintenance mode can be left
+                        // and the runtime config tuned during maintenance.
+                        .merge(admin_only(
+                            axum::Router::new().route(
+                                "/admin/maintenance",
+                                get(maintenance::get_maintenance)
+                                    .put(maintenance::set_maintenance)
+                                    .with_state(maintenance.clone()),
+                            ),
+                            authentication.clone(),
+                        ))
+                        .merge(admin_only(
+                            axum::Router::new().route(
+                                "/admin/config",
+                                get(runtime_config::get_runtime_config)
+                                    .patch(runtime_config::patch_runtime_config)
+                                    .with_state(runtime_config.clone()),
+                            ),
+                            authentication.clone(),
+                        )),
+                        api_limits,
+                    ),
+                )
+                .nest(
+                    "/admin",
+                    limits::limited(
+                        under_maintenance(
+                            admin_only(admin::admin_routes(), admin_pages_authentication.clone()),
+                            maintenance.clone(),
+                        ),
+                        admin_limits,
+                    ),
+                );
+            if let Some(static_dir) = &static_dir {
+                let static_dir = static_dir.clone();
+                router = router.fallback(move |request| {
+                    let static_dir = static_dir.clone();
+                    async move { static_files::serve_static_file(&static_dir, request).await }
+                });
+            }
+            #[cfg(feature = "error-reporting")]
+            let router = router.layer(axum::middleware::from_fn(
+                error_reporting::capture_request_context,
+            ));
+            router
+                .layer(axum::middleware::from_fn(locale::negotiate_locale))
+                .layer(trace_layer.clone())
+                .with_state(state.clone())
+        };
+        let api_extensions = axum::Router::new();
+        let router = assemble_router(api_extensions.clone());
+        let listener = listener::listen(config.port, config.reuse_port)?;
+
+        Ok(Self {
+            router,
+            assemble_router: Arc::new(assemble_router),
+            api_extensions,
+            listener,
+            drain_timeout: config.drain_timeout,
+        })
+    }
 
-        Ok(Self { router, listener })
+    /// Serves the routes of `extension` under `/api`, alongside the built-in ones, so that
+    /// embedders can add endpoints of their own.
+    ///
+    /// The routes are subject to the same limits and maintenance mode as the built-in ones, and
+    /// any layer added to `extension` applies to its routes only. Handlers needing the
+    /// [CrowdSrcService] share it by being given a clone of the one the server was created with.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a route of `extension` is already served, as [axum::Router::merge] does.
+    pub fn with_extension(mut self, extension: axum::Router) -> Self {
+        self.api_extensions = self.api_extensions.merge(extension);
+        self.router = (self.assemble_router)(self.api_extensions.clone());
+        self
     }
 
     /// Runs the HTTP server.
     pub async fn run(self) -> anyhow::Result<()> {
+        self.run_until(std::future::pending()).await
+    }
+
+    /// Runs the HTTP server until `shutdown` completes, then stops accepting connections and
+    /// waits at most [HttpServerConfig::drain_timeout] for in-flight requests to complete.
+    pub async fn run_until(
+        self,
+        shutdown: impl Future<Output = ()> + Send + 'static,
+    ) -> anyhow::Result<()> {
         tracing::debug!("listening on {}", self.listener.local_addr().unwrap());
-        axum::serve(self.listener, self.router)
-            .await
-            .context("received error from running server")?;
+        let shutdown = shutdown.shared();
+        let serve = axum::serve(
+            self.listener,
+            self.router
+                .into_make_service_with_connect_info::<std::net::SocketAddr>(),
+        )
+        .with_graceful_shutdown(shutdown.clone());
+        let drain_timeout = self.drain_timeout;
+        tokio::select! {
+            result = serve => result.context("received error from running server")?,
+            () = async move {
+                shutdown.await;
+                tokio::time::sleep(drain_timeout).await;
+            } => tracing::warn!("requests still in flight after {drain_timeout:?}, stopping anyway"),
+        }
         Ok(())
     }
 
@@ -67,8 +290,196 @@ impl HttpServer {
     }
 }
 
-fn api_routes<CS: CrowdSrcService>() -> axum::Router<AppState<CS>> {
+fn api_routes<
+    CS: CrowdSrcService,
+    FF: FeatureFlags,
+    TM: TransactionManager,
+    AC: AbuseChallenge,
+    ST: SubmissionThrottle,
+>(
+    transaction_manager: TM,
+    abuse_challenge: AC,
+    throttling: throttle::Throttling<ST>,
+    authentication: auth::Authentication,
+    crwdsrc_service: Arc<CS>,
+    challenged_routes: &[ChallengedRoute],
+) -> axum::Router<AppState<CS, FF>> {
+    let signup = transactional(
+        axum::Router::new().route("/users", post(create_user::<CS, FF>)),
+        transaction_manager.clone(),
+    );
+    let signup = challenged(
+        signup,
+        abuse_challenge.clone(),
+        challenged_routes.contains(&ChallengedRoute::Signup),
+    )
+    .route_layer(axum::middleware::from_fn_with_state(
+        throttling,
+        throttle::throttle_submissions::<ST>,
+    ));
+
     axum::Router::new()
         .route("/", get(api_home))
-        .route("/users", post(create_user::<CS>))
+        .route(
+            "/challenge",
+            get(abuse_challenge::issue_challenge::<AC>).with_state(abuse_challenge),
+        )
+        .route("/features", get(list_features::<CS, FF>))
+        .route(
+            "/shared/users/{id}/data-export",
+            get(export_shared_user_data::<CS, FF>),
+        )
+        .route(
+            "/users/{id}/email-change/confirmation",
+            post(confirm_email_change::<CS, FF>),
+        )
+        .merge(signup)
+        .merge(for_user_or_admin(
+            axum::Router::new()
+                .route("/users/{id}", get(get_user::<CS, FF>))
+                .route("/users/{id}/activity", get(list_user_activity::<CS, FF>))
+                .route("/users/{id}/data-export", get(export_user_data::<CS, FF>))
+                .merge(transactional(
+                    axum::Router::new().route("/users/{id}/erasure", post(erase_user::<CS, FF>)),
+                    transaction_manager.clone(),
+                )),
+            authentication.clone(),
+        ))
+        .merge(for_user(
+            axum::Router::new()
+                .route("/analytics/events", post(record_analytics_events::<CS, FF>))
+                .route(
+                    "/users/{id}/data-export/share",
+                    post(share_user_data_export::<CS, FF>),
+                )
+                .route("/users/{id}/terms-acceptance", post(accept_terms::<CS, FF>))
+                .route(
+                    "/users/{id}/notifications",
+                    get(list_notifications::<CS, FF>),
+                )
+                .route(
+                    "/users/{id}/notifications/unread-count",
+                    get(count_unread_notifications::<CS, FF>),
+                )
+                .route(
+                    "/users/{id}/notifications/read",
+                    post(mark_all_notifications_read::<CS, FF>),
+                )
+                .route(
+                    "/users/{id}/notifications/{notification_id}/read",
+                    post(mark_notification_read::<CS, FF>),
+                )
+                .route(
+                    "/users/{id}/email-change",
+                    delete(cancel_email_change::<CS, FF>),
+                )
+                .merge(under_current_terms(
+                    transactional(
+                        axum::Router::new().route(
+                            "/users/{id}/email-change",
+                            post(request_email_change::<CS, FF>),
+                        ),
+                        transaction_manager.clone(),
+                    ),
+                    crwdsrc_service,
+                )),
+            authentication.clone(),
+        ))
+        .merge(admin_only(
+            axum::Router::new()
+                .route("/admin/dead-letters", get(list_dead_letters::<CS, FF>))
+                .route("/admin/users/export.csv", get(export_users_csv::<CS, FF>))
+                .route("/admin/users/search", get(search_users::<CS, FF>))
+                .merge(transactional(
+                    axum::Router::new().route(
+                        "/admin/dead-letters/{id}/redrive",
+                        post(redrive_dead_letter::<CS, FF>),
+                    ),
+                    transaction_manager.clone(),
+                )),
+            authentication,
+        ))
+}
+
+/// Handles each route of `router`, whose handlers write several times, in a transaction of its
+/// own.
+fn transactional<CS: CrowdSrcService, FF: FeatureFlags, TM: TransactionManager>(
+    router: axum::Router<AppState<CS, FF>>,
+    transaction_manager: TM,
+) -> axum::Router<AppState<CS, FF>> {
+    router.route_layer(axum::middleware::from_fn_with_state(
+        transaction_manager,
+        transaction::transactional::<TM>,
+    ))
+}
+
+/// Admits only admins to the routes of `router`, authenticated by `authentication`.
+fn admin_only<CS: CrowdSrcService, FF: FeatureFlags>(
+    router: axum::Router<AppState<CS, FF>>,
+    authentication: auth::Authentication,
+) -> axum::Router<AppState<CS, FF>> {
+    router.route_layer(axum::middleware::from_fn_with_state(
+        authentication,
+        auth::require_admin,
+    ))
+}
+
+/// Admits only the user a route of `router` is for, authenticated by `authentication`.
+fn for_user<CS: CrowdSrcService, FF: FeatureFlags>(
+    router: axum::Router<AppState<CS, FF>>,
+    authentication: auth::Authentication,
+) -> axum::Router<AppState<CS, FF>> {
+    router.route_layer(axum::middleware::from_fn_with_state(
+        authentication,
+        auth::require_user,
+    ))
+}
+
+/// Admits only the user a route of `router` is for, or an admin, authenticated by `authentication`.
+fn for_user_or_admin<CS: CrowdSrcService, FF: FeatureFlags>(
+    router: axum::Router<AppState<CS, FF>>,
+    authentication: auth::Authentication,
+) -> axum::Router<AppState<CS, FF>> {
+    router.route_layer(axum::middleware::from_fn_with_state(
+        authentication,
+        auth::require_user_or_admin,
+    ))
+}
+
+/// Requires the user a route of `router` is for to have accepted the current terms of service
+/// before writing.
+fn under_current_terms<CS: CrowdSrcService, FF: FeatureFlags>(
+    router: axum::Router<AppState<CS, FF>>,
+    crwdsrc_service: Arc<CS>,
+) -> axum::Router<AppState<CS, FF>> {
+    router.route_layer(axum::middleware::from_fn_with_state(
+        crwdsrc_service,
+        terms::require_current_terms::<CS>,
+    ))
+}
+
+/// Requires a solved abuse challenge for each route of `router`, if `enabled`.
+fn challenged<CS: CrowdSrcService, FF: FeatureFlags, AC: AbuseChallenge>(
+    router: axum::Router<AppState<CS, FF>>,
+    abuse_challenge: AC,
+    enabled: bool,
+) -> axum::Router<AppState<CS, FF>> {
+    if !enabled {
+        return router;
+    }
+    router.route_layer(axum::middleware::from_fn_with_state(
+        abuse_challenge,
+        abuse_challenge::require_challenge_solution::<AC>,
+    ))
+}
+
+/// Refuses requests to the routes of `router` that may write while maintenance mode is on.
+fn under_maintenance<CS: CrowdSrcService, FF: FeatureFlags, MS: MaintenanceSwitch>(
+    router: axum::Router<AppState<CS, FF>>,
+    maintenance: maintenance::Maintenance<MS>,
+) -> axum::Router<AppState<CS, FF>> {
+    router.route_layer(axum::middleware::from_fn_with_state(
+        maintenance,
+        maintenance::refuse_writes_during_maintenance::<MS>,
+    ))
 }
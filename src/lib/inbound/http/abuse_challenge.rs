@@ -0,0 +1,117 @@
+use std::net::SocketAddr;
+
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+#[cfg(feature = "typescript")]
+use crate::inbound::http::typescript::{TypeScript, ts_object, ts_union};
+use crate::{
+    domain::crowdsrc::{
+        models::abuse_challenge::{AbuseChallengeError, Challenge},
+        ports::AbuseChallenge,
+    },
+    inbound::http::responses::{ApiError, ApiSuccess},
+};
+
+/// The header carrying the solution to a [Challenge].
+pub const CHALLENGE_SOLUTION_HEADER: &str = "x-challenge-solution";
+
+/// The routes that can be configured to require a solved [Challenge].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChallengedRoute {
+    /// `POST /api/users`
+    Signup,
+}
+
+/// Middleware that rejects requests without a valid solution to a [Challenge] in the
+/// [CHALLENGE_SOLUTION_HEADER] with 403 Forbidden.
+pub async fn require_challenge_solution<AC: AbuseChallenge>(
+    State(abuse_challenge): State<AC>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(solution) = request
+        .headers()
+        .get(CHALLENGE_SOLUTION_HEADER)
+        .and_then(|value| value.to_str().ok())
+    else {
+        return ApiError::from(AbuseChallengeError::Missing).into_response();
+    };
+    let remote_ip = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip());
+
+    match abuse_challenge.verify(solution, remote_ip).await {
+        Ok(()) => next.run(request).await,
+        Err(e) => ApiError::from(e).into_response(),
+    }
+}
+
+/// Issue a [Challenge] to solve before a challenged request.
+///
+/// # Responses
+///
+/// - 200 OK: the [Challenge].
+pub async fn issue_challenge<AC: AbuseChallenge>(
+    State(abuse_challenge): State<AC>,
+) -> Result<ApiSuccess<ChallengeData>, ApiError> {
+    abuse_challenge
+        .issue()
+        .await
+        .map_err(ApiError::from)
+        .map(|challenge| ApiSuccess::new(StatusCode::OK, challenge.into()))
+}
+
+/// The representation of a [Challenge] in responses.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[cfg_attr(feature = "client-models", derive(serde::Deserialize))]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ChallengeData {
+    ProofOfWork { challenge: String, difficulty: u8 },
+    Captcha { provider: String, site_key: String },
+}
+
+#[cfg(feature = "typescript")]
+impl TypeScript for ChallengeData {
+    fn ts_type() -> String {
+        "ChallengeData".to_string()
+    }
+
+    fn ts_declaration() -> Option<String> {
+        Some(ts_union(
+            "ChallengeData",
+            &[
+                ts_object(&[
+                    ("type", "\"proof_of_work\"".to_string()),
+                    ("challenge", String::ts_type()),
+                    ("difficulty", u8::ts_type()),
+                ]),
+                ts_object(&[
+                    ("type", "\"captcha\"".to_string()),
+                    ("provider", String::ts_type()),
+                    ("site_key", String::ts_type()),
+                ]),
+            ],
+        ))
+    }
+}
+
+impl From<Challenge> for ChallengeData {
+    fn from(challenge: Challenge) -> Self {
+        match challenge {
+            Challenge::ProofOfWork {
+                challenge,
+                difficulty,
+            } => Self::ProofOfWork {
+                challenge,
+                difficulty,
+            },
+            Challenge::Captcha { provider, site_key } => Self::Captcha { provider, site_key },
+        }
+    }
+}
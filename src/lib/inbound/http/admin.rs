@@ -0,0 +1,154 @@
+/*
+   Module `admin` serves the server-rendered admin pages, so that small deployments can be
+   administered without a separate frontend.
+
+   The templates are compiled into the crate, so the pages work wherever the binary runs.
+
+   The pages are only served to admins, who sign in with an admin access token as password.
+*/
+
+use std::sync::LazyLock;
+
+use anyhow::Context;
+use axum::{
+    extract::{Path, State},
+    response::{Html, Redirect},
+    routing::{get, post},
+};
+use axum_extra::extract::WithRejection;
+use futures::TryStreamExt;
+use uuid::Uuid;
+
+use crate::{
+    domain::crowdsrc::{
+        models::user::User,
+        ports::{CrowdSrcService, FeatureFlags},
+    },
+    inbound::http::{AppState, handlers::list_dead_letters::DeadLetterData, responses::ApiError},
+};
+
+static TEMPLATES: LazyLock<tera::Tera> = LazyLock::new(|| {
+    let mut tera = tera::Tera::default();
+    tera.add_raw_templates([
+        (
+            "base.html",
+            include_str!("../../../../templates/admin/base.html"),
+        ),
+        (
+            "overview.html",
+            include_str!("../../../../templates/admin/overview.html"),
+        ),
+        (
+            "users.html",
+            include_str!("../../../../templates/admin/users.html"),
+        ),
+        (
+            "dead_letters.html",
+            include_str!("../../../../templates/admin/dead_letters.html"),
+        ),
+    ])
+    .expect("admin templates are valid");
+    tera
+});
+
+pub fn admin_routes<CS: CrowdSrcService, FF: FeatureFlags>() -> axum::Router<AppState<CS, FF>> {
+    axum::Router::new()
+        .route("/", get(overview::<CS, FF>))
+        .route("/users", get(users::<CS, FF>))
+        .route("/dead-letters", get(dead_letters::<CS, FF>))
+        .route(
+            "/dead-letters/{id}/redrive",
+            post(redrive_dead_letter::<CS, FF>),
+        )
+}
+
+/// Counts of what needs the attention of an admin.
+async fn overview<CS: CrowdSrcService, FF: FeatureFlags>(
+    State(state): State<AppState<CS, FF>>,
+) -> Result<Html<String>, ApiError> {
+    let user_count = state
+        .crwdsrc_service
+        .stream_users()
+        .try_fold(0, |count, _| async move { Ok(count + 1) })
+        .await?;
+    let dead_letter_count = state.crwdsrc_service.list_dead_letters().await?.len();
+
+    let mut context = tera::Context::new();
+    context.insert("user_count", &user_count);
+    context.insert("dead_letter_count", &dead_letter_count);
+    render("overview.html", "Overview", context)
+}
+
+/// All [User]s, oldest first.
+async fn users<CS: CrowdSrcService, FF: FeatureFlags>(
+    State(state): State<AppState<CS, FF>>,
+) -> Result<Html<String>, ApiError> {
+    let users: Vec<UserRow> = state
+        .crwdsrc_service
+        .stream_users()
+        .map_ok(|ref user| user.into())
+        .try_collect()
+        .await?;
+
+    let mut context = tera::Context::new();
+    context.insert("users", &users);
+    render("users.html", "Users", context)
+}
+
+/// The notifications that could not be delivered, oldest first.
+async fn dead_letters<CS: CrowdSrcService, FF: FeatureFlags>(
+    State(state): State<AppState<CS, FF>>,
+) -> Result<Html<String>, ApiError> {
+    let dead_letters: Vec<DeadLetterData> = state
+        .crwdsrc_service
+        .list_dead_letters()
+        .await?
+        .iter()
+        .map(DeadLetterData::from)
+        .collect();
+
+    let mut context = tera::Context::new();
+    context.insert("dead_letters", &dead_letters);
+    render("dead_letters.html", "Dead letters", context)
+}
+
+/// Redrives a dead letter and returns to the dead letter page, whether delivery succeeded or not.
+async fn redrive_dead_letter<CS: CrowdSrcService, FF: FeatureFlags>(
+    State(state): State<AppState<CS, FF>>,
+    WithRejection(Path(id), _): WithRejection<Path<Uuid>, ApiError>,
+) -> Result<Redirect, ApiError> {
+    state.crwdsrc_service.redrive_dead_letter(&id).await?;
+    Ok(Redirect::to("/admin/dead-letters"))
+}
+
+fn render(
+    template: &str,
+    title: &str,
+    mut context: tera::Context,
+) -> Result<Html<String>, ApiError> {
+    context.insert("title", title);
+    let html = TEMPLATES
+        .render(template, &context)
+        .with_context(|| format!("failed to render admin template {}", template))?;
+    Ok(Html(html))
+}
+
+/// The representation of a [User] on the admin pages.
+#[derive(Debug, Clone, serde::Serialize)]
+struct UserRow {
+    id: String,
+    username: String,
+    email: String,
+    created_at: String,
+}
+
+impl From<&User> for UserRow {
+    fn from(user: &User) -> Self {
+        Self {
+            id: user.id().to_string(),
+            username: user.username().to_string(),
+            email: user.email().to_string(),
+            created_at: user.created_at().to_rfc3339(),
+        }
+    }
+}
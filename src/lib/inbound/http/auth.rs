@@ -0,0 +1,144 @@
+use axum::{
+    extract::{RawPathParams, Request, State},
+    http::{HeaderMap, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use base64::Engine;
+use chrono::Utc;
+
+use crate::{
+    domain::crowdsrc::models::{
+        access_token::{AccessToken, AccessTokenError, Principal},
+        signed_url::SigningKey,
+    },
+    i18n,
+    inbound::http::responses::ApiError,
+};
+
+/// The challenge of the API, whose clients send [AccessToken]s as bearer tokens.
+pub const BEARER_CHALLENGE: &str = "Bearer";
+
+/// The challenge of the admin pages, so that browsers prompt for credentials, the password
+/// being an [AccessToken].
+pub const ADMIN_PAGES_CHALLENGE: &str = r#"Basic realm="crowdsource admin", charset="UTF-8""#;
+
+/// The state of the authentication middleware.
+#[derive(Debug, Clone)]
+pub struct Authentication {
+    /// The key [AccessToken]s are verified with.
+    pub key: SigningKey,
+    /// The `WWW-Authenticate` challenge of requests without a valid [AccessToken].
+    pub challenge: &'static str,
+}
+
+impl Authentication {
+    /// Verifies the [AccessToken] in the `Authorization` header of a request, given as a bearer
+    /// token or as the password of basic credentials.
+    fn authenticate(&self, headers: &HeaderMap) -> Result<Principal, ApiError> {
+        let authorization = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok());
+        let token = match authorization.and_then(|value| value.split_once(' ')) {
+            Some((scheme, token)) if scheme.eq_ignore_ascii_case("bearer") => {
+                Some(token.trim().to_string())
+            }
+            Some((scheme, credentials)) if scheme.eq_ignore_ascii_case("basic") => {
+                basic_password(credentials.trim())
+            }
+            _ => None,
+        };
+        token
+            .ok_or(AccessTokenError::Missing)
+            .and_then(|token| AccessToken::parse(&token))
+            .and_then(|token| token.verify(&self.key, Utc::now()))
+            .map_err(|e| ApiError::unauthorized(e, self.challenge))
+    }
+}
+
+/// The password of base64 encoded `user:password` basic credentials.
+fn basic_password(credentials: &str) -> Option<String> {
+    let decoded = base64::engine::general_purpose::STANDARD
+        .decode(credentials)
+        .ok()?;
+    let credentials = String::from_utf8(decoded).ok()?;
+    let (_, password) = credentials.split_once(':')?;
+    Some(password.to_string())
+}
+
+/// Middleware that admits only requests by an admin, rejecting requests without a valid
+/// [AccessToken] with 401 Unauthorized and those by anyone else with 403 Forbidden.
+pub async fn require_admin(
+    State(authentication): State<Authentication>,
+    request: Request,
+    next: Next,
+) -> Response {
+    match authentication.authenticate(request.headers()) {
+        Ok(Principal::Admin) => next.run(request).await,
+        Ok(Principal::User(_)) => forbidden().into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// Middleware that admits only requests by the user identified by the `id` path parameter or
+/// by an admin, rejecting requests without a valid [AccessToken] with 401 Unauthorized and
+/// those by anyone else with 403 Forbidden.
+///
+/// Requests for an `id` that is not a user id are let through to be rejected by the handler.
+pub async fn require_user_or_admin(
+    State(authentication): State<Authentication>,
+    path_params: RawPathParams,
+    request: Request,
+    next: Next,
+) -> Response {
+    let principal = match authentication.authenticate(request.headers()) {
+        Ok(principal) => principal,
+        Err(e) => return e.into_response(),
+    };
+    match path_user_id(&path_params) {
+        Some(user_id) if !principal.may_act_for(&user_id) => forbidden().into_response(),
+        _ => next.run(request).await,
+    }
+}
+
+/// The user a request was admitted for by [require_user], in the request extensions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuthenticatedUser(pub uuid::Uuid);
+
+/// Middleware that admits only requests by the user identified by the `id` path parameter, not
+/// even by an admin, rejecting requests without a valid [AccessToken] with 401 Unauthorized and
+/// those by anyone else with 403 Forbidden. The user is passed on as an [AuthenticatedUser].
+///
+/// Requests for an `id` that is not a user id, or without an `id`, are let through for any
+/// user, to be rejected by the handler.
+pub async fn require_user(
+    State(authentication): State<Authentication>,
+    path_params: RawPathParams,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let user_id = match authentication.authenticate(request.headers()) {
+        Ok(Principal::User(user_id)) => user_id,
+        Ok(Principal::Admin) => return forbidden().into_response(),
+        Err(e) => return e.into_response(),
+    };
+    match path_user_id(&path_params) {
+        Some(id) if id != user_id => forbidden().into_response(),
+        _ => {
+            request.extensions_mut().insert(AuthenticatedUser(user_id));
+            next.run(request).await
+        }
+    }
+}
+
+/// The user id in the `id` path parameter, if it is one.
+pub(super) fn path_user_id(path_params: &RawPathParams) -> Option<uuid::Uuid> {
+    path_params
+        .iter()
+        .find(|(name, _)| *name == "id")
+        .and_then(|(_, value)| uuid::Uuid::parse_str(value).ok())
+}
+
+fn forbidden() -> ApiError {
+    ApiError::Forbidden(i18n::message("error.access_token.forbidden", &[]))
+}
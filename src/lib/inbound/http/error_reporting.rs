@@ -0,0 +1,13 @@
+use axum::{extract::Request, middleware::Next, response::Response};
+
+use crate::error_reporting::{self, RequestContext};
+
+/// Middleware that handles the request with its method and URI as the [RequestContext] of
+/// errors reported while handling it.
+pub async fn capture_request_context(request: Request, next: Next) -> Response {
+    let context = RequestContext {
+        method: request.method().to_string(),
+        url: request.uri().to_string(),
+    };
+    error_reporting::with_request(context, next.run(request)).await
+}
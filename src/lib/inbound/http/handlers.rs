@@ -1,2 +1,15 @@
+pub mod accept_terms;
 pub mod api_home;
 pub mod create_user;
+pub mod email_change;
+pub mod erase_user;
+pub mod export_user_data;
+pub mod export_users_csv;
+pub mod get_user;
+pub mod list_dead_letters;
+pub mod list_features;
+pub mod list_user_activity;
+pub mod notifications;
+pub mod record_analytics_events;
+pub mod redrive_dead_letter;
+pub mod search_users;
@@ -0,0 +1,76 @@
+use axum::{Json, extract::Path, extract::State, http::StatusCode};
+use axum_extra::extract::WithRejection;
+use uuid::Uuid;
+
+use crate::{
+    domain::crowdsrc::{
+        models::terms::{TermsAcceptance, TermsVersion},
+        ports::{CrowdSrcService, FeatureFlags},
+    },
+    inbound::http::{
+        AppState,
+        responses::{ApiError, ApiSuccess},
+    },
+};
+
+/// Record that a [User](crate::domain::crowdsrc::models::user::User) accepted the current terms
+/// of service.
+///
+/// # Responses
+///
+/// - 200 OK: the [TermsAcceptance] was recorded.
+/// - 401 Unauthorized: no valid access token was given.
+/// - 403 Forbidden: the access token is not the user's.
+/// - 404 Not found: no user with the given id exists.
+/// - 409 Conflict: the accepted terms of service version isn't the current one.
+pub async fn accept_terms<CS: CrowdSrcService, FF: FeatureFlags>(
+    State(state): State<AppState<CS, FF>>,
+    WithRejection(Path(user_id), _): WithRejection<Path<Uuid>, ApiError>,
+    WithRejection(Json(body), _): WithRejection<Json<AcceptTermsHttpRequestBody>, ApiError>,
+) -> Result<ApiSuccess<AcceptTermsResponseData>, ApiError> {
+    let version = TermsVersion::new(&body.terms_version)?;
+    state
+        .crwdsrc_service
+        .accept_terms(&user_id, &version)
+        .await
+        .map_err(ApiError::from)
+        .map(|ref acceptance| ApiSuccess::new(StatusCode::OK, acceptance.into()))
+}
+
+/// The body of a terms of service acceptance request.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+#[cfg_attr(feature = "client-models", derive(serde::Serialize))]
+pub struct AcceptTermsHttpRequestBody {
+    pub terms_version: String,
+}
+
+#[cfg(feature = "typescript")]
+crate::inbound::http::typescript::ts_interface!(AcceptTermsHttpRequestBody {
+    terms_version: String,
+});
+
+/// The response body data field for a successful [TermsAcceptance].
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[cfg_attr(feature = "client-models", derive(serde::Deserialize))]
+pub struct AcceptTermsResponseData {
+    pub user_id: String,
+    pub terms_version: String,
+    pub accepted_at: String,
+}
+
+#[cfg(feature = "typescript")]
+crate::inbound::http::typescript::ts_interface!(AcceptTermsResponseData {
+    user_id: String,
+    terms_version: String,
+    accepted_at: String,
+});
+
+impl From<&TermsAcceptance> for AcceptTermsResponseData {
+    fn from(acceptance: &TermsAcceptance) -> Self {
+        Self {
+            user_id: acceptance.user_id().to_string(),
+            terms_version: acceptance.terms_version().to_string(),
+            accepted_at: acceptance.accepted_at().to_rfc3339(),
+        }
+    }
+}
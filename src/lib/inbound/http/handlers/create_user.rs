@@ -1,15 +1,19 @@
 use axum::{Json, extract::State, http::StatusCode};
 use axum_extra::extract::WithRejection;
+use chrono::{DateTime, Utc};
 
 use crate::{
     domain::crowdsrc::{
+        models::terms::{TermsVersion, TermsVersionError},
         models::user::{
             CreateUserRequest, EmailAddress, EmailAddressError, User, UserName, UserNameError,
+            UserNamePolicy,
         },
-        ports::CrowdSrcService,
+        ports::{CrowdSrcService, FeatureFlags},
     },
     inbound::http::{
         AppState,
+        handlers::get_user::user_path,
         responses::{ApiError, ApiSuccess},
     },
 };
@@ -18,27 +22,39 @@ use crate::{
 ///
 /// # Responses
 ///
-/// - 201 Created: the [User] was successfully created.
+/// - 201 Created: the [User] was successfully created, and can be found at the `Location`.
+/// - 409 Conflict: the accepted terms of service version isn't the current one.
 /// - 422 Unprocessable entity: An [User] with the same name already exists.
-pub async fn create_user<CS: CrowdSrcService>(
-    State(state): State<AppState<CS>>,
+pub async fn create_user<CS: CrowdSrcService, FF: FeatureFlags>(
+    State(state): State<AppState<CS, FF>>,
     WithRejection(Json(body), _): WithRejection<Json<CreateUserHttpRequestBody>, ApiError>,
 ) -> Result<ApiSuccess<CreateUserResponseData>, ApiError> {
-    let domain_req = body.try_into_domain()?;
+    let domain_req = body.try_into_domain(&state.username_policy)?;
     state
         .crwdsrc_service
         .create_user(&domain_req)
         .await
         .map_err(ApiError::from)
-        .map(|ref user| ApiSuccess::new(StatusCode::CREATED, user.into()))
+        .map(|ref user| {
+            ApiSuccess::new(StatusCode::CREATED, user.into()).with_location(user_path(user.id()))
+        })
 }
 
 /// The body of an [User] creation request.
 #[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+#[cfg_attr(feature = "client-models", derive(serde::Serialize))]
 pub struct CreateUserHttpRequestBody {
+    pub username: String,
+    pub email_address: String,
+    pub accepted_terms_version: String,
+}
+
+#[cfg(feature = "typescript")]
+crate::inbound::http::typescript::ts_interface!(CreateUserHttpRequestBody {
     username: String,
     email_address: String,
-}
+    accepted_terms_version: String,
+});
 
 #[derive(Debug, Clone, thiserror::Error)]
 pub enum ParseCreateUserHttpRequestError {
@@ -46,45 +62,95 @@ pub enum ParseCreateUserHttpRequestError {
     Name(#[from] UserNameError),
     #[error(transparent)]
     EmailAddress(#[from] EmailAddressError),
+    #[error(transparent)]
+    TermsVersion(#[from] TermsVersionError),
 }
 
 impl CreateUserHttpRequestBody {
-    /// Converts the HTTP request body into a domain request.
-    fn try_into_domain(self) -> Result<CreateUserRequest, ParseCreateUserHttpRequestError> {
-        let name = UserName::new(&self.username)?;
+    /// Converts the HTTP request body into a domain request, validating the username under
+    /// `username_policy`.
+    pub fn try_into_domain(
+        self,
+        username_policy: &UserNamePolicy,
+    ) -> Result<CreateUserRequest, ParseCreateUserHttpRequestError> {
+        let name = UserName::with_policy(&self.username, username_policy)?;
         let email = EmailAddress::new(&self.email_address)?;
-        Ok(CreateUserRequest::new(name, email))
+        let accepted_terms = TermsVersion::new(&self.accepted_terms_version)?;
+        Ok(CreateUserRequest::new(name, email, accepted_terms))
     }
 }
 
 /// The response body data field for successful [User] creation.
 #[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[cfg_attr(feature = "client-models", derive(serde::Deserialize))]
 pub struct CreateUserResponseData {
-    id: String,
+    pub id: String,
+    pub username: String,
+    pub email_address: String,
+    pub created_at: DateTime<Utc>,
 }
 
+#[cfg(feature = "typescript")]
+crate::inbound::http::typescript::ts_interface!(CreateUserResponseData {
+    id: String,
+    username: String,
+    email_address: String,
+    created_at: DateTime<Utc>,
+});
+
 impl From<&User> for CreateUserResponseData {
     fn from(user: &User) -> Self {
         Self {
             id: user.id().to_string(),
+            username: user.username().to_string(),
+            email_address: user.email().to_string(),
+            created_at: *user.created_at(),
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
     use std::marker::PhantomData;
     use std::mem;
     use std::sync::Arc;
 
     use anyhow::anyhow;
     use chrono::Utc;
+    use futures::Stream;
+    use unicode_normalization::UnicodeNormalization;
     use uuid::Uuid;
 
+    use crate::domain::crowdsrc::models::activity::{ActivityError, ActivityPage, ActivityQuery};
+    use crate::domain::crowdsrc::models::analytics::AnalyticsError;
+    use crate::domain::crowdsrc::models::analytics::AnalyticsEvent;
+    use crate::domain::crowdsrc::models::dead_letter::DeadLetter;
+    use crate::domain::crowdsrc::models::dead_letter::DeadLetterError;
+    use crate::domain::crowdsrc::models::dead_letter::RedriveOutcome;
+    use crate::domain::crowdsrc::models::email_change::EmailChangeError;
+    use crate::domain::crowdsrc::models::email_change::EmailChangeToken;
+    use crate::domain::crowdsrc::models::email_change::PendingEmailChange;
+    use crate::domain::crowdsrc::models::inbox::Inbox;
+    use crate::domain::crowdsrc::models::inbox::InboxError;
+    use crate::domain::crowdsrc::models::inbox::InboxQuery;
+    use crate::domain::crowdsrc::models::search::SearchError;
+    use crate::domain::crowdsrc::models::search::UserSearchQuery;
+    use crate::domain::crowdsrc::models::signed_url::SigningKey;
+    use crate::domain::crowdsrc::models::strategies::{self, check};
+    use crate::domain::crowdsrc::models::terms::AcceptTermsError;
+    use crate::domain::crowdsrc::models::terms::TermsAcceptance;
     use crate::domain::crowdsrc::models::user::CreateUserError;
+    use crate::domain::crowdsrc::models::user::CreateUserOutcome;
     use crate::domain::crowdsrc::models::user::CreateUserRequest;
+    use crate::domain::crowdsrc::models::user::CreateUsersError;
+    use crate::domain::crowdsrc::models::user::EraseUserError;
+    use crate::domain::crowdsrc::models::user::GetUserError;
+    use crate::domain::crowdsrc::models::user::ListUsersError;
     use crate::domain::crowdsrc::models::user::User;
+    use crate::domain::crowdsrc::models::user::UserDataExport;
     use crate::domain::crowdsrc::ports::CrowdSrcService;
+    use crate::outbound::config_feature_flags::ConfigFeatureFlags;
 
     use super::*;
 
@@ -100,6 +166,120 @@ mod tests {
             mem::swap(guard.as_deref_mut().unwrap(), &mut result);
             result
         }
+
+        async fn create_users(
+            &self,
+            _: &[CreateUserRequest],
+        ) -> Result<Vec<CreateUserOutcome>, CreateUsersError> {
+            unimplemented!()
+        }
+
+        fn stream_users(&self) -> impl Stream<Item = Result<User, ListUsersError>> + Send {
+            futures::stream::empty()
+        }
+
+        async fn get_user(&self, _: &Uuid) -> Result<User, GetUserError> {
+            unimplemented!()
+        }
+
+        async fn export_user_data(&self, _: &Uuid) -> Result<UserDataExport, GetUserError> {
+            unimplemented!()
+        }
+
+        async fn erase_user(&self, _: &Uuid) -> Result<User, EraseUserError> {
+            unimplemented!()
+        }
+
+        async fn accept_terms(
+            &self,
+            _: &Uuid,
+            _: &TermsVersion,
+        ) -> Result<TermsAcceptance, AcceptTermsError> {
+            unimplemented!()
+        }
+
+        async fn check_current_terms(&self, _: &Uuid) -> Result<(), AcceptTermsError> {
+            unimplemented!()
+        }
+
+        async fn list_dead_letters(&self) -> Result<Vec<DeadLetter>, DeadLetterError> {
+            unimplemented!()
+        }
+
+        async fn redrive_dead_letter(&self, _: &Uuid) -> Result<RedriveOutcome, DeadLetterError> {
+            unimplemented!()
+        }
+
+        async fn list_user_activity(
+            &self,
+            _: &Uuid,
+            _: &ActivityQuery,
+        ) -> Result<ActivityPage, ActivityError> {
+            unimplemented!()
+        }
+
+        async fn request_email_change(
+            &self,
+            _: &Uuid,
+            _: &EmailAddress,
+        ) -> Result<PendingEmailChange, EmailChangeError> {
+            unimplemented!()
+        }
+
+        async fn confirm_email_change(
+            &self,
+            _: &Uuid,
+            _: &EmailChangeToken,
+        ) -> Result<User, EmailChangeError> {
+            unimplemented!()
+        }
+
+        async fn cancel_email_change(&self, _: &Uuid) -> Result<(), EmailChangeError> {
+            unimplemented!()
+        }
+
+        async fn purge_expired_email_changes(&self) -> Result<u64, EmailChangeError> {
+            unimplemented!()
+        }
+
+        async fn list_notifications(
+            &self,
+            _: &uuid::Uuid,
+            _: &InboxQuery,
+        ) -> Result<Inbox, InboxError> {
+            unimplemented!()
+        }
+
+        async fn count_unread_notifications(&self, _: &uuid::Uuid) -> Result<u64, InboxError> {
+            unimplemented!()
+        }
+
+        async fn mark_notification_read(
+            &self,
+            _: &uuid::Uuid,
+            _: &uuid::Uuid,
+        ) -> Result<(), InboxError> {
+            unimplemented!()
+        }
+
+        async fn mark_all_notifications_read(&self, _: &uuid::Uuid) -> Result<u64, InboxError> {
+            unimplemented!()
+        }
+
+        async fn purge_old_notifications(&self) -> Result<u64, InboxError> {
+            unimplemented!()
+        }
+
+        async fn search_users(&self, _: &UserSearchQuery) -> Result<Vec<User>, SearchError> {
+            unimplemented!()
+        }
+
+        async fn record_analytics_events(
+            &self,
+            _: Vec<AnalyticsEvent>,
+        ) -> Result<(), AnalyticsError> {
+            unimplemented!()
+        }
     }
 
     async fn run_create_user(
@@ -109,11 +289,15 @@ mod tests {
     ) -> Result<ApiSuccess<CreateUserResponseData>, ApiError> {
         let state = axum::extract::State(AppState {
             crwdsrc_service: Arc::new(service),
+            feature_flags: Arc::new(ConfigFeatureFlags::new(HashMap::new())),
+            signing_key: SigningKey::new(b"key"),
+            username_policy: UserNamePolicy::default(),
         });
         let body = WithRejection(
             axum::extract::Json(CreateUserHttpRequestBody {
                 username: user_name.to_string(),
                 email_address: user_email.to_string(),
+                accepted_terms_version: "2026-01-30".to_string(),
             }),
             PhantomData,
         );
@@ -206,8 +390,12 @@ mod tests {
             StatusCode::CREATED,
             CreateUserResponseData {
                 id: user_id.to_string(),
+                username: user_name.to_string(),
+                email_address: user_email.to_string(),
+                created_at,
             },
-        );
+        )
+        .with_location(format!("/api/users/{user_id}"));
         let actual = actual.unwrap();
         assert_eq!(
             actual, expected,
@@ -215,4 +403,30 @@ mod tests {
             expected, actual
         )
     }
+
+    #[test]
+    fn test_request_body_parses_to_normalized_username() {
+        check(
+            |rng| {
+                serde_json::json!({
+                    "username": strategies::raw_username(rng),
+                    "email_address": strategies::raw_email(rng),
+                    "accepted_terms_version": "2026-01-30",
+                })
+                .to_string()
+            },
+            |json| {
+                let body: CreateUserHttpRequestBody =
+                    serde_json::from_str(&json).expect("generated body is valid");
+                let username = body.username.clone();
+                if let Ok(req) = body.try_into_domain(&UserNamePolicy::default()) {
+                    assert_eq!(
+                        req.username().to_string(),
+                        username.trim().nfc().collect::<String>(),
+                        "parsing {json}"
+                    );
+                }
+            },
+        );
+    }
 }
@@ -0,0 +1,174 @@
+use axum::{Json, extract::Path, extract::State, http::StatusCode};
+use axum_extra::extract::WithRejection;
+use uuid::Uuid;
+
+use crate::{
+    domain::crowdsrc::{
+        models::{
+            email_change::{EmailChangeToken, PendingEmailChange},
+            user::{EmailAddress, User},
+        },
+        ports::{CrowdSrcService, FeatureFlags},
+    },
+    inbound::http::{
+        AppState,
+        responses::{ApiError, ApiSuccess},
+    },
+};
+
+/// Request that the email of a [User] is changed, sending a token confirming it to the new
+/// address and a notice to the current one.
+///
+/// # Responses
+///
+/// - 202 Accepted: the [PendingEmailChange], awaiting confirmation.
+/// - 401 Unauthorized: no valid access token was given.
+/// - 403 Forbidden: the access token is not the [User]'s.
+/// - 404 Not found: no [User] with the given id exists.
+/// - 409 Conflict: the [User] has not accepted the current terms of service.
+/// - 422 Unprocessable entity: the email is invalid or another [User] has it.
+pub async fn request_email_change<CS: CrowdSrcService, FF: FeatureFlags>(
+    State(state): State<AppState<CS, FF>>,
+    WithRejection(Path(user_id), _): WithRejection<Path<Uuid>, ApiError>,
+    WithRejection(Json(body), _): WithRejection<Json<RequestEmailChangeHttpRequestBody>, ApiError>,
+) -> Result<ApiSuccess<EmailChangeResponseData>, ApiError> {
+    let new_email = EmailAddress::new(&body.email)?;
+    state
+        .crwdsrc_service
+        .request_email_change(&user_id, &new_email)
+        .await
+        .map_err(ApiError::from)
+        .map(|ref change| ApiSuccess::new(StatusCode::ACCEPTED, change.into()))
+}
+
+/// Confirm the pending email change of a [User] with the token sent to the new address.
+///
+/// # Responses
+///
+/// - 200 OK: the email of the [User] was changed.
+/// - 403 Forbidden: the token is not that of the pending change.
+/// - 404 Not found: the [User] has no pending email change.
+/// - 409 Conflict: the pending change has expired, and has been discarded.
+/// - 422 Unprocessable entity: another [User] has taken the new email since.
+pub async fn confirm_email_change<CS: CrowdSrcService, FF: FeatureFlags>(
+    State(state): State<AppState<CS, FF>>,
+    WithRejection(Path(user_id), _): WithRejection<Path<Uuid>, ApiError>,
+    WithRejection(Json(body), _): WithRejection<Json<ConfirmEmailChangeHttpRequestBody>, ApiError>,
+) -> Result<ApiSuccess<ConfirmEmailChangeResponseData>, ApiError> {
+    state
+        .crwdsrc_service
+        .confirm_email_change(&user_id, &EmailChangeToken::new(&body.token))
+        .await
+        .map_err(ApiError::from)
+        .map(|ref user| ApiSuccess::new(StatusCode::OK, user.into()))
+}
+
+/// Cancel the pending email change of a [User], keeping its email.
+///
+/// # Responses
+///
+/// - 200 OK: the pending change was discarded.
+/// - 401 Unauthorized: no valid access token was given.
+/// - 403 Forbidden: the access token is not the [User]'s.
+/// - 404 Not found: the [User] has no pending email change.
+pub async fn cancel_email_change<CS: CrowdSrcService, FF: FeatureFlags>(
+    State(state): State<AppState<CS, FF>>,
+    WithRejection(Path(user_id), _): WithRejection<Path<Uuid>, ApiError>,
+) -> Result<ApiSuccess<CancelEmailChangeResponseData>, ApiError> {
+    state
+        .crwdsrc_service
+        .cancel_email_change(&user_id)
+        .await
+        .map_err(ApiError::from)
+        .map(|()| {
+            ApiSuccess::new(
+                StatusCode::OK,
+                CancelEmailChangeResponseData {
+                    user_id: user_id.to_string(),
+                },
+            )
+        })
+}
+
+/// The body of an email change request.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+#[cfg_attr(feature = "client-models", derive(serde::Serialize))]
+pub struct RequestEmailChangeHttpRequestBody {
+    pub email: String,
+}
+
+#[cfg(feature = "typescript")]
+crate::inbound::http::typescript::ts_interface!(RequestEmailChangeHttpRequestBody {
+    email: String,
+});
+
+/// The body of an email change confirmation.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+#[cfg_attr(feature = "client-models", derive(serde::Serialize))]
+pub struct ConfirmEmailChangeHttpRequestBody {
+    pub token: String,
+}
+
+#[cfg(feature = "typescript")]
+crate::inbound::http::typescript::ts_interface!(ConfirmEmailChangeHttpRequestBody {
+    token: String,
+});
+
+/// The response body data field for a requested [PendingEmailChange].
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[cfg_attr(feature = "client-models", derive(serde::Deserialize))]
+pub struct EmailChangeResponseData {
+    pub user_id: String,
+    pub new_email: String,
+    pub expires_at: String,
+}
+
+#[cfg(feature = "typescript")]
+crate::inbound::http::typescript::ts_interface!(EmailChangeResponseData {
+    user_id: String,
+    new_email: String,
+    expires_at: String,
+});
+
+impl From<&PendingEmailChange> for EmailChangeResponseData {
+    fn from(change: &PendingEmailChange) -> Self {
+        Self {
+            user_id: change.user_id().to_string(),
+            new_email: change.new_email().to_string(),
+            expires_at: change.expires_at().to_rfc3339(),
+        }
+    }
+}
+
+/// The response body data field for a confirmed email change.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[cfg_attr(feature = "client-models", derive(serde::Deserialize))]
+pub struct ConfirmEmailChangeResponseData {
+    pub id: String,
+    pub email: String,
+}
+
+#[cfg(feature = "typescript")]
+crate::inbound::http::typescript::ts_interface!(ConfirmEmailChangeResponseData {
+    id: String,
+    email: String,
+});
+
+impl From<&User> for ConfirmEmailChangeResponseData {
+    fn from(user: &User) -> Self {
+        Self {
+            id: user.id().to_string(),
+            email: user.email().to_string(),
+        }
+    }
+}
+
+/// The response body data field for a cancelled email change.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[cfg_attr(feature = "client-models", derive(serde::Deserialize))]
+pub struct CancelEmailChangeResponseData {
+    pub user_id: String,
+}
+
+#[cfg(feature = "typescript")]
+crate::inbound::http::typescript::ts_interface!(CancelEmailChangeResponseData { user_id: String });
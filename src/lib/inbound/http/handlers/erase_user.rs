@@ -0,0 +1,52 @@
+use axum::{extract::Path, extract::State, http::StatusCode};
+use axum_extra::extract::WithRejection;
+use uuid::Uuid;
+
+use crate::{
+    domain::crowdsrc::{
+        models::user::User,
+        ports::{CrowdSrcService, FeatureFlags},
+    },
+    inbound::http::{
+        AppState,
+        responses::{ApiError, ApiSuccess},
+    },
+};
+
+/// Erase the personal data of an [User].
+///
+/// # Responses
+///
+/// - 200 OK: the personal data of the [User] was erased.
+/// - 401 Unauthorized: no valid access token was given.
+/// - 403 Forbidden: the access token is neither the [User]'s nor an admin's.
+/// - 404 Not found: no [User] with the given id exists.
+pub async fn erase_user<CS: CrowdSrcService, FF: FeatureFlags>(
+    State(state): State<AppState<CS, FF>>,
+    WithRejection(Path(id), _): WithRejection<Path<Uuid>, ApiError>,
+) -> Result<ApiSuccess<EraseUserResponseData>, ApiError> {
+    state
+        .crwdsrc_service
+        .erase_user(&id)
+        .await
+        .map_err(ApiError::from)
+        .map(|ref user| ApiSuccess::new(StatusCode::OK, user.into()))
+}
+
+/// The response body data field for successful [User] erasure.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[cfg_attr(feature = "client-models", derive(serde::Deserialize))]
+pub struct EraseUserResponseData {
+    pub id: String,
+}
+
+#[cfg(feature = "typescript")]
+crate::inbound::http::typescript::ts_interface!(EraseUserResponseData { id: String });
+
+impl From<&User> for EraseUserResponseData {
+    fn from(user: &User) -> Self {
+        Self {
+            id: user.id().to_string(),
+        }
+    }
+}
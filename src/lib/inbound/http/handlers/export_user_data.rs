@@ -0,0 +1,192 @@
+use axum::{extract::Path, extract::State, http::StatusCode};
+use axum_extra::extract::WithRejection;
+use chrono::{TimeDelta, Utc};
+use uuid::Uuid;
+
+use crate::{
+    domain::crowdsrc::{
+        models::signed_url::SignedUrl,
+        models::terms::TermsAcceptance,
+        models::user::{User, UserDataExport},
+        ports::{CrowdSrcService, FeatureFlags},
+    },
+    inbound::http::{
+        AppState,
+        responses::{ApiError, ApiSuccess},
+        signed_url::VerifiedSignedUrl,
+    },
+};
+
+/// The version of the [UserDataExportResponseData] format, bumped on breaking changes.
+const USER_DATA_EXPORT_FORMAT_VERSION: u32 = 1;
+
+/// How long a link to a shared export can be used.
+const SHARED_EXPORT_TTL: TimeDelta = TimeDelta::hours(24);
+
+/// Export all data held about an [User].
+///
+/// # Responses
+///
+/// - 200 OK: the exported data.
+/// - 401 Unauthorized: no valid access token was given.
+/// - 403 Forbidden: the access token is neither the [User]'s nor an admin's.
+/// - 404 Not found: no [User] with the given id exists.
+pub async fn export_user_data<CS: CrowdSrcService, FF: FeatureFlags>(
+    State(state): State<AppState<CS, FF>>,
+    WithRejection(Path(id), _): WithRejection<Path<Uuid>, ApiError>,
+) -> Result<ApiSuccess<UserDataExportResponseData>, ApiError> {
+    state
+        .crwdsrc_service
+        .export_user_data(&id)
+        .await
+        .map_err(ApiError::from)
+        .map(|ref export| ApiSuccess::new(StatusCode::OK, export.into()))
+}
+
+/// Create a link to the data export of an [User] that can be used without authenticating until
+/// it expires.
+///
+/// The existence of the [User] is checked when the link is used, not when it is created.
+///
+/// # Responses
+///
+/// - 201 Created: the [SharedLinkData].
+/// - 401 Unauthorized: no valid access token was given.
+/// - 403 Forbidden: the access token is not the [User]'s.
+pub async fn share_user_data_export<CS: CrowdSrcService, FF: FeatureFlags>(
+    State(state): State<AppState<CS, FF>>,
+    WithRejection(Path(id), _): WithRejection<Path<Uuid>, ApiError>,
+) -> Result<ApiSuccess<SharedLinkData>, ApiError> {
+    let url = SignedUrl::sign(
+        &state.signing_key,
+        &format!("/api/shared/users/{id}/data-export"),
+        Utc::now() + SHARED_EXPORT_TTL,
+    );
+    Ok(ApiSuccess::new(StatusCode::CREATED, (&url).into()))
+}
+
+/// Export all data held about an [User] through a link created by [share_user_data_export].
+///
+/// # Responses
+///
+/// - 200 OK: the exported data.
+/// - 403 Forbidden: the link is unsigned, tampered with or expired.
+/// - 404 Not found: no [User] with the given id exists.
+pub async fn export_shared_user_data<CS: CrowdSrcService, FF: FeatureFlags>(
+    _: VerifiedSignedUrl,
+    state: State<AppState<CS, FF>>,
+    id: WithRejection<Path<Uuid>, ApiError>,
+) -> Result<ApiSuccess<UserDataExportResponseData>, ApiError> {
+    export_user_data(state, id).await
+}
+
+/// The response body data field for a shared link.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[cfg_attr(feature = "client-models", derive(serde::Deserialize))]
+pub struct SharedLinkData {
+    pub url: String,
+    pub expires_at: String,
+}
+
+#[cfg(feature = "typescript")]
+crate::inbound::http::typescript::ts_interface!(SharedLinkData {
+    url: String,
+    expires_at: String,
+});
+
+impl From<&SignedUrl> for SharedLinkData {
+    fn from(url: &SignedUrl) -> Self {
+        Self {
+            url: url.to_string(),
+            expires_at: url.expires_at().to_rfc3339(),
+        }
+    }
+}
+
+/// The response body data field for a successful [User] data export.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[cfg_attr(feature = "client-models", derive(serde::Deserialize))]
+pub struct UserDataExportResponseData {
+    pub format_version: u32,
+    pub exported_at: String,
+    pub user: ExportedUser,
+    pub terms_acceptances: Vec<ExportedTermsAcceptance>,
+}
+
+#[cfg(feature = "typescript")]
+crate::inbound::http::typescript::ts_interface!(UserDataExportResponseData {
+    format_version: u32,
+    exported_at: String,
+    user: ExportedUser,
+    terms_acceptances: Vec<ExportedTermsAcceptance>,
+});
+
+/// The exported fields of an [User].
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[cfg_attr(feature = "client-models", derive(serde::Deserialize))]
+pub struct ExportedUser {
+    pub id: String,
+    pub username: String,
+    pub email_address: String,
+    pub email_verified: bool,
+    pub created_at: String,
+}
+
+#[cfg(feature = "typescript")]
+crate::inbound::http::typescript::ts_interface!(ExportedUser {
+    id: String,
+    username: String,
+    email_address: String,
+    email_verified: bool,
+    created_at: String,
+});
+
+impl From<&User> for ExportedUser {
+    fn from(user: &User) -> Self {
+        Self {
+            id: user.id().to_string(),
+            username: user.username().to_string(),
+            email_address: user.email().to_string(),
+            email_verified: user.is_email_verified(),
+            created_at: user.created_at().to_rfc3339(),
+        }
+    }
+}
+
+/// The exported fields of a [TermsAcceptance].
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[cfg_attr(feature = "client-models", derive(serde::Deserialize))]
+pub struct ExportedTermsAcceptance {
+    pub terms_version: String,
+    pub accepted_at: String,
+}
+
+#[cfg(feature = "typescript")]
+crate::inbound::http::typescript::ts_interface!(ExportedTermsAcceptance {
+    terms_version: String,
+    accepted_at: String,
+});
+
+impl From<&TermsAcceptance> for ExportedTermsAcceptance {
+    fn from(acceptance: &TermsAcceptance) -> Self {
+        Self {
+            terms_version: acceptance.terms_version().to_string(),
+            accepted_at: acceptance.accepted_at().to_rfc3339(),
+        }
+    }
+}
+
+impl From<&UserDataExport> for UserDataExportResponseData {
+    fn from(export: &UserDataExport) -> Self {
+        Self {
+            format_version: USER_DATA_EXPORT_FORMAT_VERSION,
+            exported_at: export.exported_at().to_rfc3339(),
+            user: export.user().into(),
+            terms_acceptances: export
+                .terms_acceptances()
+                .iter()
+                .map(ExportedTermsAcceptance::from)
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use axum::{extract::Path, extract::State, http::StatusCode};
+use axum_extra::extract::WithRejection;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::{
+    domain::crowdsrc::{
+        models::user::User,
+        ports::{CrowdSrcService, FeatureFlags},
+    },
+    inbound::http::{
+        AppState,
+        responses::{ApiError, ApiSuccess},
+    },
+};
+
+/// Get an [User], with their email, so only for the [User] or an admin.
+///
+/// # Responses
+///
+/// - 200 OK: the [User].
+/// - 401 Unauthorized: no valid access token was given.
+/// - 403 Forbidden: the access token is neither the [User]'s nor an admin's.
+/// - 404 Not found: no [User] with the given id exists.
+pub async fn get_user<CS: CrowdSrcService, FF: FeatureFlags>(
+    State(state): State<AppState<CS, FF>>,
+    WithRejection(Path(id), _): WithRejection<Path<Uuid>, ApiError>,
+) -> Result<ApiSuccess<UserResponseData>, ApiError> {
+    state
+        .crwdsrc_service
+        .get_user(&id)
+        .await
+        .map_err(ApiError::from)
+        .map(|ref user| ApiSuccess::new(StatusCode::OK, user.into()))
+}
+
+/// The path of the [User] with the given id, as served by [get_user].
+pub fn user_path(id: &Uuid) -> String {
+    format!("/api/users/{id}")
+}
+
+/// The response body data field for an [User].
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[cfg_attr(feature = "client-models", derive(serde::Deserialize))]
+pub struct UserResponseData {
+    pub id: String,
+    pub username: String,
+    pub email_address: String,
+    pub email_verified: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+#[cfg(feature = "typescript")]
+crate::inbound::http::typescript::ts_interface!(UserResponseData {
+    id: String,
+    username: String,
+    email_address: String,
+    email_verified: bool,
+    created_at: DateTime<Utc>,
+});
+
+impl From<&User> for UserResponseData {
+    fn from(user: &User) -> Self {
+        Self {
+            id: user.id().to_string(),
+            username: user.username().to_string(),
+            email_address: user.email().to_string(),
+            email_verified: user.is_email_verified(),
+            created_at: *user.created_at(),
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use axum::{extract::State, http::StatusCode};
+
+use crate::{
+    domain::crowdsrc::{
+        models::dead_letter::{DeadLetter, NotificationEvent},
+        ports::{CrowdSrcService, FeatureFlags},
+    },
+    inbound::http::{
+        AppState,
+        responses::{ApiError, ApiSuccess},
+    },
+};
+
+/// List the notifications that could not be delivered.
+///
+/// # Responses
+///
+/// - 200 OK: the [DeadLetter]s, oldest first.
+/// - 401 Unauthorized: no valid access token was given.
+/// - 403 Forbidden: the access token is not an admin's.
+pub async fn list_dead_letters<CS: CrowdSrcService, FF: FeatureFlags>(
+    State(state): State<AppState<CS, FF>>,
+) -> Result<ApiSuccess<Vec<DeadLetterData>>, ApiError> {
+    state
+        .crwdsrc_service
+        .list_dead_letters()
+        .await
+        .map_err(ApiError::from)
+        .map(|dead_letters| {
+            ApiSuccess::new(
+                StatusCode::OK,
+                dead_letters.iter().map(DeadLetterData::from).collect(),
+            )
+        })
+}
+
+/// The representation of a [DeadLetter] in responses.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[cfg_attr(feature = "client-models", derive(serde::Deserialize))]
+pub struct DeadLetterData {
+    pub id: String,
+    pub event_type: String,
+    pub user_id: String,
+    pub failure_reason: String,
+    pub retry_count: u32,
+    pub created_at: String,
+    pub last_failed_at: String,
+}
+
+#[cfg(feature = "typescript")]
+crate::inbound::http::typescript::ts_interface!(DeadLetterData {
+    id: String,
+    event_type: String,
+    user_id: String,
+    failure_reason: String,
+    retry_count: u32,
+    created_at: String,
+    last_failed_at: String,
+});
+
+impl From<&DeadLetter> for DeadLetterData {
+    fn from(dead_letter: &DeadLetter) -> Self {
+        let event_type = match dead_letter.event() {
+            NotificationEvent::UserCreated { .. } => "user_created",
+        };
+        Self {
+            id: dead_letter.id().to_string(),
+            event_type: event_type.to_string(),
+            user_id: dead_letter.event().user_id().to_string(),
+            failure_reason: dead_letter.failure_reason().to_string(),
+            retry_count: dead_letter.retry_count(),
+            created_at: dead_letter.created_at().to_rfc3339(),
+            last_failed_at: dead_letter.last_failed_at().to_rfc3339(),
+        }
+    }
+}
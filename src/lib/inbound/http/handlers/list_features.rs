@@ -0,0 +1,37 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+};
+
+use crate::{
+    domain::crowdsrc::ports::{CrowdSrcService, FeatureFlags},
+    inbound::http::{AppState, responses::ApiSuccess},
+};
+
+/// List the feature flags enabled for a tenant, or globally if no tenant is given.
+///
+/// # Responses
+///
+/// - 200 OK: the names of the enabled flags.
+pub async fn list_features<CS: CrowdSrcService, FF: FeatureFlags>(
+    State(state): State<AppState<CS, FF>>,
+    Query(query): Query<ListFeaturesQuery>,
+) -> ApiSuccess<Vec<String>> {
+    let flags = state.feature_flags.enabled(query.tenant.as_deref()).await;
+    ApiSuccess::new(
+        StatusCode::OK,
+        flags.iter().map(|flag| flag.to_string()).collect(),
+    )
+}
+
+/// The query parameters of [list_features].
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+#[cfg_attr(feature = "client-models", derive(serde::Serialize))]
+pub struct ListFeaturesQuery {
+    pub tenant: Option<String>,
+}
+
+#[cfg(feature = "typescript")]
+crate::inbound::http::typescript::ts_interface!(ListFeaturesQuery {
+    tenant as "tenant?": Option<String>,
+});
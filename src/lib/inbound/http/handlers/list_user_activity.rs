@@ -0,0 +1,123 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+use axum_extra::extract::WithRejection;
+use uuid::Uuid;
+
+use crate::{
+    domain::crowdsrc::{
+        models::activity::{Activity, ActivityCursor, ActivityKind, ActivityPage, ActivityQuery},
+        ports::{CrowdSrcService, FeatureFlags},
+    },
+    inbound::http::{
+        AppState,
+        responses::{ApiError, ApiSuccess},
+    },
+};
+
+/// How many entries a page holds unless the client asks for another limit.
+const DEFAULT_LIMIT: u32 = 20;
+
+/// List a page of the activity feed of an [User](crate::domain::crowdsrc::models::user::User),
+/// newest first.
+///
+/// # Responses
+///
+/// - 200 OK: the page, with the cursor of the next page if there is one.
+/// - 401 Unauthorized: no valid access token was given.
+/// - 403 Forbidden: the access token is neither the user's nor an admin's.
+/// - 404 Not found: no user with the given id exists.
+/// - 422 Unprocessable entity: the cursor is invalid.
+pub async fn list_user_activity<CS: CrowdSrcService, FF: FeatureFlags>(
+    State(state): State<AppState<CS, FF>>,
+    WithRejection(Path(id), _): WithRejection<Path<Uuid>, ApiError>,
+    WithRejection(Query(params), _): WithRejection<Query<ListUserActivityParams>, ApiError>,
+) -> Result<ApiSuccess<ActivityPageData>, ApiError> {
+    let after = params
+        .after
+        .as_deref()
+        .map(ActivityCursor::parse)
+        .transpose()?;
+    let query = ActivityQuery::new(params.limit.unwrap_or(DEFAULT_LIMIT), after);
+    state
+        .crwdsrc_service
+        .list_user_activity(&id, &query)
+        .await
+        .map_err(ApiError::from)
+        .map(|ref page| ApiSuccess::new(StatusCode::OK, page.into()))
+}
+
+/// The query parameters of [list_user_activity].
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+#[cfg_attr(feature = "client-models", derive(serde::Serialize))]
+pub struct ListUserActivityParams {
+    pub limit: Option<u32>,
+    pub after: Option<String>,
+}
+
+#[cfg(feature = "typescript")]
+crate::inbound::http::typescript::ts_interface!(ListUserActivityParams {
+    limit as "limit?": Option<u32>,
+    after as "after?": Option<String>,
+});
+
+/// The representation of an [ActivityPage] in responses.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[cfg_attr(feature = "client-models", derive(serde::Deserialize))]
+pub struct ActivityPageData {
+    pub activities: Vec<ActivityData>,
+    pub next_cursor: Option<String>,
+}
+
+#[cfg(feature = "typescript")]
+crate::inbound::http::typescript::ts_interface!(ActivityPageData {
+    activities: Vec<ActivityData>,
+    next_cursor: Option<String>,
+});
+
+impl From<&ActivityPage> for ActivityPageData {
+    fn from(page: &ActivityPage) -> Self {
+        Self {
+            activities: page.activities().iter().map(ActivityData::from).collect(),
+            next_cursor: page.next().map(|cursor| cursor.to_string()),
+        }
+    }
+}
+
+/// The representation of an [Activity] in responses.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[cfg_attr(feature = "client-models", derive(serde::Deserialize))]
+pub struct ActivityData {
+    #[serde(rename = "type")]
+    pub activity_type: String,
+    pub user_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub terms_version: Option<String>,
+    pub occurred_at: String,
+}
+
+#[cfg(feature = "typescript")]
+crate::inbound::http::typescript::ts_interface!(ActivityData {
+    activity_type as "type": String,
+    user_id: String,
+    terms_version as "terms_version?": Option<String>,
+    occurred_at: String,
+});
+
+impl From<&Activity> for ActivityData {
+    fn from(activity: &Activity) -> Self {
+        let (activity_type, terms_version) = match activity.kind() {
+            ActivityKind::SignedUp => ("signed_up", None),
+            ActivityKind::AcceptedTerms { version } => {
+                ("accepted_terms", Some(version.to_string()))
+            }
+        };
+        Self {
+            activity_type: activity_type.to_string(),
+            user_id: activity.user_id().to_string(),
+            terms_version,
+            occurred_at: activity.occurred_at().to_rfc3339(),
+        }
+    }
+}
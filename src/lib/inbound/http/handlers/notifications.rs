@@ -0,0 +1,202 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+use axum_extra::extract::WithRejection;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::{
+    domain::crowdsrc::{
+        models::inbox::{Inbox, InboxNotification, InboxQuery},
+        ports::{CrowdSrcService, FeatureFlags},
+    },
+    i18n,
+    inbound::http::{
+        AppState,
+        responses::{ApiError, ApiSuccess},
+    },
+};
+
+/// How many notifications are listed unless the client asks for another limit.
+const DEFAULT_LIMIT: u32 = 20;
+
+/// List the newest notifications in the inbox of a user, with how many are unread, each with
+/// its message in the locale of the request.
+///
+/// # Responses
+///
+/// - 200 OK: the notifications, newest first, and the unread count.
+/// - 401 Unauthorized: no valid access token was given.
+/// - 403 Forbidden: the access token is not the user's.
+/// - 404 Not found: no user with the given id exists.
+pub async fn list_notifications<CS: CrowdSrcService, FF: FeatureFlags>(
+    State(state): State<AppState<CS, FF>>,
+    WithRejection(Path(user_id), _): WithRejection<Path<Uuid>, ApiError>,
+    WithRejection(Query(params), _): WithRejection<Query<ListNotificationsParams>, ApiError>,
+) -> Result<ApiSuccess<InboxData>, ApiError> {
+    let query = InboxQuery::new(
+        params.limit.unwrap_or(DEFAULT_LIMIT),
+        params.unread_only.unwrap_or(false),
+    );
+    state
+        .crwdsrc_service
+        .list_notifications(&user_id, &query)
+        .await
+        .map_err(ApiError::from)
+        .map(|ref inbox| ApiSuccess::new(StatusCode::OK, inbox.into()))
+}
+
+/// Count the unread notifications in the inbox of a user, as shown on a bell icon.
+///
+/// # Responses
+///
+/// - 200 OK: the unread count.
+/// - 401 Unauthorized: no valid access token was given.
+/// - 403 Forbidden: the access token is not the user's.
+/// - 404 Not found: no user with the given id exists.
+pub async fn count_unread_notifications<CS: CrowdSrcService, FF: FeatureFlags>(
+    State(state): State<AppState<CS, FF>>,
+    WithRejection(Path(user_id), _): WithRejection<Path<Uuid>, ApiError>,
+) -> Result<ApiSuccess<UnreadCountData>, ApiError> {
+    state
+        .crwdsrc_service
+        .count_unread_notifications(&user_id)
+        .await
+        .map_err(ApiError::from)
+        .map(|unread_count| ApiSuccess::new(StatusCode::OK, UnreadCountData { unread_count }))
+}
+
+/// Mark a notification in the inbox of a user as read.
+///
+/// # Responses
+///
+/// - 204 No content: the notification is read.
+/// - 401 Unauthorized: no valid access token was given.
+/// - 403 Forbidden: the access token is not the user's.
+/// - 404 Not found: the user has no notification with the given id.
+pub async fn mark_notification_read<CS: CrowdSrcService, FF: FeatureFlags>(
+    State(state): State<AppState<CS, FF>>,
+    WithRejection(Path((user_id, notification_id)), _): WithRejection<Path<(Uuid, Uuid)>, ApiError>,
+) -> Result<StatusCode, ApiError> {
+    state
+        .crwdsrc_service
+        .mark_notification_read(&user_id, &notification_id)
+        .await
+        .map_err(ApiError::from)
+        .map(|()| StatusCode::NO_CONTENT)
+}
+
+/// Mark all notifications in the inbox of a user as read.
+///
+/// # Responses
+///
+/// - 200 OK: how many notifications were unread until now.
+/// - 401 Unauthorized: no valid access token was given.
+/// - 403 Forbidden: the access token is not the user's.
+/// - 404 Not found: no user with the given id exists.
+pub async fn mark_all_notifications_read<CS: CrowdSrcService, FF: FeatureFlags>(
+    State(state): State<AppState<CS, FF>>,
+    WithRejection(Path(user_id), _): WithRejection<Path<Uuid>, ApiError>,
+) -> Result<ApiSuccess<MarkedReadData>, ApiError> {
+    state
+        .crwdsrc_service
+        .mark_all_notifications_read(&user_id)
+        .await
+        .map_err(ApiError::from)
+        .map(|marked_read| ApiSuccess::new(StatusCode::OK, MarkedReadData { marked_read }))
+}
+
+/// The query parameters of [list_notifications].
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+#[cfg_attr(feature = "client-models", derive(serde::Serialize))]
+pub struct ListNotificationsParams {
+    pub limit: Option<u32>,
+    pub unread_only: Option<bool>,
+}
+
+#[cfg(feature = "typescript")]
+crate::inbound::http::typescript::ts_interface!(ListNotificationsParams {
+    limit as "limit?": Option<u32>,
+    unread_only as "unread_only?": Option<bool>,
+});
+
+/// The representation of an [Inbox] in responses.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[cfg_attr(feature = "client-models", derive(serde::Deserialize))]
+pub struct InboxData {
+    pub notifications: Vec<NotificationData>,
+    pub unread_count: u64,
+}
+
+#[cfg(feature = "typescript")]
+crate::inbound::http::typescript::ts_interface!(InboxData {
+    notifications: Vec<NotificationData>,
+    unread_count: u64,
+});
+
+impl From<&Inbox> for InboxData {
+    fn from(inbox: &Inbox) -> Self {
+        Self {
+            notifications: inbox
+                .notifications()
+                .iter()
+                .map(NotificationData::from)
+                .collect(),
+            unread_count: inbox.unread_count(),
+        }
+    }
+}
+
+/// The representation of an [InboxNotification] in responses.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[cfg_attr(feature = "client-models", derive(serde::Deserialize))]
+pub struct NotificationData {
+    pub id: String,
+    pub kind: String,
+    pub message: String,
+    pub created_at: DateTime<Utc>,
+    pub read_at: Option<DateTime<Utc>>,
+}
+
+#[cfg(feature = "typescript")]
+crate::inbound::http::typescript::ts_interface!(NotificationData {
+    id: String,
+    kind: String,
+    message: String,
+    created_at: DateTime<Utc>,
+    read_at: Option<DateTime<Utc>>,
+});
+
+impl From<&InboxNotification> for NotificationData {
+    fn from(notification: &InboxNotification) -> Self {
+        let kind = notification.kind().as_str();
+        Self {
+            id: notification.id().to_string(),
+            kind: kind.to_string(),
+            message: i18n::message(&format!("inbox.{kind}"), &[]),
+            created_at: *notification.created_at(),
+            read_at: notification.read_at().copied(),
+        }
+    }
+}
+
+/// The response body data field of [count_unread_notifications].
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[cfg_attr(feature = "client-models", derive(serde::Deserialize))]
+pub struct UnreadCountData {
+    pub unread_count: u64,
+}
+
+#[cfg(feature = "typescript")]
+crate::inbound::http::typescript::ts_interface!(UnreadCountData { unread_count: u64 });
+
+/// The response body data field of [mark_all_notifications_read].
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[cfg_attr(feature = "client-models", derive(serde::Deserialize))]
+pub struct MarkedReadData {
+    pub marked_read: u64,
+}
+
+#[cfg(feature = "typescript")]
+crate::inbound::http::typescript::ts_interface!(MarkedReadData { marked_read: u64 });
@@ -0,0 +1,173 @@
+use axum::{Extension, Json, extract::State, http::StatusCode};
+use axum_extra::extract::WithRejection;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+#[cfg(feature = "typescript")]
+use crate::inbound::http::typescript::{TypeScript, ts_object, ts_union};
+use crate::{
+    domain::crowdsrc::{
+        models::analytics::{AnalyticsError, AnalyticsEvent, AnalyticsEventKind, MAX_BATCH_SIZE},
+        ports::{CrowdSrcService, FeatureFlags},
+    },
+    inbound::http::{
+        AppState,
+        auth::AuthenticatedUser,
+        responses::{ApiError, ApiSuccess},
+    },
+};
+
+/// Record a batch of events reported by the client of the authenticated user, for studying
+/// annotation behavior. The events are written asynchronously, so they may take a while to show
+/// up.
+///
+/// # Responses
+///
+/// - 202 Accepted: the events will be written.
+/// - 401 Unauthorized: no valid access token was given.
+/// - 403 Forbidden: the access token is not a user's.
+/// - 404 Not found: no user with the given id exists.
+/// - 422 Unprocessable entity: an event is invalid, or there are too many.
+/// - 503 Service unavailable: events arrive faster than they can be written.
+pub async fn record_analytics_events<CS: CrowdSrcService, FF: FeatureFlags>(
+    State(state): State<AppState<CS, FF>>,
+    Extension(AuthenticatedUser(user_id)): Extension<AuthenticatedUser>,
+    WithRejection(Json(body), _): WithRejection<
+        Json<RecordAnalyticsEventsHttpRequestBody>,
+        ApiError,
+    >,
+) -> Result<ApiSuccess<RecordedAnalyticsEventsData>, ApiError> {
+    let events = body.try_into_domain(user_id, Utc::now())?;
+    let accepted = events.len();
+    state
+        .crwdsrc_service
+        .record_analytics_events(events)
+        .await
+        .map_err(ApiError::from)
+        .map(|()| {
+            ApiSuccess::new(
+                StatusCode::ACCEPTED,
+                RecordedAnalyticsEventsData { accepted },
+            )
+        })
+}
+
+/// The body of a request recording analytics events.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+#[cfg_attr(feature = "client-models", derive(serde::Serialize))]
+pub struct RecordAnalyticsEventsHttpRequestBody {
+    pub events: Vec<AnalyticsEventHttpRequestBody>,
+}
+
+#[cfg(feature = "typescript")]
+crate::inbound::http::typescript::ts_interface!(RecordAnalyticsEventsHttpRequestBody {
+    events: Vec<AnalyticsEventHttpRequestBody>,
+});
+
+/// An event in a [RecordAnalyticsEventsHttpRequestBody], of the kind given by its `type`.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+#[cfg_attr(feature = "client-models", derive(serde::Serialize))]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AnalyticsEventHttpRequestBody {
+    TaskViewed {
+        task_id: String,
+        occurred_at: DateTime<Utc>,
+    },
+    TaskSkipped {
+        task_id: String,
+        occurred_at: DateTime<Utc>,
+    },
+    TimeOnTask {
+        task_id: String,
+        occurred_at: DateTime<Utc>,
+        duration_ms: u64,
+    },
+}
+
+#[cfg(feature = "typescript")]
+impl TypeScript for AnalyticsEventHttpRequestBody {
+    fn ts_type() -> String {
+        "AnalyticsEventHttpRequestBody".to_string()
+    }
+
+    fn ts_declaration() -> Option<String> {
+        let event = |kind: &str, duration: bool| {
+            let mut fields = vec![
+                ("type", format!("\"{kind}\"")),
+                ("task_id", String::ts_type()),
+                ("occurred_at", DateTime::<Utc>::ts_type()),
+            ];
+            if duration {
+                fields.push(("duration_ms", u64::ts_type()));
+            }
+            ts_object(&fields)
+        };
+        Some(ts_union(
+            "AnalyticsEventHttpRequestBody",
+            &[
+                event("task_viewed", false),
+                event("task_skipped", false),
+                event("time_on_task", true),
+            ],
+        ))
+    }
+}
+
+impl RecordAnalyticsEventsHttpRequestBody {
+    /// Converts the HTTP request body into domain events of the user `user_id`, received at
+    /// `received_at`.
+    fn try_into_domain(
+        self,
+        user_id: Uuid,
+        received_at: DateTime<Utc>,
+    ) -> Result<Vec<AnalyticsEvent>, AnalyticsError> {
+        if self.events.len() > MAX_BATCH_SIZE {
+            return Err(AnalyticsError::TooManyEvents {
+                max: MAX_BATCH_SIZE,
+            });
+        }
+        self.events
+            .into_iter()
+            .enumerate()
+            .map(|(index, event)| {
+                let (task_id, occurred_at, kind) = match event {
+                    AnalyticsEventHttpRequestBody::TaskViewed {
+                        task_id,
+                        occurred_at,
+                    } => (task_id, occurred_at, AnalyticsEventKind::TaskViewed),
+                    AnalyticsEventHttpRequestBody::TaskSkipped {
+                        task_id,
+                        occurred_at,
+                    } => (task_id, occurred_at, AnalyticsEventKind::TaskSkipped),
+                    AnalyticsEventHttpRequestBody::TimeOnTask {
+                        task_id,
+                        occurred_at,
+                        duration_ms,
+                    } => (
+                        task_id,
+                        occurred_at,
+                        AnalyticsEventKind::TimeOnTask { duration_ms },
+                    ),
+                };
+                AnalyticsEvent::new(user_id, &task_id, kind, occurred_at, received_at).map_err(
+                    |err| match err {
+                        AnalyticsError::InvalidEvent { reason } => AnalyticsError::InvalidEvent {
+                            reason: format!("events[{index}]: {reason}"),
+                        },
+                        err => err,
+                    },
+                )
+            })
+            .collect()
+    }
+}
+
+/// The response body data field of [record_analytics_events].
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[cfg_attr(feature = "client-models", derive(serde::Deserialize))]
+pub struct RecordedAnalyticsEventsData {
+    pub accepted: usize,
+}
+
+#[cfg(feature = "typescript")]
+crate::inbound::http::typescript::ts_interface!(RecordedAnalyticsEventsData { accepted: usize });
@@ -0,0 +1,81 @@
+use axum::{extract::Path, extract::State, http::StatusCode};
+use axum_extra::extract::WithRejection;
+use uuid::Uuid;
+
+#[cfg(feature = "typescript")]
+use crate::inbound::http::typescript::{TypeScript, ts_object, ts_union};
+use crate::{
+    domain::crowdsrc::{
+        models::dead_letter::RedriveOutcome,
+        ports::{CrowdSrcService, FeatureFlags},
+    },
+    inbound::http::{
+        AppState,
+        handlers::list_dead_letters::DeadLetterData,
+        responses::{ApiError, ApiSuccess},
+    },
+};
+
+/// Try to deliver an undelivered notification again.
+///
+/// # Responses
+///
+/// - 200 OK: the [RedriveOutcome], the delivery having failed again, or the dead letter having
+///   been discarded as its user was erased, is not an error.
+/// - 401 Unauthorized: no valid access token was given.
+/// - 403 Forbidden: the access token is not an admin's.
+/// - 404 Not found: no dead letter with the given id exists.
+pub async fn redrive_dead_letter<CS: CrowdSrcService, FF: FeatureFlags>(
+    State(state): State<AppState<CS, FF>>,
+    WithRejection(Path(id), _): WithRejection<Path<Uuid>, ApiError>,
+) -> Result<ApiSuccess<RedriveDeadLetterResponseData>, ApiError> {
+    state
+        .crwdsrc_service
+        .redrive_dead_letter(&id)
+        .await
+        .map_err(ApiError::from)
+        .map(|ref outcome| ApiSuccess::new(StatusCode::OK, outcome.into()))
+}
+
+/// The response body data field for a redriven dead letter.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[cfg_attr(feature = "client-models", derive(serde::Deserialize))]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum RedriveDeadLetterResponseData {
+    Delivered,
+    Failed { dead_letter: DeadLetterData },
+    Discarded,
+}
+
+#[cfg(feature = "typescript")]
+impl TypeScript for RedriveDeadLetterResponseData {
+    fn ts_type() -> String {
+        "RedriveDeadLetterResponseData".to_string()
+    }
+
+    fn ts_declaration() -> Option<String> {
+        Some(ts_union(
+            "RedriveDeadLetterResponseData",
+            &[
+                ts_object(&[("status", "\"delivered\"".to_string())]),
+                ts_object(&[
+                    ("status", "\"failed\"".to_string()),
+                    ("dead_letter", DeadLetterData::ts_type()),
+                ]),
+                ts_object(&[("status", "\"discarded\"".to_string())]),
+            ],
+        ))
+    }
+}
+
+impl From<&RedriveOutcome> for RedriveDeadLetterResponseData {
+    fn from(outcome: &RedriveOutcome) -> Self {
+        match outcome {
+            RedriveOutcome::Delivered => Self::Delivered,
+            RedriveOutcome::Failed(dead_letter) => Self::Failed {
+                dead_letter: dead_letter.into(),
+            },
+            RedriveOutcome::Discarded => Self::Discarded,
+        }
+    }
+}
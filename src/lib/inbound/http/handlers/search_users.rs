@@ -0,0 +1,92 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+};
+use axum_extra::extract::WithRejection;
+use chrono::{DateTime, Utc};
+
+use crate::{
+    domain::crowdsrc::{
+        models::{search::UserSearchQuery, user::User},
+        ports::{CrowdSrcService, FeatureFlags},
+    },
+    inbound::http::{
+        AppState,
+        responses::{ApiError, ApiSuccess},
+    },
+};
+
+/// How many users are found unless the client asks for another limit.
+const DEFAULT_LIMIT: u32 = 20;
+
+/// Find the users whose username matches `q`, best match first.
+///
+/// Users may be searched in an index lagging behind them, so a user created a moment ago may
+/// not be found yet. Users erased since they were indexed are never returned.
+///
+/// # Responses
+///
+/// - 200 OK: the users found.
+/// - 401 Unauthorized: no valid access token was given.
+/// - 403 Forbidden: the access token is not an admin's.
+/// - 422 Unprocessable entity: `q` is blank or too long.
+pub async fn search_users<CS: CrowdSrcService, FF: FeatureFlags>(
+    State(state): State<AppState<CS, FF>>,
+    WithRejection(Query(params), _): WithRejection<Query<SearchUsersParams>, ApiError>,
+) -> Result<ApiSuccess<Vec<UserSearchHitData>>, ApiError> {
+    let query = UserSearchQuery::new(&params.q, params.limit.unwrap_or(DEFAULT_LIMIT))?;
+    state
+        .crwdsrc_service
+        .search_users(&query)
+        .await
+        .map_err(ApiError::from)
+        .map(|users| {
+            ApiSuccess::new(
+                StatusCode::OK,
+                users.iter().map(UserSearchHitData::from).collect(),
+            )
+        })
+}
+
+/// The query parameters of [search_users].
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+#[cfg_attr(feature = "client-models", derive(serde::Serialize))]
+pub struct SearchUsersParams {
+    pub q: String,
+    pub limit: Option<u32>,
+}
+
+#[cfg(feature = "typescript")]
+crate::inbound::http::typescript::ts_interface!(SearchUsersParams {
+    q: String,
+    limit as "limit?": Option<u32>,
+});
+
+/// The representation of a [User] found in responses.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[cfg_attr(feature = "client-models", derive(serde::Deserialize))]
+pub struct UserSearchHitData {
+    pub id: String,
+    pub username: String,
+    pub email_verified: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+#[cfg(feature = "typescript")]
+crate::inbound::http::typescript::ts_interface!(UserSearchHitData {
+    id: String,
+    username: String,
+    email_verified: bool,
+    created_at: DateTime<Utc>,
+});
+
+impl From<&User> for UserSearchHitData {
+    fn from(user: &User) -> Self {
+        Self {
+            id: user.id().to_string(),
+            username: user.username().to_string(),
+            email_verified: user.is_email_verified(),
+            created_at: *user.created_at(),
+        }
+    }
+}
@@ -0,0 +1,318 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    BoxError,
+    error_handling::HandleErrorLayer,
+    extract::{Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use tokio::sync::Semaphore;
+use tower::{
+    ServiceBuilder,
+    timeout::{TimeoutLayer, error::Elapsed},
+};
+
+use crate::{i18n, inbound::http::responses::ApiError};
+
+/// Limits on how long requests to a route group may take and how many may be handled at once,
+/// so that requests don't pile up when a dependency such as Postgres is slow.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RouteLimits {
+    /// Requests taking longer are answered with 504 Gateway Timeout.
+    pub timeout: Option<Duration>,
+    /// Requests arriving while this many are in flight in the group are answered with
+    /// 503 Service Unavailable.
+    pub max_concurrent_requests: Option<usize>,
+    /// Requests taking longer are logged as slow, but answered as usual.
+    pub latency_budget: Option<Duration>,
+    /// Requests arriving while the 99th percentile latency of the group over the last
+    /// [LATENCY_WINDOW] exceeds this are answered with 503 Service Unavailable.
+    pub max_p99_latency: Option<Duration>,
+}
+
+/// How far back the latencies considered by [RouteLimits::max_p99_latency] go. Since shed
+/// requests aren't measured, this is also how long shedding lasts at most once requests stop
+/// completing.
+pub const LATENCY_WINDOW: Duration = Duration::from_secs(10);
+
+/// How many latencies the window holds at most, the oldest being dropped first.
+const MAX_LATENCY_SAMPLES: usize = 1000;
+
+/// How many latencies the window must hold before its percentile is trusted.
+const MIN_LATENCY_SAMPLES: usize = 20;
+
+/// Applies `limits` to all routes of `router` together.
+pub fn limited<S: Clone + Send + Sync + 'static>(
+    router: axum::Router<S>,
+    limits: RouteLimits,
+) -> axum::Router<S> {
+    let router = match limits.timeout {
+        Some(timeout) => router.layer(
+            ServiceBuilder::new()
+                .layer(HandleErrorLayer::new(handle_timeout_error))
+                .layer(TimeoutLayer::new(timeout)),
+        ),
+        None => router,
+    };
+    let router = match limits.max_p99_latency {
+        Some(max_p99) => router.layer(axum::middleware::from_fn_with_state(
+            LatencyShedding {
+                latencies: Arc::new(LatencyWindow::new(LATENCY_WINDOW)),
+                max_p99,
+            },
+            shed_requests_while_slow,
+        )),
+        None => router,
+    };
+    let router = match limits.max_concurrent_requests {
+        // A single semaphore for the whole group, rather than one per route.
+        Some(max) => router.layer(axum::middleware::from_fn_with_state(
+            Arc::new(Semaphore::new(max)),
+            shed_excess_requests,
+        )),
+        None => router,
+    };
+    match limits.latency_budget {
+        Some(budget) => router.layer(axum::middleware::from_fn_with_state(
+            budget,
+            warn_on_slow_requests,
+        )),
+        None => router,
+    }
+}
+
+async fn handle_timeout_error(e: BoxError) -> Response {
+    let error = if e.is::<Elapsed>() {
+        ApiError::GatewayTimeout(i18n::message("error.timeout", &[]))
+    } else {
+        ApiError::InternalServerError(e.to_string())
+    };
+    error.into_response()
+}
+
+/// Middleware that answers requests with 503 Service Unavailable while all permits of
+/// `in_flight` are taken.
+async fn shed_excess_requests(
+    State(in_flight): State<Arc<Semaphore>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Ok(_permit) = in_flight.try_acquire() else {
+        return ApiError::ServiceUnavailable(i18n::message("error.overloaded", &[]))
+            .into_response();
+    };
+    next.run(request).await
+}
+
+/// The latencies of the requests completed within `window`.
+#[derive(Debug)]
+struct LatencyWindow {
+    window: Duration,
+    samples: Mutex<VecDeque<(Instant, Duration)>>,
+}
+
+impl LatencyWindow {
+    fn new(window: Duration) -> Self {
+        Self {
+            window,
+            samples: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    fn record(&self, latency: Duration) {
+        let mut samples = self.samples.lock().expect("latency lock poisoned");
+        if samples.len() == MAX_LATENCY_SAMPLES {
+            samples.pop_front();
+        }
+        samples.push_back((Instant::now(), latency));
+    }
+
+    /// The 99th percentile of the latencies within the window, or `None` if there are too
+    /// few to tell.
+    fn p99(&self) -> Option<Duration> {
+        let mut samples = self.samples.lock().expect("latency lock poisoned");
+        while samples
+            .front()
+            .is_some_and(|(recorded_at, _)| recorded_at.elapsed() > self.window)
+        {
+            samples.pop_front();
+        }
+        if samples.len() < MIN_LATENCY_SAMPLES {
+            return None;
+        }
+        let mut latencies: Vec<Duration> = samples.iter().map(|(_, latency)| *latency).collect();
+        latencies.sort_unstable();
+        Some(latencies[(latencies.len() * 99).div_ceil(100) - 1])
+    }
+}
+
+#[derive(Debug, Clone)]
+struct LatencyShedding {
+    latencies: Arc<LatencyWindow>,
+    max_p99: Duration,
+}
+
+/// Middleware that answers requests with 503 Service Unavailable while the 99th percentile
+/// latency of the requests handled exceeds `max_p99`.
+async fn shed_requests_while_slow(
+    State(shedding): State<LatencyShedding>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if shedding
+        .latencies
+        .p99()
+        .is_some_and(|p99| p99 > shedding.max_p99)
+    {
+        return ApiError::ServiceUnavailable(i18n::message("error.overloaded", &[]))
+            .into_response();
+    }
+    let started_at = Instant::now();
+    let response = next.run(request).await;
+    shedding.latencies.record(started_at.elapsed());
+    response
+}
+
+/// Middleware that warns about requests taking longer than `budget`, within the span of the
+/// request.
+async fn warn_on_slow_requests(
+    State(budget): State<Duration>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    let started_at = Instant::now();
+    let response = next.run(request).await;
+    let elapsed = started_at.elapsed();
+    if elapsed > budget {
+        tracing::warn!(
+            %method,
+            path,
+            status = response.status().as_u16(),
+            elapsed_ms = elapsed.as_millis() as u64,
+            budget_ms = budget.as_millis() as u64,
+            "slow request"
+        );
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, routing::get};
+    use tower::ServiceExt;
+
+    use super::*;
+
+    fn slow_router(limits: RouteLimits) -> axum::Router {
+        limited(
+            axum::Router::new().route(
+                "/",
+                get(|| async {
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                    "done"
+                }),
+            ),
+            limits,
+        )
+    }
+
+    async fn status(router: axum::Router) -> u16 {
+        let request = Request::get("/").body(Body::empty()).unwrap();
+        router.oneshot(request).await.unwrap().status().as_u16()
+    }
+
+    #[tokio::test]
+    async fn requests_within_limits_succeed() {
+        let router = slow_router(RouteLimits {
+            timeout: Some(Duration::from_secs(5)),
+            max_concurrent_requests: Some(1),
+            latency_budget: None,
+            max_p99_latency: None,
+        });
+
+        assert_eq!(status(router).await, 200);
+    }
+
+    #[test]
+    fn p99_is_only_known_with_enough_recent_samples() {
+        let latencies = LatencyWindow::new(Duration::from_secs(60));
+        for ms in 1..MIN_LATENCY_SAMPLES as u64 {
+            latencies.record(Duration::from_millis(ms));
+        }
+        assert_eq!(latencies.p99(), None);
+
+        for ms in MIN_LATENCY_SAMPLES as u64..=100 {
+            latencies.record(Duration::from_millis(ms));
+        }
+        assert_eq!(latencies.p99(), Some(Duration::from_millis(99)));
+
+        let expired = LatencyWindow::new(Duration::ZERO);
+        for _ in 0..MIN_LATENCY_SAMPLES {
+            expired.record(Duration::from_millis(1));
+        }
+        std::thread::sleep(Duration::from_millis(1));
+        assert_eq!(expired.p99(), None);
+    }
+
+    #[tokio::test]
+    async fn requests_are_shed_while_p99_latency_is_too_high() {
+        let router = slow_router(RouteLimits {
+            max_p99_latency: Some(Duration::from_millis(10)),
+            ..RouteLimits::default()
+        });
+
+        let statuses =
+            futures::future::join_all((0..MIN_LATENCY_SAMPLES).map(|_| status(router.clone())))
+                .await;
+
+        assert!(statuses.iter().all(|status| *status == 200));
+        assert_eq!(status(router).await, 503);
+    }
+
+    #[tokio::test]
+    async fn request_over_latency_budget_is_still_answered() {
+        let router = slow_router(RouteLimits {
+            latency_budget: Some(Duration::from_millis(1)),
+            ..RouteLimits::default()
+        });
+
+        assert_eq!(status(router).await, 200);
+    }
+
+    #[tokio::test]
+    async fn slow_request_times_out_with_504() {
+        let router = slow_router(RouteLimits {
+            timeout: Some(Duration::from_millis(1)),
+            max_concurrent_requests: None,
+            latency_budget: None,
+            max_p99_latency: None,
+        });
+
+        assert_eq!(status(router).await, 504);
+    }
+
+    #[tokio::test]
+    async fn request_beyond_concurrency_limit_is_rejected_with_503() {
+        let router = slow_router(RouteLimits {
+            timeout: None,
+            max_concurrent_requests: Some(1),
+            latency_budget: None,
+            max_p99_latency: None,
+        });
+
+        let (first, second) = tokio::join!(status(router.clone()), async {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            status(router.clone()).await
+        });
+
+        assert_eq!(first, 200);
+        assert_eq!(second, 503);
+    }
+}
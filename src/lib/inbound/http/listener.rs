@@ -0,0 +1,84 @@
+//! Binding the listening socket, either inherited through socket activation or bound with
+//! `SO_REUSEPORT`, so that a restarted server can take over without refusing connections.
+
+use std::net::{Ipv4Addr, SocketAddr};
+use std::os::fd::FromRawFd;
+
+use anyhow::Context;
+use socket2::{Domain, Socket, Type};
+use tokio::net;
+
+/// The first file descriptor passed by systemd-style socket activation.
+const LISTEN_FDS_START: i32 = 3;
+
+/// How many pending connections the kernel queues before refusing new ones.
+const BACKLOG: i32 = 1024;
+
+/// Returns the listener passed by socket activation if there is one, or else binds `port` on
+/// all interfaces, with `SO_REUSEPORT` set if `reuse_port`.
+pub(super) fn listen(port: &str, reuse_port: bool) -> anyhow::Result<net::TcpListener> {
+    let listener = match inherited_listener() {
+        Some(listener) => {
+            tracing::info!("using listener passed by socket activation");
+            listener
+        }
+        None => bind(port, reuse_port)?,
+    };
+    listener
+        .set_nonblocking(true)
+        .context("failed to make listener non-blocking")?;
+    net::TcpListener::from_std(listener).context("failed to register listener")
+}
+
+fn bind(port: &str, reuse_port: bool) -> anyhow::Result<std::net::TcpListener> {
+    let port: u16 = port
+        .parse()
+        .with_context(|| format!("invalid port {port}"))?;
+    let address = SocketAddr::from((Ipv4Addr::UNSPECIFIED, port));
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None)?;
+    socket.set_reuse_address(true)?;
+    socket.set_reuse_port(reuse_port)?;
+    socket
+        .bind(&address.into())
+        .with_context(|| format!("failed to listen on {port}"))?;
+    socket.listen(BACKLOG)?;
+    Ok(socket.into())
+}
+
+/// The listener passed as the first file descriptor if `LISTEN_PID` names this process and
+/// `LISTEN_FDS` is at least one, following the systemd socket activation protocol.
+fn inherited_listener() -> Option<std::net::TcpListener> {
+    let pid: u32 = std::env::var("LISTEN_PID").ok()?.parse().ok()?;
+    let fds: u32 = std::env::var("LISTEN_FDS").ok()?.parse().ok()?;
+    if pid != std::process::id() || fds == 0 {
+        return None;
+    }
+    // SAFETY: the protocol hands this process ownership of the descriptors from
+    // LISTEN_FDS_START, and nothing else in the process claims them.
+    Some(unsafe { std::net::TcpListener::from_raw_fd(LISTEN_FDS_START) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn port_can_be_shared_with_reuse_port() {
+        let first = listen("0", true).unwrap();
+        let port = first.local_addr().unwrap().port().to_string();
+
+        let second = listen(&port, true);
+
+        assert!(second.is_ok());
+    }
+
+    #[tokio::test]
+    async fn port_cannot_be_shared_without_reuse_port() {
+        let first = listen("0", false).unwrap();
+        let port = first.local_addr().unwrap().port().to_string();
+
+        let second = listen(&port, false);
+
+        assert!(second.is_err());
+    }
+}
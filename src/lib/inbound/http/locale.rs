@@ -0,0 +1,26 @@
+use axum::{
+    extract::Request,
+    http::{HeaderValue, header},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::i18n::{self, Locale};
+
+/// Middleware that handles the request in the [Locale] negotiated from its `Accept-Language`
+/// header, so that messages in the response are in the language of the user.
+pub async fn negotiate_locale(request: Request, next: Next) -> Response {
+    let locale = request
+        .headers()
+        .get(header::ACCEPT_LANGUAGE)
+        .and_then(|value| value.to_str().ok())
+        .map(Locale::negotiate)
+        .unwrap_or_default();
+
+    let mut response = i18n::with_locale(locale, next.run(request)).await;
+    response.headers_mut().insert(
+        header::CONTENT_LANGUAGE,
+        HeaderValue::from_static(locale.as_language_tag()),
+    );
+    response
+}
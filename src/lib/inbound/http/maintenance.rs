@@ -0,0 +1,226 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+
+use axum::{
+    Json,
+    extract::{Request, State},
+    http::{Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use axum_extra::extract::WithRejection;
+
+use crate::{
+    domain::crowdsrc::ports::MaintenanceSwitch,
+    i18n,
+    inbound::http::responses::{ApiError, ApiSuccess},
+};
+
+/// The state of [refuse_writes_during_maintenance] and the maintenance handlers.
+#[derive(Debug, Clone)]
+pub struct Maintenance<MS: MaintenanceSwitch> {
+    pub switch: MS,
+    /// Keeps maintenance mode on regardless of `switch`, as set in the configuration.
+    pub forced: bool,
+    /// How long clients are told to wait before retrying a refused request.
+    pub retry_after: Duration,
+    /// The state of `switch` when it was last read or written, off until then.
+    last_known: Arc<AtomicBool>,
+}
+
+impl<MS: MaintenanceSwitch> Maintenance<MS> {
+    pub fn new(switch: MS, forced: bool, retry_after: Duration) -> Self {
+        Self {
+            switch,
+            forced,
+            retry_after,
+            last_known: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    async fn is_enabled(&self) -> Result<bool, ApiError> {
+        let enabled = self.switch.is_enabled().await?;
+        self.last_known.store(enabled, Ordering::Relaxed);
+        Ok(self.forced || enabled)
+    }
+
+    /// Whether maintenance mode is on, as it was last known if the switch can't be read.
+    async fn is_enabled_or_last_known(&self) -> bool {
+        match self.switch.is_enabled().await {
+            Ok(enabled) => {
+                self.last_known.store(enabled, Ordering::Relaxed);
+                self.forced || enabled
+            }
+            Err(e) => {
+                let enabled = self.last_known.load(Ordering::Relaxed);
+                tracing::error!(
+                    error = ?e,
+                    enabled,
+                    "maintenance mode could not be read, keeping the last known state"
+                );
+                self.forced || enabled
+            }
+        }
+    }
+}
+
+/// Middleware that answers requests that may write with 503 Service Unavailable while
+/// maintenance mode is on, letting reads through.
+///
+/// The last known state is kept while the switch can't be read, so that a failing switch
+/// neither lets writes through during maintenance nor turns into an outage.
+pub async fn refuse_writes_during_maintenance<MS: MaintenanceSwitch>(
+    State(maintenance): State<Maintenance<MS>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if is_read(request.method()) {
+        return next.run(request).await;
+    }
+    if maintenance.is_enabled_or_last_known().await {
+        return ApiError::UnderMaintenance {
+            message: i18n::message("error.maintenance", &[]),
+            retry_after: maintenance.retry_after,
+        }
+        .into_response();
+    }
+    next.run(request).await
+}
+
+fn is_read(method: &Method) -> bool {
+    matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+}
+
+/// Tell whether maintenance mode is on.
+///
+/// # Responses
+///
+/// - 200 OK: the [MaintenanceData].
+/// - 401 Unauthorized: no valid access token was given.
+/// - 403 Forbidden: the access token is not an admin's.
+pub async fn get_maintenance<MS: MaintenanceSwitch>(
+    State(maintenance): State<Maintenance<MS>>,
+) -> Result<ApiSuccess<MaintenanceData>, ApiError> {
+    let enabled = maintenance.is_enabled().await?;
+    Ok(ApiSuccess::new(
+        StatusCode::OK,
+        MaintenanceData {
+            enabled,
+            forced: maintenance.forced,
+        },
+    ))
+}
+
+/// Switch maintenance mode on or off.
+///
+/// Switching it off has no effect while it is forced on in the configuration.
+///
+/// # Responses
+///
+/// - 200 OK: the resulting [MaintenanceData].
+/// - 401 Unauthorized: no valid access token was given.
+/// - 403 Forbidden: the access token is not an admin's.
+pub async fn set_maintenance<MS: MaintenanceSwitch>(
+    State(maintenance): State<Maintenance<MS>>,
+    WithRejection(Json(body), _): WithRejection<Json<SetMaintenanceHttpRequestBody>, ApiError>,
+) -> Result<ApiSuccess<MaintenanceData>, ApiError> {
+    maintenance.switch.set_enabled(body.enabled).await?;
+    maintenance
+        .last_known
+        .store(body.enabled, Ordering::Relaxed);
+    tracing::info!(enabled = body.enabled, "maintenance mode switched");
+    Ok(ApiSuccess::new(
+        StatusCode::OK,
+        MaintenanceData {
+            enabled: maintenance.forced || body.enabled,
+            forced: maintenance.forced,
+        },
+    ))
+}
+
+/// The body of a request switching maintenance mode.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+#[cfg_attr(feature = "client-models", derive(serde::Serialize))]
+pub struct SetMaintenanceHttpRequestBody {
+    pub enabled: bool,
+}
+
+#[cfg(feature = "typescript")]
+crate::inbound::http::typescript::ts_interface!(SetMaintenanceHttpRequestBody { enabled: bool });
+
+/// The representation of the maintenance mode in responses.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[cfg_attr(feature = "client-models", derive(serde::Deserialize))]
+pub struct MaintenanceData {
+    pub enabled: bool,
+    pub forced: bool,
+}
+
+#[cfg(feature = "typescript")]
+crate::inbound::http::typescript::ts_interface!(MaintenanceData {
+    enabled: bool,
+    forced: bool,
+});
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, routing::post};
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::domain::crowdsrc::models::maintenance::MaintenanceError;
+
+    /// A switch that is on, and can be made unreadable.
+    #[derive(Debug, Clone, Default)]
+    struct UnreliableSwitch {
+        failing: Arc<AtomicBool>,
+    }
+
+    impl MaintenanceSwitch for UnreliableSwitch {
+        async fn is_enabled(&self) -> Result<bool, MaintenanceError> {
+            if self.failing.load(Ordering::SeqCst) {
+                return Err(anyhow::anyhow!("switch is unreadable").into());
+            }
+            Ok(true)
+        }
+
+        async fn set_enabled(&self, _: bool) -> Result<(), MaintenanceError> {
+            unimplemented!()
+        }
+    }
+
+    async fn write_status(maintenance: Maintenance<UnreliableSwitch>) -> u16 {
+        let router = axum::Router::new()
+            .route("/", post(|| async { "written" }))
+            .route_layer(axum::middleware::from_fn_with_state(
+                maintenance,
+                refuse_writes_during_maintenance::<UnreliableSwitch>,
+            ));
+        let request = Request::post("/").body(Body::empty()).unwrap();
+        router.oneshot(request).await.unwrap().status().as_u16()
+    }
+
+    #[tokio::test]
+    async fn last_known_state_is_kept_while_the_switch_is_unreadable() {
+        let switch = UnreliableSwitch::default();
+        let maintenance = Maintenance::new(switch.clone(), false, Duration::from_secs(60));
+
+        assert_eq!(write_status(maintenance.clone()).await, 503);
+        switch.failing.store(true, Ordering::SeqCst);
+        assert_eq!(write_status(maintenance).await, 503);
+    }
+
+    #[tokio::test]
+    async fn writes_are_let_through_if_the_switch_was_never_read() {
+        let switch = UnreliableSwitch::default();
+        switch.failing.store(true, Ordering::SeqCst);
+        let maintenance = Maintenance::new(switch, false, Duration::from_secs(60));
+
+        assert_eq!(write_status(maintenance).await, 200);
+    }
+}
@@ -0,0 +1,36 @@
+//! The bodies of the requests to and responses from the HTTP API, as served by
+//! [HttpServer](super::HttpServer).
+
+pub use crate::inbound::http::{
+    abuse_challenge::ChallengeData,
+    handlers::{
+        accept_terms::{AcceptTermsHttpRequestBody, AcceptTermsResponseData},
+        create_user::{CreateUserHttpRequestBody, CreateUserResponseData},
+        email_change::{
+            CancelEmailChangeResponseData, ConfirmEmailChangeHttpRequestBody,
+            ConfirmEmailChangeResponseData, EmailChangeResponseData,
+            RequestEmailChangeHttpRequestBody,
+        },
+        erase_user::EraseUserResponseData,
+        export_user_data::{
+            ExportedTermsAcceptance, ExportedUser, SharedLinkData, UserDataExportResponseData,
+        },
+        export_users_csv::ExportUsersCsvParams,
+        get_user::UserResponseData,
+        list_dead_letters::DeadLetterData,
+        list_features::ListFeaturesQuery,
+        list_user_activity::{ActivityData, ActivityPageData, ListUserActivityParams},
+        notifications::{
+            InboxData, ListNotificationsParams, MarkedReadData, NotificationData, UnreadCountData,
+        },
+        record_analytics_events::{
+            AnalyticsEventHttpRequestBody, RecordAnalyticsEventsHttpRequestBody,
+            RecordedAnalyticsEventsData,
+        },
+        redrive_dead_letter::RedriveDeadLetterResponseData,
+        search_users::{SearchUsersParams, UserSearchHitData},
+    },
+    maintenance::{MaintenanceData, SetMaintenanceHttpRequestBody},
+    responses::{ApiErrorData, ApiResponseBody},
+    runtime_config::{PatchRuntimeConfigHttpRequestBody, RuntimeConfigData},
+};
@@ -1,42 +1,121 @@
+use std::time::Duration;
+
 use axum::{
     Json,
-    http::StatusCode,
+    http::{HeaderValue, StatusCode, header},
     response::{IntoResponse, Response},
 };
 
 use crate::{
-    domain::crowdsrc::models::user::{CreateUserError, UserNameError},
+    domain::crowdsrc::models::{
+        abuse_challenge::AbuseChallengeError,
+        access_token::AccessTokenError,
+        activity::{ActivityCursorError, ActivityError},
+        analytics::AnalyticsError,
+        dead_letter::DeadLetterError,
+        email_change::EmailChangeError,
+        inbox::InboxError,
+        maintenance::MaintenanceError,
+        runtime_config::RuntimeConfigError,
+        search::SearchError,
+        signed_url::SignedUrlError,
+        terms::{AcceptTermsError, TermsVersion, TermsVersionError},
+        throttle::ThrottleError,
+        user::{
+            CreateUserError, EmailAddressError, EraseUserError, GetUserError, ListUsersError,
+            UserNameError,
+        },
+    },
+    i18n,
     inbound::http::handlers::create_user::ParseCreateUserHttpRequestError,
 };
 
+/// A successful response, with the [header::LOCATION] of the resource it created, if any.
 #[derive(Debug, Clone)]
-pub struct ApiSuccess<T: serde::Serialize + PartialEq>(StatusCode, Json<ApiResponseBody<T>>);
+pub struct ApiSuccess<T: serde::Serialize + PartialEq>(
+    StatusCode,
+    Json<ApiResponseBody<T>>,
+    Option<String>,
+);
 
 impl<T> PartialEq for ApiSuccess<T>
 where
     T: serde::Serialize + PartialEq,
 {
     fn eq(&self, other: &Self) -> bool {
-        self.0 == other.0 && self.1.0 == other.1.0
+        self.0 == other.0 && self.1.0 == other.1.0 && self.2 == other.2
     }
 }
 
 impl<T: serde::Serialize + PartialEq> ApiSuccess<T> {
     pub fn new(status: StatusCode, data: T) -> Self {
-        ApiSuccess(status, Json(ApiResponseBody::new(status, data)))
+        ApiSuccess(status, Json(ApiResponseBody::new(status, data)), None)
+    }
+
+    /// Points the client at the resource the request created, found at `path`.
+    pub fn with_location(mut self, path: String) -> Self {
+        self.2 = Some(path);
+        self
     }
 }
 
 impl<T: serde::Serialize + PartialEq> IntoResponse for ApiSuccess<T> {
     fn into_response(self) -> Response {
-        (self.0, self.1).into_response()
+        match self.2 {
+            Some(location) => (self.0, [(header::LOCATION, location)], self.1).into_response(),
+            None => (self.0, self.1).into_response(),
+        }
     }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ApiError {
     InternalServerError(String),
+    Conflict(String),
+    /// The request carried no valid credentials, which the client is told how to give with
+    /// the `WWW-Authenticate` `challenge`.
+    Unauthorized {
+        message: String,
+        challenge: &'static str,
+    },
+    Forbidden(String),
+    NotFound(String),
     UnprocessableEntity(String),
+    TooManyRequests {
+        message: String,
+        retry_after: Duration,
+    },
+    ServiceUnavailable(String),
+    UnderMaintenance {
+        message: String,
+        retry_after: Duration,
+    },
+    GatewayTimeout(String),
+}
+
+impl ApiError {
+    /// An [ApiError::InternalServerError] hiding `cause` from the client, which is logged and
+    /// reported instead.
+    pub fn internal(cause: anyhow::Error) -> Self {
+        tracing::error!("{:?}\n{}", cause, cause.backtrace());
+        #[cfg(feature = "error-reporting")]
+        crate::error_reporting::capture_error(&cause);
+        Self::InternalServerError(i18n::message("error.internal", &[]))
+    }
+}
+
+impl ApiError {
+    /// An [ApiError::Unauthorized] for `e`, telling the client to authenticate as `challenge`
+    /// asks.
+    pub fn unauthorized(e: AccessTokenError, challenge: &'static str) -> Self {
+        let message = match e {
+            AccessTokenError::Missing => i18n::message("error.access_token.missing", &[]),
+            AccessTokenError::Invalid => i18n::message("error.access_token.invalid", &[]),
+            AccessTokenError::Expired { .. } => i18n::message("error.access_token.expired", &[]),
+        };
+
+        Self::Unauthorized { message, challenge }
+    }
 }
 
 impl From<anyhow::Error> for ApiError {
@@ -51,39 +130,314 @@ impl From<axum::extract::rejection::JsonRejection> for ApiError {
     }
 }
 
+impl From<axum::extract::rejection::PathRejection> for ApiError {
+    fn from(value: axum::extract::rejection::PathRejection) -> Self {
+        ApiError::UnprocessableEntity(value.body_text())
+    }
+}
+
+impl From<axum::extract::rejection::QueryRejection> for ApiError {
+    fn from(value: axum::extract::rejection::QueryRejection) -> Self {
+        ApiError::UnprocessableEntity(value.body_text())
+    }
+}
+
 impl From<CreateUserError> for ApiError {
     fn from(e: CreateUserError) -> Self {
         match e {
-            CreateUserError::DuplicateUserName { username } => Self::UnprocessableEntity(format!(
-                "user with username '{}' already exists",
-                username
+            CreateUserError::DuplicateUserName { username } => Self::UnprocessableEntity(
+                i18n::message("error.user.duplicate_username", &[("username", &username)]),
+            ),
+            CreateUserError::DuplicateEmail { email } => Self::UnprocessableEntity(i18n::message(
+                "error.user.duplicate_email",
+                &[("email", &email)],
+            )),
+            CreateUserError::StaleTermsVersion { accepted, current } => {
+                Self::Conflict(stale_terms_message(&current, &accepted))
+            }
+            CreateUserError::Unknown(cause) => Self::internal(cause),
+        }
+    }
+}
+
+impl From<GetUserError> for ApiError {
+    fn from(e: GetUserError) -> Self {
+        match e {
+            GetUserError::NotFound { id } => {
+                Self::NotFound(i18n::message("error.user.not_found", &[("id", &id)]))
+            }
+            GetUserError::Unknown(cause) => Self::internal(cause),
+        }
+    }
+}
+
+impl From<ActivityError> for ApiError {
+    fn from(e: ActivityError) -> Self {
+        match e {
+            ActivityError::UserNotFound { id } => {
+                Self::NotFound(i18n::message("error.user.not_found", &[("id", &id)]))
+            }
+            ActivityError::Unknown(cause) => Self::internal(cause),
+        }
+    }
+}
+
+impl From<ActivityCursorError> for ApiError {
+    fn from(e: ActivityCursorError) -> Self {
+        Self::UnprocessableEntity(i18n::message(
+            "error.activity_cursor.invalid",
+            &[("cursor", &e.invalid_cursor)],
+        ))
+    }
+}
+
+impl From<ListUsersError> for ApiError {
+    fn from(e: ListUsersError) -> Self {
+        match e {
+            ListUsersError::Unknown(cause) => Self::internal(cause),
+        }
+    }
+}
+
+impl From<EraseUserError> for ApiError {
+    fn from(e: EraseUserError) -> Self {
+        match e {
+            EraseUserError::NotFound { id } => {
+                Self::NotFound(i18n::message("error.user.not_found", &[("id", &id)]))
+            }
+            EraseUserError::Unknown(cause) => Self::internal(cause),
+        }
+    }
+}
+
+impl From<AcceptTermsError> for ApiError {
+    fn from(e: AcceptTermsError) -> Self {
+        match e {
+            AcceptTermsError::UserNotFound { id } => {
+                Self::NotFound(i18n::message("error.user.not_found", &[("id", &id)]))
+            }
+            AcceptTermsError::StaleTermsVersion { accepted, current } => {
+                Self::Conflict(stale_terms_message(&current, &accepted))
+            }
+            AcceptTermsError::Unknown(cause) => Self::internal(cause),
+        }
+    }
+}
+
+impl From<DeadLetterError> for ApiError {
+    fn from(e: DeadLetterError) -> Self {
+        match e {
+            DeadLetterError::NotFound { id } => {
+                Self::NotFound(i18n::message("error.dead_letter.not_found", &[("id", &id)]))
+            }
+            DeadLetterError::Unknown(cause) => Self::internal(cause),
+        }
+    }
+}
+
+impl From<EmailChangeError> for ApiError {
+    fn from(e: EmailChangeError) -> Self {
+        match e {
+            EmailChangeError::UserNotFound { id } => {
+                Self::NotFound(i18n::message("error.user.not_found", &[("id", &id)]))
+            }
+            EmailChangeError::NotPending { user_id } => Self::NotFound(i18n::message(
+                "error.email_change.not_pending",
+                &[("id", &user_id)],
+            )),
+            EmailChangeError::InvalidToken => {
+                Self::Forbidden(i18n::message("error.email_change.invalid_token", &[]))
+            }
+            EmailChangeError::Expired { .. } => {
+                Self::Conflict(i18n::message("error.email_change.expired", &[]))
+            }
+            EmailChangeError::DuplicateEmail { email } => Self::UnprocessableEntity(i18n::message(
+                "error.user.duplicate_email",
+                &[("email", &email)],
+            )),
+            EmailChangeError::Unknown(cause) => Self::internal(cause),
+        }
+    }
+}
+
+impl From<InboxError> for ApiError {
+    fn from(e: InboxError) -> Self {
+        match e {
+            InboxError::UserNotFound { id } => {
+                Self::NotFound(i18n::message("error.user.not_found", &[("id", &id)]))
+            }
+            InboxError::NotFound { id } => Self::NotFound(i18n::message(
+                "error.notification.not_found",
+                &[("id", &id)],
+            )),
+            InboxError::Unknown(cause) => Self::internal(cause),
+        }
+    }
+}
+
+impl From<AnalyticsError> for ApiError {
+    fn from(e: AnalyticsError) -> Self {
+        match e {
+            AnalyticsError::InvalidEvent { reason } => Self::UnprocessableEntity(i18n::message(
+                "error.analytics.invalid_event",
+                &[("reason", &reason)],
+            )),
+            AnalyticsError::TooManyEvents { max } => Self::UnprocessableEntity(i18n::message(
+                "error.analytics.too_many_events",
+                &[("max", &max.to_string())],
+            )),
+            AnalyticsError::UserNotFound { id } => Self::NotFound(i18n::message(
+                "error.user.not_found",
+                &[("id", &id.to_string())],
+            )),
+            AnalyticsError::Overloaded => {
+                Self::ServiceUnavailable(i18n::message("error.analytics.overloaded", &[]))
+            }
+            AnalyticsError::Unknown(cause) => Self::internal(cause),
+        }
+    }
+}
+
+impl From<SearchError> for ApiError {
+    fn from(e: SearchError) -> Self {
+        match e {
+            SearchError::InvalidQuery { reason } => Self::UnprocessableEntity(i18n::message(
+                "error.search.invalid_query",
+                &[("reason", &reason)],
             )),
-            CreateUserError::DuplicateEmail { email } => {
-                Self::UnprocessableEntity(format!("user with email '{}' already exists", email))
+            SearchError::Unknown(cause) => Self::internal(cause),
+        }
+    }
+}
+
+impl From<EmailAddressError> for ApiError {
+    fn from(e: EmailAddressError) -> Self {
+        Self::UnprocessableEntity(i18n::message(
+            "error.email.invalid",
+            &[("reason", &e.message)],
+        ))
+    }
+}
+
+fn stale_terms_message(current: &TermsVersion, accepted: &TermsVersion) -> String {
+    i18n::message(
+        "error.terms_version.stale",
+        &[("current", current), ("accepted", accepted)],
+    )
+}
+
+impl From<AbuseChallengeError> for ApiError {
+    fn from(e: AbuseChallengeError) -> Self {
+        match e {
+            AbuseChallengeError::Missing => {
+                Self::Forbidden(i18n::message("error.challenge.missing", &[]))
+            }
+            AbuseChallengeError::Rejected => {
+                Self::Forbidden(i18n::message("error.challenge.rejected", &[]))
+            }
+            AbuseChallengeError::Unknown(cause) => Self::internal(cause),
+        }
+    }
+}
+
+impl From<ThrottleError> for ApiError {
+    fn from(e: ThrottleError) -> Self {
+        match e {
+            ThrottleError::TooManySubmissions { retry_after } => Self::TooManyRequests {
+                message: i18n::message("error.throttle.too_many_submissions", &[]),
+                retry_after,
+            },
+            ThrottleError::Unknown(cause) => Self::internal(cause),
+        }
+    }
+}
+
+impl From<MaintenanceError> for ApiError {
+    fn from(e: MaintenanceError) -> Self {
+        match e {
+            MaintenanceError::Unknown(cause) => Self::internal(cause),
+        }
+    }
+}
+
+impl From<RuntimeConfigError> for ApiError {
+    fn from(e: RuntimeConfigError) -> Self {
+        match e {
+            RuntimeConfigError::InvalidLogLevel { invalid_level } => {
+                Self::UnprocessableEntity(i18n::message(
+                    "error.runtime_config.invalid_log_level",
+                    &[("level", &invalid_level)],
+                ))
             }
-            CreateUserError::Unknown(cause) => {
-                tracing::error!("{:?}\n{}", cause, cause.backtrace());
-                Self::InternalServerError("Internal server error".to_string())
+            RuntimeConfigError::Unknown(cause) => Self::internal(cause),
+        }
+    }
+}
+
+impl From<AccessTokenError> for ApiError {
+    fn from(e: AccessTokenError) -> Self {
+        Self::unauthorized(e, "Bearer")
+    }
+}
+
+impl From<SignedUrlError> for ApiError {
+    fn from(e: SignedUrlError) -> Self {
+        match e {
+            SignedUrlError::Missing => {
+                Self::Forbidden(i18n::message("error.signed_url.missing", &[]))
+            }
+            SignedUrlError::InvalidSignature => {
+                Self::Forbidden(i18n::message("error.signed_url.invalid", &[]))
+            }
+            SignedUrlError::Expired { .. } => {
+                Self::Forbidden(i18n::message("error.signed_url.expired", &[]))
             }
         }
     }
 }
 
+impl From<TermsVersionError> for ApiError {
+    fn from(e: TermsVersionError) -> Self {
+        let message = match e {
+            TermsVersionError::Empty => i18n::message("error.terms_version.empty", &[]),
+            TermsVersionError::WithWhitespace { invalid_version } => i18n::message(
+                "error.terms_version.whitespace",
+                &[("version", &invalid_version)],
+            ),
+        };
+
+        Self::UnprocessableEntity(message)
+    }
+}
+
 impl From<ParseCreateUserHttpRequestError> for ApiError {
     fn from(e: ParseCreateUserHttpRequestError) -> Self {
         let message = match e {
             ParseCreateUserHttpRequestError::Name(UserNameError::Empty) => {
-                "username can't be empty".to_string()
+                i18n::message("error.username.empty", &[])
             }
             ParseCreateUserHttpRequestError::Name(UserNameError::WithWhitespace {
                 invalid_username,
-            }) => format!(
-                "username cannot contain whitespace (got: '{}')",
-                invalid_username
+            }) => i18n::message(
+                "error.username.whitespace",
+                &[("username", &invalid_username)],
+            ),
+            ParseCreateUserHttpRequestError::Name(UserNameError::WithControlCharacters {
+                invalid_username,
+            }) => i18n::message(
+                "error.username.control_characters",
+                &[("username", &invalid_username.escape_debug())],
             ),
+            ParseCreateUserHttpRequestError::Name(UserNameError::TooLong { max_graphemes }) => {
+                i18n::message("error.username.too_long", &[("max_length", &max_graphemes)])
+            }
+            ParseCreateUserHttpRequestError::Name(UserNameError::Reserved { prefix }) => {
+                i18n::message("error.username.reserved", &[("prefix", &prefix)])
+            }
             ParseCreateUserHttpRequestError::EmailAddress(cause) => {
-                format!("email address '{}' is invalid", cause.invalid_email)
+                i18n::message("error.email.invalid", &[("reason", &cause.message)])
             }
+            ParseCreateUserHttpRequestError::TermsVersion(cause) => return cause.into(),
         };
 
         Self::UnprocessableEntity(message)
@@ -101,11 +455,38 @@ impl IntoResponse for ApiError {
                     StatusCode::INTERNAL_SERVER_ERROR,
                     Json(ApiResponseBody::new_error(
                         StatusCode::INTERNAL_SERVER_ERROR,
-                        "Internal server error".to_string(),
+                        i18n::message("error.internal", &[]),
                     )),
                 )
                     .into_response()
             }
+            Conflict(message) => (
+                StatusCode::CONFLICT,
+                Json(ApiResponseBody::new_error(StatusCode::CONFLICT, message)),
+            )
+                .into_response(),
+            Unauthorized { message, challenge } => (
+                StatusCode::UNAUTHORIZED,
+                [(
+                    header::WWW_AUTHENTICATE,
+                    HeaderValue::from_static(challenge),
+                )],
+                Json(ApiResponseBody::new_error(
+                    StatusCode::UNAUTHORIZED,
+                    message,
+                )),
+            )
+                .into_response(),
+            Forbidden(message) => (
+                StatusCode::FORBIDDEN,
+                Json(ApiResponseBody::new_error(StatusCode::FORBIDDEN, message)),
+            )
+                .into_response(),
+            NotFound(message) => (
+                StatusCode::NOT_FOUND,
+                Json(ApiResponseBody::new_error(StatusCode::NOT_FOUND, message)),
+            )
+                .into_response(),
             UnprocessableEntity(message) => (
                 StatusCode::UNPROCESSABLE_ENTITY,
                 Json(ApiResponseBody::new_error(
@@ -114,15 +495,61 @@ impl IntoResponse for ApiError {
                 )),
             )
                 .into_response(),
+            TooManyRequests {
+                message,
+                retry_after,
+            } => (
+                StatusCode::TOO_MANY_REQUESTS,
+                [(header::RETRY_AFTER, retry_after_header(retry_after))],
+                Json(ApiResponseBody::new_error(
+                    StatusCode::TOO_MANY_REQUESTS,
+                    message,
+                )),
+            )
+                .into_response(),
+            ServiceUnavailable(message) => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(ApiResponseBody::new_error(
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    message,
+                )),
+            )
+                .into_response(),
+            UnderMaintenance {
+                message,
+                retry_after,
+            } => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                [(header::RETRY_AFTER, retry_after_header(retry_after))],
+                Json(ApiResponseBody::new_error(
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    message,
+                )),
+            )
+                .into_response(),
+            GatewayTimeout(message) => (
+                StatusCode::GATEWAY_TIMEOUT,
+                Json(ApiResponseBody::new_error(
+                    StatusCode::GATEWAY_TIMEOUT,
+                    message,
+                )),
+            )
+                .into_response(),
         }
     }
 }
 
+/// The value of a `Retry-After` header for `retry_after`, in whole seconds rounded up.
+fn retry_after_header(retry_after: Duration) -> HeaderValue {
+    HeaderValue::from(retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0))
+}
+
 /// Generic response structure shared by all API responses.
 #[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[cfg_attr(feature = "client-models", derive(serde::Deserialize))]
 pub struct ApiResponseBody<T: serde::Serialize + PartialEq> {
-    status_code: u16,
-    data: T,
+    pub status_code: u16,
+    pub data: T,
 }
 
 impl<T: serde::Serialize + PartialEq> ApiResponseBody<T> {
@@ -145,6 +572,7 @@ impl ApiResponseBody<ApiErrorData> {
 
 /// The response data format for all error responses.
 #[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[cfg_attr(feature = "client-models", derive(serde::Deserialize))]
 pub struct ApiErrorData {
     pub message: String,
 }
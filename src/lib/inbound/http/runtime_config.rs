@@ -0,0 +1,125 @@
+use axum::{Json, extract::State, http::StatusCode};
+use axum_extra::extract::WithRejection;
+use serde::Deserialize;
+use tokio::sync::watch;
+
+use crate::{
+    domain::crowdsrc::{
+        models::runtime_config::{LogLevel, RuntimeConfig},
+        ports::RuntimeConfigStore,
+    },
+    inbound::http::responses::{ApiError, ApiSuccess},
+};
+
+/// The state of the runtime config handlers.
+///
+/// The [RuntimeConfig] in effect is held by `updates`, which components that apply it
+/// subscribe to.
+#[derive(Debug, Clone)]
+pub struct RuntimeConfigControl<RS: RuntimeConfigStore> {
+    pub store: RS,
+    pub updates: watch::Sender<RuntimeConfig>,
+}
+
+/// Retrieve the [RuntimeConfig] in effect.
+///
+/// # Responses
+///
+/// - 200 OK: the [RuntimeConfigData].
+/// - 401 Unauthorized: no valid access token was given.
+/// - 403 Forbidden: the access token is not an admin's.
+pub async fn get_runtime_config<RS: RuntimeConfigStore>(
+    State(control): State<RuntimeConfigControl<RS>>,
+) -> ApiSuccess<RuntimeConfigData> {
+    ApiSuccess::new(StatusCode::OK, (&*control.updates.borrow()).into())
+}
+
+/// Change the settings given in the body, persisting them and applying them right away.
+///
+/// Settings missing from the body are kept, and settings set to `null` are reset to their
+/// configured value.
+///
+/// # Responses
+///
+/// - 200 OK: the resulting [RuntimeConfigData].
+/// - 401 Unauthorized: no valid access token was given.
+/// - 403 Forbidden: the access token is not an admin's.
+/// - 422 Unprocessable entity: the body holds an unknown setting or an invalid value.
+pub async fn patch_runtime_config<RS: RuntimeConfigStore>(
+    State(control): State<RuntimeConfigControl<RS>>,
+    WithRejection(Json(body), _): WithRejection<Json<PatchRuntimeConfigHttpRequestBody>, ApiError>,
+) -> Result<ApiSuccess<RuntimeConfigData>, ApiError> {
+    let mut config = control.updates.borrow().clone();
+    if let Some(log_level) = body.log_level {
+        config.log_level = log_level.as_deref().map(LogLevel::new).transpose()?;
+    }
+    if let Some(max_submissions) = body.max_submissions {
+        config.max_submissions = max_submissions;
+    }
+    control.store.save(&config).await?;
+    tracing::info!(?config, "runtime config changed");
+    let data = (&config).into();
+    control.updates.send_replace(config);
+    Ok(ApiSuccess::new(StatusCode::OK, data))
+}
+
+/// The body of a request changing the runtime config, limited to the settings that can be
+/// changed at runtime.
+///
+/// The outer [Option] tells whether a setting is given, the inner whether it is reset.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[cfg_attr(feature = "client-models", derive(serde::Serialize))]
+#[serde(deny_unknown_fields)]
+pub struct PatchRuntimeConfigHttpRequestBody {
+    #[serde(default, deserialize_with = "present")]
+    #[cfg_attr(
+        feature = "client-models",
+        serde(skip_serializing_if = "Option::is_none")
+    )]
+    pub log_level: Option<Option<String>>,
+    #[serde(default, deserialize_with = "present")]
+    #[cfg_attr(
+        feature = "client-models",
+        serde(skip_serializing_if = "Option::is_none")
+    )]
+    pub max_submissions: Option<Option<u32>>,
+}
+
+#[cfg(feature = "typescript")]
+crate::inbound::http::typescript::ts_interface!(PatchRuntimeConfigHttpRequestBody {
+    log_level as "log_level?": Option<Option<String>>,
+    max_submissions as "max_submissions?": Option<Option<u32>>,
+});
+
+/// Deserializes a field that is present, including as `null`, as `Some`.
+fn present<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::deserialize(deserializer).map(Some)
+}
+
+/// The representation of the [RuntimeConfig] in responses, with `null` for the settings that
+/// keep their configured value.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[cfg_attr(feature = "client-models", derive(serde::Deserialize))]
+pub struct RuntimeConfigData {
+    pub log_level: Option<String>,
+    pub max_submissions: Option<u32>,
+}
+
+#[cfg(feature = "typescript")]
+crate::inbound::http::typescript::ts_interface!(RuntimeConfigData {
+    log_level: Option<String>,
+    max_submissions: Option<u32>,
+});
+
+impl From<&RuntimeConfig> for RuntimeConfigData {
+    fn from(config: &RuntimeConfig) -> Self {
+        Self {
+            log_level: config.log_level.as_ref().map(LogLevel::to_string),
+            max_submissions: config.max_submissions,
+        }
+    }
+}
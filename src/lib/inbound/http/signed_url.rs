@@ -0,0 +1,44 @@
+use axum::{
+    extract::{FromRef, FromRequestParts, OriginalUri, Query},
+    http::request::Parts,
+};
+use chrono::Utc;
+
+use crate::{
+    domain::crowdsrc::models::signed_url::{SignedUrl, SignedUrlError, SigningKey},
+    inbound::http::responses::ApiError,
+};
+
+/// Extractor admitting only requests made through a valid [SignedUrl] for the requested path,
+/// rejecting others with 403 Forbidden.
+#[derive(Debug, Clone)]
+pub struct VerifiedSignedUrl;
+
+/// The query parameters carrying the signature of a [SignedUrl].
+#[derive(Debug, serde::Deserialize)]
+struct SignatureQuery {
+    expires: String,
+    signature: String,
+}
+
+impl<S> FromRequestParts<S> for VerifiedSignedUrl
+where
+    S: Send + Sync,
+    SigningKey: FromRef<S>,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        // Nested routers see the path without their prefix, but the full path was signed.
+        let path = parts
+            .extensions
+            .get::<OriginalUri>()
+            .map_or(parts.uri.path(), |OriginalUri(uri)| uri.path())
+            .to_string();
+        let Query(query) = Query::<SignatureQuery>::try_from_uri(&parts.uri)
+            .map_err(|_| ApiError::from(SignedUrlError::Missing))?;
+        let signed_url = SignedUrl::from_parts(&path, &query.expires, &query.signature)?;
+        signed_url.verify(&SigningKey::from_ref(state), Utc::now())?;
+        Ok(Self)
+    }
+}
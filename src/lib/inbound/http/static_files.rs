@@ -0,0 +1,123 @@
+use std::path::{Component, Path, PathBuf};
+
+use axum::{
+    extract::Request,
+    http::{HeaderValue, Method, StatusCode, header},
+    response::{IntoResponse, Response},
+};
+
+/// Serves the file at the request path from `dir`.
+///
+/// Requests for a missing file that accept HTML are answered with `index.html`, so that a
+/// single page application using history-mode routing can handle its own paths.
+pub async fn serve_static_file(dir: &Path, request: Request) -> Response {
+    if request.method() != Method::GET && request.method() != Method::HEAD {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    let Some(relative_path) = relative_path(request.uri().path()) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    let mut path = dir.join(relative_path);
+    if tokio::fs::metadata(&path)
+        .await
+        .is_ok_and(|metadata| metadata.is_dir())
+    {
+        path.push("index.html");
+    }
+    if !tokio::fs::metadata(&path)
+        .await
+        .is_ok_and(|metadata| metadata.is_file())
+    {
+        if !accepts_html(&request) {
+            return StatusCode::NOT_FOUND.into_response();
+        }
+        path = dir.join("index.html");
+    }
+
+    match tokio::fs::read(&path).await {
+        Ok(contents) => (
+            [(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static(content_type(&path)),
+            )],
+            contents,
+        )
+            .into_response(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => StatusCode::NOT_FOUND.into_response(),
+        Err(e) => {
+            tracing::error!("failed to read static file {}: {:?}", path.display(), e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+/// The request path relative to the static directory, unless it would escape it.
+fn relative_path(request_path: &str) -> Option<PathBuf> {
+    let path = Path::new(request_path.trim_start_matches('/'));
+    path.components()
+        .all(|component| matches!(component, Component::Normal(_)))
+        .then(|| path.to_path_buf())
+}
+
+fn accepts_html(request: &Request) -> bool {
+    request
+        .headers()
+        .get(header::ACCEPT)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|accept| accept.contains("text/html"))
+}
+
+fn content_type(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default();
+    match extension.to_ascii_lowercase().as_str() {
+        "html" | "htm" => "text/html; charset=utf-8",
+        "js" | "mjs" => "text/javascript; charset=utf-8",
+        "css" => "text/css; charset=utf-8",
+        "json" | "map" => "application/json",
+        "txt" => "text/plain; charset=utf-8",
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "ico" => "image/x-icon",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "wasm" => "application/wasm",
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relative_path_strips_leading_slash() {
+        assert_eq!(
+            relative_path("/assets/app.js"),
+            Some(PathBuf::from("assets/app.js"))
+        );
+    }
+
+    #[test]
+    fn relative_path_rejects_parent_directories() {
+        assert_eq!(relative_path("/assets/../../etc/passwd"), None);
+    }
+
+    #[test]
+    fn content_type_falls_back_to_octet_stream() {
+        assert_eq!(
+            content_type(Path::new("bundle.js")),
+            "text/javascript; charset=utf-8"
+        );
+        assert_eq!(
+            content_type(Path::new("data.bin")),
+            "application/octet-stream"
+        );
+    }
+}
@@ -0,0 +1,38 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{RawPathParams, Request, State},
+    http::Method,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::{
+    domain::crowdsrc::ports::CrowdSrcService,
+    inbound::http::{auth, responses::ApiError},
+};
+
+/// Middleware that answers requests that may write on behalf of the user identified by the
+/// `id` path parameter with 409 Conflict until that user has accepted the current terms of
+/// service, letting reads through.
+///
+/// Requests for an `id` that is not a user id are let through to be rejected by the handler.
+pub async fn require_current_terms<CS: CrowdSrcService>(
+    State(crwdsrc_service): State<Arc<CS>>,
+    path_params: RawPathParams,
+    request: Request,
+    next: Next,
+) -> Response {
+    let user_id = match auth::path_user_id(&path_params) {
+        Some(user_id) if !is_read(request.method()) => user_id,
+        _ => return next.run(request).await,
+    };
+    match crwdsrc_service.check_current_terms(&user_id).await {
+        Ok(()) => next.run(request).await,
+        Err(e) => ApiError::from(e).into_response(),
+    }
+}
+
+fn is_read(method: &Method) -> bool {
+    matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+}
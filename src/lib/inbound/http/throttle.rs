@@ -0,0 +1,59 @@
+use std::{
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+};
+
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::header,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::{
+    domain::crowdsrc::{models::throttle::SubmissionSource, ports::SubmissionThrottle},
+    inbound::http::responses::ApiError,
+};
+
+/// The state of [throttle_submissions].
+#[derive(Debug, Clone)]
+pub struct Throttling<ST: SubmissionThrottle> {
+    pub throttle: ST,
+    /// Addresses that are never throttled, such as those of admins importing users.
+    pub allowlist: Arc<[IpAddr]>,
+}
+
+/// Middleware that records each request as a submission from its [SubmissionSource], rejecting
+/// it with 429 Too Many Requests once the source has used up its allowance.
+///
+/// Requests without a known peer address are let through.
+pub async fn throttle_submissions<ST: SubmissionThrottle>(
+    State(throttling): State<Throttling<ST>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(ip) = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip())
+    else {
+        return next.run(request).await;
+    };
+    if throttling.allowlist.contains(&ip) {
+        return next.run(request).await;
+    }
+    let user_agent = request
+        .headers()
+        .get(header::USER_AGENT)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+
+    match throttling
+        .throttle
+        .record(&SubmissionSource::new(ip, user_agent))
+        .await
+    {
+        Ok(()) => next.run(request).await,
+        Err(e) => ApiError::from(e).into_response(),
+    }
+}
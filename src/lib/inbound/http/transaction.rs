@@ -0,0 +1,47 @@
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::{
+    domain::crowdsrc::{
+        after_commit::AfterCommit,
+        ports::{Transaction, TransactionManager},
+    },
+    inbound::http::responses::ApiError,
+};
+
+/// Middleware that handles the request in a [Transaction] of its own, so that all repository
+/// writes of the handler are atomic as a whole.
+///
+/// The [Transaction] is also available to the handler as a request extension. It is committed if
+/// the response is successful and rolled back otherwise. Effects the handler deferred to
+/// [AfterCommit] run once it is committed, and are discarded if it is rolled back.
+pub async fn transactional<TM: TransactionManager>(
+    State(transaction_manager): State<TM>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let tx = match transaction_manager.begin().await {
+        Ok(tx) => tx,
+        Err(e) => return ApiError::internal(e).into_response(),
+    };
+    request.extensions_mut().insert(tx.clone());
+
+    let after_commit = AfterCommit::new();
+    let response = tx.scope(after_commit.scope(next.run(request))).await;
+    if !response.status().is_success() {
+        if let Err(e) = tx.rollback().await {
+            tracing::error!("{:?}", e);
+        }
+        return response;
+    }
+    match tx.commit().await {
+        Ok(()) => {
+            after_commit.run().await;
+            response
+        }
+        Err(e) => ApiError::internal(e).into_response(),
+    }
+}
@@ -0,0 +1,219 @@
+/*!
+   Module `typescript` declares the request and response bodies of the HTTP API as TypeScript
+   types, bundled into a `types.d.ts` by the `crowdsource-types` binary, so that frontends are
+   typed against the same bodies the handlers use.
+
+   The bodies are declared next to them with [ts_interface], which fails to compile when the
+   fields declared differ from those of the body, or with a [TypeScript] impl for enums.
+*/
+
+use chrono::{DateTime, Utc};
+
+use crate::inbound::http::{
+    abuse_challenge::ChallengeData,
+    handlers::{
+        accept_terms::{AcceptTermsHttpRequestBody, AcceptTermsResponseData},
+        create_user::{CreateUserHttpRequestBody, CreateUserResponseData},
+        email_change::{
+            CancelEmailChangeResponseData, ConfirmEmailChangeHttpRequestBody,
+            ConfirmEmailChangeResponseData, EmailChangeResponseData,
+            RequestEmailChangeHttpRequestBody,
+        },
+        erase_user::EraseUserResponseData,
+        export_user_data::{
+            ExportedTermsAcceptance, ExportedUser, SharedLinkData, UserDataExportResponseData,
+        },
+        export_users_csv::ExportUsersCsvParams,
+        get_user::UserResponseData,
+        list_dead_letters::DeadLetterData,
+        list_features::ListFeaturesQuery,
+        list_user_activity::{ActivityData, ActivityPageData, ListUserActivityParams},
+        notifications::{
+            InboxData, ListNotificationsParams, MarkedReadData, NotificationData, UnreadCountData,
+        },
+        record_analytics_events::{
+            AnalyticsEventHttpRequestBody, RecordAnalyticsEventsHttpRequestBody,
+            RecordedAnalyticsEventsData,
+        },
+        redrive_dead_letter::RedriveDeadLetterResponseData,
+        search_users::{SearchUsersParams, UserSearchHitData},
+    },
+    maintenance::{MaintenanceData, SetMaintenanceHttpRequestBody},
+    runtime_config::{PatchRuntimeConfigHttpRequestBody, RuntimeConfigData},
+};
+
+/// A type with a TypeScript counterpart, as serialized to JSON.
+pub trait TypeScript {
+    /// The TypeScript type of values of this type, as referred to by other types.
+    fn ts_type() -> String;
+
+    /// The declaration of the type in `types.d.ts`, if it is declared rather than inlined.
+    fn ts_declaration() -> Option<String> {
+        None
+    }
+}
+
+/// Implements [TypeScript] for a body with named fields, declaring it as an interface.
+///
+/// Each field is given with its Rust type, and its name in JSON if serde renames it. A name
+/// ending with `?` declares an optional field, as for fields skipped when `None`.
+///
+/// ```ignore
+/// ts_interface!(ActivityData {
+///     activity_type as "type": String,
+///     terms_version as "terms_version?": Option<String>,
+/// });
+/// ```
+macro_rules! ts_interface {
+    ($name:ident { $($field:ident $(as $rename:literal)? : $ty:ty),* $(,)? }) => {
+        impl $crate::inbound::http::typescript::TypeScript for $name {
+            fn ts_type() -> String {
+                stringify!($name).to_string()
+            }
+
+            fn ts_declaration() -> Option<String> {
+                let mut declaration = format!("export interface {} {{\n", stringify!($name));
+                $(
+                    let field = [stringify!($field) $(, $rename)?];
+                    declaration.push_str(&format!(
+                        "  {}: {};\n",
+                        field[field.len() - 1],
+                        <$ty as $crate::inbound::http::typescript::TypeScript>::ts_type(),
+                    ));
+                )*
+                declaration.push('}');
+                Some(declaration)
+            }
+        }
+
+        // Fails to compile unless the fields are exactly those of the body, with their types.
+        const _: fn(&$name) = |body| {
+            let $name { $($field: _),* } = body;
+            $(let _: &$ty = &body.$field;)*
+        };
+    };
+}
+
+pub(crate) use ts_interface;
+
+macro_rules! ts_inline {
+    ($ts_type:literal: $($ty:ty),*) => {
+        $(
+            impl TypeScript for $ty {
+                fn ts_type() -> String {
+                    $ts_type.to_string()
+                }
+            }
+        )*
+    };
+}
+
+ts_inline!("string": String, uuid::Uuid, DateTime<Utc>);
+ts_inline!("number": u8, u32, u64, usize, i64);
+ts_inline!("boolean": bool);
+
+impl<T: TypeScript> TypeScript for Option<T> {
+    fn ts_type() -> String {
+        let ts_type = T::ts_type();
+        // An Option<Option<T>> tells absent from null, which optional fields do.
+        if ts_type.ends_with(" | null") {
+            ts_type
+        } else {
+            format!("{ts_type} | null")
+        }
+    }
+}
+
+impl<T: TypeScript> TypeScript for Vec<T> {
+    fn ts_type() -> String {
+        let item = T::ts_type();
+        if item.contains(' ') {
+            format!("({item})[]")
+        } else {
+            format!("{item}[]")
+        }
+    }
+}
+
+/// An inline object type with the given fields and TypeScript types.
+pub(crate) fn ts_object(fields: &[(&str, String)]) -> String {
+    let fields: Vec<String> = fields
+        .iter()
+        .map(|(name, ts_type)| format!("{name}: {ts_type}"))
+        .collect();
+    format!("{{ {} }}", fields.join("; "))
+}
+
+/// The declaration of the type alias `name` for a union of `variants`.
+pub(crate) fn ts_union(name: &str, variants: &[String]) -> String {
+    let variants: Vec<String> = variants
+        .iter()
+        .map(|variant| format!("  | {variant}"))
+        .collect();
+    format!("export type {name} =\n{};", variants.join("\n"))
+}
+
+/// The `types.d.ts` bundle, declaring the envelope of responses and every request and response
+/// body.
+pub fn bundle() -> String {
+    let declarations = [
+        ChallengeData::ts_declaration(),
+        AcceptTermsHttpRequestBody::ts_declaration(),
+        AcceptTermsResponseData::ts_declaration(),
+        CreateUserHttpRequestBody::ts_declaration(),
+        CreateUserResponseData::ts_declaration(),
+        UserResponseData::ts_declaration(),
+        RequestEmailChangeHttpRequestBody::ts_declaration(),
+        ConfirmEmailChangeHttpRequestBody::ts_declaration(),
+        EmailChangeResponseData::ts_declaration(),
+        ConfirmEmailChangeResponseData::ts_declaration(),
+        CancelEmailChangeResponseData::ts_declaration(),
+        EraseUserResponseData::ts_declaration(),
+        SharedLinkData::ts_declaration(),
+        UserDataExportResponseData::ts_declaration(),
+        ExportedUser::ts_declaration(),
+        ExportedTermsAcceptance::ts_declaration(),
+        ExportUsersCsvParams::ts_declaration(),
+        DeadLetterData::ts_declaration(),
+        ListFeaturesQuery::ts_declaration(),
+        ListUserActivityParams::ts_declaration(),
+        ActivityPageData::ts_declaration(),
+        ActivityData::ts_declaration(),
+        ListNotificationsParams::ts_declaration(),
+        InboxData::ts_declaration(),
+        NotificationData::ts_declaration(),
+        UnreadCountData::ts_declaration(),
+        MarkedReadData::ts_declaration(),
+        RecordAnalyticsEventsHttpRequestBody::ts_declaration(),
+        AnalyticsEventHttpRequestBody::ts_declaration(),
+        RecordedAnalyticsEventsData::ts_declaration(),
+        RedriveDeadLetterResponseData::ts_declaration(),
+        SearchUsersParams::ts_declaration(),
+        UserSearchHitData::ts_declaration(),
+        SetMaintenanceHttpRequestBody::ts_declaration(),
+        MaintenanceData::ts_declaration(),
+        PatchRuntimeConfigHttpRequestBody::ts_declaration(),
+        RuntimeConfigData::ts_declaration(),
+    ];
+    let mut bundle = String::from(
+        "// Generated by `crowdsource-types`, do not edit.\n\n\
+        export interface ApiResponseBody<T> {\n  status_code: number;\n  data: T;\n}\n\n\
+        export interface ApiErrorData {\n  message: string;\n}\n",
+    );
+    for declaration in declarations.into_iter().flatten() {
+        bundle.push('\n');
+        bundle.push_str(&declaration);
+        bundle.push('\n');
+    }
+    bundle
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundle_declares_all_bodies() {
+        insta::assert_snapshot!(bundle());
+    }
+}
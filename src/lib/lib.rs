@@ -1,4 +1,18 @@
+pub mod backup;
+pub mod bootstrap;
+#[cfg(feature = "client-models")]
+pub mod client;
 pub mod configuration;
+pub mod dev_seed;
 pub mod domain;
+#[cfg(feature = "error-reporting")]
+pub mod error_reporting;
+pub mod i18n;
 pub mod inbound;
+pub mod migrations;
 pub mod outbound;
+pub mod scheduler;
+pub mod shutdown;
+pub mod telemetry;
+
+pub use bootstrap::CrowdsourceApp;
@@ -0,0 +1,56 @@
+//! Module `migrations` applies the database migrations embedded in the crate.
+
+use std::sync::LazyLock;
+
+use anyhow::Context;
+use sqlx::{PgPool, migrate::Migrator};
+
+use crate::{
+    domain::crowdsrc::ports::DistributedLock, outbound::sqlx_advisory_lock::SqlxAdvisoryLock,
+};
+
+/// The name of the [DistributedLock] held while migrating.
+const MIGRATIONS_LOCK: &str = "migrations";
+
+/// The embedded migrations, applied under [MIGRATIONS_LOCK] rather than the lock sqlx takes.
+static MIGRATOR: LazyLock<Migrator> = LazyLock::new(|| {
+    let mut migrator = sqlx::migrate!("./migrations");
+    migrator.set_locking(false);
+    migrator
+});
+
+/// Applies all pending migrations.
+///
+/// The migrations are applied while holding a [DistributedLock], so replicas starting at the
+/// same time apply each migration once, the others waiting for the lock and then finding
+/// nothing to do.
+pub async fn run(db_pool: &PgPool) -> anyhow::Result<()> {
+    SqlxAdvisoryLock::new(db_pool.clone())
+        .with_lock(MIGRATIONS_LOCK, MIGRATOR.run(db_pool))
+        .await?
+        .context("failed to migrate the database")
+}
+
+/// Lists the migrations that have not been applied yet, as `<version> <description>`.
+pub async fn pending(db_pool: &PgPool) -> anyhow::Result<Vec<String>> {
+    let has_migrations_table: bool =
+        sqlx::query_scalar("SELECT to_regclass('_sqlx_migrations') IS NOT NULL")
+            .fetch_one(db_pool)
+            .await
+            .context("failed to look for the migrations table")?;
+    let applied: Vec<i64> = if has_migrations_table {
+        sqlx::query_scalar("SELECT version FROM _sqlx_migrations WHERE success")
+            .fetch_all(db_pool)
+            .await
+            .context("failed to list applied migrations")?
+    } else {
+        Vec::new()
+    };
+
+    Ok(MIGRATOR
+        .iter()
+        .filter(|migration| migration.migration_type.is_up_migration())
+        .filter(|migration| !applied.contains(&migration.version))
+        .map(|migration| format!("{} {}", migration.version, migration.description))
+        .collect())
+}
@@ -1,3 +1,26 @@
+pub mod captcha_challenge;
+pub mod circuit_breaker;
+pub mod clickhouse_analytics_sink;
 pub mod collecting_user_notifier;
+pub mod composite_user_notifier;
+pub mod config_feature_flags;
+pub mod email_templates;
 pub mod email_user_notifier;
+pub mod field_cipher;
+pub mod http_client;
+pub mod in_memory_submission_throttle;
+pub mod meilisearch_index;
+pub mod proof_of_work_challenge;
+pub mod remote_feature_flags;
+pub mod retry;
+pub mod retrying_repository;
+pub mod sendgrid_user_notifier;
+pub mod ses_user_notifier;
+pub mod sqlx_advisory_lock;
+pub mod sqlx_analytics_sink;
+pub mod sqlx_maintenance_switch;
+pub mod sqlx_runtime_config_store;
+pub mod sqlx_schedule_store;
+pub mod sqlx_transaction;
 pub mod sqlx_user_repository;
+pub mod timed_repository;
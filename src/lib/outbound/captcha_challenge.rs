@@ -0,0 +1,112 @@
+use std::net::IpAddr;
+
+use anyhow::Context;
+
+use crate::{
+    domain::crowdsrc::{
+        models::abuse_challenge::{AbuseChallengeError, Challenge},
+        ports::AbuseChallenge,
+    },
+    outbound::http_client::HttpClient,
+};
+
+/// The CAPTCHA services [CaptchaChallenge] can verify solutions with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptchaProvider {
+    HCaptcha,
+    Turnstile,
+}
+
+impl CaptchaProvider {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::HCaptcha => "hcaptcha",
+            Self::Turnstile => "turnstile",
+        }
+    }
+
+    fn verify_url(&self) -> &'static str {
+        match self {
+            Self::HCaptcha => "https://api.hcaptcha.com/siteverify",
+            Self::Turnstile => "https://challenges.cloudflare.com/turnstile/v0/siteverify",
+        }
+    }
+}
+
+/// An [AbuseChallenge] verifying CAPTCHA tokens with a [CaptchaProvider].
+#[derive(Clone)]
+pub struct CaptchaChallenge {
+    client: HttpClient,
+    provider: CaptchaProvider,
+    site_key: String,
+    secret: String,
+}
+
+impl std::fmt::Debug for CaptchaChallenge {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CaptchaChallenge")
+            .field("provider", &self.provider)
+            .field("site_key", &self.site_key)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct SiteVerifyResponse {
+    success: bool,
+}
+
+impl CaptchaChallenge {
+    pub fn new(
+        client: HttpClient,
+        provider: CaptchaProvider,
+        site_key: String,
+        secret: String,
+    ) -> Self {
+        Self {
+            client,
+            provider,
+            site_key,
+            secret,
+        }
+    }
+}
+
+impl AbuseChallenge for CaptchaChallenge {
+    async fn issue(&self) -> Result<Challenge, AbuseChallengeError> {
+        Ok(Challenge::Captcha {
+            provider: self.provider.name().to_string(),
+            site_key: self.site_key.clone(),
+        })
+    }
+
+    async fn verify(
+        &self,
+        solution: &str,
+        remote_ip: Option<IpAddr>,
+    ) -> Result<(), AbuseChallengeError> {
+        let remote_ip = remote_ip.map(|ip| ip.to_string());
+        let mut form = vec![
+            ("secret", self.secret.as_str()),
+            ("response", solution),
+            ("sitekey", self.site_key.as_str()),
+        ];
+        if let Some(remote_ip) = &remote_ip {
+            form.push(("remoteip", remote_ip));
+        }
+        let response: SiteVerifyResponse = self
+            .client
+            .send(self.client.post(self.provider.verify_url()).form(&form))
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .with_context(|| format!("failed to reach {} siteverify", self.provider.name()))?
+            .json()
+            .await
+            .with_context(|| format!("invalid {} siteverify response", self.provider.name()))?;
+        if response.success {
+            Ok(())
+        } else {
+            Err(AbuseChallengeError::Rejected)
+        }
+    }
+}
@@ -0,0 +1,516 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Utc};
+use futures::{Stream, StreamExt, future::Either};
+
+use crate::domain::crowdsrc::{
+    models::activity::{Activity, ActivityError, ActivityPage, ActivityQuery},
+    models::dead_letter::{DeadLetter, DeadLetterError, NotificationEvent},
+    models::email_change::{
+        EmailChangeError, EmailChangeToken, EmailChangeTokenHash, PendingEmailChange,
+    },
+    models::inbox::{InboxError, InboxNotification, InboxQuery},
+    models::search::{SearchError, UserSearchQuery},
+    models::terms::{AcceptTermsError, TermsAcceptance, TermsVersion},
+    models::user::{
+        CreateUserError, CreateUserOutcome, CreateUserRequest, CreateUsersError, EraseUserError,
+        GetUserError, ListUsersError, NotifyUserError, User,
+    },
+    ports::{UserNotifier, UserRepository},
+};
+
+/// Returned, wrapped in the operation's error type, when a call is rejected by an open
+/// [CircuitBreaker].
+#[derive(Debug, Clone, Copy, thiserror::Error)]
+#[error("circuit breaker is open")]
+pub struct CircuitOpenError;
+
+#[derive(Debug, Clone, Copy)]
+enum CircuitState {
+    Closed { consecutive_failures: u32 },
+    Open { until: Instant },
+    HalfOpen,
+}
+
+/// A decorator that fails fast once the wrapped [UserRepository] or [UserNotifier] keeps
+/// failing.
+///
+/// After `failure_threshold` consecutive failures the circuit opens and every call fails with
+/// [CircuitOpenError] for `open_duration`. After that, calls are let through again (half-open):
+/// the first success closes the circuit, the first failure opens it again.
+///
+/// Only unexpected errors count as failures: duplicates are valid answers from a healthy backend.
+#[derive(Debug, Clone)]
+pub struct CircuitBreaker<T> {
+    inner: T,
+    failure_threshold: u32,
+    open_duration: Duration,
+    state: Arc<Mutex<CircuitState>>,
+}
+
+impl<T> CircuitBreaker<T> {
+    pub fn new(inner: T, failure_threshold: u32, open_duration: Duration) -> Self {
+        Self {
+            inner,
+            failure_threshold: failure_threshold.max(1),
+            open_duration,
+            state: Arc::new(Mutex::new(CircuitState::Closed {
+                consecutive_failures: 0,
+            })),
+        }
+    }
+
+    /// Returns whether the circuit is currently rejecting calls.
+    pub fn is_open(&self) -> bool {
+        matches!(*self.state.lock().unwrap(), CircuitState::Open { until } if Instant::now() < until)
+    }
+
+    fn permit(&self) -> Result<(), CircuitOpenError> {
+        let mut state = self.state.lock().unwrap();
+        if let CircuitState::Open { until } = *state {
+            if Instant::now() < until {
+                return Err(CircuitOpenError);
+            }
+            *state = CircuitState::HalfOpen;
+        }
+        Ok(())
+    }
+
+    fn record(&self, failed: bool) {
+        let mut state = self.state.lock().unwrap();
+        *state = match (*state, failed) {
+            (_, false) => CircuitState::Closed {
+                consecutive_failures: 0,
+            },
+            (
+                CircuitState::Closed {
+                    consecutive_failures,
+                },
+                true,
+            ) if consecutive_failures + 1 < self.failure_threshold => CircuitState::Closed {
+                consecutive_failures: consecutive_failures + 1,
+            },
+            (_, true) => {
+                tracing::warn!("opening circuit breaker for {:?}", self.open_duration);
+                CircuitState::Open {
+                    until: Instant::now() + self.open_duration,
+                }
+            }
+        };
+    }
+}
+
+impl<R> UserRepository for CircuitBreaker<R>
+where
+    R: UserRepository,
+{
+    async fn create_user(&self, req: &CreateUserRequest) -> Result<User, CreateUserError> {
+        self.permit().map_err(anyhow::Error::from)?;
+        let result = self.inner.create_user(req).await;
+        self.record(matches!(result, Err(CreateUserError::Unknown(_))));
+        result
+    }
+
+    async fn create_users(
+        &self,
+        reqs: &[CreateUserRequest],
+    ) -> Result<Vec<CreateUserOutcome>, CreateUsersError> {
+        self.permit().map_err(anyhow::Error::from)?;
+        let result = self.inner.create_users(reqs).await;
+        self.record(result.is_err());
+        result
+    }
+
+    fn stream_users(&self) -> impl Stream<Item = Result<User, ListUsersError>> + Send {
+        match self.permit() {
+            Err(err) => Either::Left(futures::stream::once(async move {
+                Err(ListUsersError::Unknown(err.into()))
+            })),
+            Ok(()) => Either::Right(
+                self.inner
+                    .stream_users()
+                    .inspect(|item| self.record(item.is_err())),
+            ),
+        }
+    }
+
+    async fn get_user(&self, id: &uuid::Uuid) -> Result<User, GetUserError> {
+        self.permit().map_err(anyhow::Error::from)?;
+        let result = self.inner.get_user(id).await;
+        self.record(matches!(result, Err(GetUserError::Unknown(_))));
+        result
+    }
+
+    async fn erase_user(&self, id: &uuid::Uuid) -> Result<User, EraseUserError> {
+        self.permit().map_err(anyhow::Error::from)?;
+        let result = self.inner.erase_user(id).await;
+        self.record(matches!(result, Err(EraseUserError::Unknown(_))));
+        result
+    }
+
+    async fn accept_terms(
+        &self,
+        user_id: &uuid::Uuid,
+        version: &TermsVersion,
+    ) -> Result<TermsAcceptance, AcceptTermsError> {
+        self.permit().map_err(anyhow::Error::from)?;
+        let result = self.inner.accept_terms(user_id, version).await;
+        self.record(matches!(result, Err(AcceptTermsError::Unknown(_))));
+        result
+    }
+
+    async fn list_terms_acceptances(
+        &self,
+        user_id: &uuid::Uuid,
+    ) -> Result<Vec<TermsAcceptance>, GetUserError> {
+        self.permit().map_err(anyhow::Error::from)?;
+        let result = self.inner.list_terms_acceptances(user_id).await;
+        self.record(matches!(result, Err(GetUserError::Unknown(_))));
+        result
+    }
+
+    async fn save_dead_letter(
+        &self,
+        event: &NotificationEvent,
+        failure_reason: &str,
+    ) -> Result<DeadLetter, DeadLetterError> {
+        self.permit().map_err(anyhow::Error::from)?;
+        let result = self.inner.save_dead_letter(event, failure_reason).await;
+        self.record(result.is_err());
+        result
+    }
+
+    async fn list_dead_letters(&self) -> Result<Vec<DeadLetter>, DeadLetterError> {
+        self.permit().map_err(anyhow::Error::from)?;
+        let result = self.inner.list_dead_letters().await;
+        self.record(result.is_err());
+        result
+    }
+
+    async fn get_dead_letter(&self, id: &uuid::Uuid) -> Result<DeadLetter, DeadLetterError> {
+        self.permit().map_err(anyhow::Error::from)?;
+        let result = self.inner.get_dead_letter(id).await;
+        self.record(matches!(result, Err(DeadLetterError::Unknown(_))));
+        result
+    }
+
+    async fn record_dead_letter_retry(
+        &self,
+        id: &uuid::Uuid,
+        failure_reason: &str,
+    ) -> Result<DeadLetter, DeadLetterError> {
+        self.permit().map_err(anyhow::Error::from)?;
+        let result = self
+            .inner
+            .record_dead_letter_retry(id, failure_reason)
+            .await;
+        self.record(matches!(result, Err(DeadLetterError::Unknown(_))));
+        result
+    }
+
+    async fn delete_dead_letter(&self, id: &uuid::Uuid) -> Result<(), DeadLetterError> {
+        self.permit().map_err(anyhow::Error::from)?;
+        let result = self.inner.delete_dead_letter(id).await;
+        self.record(matches!(result, Err(DeadLetterError::Unknown(_))));
+        result
+    }
+
+    async fn import_user(
+        &self,
+        user: &User,
+        terms_acceptances: &[TermsAcceptance],
+    ) -> Result<(), CreateUserError> {
+        self.permit().map_err(anyhow::Error::from)?;
+        let result = self.inner.import_user(user, terms_acceptances).await;
+        self.record(matches!(result, Err(CreateUserError::Unknown(_))));
+        result
+    }
+
+    async fn record_activity(&self, activity: &Activity) -> Result<(), ActivityError> {
+        self.permit().map_err(anyhow::Error::from)?;
+        let result = self.inner.record_activity(activity).await;
+        self.record(matches!(result, Err(ActivityError::Unknown(_))));
+        result
+    }
+
+    async fn list_activity(
+        &self,
+        user_id: &uuid::Uuid,
+        query: &ActivityQuery,
+    ) -> Result<ActivityPage, ActivityError> {
+        self.permit().map_err(anyhow::Error::from)?;
+        let result = self.inner.list_activity(user_id, query).await;
+        self.record(matches!(result, Err(ActivityError::Unknown(_))));
+        result
+    }
+
+    async fn save_email_change(
+        &self,
+        change: &PendingEmailChange,
+        token: &EmailChangeToken,
+    ) -> Result<(), EmailChangeError> {
+        self.permit().map_err(anyhow::Error::from)?;
+        let result = self.inner.save_email_change(change, token).await;
+        self.record(matches!(result, Err(EmailChangeError::Unknown(_))));
+        result
+    }
+
+    async fn confirm_email_change(
+        &self,
+        user_id: &uuid::Uuid,
+        token: &EmailChangeToken,
+    ) -> Result<User, EmailChangeError> {
+        self.permit().map_err(anyhow::Error::from)?;
+        let result = self.inner.confirm_email_change(user_id, token).await;
+        self.record(matches!(result, Err(EmailChangeError::Unknown(_))));
+        result
+    }
+
+    fn stream_email_changes(
+        &self,
+    ) -> impl Stream<Item = Result<(PendingEmailChange, EmailChangeTokenHash), EmailChangeError>> + Send
+    {
+        match self.permit() {
+            Err(err) => Either::Left(futures::stream::once(async move {
+                Err(EmailChangeError::Unknown(err.into()))
+            })),
+            Ok(()) => Either::Right(
+                self.inner
+                    .stream_email_changes()
+                    .inspect(|item| self.record(item.is_err())),
+            ),
+        }
+    }
+
+    async fn import_email_change(
+        &self,
+        change: &PendingEmailChange,
+        token_hash: &EmailChangeTokenHash,
+    ) -> Result<(), EmailChangeError> {
+        self.permit().map_err(anyhow::Error::from)?;
+        let result = self.inner.import_email_change(change, token_hash).await;
+        self.record(matches!(result, Err(EmailChangeError::Unknown(_))));
+        result
+    }
+
+    async fn delete_email_change(&self, user_id: &uuid::Uuid) -> Result<(), EmailChangeError> {
+        self.permit().map_err(anyhow::Error::from)?;
+        let result = self.inner.delete_email_change(user_id).await;
+        self.record(matches!(result, Err(EmailChangeError::Unknown(_))));
+        result
+    }
+
+    async fn delete_expired_email_changes(
+        &self,
+        now: &DateTime<Utc>,
+    ) -> Result<u64, EmailChangeError> {
+        self.permit().map_err(anyhow::Error::from)?;
+        let result = self.inner.delete_expired_email_changes(now).await;
+        self.record(matches!(result, Err(EmailChangeError::Unknown(_))));
+        result
+    }
+
+    async fn save_notification(&self, notification: &InboxNotification) -> Result<(), InboxError> {
+        self.permit().map_err(anyhow::Error::from)?;
+        let result = self.inner.save_notification(notification).await;
+        self.record(matches!(result, Err(InboxError::Unknown(_))));
+        result
+    }
+
+    async fn list_notifications(
+        &self,
+        user_id: &uuid::Uuid,
+        query: &InboxQuery,
+    ) -> Result<Vec<InboxNotification>, InboxError> {
+        self.permit().map_err(anyhow::Error::from)?;
+        let result = self.inner.list_notifications(user_id, query).await;
+        self.record(matches!(result, Err(InboxError::Unknown(_))));
+        result
+    }
+
+    fn stream_notifications(
+        &self,
+    ) -> impl Stream<Item = Result<InboxNotification, InboxError>> + Send {
+        match self.permit() {
+            Err(err) => Either::Left(futures::stream::once(async move {
+                Err(InboxError::Unknown(err.into()))
+            })),
+            Ok(()) => Either::Right(
+                self.inner
+                    .stream_notifications()
+                    .inspect(|item| self.record(item.is_err())),
+            ),
+        }
+    }
+
+    async fn count_unread_notifications(&self, user_id: &uuid::Uuid) -> Result<u64, InboxError> {
+        self.permit().map_err(anyhow::Error::from)?;
+        let result = self.inner.count_unread_notifications(user_id).await;
+        self.record(matches!(result, Err(InboxError::Unknown(_))));
+        result
+    }
+
+    async fn mark_notifications_read(
+        &self,
+        user_id: &uuid::Uuid,
+        notification_id: Option<&uuid::Uuid>,
+        read_at: &DateTime<Utc>,
+    ) -> Result<u64, InboxError> {
+        self.permit().map_err(anyhow::Error::from)?;
+        let result = self
+            .inner
+            .mark_notifications_read(user_id, notification_id, read_at)
+            .await;
+        self.record(matches!(result, Err(InboxError::Unknown(_))));
+        result
+    }
+
+    async fn delete_notifications_before(&self, cutoff: &DateTime<Utc>) -> Result<u64, InboxError> {
+        self.permit().map_err(anyhow::Error::from)?;
+        let result = self.inner.delete_notifications_before(cutoff).await;
+        self.record(matches!(result, Err(InboxError::Unknown(_))));
+        result
+    }
+
+    async fn search_users(&self, query: &UserSearchQuery) -> Result<Vec<User>, SearchError> {
+        self.permit().map_err(anyhow::Error::from)?;
+        let result = self.inner.search_users(query).await;
+        self.record(matches!(result, Err(SearchError::Unknown(_))));
+        result
+    }
+}
+
+impl<N> UserNotifier for CircuitBreaker<N>
+where
+    N: UserNotifier,
+{
+    async fn user_created(&self, user: &User) -> Result<(), NotifyUserError> {
+        self.permit().map_err(anyhow::Error::from)?;
+        let result = self.inner.user_created(user).await;
+        self.record(result.is_err());
+        result
+    }
+
+    async fn email_change_requested(
+        &self,
+        user: &User,
+        change: &PendingEmailChange,
+        token: &EmailChangeToken,
+    ) -> Result<(), NotifyUserError> {
+        self.permit().map_err(anyhow::Error::from)?;
+        let result = self.inner.email_change_requested(user, change, token).await;
+        self.record(result.is_err());
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+    use anyhow::anyhow;
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    use crate::domain::crowdsrc::models::user::{EmailAddress, UserName};
+
+    use super::*;
+
+    #[derive(Clone)]
+    struct FailingUserNotifier {
+        failing: Arc<AtomicBool>,
+        calls: Arc<AtomicU32>,
+    }
+
+    impl FailingUserNotifier {
+        fn new() -> Self {
+            Self {
+                failing: Arc::new(AtomicBool::new(true)),
+                calls: Arc::new(AtomicU32::new(0)),
+            }
+        }
+    }
+
+    impl UserNotifier for FailingUserNotifier {
+        async fn user_created(&self, _: &User) -> Result<(), NotifyUserError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if self.failing.load(Ordering::SeqCst) {
+                Err(anyhow!("SMTP server unreachable").into())
+            } else {
+                Ok(())
+            }
+        }
+
+        async fn email_change_requested(
+            &self,
+            _: &User,
+            _: &PendingEmailChange,
+            _: &EmailChangeToken,
+        ) -> Result<(), NotifyUserError> {
+            unimplemented!()
+        }
+    }
+
+    fn user() -> User {
+        User::new(
+            Uuid::new_v4(),
+            UserName::new("Kristoffer").unwrap(),
+            EmailAddress::new("kristoffer@example.com").unwrap(),
+            Utc::now(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_opens_after_failure_threshold_and_fails_fast() {
+        let inner = FailingUserNotifier::new();
+        let breaker = CircuitBreaker::new(inner.clone(), 2, Duration::from_secs(60));
+        let user = user();
+
+        for _ in 0..2 {
+            assert!(breaker.user_created(&user).await.is_err());
+        }
+        let actual = breaker.user_created(&user).await;
+
+        assert!(breaker.is_open(), "expected circuit breaker to be open");
+        let NotifyUserError::Unknown(cause) = actual.unwrap_err();
+        assert!(
+            cause.is::<CircuitOpenError>(),
+            "expected CircuitOpenError, but got {:?}",
+            cause
+        );
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_closes_after_successful_trial_call() {
+        let inner = FailingUserNotifier::new();
+        let breaker = CircuitBreaker::new(inner.clone(), 1, Duration::ZERO);
+        let user = user();
+        assert!(breaker.user_created(&user).await.is_err());
+
+        inner.failing.store(false, Ordering::SeqCst);
+        let actual = breaker.user_created(&user).await;
+
+        assert!(actual.is_ok(), "expected success, but got {:?}", actual);
+        assert!(!breaker.is_open(), "expected circuit breaker to be closed");
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_success_resets_consecutive_failures() {
+        let inner = FailingUserNotifier::new();
+        let breaker = CircuitBreaker::new(inner.clone(), 2, Duration::from_secs(60));
+        let user = user();
+
+        assert!(breaker.user_created(&user).await.is_err());
+        inner.failing.store(false, Ordering::SeqCst);
+        assert!(breaker.user_created(&user).await.is_ok());
+        inner.failing.store(true, Ordering::SeqCst);
+        assert!(breaker.user_created(&user).await.is_err());
+
+        assert!(!breaker.is_open(), "expected circuit breaker to be closed");
+    }
+}
@@ -0,0 +1,197 @@
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+
+use crate::{
+    domain::crowdsrc::{
+        models::analytics::{AnalyticsError, AnalyticsEvent},
+        ports::AnalyticsSink,
+    },
+    outbound::http_client::HttpClient,
+};
+
+/// An [AnalyticsSink] inserting [AnalyticsEvent]s into a ClickHouse table through its HTTP
+/// interface, one row per event.
+///
+/// The table is managed by the operator, with the columns:
+///
+/// ```sql
+/// CREATE TABLE analytics_events (
+///     user_id UUID,
+///     task_id String,
+///     kind LowCardinality(String),
+///     duration_ms Nullable(UInt64),
+///     occurred_at DateTime64(3, 'UTC'),
+///     received_at DateTime64(3, 'UTC')
+/// ) ENGINE = MergeTree PARTITION BY toYYYYMM(occurred_at) ORDER BY (task_id, occurred_at);
+/// ```
+#[derive(Clone)]
+pub struct ClickHouseAnalyticsSink {
+    client: HttpClient,
+    url: String,
+    user: String,
+    password: String,
+    table: String,
+}
+
+impl std::fmt::Debug for ClickHouseAnalyticsSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ClickHouseAnalyticsSink")
+            .field("url", &self.url)
+            .field("user", &self.user)
+            .field("table", &self.table)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ClickHouseAnalyticsSink {
+    /// Inserts into `table` of the ClickHouse server at `url`, as `user`.
+    pub fn new(client: HttpClient, url: &str, user: String, password: String, table: &str) -> Self {
+        Self {
+            client,
+            url: url.trim_end_matches('/').to_string(),
+            user,
+            password,
+            table: table.to_string(),
+        }
+    }
+}
+
+impl AnalyticsSink for ClickHouseAnalyticsSink {
+    async fn write_events(&self, events: &[AnalyticsEvent]) -> Result<(), AnalyticsError> {
+        let url = reqwest::Url::parse_with_params(
+            &format!("{}/", self.url),
+            [
+                (
+                    "query",
+                    format!("INSERT INTO {} FORMAT JSONEachRow", self.table).as_str(),
+                ),
+                ("date_time_input_format", "best_effort"),
+            ],
+        )
+        .context("invalid ClickHouse URL")?;
+        let mut rows = Vec::new();
+        for event in events {
+            serde_json::to_writer(&mut rows, &EventRow::from(event))
+                .context("failed to serialize analytics event")?;
+            rows.push(b'\n');
+        }
+        self.client
+            .send(
+                self.client
+                    .post(url.as_str())
+                    .header("X-ClickHouse-User", &self.user)
+                    .header("X-ClickHouse-Key", &self.password)
+                    .body(rows),
+            )
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .context("failed to insert analytics events into ClickHouse")?;
+        tracing::debug!(
+            events = events.len(),
+            "inserted analytics events into ClickHouse"
+        );
+        Ok(())
+    }
+}
+
+/// The row an [AnalyticsEvent] is inserted as.
+#[derive(Debug, serde::Serialize)]
+struct EventRow<'a> {
+    user_id: uuid::Uuid,
+    task_id: &'a str,
+    kind: &'static str,
+    duration_ms: Option<u64>,
+    occurred_at: DateTime<Utc>,
+    received_at: DateTime<Utc>,
+}
+
+impl<'a> From<&'a AnalyticsEvent> for EventRow<'a> {
+    fn from(event: &'a AnalyticsEvent) -> Self {
+        Self {
+            user_id: *event.user_id(),
+            task_id: event.task_id(),
+            kind: event.kind().as_str(),
+            duration_ms: event.kind().duration_ms(),
+            occurred_at: *event.occurred_at(),
+            received_at: *event.received_at(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashMap,
+        sync::{Arc, Mutex},
+    };
+
+    use axum::{extract::Query, http::HeaderMap, routing::post};
+
+    use super::*;
+    use crate::domain::crowdsrc::models::analytics::AnalyticsEventKind;
+
+    type Received = Arc<Mutex<Vec<(HashMap<String, String>, Option<String>, String)>>>;
+
+    /// Stands in for the HTTP interface of ClickHouse, keeping the queries, users and bodies
+    /// received.
+    async fn serve_clickhouse(received: Received) -> String {
+        let router = axum::Router::new().route(
+            "/",
+            post(
+                move |Query(params): Query<HashMap<String, String>>,
+                      headers: HeaderMap,
+                      body: String| async move {
+                    let user = headers
+                        .get("x-clickhouse-user")
+                        .map(|value| value.to_str().unwrap().to_string());
+                    received.lock().unwrap().push((params, user, body));
+                },
+            ),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, router).await });
+        url
+    }
+
+    #[tokio::test]
+    async fn test_write_events_inserts_a_row_per_event() {
+        let received = Received::default();
+        let url = serve_clickhouse(received.clone()).await;
+        let sink = ClickHouseAnalyticsSink::new(
+            HttpClient::default(),
+            &url,
+            "crowdsource".to_string(),
+            "secret".to_string(),
+            "analytics_events",
+        );
+        let event = |kind| {
+            AnalyticsEvent::new(uuid::Uuid::new_v4(), "task-1", kind, Utc::now(), Utc::now())
+                .unwrap()
+        };
+
+        sink.write_events(&[
+            event(AnalyticsEventKind::TaskViewed),
+            event(AnalyticsEventKind::TimeOnTask { duration_ms: 1500 }),
+        ])
+        .await
+        .unwrap();
+
+        let received = received.lock().unwrap();
+        let (params, user, body) = &received[0];
+        assert_eq!(
+            params["query"],
+            "INSERT INTO analytics_events FORMAT JSONEachRow"
+        );
+        assert_eq!(user.as_deref(), Some("crowdsource"));
+        let rows: Vec<serde_json::Value> = body
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0]["kind"], "task_viewed");
+        assert!(rows[0]["duration_ms"].is_null());
+        assert_eq!(rows[1]["kind"], "time_on_task");
+        assert_eq!(rows[1]["duration_ms"], 1500);
+    }
+}
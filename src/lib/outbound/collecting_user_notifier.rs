@@ -2,7 +2,11 @@ use std::{collections::HashMap, sync::Arc};
 
 use tokio::sync::RwLock;
 
-use crate::domain::crowdsrc::{models::user::EmailAddress, ports::UserNotifier};
+use crate::domain::crowdsrc::{
+    models::email_change::{EmailChangeToken, PendingEmailChange},
+    models::user::{EmailAddress, NotifyUserError, User},
+    ports::UserNotifier,
+};
 #[derive(Clone, Debug)]
 pub struct CollectingUserNotifier {
     user_email_map: Arc<RwLock<HashMap<EmailAddress, String>>>,
@@ -19,12 +23,27 @@ impl UserNotifier for CollectingUserNotifier {
     fn user_created(
         &self,
         user: &crate::domain::crowdsrc::models::user::User,
-    ) -> impl Future<Output = ()> + Send {
+    ) -> impl Future<Output = Result<(), NotifyUserError>> + Send {
         async {
             self.user_email_map
                 .write()
                 .await
                 .insert(user.email().clone(), String::new());
+            Ok(())
         }
     }
+
+    /// Collects `token` as the message to the new address of `change`.
+    async fn email_change_requested(
+        &self,
+        _: &User,
+        change: &PendingEmailChange,
+        token: &EmailChangeToken,
+    ) -> Result<(), NotifyUserError> {
+        self.user_email_map
+            .write()
+            .await
+            .insert(change.new_email().clone(), token.as_str().to_string());
+        Ok(())
+    }
 }
@@ -0,0 +1,242 @@
+use std::{pin::Pin, sync::Arc};
+
+use anyhow::anyhow;
+
+use crate::domain::crowdsrc::{
+    models::email_change::{EmailChangeToken, PendingEmailChange},
+    models::user::{NotifyUserError, User},
+    ports::UserNotifier,
+};
+
+type NotifyFuture<'a> = Pin<Box<dyn Future<Output = Result<(), NotifyUserError>> + Send + 'a>>;
+
+/// An object safe version of [UserNotifier], so that notifiers of different types can be stored
+/// together.
+trait DynUserNotifier: Send + Sync + 'static {
+    fn name(&self) -> &'static str;
+
+    fn notify_user_created<'a>(&'a self, user: &'a User) -> NotifyFuture<'a>;
+
+    fn notify_email_change_requested<'a>(
+        &'a self,
+        user: &'a User,
+        change: &'a PendingEmailChange,
+        token: &'a EmailChangeToken,
+    ) -> NotifyFuture<'a>;
+}
+
+impl<N: UserNotifier> DynUserNotifier for N {
+    fn name(&self) -> &'static str {
+        std::any::type_name::<N>()
+    }
+
+    fn notify_user_created<'a>(&'a self, user: &'a User) -> NotifyFuture<'a> {
+        Box::pin(UserNotifier::user_created(self, user))
+    }
+
+    fn notify_email_change_requested<'a>(
+        &'a self,
+        user: &'a User,
+        change: &'a PendingEmailChange,
+        token: &'a EmailChangeToken,
+    ) -> NotifyFuture<'a> {
+        Box::pin(UserNotifier::email_change_requested(
+            self, user, change, token,
+        ))
+    }
+}
+
+/// A [UserNotifier] that sends every event to several notifiers, e.g. by email and to a webhook.
+///
+/// The notifiers are invoked concurrently, and a failing notifier doesn't keep the event from
+/// reaching the others. The result is an error if any of them failed.
+#[derive(Clone, Default)]
+pub struct CompositeUserNotifier {
+    notifiers: Vec<Arc<dyn DynUserNotifier>>,
+}
+
+impl CompositeUserNotifier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `notifier` to the notifiers events are sent to.
+    pub fn with(mut self, notifier: impl UserNotifier) -> Self {
+        self.notifiers.push(Arc::new(notifier));
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.notifiers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.notifiers.is_empty()
+    }
+}
+
+impl std::fmt::Debug for CompositeUserNotifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.notifiers.iter().map(|notifier| notifier.name()))
+            .finish()
+    }
+}
+
+impl CompositeUserNotifier {
+    /// Sends an event to every notifier through `notify`, failing if any of them failed.
+    async fn notify_all<'a>(
+        &'a self,
+        notify: impl Fn(&'a dyn DynUserNotifier) -> NotifyFuture<'a>,
+    ) -> Result<(), NotifyUserError> {
+        let results = futures::future::join_all(
+            self.notifiers
+                .iter()
+                .map(|notifier| notify(notifier.as_ref())),
+        )
+        .await;
+
+        let failed: Vec<_> = self
+            .notifiers
+            .iter()
+            .zip(results)
+            .filter_map(|(notifier, result)| {
+                let err = result.err()?;
+                tracing::warn!("{} failed to notify user: {:?}", notifier.name(), err);
+                Some(notifier.name())
+            })
+            .collect();
+        if failed.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "{} of {} notifiers failed: {}",
+                failed.len(),
+                self.notifiers.len(),
+                failed.join(", ")
+            )
+            .into())
+        }
+    }
+}
+
+impl UserNotifier for CompositeUserNotifier {
+    async fn user_created(&self, user: &User) -> Result<(), NotifyUserError> {
+        self.notify_all(|notifier| notifier.notify_user_created(user))
+            .await
+    }
+
+    async fn email_change_requested(
+        &self,
+        user: &User,
+        change: &PendingEmailChange,
+        token: &EmailChangeToken,
+    ) -> Result<(), NotifyUserError> {
+        self.notify_all(|notifier| notifier.notify_email_change_requested(user, change, token))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    use crate::domain::crowdsrc::models::user::{EmailAddress, UserName};
+
+    use super::*;
+
+    #[derive(Clone, Default)]
+    struct CountingUserNotifier {
+        calls: Arc<AtomicU32>,
+    }
+
+    impl UserNotifier for CountingUserNotifier {
+        async fn user_created(&self, _: &User) -> Result<(), NotifyUserError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+
+        async fn email_change_requested(
+            &self,
+            _: &User,
+            _: &PendingEmailChange,
+            _: &EmailChangeToken,
+        ) -> Result<(), NotifyUserError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[derive(Clone)]
+    struct FailingUserNotifier;
+
+    impl UserNotifier for FailingUserNotifier {
+        async fn user_created(&self, _: &User) -> Result<(), NotifyUserError> {
+            Err(anyhow!("webhook unreachable").into())
+        }
+
+        async fn email_change_requested(
+            &self,
+            _: &User,
+            _: &PendingEmailChange,
+            _: &EmailChangeToken,
+        ) -> Result<(), NotifyUserError> {
+            Err(anyhow!("webhook unreachable").into())
+        }
+    }
+
+    fn user() -> User {
+        User::new(
+            Uuid::new_v4(),
+            UserName::new("Kristoffer").unwrap(),
+            EmailAddress::new("kristoffer@example.com").unwrap(),
+            Utc::now(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_notifies_all_notifiers() {
+        let first = CountingUserNotifier::default();
+        let second = CountingUserNotifier::default();
+        let notifier = CompositeUserNotifier::new()
+            .with(first.clone())
+            .with(second.clone());
+
+        let actual = notifier.user_created(&user()).await;
+
+        assert!(actual.is_ok(), "expected success, but got {:?}", actual);
+        assert_eq!(first.calls.load(Ordering::SeqCst), 1);
+        assert_eq!(second.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_failing_notifier_does_not_affect_the_others() {
+        let counting = CountingUserNotifier::default();
+        let notifier = CompositeUserNotifier::new()
+            .with(FailingUserNotifier)
+            .with(counting.clone());
+
+        let actual = notifier.user_created(&user()).await;
+
+        let NotifyUserError::Unknown(cause) = actual.unwrap_err();
+        assert!(
+            cause.to_string().starts_with("1 of 2 notifiers failed"),
+            "unexpected error: {}",
+            cause
+        );
+        assert_eq!(counting.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_without_notifiers_succeeds() {
+        let notifier = CompositeUserNotifier::new();
+
+        let actual = notifier.user_created(&user()).await;
+
+        assert!(notifier.is_empty());
+        assert!(actual.is_ok(), "expected success, but got {:?}", actual);
+    }
+}
@@ -0,0 +1,131 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+use crate::{
+    configuration::FeatureFlagSettings,
+    domain::crowdsrc::{models::feature_flag::FeatureFlag, ports::FeatureFlags},
+};
+
+/// [FeatureFlags] as set in the configuration file.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigFeatureFlags {
+    flags: Arc<RwLock<HashMap<String, FeatureFlagSettings>>>,
+}
+
+impl ConfigFeatureFlags {
+    pub fn new(flags: HashMap<String, FeatureFlagSettings>) -> Self {
+        Self {
+            flags: Arc::new(RwLock::new(flags)),
+        }
+    }
+
+    /// Replaces the flags, such as when the configuration file is reloaded.
+    pub fn set_flags(&self, flags: HashMap<String, FeatureFlagSettings>) {
+        *self.flags.write().expect("feature flags lock poisoned") = flags;
+    }
+}
+
+impl FeatureFlags for ConfigFeatureFlags {
+    async fn enabled(&self, tenant: Option<&str>) -> Vec<FeatureFlag> {
+        enabled_flags(
+            &self.flags.read().expect("feature flags lock poisoned"),
+            tenant,
+        )
+    }
+}
+
+/// The flags of `flags` switched on for `tenant`, in alphabetical order.
+pub(super) fn enabled_flags(
+    flags: &HashMap<String, FeatureFlagSettings>,
+    tenant: Option<&str>,
+) -> Vec<FeatureFlag> {
+    let mut enabled: Vec<FeatureFlag> = flags
+        .iter()
+        .filter(|(_, settings)| settings.is_enabled(tenant))
+        .map(|(name, _)| FeatureFlag::new(name))
+        .collect();
+    enabled.sort();
+    enabled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flags() -> ConfigFeatureFlags {
+        ConfigFeatureFlags::new(HashMap::from([
+            (
+                "anonymous_mode".to_string(),
+                FeatureFlagSettings {
+                    enabled: false,
+                    tenants: HashMap::from([("acme".to_string(), true)]),
+                },
+            ),
+            (
+                "payments".to_string(),
+                FeatureFlagSettings {
+                    enabled: true,
+                    tenants: HashMap::from([("acme".to_string(), false)]),
+                },
+            ),
+            (
+                "new_assignment".to_string(),
+                FeatureFlagSettings {
+                    enabled: true,
+                    tenants: HashMap::new(),
+                },
+            ),
+        ]))
+    }
+
+    #[tokio::test]
+    async fn lists_globally_enabled_flags_in_order() {
+        let enabled = flags().enabled(None).await;
+
+        assert_eq!(
+            enabled,
+            vec![
+                FeatureFlag::new("new_assignment"),
+                FeatureFlag::new("payments")
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn tenant_settings_override_global_ones() {
+        let flags = flags();
+
+        assert!(
+            flags
+                .is_enabled(&FeatureFlag::new("anonymous_mode"), Some("acme"))
+                .await
+        );
+        assert!(
+            !flags
+                .is_enabled(&FeatureFlag::new("payments"), Some("acme"))
+                .await
+        );
+        assert!(
+            flags
+                .is_enabled(&FeatureFlag::new("payments"), Some("other"))
+                .await
+        );
+    }
+
+    #[tokio::test]
+    async fn unknown_flags_are_disabled() {
+        assert!(!flags().is_enabled(&FeatureFlag::new("unknown"), None).await);
+    }
+
+    #[tokio::test]
+    async fn replaced_flags_apply_to_clones() {
+        let flags = flags();
+        let clone = flags.clone();
+
+        flags.set_flags(HashMap::new());
+
+        assert!(clone.enabled(None).await.is_empty());
+    }
+}
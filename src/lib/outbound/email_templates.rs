@@ -0,0 +1,230 @@
+use std::{path::Path, sync::Arc};
+
+use anyhow::Context;
+
+use crate::i18n::Locale;
+
+/// The context an email template is rendered with.
+///
+/// An email named `welcome` is rendered from the templates `<locale>/welcome.subject.txt` and
+/// `<locale>/welcome.body.txt`, falling back to the default [Locale] if there are no templates
+/// for the requested one.
+pub trait EmailTemplate: serde::Serialize {
+    const NAME: &'static str;
+}
+
+/// Sent when a user has been created.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct WelcomeEmail {
+    pub username: String,
+}
+
+impl EmailTemplate for WelcomeEmail {
+    const NAME: &'static str = "welcome";
+}
+
+/// Asks the user to confirm their email address.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ConfirmationEmail {
+    pub username: String,
+    pub confirmation_url: String,
+}
+
+impl EmailTemplate for ConfirmationEmail {
+    const NAME: &'static str = "confirmation";
+}
+
+/// Sent to the new address of a user changing their email, with the token confirming it.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct EmailChangeConfirmationEmail {
+    pub username: String,
+    pub token: String,
+    /// When the token expires, as it should be shown to the user.
+    pub expires_at: String,
+}
+
+impl EmailTemplate for EmailChangeConfirmationEmail {
+    const NAME: &'static str = "email_change_confirmation";
+}
+
+/// Sent to the current address of a user changing their email, so that they notice if someone
+/// else is changing it.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct EmailChangeNoticeEmail {
+    pub username: String,
+    pub new_email: String,
+}
+
+impl EmailTemplate for EmailChangeNoticeEmail {
+    const NAME: &'static str = "email_change_notice";
+}
+
+const EMAILS: [&str; 4] = [
+    WelcomeEmail::NAME,
+    ConfirmationEmail::NAME,
+    EmailChangeConfirmationEmail::NAME,
+    EmailChangeNoticeEmail::NAME,
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderedEmail {
+    pub subject: String,
+    pub body: String,
+}
+
+/// Email templates loaded from a directory with one subdirectory per [Locale].
+#[derive(Debug, Clone)]
+pub struct EmailTemplates {
+    tera: Arc<tera::Tera>,
+}
+
+impl EmailTemplates {
+    /// Loads all templates in `dir`.
+    ///
+    /// Fails if a template can't be parsed, or if an email is missing in the default [Locale].
+    pub fn from_dir(dir: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let dir = dir.as_ref();
+        let tera = tera::Tera::new(&format!("{}/**/*.txt", dir.display()))
+            .with_context(|| format!("failed to load email templates from {}", dir.display()))?;
+        let templates = Self {
+            tera: Arc::new(tera),
+        };
+        for name in EMAILS {
+            for part in ["subject", "body"] {
+                let template = template_name(Locale::default(), name, part);
+                anyhow::ensure!(
+                    templates.has_template(&template),
+                    "email template {} is missing in {}",
+                    template,
+                    dir.display()
+                );
+            }
+        }
+        Ok(templates)
+    }
+
+    /// Renders `email` in `locale`.
+    pub fn render<T: EmailTemplate>(
+        &self,
+        locale: Locale,
+        email: &T,
+    ) -> anyhow::Result<RenderedEmail> {
+        let context = tera::Context::from_serialize(email)
+            .with_context(|| format!("failed to build context for {} email", T::NAME))?;
+        let locale = if self.has_template(&template_name(locale, T::NAME, "subject")) {
+            locale
+        } else {
+            Locale::default()
+        };
+        let render = |part| {
+            let template = template_name(locale, T::NAME, part);
+            self.tera
+                .render(&template, &context)
+                .with_context(|| format!("failed to render email template {}", template))
+        };
+
+        Ok(RenderedEmail {
+            subject: render("subject")?.trim_end().to_string(),
+            body: render("body")?,
+        })
+    }
+
+    fn has_template(&self, template: &str) -> bool {
+        self.tera.get_template_names().any(|name| name == template)
+    }
+}
+
+fn template_name(locale: Locale, email: &str, part: &str) -> String {
+    format!("{}/{}.{}.txt", locale.as_language_tag(), email, part)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn templates() -> EmailTemplates {
+        EmailTemplates::from_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/templates/email")).unwrap()
+    }
+
+    #[test]
+    fn test_render_welcome_email() {
+        let email = WelcomeEmail {
+            username: "Kristoffer".to_string(),
+        };
+
+        for locale in [Locale::En, Locale::Sv] {
+            let actual = templates().render(locale, &email).unwrap();
+            insta::assert_debug_snapshot!(format!("welcome_{}", locale.as_language_tag()), actual);
+        }
+    }
+
+    #[test]
+    fn test_render_confirmation_email() {
+        let email = ConfirmationEmail {
+            username: "Kristoffer".to_string(),
+            confirmation_url: "https://example.com/confirm/abc123".to_string(),
+        };
+
+        let actual = templates().render(Locale::En, &email).unwrap();
+
+        insta::assert_debug_snapshot!(actual);
+    }
+
+    #[test]
+    fn test_render_email_change_emails() {
+        let confirmation = EmailChangeConfirmationEmail {
+            username: "Kristoffer".to_string(),
+            token: "abc123".to_string(),
+            expires_at: "2026-10-16 12:00 UTC".to_string(),
+        };
+        let notice = EmailChangeNoticeEmail {
+            username: "Kristoffer".to_string(),
+            new_email: "kristoffer@example.org".to_string(),
+        };
+
+        insta::assert_debug_snapshot!(
+            "email_change_confirmation",
+            templates().render(Locale::En, &confirmation).unwrap()
+        );
+        insta::assert_debug_snapshot!(
+            "email_change_notice",
+            templates().render(Locale::En, &notice).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_render_falls_back_to_default_locale() {
+        let dir = std::env::temp_dir().join(format!("email-templates-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("en")).unwrap();
+        for name in EMAILS {
+            std::fs::write(dir.join(format!("en/{name}.subject.txt")), "Hello").unwrap();
+            std::fs::write(dir.join(format!("en/{name}.body.txt")), "{{ username }}").unwrap();
+        }
+        let templates = EmailTemplates::from_dir(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        let email = WelcomeEmail {
+            username: "Kristoffer".to_string(),
+        };
+
+        let actual = templates.render(Locale::Sv, &email).unwrap();
+
+        assert_eq!(
+            actual,
+            RenderedEmail {
+                subject: "Hello".to_string(),
+                body: "Kristoffer".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_from_dir_fails_for_missing_templates() {
+        let dir = std::env::temp_dir().join(format!("email-templates-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let actual = EmailTemplates::from_dir(&dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(actual.is_err(), "expected failure, but got {:?}", actual);
+    }
+}
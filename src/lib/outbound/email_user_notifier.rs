@@ -1,11 +1,41 @@
-use crate::domain::crowdsrc::ports::UserNotifier;
+use crate::{
+    domain::crowdsrc::{
+        models::{
+            email_change::{EmailChangeToken, PendingEmailChange},
+            redacted::Redacted,
+            user::{EmailAddress, NotifyUserError, User},
+        },
+        ports::UserNotifier,
+    },
+    i18n::Locale,
+    outbound::email_templates::{
+        EmailChangeConfirmationEmail, EmailChangeNoticeEmail, EmailTemplates, RenderedEmail,
+        WelcomeEmail,
+    },
+};
 
+/// A [UserNotifier] composing the emails users are sent, and only logging them, for
+/// deployments that don't deliver email.
 #[derive(Debug, Clone)]
-pub struct EmailUserNotifier {}
+pub struct EmailUserNotifier {
+    templates: EmailTemplates,
+}
 
 impl EmailUserNotifier {
-    pub fn new() -> Self {
-        Self {}
+    pub fn new(templates: EmailTemplates) -> Self {
+        Self { templates }
+    }
+
+    fn log(emails: &[OutgoingEmail]) {
+        for email in emails {
+            tracing::debug!(
+                to = %Redacted(&email.to),
+                subject = email.content.subject,
+                body_len = email.content.body.len(),
+                "composed {} email",
+                email.kind
+            );
+        }
     }
 }
 
@@ -13,8 +43,89 @@ impl UserNotifier for EmailUserNotifier {
     #[allow(clippy::manual_async_fn)]
     fn user_created(
         &self,
-        _user: &crate::domain::crowdsrc::models::user::User,
-    ) -> impl Future<Output = ()> + Send {
-        async {}
+        user: &crate::domain::crowdsrc::models::user::User,
+    ) -> impl Future<Output = Result<(), NotifyUserError>> + Send {
+        let result = user_created_emails(&self.templates, user).map(|emails| Self::log(&emails));
+        async { result }
+    }
+
+    async fn email_change_requested(
+        &self,
+        user: &User,
+        change: &PendingEmailChange,
+        token: &EmailChangeToken,
+    ) -> Result<(), NotifyUserError> {
+        Self::log(&email_change_requested_emails(
+            &self.templates,
+            user,
+            change,
+            token,
+        )?);
+        Ok(())
     }
 }
+
+/// An email composed for a [User], ready to be delivered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct OutgoingEmail {
+    /// What the email is about, such as `welcome`.
+    pub kind: &'static str,
+    pub to: EmailAddress,
+    pub content: RenderedEmail,
+}
+
+/// The emails sent when `user` is created.
+pub(crate) fn user_created_emails(
+    templates: &EmailTemplates,
+    user: &User,
+) -> Result<Vec<OutgoingEmail>, NotifyUserError> {
+    // Users don't have a preferred language yet, so emails use the default locale.
+    let welcome = templates.render(
+        Locale::default(),
+        &WelcomeEmail {
+            username: user.username().to_string(),
+        },
+    )?;
+    Ok(vec![OutgoingEmail {
+        kind: "welcome",
+        to: user.email().clone(),
+        content: welcome,
+    }])
+}
+
+/// The emails sent when `user` requests `change`: the confirmation, with `token`, to the new
+/// address, then the notice to the current one.
+pub(crate) fn email_change_requested_emails(
+    templates: &EmailTemplates,
+    user: &User,
+    change: &PendingEmailChange,
+    token: &EmailChangeToken,
+) -> Result<Vec<OutgoingEmail>, NotifyUserError> {
+    let confirmation = templates.render(
+        Locale::default(),
+        &EmailChangeConfirmationEmail {
+            username: user.username().to_string(),
+            token: token.as_str().to_string(),
+            expires_at: change.expires_at().format("%Y-%m-%d %H:%M UTC").to_string(),
+        },
+    )?;
+    let notice = templates.render(
+        Locale::default(),
+        &EmailChangeNoticeEmail {
+            username: user.username().to_string(),
+            new_email: change.new_email().to_string(),
+        },
+    )?;
+    Ok(vec![
+        OutgoingEmail {
+            kind: "email change confirmation",
+            to: change.new_email().clone(),
+            content: confirmation,
+        },
+        OutgoingEmail {
+            kind: "email change notice",
+            to: user.email().clone(),
+            content: notice,
+        },
+    ])
+}
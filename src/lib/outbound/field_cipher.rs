@@ -0,0 +1,252 @@
+use std::{collections::HashMap, fmt, sync::Arc};
+
+use anyhow::Context;
+use hmac::{Hmac, Mac};
+use ring::{
+    aead::{AES_256_GCM, Aad, LessSafeKey, NONCE_LEN, Nonce, UnboundKey},
+    rand::{SecureRandom, SystemRandom},
+};
+use sha2::Sha256;
+
+/// The prefix of sealed values, followed by the id of the key they were sealed with and the
+/// hex encoded nonce and ciphertext, as in `enc:v1:2026-10:…`.
+const SEALED_PREFIX: &str = "enc:v1:";
+
+/// Encrypts personal data before it is stored, using AES-256-GCM under one of several named
+/// keys, so that keys can be rotated.
+///
+/// Values are sealed under the current key and opened under whichever key they name, so rows
+/// sealed under a retired key stay readable until they are re-encrypted. Values stored before
+/// encryption was enabled are opened as they are.
+///
+/// Since sealed values differ each time, equal values are found through their [Self::index],
+/// a keyed hash that reveals nothing but equality.
+#[derive(Clone)]
+pub struct FieldCipher {
+    keys: Arc<HashMap<String, LessSafeKey>>,
+    current_key_id: String,
+    index_key: Arc<[u8]>,
+    rng: SystemRandom,
+}
+
+impl FieldCipher {
+    /// Creates a cipher sealing under the key named `current_key_id` among `keys`, which are
+    /// 32 bytes each, and indexing under `index_key`.
+    pub fn new(
+        keys: HashMap<String, Vec<u8>>,
+        current_key_id: &str,
+        index_key: &[u8],
+    ) -> anyhow::Result<Self> {
+        anyhow::ensure!(
+            keys.contains_key(current_key_id),
+            "current key '{current_key_id}' is not among the keys"
+        );
+        anyhow::ensure!(!index_key.is_empty(), "the index key is empty");
+        let keys = keys
+            .into_iter()
+            .map(|(id, key)| {
+                anyhow::ensure!(
+                    !id.is_empty() && !id.contains(':'),
+                    "key id '{id}' is empty or contains ':'"
+                );
+                let key = UnboundKey::new(&AES_256_GCM, &key)
+                    .map_err(|_| anyhow::anyhow!("key '{id}' is not 32 bytes"))?;
+                Ok((id, LessSafeKey::new(key)))
+            })
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Self {
+            keys: Arc::new(keys),
+            current_key_id: current_key_id.to_string(),
+            index_key: index_key.into(),
+            rng: SystemRandom::new(),
+        })
+    }
+
+    /// Creates a cipher from hex encoded keys, as given in the configuration file.
+    pub fn from_hex(
+        keys: &HashMap<String, String>,
+        current_key_id: &str,
+        index_key: &str,
+    ) -> anyhow::Result<Self> {
+        let keys = keys
+            .iter()
+            .map(|(id, key)| {
+                let key = hex::decode(key).with_context(|| format!("key '{id}' is not hex"))?;
+                Ok((id.clone(), key))
+            })
+            .collect::<anyhow::Result<_>>()?;
+        let index_key = hex::decode(index_key).context("the index key is not hex")?;
+        Self::new(keys, current_key_id, &index_key)
+    }
+
+    /// Seals `plaintext` under the current key, bound to `field` so that it can't be moved to
+    /// another column.
+    pub fn seal(&self, field: &str, plaintext: &str) -> anyhow::Result<String> {
+        let key = &self.keys[&self.current_key_id];
+        let mut nonce = [0; NONCE_LEN];
+        self.rng
+            .fill(&mut nonce)
+            .map_err(|_| anyhow::anyhow!("failed to generate nonce"))?;
+        let mut sealed = plaintext.as_bytes().to_vec();
+        key.seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::from(field),
+            &mut sealed,
+        )
+        .map_err(|_| anyhow::anyhow!("failed to seal {field}"))?;
+        Ok(format!(
+            "{SEALED_PREFIX}{}:{}{}",
+            self.current_key_id,
+            hex::encode(nonce),
+            hex::encode(sealed)
+        ))
+    }
+
+    /// Opens `stored`, sealed by [Self::seal] for `field`, or returns it as it is if it was
+    /// stored before encryption was enabled.
+    pub fn open(&self, field: &str, stored: &str) -> anyhow::Result<String> {
+        let Some((key_id, sealed)) = split_sealed(stored) else {
+            return Ok(stored.to_string());
+        };
+        let key = self
+            .keys
+            .get(key_id)
+            .with_context(|| format!("{field} is sealed under unknown key '{key_id}'"))?;
+        let sealed = hex::decode(sealed).with_context(|| format!("{field} is not hex"))?;
+        anyhow::ensure!(sealed.len() > NONCE_LEN, "{field} is truncated");
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce)
+            .map_err(|_| anyhow::anyhow!("{field} has an invalid nonce"))?;
+        let mut ciphertext = ciphertext.to_vec();
+        let plaintext = key
+            .open_in_place(nonce, Aad::from(field), &mut ciphertext)
+            .map_err(|_| anyhow::anyhow!("{field} does not open under key '{key_id}'"))?;
+        String::from_utf8(plaintext.to_vec()).with_context(|| format!("{field} is not UTF-8"))
+    }
+
+    /// Whether `stored` would be sealed differently now, since it is stored in plain text or
+    /// sealed under a key other than the current one.
+    pub fn needs_resealing(&self, stored: &str) -> bool {
+        split_sealed(stored).is_none_or(|(key_id, _)| key_id != self.current_key_id)
+    }
+
+    /// A keyed hash of `plaintext` for `field`, equal for equal values, to look values up by.
+    pub fn index(&self, field: &str, plaintext: &str) -> String {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.index_key).expect("HMAC accepts any key");
+        mac.update(field.as_bytes());
+        mac.update(&[0]);
+        mac.update(plaintext.as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+}
+
+/// Splits a sealed value into the id of its key and its hex encoded nonce and ciphertext.
+fn split_sealed(stored: &str) -> Option<(&str, &str)> {
+    stored.strip_prefix(SEALED_PREFIX)?.split_once(':')
+}
+
+impl fmt::Debug for FieldCipher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut key_ids: Vec<&String> = self.keys.keys().collect();
+        key_ids.sort();
+        f.debug_struct("FieldCipher")
+            .field("key_ids", &key_ids)
+            .field("current_key_id", &self.current_key_id)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIELD: &str = "users.email";
+    const EMAIL: &str = "kristoffer@example.com";
+
+    fn cipher(current_key_id: &str) -> FieldCipher {
+        let keys = HashMap::from([
+            ("old".to_string(), vec![1; 32]),
+            ("new".to_string(), vec![2; 32]),
+        ]);
+        FieldCipher::new(keys, current_key_id, b"index key").unwrap()
+    }
+
+    #[test]
+    fn sealed_value_opens_to_plaintext() {
+        let cipher = cipher("new");
+
+        let sealed = cipher.seal(FIELD, EMAIL).unwrap();
+
+        assert!(sealed.starts_with("enc:v1:new:"));
+        assert!(!sealed.contains("kristoffer"));
+        assert_ne!(sealed, cipher.seal(FIELD, EMAIL).unwrap());
+        assert_eq!(cipher.open(FIELD, &sealed).unwrap(), EMAIL);
+    }
+
+    #[test]
+    fn value_sealed_under_retired_key_opens_and_needs_resealing() {
+        let sealed = cipher("old").seal(FIELD, EMAIL).unwrap();
+        let cipher = cipher("new");
+
+        assert_eq!(cipher.open(FIELD, &sealed).unwrap(), EMAIL);
+        assert!(cipher.needs_resealing(&sealed));
+        assert!(!cipher.needs_resealing(&cipher.seal(FIELD, EMAIL).unwrap()));
+    }
+
+    #[test]
+    fn plain_value_opens_as_it_is_and_needs_resealing() {
+        let cipher = cipher("new");
+
+        assert_eq!(cipher.open(FIELD, EMAIL).unwrap(), EMAIL);
+        assert!(cipher.needs_resealing(EMAIL));
+    }
+
+    #[test]
+    fn value_sealed_for_other_field_does_not_open() {
+        let cipher = cipher("new");
+        let sealed = cipher.seal("users.username", EMAIL).unwrap();
+
+        assert!(cipher.open(FIELD, &sealed).is_err());
+    }
+
+    #[test]
+    fn tampered_value_does_not_open() {
+        let cipher = cipher("new");
+        let mut sealed = cipher.seal(FIELD, EMAIL).unwrap();
+        let last = if sealed.ends_with('0') { "1" } else { "0" };
+        sealed.replace_range(sealed.len() - 1.., last);
+
+        assert!(cipher.open(FIELD, &sealed).is_err());
+    }
+
+    #[test]
+    fn value_sealed_under_unknown_key_does_not_open() {
+        let sealed = cipher("new").seal(FIELD, EMAIL).unwrap();
+        let keys = HashMap::from([("other".to_string(), vec![2; 32])]);
+        let cipher = FieldCipher::new(keys, "other", b"index key").unwrap();
+
+        assert!(cipher.open(FIELD, &sealed).is_err());
+    }
+
+    #[test]
+    fn index_is_equal_for_equal_values_only() {
+        let new = cipher("new");
+
+        let index = new.index(FIELD, EMAIL);
+
+        assert_eq!(index, cipher("old").index(FIELD, EMAIL));
+        assert_ne!(index, new.index(FIELD, "someone@example.com"));
+        assert_ne!(index, new.index("users.username", EMAIL));
+    }
+
+    #[test]
+    fn invalid_keys_are_rejected() {
+        let short = HashMap::from([("short".to_string(), vec![1; 16])]);
+        let valid = HashMap::from([("valid".to_string(), vec![1; 32])]);
+
+        assert!(FieldCipher::new(short, "short", b"index key").is_err());
+        assert!(FieldCipher::new(valid.clone(), "missing", b"index key").is_err());
+        assert!(FieldCipher::new(valid, "valid", b"").is_err());
+    }
+}
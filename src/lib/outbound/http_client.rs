@@ -0,0 +1,235 @@
+use std::time::Duration;
+
+use anyhow::Context;
+use reqwest::{Method, Response, StatusCode};
+use tracing::Instrument;
+
+use crate::{configuration::OutboundHttpSettings, outbound::retry::RetryPolicy};
+
+/// How long requests may take, unless configured.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long connecting may take, unless configured.
+const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How many times failed requests are retried, unless configured.
+const DEFAULT_MAX_RETRIES: u32 = 2;
+
+/// The delay before the first retry, doubling for each further one.
+const INITIAL_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+/// The HTTP client shared by the adapters calling third-party services, so that they all time
+/// out, retry, go through a proxy and identify themselves the same way.
+///
+/// Requests that could not be sent, such as for a refused connection, are retried. Requests
+/// that may have reached the service are only retried if repeating them is harmless, that is
+/// if their method is idempotent, when they time out or are answered with 429 Too Many Requests
+/// or a 5xx status.
+#[derive(Debug, Clone)]
+pub struct HttpClient {
+    client: reqwest::Client,
+    retry_policy: RetryPolicy,
+}
+
+impl HttpClient {
+    /// # Errors
+    ///
+    /// Fails if the proxy URL is invalid.
+    pub fn new(settings: &OutboundHttpSettings) -> anyhow::Result<Self> {
+        let mut builder = reqwest::Client::builder()
+            .user_agent(concat!("crowdsource/", env!("CARGO_PKG_VERSION")))
+            .timeout(
+                settings
+                    .timeout_ms
+                    .map_or(DEFAULT_TIMEOUT, Duration::from_millis),
+            )
+            .connect_timeout(
+                settings
+                    .connect_timeout_ms
+                    .map_or(DEFAULT_CONNECT_TIMEOUT, Duration::from_millis),
+            );
+        if let Some(proxy) = &settings.proxy {
+            builder = builder.proxy(
+                reqwest::Proxy::all(proxy)
+                    .with_context(|| format!("invalid outbound_http.proxy {proxy}"))?,
+            );
+        }
+        Ok(Self {
+            client: builder
+                .build()
+                .context("failed to build the outbound HTTP client")?,
+            retry_policy: RetryPolicy::new(
+                settings.max_retries.unwrap_or(DEFAULT_MAX_RETRIES),
+                INITIAL_RETRY_DELAY,
+            ),
+        })
+    }
+
+    /// Starts building a request, to [send](HttpClient::send).
+    pub fn request(&self, method: Method, url: &str) -> reqwest::RequestBuilder {
+        self.client.request(method, url)
+    }
+
+    pub fn get(&self, url: &str) -> reqwest::RequestBuilder {
+        self.request(Method::GET, url)
+    }
+
+    pub fn post(&self, url: &str) -> reqwest::RequestBuilder {
+        self.request(Method::POST, url)
+    }
+
+    /// Sends `request`, retrying it if it fails in a way that may go away, and returns the last
+    /// response, whatever its status.
+    ///
+    /// Requests whose body is a stream are sent once, since the stream can't be replayed.
+    pub async fn send(&self, request: reqwest::RequestBuilder) -> reqwest::Result<Response> {
+        let request = request.build()?;
+        let span = tracing::info_span!(
+            "outbound_http_request",
+            method = %request.method(),
+            host = request.url().host_str().unwrap_or_default(),
+        );
+        if request.try_clone().is_none() {
+            return self.client.execute(request).instrument(span).await;
+        }
+        let idempotent = is_idempotent(request.method());
+        let result = self
+            .retry_policy
+            .retry(
+                || {
+                    let request = request.try_clone().expect("request body can be cloned");
+                    async move {
+                        let response = self
+                            .client
+                            .execute(request)
+                            .await
+                            .map_err(Attempt::Failed)?;
+                        if idempotent && is_retryable_status(response.status()) {
+                            return Err(Attempt::Answered(response));
+                        }
+                        Ok(response)
+                    }
+                },
+                |attempt| match attempt {
+                    Attempt::Failed(e) => e.is_connect() || (idempotent && e.is_timeout()),
+                    Attempt::Answered(_) => true,
+                },
+            )
+            .instrument(span)
+            .await;
+        match result {
+            Ok(response) | Err(Attempt::Answered(response)) => Ok(response),
+            Err(Attempt::Failed(e)) => Err(e),
+        }
+    }
+}
+
+impl Default for HttpClient {
+    /// A client with the default timeouts and retries, and no proxy.
+    fn default() -> Self {
+        Self::new(&OutboundHttpSettings::default()).expect("default settings are valid")
+    }
+}
+
+/// How an attempt at sending a request went wrong.
+#[derive(Debug)]
+enum Attempt {
+    Failed(reqwest::Error),
+    /// Answered with a status worth retrying for.
+    Answered(Response),
+}
+
+fn is_idempotent(method: &Method) -> bool {
+    matches!(
+        *method,
+        Method::GET | Method::HEAD | Method::OPTIONS | Method::PUT | Method::DELETE
+    )
+}
+
+fn is_retryable_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    use axum::{http::StatusCode, routing::any};
+
+    use super::*;
+
+    /// Answers with 503 Service Unavailable until `failures` requests were made, counting them.
+    async fn serve_flaky(failures: usize, requests: Arc<AtomicUsize>) -> String {
+        let router = axum::Router::new().route(
+            "/",
+            any(move || async move {
+                if requests.fetch_add(1, Ordering::SeqCst) < failures {
+                    StatusCode::SERVICE_UNAVAILABLE
+                } else {
+                    StatusCode::OK
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, router).await });
+        url
+    }
+
+    fn client(max_retries: u32) -> HttpClient {
+        HttpClient::new(&OutboundHttpSettings {
+            max_retries: Some(max_retries),
+            ..OutboundHttpSettings::default()
+        })
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_send_retries_idempotent_requests_answered_with_server_error() {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let url = serve_flaky(2, requests.clone()).await;
+        let client = client(2);
+
+        let response = client.send(client.get(&url)).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(requests.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_send_returns_last_response_once_retries_are_exhausted() {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let url = serve_flaky(5, requests.clone()).await;
+        let client = client(1);
+
+        let response = client.send(client.get(&url)).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_send_does_not_retry_answered_post() {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let url = serve_flaky(1, requests.clone()).await;
+        let client = client(2);
+
+        let response = client.send(client.post(&url).body("x")).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_new_rejects_invalid_proxy() {
+        let settings = OutboundHttpSettings {
+            proxy: Some("not a url".to_string()),
+            ..OutboundHttpSettings::default()
+        };
+
+        assert!(HttpClient::new(&settings).is_err());
+    }
+}
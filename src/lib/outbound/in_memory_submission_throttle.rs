@@ -0,0 +1,131 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+use crate::domain::crowdsrc::{
+    models::throttle::{SubmissionSource, ThrottleError, ThrottlePolicy},
+    ports::SubmissionThrottle,
+};
+
+#[derive(Debug, Clone, Copy)]
+struct Window {
+    started_at: Instant,
+    submissions: u32,
+}
+
+/// A [SubmissionThrottle] counting submissions per fixed window in memory.
+///
+/// Counts are per process, so with several replicas each source gets the allowance of the
+/// [ThrottlePolicy] once per replica. Windows that have ended are pruned on each submission.
+#[derive(Debug, Clone)]
+pub struct InMemorySubmissionThrottle {
+    policy: Arc<Mutex<ThrottlePolicy>>,
+    windows: Arc<Mutex<HashMap<SubmissionSource, Window>>>,
+}
+
+impl InMemorySubmissionThrottle {
+    pub fn new(policy: ThrottlePolicy) -> Self {
+        Self {
+            policy: Arc::new(Mutex::new(policy)),
+            windows: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Applies `policy` to all submissions from now on, including those within windows that
+    /// have already started.
+    pub fn set_policy(&self, policy: ThrottlePolicy) {
+        *self.policy.lock().expect("throttle lock poisoned") = policy;
+    }
+}
+
+impl SubmissionThrottle for InMemorySubmissionThrottle {
+    async fn record(&self, source: &SubmissionSource) -> Result<(), ThrottleError> {
+        let policy = *self.policy.lock().expect("throttle lock poisoned");
+        let now = Instant::now();
+        let mut windows = self.windows.lock().expect("throttle lock poisoned");
+        windows.retain(|_, window| now.duration_since(window.started_at) < policy.window);
+
+        let window = windows.entry(source.clone()).or_insert(Window {
+            started_at: now,
+            submissions: 0,
+        });
+        if window.submissions >= policy.max_submissions {
+            return Err(ThrottleError::TooManySubmissions {
+                retry_after: policy.window - now.duration_since(window.started_at),
+            });
+        }
+        window.submissions += 1;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn source(ip: &str) -> SubmissionSource {
+        SubmissionSource::new(ip.parse().unwrap(), "test")
+    }
+
+    #[tokio::test]
+    async fn rejects_submissions_beyond_allowance() {
+        let throttle = InMemorySubmissionThrottle::new(ThrottlePolicy {
+            max_submissions: 2,
+            window: Duration::from_secs(60),
+        });
+
+        assert!(throttle.record(&source("192.0.2.1")).await.is_ok());
+        assert!(throttle.record(&source("192.0.2.1")).await.is_ok());
+        let result = throttle.record(&source("192.0.2.1")).await;
+
+        assert!(matches!(
+            result,
+            Err(ThrottleError::TooManySubmissions { retry_after }) if retry_after <= Duration::from_secs(60)
+        ));
+    }
+
+    #[tokio::test]
+    async fn counts_sources_separately() {
+        let throttle = InMemorySubmissionThrottle::new(ThrottlePolicy {
+            max_submissions: 1,
+            window: Duration::from_secs(60),
+        });
+
+        assert!(throttle.record(&source("192.0.2.1")).await.is_ok());
+        assert!(throttle.record(&source("192.0.2.2")).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn allows_submissions_again_in_next_window() {
+        let throttle = InMemorySubmissionThrottle::new(ThrottlePolicy {
+            max_submissions: 1,
+            window: Duration::from_millis(20),
+        });
+        throttle.record(&source("192.0.2.1")).await.unwrap();
+
+        tokio::time::sleep(Duration::from_millis(30)).await;
+
+        assert!(throttle.record(&source("192.0.2.1")).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn applies_changed_policy_to_started_windows() {
+        let throttle = InMemorySubmissionThrottle::new(ThrottlePolicy {
+            max_submissions: 1,
+            window: Duration::from_secs(60),
+        });
+        throttle.record(&source("192.0.2.1")).await.unwrap();
+
+        throttle.set_policy(ThrottlePolicy {
+            max_submissions: 2,
+            window: Duration::from_secs(60),
+        });
+
+        assert!(throttle.record(&source("192.0.2.1")).await.is_ok());
+        assert!(throttle.record(&source("192.0.2.1")).await.is_err());
+    }
+}
@@ -0,0 +1,281 @@
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use reqwest::{Method, StatusCode};
+
+use crate::{
+    domain::crowdsrc::{
+        models::{
+            search::{SearchError, UserSearchQuery},
+            user::User,
+        },
+        ports::SearchIndex,
+    },
+    outbound::http_client::HttpClient,
+};
+
+/// A [SearchIndex] keeping [User]s as documents of a Meilisearch index, created on the first
+/// [User] indexed.
+///
+/// Meilisearch applies changes asynchronously, so a change is only searchable once Meilisearch
+/// has processed it, and a change it fails to process is only seen in its task list.
+#[derive(Clone)]
+pub struct MeilisearchIndex {
+    client: HttpClient,
+    url: String,
+    api_key: Option<String>,
+    index: String,
+}
+
+impl std::fmt::Debug for MeilisearchIndex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MeilisearchIndex")
+            .field("url", &self.url)
+            .field("index", &self.index)
+            .finish_non_exhaustive()
+    }
+}
+
+impl MeilisearchIndex {
+    /// Indexes [User]s in the index with the uid `index` of the Meilisearch instance at `url`,
+    /// authenticating with `api_key`, if any.
+    pub fn new(client: HttpClient, url: &str, api_key: Option<String>, index: &str) -> Self {
+        Self {
+            client,
+            url: url.trim_end_matches('/').to_string(),
+            api_key,
+            index: index.to_string(),
+        }
+    }
+
+    /// The URL of `path` within the index.
+    fn url(&self, path: &str) -> String {
+        format!("{}/indexes/{}{path}", self.url, self.index)
+    }
+
+    fn request(&self, method: Method, url: &str) -> reqwest::RequestBuilder {
+        let request = self.client.request(method, url);
+        match &self.api_key {
+            Some(api_key) => request.bearer_auth(api_key),
+            None => request,
+        }
+    }
+
+    /// Sends `request`, failing with `failure` unless Meilisearch accepts it.
+    async fn send(
+        &self,
+        request: reqwest::RequestBuilder,
+        failure: &'static str,
+    ) -> Result<reqwest::Response, SearchError> {
+        let response = self
+            .client
+            .send(request)
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .context(failure)?;
+        Ok(response)
+    }
+}
+
+impl SearchIndex for MeilisearchIndex {
+    async fn index_users(&self, users: &[User]) -> Result<(), SearchError> {
+        let documents: Vec<UserDocument> = users.iter().map(UserDocument::from).collect();
+        // PUT updates documents, so, unlike POST, it is retried if it times out.
+        self.send(
+            self.request(Method::PUT, &self.url("/documents?primaryKey=id"))
+                .json(&documents),
+            "failed to index users in Meilisearch",
+        )
+        .await?;
+        tracing::debug!(users = users.len(), "indexed users in Meilisearch");
+        Ok(())
+    }
+
+    async fn remove_user(&self, id: &uuid::Uuid) -> Result<(), SearchError> {
+        self.send(
+            self.request(Method::DELETE, &self.url(&format!("/documents/{id}"))),
+            "failed to remove user from Meilisearch",
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn clear(&self) -> Result<(), SearchError> {
+        self.send(
+            self.request(Method::DELETE, &self.url("/documents")),
+            "failed to clear Meilisearch index",
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn search_users(&self, query: &UserSearchQuery) -> Result<Vec<uuid::Uuid>, SearchError> {
+        let url = reqwest::Url::parse_with_params(
+            &self.url("/search"),
+            [
+                ("q", query.text()),
+                ("limit", &query.limit().to_string()),
+                ("attributesToRetrieve", "id"),
+            ],
+        )
+        .context("invalid Meilisearch URL")?;
+        let response = self
+            .client
+            .send(self.request(Method::GET, url.as_str()))
+            .await
+            .context("failed to search users in Meilisearch")?;
+        // The index is only created with the first user indexed.
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(Vec::new());
+        }
+        let results: SearchResponse = response
+            .error_for_status()
+            .context("failed to search users in Meilisearch")?
+            .json()
+            .await
+            .context("invalid search response from Meilisearch")?;
+        Ok(results.hits.into_iter().map(|hit| hit.id).collect())
+    }
+}
+
+/// The document a [User] is indexed as, leaving out their email.
+#[derive(Debug, serde::Serialize)]
+struct UserDocument {
+    id: uuid::Uuid,
+    username: String,
+    created_at: DateTime<Utc>,
+}
+
+impl From<&User> for UserDocument {
+    fn from(user: &User) -> Self {
+        Self {
+            id: *user.id(),
+            username: user.username().to_string(),
+            created_at: *user.created_at(),
+        }
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct SearchResponse {
+    hits: Vec<SearchHit>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct SearchHit {
+    id: uuid::Uuid,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashMap,
+        sync::{Arc, Mutex},
+    };
+
+    use axum::{
+        Json,
+        extract::Query,
+        http::HeaderMap,
+        routing::{get, put},
+    };
+
+    use super::*;
+    use crate::domain::crowdsrc::models::user::{EmailAddress, UserName};
+
+    type Received = Arc<Mutex<Vec<(Option<String>, serde_json::Value)>>>;
+
+    /// Stands in for Meilisearch, keeping the documents indexed in `users`, and finding the one
+    /// with id `hit` whatever is searched for.
+    async fn serve_meilisearch(received: Received, hit: uuid::Uuid) -> String {
+        let router = axum::Router::new()
+            .route(
+                "/indexes/users/documents",
+                put(
+                    move |headers: HeaderMap, Json(documents): Json<serde_json::Value>| async move {
+                        let authorization = headers
+                            .get("authorization")
+                            .map(|value| value.to_str().unwrap().to_string());
+                        received.lock().unwrap().push((authorization, documents));
+                        (
+                            StatusCode::ACCEPTED,
+                            Json(serde_json::json!({ "taskUid": 1, "status": "enqueued" })),
+                        )
+                    },
+                ),
+            )
+            .route(
+                "/indexes/users/search",
+                get(
+                    move |Query(params): Query<HashMap<String, String>>| async move {
+                        assert_eq!(params["attributesToRetrieve"], "id");
+                        Json(serde_json::json!({ "hits": [{ "id": hit }], "query": params["q"] }))
+                    },
+                ),
+            );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, router).await });
+        url
+    }
+
+    fn user() -> User {
+        User::new(
+            uuid::Uuid::new_v4(),
+            UserName::new("Kristoffer").unwrap(),
+            EmailAddress::new("kristoffer@example.com").unwrap(),
+            Utc::now(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_index_users_sends_documents_without_email() {
+        let received = Received::default();
+        let url = serve_meilisearch(received.clone(), uuid::Uuid::new_v4()).await;
+        let index = MeilisearchIndex::new(
+            HttpClient::default(),
+            &url,
+            Some("master-key".to_string()),
+            "users",
+        );
+        let user = user();
+
+        index
+            .index_users(std::slice::from_ref(&user))
+            .await
+            .unwrap();
+
+        let received = received.lock().unwrap();
+        let (authorization, documents) = &received[0];
+        assert_eq!(authorization.as_deref(), Some("Bearer master-key"));
+        assert_eq!(documents[0]["id"], user.id().to_string());
+        assert_eq!(documents[0]["username"], "Kristoffer");
+        assert!(documents[0].get("email").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_search_users_returns_ids_of_hits() {
+        let hit = uuid::Uuid::new_v4();
+        let url = serve_meilisearch(Received::default(), hit).await;
+        let index = MeilisearchIndex::new(HttpClient::default(), &url, None, "users");
+
+        let ids = index
+            .search_users(&UserSearchQuery::new("kris", 10).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(ids, vec![hit]);
+    }
+
+    #[tokio::test]
+    async fn test_search_users_finds_nothing_in_missing_index() {
+        let url = serve_meilisearch(Received::default(), uuid::Uuid::new_v4()).await;
+        let index = MeilisearchIndex::new(HttpClient::default(), &url, None, "missing");
+
+        let ids = index
+            .search_users(&UserSearchQuery::new("kris", 10).unwrap())
+            .await
+            .unwrap();
+
+        assert!(ids.is_empty());
+    }
+}
@@ -0,0 +1,179 @@
+use std::{net::IpAddr, sync::Arc, time::Duration};
+
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+
+use crate::domain::crowdsrc::{
+    models::abuse_challenge::{AbuseChallengeError, Challenge},
+    ports::AbuseChallenge,
+};
+
+/// A built-in [AbuseChallenge] asking clients to spend CPU time on a hash puzzle, for when no
+/// CAPTCHA provider is configured.
+///
+/// Challenges are stateless: each one carries its expiry and is signed with `key`, so any
+/// replica sharing the key can verify it. A solution can be reused until its challenge expires.
+#[derive(Clone)]
+pub struct ProofOfWorkChallenge {
+    key: Arc<[u8]>,
+    difficulty: u8,
+    ttl: Duration,
+}
+
+impl std::fmt::Debug for ProofOfWorkChallenge {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProofOfWorkChallenge")
+            .field("difficulty", &self.difficulty)
+            .field("ttl", &self.ttl)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ProofOfWorkChallenge {
+    /// Issues challenges signed with `key` that require `difficulty` leading zero bits and
+    /// expire after `ttl`.
+    pub fn new(key: &[u8], difficulty: u8, ttl: Duration) -> Self {
+        Self {
+            key: key.into(),
+            difficulty,
+            ttl,
+        }
+    }
+
+    fn sign(&self, payload: &str) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.key).expect("HMAC accepts any key");
+        mac.update(payload.as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    fn is_valid_challenge(&self, challenge: &str) -> bool {
+        let Some((payload, signature)) = challenge.rsplit_once('.') else {
+            return false;
+        };
+        let Ok(signature) = hex::decode(signature) else {
+            return false;
+        };
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.key).expect("HMAC accepts any key");
+        mac.update(payload.as_bytes());
+        if mac.verify_slice(&signature).is_err() {
+            return false;
+        }
+        payload
+            .split_once('.')
+            .and_then(|(expires_at, _)| expires_at.parse::<i64>().ok())
+            .is_some_and(|expires_at| Utc::now().timestamp() <= expires_at)
+    }
+}
+
+impl AbuseChallenge for ProofOfWorkChallenge {
+    async fn issue(&self) -> Result<Challenge, AbuseChallengeError> {
+        let expires_at = Utc::now().timestamp() + self.ttl.as_secs() as i64;
+        let payload = format!("{}.{}", expires_at, uuid::Uuid::new_v4().simple());
+        let signature = self.sign(&payload);
+        Ok(Challenge::ProofOfWork {
+            challenge: format!("{payload}.{signature}"),
+            difficulty: self.difficulty,
+        })
+    }
+
+    async fn verify(
+        &self,
+        solution: &str,
+        _remote_ip: Option<IpAddr>,
+    ) -> Result<(), AbuseChallengeError> {
+        let Some((challenge, _nonce)) = solution.rsplit_once(':') else {
+            return Err(AbuseChallengeError::Rejected);
+        };
+        if !self.is_valid_challenge(challenge) {
+            return Err(AbuseChallengeError::Rejected);
+        }
+        if leading_zero_bits(&Sha256::digest(solution.as_bytes())) < u32::from(self.difficulty) {
+            return Err(AbuseChallengeError::Rejected);
+        }
+        Ok(())
+    }
+}
+
+fn leading_zero_bits(hash: &[u8]) -> u32 {
+    let mut bits = 0;
+    for byte in hash {
+        bits += byte.leading_zeros();
+        if *byte != 0 {
+            break;
+        }
+    }
+    bits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solve(challenge: &str, difficulty: u8) -> String {
+        (0u64..)
+            .map(|nonce| format!("{challenge}:{nonce}"))
+            .find(|solution| {
+                leading_zero_bits(&Sha256::digest(solution.as_bytes())) >= u32::from(difficulty)
+            })
+            .unwrap()
+    }
+
+    async fn issue(pow: &ProofOfWorkChallenge) -> String {
+        match pow.issue().await.unwrap() {
+            Challenge::ProofOfWork { challenge, .. } => challenge,
+            challenge => panic!("unexpected challenge {challenge:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn solved_challenge_is_accepted() {
+        let pow = ProofOfWorkChallenge::new(b"key", 8, Duration::from_secs(60));
+        let solution = solve(&issue(&pow).await, 8);
+
+        assert!(pow.verify(&solution, None).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn unsolved_challenge_is_rejected() {
+        let pow = ProofOfWorkChallenge::new(b"key", 16, Duration::from_secs(60));
+        let challenge = issue(&pow).await;
+        let solution = (0u64..)
+            .map(|nonce| format!("{challenge}:{nonce}"))
+            .find(|solution| leading_zero_bits(&Sha256::digest(solution.as_bytes())) < 16)
+            .unwrap();
+
+        let result = pow.verify(&solution, None).await;
+
+        assert!(matches!(result, Err(AbuseChallengeError::Rejected)));
+    }
+
+    #[tokio::test]
+    async fn challenge_signed_with_other_key_is_rejected() {
+        let pow = ProofOfWorkChallenge::new(b"key", 4, Duration::from_secs(60));
+        let other = ProofOfWorkChallenge::new(b"other key", 4, Duration::from_secs(60));
+        let solution = solve(&issue(&other).await, 4);
+
+        let result = pow.verify(&solution, None).await;
+
+        assert!(matches!(result, Err(AbuseChallengeError::Rejected)));
+    }
+
+    #[tokio::test]
+    async fn expired_challenge_is_rejected() {
+        let pow = ProofOfWorkChallenge::new(b"key", 4, Duration::from_secs(60));
+        let payload = format!("{}.nonce", Utc::now().timestamp() - 1);
+        let challenge = format!("{payload}.{}", pow.sign(&payload));
+        let solution = solve(&challenge, 4);
+
+        let result = pow.verify(&solution, None).await;
+
+        assert!(matches!(result, Err(AbuseChallengeError::Rejected)));
+    }
+
+    #[test]
+    fn leading_zero_bits_counts_across_bytes() {
+        assert_eq!(leading_zero_bits(&[0, 0b0001_0000, 0xff]), 11);
+        assert_eq!(leading_zero_bits(&[0xff]), 0);
+    }
+}
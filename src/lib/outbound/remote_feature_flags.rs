@@ -0,0 +1,180 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use anyhow::Context;
+
+use crate::{
+    configuration::FeatureFlagSettings,
+    domain::crowdsrc::{models::feature_flag::FeatureFlag, ports::FeatureFlags},
+    outbound::{config_feature_flags::enabled_flags, http_client::HttpClient},
+};
+
+type FlagTable = HashMap<String, FeatureFlagSettings>;
+
+/// The last fetched flags and when they were fetched.
+type Cache = Option<(Instant, Arc<FlagTable>)>;
+
+/// [FeatureFlags] fetched from a remote provider as a JSON object of [FeatureFlagSettings] by
+/// flag name, refreshed once they are older than `ttl`.
+///
+/// While the provider can't be reached, the last fetched flags are used, or `fallback` if none
+/// have been fetched yet.
+#[derive(Debug, Clone)]
+pub struct RemoteFeatureFlags<F: FeatureFlags> {
+    client: HttpClient,
+    url: String,
+    ttl: Duration,
+    fallback: F,
+    cache: Arc<Mutex<Cache>>,
+}
+
+impl<F: FeatureFlags> RemoteFeatureFlags<F> {
+    pub fn new(client: HttpClient, url: String, ttl: Duration, fallback: F) -> Self {
+        Self {
+            client,
+            url,
+            ttl,
+            fallback,
+            cache: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    async fn fetch(&self) -> anyhow::Result<FlagTable> {
+        self.client
+            .send(self.client.get(&self.url))
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .with_context(|| format!("failed to fetch feature flags from {}", self.url))?
+            .json()
+            .await
+            .with_context(|| format!("invalid feature flags from {}", self.url))
+    }
+
+    /// The cached flags, refreshed first if stale.
+    async fn flags(&self) -> Option<Arc<FlagTable>> {
+        let cached = self.cache.lock().expect("cache lock poisoned").clone();
+        if let Some((fetched_at, flags)) = &cached
+            && fetched_at.elapsed() < self.ttl
+        {
+            return Some(flags.clone());
+        }
+        match self.fetch().await {
+            Ok(flags) => {
+                let flags = Arc::new(flags);
+                *self.cache.lock().expect("cache lock poisoned") =
+                    Some((Instant::now(), flags.clone()));
+                Some(flags)
+            }
+            Err(e) => {
+                tracing::warn!("{:?}", e);
+                cached.map(|(_, flags)| flags)
+            }
+        }
+    }
+}
+
+impl<F: FeatureFlags> FeatureFlags for RemoteFeatureFlags<F> {
+    async fn enabled(&self, tenant: Option<&str>) -> Vec<FeatureFlag> {
+        match self.flags().await {
+            Some(flags) => enabled_flags(&flags, tenant),
+            None => self.fallback.enabled(tenant).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    use axum::{Json, http::StatusCode, response::IntoResponse, routing::get};
+
+    use super::*;
+    use crate::outbound::config_feature_flags::ConfigFeatureFlags;
+
+    /// Serves `{"payments": {"enabled": true}}` until `failing` is set, counting requests.
+    async fn serve_flags(failing: Arc<AtomicBool>, requests: Arc<AtomicUsize>) -> String {
+        let router = axum::Router::new().route(
+            "/flags",
+            get(move || async move {
+                requests.fetch_add(1, Ordering::SeqCst);
+                if failing.load(Ordering::SeqCst) {
+                    return StatusCode::SERVICE_UNAVAILABLE.into_response();
+                }
+                Json(serde_json::json!({"payments": {"enabled": true}})).into_response()
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/flags", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, router).await });
+        url
+    }
+
+    fn fallback() -> ConfigFeatureFlags {
+        ConfigFeatureFlags::new(HashMap::from([(
+            "anonymous_mode".to_string(),
+            FeatureFlagSettings {
+                enabled: true,
+                tenants: HashMap::new(),
+            },
+        )]))
+    }
+
+    #[tokio::test]
+    async fn uses_fetched_flags_until_stale() {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let url = serve_flags(Arc::new(AtomicBool::new(false)), requests.clone()).await;
+        let flags = RemoteFeatureFlags::new(
+            HttpClient::default(),
+            url,
+            Duration::from_secs(60),
+            fallback(),
+        );
+
+        assert_eq!(
+            flags.enabled(None).await,
+            vec![FeatureFlag::new("payments")]
+        );
+        assert_eq!(
+            flags.enabled(None).await,
+            vec![FeatureFlag::new("payments")]
+        );
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn keeps_last_fetched_flags_while_provider_fails() {
+        let failing = Arc::new(AtomicBool::new(false));
+        let url = serve_flags(failing.clone(), Arc::new(AtomicUsize::new(0))).await;
+        let flags = RemoteFeatureFlags::new(HttpClient::default(), url, Duration::ZERO, fallback());
+        flags.enabled(None).await;
+        failing.store(true, Ordering::SeqCst);
+
+        assert_eq!(
+            flags.enabled(None).await,
+            vec![FeatureFlag::new("payments")]
+        );
+    }
+
+    #[tokio::test]
+    async fn uses_fallback_until_flags_are_fetched() {
+        let url = serve_flags(
+            Arc::new(AtomicBool::new(true)),
+            Arc::new(AtomicUsize::new(0)),
+        )
+        .await;
+        let flags = RemoteFeatureFlags::new(
+            HttpClient::default(),
+            url,
+            Duration::from_secs(60),
+            fallback(),
+        );
+
+        assert_eq!(
+            flags.enabled(None).await,
+            vec![FeatureFlag::new("anonymous_mode")]
+        );
+    }
+}
@@ -0,0 +1,96 @@
+use std::time::Duration;
+
+/// How often, and how patiently, a failed operation is retried.
+///
+/// The delay before retry `n` (starting at 0) is `initial_delay * 2^n`, capped at `max_delay`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    max_retries: u32,
+    initial_delay: Duration,
+    max_delay: Duration,
+}
+
+impl RetryPolicy {
+    pub fn new(max_retries: u32, initial_delay: Duration) -> Self {
+        Self {
+            max_retries,
+            initial_delay,
+            max_delay: Duration::from_secs(5),
+        }
+    }
+
+    pub fn with_max_delay(self, max_delay: Duration) -> Self {
+        Self { max_delay, ..self }
+    }
+
+    pub fn max_retries(&self) -> u32 {
+        self.max_retries
+    }
+
+    fn delay(&self, retry: u32) -> Duration {
+        self.initial_delay
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_delay)
+    }
+
+    /// Runs `op` until it succeeds, fails with an error for which `is_retryable` is false, or
+    /// the retries are exhausted.
+    pub async fn retry<T, E, F, Fut>(
+        &self,
+        mut op: F,
+        is_retryable: impl Fn(&E) -> bool,
+    ) -> Result<T, E>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        E: std::fmt::Debug,
+    {
+        let mut retry = 0;
+        loop {
+            match op().await {
+                Err(err) if retry < self.max_retries && is_retryable(&err) => {
+                    let delay = self.delay(retry);
+                    tracing::warn!(
+                        "transient failure (retry {} of {} in {:?}): {:?}",
+                        retry + 1,
+                        self.max_retries,
+                        delay,
+                        err
+                    );
+                    tokio::time::sleep(delay).await;
+                    retry += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new(3, Duration::from_millis(50))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delay_grows_exponentially_up_to_max_delay() {
+        let policy = RetryPolicy::new(5, Duration::from_millis(10))
+            .with_max_delay(Duration::from_millis(50));
+
+        let delays: Vec<_> = (0..4).map(|retry| policy.delay(retry)).collect();
+
+        assert_eq!(
+            delays,
+            vec![
+                Duration::from_millis(10),
+                Duration::from_millis(20),
+                Duration::from_millis(40),
+                Duration::from_millis(50),
+            ]
+        );
+    }
+}
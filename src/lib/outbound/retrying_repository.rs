@@ -0,0 +1,617 @@
+use chrono::{DateTime, Utc};
+use futures::Stream;
+
+use crate::domain::crowdsrc::{
+    models::activity::{Activity, ActivityError, ActivityPage, ActivityQuery},
+    models::dead_letter::{DeadLetter, DeadLetterError, NotificationEvent},
+    models::email_change::{
+        EmailChangeError, EmailChangeToken, EmailChangeTokenHash, PendingEmailChange,
+    },
+    models::inbox::{InboxError, InboxNotification, InboxQuery},
+    models::search::{SearchError, UserSearchQuery},
+    models::terms::{AcceptTermsError, TermsAcceptance, TermsVersion},
+    models::user::{
+        CreateUserError, CreateUserOutcome, CreateUserRequest, CreateUsersError, EraseUserError,
+        GetUserError, ListUsersError, User,
+    },
+    ports::UserRepository,
+};
+use crate::outbound::{retry::RetryPolicy, sqlx_transaction::RequestTransaction};
+
+/// Returns whether `err` is caused by a failure that may go away if the operation is retried,
+/// such as a dropped connection or a serialization conflict.
+///
+/// Database errors are classified by their SQLSTATE. Of the other errors, only those raised
+/// before a query reached the database, such as a pool timeout, are transient; an I/O error may
+/// have cut off a query that the database still ran.
+pub fn is_transient(err: &anyhow::Error) -> bool {
+    err.chain()
+        .filter_map(|cause| cause.downcast_ref::<sqlx::Error>())
+        .any(is_transient_sqlx_error)
+}
+
+fn is_transient_sqlx_error(err: &sqlx::Error) -> bool {
+    match err {
+        sqlx::Error::PoolTimedOut => true,
+        sqlx::Error::Database(db_err) => db_err.code().is_some_and(|code| {
+            // connection exceptions, serialization failure, deadlock, too many connections and
+            // server shutdown
+            code.starts_with("08")
+                || matches!(
+                    code.as_ref(),
+                    "40001" | "40P01" | "53300" | "57P01" | "57P02" | "57P03"
+                )
+        }),
+        _ => false,
+    }
+}
+
+/// A [UserRepository] decorator that retries reads failing with transient errors.
+///
+/// Writes are not retried, since one that failed after the database ran it, such as when the
+/// connection dropped before the reply, would be applied twice. Nothing is retried within a
+/// [RequestTransaction] either, since the failure aborted the transaction and every retry would
+/// fail the same way. Streams are not retried, since a retry could yield a [User] twice.
+#[derive(Debug, Clone)]
+pub struct RetryingRepository<R>
+where
+    R: UserRepository,
+{
+    inner: R,
+    policy: RetryPolicy,
+}
+
+impl<R> RetryingRepository<R>
+where
+    R: UserRepository,
+{
+    pub fn new(inner: R, policy: RetryPolicy) -> Self {
+        Self { inner, policy }
+    }
+
+    async fn retry<T, E, F, Fut>(
+        &self,
+        mut op: F,
+        is_retryable: impl Fn(&E) -> bool,
+    ) -> Result<T, E>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        E: std::fmt::Debug,
+    {
+        if RequestTransaction::in_scope() {
+            return op().await;
+        }
+        self.policy.retry(op, is_retryable).await
+    }
+}
+
+impl<R> UserRepository for RetryingRepository<R>
+where
+    R: UserRepository,
+{
+    async fn create_user(&self, req: &CreateUserRequest) -> Result<User, CreateUserError> {
+        self.inner.create_user(req).await
+    }
+
+    async fn create_users(
+        &self,
+        reqs: &[CreateUserRequest],
+    ) -> Result<Vec<CreateUserOutcome>, CreateUsersError> {
+        self.inner.create_users(reqs).await
+    }
+
+    fn stream_users(&self) -> impl Stream<Item = Result<User, ListUsersError>> + Send {
+        self.inner.stream_users()
+    }
+
+    async fn get_user(&self, id: &uuid::Uuid) -> Result<User, GetUserError> {
+        self.retry(
+            || self.inner.get_user(id),
+            |err| matches!(err, GetUserError::Unknown(cause) if is_transient(cause)),
+        )
+        .await
+    }
+
+    async fn erase_user(&self, id: &uuid::Uuid) -> Result<User, EraseUserError> {
+        self.inner.erase_user(id).await
+    }
+
+    async fn accept_terms(
+        &self,
+        user_id: &uuid::Uuid,
+        version: &TermsVersion,
+    ) -> Result<TermsAcceptance, AcceptTermsError> {
+        self.inner.accept_terms(user_id, version).await
+    }
+
+    async fn list_terms_acceptances(
+        &self,
+        user_id: &uuid::Uuid,
+    ) -> Result<Vec<TermsAcceptance>, GetUserError> {
+        self.retry(
+            || self.inner.list_terms_acceptances(user_id),
+            |err| matches!(err, GetUserError::Unknown(cause) if is_transient(cause)),
+        )
+        .await
+    }
+
+    async fn save_dead_letter(
+        &self,
+        event: &NotificationEvent,
+        failure_reason: &str,
+    ) -> Result<DeadLetter, DeadLetterError> {
+        self.inner.save_dead_letter(event, failure_reason).await
+    }
+
+    async fn list_dead_letters(&self) -> Result<Vec<DeadLetter>, DeadLetterError> {
+        self.retry(
+            || self.inner.list_dead_letters(),
+            is_transient_dead_letter_error,
+        )
+        .await
+    }
+
+    async fn get_dead_letter(&self, id: &uuid::Uuid) -> Result<DeadLetter, DeadLetterError> {
+        self.retry(
+            || self.inner.get_dead_letter(id),
+            is_transient_dead_letter_error,
+        )
+        .await
+    }
+
+    async fn record_dead_letter_retry(
+        &self,
+        id: &uuid::Uuid,
+        failure_reason: &str,
+    ) -> Result<DeadLetter, DeadLetterError> {
+        self.inner
+            .record_dead_letter_retry(id, failure_reason)
+            .await
+    }
+
+    async fn delete_dead_letter(&self, id: &uuid::Uuid) -> Result<(), DeadLetterError> {
+        self.inner.delete_dead_letter(id).await
+    }
+
+    async fn import_user(
+        &self,
+        user: &User,
+        terms_acceptances: &[TermsAcceptance],
+    ) -> Result<(), CreateUserError> {
+        self.inner.import_user(user, terms_acceptances).await
+    }
+
+    async fn record_activity(&self, activity: &Activity) -> Result<(), ActivityError> {
+        self.inner.record_activity(activity).await
+    }
+
+    async fn list_activity(
+        &self,
+        user_id: &uuid::Uuid,
+        query: &ActivityQuery,
+    ) -> Result<ActivityPage, ActivityError> {
+        self.retry(
+            || self.inner.list_activity(user_id, query),
+            is_transient_activity_error,
+        )
+        .await
+    }
+
+    async fn save_email_change(
+        &self,
+        change: &PendingEmailChange,
+        token: &EmailChangeToken,
+    ) -> Result<(), EmailChangeError> {
+        self.inner.save_email_change(change, token).await
+    }
+
+    async fn confirm_email_change(
+        &self,
+        user_id: &uuid::Uuid,
+        token: &EmailChangeToken,
+    ) -> Result<User, EmailChangeError> {
+        self.inner.confirm_email_change(user_id, token).await
+    }
+
+    fn stream_email_changes(
+        &self,
+    ) -> impl Stream<Item = Result<(PendingEmailChange, EmailChangeTokenHash), EmailChangeError>> + Send
+    {
+        self.inner.stream_email_changes()
+    }
+
+    async fn import_email_change(
+        &self,
+        change: &PendingEmailChange,
+        token_hash: &EmailChangeTokenHash,
+    ) -> Result<(), EmailChangeError> {
+        self.inner.import_email_change(change, token_hash).await
+    }
+
+    async fn delete_email_change(&self, user_id: &uuid::Uuid) -> Result<(), EmailChangeError> {
+        self.inner.delete_email_change(user_id).await
+    }
+
+    async fn delete_expired_email_changes(
+        &self,
+        now: &DateTime<Utc>,
+    ) -> Result<u64, EmailChangeError> {
+        self.inner.delete_expired_email_changes(now).await
+    }
+
+    async fn save_notification(&self, notification: &InboxNotification) -> Result<(), InboxError> {
+        self.inner.save_notification(notification).await
+    }
+
+    async fn list_notifications(
+        &self,
+        user_id: &uuid::Uuid,
+        query: &InboxQuery,
+    ) -> Result<Vec<InboxNotification>, InboxError> {
+        self.retry(
+            || self.inner.list_notifications(user_id, query),
+            is_transient_inbox_error,
+        )
+        .await
+    }
+
+    fn stream_notifications(
+        &self,
+    ) -> impl Stream<Item = Result<InboxNotification, InboxError>> + Send {
+        self.inner.stream_notifications()
+    }
+
+    async fn count_unread_notifications(&self, user_id: &uuid::Uuid) -> Result<u64, InboxError> {
+        self.retry(
+            || self.inner.count_unread_notifications(user_id),
+            is_transient_inbox_error,
+        )
+        .await
+    }
+
+    async fn mark_notifications_read(
+        &self,
+        user_id: &uuid::Uuid,
+        notification_id: Option<&uuid::Uuid>,
+        read_at: &DateTime<Utc>,
+    ) -> Result<u64, InboxError> {
+        self.inner
+            .mark_notifications_read(user_id, notification_id, read_at)
+            .await
+    }
+
+    async fn delete_notifications_before(&self, cutoff: &DateTime<Utc>) -> Result<u64, InboxError> {
+        self.inner.delete_notifications_before(cutoff).await
+    }
+
+    async fn search_users(&self, query: &UserSearchQuery) -> Result<Vec<User>, SearchError> {
+        self.retry(|| self.inner.search_users(query), is_transient_search_error)
+            .await
+    }
+}
+
+fn is_transient_dead_letter_error(err: &DeadLetterError) -> bool {
+    matches!(err, DeadLetterError::Unknown(cause) if is_transient(cause))
+}
+
+fn is_transient_activity_error(err: &ActivityError) -> bool {
+    matches!(err, ActivityError::Unknown(cause) if is_transient(cause))
+}
+
+fn is_transient_inbox_error(err: &InboxError) -> bool {
+    matches!(err, InboxError::Unknown(cause) if is_transient(cause))
+}
+
+fn is_transient_search_error(err: &SearchError) -> bool {
+    matches!(err, SearchError::Unknown(cause) if is_transient(cause))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            Arc, Mutex,
+            atomic::{AtomicU32, Ordering},
+        },
+        time::Duration,
+    };
+
+    use anyhow::anyhow;
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    use crate::domain::crowdsrc::{
+        models::user::{EmailAddress, UserName},
+        ports::Transaction,
+    };
+
+    use super::*;
+
+    /// Fails `get_user` and `create_user` with the queued failures, then succeeds.
+    #[derive(Clone)]
+    struct FlakyUserRepository {
+        failures: Arc<Mutex<Vec<anyhow::Error>>>,
+        calls: Arc<AtomicU32>,
+    }
+
+    impl FlakyUserRepository {
+        fn new(failures: Vec<anyhow::Error>) -> Self {
+            Self {
+                failures: Arc::new(Mutex::new(failures)),
+                calls: Arc::new(AtomicU32::new(0)),
+            }
+        }
+
+        fn next_failure(&self) -> Option<anyhow::Error> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            self.failures.lock().unwrap().pop()
+        }
+    }
+
+    impl UserRepository for FlakyUserRepository {
+        async fn create_user(&self, req: &CreateUserRequest) -> Result<User, CreateUserError> {
+            if let Some(err) = self.next_failure() {
+                return Err(CreateUserError::Unknown(err));
+            }
+            Ok(User::new(
+                Uuid::new_v4(),
+                req.username().clone(),
+                req.email().clone(),
+                Utc::now(),
+            ))
+        }
+
+        async fn create_users(
+            &self,
+            _: &[CreateUserRequest],
+        ) -> Result<Vec<CreateUserOutcome>, CreateUsersError> {
+            unimplemented!()
+        }
+
+        fn stream_users(&self) -> impl Stream<Item = Result<User, ListUsersError>> + Send {
+            futures::stream::empty()
+        }
+
+        async fn get_user(&self, id: &Uuid) -> Result<User, GetUserError> {
+            if let Some(err) = self.next_failure() {
+                return Err(GetUserError::Unknown(err));
+            }
+            Ok(User::new(
+                *id,
+                UserName::new("Kristoffer").unwrap(),
+                EmailAddress::new("kristoffer@example.com").unwrap(),
+                Utc::now(),
+            ))
+        }
+
+        async fn erase_user(&self, _: &Uuid) -> Result<User, EraseUserError> {
+            unimplemented!()
+        }
+
+        async fn accept_terms(
+            &self,
+            _: &Uuid,
+            _: &TermsVersion,
+        ) -> Result<TermsAcceptance, AcceptTermsError> {
+            unimplemented!()
+        }
+
+        async fn list_terms_acceptances(
+            &self,
+            _: &Uuid,
+        ) -> Result<Vec<TermsAcceptance>, GetUserError> {
+            unimplemented!()
+        }
+
+        async fn save_dead_letter(
+            &self,
+            _: &NotificationEvent,
+            _: &str,
+        ) -> Result<DeadLetter, DeadLetterError> {
+            unimplemented!()
+        }
+
+        async fn list_dead_letters(&self) -> Result<Vec<DeadLetter>, DeadLetterError> {
+            unimplemented!()
+        }
+
+        async fn get_dead_letter(&self, _: &Uuid) -> Result<DeadLetter, DeadLetterError> {
+            unimplemented!()
+        }
+
+        async fn record_dead_letter_retry(
+            &self,
+            _: &Uuid,
+            _: &str,
+        ) -> Result<DeadLetter, DeadLetterError> {
+            unimplemented!()
+        }
+
+        async fn delete_dead_letter(&self, _: &Uuid) -> Result<(), DeadLetterError> {
+            unimplemented!()
+        }
+
+        async fn import_user(
+            &self,
+            _: &User,
+            _: &[TermsAcceptance],
+        ) -> Result<(), CreateUserError> {
+            unimplemented!()
+        }
+
+        async fn record_activity(&self, _: &Activity) -> Result<(), ActivityError> {
+            unimplemented!()
+        }
+
+        async fn list_activity(
+            &self,
+            _: &Uuid,
+            _: &ActivityQuery,
+        ) -> Result<ActivityPage, ActivityError> {
+            unimplemented!()
+        }
+
+        async fn save_email_change(
+            &self,
+            _: &PendingEmailChange,
+            _: &EmailChangeToken,
+        ) -> Result<(), EmailChangeError> {
+            unimplemented!()
+        }
+
+        async fn confirm_email_change(
+            &self,
+            _: &Uuid,
+            _: &EmailChangeToken,
+        ) -> Result<User, EmailChangeError> {
+            unimplemented!()
+        }
+
+        fn stream_email_changes(
+            &self,
+        ) -> impl Stream<
+            Item = Result<(PendingEmailChange, EmailChangeTokenHash), EmailChangeError>,
+        > + Send {
+            futures::stream::empty()
+        }
+
+        async fn import_email_change(
+            &self,
+            _: &PendingEmailChange,
+            _: &EmailChangeTokenHash,
+        ) -> Result<(), EmailChangeError> {
+            unimplemented!()
+        }
+
+        async fn delete_email_change(&self, _: &Uuid) -> Result<(), EmailChangeError> {
+            unimplemented!()
+        }
+
+        async fn delete_expired_email_changes(
+            &self,
+            _: &DateTime<Utc>,
+        ) -> Result<u64, EmailChangeError> {
+            unimplemented!()
+        }
+
+        async fn save_notification(&self, _: &InboxNotification) -> Result<(), InboxError> {
+            unimplemented!()
+        }
+
+        async fn list_notifications(
+            &self,
+            _: &uuid::Uuid,
+            _: &InboxQuery,
+        ) -> Result<Vec<InboxNotification>, InboxError> {
+            unimplemented!()
+        }
+
+        fn stream_notifications(
+            &self,
+        ) -> impl Stream<Item = Result<InboxNotification, InboxError>> + Send {
+            futures::stream::empty()
+        }
+
+        async fn count_unread_notifications(&self, _: &uuid::Uuid) -> Result<u64, InboxError> {
+            unimplemented!()
+        }
+
+        async fn mark_notifications_read(
+            &self,
+            _: &uuid::Uuid,
+            _: Option<&uuid::Uuid>,
+            _: &DateTime<Utc>,
+        ) -> Result<u64, InboxError> {
+            unimplemented!()
+        }
+
+        async fn delete_notifications_before(&self, _: &DateTime<Utc>) -> Result<u64, InboxError> {
+            unimplemented!()
+        }
+
+        async fn search_users(&self, _: &UserSearchQuery) -> Result<Vec<User>, SearchError> {
+            unimplemented!()
+        }
+    }
+
+    fn transient_error() -> anyhow::Error {
+        anyhow!(sqlx::Error::PoolTimedOut).context("failed to query")
+    }
+
+    fn create_user_request() -> CreateUserRequest {
+        CreateUserRequest::new(
+            UserName::new("Kristoffer").unwrap(),
+            EmailAddress::new("kristoffer@example.com").unwrap(),
+            TermsVersion::new("2026-01-30").unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_get_user_retries_transient_errors() {
+        let inner = FlakyUserRepository::new(vec![transient_error(), transient_error()]);
+        let repo = RetryingRepository::new(inner.clone(), RetryPolicy::new(2, Duration::ZERO));
+
+        let actual = repo.get_user(&Uuid::new_v4()).await;
+
+        assert!(actual.is_ok(), "expected success, but got {:?}", actual);
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_get_user_gives_up_after_max_retries() {
+        let inner = FlakyUserRepository::new(vec![transient_error(), transient_error()]);
+        let repo = RetryingRepository::new(inner.clone(), RetryPolicy::new(1, Duration::ZERO));
+
+        let actual = repo.get_user(&Uuid::new_v4()).await;
+
+        assert!(actual.is_err(), "expected failure, but got {:?}", actual);
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_get_user_does_not_retry_permanent_errors() {
+        let inner = FlakyUserRepository::new(vec![anyhow!(sqlx::Error::RowNotFound)]);
+        let repo = RetryingRepository::new(inner.clone(), RetryPolicy::new(3, Duration::ZERO));
+
+        let actual = repo.get_user(&Uuid::new_v4()).await;
+
+        assert!(actual.is_err(), "expected failure, but got {:?}", actual);
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_get_user_does_not_retry_within_a_request_transaction() {
+        let inner = FlakyUserRepository::new(vec![transient_error()]);
+        let repo = RetryingRepository::new(inner.clone(), RetryPolicy::new(3, Duration::ZERO));
+
+        let actual = RequestTransaction::finished()
+            .scope(repo.get_user(&Uuid::new_v4()))
+            .await;
+
+        assert!(actual.is_err(), "expected failure, but got {:?}", actual);
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_create_user_does_not_retry() {
+        let inner = FlakyUserRepository::new(vec![transient_error()]);
+        let repo = RetryingRepository::new(inner.clone(), RetryPolicy::new(3, Duration::ZERO));
+
+        let actual = repo.create_user(&create_user_request()).await;
+
+        assert!(actual.is_err(), "expected failure, but got {:?}", actual);
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_is_transient_only_for_transient_causes() {
+        assert!(is_transient(&anyhow!(sqlx::Error::PoolTimedOut)));
+        assert!(!is_transient(&anyhow!(std::io::Error::other("reset"))));
+        assert!(!is_transient(&anyhow!(sqlx::Error::Io(
+            std::io::Error::other("reset")
+        ))));
+        assert!(!is_transient(&anyhow!(sqlx::Error::RowNotFound)));
+        assert!(!is_transient(&anyhow!("something else")));
+    }
+}
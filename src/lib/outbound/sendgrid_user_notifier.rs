@@ -0,0 +1,213 @@
+use anyhow::Context;
+use serde_json::json;
+
+use crate::{
+    domain::crowdsrc::{
+        models::{
+            email_change::{EmailChangeToken, PendingEmailChange},
+            redacted::Redacted,
+            user::{EmailAddress, NotifyUserError, User},
+        },
+        ports::UserNotifier,
+    },
+    outbound::{
+        email_templates::EmailTemplates,
+        email_user_notifier::{OutgoingEmail, email_change_requested_emails, user_created_emails},
+        http_client::HttpClient,
+    },
+};
+
+/// The SendGrid v3 endpoint emails are sent to.
+const SEND_URL: &str = "https://api.sendgrid.com/v3/mail/send";
+
+/// A [UserNotifier] delivering the emails users are sent through the SendGrid v3 API.
+///
+/// In sandbox mode, SendGrid validates the emails without delivering them.
+#[derive(Clone)]
+pub struct SendGridUserNotifier {
+    templates: EmailTemplates,
+    client: HttpClient,
+    url: String,
+    api_key: String,
+    from: EmailAddress,
+    sandbox: bool,
+}
+
+impl std::fmt::Debug for SendGridUserNotifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SendGridUserNotifier")
+            .field("url", &self.url)
+            .field("sandbox", &self.sandbox)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SendGridUserNotifier {
+    pub fn new(
+        templates: EmailTemplates,
+        client: HttpClient,
+        api_key: String,
+        from: EmailAddress,
+        sandbox: bool,
+    ) -> Self {
+        Self {
+            templates,
+            client,
+            url: SEND_URL.to_string(),
+            api_key,
+            from,
+            sandbox,
+        }
+    }
+
+    /// Sends to `url` instead of to SendGrid, such as to a stand-in in tests.
+    pub fn with_url(self, url: String) -> Self {
+        Self { url, ..self }
+    }
+
+    async fn send(&self, emails: Vec<OutgoingEmail>) -> Result<(), NotifyUserError> {
+        for email in emails {
+            self.client
+                .send(
+                    self.client
+                        .post(&self.url)
+                        .bearer_auth(&self.api_key)
+                        .json(&self.payload(&email)),
+                )
+                .await
+                .and_then(reqwest::Response::error_for_status)
+                .with_context(|| format!("failed to send {} email through SendGrid", email.kind))?;
+            tracing::debug!(
+                to = %Redacted(&email.to),
+                sandbox = self.sandbox,
+                "sent {} email through SendGrid",
+                email.kind
+            );
+        }
+        Ok(())
+    }
+
+    fn payload(&self, email: &OutgoingEmail) -> serde_json::Value {
+        json!({
+            "personalizations": [{"to": [{"email": email.to.as_str()}]}],
+            "from": {"email": self.from.as_str()},
+            "subject": email.content.subject,
+            "content": [{"type": "text/plain", "value": email.content.body}],
+            "mail_settings": {"sandbox_mode": {"enable": self.sandbox}},
+        })
+    }
+}
+
+impl UserNotifier for SendGridUserNotifier {
+    async fn user_created(&self, user: &User) -> Result<(), NotifyUserError> {
+        self.send(user_created_emails(&self.templates, user)?).await
+    }
+
+    async fn email_change_requested(
+        &self,
+        user: &User,
+        change: &PendingEmailChange,
+        token: &EmailChangeToken,
+    ) -> Result<(), NotifyUserError> {
+        self.send(email_change_requested_emails(
+            &self.templates,
+            user,
+            change,
+            token,
+        )?)
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use axum::{Json, http::HeaderMap, http::StatusCode, routing::post};
+    use chrono::Utc;
+
+    use super::*;
+    use crate::domain::crowdsrc::models::user::UserName;
+
+    type Received = Arc<Mutex<Vec<(Option<String>, serde_json::Value)>>>;
+
+    /// Accepts emails like SendGrid does, keeping their authorization header and payload.
+    async fn serve_sendgrid(received: Received) -> String {
+        let router = axum::Router::new().route(
+            "/v3/mail/send",
+            post(
+                move |headers: HeaderMap, Json(payload): Json<serde_json::Value>| async move {
+                    let authorization = headers
+                        .get("authorization")
+                        .map(|value| value.to_str().unwrap().to_string());
+                    received.lock().unwrap().push((authorization, payload));
+                    StatusCode::ACCEPTED
+                },
+            ),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/v3/mail/send", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, router).await });
+        url
+    }
+
+    fn templates() -> EmailTemplates {
+        EmailTemplates::from_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/templates/email")).unwrap()
+    }
+
+    fn user() -> User {
+        User::new(
+            uuid::Uuid::new_v4(),
+            UserName::new("Kristoffer").unwrap(),
+            EmailAddress::new("kristoffer@example.com").unwrap(),
+            Utc::now(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_user_created_sends_welcome_email() {
+        let received = Received::default();
+        let url = serve_sendgrid(received.clone()).await;
+        let notifier = SendGridUserNotifier::new(
+            templates(),
+            HttpClient::default(),
+            "SG.key".to_string(),
+            EmailAddress::new("noreply@example.com").unwrap(),
+            true,
+        )
+        .with_url(url);
+
+        notifier.user_created(&user()).await.unwrap();
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 1);
+        let (authorization, payload) = &received[0];
+        assert_eq!(authorization.as_deref(), Some("Bearer SG.key"));
+        assert_eq!(
+            payload["personalizations"][0]["to"][0]["email"],
+            "kristoffer@example.com"
+        );
+        assert_eq!(payload["from"]["email"], "noreply@example.com");
+        assert_eq!(payload["mail_settings"]["sandbox_mode"]["enable"], true);
+        assert!(
+            payload["content"][0]["value"]
+                .as_str()
+                .unwrap()
+                .contains("Kristoffer")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_user_created_fails_when_sendgrid_is_unreachable() {
+        let notifier = SendGridUserNotifier::new(
+            templates(),
+            HttpClient::default(),
+            "SG.key".to_string(),
+            EmailAddress::new("noreply@example.com").unwrap(),
+            false,
+        )
+        .with_url("http://127.0.0.1:1/v3/mail/send".to_string());
+
+        assert!(notifier.user_created(&user()).await.is_err());
+    }
+}
@@ -0,0 +1,339 @@
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    domain::crowdsrc::{
+        models::{
+            email_change::{EmailChangeToken, PendingEmailChange},
+            redacted::Redacted,
+            user::{EmailAddress, NotifyUserError, User},
+        },
+        ports::UserNotifier,
+    },
+    outbound::{
+        email_templates::EmailTemplates,
+        email_user_notifier::{OutgoingEmail, email_change_requested_emails, user_created_emails},
+        http_client::HttpClient,
+    },
+};
+
+/// The address of the SES mailbox simulator, which accepts emails without delivering them.
+const SIMULATOR_ADDRESS: &str = "success@simulator.amazonses.com";
+
+/// A [UserNotifier] delivering the emails users are sent through the Amazon SES v2 API.
+///
+/// In sandbox mode, emails are sent to the SES mailbox simulator instead of to users.
+#[derive(Clone)]
+pub struct SesUserNotifier {
+    templates: EmailTemplates,
+    client: HttpClient,
+    url: String,
+    signer: Signer,
+    from: EmailAddress,
+    sandbox: bool,
+}
+
+impl std::fmt::Debug for SesUserNotifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SesUserNotifier")
+            .field("url", &self.url)
+            .field("sandbox", &self.sandbox)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SesUserNotifier {
+    pub fn new(
+        templates: EmailTemplates,
+        client: HttpClient,
+        region: String,
+        access_key_id: String,
+        secret_access_key: String,
+        from: EmailAddress,
+        sandbox: bool,
+    ) -> Self {
+        Self {
+            templates,
+            client,
+            url: format!("https://email.{region}.amazonaws.com/v2/email/outbound-emails"),
+            signer: Signer {
+                region,
+                service: "ses".to_string(),
+                access_key_id,
+                secret_access_key,
+            },
+            from,
+            sandbox,
+        }
+    }
+
+    /// Sends to `url` instead of to SES, such as to a stand-in in tests.
+    pub fn with_url(self, url: String) -> Self {
+        Self { url, ..self }
+    }
+
+    async fn send(&self, emails: Vec<OutgoingEmail>) -> Result<(), NotifyUserError> {
+        let url = reqwest::Url::parse(&self.url).context("invalid SES url")?;
+        let host = match url.port() {
+            Some(port) => format!("{}:{port}", url.host_str().unwrap_or_default()),
+            None => url.host_str().unwrap_or_default().to_string(),
+        };
+        for email in emails {
+            let body =
+                serde_json::to_vec(&self.payload(&email)).context("failed to encode email")?;
+            let signed = self
+                .signer
+                .sign("POST", &host, url.path(), &body, Utc::now());
+            self.client
+                .send(
+                    self.client
+                        .post(&self.url)
+                        .header("content-type", "application/json")
+                        .header("x-amz-date", signed.amz_date)
+                        .header("authorization", signed.authorization)
+                        .body(body),
+                )
+                .await
+                .and_then(reqwest::Response::error_for_status)
+                .with_context(|| format!("failed to send {} email through SES", email.kind))?;
+            tracing::debug!(
+                to = %Redacted(&email.to),
+                sandbox = self.sandbox,
+                "sent {} email through SES",
+                email.kind
+            );
+        }
+        Ok(())
+    }
+
+    fn payload(&self, email: &OutgoingEmail) -> serde_json::Value {
+        let to = if self.sandbox {
+            SIMULATOR_ADDRESS
+        } else {
+            email.to.as_str()
+        };
+        json!({
+            "FromEmailAddress": self.from.as_str(),
+            "Destination": {"ToAddresses": [to]},
+            "Content": {
+                "Simple": {
+                    "Subject": {"Data": email.content.subject, "Charset": "UTF-8"},
+                    "Body": {"Text": {"Data": email.content.body, "Charset": "UTF-8"}},
+                },
+            },
+        })
+    }
+}
+
+impl UserNotifier for SesUserNotifier {
+    async fn user_created(&self, user: &User) -> Result<(), NotifyUserError> {
+        self.send(user_created_emails(&self.templates, user)?).await
+    }
+
+    async fn email_change_requested(
+        &self,
+        user: &User,
+        change: &PendingEmailChange,
+        token: &EmailChangeToken,
+    ) -> Result<(), NotifyUserError> {
+        self.send(email_change_requested_emails(
+            &self.templates,
+            user,
+            change,
+            token,
+        )?)
+        .await
+    }
+}
+
+/// Signs requests to an AWS service with Signature Version 4, over the `host` and
+/// `x-amz-date` headers and the body.
+#[derive(Clone)]
+struct Signer {
+    region: String,
+    service: String,
+    access_key_id: String,
+    secret_access_key: String,
+}
+
+/// The headers a request signed by [Signer] is sent with.
+struct SignedHeaders {
+    amz_date: String,
+    authorization: String,
+}
+
+impl Signer {
+    fn sign(
+        &self,
+        method: &str,
+        host: &str,
+        path: &str,
+        body: &[u8],
+        now: DateTime<Utc>,
+    ) -> SignedHeaders {
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let scope = format!("{date}/{}/{}/aws4_request", self.region, self.service);
+        let signed_headers = "host;x-amz-date";
+        let canonical_request = format!(
+            "{method}\n{path}\n\nhost:{host}\nx-amz-date:{amz_date}\n\n{signed_headers}\n{}",
+            hex::encode(Sha256::digest(body))
+        );
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{amz_date}\n{scope}\n{}",
+            hex::encode(Sha256::digest(canonical_request.as_bytes()))
+        );
+        let key = [date.as_str(), &self.region, &self.service, "aws4_request"]
+            .into_iter()
+            .fold(
+                format!("AWS4{}", self.secret_access_key).into_bytes(),
+                |key, part| hmac(&key, part.as_bytes()),
+            );
+        let signature = hex::encode(hmac(&key, string_to_sign.as_bytes()));
+        SignedHeaders {
+            amz_date,
+            authorization: format!(
+                "AWS4-HMAC-SHA256 Credential={}/{scope}, SignedHeaders={signed_headers}, Signature={signature}",
+                self.access_key_id
+            ),
+        }
+    }
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use axum::{Json, http::HeaderMap, routing::post};
+
+    use super::*;
+    use crate::domain::crowdsrc::models::user::UserName;
+
+    type Received = Arc<Mutex<Vec<(Option<String>, serde_json::Value)>>>;
+
+    /// Accepts emails like SES does, keeping their authorization header and payload.
+    async fn serve_ses(received: Received) -> String {
+        let router = axum::Router::new().route(
+            "/v2/email/outbound-emails",
+            post(
+                move |headers: HeaderMap, Json(payload): Json<serde_json::Value>| async move {
+                    let authorization = headers
+                        .get("authorization")
+                        .map(|value| value.to_str().unwrap().to_string());
+                    received.lock().unwrap().push((authorization, payload));
+                    Json(json!({"MessageId": "message-id"}))
+                },
+            ),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!(
+            "http://{}/v2/email/outbound-emails",
+            listener.local_addr().unwrap()
+        );
+        tokio::spawn(async move { axum::serve(listener, router).await });
+        url
+    }
+
+    fn notifier(sandbox: bool) -> SesUserNotifier {
+        SesUserNotifier::new(
+            EmailTemplates::from_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/templates/email"))
+                .unwrap(),
+            HttpClient::default(),
+            "eu-north-1".to_string(),
+            "AKIDEXAMPLE".to_string(),
+            "secret".to_string(),
+            EmailAddress::new("noreply@example.com").unwrap(),
+            sandbox,
+        )
+    }
+
+    fn user() -> User {
+        User::new(
+            uuid::Uuid::new_v4(),
+            UserName::new("Kristoffer").unwrap(),
+            EmailAddress::new("kristoffer@example.com").unwrap(),
+            Utc::now(),
+        )
+    }
+
+    #[test]
+    fn test_sign_matches_aws_test_suite() {
+        // the get-vanilla case of the AWS Signature Version 4 test suite
+        let signer = Signer {
+            region: "us-east-1".to_string(),
+            service: "service".to_string(),
+            access_key_id: "AKIDEXAMPLE".to_string(),
+            secret_access_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".to_string(),
+        };
+        let now = DateTime::parse_from_rfc3339("2015-08-30T12:36:00Z")
+            .unwrap()
+            .to_utc();
+
+        let signed = signer.sign("GET", "example.amazonaws.com", "/", b"", now);
+
+        assert_eq!(signed.amz_date, "20150830T123600Z");
+        assert_eq!(
+            signed.authorization,
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, \
+            SignedHeaders=host;x-amz-date, \
+            Signature=5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_email_change_requested_sends_signed_emails() {
+        let received = Received::default();
+        let url = serve_ses(received.clone()).await;
+        let notifier = notifier(false).with_url(url);
+        let user = user();
+        let change = PendingEmailChange::new(
+            *user.id(),
+            EmailAddress::new("new@example.com").unwrap(),
+            Utc::now(),
+            Utc::now() + chrono::TimeDelta::hours(1),
+        );
+
+        notifier
+            .email_change_requested(&user, &change, &EmailChangeToken::generate())
+            .await
+            .unwrap();
+
+        let received = received.lock().unwrap();
+        let recipients: Vec<_> = received
+            .iter()
+            .map(|(_, payload)| payload["Destination"]["ToAddresses"][0].clone())
+            .collect();
+        assert_eq!(recipients, ["new@example.com", "kristoffer@example.com"]);
+        assert!(received.iter().all(|(authorization, _)| {
+            authorization.as_deref().is_some_and(|authorization| {
+                authorization.starts_with("AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/")
+                    && authorization.contains("/eu-north-1/ses/aws4_request")
+            })
+        }));
+    }
+
+    #[tokio::test]
+    async fn test_sandbox_sends_to_mailbox_simulator() {
+        let received = Received::default();
+        let url = serve_ses(received.clone()).await;
+        let notifier = notifier(true).with_url(url);
+
+        notifier.user_created(&user()).await.unwrap();
+
+        let received = received.lock().unwrap();
+        assert_eq!(
+            received[0].1["Destination"]["ToAddresses"][0],
+            SIMULATOR_ADDRESS
+        );
+        assert_eq!(received[0].1["FromEmailAddress"], "noreply@example.com");
+    }
+}
@@ -0,0 +1,86 @@
+use anyhow::Context;
+use sqlx::PgPool;
+
+use crate::domain::crowdsrc::{models::lock::LockError, ports::DistributedLock};
+
+/// The first key of the advisory locks taken by [SqlxAdvisoryLock], telling them apart from
+/// other advisory locks. The second key is a hash of the name of the lock.
+const LOCK_CLASS: i32 = 0x4c4f_434b;
+
+/// A [DistributedLock] over Postgres advisory locks, shared by all instances using the same
+/// database.
+///
+/// Each lock is held by a transaction on a connection of its own, so that Postgres releases it
+/// when the transaction ends, even if the instance holding it dies. Lock names are hashed to
+/// 32 bits, so two names may, rarely, share a lock.
+#[derive(Debug, Clone)]
+pub struct SqlxAdvisoryLock {
+    db_pool: PgPool,
+}
+
+impl SqlxAdvisoryLock {
+    pub fn new(db_pool: PgPool) -> Self {
+        Self { db_pool }
+    }
+}
+
+impl DistributedLock for SqlxAdvisoryLock {
+    async fn with_lock<T, W>(&self, name: &str, work: W) -> Result<T, LockError>
+    where
+        T: Send,
+        W: Future<Output = T> + Send,
+    {
+        let mut tx = self
+            .db_pool
+            .begin()
+            .await
+            .context("failed to start transaction")?;
+        sqlx::query!(
+            "SELECT pg_advisory_xact_lock($1, hashtext($2))",
+            LOCK_CLASS,
+            name,
+        )
+        .execute(&mut *tx)
+        .await
+        .with_context(|| format!("failed to take lock '{name}'"))?;
+        let output = work.await;
+        release(tx, name).await;
+        Ok(output)
+    }
+
+    async fn try_with_lock<T, W>(&self, name: &str, work: W) -> Result<Option<T>, LockError>
+    where
+        T: Send,
+        W: Future<Output = T> + Send,
+    {
+        let mut tx = self
+            .db_pool
+            .begin()
+            .await
+            .context("failed to start transaction")?;
+        let locked = sqlx::query_scalar!(
+            r#"SELECT pg_try_advisory_xact_lock($1, hashtext($2)) AS "locked!""#,
+            LOCK_CLASS,
+            name,
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .with_context(|| format!("failed to take lock '{name}'"))?;
+        if !locked {
+            return Ok(None);
+        }
+        let output = work.await;
+        release(tx, name).await;
+        Ok(Some(output))
+    }
+}
+
+/// Ends the transaction holding the lock named `name`, releasing it.
+///
+/// The work has been done by then, so failing to end the transaction is only logged: Postgres
+/// releases the lock all the same once the broken connection is closed.
+async fn release(tx: sqlx::Transaction<'static, sqlx::Postgres>, name: &str) {
+    if let Err(e) = tx.rollback().await {
+        tracing::warn!(lock = name, "failed to release lock: {e}");
+    }
+}
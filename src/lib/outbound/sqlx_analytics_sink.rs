@@ -0,0 +1,118 @@
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::Context;
+use chrono::{DateTime, Datelike, Months, NaiveDate, Utc};
+use sqlx::PgPool;
+
+use crate::domain::crowdsrc::{
+    models::analytics::{AnalyticsError, AnalyticsEvent},
+    ports::AnalyticsSink,
+};
+
+const DUPLICATE_TABLE_CODE: &str = "42P07";
+const UNIQUE_CONSTRAINT_VIOLATION_CODE: &str = "23505";
+
+/// An [AnalyticsSink] writing [AnalyticsEvent]s to the `analytics_events` table, partitioned by
+/// the month the events occurred in.
+///
+/// The partition of a month is created with its first events. The partitions known to exist
+/// are remembered per process, so that only the first write of a month checks for it.
+#[derive(Debug, Clone)]
+pub struct SqlxAnalyticsSink {
+    db_pool: PgPool,
+    partitions: Arc<Mutex<HashSet<NaiveDate>>>,
+}
+
+impl SqlxAnalyticsSink {
+    pub fn new(db_pool: PgPool) -> Self {
+        Self {
+            db_pool,
+            partitions: Arc::default(),
+        }
+    }
+
+    /// Creates the partition of the month starting on `month`, unless it exists.
+    async fn ensure_partition(&self, month: NaiveDate) -> anyhow::Result<()> {
+        if self.partitions.lock().unwrap().contains(&month) {
+            return Ok(());
+        }
+        let next_month = month + Months::new(1);
+        // Partition bounds can't be bound as parameters, but are formatted from dates only.
+        let ddl = format!(
+            "CREATE TABLE IF NOT EXISTS analytics_events_{} PARTITION OF analytics_events
+            FOR VALUES FROM ('{month} 00:00:00+00') TO ('{next_month} 00:00:00+00')",
+            month.format("%Y_%m"),
+        );
+        match sqlx::query(&ddl).execute(&self.db_pool).await {
+            Ok(_) => {}
+            // Another instance created the partition at the same time.
+            Err(sqlx::Error::Database(db_err))
+                if db_err.code().is_some_and(|code| {
+                    code == DUPLICATE_TABLE_CODE || code == UNIQUE_CONSTRAINT_VIOLATION_CODE
+                }) => {}
+            Err(err) => {
+                return Err(err)
+                    .with_context(|| format!("failed to create analytics partition for {month}"));
+            }
+        }
+        self.partitions.lock().unwrap().insert(month);
+        Ok(())
+    }
+}
+
+impl AnalyticsSink for SqlxAnalyticsSink {
+    async fn write_events(&self, events: &[AnalyticsEvent]) -> Result<(), AnalyticsError> {
+        let months: HashSet<NaiveDate> = events
+            .iter()
+            .map(|event| month_of(event.occurred_at()))
+            .collect();
+        for month in months {
+            self.ensure_partition(month).await?;
+        }
+        let user_ids: Vec<uuid::Uuid> = events.iter().map(|event| *event.user_id()).collect();
+        let task_ids: Vec<String> = events
+            .iter()
+            .map(|event| event.task_id().to_string())
+            .collect();
+        let kinds: Vec<String> = events
+            .iter()
+            .map(|event| event.kind().as_str().to_string())
+            .collect();
+        let durations: Vec<Option<i64>> = events
+            .iter()
+            .map(|event| event.kind().duration_ms().map(|ms| ms as i64))
+            .collect();
+        let occurred_at: Vec<DateTime<Utc>> =
+            events.iter().map(|event| *event.occurred_at()).collect();
+        let received_at: Vec<DateTime<Utc>> =
+            events.iter().map(|event| *event.received_at()).collect();
+        sqlx::query!(
+            r#"INSERT INTO analytics_events
+                (user_id, task_id, kind, duration_ms, occurred_at, received_at)
+            SELECT * FROM UNNEST(
+                $1::uuid[], $2::text[], $3::text[], $4::bigint[], $5::timestamptz[],
+                $6::timestamptz[]
+            )"#,
+            &user_ids,
+            &task_ids,
+            &kinds,
+            &durations as &[Option<i64>],
+            &occurred_at,
+            &received_at,
+        )
+        .execute(&self.db_pool)
+        .await
+        .context("failed to write analytics events")?;
+        Ok(())
+    }
+}
+
+/// The first day of the month `at` is in, in UTC.
+fn month_of(at: &DateTime<Utc>) -> NaiveDate {
+    at.date_naive()
+        .with_day(1)
+        .expect("every month has a first day")
+}
@@ -0,0 +1,40 @@
+use anyhow::Context;
+use chrono::Utc;
+use sqlx::PgPool;
+
+use crate::domain::crowdsrc::{models::maintenance::MaintenanceError, ports::MaintenanceSwitch};
+
+/// A [MaintenanceSwitch] persisted in the single row of the `maintenance_mode` table, so that
+/// it is shared by all instances and survives restarts.
+#[derive(Debug, Clone)]
+pub struct SqlxMaintenanceSwitch {
+    db_pool: PgPool,
+}
+
+impl SqlxMaintenanceSwitch {
+    pub fn new(db_pool: PgPool) -> Self {
+        Self { db_pool }
+    }
+}
+
+impl MaintenanceSwitch for SqlxMaintenanceSwitch {
+    async fn is_enabled(&self) -> Result<bool, MaintenanceError> {
+        let enabled = sqlx::query_scalar!("SELECT enabled FROM maintenance_mode")
+            .fetch_optional(&self.db_pool)
+            .await
+            .context("failed to read maintenance mode")?;
+        Ok(enabled.unwrap_or(false))
+    }
+
+    async fn set_enabled(&self, enabled: bool) -> Result<(), MaintenanceError> {
+        sqlx::query!(
+            "UPDATE maintenance_mode SET enabled = $1, updated_at = $2",
+            enabled,
+            Utc::now()
+        )
+        .execute(&self.db_pool)
+        .await
+        .context("failed to write maintenance mode")?;
+        Ok(())
+    }
+}
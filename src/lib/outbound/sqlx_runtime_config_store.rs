@@ -0,0 +1,92 @@
+use anyhow::Context;
+use chrono::Utc;
+use sqlx::PgPool;
+
+use crate::domain::crowdsrc::{
+    models::runtime_config::{LogLevel, RuntimeConfig, RuntimeConfigError},
+    ports::RuntimeConfigStore,
+};
+
+const LOG_LEVEL: &str = "log_level";
+const MAX_SUBMISSIONS: &str = "max_submissions";
+
+/// A [RuntimeConfigStore] persisting each set setting as a row of the `runtime_config` table,
+/// so that it is shared by all instances and survives restarts.
+#[derive(Debug, Clone)]
+pub struct SqlxRuntimeConfigStore {
+    db_pool: PgPool,
+}
+
+impl SqlxRuntimeConfigStore {
+    pub fn new(db_pool: PgPool) -> Self {
+        Self { db_pool }
+    }
+}
+
+impl RuntimeConfigStore for SqlxRuntimeConfigStore {
+    async fn load(&self) -> Result<RuntimeConfig, RuntimeConfigError> {
+        let rows = sqlx::query!("SELECT name, value FROM runtime_config")
+            .fetch_all(&self.db_pool)
+            .await
+            .context("failed to read runtime config")?;
+        let mut config = RuntimeConfig::default();
+        for row in rows {
+            match row.name.as_str() {
+                LOG_LEVEL => {
+                    config.log_level = Some(
+                        LogLevel::new(&row.value)
+                            .with_context(|| format!("invalid {LOG_LEVEL} '{}'", row.value))?,
+                    )
+                }
+                MAX_SUBMISSIONS => {
+                    config.max_submissions =
+                        Some(row.value.parse().with_context(|| {
+                            format!("invalid {MAX_SUBMISSIONS} '{}'", row.value)
+                        })?)
+                }
+                name => tracing::warn!("ignoring unknown runtime config setting {name}"),
+            }
+        }
+        Ok(config)
+    }
+
+    async fn save(&self, config: &RuntimeConfig) -> Result<(), RuntimeConfigError> {
+        let settings = [
+            (
+                LOG_LEVEL,
+                config.log_level.as_ref().map(LogLevel::to_string),
+            ),
+            (
+                MAX_SUBMISSIONS,
+                config.max_submissions.map(|max| max.to_string()),
+            ),
+        ];
+        let mut tx = self
+            .db_pool
+            .begin()
+            .await
+            .context("failed to start transaction")?;
+        sqlx::query!("DELETE FROM runtime_config")
+            .execute(&mut *tx)
+            .await
+            .context("failed to clear runtime config")?;
+        for (name, value) in settings {
+            let Some(value) = value else {
+                continue;
+            };
+            sqlx::query!(
+                "INSERT INTO runtime_config (name, value, updated_at) VALUES ($1, $2, $3)",
+                name,
+                value,
+                Utc::now()
+            )
+            .execute(&mut *tx)
+            .await
+            .with_context(|| format!("failed to write runtime config setting {name}"))?;
+        }
+        tx.commit()
+            .await
+            .context("failed to commit runtime config")?;
+        Ok(())
+    }
+}
@@ -0,0 +1,126 @@
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+use crate::domain::crowdsrc::{
+    models::schedule::{CronExpression, Schedule, ScheduleError},
+    ports::ScheduleStore,
+};
+
+/// A [ScheduleStore] persisting each [Schedule] as a row of the `schedules` table.
+///
+/// A run is claimed by moving the next run of its schedule on only if it is still the one
+/// read, so that of the instances racing for the same run, the first one claims it and the
+/// others find it gone, without waiting for each other.
+#[derive(Debug, Clone)]
+pub struct SqlxScheduleStore {
+    db_pool: PgPool,
+}
+
+impl SqlxScheduleStore {
+    pub fn new(db_pool: PgPool) -> Self {
+        Self { db_pool }
+    }
+}
+
+impl ScheduleStore for SqlxScheduleStore {
+    async fn register(
+        &self,
+        name: &str,
+        cron: &CronExpression,
+        now: DateTime<Utc>,
+    ) -> Result<Schedule, ScheduleError> {
+        let next_run_at = cron
+            .next_after(now)
+            .with_context(|| format!("schedule '{name}' never runs"))?;
+        let row = sqlx::query_as!(
+            ScheduleRow,
+            r#"INSERT INTO schedules (name, cron, next_run_at) VALUES ($1, $2, $3)
+            ON CONFLICT (name) DO UPDATE
+            SET cron = EXCLUDED.cron,
+                next_run_at = CASE
+                    WHEN schedules.cron = EXCLUDED.cron THEN schedules.next_run_at
+                    ELSE EXCLUDED.next_run_at
+                END
+            RETURNING name, cron, next_run_at, last_run_at"#,
+            name,
+            cron.as_str(),
+            next_run_at,
+        )
+        .fetch_one(&self.db_pool)
+        .await
+        .with_context(|| format!("failed to register schedule '{name}'"))?;
+        Ok(row.try_into()?)
+    }
+
+    async fn claim(&self, name: &str, now: DateTime<Utc>) -> Result<bool, ScheduleError> {
+        let schedule: Schedule = sqlx::query_as!(
+            ScheduleRow,
+            "SELECT name, cron, next_run_at, last_run_at FROM schedules WHERE name = $1",
+            name
+        )
+        .fetch_optional(&self.db_pool)
+        .await
+        .with_context(|| format!("failed to fetch schedule '{name}'"))?
+        .ok_or_else(|| ScheduleError::NotFound {
+            name: name.to_string(),
+        })?
+        .try_into()?;
+        if *schedule.next_run_at() > now {
+            return Ok(false);
+        }
+        let next_run_at = schedule
+            .cron()
+            .next_after(now)
+            .with_context(|| format!("schedule '{name}' never runs again"))?;
+        let claimed = sqlx::query!(
+            "UPDATE schedules SET next_run_at = $3, last_run_at = $4
+            WHERE name = $1 AND next_run_at = $2",
+            name,
+            schedule.next_run_at(),
+            next_run_at,
+            now,
+        )
+        .execute(&self.db_pool)
+        .await
+        .with_context(|| format!("failed to claim run of schedule '{name}'"))?;
+        Ok(claimed.rows_affected() == 1)
+    }
+
+    async fn list(&self) -> Result<Vec<Schedule>, ScheduleError> {
+        let rows = sqlx::query_as!(
+            ScheduleRow,
+            "SELECT name, cron, next_run_at, last_run_at FROM schedules ORDER BY name"
+        )
+        .fetch_all(&self.db_pool)
+        .await
+        .context("failed to fetch schedules")?;
+        Ok(rows
+            .into_iter()
+            .map(Schedule::try_from)
+            .collect::<anyhow::Result<_>>()?)
+    }
+}
+
+/// A row of the `schedules` table.
+struct ScheduleRow {
+    name: String,
+    cron: String,
+    next_run_at: DateTime<Utc>,
+    last_run_at: Option<DateTime<Utc>>,
+}
+
+impl TryFrom<ScheduleRow> for Schedule {
+    type Error = anyhow::Error;
+
+    fn try_from(row: ScheduleRow) -> Result<Self, Self::Error> {
+        let cron = CronExpression::parse(&row.cron)
+            .with_context(|| format!("invalid cron expression stored for '{}'", row.name))?;
+        Ok(Schedule::new(
+            &row.name,
+            cron,
+            row.next_run_at,
+            row.last_run_at,
+        ))
+    }
+}
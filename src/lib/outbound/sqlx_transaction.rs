@@ -0,0 +1,149 @@
+use std::{
+    ops::{Deref, DerefMut},
+    sync::Arc,
+};
+
+use anyhow::Context;
+use sqlx::{PgConnection, PgPool, Postgres, Transaction, pool::PoolConnection};
+use tokio::sync::{Mutex, OwnedMutexGuard};
+
+use crate::domain::crowdsrc::ports;
+
+type SharedTransaction = Arc<Mutex<Option<Transaction<'static, Postgres>>>>;
+
+tokio::task_local! {
+    static CURRENT_TRANSACTION: RequestTransaction;
+}
+
+/// Begins [RequestTransaction]s on a Postgres pool.
+#[derive(Debug, Clone)]
+pub struct SqlxTransactionManager {
+    db_pool: PgPool,
+}
+
+impl SqlxTransactionManager {
+    pub fn new(db_pool: PgPool) -> Self {
+        Self { db_pool }
+    }
+}
+
+impl ports::TransactionManager for SqlxTransactionManager {
+    type Transaction = RequestTransaction;
+
+    async fn begin(&self) -> anyhow::Result<RequestTransaction> {
+        RequestTransaction::begin(&self.db_pool)
+            .await
+            .context("failed to start Postgres transaction")
+    }
+}
+
+/// A Postgres transaction shared by all [SqlxUserRepository](super::sqlx_user_repository) calls
+/// made within its [scope](ports::Transaction::scope), so that an operation writing several
+/// times is atomic as a whole.
+///
+/// Repository operations that use a transaction of their own run in a savepoint of the shared
+/// transaction instead. Dropping a [RequestTransaction] that was neither committed nor rolled
+/// back rolls it back.
+#[derive(Debug, Clone)]
+pub struct RequestTransaction {
+    tx: SharedTransaction,
+}
+
+impl RequestTransaction {
+    pub async fn begin(db_pool: &PgPool) -> Result<Self, sqlx::Error> {
+        let tx = db_pool.begin().await?;
+        Ok(Self {
+            tx: Arc::new(Mutex::new(Some(tx))),
+        })
+    }
+
+    /// Returns whether the current task runs in the [scope](ports::Transaction::scope) of a
+    /// [RequestTransaction].
+    pub fn in_scope() -> bool {
+        CURRENT_TRANSACTION.try_with(|_| ()).is_ok()
+    }
+
+    /// A transaction that has already finished, for scoping calls in tests without a database.
+    #[cfg(test)]
+    pub(crate) fn finished() -> Self {
+        Self {
+            tx: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    fn current() -> Option<Self> {
+        CURRENT_TRANSACTION.try_with(Clone::clone).ok()
+    }
+}
+
+impl ports::Transaction for RequestTransaction {
+    async fn scope<F>(&self, f: F) -> F::Output
+    where
+        F: Future + Send,
+        F::Output: Send,
+    {
+        CURRENT_TRANSACTION.scope(self.clone(), f).await
+    }
+
+    async fn commit(self) -> anyhow::Result<()> {
+        match self.tx.lock().await.take() {
+            Some(tx) => tx
+                .commit()
+                .await
+                .context("failed to commit Postgres transaction"),
+            None => Ok(()),
+        }
+    }
+
+    async fn rollback(self) -> anyhow::Result<()> {
+        match self.tx.lock().await.take() {
+            Some(tx) => tx
+                .rollback()
+                .await
+                .context("failed to roll back Postgres transaction"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// A connection to run queries on: the one of the [RequestTransaction] in scope, if any, or one
+/// from the pool otherwise.
+pub(super) enum ScopedConnection {
+    Transaction(OwnedMutexGuard<Option<Transaction<'static, Postgres>>>),
+    Pool(PoolConnection<Postgres>),
+}
+
+impl ScopedConnection {
+    pub(super) async fn acquire(db_pool: &PgPool) -> Result<Self, sqlx::Error> {
+        let Some(current) = RequestTransaction::current() else {
+            return Ok(Self::Pool(db_pool.acquire().await?));
+        };
+        let guard = current.tx.lock_owned().await;
+        if guard.is_none() {
+            return Err(sqlx::Error::Protocol(
+                "request transaction has already finished".to_string(),
+            ));
+        }
+        Ok(Self::Transaction(guard))
+    }
+}
+
+impl Deref for ScopedConnection {
+    type Target = PgConnection;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            Self::Transaction(guard) => guard.as_deref().expect("checked on acquire"),
+            Self::Pool(conn) => conn,
+        }
+    }
+}
+
+impl DerefMut for ScopedConnection {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        match self {
+            Self::Transaction(guard) => guard.as_deref_mut().expect("checked on acquire"),
+            Self::Pool(conn) => conn,
+        }
+    }
+}
@@ -1,56 +1,464 @@
+use std::collections::{HashMap, HashSet};
+
 use anyhow::Context;
-use chrono::{DateTime, Utc};
-use sqlx::{Executor, PgPool, Transaction};
+use chrono::{DateTime, SubsecRound, Utc};
+use futures::{Stream, StreamExt};
+use sha2::{Digest, Sha256};
+use sqlx::{Connection, Executor, PgPool, Transaction};
 use uuid::Uuid;
 
 use crate::domain::crowdsrc::{
-    models::user::{CreateUserError, CreateUserRequest, EmailAddress, User, UserName},
+    models::activity::{
+        Activity, ActivityCursor, ActivityError, ActivityKind, ActivityPage, ActivityQuery,
+    },
+    models::dead_letter::{DeadLetter, DeadLetterError, NotificationEvent},
+    models::email_change::{
+        EmailChangeError, EmailChangeToken, EmailChangeTokenHash, PendingEmailChange,
+    },
+    models::inbox::{InboxError, InboxKind, InboxNotification, InboxQuery},
+    models::search::{SearchError, UserSearchQuery},
+    models::terms::{AcceptTermsError, TermsAcceptance, TermsVersion},
+    models::user::{
+        CreateUserError, CreateUserOutcome, CreateUserRequest, CreateUsersError, EmailAddress,
+        EmailCanonicalization, EraseUserError, GetUserError, ListUsersError, User, UserName,
+    },
     ports::UserRepository,
 };
+#[allow(unused_imports)] // RequestTransaction is used in doc comments
+use crate::outbound::sqlx_transaction::RequestTransaction;
+use crate::outbound::{field_cipher::FieldCipher, sqlx_transaction::ScopedConnection};
+
+/// The field emails are sealed for by the [FieldCipher].
+const EMAIL_FIELD: &str = "users.email";
 
 #[derive(Debug, Clone)]
 pub struct SqlxUserRepository {
     db_pool: PgPool,
+    cipher: Option<FieldCipher>,
+    canonicalization: EmailCanonicalization,
+    reject_confusable_usernames: bool,
 }
 
 impl SqlxUserRepository {
     pub fn new(db_pool: PgPool) -> Self {
-        Self { db_pool }
+        Self {
+            db_pool,
+            cipher: None,
+            canonicalization: EmailCanonicalization::default(),
+            reject_confusable_usernames: false,
+        }
+    }
+
+    /// Encrypts emails with `cipher` from now on, looking them up by their index.
+    ///
+    /// Emails stored before are still read, but are only found by their index once
+    /// [Self::reencrypt_users] has run, so it should be run right after enabling encryption.
+    pub fn with_cipher(mut self, cipher: FieldCipher) -> Self {
+        self.cipher = Some(cipher);
+        self
+    }
+
+    /// Keeps emails unique by their canonical form under `canonicalization`.
+    ///
+    /// Like with [Self::with_cipher], [Self::reencrypt_users] should be run after changing it,
+    /// for the emails stored before to be indexed by their new canonical form.
+    pub fn with_email_canonicalization(mut self, canonicalization: EmailCanonicalization) -> Self {
+        self.canonicalization = canonicalization;
+        self
+    }
+
+    /// Refuses usernames with the same [UserName::skeleton] as that of another user if
+    /// `reject` is set, so that users can't be impersonated with lookalike usernames.
+    ///
+    /// Like with [Self::with_cipher], [Self::reencrypt_users] should be run after enabling it,
+    /// for the usernames stored before to be refused lookalikes of.
+    pub fn with_confusable_usernames_rejected(mut self, reject: bool) -> Self {
+        self.reject_confusable_usernames = reject;
+        self
+    }
+
+    /// Re-encrypts the emails of all users that are stored in plain text or under a retired
+    /// key, if encryption is enabled, and reindexes those whose email index or username
+    /// skeleton is outdated, `batch_size` users per transaction.
+    ///
+    /// Users that change while being re-encrypted are skipped, and picked up by the next run.
+    /// Users whose email now has the same index as that of another user are left as they are
+    /// and counted as duplicates, to be merged or erased. Users whose username has the same
+    /// skeleton as that of another user are left without one and counted as confusables, to be
+    /// reviewed.
+    pub async fn reencrypt_users(&self, batch_size: u32) -> anyhow::Result<ReencryptionSummary> {
+        let mut summary = ReencryptionSummary::default();
+        let mut after: Option<Uuid> = None;
+        loop {
+            let mut tx = self
+                .db_pool
+                .begin()
+                .await
+                .context("failed to start Postgres transaction")?;
+            let rows = sqlx::query!(
+                r#"SELECT id, username, email, email_index, username_skeleton,
+                    erased_at IS NOT NULL AS "erased!"
+                FROM users
+                WHERE ($1::uuid IS NULL OR id > $1)
+                ORDER BY id
+                LIMIT $2"#,
+                after,
+                i64::from(batch_size)
+            )
+            .fetch_all(&mut *tx)
+            .await
+            .context("failed to fetch users to re-encrypt")?;
+            let Some(last) = rows.last() else {
+                break;
+            };
+            after = Some(last.id);
+            for row in rows {
+                summary.users += 1;
+                let email = self
+                    .open_email(&row.email)
+                    .with_context(|| format!("failed to decrypt email of user {}", row.id))?;
+                let email = EmailAddress::from_persisted(&email)
+                    .with_context(|| format!("invalid email stored for user {}", row.id))?;
+                let resealed = self
+                    .cipher
+                    .as_ref()
+                    .is_some_and(|cipher| cipher.needs_resealing(&row.email));
+                let email_index = self.email_index(&email);
+                let mut username_skeleton = if row.erased {
+                    None
+                } else {
+                    self.username_skeleton(&UserName::from_persisted(&row.username))
+                };
+                let reindexed = row.email_index.as_ref() != Some(&email_index);
+                let mut reskeletoned = row.username_skeleton != username_skeleton;
+                if !resealed && !reindexed && !reskeletoned {
+                    continue;
+                }
+                if reindexed {
+                    let duplicate_of = sqlx::query_scalar!(
+                        "SELECT id FROM users WHERE email_index = $1 AND id <> $2",
+                        email_index,
+                        row.id
+                    )
+                    .fetch_optional(&mut *tx)
+                    .await
+                    .with_context(|| format!("failed to look up duplicates of user {}", row.id))?;
+                    if let Some(duplicate_of) = duplicate_of {
+                        tracing::warn!(
+                            user_id = %row.id,
+                            %duplicate_of,
+                            "not reindexing user with the email of another user"
+                        );
+                        summary.duplicates += 1;
+                        continue;
+                    }
+                }
+                if let Some(skeleton) = username_skeleton.as_ref().filter(|_| reskeletoned) {
+                    let confusable_with = sqlx::query_scalar!(
+                        "SELECT id FROM users WHERE username_skeleton = $1 AND id <> $2",
+                        skeleton,
+                        row.id
+                    )
+                    .fetch_optional(&mut *tx)
+                    .await
+                    .with_context(|| format!("failed to look up lookalikes of user {}", row.id))?;
+                    if let Some(confusable_with) = confusable_with {
+                        tracing::warn!(
+                            user_id = %row.id,
+                            %confusable_with,
+                            "not indexing username that looks like that of another user"
+                        );
+                        summary.confusables += 1;
+                        username_skeleton = None;
+                        reskeletoned = row.username_skeleton.is_some();
+                        if !resealed && !reindexed && !reskeletoned {
+                            continue;
+                        }
+                    }
+                }
+                let sealed = match &self.cipher {
+                    Some(cipher) if resealed => cipher.seal(EMAIL_FIELD, email.as_str())?,
+                    _ => row.email.clone(),
+                };
+                let updated = sqlx::query!(
+                    r#"UPDATE users SET email = $3, email_index = $4, username_skeleton = $5
+                    WHERE id = $1 AND email = $2"#,
+                    row.id,
+                    row.email,
+                    sealed,
+                    email_index,
+                    username_skeleton,
+                )
+                .execute(&mut *tx)
+                .await
+                .with_context(|| format!("failed to re-encrypt user {}", row.id))?;
+                if updated.rows_affected() > 0 {
+                    summary.resealed += u64::from(resealed);
+                    summary.reindexed += u64::from(reindexed || reskeletoned);
+                }
+            }
+            tx.commit()
+                .await
+                .context("failed to commit Postgres transaction")?;
+        }
+        Ok(summary)
     }
 
+    /// The value stored for `email`, sealed if encryption is enabled, and its index.
+    fn seal_email(&self, email: &EmailAddress) -> anyhow::Result<(String, String)> {
+        let sealed = match &self.cipher {
+            Some(cipher) => cipher.seal(EMAIL_FIELD, email.as_str())?,
+            None => email.to_string(),
+        };
+        Ok((sealed, self.email_index(email)))
+    }
+
+    /// The value emails are kept unique by: their canonical form, hashed if encryption is
+    /// enabled.
+    fn email_index(&self, email: &EmailAddress) -> String {
+        let canonical = email.canonical(&self.canonicalization);
+        match &self.cipher {
+            Some(cipher) => cipher.index(EMAIL_FIELD, &canonical),
+            None => canonical,
+        }
+    }
+
+    /// The value usernames are kept unique by, besides themselves: their skeleton, if
+    /// confusable usernames are rejected.
+    fn username_skeleton(&self, username: &UserName) -> Option<String> {
+        self.reject_confusable_usernames
+            .then(|| username.skeleton())
+    }
+
+    /// Opens an email stored by [Self::seal_email].
+    fn open_email(&self, stored: &str) -> anyhow::Result<String> {
+        match &self.cipher {
+            Some(cipher) => cipher.open(EMAIL_FIELD, stored),
+            None => Ok(stored.to_string()),
+        }
+    }
+
+    /// Acquires a connection, taking part in the [RequestTransaction] in scope, if any.
+    async fn connection(&self) -> anyhow::Result<ScopedConnection> {
+        ScopedConnection::acquire(&self.db_pool)
+            .await
+            .context("failed to acquire Postgres connection")
+    }
+
+    /// Inserts a user with the stored `email` and `email_index`, as given by
+    /// [Self::seal_email].
     async fn save_user(
         &self,
         tx: &mut Transaction<'_, sqlx::Postgres>,
         username: &UserName,
-        email: &EmailAddress,
+        email: &str,
+        email_index: &str,
     ) -> Result<(Uuid, DateTime<Utc>), sqlx::Error> {
         let id = Uuid::new_v4();
+        let username_skeleton = self.username_skeleton(username);
         let username = username.to_string();
-        let email = email.to_string();
-        let created_at = Utc::now();
+        // Postgres keeps microseconds, so the user returned matches the one later read back.
+        let created_at = Utc::now().trunc_subsecs(6);
         let query = sqlx::query!(
-            "INSERT INTO users (id, email, username, created_at) VALUES ($1, $2, $3, $4)",
+            r#"INSERT INTO users (id, email, email_index, username, username_skeleton, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6)"#,
             id,
             email,
+            email_index,
             username,
+            username_skeleton,
             created_at,
         );
         tx.execute(query).await?;
         Ok((id, created_at))
     }
+
+    /// Inserts all `reqs` in a single statement, skipping rows that violate a unique constraint.
+    ///
+    /// The stored `emails` and `email_indexes` are those given by [Self::seal_email] for each
+    /// request. Returns the ids assigned to each request, in order, and the set of ids actually
+    /// inserted.
+    async fn save_users(
+        &self,
+        tx: &mut Transaction<'_, sqlx::Postgres>,
+        reqs: &[CreateUserRequest],
+        emails: &[String],
+        email_indexes: &[String],
+        created_at: DateTime<Utc>,
+    ) -> Result<(Vec<Uuid>, HashSet<Uuid>), sqlx::Error> {
+        let ids: Vec<Uuid> = reqs.iter().map(|_| Uuid::new_v4()).collect();
+        let usernames: Vec<String> = reqs.iter().map(|req| req.username().to_string()).collect();
+        let username_skeletons: Vec<Option<String>> = reqs
+            .iter()
+            .map(|req| self.username_skeleton(req.username()))
+            .collect();
+        let inserted = sqlx::query_scalar!(
+            r#"INSERT INTO users (id, email, email_index, username, username_skeleton, created_at)
+            SELECT id, email, email_index, username, username_skeleton, $6
+            FROM UNNEST($1::uuid[], $2::text[], $3::text[], $4::text[], $5::text[])
+                AS batch(id, email, email_index, username, username_skeleton)
+            ON CONFLICT DO NOTHING
+            RETURNING id"#,
+            &ids,
+            emails,
+            email_indexes,
+            &usernames,
+            &username_skeletons as &[Option<String>],
+            created_at,
+        )
+        .fetch_all(&mut **tx)
+        .await?;
+        Ok((ids, inserted.into_iter().collect()))
+    }
+
+    /// Records that each of `user_ids` accepted the corresponding version in `terms_versions`.
+    async fn save_terms_acceptances(
+        &self,
+        tx: &mut Transaction<'_, sqlx::Postgres>,
+        user_ids: &[Uuid],
+        terms_versions: &[String],
+        accepted_at: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        let ids: Vec<Uuid> = user_ids.iter().map(|_| Uuid::new_v4()).collect();
+        let query = sqlx::query!(
+            r#"INSERT INTO terms_acceptances (id, user_id, terms_version, accepted_at)
+            SELECT id, user_id, terms_version, $4
+            FROM UNNEST($1::uuid[], $2::uuid[], $3::text[]) AS batch(id, user_id, terms_version)"#,
+            &ids,
+            user_ids,
+            terms_versions,
+            accepted_at,
+        );
+        tx.execute(query).await?;
+        Ok(())
+    }
+
+    /// Returns the unique constraint each of `reqs` that was not `inserted` by
+    /// [Self::save_users] violates, and `None` for those inserted.
+    ///
+    /// A rejected request collided with a persisted user, or with a request earlier in the batch
+    /// that was inserted, as rows are inserted in order. Users inserted later in the batch may
+    /// collide with it too, but didn't reject it, so they are left out.
+    async fn rejections(
+        &self,
+        tx: &mut Transaction<'_, sqlx::Postgres>,
+        reqs: &[CreateUserRequest],
+        ids: &[Uuid],
+        inserted: &HashSet<Uuid>,
+        emails: &[String],
+        email_indexes: &[String],
+    ) -> anyhow::Result<Vec<Option<Violation>>> {
+        let usernames: Vec<String> = reqs.iter().map(|req| req.username().to_string()).collect();
+        let username_skeletons: Vec<Option<String>> = reqs
+            .iter()
+            .map(|req| self.username_skeleton(req.username()))
+            .collect();
+        let colliding = sqlx::query!(
+            r#"SELECT id, email, email_index, username, username_skeleton FROM users
+            WHERE email = ANY($1) OR email_index = ANY($2) OR username = ANY($3)
+                OR username_skeleton = ANY($4)"#,
+            emails,
+            email_indexes,
+            &usernames,
+            &username_skeletons as &[Option<String>],
+        )
+        .fetch_all(&mut **tx)
+        .await
+        .context("failed to look up colliding users")?;
+        let positions: HashMap<Uuid, usize> =
+            ids.iter().enumerate().map(|(i, id)| (*id, i)).collect();
+
+        (0..reqs.len())
+            .map(|i| {
+                if inserted.contains(&ids[i]) {
+                    return Ok(None);
+                }
+                let earlier = || {
+                    colliding
+                        .iter()
+                        .filter(|user| positions.get(&user.id).is_none_or(|&j| j < i))
+                };
+                if earlier().any(|user| {
+                    user.email == emails[i] || user.email_index.as_ref() == Some(&email_indexes[i])
+                }) {
+                    Ok(Some(Violation::Email))
+                } else if earlier().any(|user| {
+                    user.username == usernames[i]
+                        || (username_skeletons[i].is_some()
+                            && user.username_skeleton == username_skeletons[i])
+                }) {
+                    Ok(Some(Violation::Username))
+                } else {
+                    Err(anyhow::anyhow!(
+                        "user {:?} of batch was not saved, but collides with no user",
+                        reqs[i].username()
+                    ))
+                }
+            })
+            .collect()
+    }
+
+    /// Returns whether a user other than `user_id` has `email`, found by its index or, if not
+    /// yet indexed, stored as it is.
+    async fn is_email_taken(
+        &self,
+        tx: &mut Transaction<'_, sqlx::Postgres>,
+        user_id: &Uuid,
+        email: &EmailAddress,
+    ) -> Result<bool, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"SELECT EXISTS (
+                SELECT 1 FROM users WHERE id <> $1 AND (email = $2 OR email_index = $3)
+            ) AS "taken!""#,
+            user_id,
+            email.as_str(),
+            self.email_index(email),
+        )
+        .fetch_one(&mut **tx)
+        .await
+    }
+
+    /// Maps a row of the `users` table to a [User], opening its email.
+    fn user_from_row(&self, row: UserRow) -> anyhow::Result<User> {
+        let username = UserName::from_persisted(&row.username);
+        let email = self
+            .open_email(&row.email)
+            .with_context(|| format!("failed to decrypt email of user {}", row.id))?;
+        let email = EmailAddress::from_persisted(&email)
+            .with_context(|| format!("invalid email stored for user {}", row.id))?;
+        Ok(User::new(row.id, username, email, row.created_at)
+            .with_email_verified(row.email_verified))
+    }
+}
+
+/// What [SqlxUserRepository::reencrypt_users] did.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReencryptionSummary {
+    /// How many users were examined.
+    pub users: u64,
+    /// How many emails were encrypted under the current key.
+    pub resealed: u64,
+    /// How many email indexes or username skeletons were recomputed.
+    pub reindexed: u64,
+    /// How many users were not reindexed, since their email has the same index as that of
+    /// another user.
+    pub duplicates: u64,
+    /// How many users were left without a username skeleton, since their username looks like
+    /// that of another user.
+    pub confusables: u64,
 }
 
 impl UserRepository for SqlxUserRepository {
-    #[allow(clippy::manual_async_fn)]
     async fn create_user(&self, req: &CreateUserRequest) -> Result<User, CreateUserError> {
-        let mut tx = self
-            .db_pool
+        let mut conn = self.connection().await?;
+        let mut tx = conn
             .begin()
             .await
             .context("failed to start Postgres transaction")?;
 
+        let (email, email_index) = self.seal_email(req.email())?;
         let (user_id, created_at) = self
-            .save_user(&mut tx, req.username(), req.email())
+            .save_user(&mut tx, req.username(), &email, &email_index)
             .await
             .map_err(|e| match is_unique_constraint_violation(&e) {
                 Some(Violation::Email) => CreateUserError::DuplicateEmail {
@@ -67,6 +475,14 @@ impl UserRepository for SqlxUserRepository {
                     ))
                     .into(),
             })?;
+        self.save_terms_acceptances(
+            &mut tx,
+            &[user_id],
+            &[req.accepted_terms().to_string()],
+            created_at,
+        )
+        .await
+        .with_context(|| format!("failed to save terms acceptance of user {user_id}"))?;
 
         tx.commit()
             .await
@@ -79,29 +495,957 @@ impl UserRepository for SqlxUserRepository {
             created_at,
         ))
     }
+
+    async fn create_users(
+        &self,
+        reqs: &[CreateUserRequest],
+    ) -> Result<Vec<CreateUserOutcome>, CreateUsersError> {
+        if reqs.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut conn = self.connection().await?;
+        let mut tx = conn
+            .begin()
+            .await
+            .context("failed to start Postgres transaction")?;
+
+        let (emails, email_indexes): (Vec<String>, Vec<String>) = reqs
+            .iter()
+            .map(|req| self.seal_email(req.email()))
+            .collect::<anyhow::Result<_>>()?;
+        let created_at = Utc::now().trunc_subsecs(6);
+        let (ids, inserted) = self
+            .save_users(&mut tx, reqs, &emails, &email_indexes, created_at)
+            .await
+            .with_context(|| format!("failed to save batch of {} users", reqs.len()))?;
+        let (accepted_by, accepted_terms): (Vec<Uuid>, Vec<String>) = reqs
+            .iter()
+            .zip(&ids)
+            .filter(|(_, id)| inserted.contains(id))
+            .map(|(req, id)| (*id, req.accepted_terms().to_string()))
+            .unzip();
+        self.save_terms_acceptances(&mut tx, &accepted_by, &accepted_terms, created_at)
+            .await
+            .context("failed to save terms acceptances of batch")?;
+        let rejections = if inserted.len() < reqs.len() {
+            self.rejections(&mut tx, reqs, &ids, &inserted, &emails, &email_indexes)
+                .await?
+        } else {
+            vec![None; reqs.len()]
+        };
+
+        tx.commit()
+            .await
+            .context("failed to commit Postgres transaction")?;
+
+        let outcomes = reqs
+            .iter()
+            .zip(ids)
+            .zip(rejections)
+            .map(|((req, id), rejection)| match rejection {
+                None => CreateUserOutcome::Created(User::new(
+                    id,
+                    req.username().clone(),
+                    req.email().clone(),
+                    created_at,
+                )),
+                Some(Violation::Email) => CreateUserOutcome::DuplicateEmail {
+                    email: req.email().clone(),
+                },
+                Some(Violation::Username) => CreateUserOutcome::DuplicateUserName {
+                    username: req.username().clone(),
+                },
+            })
+            .collect();
+
+        Ok(outcomes)
+    }
+
+    fn stream_users(&self) -> impl Stream<Item = Result<User, ListUsersError>> + Send {
+        // The stream holds its connection until it is consumed, so it reads from the pool rather
+        // than blocking the RequestTransaction in scope.
+        let repo = self.clone();
+        sqlx::query_as!(
+            UserRow,
+            r#"SELECT id, username, email, created_at, email_verified FROM users
+            ORDER BY created_at, id"#
+        )
+        .fetch(&self.db_pool)
+        .map(move |row| {
+            let row = row.context("failed to fetch user from Postgres")?;
+            Ok(repo.user_from_row(row)?)
+        })
+    }
+
+    async fn get_user(&self, id: &Uuid) -> Result<User, GetUserError> {
+        let mut conn = self.connection().await?;
+        let row = sqlx::query_as!(
+            UserRow,
+            r#"SELECT id, username, email, created_at, email_verified FROM users
+            WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(&mut *conn)
+        .await
+        .with_context(|| format!("failed to fetch user {id}"))?
+        .ok_or(GetUserError::NotFound { id: *id })?;
+        Ok(self.user_from_row(row)?)
+    }
+
+    async fn erase_user(&self, id: &Uuid) -> Result<User, EraseUserError> {
+        let mut conn = self.connection().await?;
+        let mut tx = conn
+            .begin()
+            .await
+            .context("failed to start Postgres transaction")?;
+
+        let (email, email_index) = self.seal_email(&EmailAddress::erased(id))?;
+        let row = sqlx::query_as!(
+            UserRow,
+            r#"UPDATE users
+            SET username = $2, username_skeleton = NULL, email = $3, email_index = $4,
+                email_verified = false, erased_at = COALESCE(erased_at, $5)
+            WHERE id = $1
+            RETURNING id, username, email, created_at, email_verified"#,
+            id,
+            UserName::erased(id).to_string(),
+            email,
+            email_index,
+            Utc::now(),
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .with_context(|| format!("failed to erase user {id}"))?
+        .ok_or(EraseUserError::NotFound { id: *id })?;
+        sqlx::query!("DELETE FROM email_changes WHERE user_id = $1", id)
+            .execute(&mut *tx)
+            .await
+            .with_context(|| format!("failed to erase email change of user {id}"))?;
+        sqlx::query!("DELETE FROM inbox_notifications WHERE user_id = $1", id)
+            .execute(&mut *tx)
+            .await
+            .with_context(|| format!("failed to erase notifications of user {id}"))?;
+        sqlx::query!("DELETE FROM analytics_events WHERE user_id = $1", id)
+            .execute(&mut *tx)
+            .await
+            .with_context(|| format!("failed to erase analytics events of user {id}"))?;
+
+        tx.commit()
+            .await
+            .context("failed to commit Postgres transaction")?;
+
+        Ok(self.user_from_row(row)?)
+    }
+
+    async fn accept_terms(
+        &self,
+        user_id: &Uuid,
+        version: &TermsVersion,
+    ) -> Result<TermsAcceptance, AcceptTermsError> {
+        let mut conn = self.connection().await?;
+        let mut tx = conn
+            .begin()
+            .await
+            .context("failed to start Postgres transaction")?;
+
+        let accepted_at = Utc::now();
+        self.save_terms_acceptances(&mut tx, &[*user_id], &[version.to_string()], accepted_at)
+            .await
+            .map_err(|e| {
+                if is_foreign_key_violation(&e) {
+                    AcceptTermsError::UserNotFound { id: *user_id }
+                } else {
+                    anyhow::anyhow!(e)
+                        .context(format!("failed to save terms acceptance of user {user_id}"))
+                        .into()
+                }
+            })?;
+
+        tx.commit()
+            .await
+            .context("failed to commit Postgres transaction")?;
+
+        Ok(TermsAcceptance::new(*user_id, version.clone(), accepted_at))
+    }
+
+    async fn list_terms_acceptances(
+        &self,
+        user_id: &Uuid,
+    ) -> Result<Vec<TermsAcceptance>, GetUserError> {
+        let mut conn = self.connection().await?;
+        let rows = sqlx::query!(
+            r#"SELECT terms_version, accepted_at FROM terms_acceptances
+            WHERE user_id = $1
+            ORDER BY accepted_at"#,
+            user_id
+        )
+        .fetch_all(&mut *conn)
+        .await
+        .with_context(|| format!("failed to fetch terms acceptances of user {user_id}"))?;
+        rows.into_iter()
+            .map(|row| {
+                let version = TermsVersion::new(&row.terms_version)
+                    .with_context(|| format!("invalid terms version stored for user {user_id}"))?;
+                Ok(TermsAcceptance::new(*user_id, version, row.accepted_at))
+            })
+            .collect()
+    }
+
+    async fn save_dead_letter(
+        &self,
+        event: &NotificationEvent,
+        failure_reason: &str,
+    ) -> Result<DeadLetter, DeadLetterError> {
+        let id = Uuid::new_v4();
+        let created_at = Utc::now();
+        let mut conn = self.connection().await?;
+        sqlx::query!(
+            r#"INSERT INTO notification_dead_letters
+            (id, event_type, user_id, failure_reason, retry_count, created_at, last_failed_at)
+            VALUES ($1, $2, $3, $4, 0, $5, $5)"#,
+            id,
+            event_type(event),
+            event.user_id(),
+            failure_reason,
+            created_at
+        )
+        .execute(&mut *conn)
+        .await
+        .with_context(|| format!("failed to save dead letter for {:?}", event))?;
+
+        Ok(DeadLetter::new(
+            id,
+            *event,
+            failure_reason.to_string(),
+            0,
+            created_at,
+            created_at,
+        ))
+    }
+
+    async fn list_dead_letters(&self) -> Result<Vec<DeadLetter>, DeadLetterError> {
+        let mut conn = self.connection().await?;
+        let rows = sqlx::query_as!(
+            DeadLetterRow,
+            r#"SELECT id, event_type, user_id, failure_reason, retry_count, created_at, last_failed_at
+            FROM notification_dead_letters
+            ORDER BY created_at"#
+        )
+        .fetch_all(&mut *conn)
+        .await
+        .context("failed to fetch dead letters")?;
+        rows.into_iter()
+            .map(|row| DeadLetter::try_from(row).map_err(DeadLetterError::from))
+            .collect()
+    }
+
+    async fn get_dead_letter(&self, id: &Uuid) -> Result<DeadLetter, DeadLetterError> {
+        let mut conn = self.connection().await?;
+        let row = sqlx::query_as!(
+            DeadLetterRow,
+            r#"SELECT id, event_type, user_id, failure_reason, retry_count, created_at, last_failed_at
+            FROM notification_dead_letters
+            WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(&mut *conn)
+        .await
+        .with_context(|| format!("failed to fetch dead letter {id}"))?
+        .ok_or(DeadLetterError::NotFound { id: *id })?;
+        Ok(row.try_into()?)
+    }
+
+    async fn record_dead_letter_retry(
+        &self,
+        id: &Uuid,
+        failure_reason: &str,
+    ) -> Result<DeadLetter, DeadLetterError> {
+        let mut conn = self.connection().await?;
+        let row = sqlx::query_as!(
+            DeadLetterRow,
+            r#"UPDATE notification_dead_letters
+            SET failure_reason = $2, retry_count = retry_count + 1, last_failed_at = $3
+            WHERE id = $1
+            RETURNING id, event_type, user_id, failure_reason, retry_count, created_at, last_failed_at"#,
+            id,
+            failure_reason,
+            Utc::now()
+        )
+        .fetch_optional(&mut *conn)
+        .await
+        .with_context(|| format!("failed to record retry of dead letter {id}"))?
+        .ok_or(DeadLetterError::NotFound { id: *id })?;
+        Ok(row.try_into()?)
+    }
+
+    async fn delete_dead_letter(&self, id: &Uuid) -> Result<(), DeadLetterError> {
+        let mut conn = self.connection().await?;
+        let result = sqlx::query!("DELETE FROM notification_dead_letters WHERE id = $1", id)
+            .execute(&mut *conn)
+            .await
+            .with_context(|| format!("failed to delete dead letter {id}"))?;
+        if result.rows_affected() == 0 {
+            return Err(DeadLetterError::NotFound { id: *id });
+        }
+        Ok(())
+    }
+
+    async fn import_user(
+        &self,
+        user: &User,
+        terms_acceptances: &[TermsAcceptance],
+    ) -> Result<(), CreateUserError> {
+        let mut conn = self.connection().await?;
+        let mut tx = conn
+            .begin()
+            .await
+            .context("failed to start Postgres transaction")?;
+
+        let (email, email_index) = self.seal_email(user.email())?;
+        sqlx::query!(
+            r#"INSERT INTO users
+                (id, email, email_index, username, username_skeleton, created_at, email_verified)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)"#,
+            user.id(),
+            email,
+            email_index,
+            user.username().to_string(),
+            self.username_skeleton(user.username()),
+            user.created_at(),
+            user.is_email_verified(),
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| match is_unique_constraint_violation(&e) {
+            Some(Violation::Email) => CreateUserError::DuplicateEmail {
+                email: user.email().clone(),
+            },
+            Some(Violation::Username) => CreateUserError::DuplicateUserName {
+                username: user.username().clone(),
+            },
+            None => anyhow::anyhow!(e)
+                .context(format!("failed to import user {}", user.id()))
+                .into(),
+        })?;
+
+        let ids: Vec<Uuid> = terms_acceptances.iter().map(|_| Uuid::new_v4()).collect();
+        let versions: Vec<String> = terms_acceptances
+            .iter()
+            .map(|acceptance| acceptance.terms_version().to_string())
+            .collect();
+        let accepted_ats: Vec<DateTime<Utc>> = terms_acceptances
+            .iter()
+            .map(|acceptance| *acceptance.accepted_at())
+            .collect();
+        sqlx::query!(
+            r#"INSERT INTO terms_acceptances (id, user_id, terms_version, accepted_at)
+            SELECT id, $2, terms_version, accepted_at
+            FROM UNNEST($1::uuid[], $3::text[], $4::timestamptz[])
+                AS batch(id, terms_version, accepted_at)"#,
+            &ids,
+            user.id(),
+            &versions,
+            &accepted_ats,
+        )
+        .execute(&mut *tx)
+        .await
+        .with_context(|| format!("failed to import terms acceptances of user {}", user.id()))?;
+
+        tx.commit()
+            .await
+            .context("failed to commit Postgres transaction")?;
+
+        Ok(())
+    }
+
+    async fn record_activity(&self, activity: &Activity) -> Result<(), ActivityError> {
+        let user_id = activity.user_id();
+        let terms_version = match activity.kind() {
+            ActivityKind::SignedUp => None,
+            ActivityKind::AcceptedTerms { version } => Some(version.to_string()),
+        };
+        let mut conn = self.connection().await?;
+        sqlx::query!(
+            r#"INSERT INTO user_activities (user_id, activity_type, terms_version, occurred_at)
+            VALUES ($1, $2, $3, $4)"#,
+            user_id,
+            activity_type(activity.kind()),
+            terms_version,
+            activity.occurred_at()
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| {
+            if is_foreign_key_violation(&e) {
+                ActivityError::UserNotFound { id: *user_id }
+            } else {
+                anyhow::anyhow!(e)
+                    .context(format!("failed to record activity of user {user_id}"))
+                    .into()
+            }
+        })?;
+        Ok(())
+    }
+
+    async fn list_activity(
+        &self,
+        user_id: &Uuid,
+        query: &ActivityQuery,
+    ) -> Result<ActivityPage, ActivityError> {
+        let mut conn = self.connection().await?;
+        // One more than requested, to tell whether there is a next page.
+        let rows = sqlx::query_as!(
+            ActivityRow,
+            r#"SELECT seq, user_id, activity_type, terms_version, occurred_at
+            FROM user_activities
+            WHERE user_id = $1 AND ($2::BIGINT IS NULL OR seq < $2)
+            ORDER BY seq DESC
+            LIMIT $3"#,
+            user_id,
+            query.after().map(|cursor| cursor.position()),
+            i64::from(query.limit()) + 1
+        )
+        .fetch_all(&mut *conn)
+        .await
+        .with_context(|| format!("failed to fetch activity of user {user_id}"))?;
+        let next = (rows.len() > query.limit() as usize)
+            .then(|| ActivityCursor::new(rows[query.limit() as usize - 1].seq));
+        let activities = rows
+            .into_iter()
+            .take(query.limit() as usize)
+            .map(Activity::try_from)
+            .collect::<Result<_, _>>()?;
+        Ok(ActivityPage::new(activities, next))
+    }
+
+    async fn save_email_change(
+        &self,
+        change: &PendingEmailChange,
+        token: &EmailChangeToken,
+    ) -> Result<(), EmailChangeError> {
+        let user_id = change.user_id();
+        let mut conn = self.connection().await?;
+        let mut tx = conn
+            .begin()
+            .await
+            .context("failed to start Postgres transaction")?;
+
+        if self
+            .is_email_taken(&mut tx, user_id, change.new_email())
+            .await
+            .context("failed to look up email")?
+        {
+            return Err(EmailChangeError::DuplicateEmail {
+                email: change.new_email().clone(),
+            });
+        }
+        let (new_email, _) = self.seal_email(change.new_email())?;
+        sqlx::query!(
+            r#"INSERT INTO email_changes (user_id, new_email, token_hash, requested_at, expires_at)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (user_id) DO UPDATE
+            SET new_email = $2, token_hash = $3, requested_at = $4, expires_at = $5"#,
+            user_id,
+            new_email,
+            token_hash(token),
+            change.requested_at(),
+            change.expires_at(),
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| {
+            if is_foreign_key_violation(&e) {
+                EmailChangeError::UserNotFound { id: *user_id }
+            } else {
+                anyhow::anyhow!(e)
+                    .context(format!("failed to save email change of user {user_id}"))
+                    .into()
+            }
+        })?;
+
+        tx.commit()
+            .await
+            .context("failed to commit Postgres transaction")?;
+        Ok(())
+    }
+
+    async fn confirm_email_change(
+        &self,
+        user_id: &Uuid,
+        token: &EmailChangeToken,
+    ) -> Result<User, EmailChangeError> {
+        let mut conn = self.connection().await?;
+        let mut tx = conn
+            .begin()
+            .await
+            .context("failed to start Postgres transaction")?;
+
+        let change = sqlx::query!(
+            r#"SELECT new_email, token_hash, expires_at FROM email_changes
+            WHERE user_id = $1
+            FOR UPDATE"#,
+            user_id
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .with_context(|| format!("failed to fetch email change of user {user_id}"))?
+        .ok_or(EmailChangeError::NotPending { user_id: *user_id })?;
+        if change.token_hash != token_hash(token) {
+            return Err(EmailChangeError::InvalidToken);
+        }
+        if change.expires_at <= Utc::now() {
+            sqlx::query!("DELETE FROM email_changes WHERE user_id = $1", user_id)
+                .execute(&mut *tx)
+                .await
+                .with_context(|| format!("failed to delete email change of user {user_id}"))?;
+            tx.commit()
+                .await
+                .context("failed to commit Postgres transaction")?;
+            return Err(EmailChangeError::Expired { user_id: *user_id });
+        }
+
+        let new_email = self
+            .open_email(&change.new_email)
+            .with_context(|| format!("failed to decrypt new email of user {user_id}"))?;
+        let new_email = EmailAddress::from_persisted(&new_email)
+            .with_context(|| format!("invalid new email stored for user {user_id}"))?;
+        if self
+            .is_email_taken(&mut tx, user_id, &new_email)
+            .await
+            .context("failed to look up email")?
+        {
+            return Err(EmailChangeError::DuplicateEmail { email: new_email });
+        }
+        let (email, email_index) = self.seal_email(&new_email)?;
+        let row = sqlx::query_as!(
+            UserRow,
+            r#"UPDATE users SET email = $2, email_index = $3, email_verified = true
+            WHERE id = $1
+            RETURNING id, username, email, created_at, email_verified"#,
+            user_id,
+            email,
+            email_index,
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| {
+            if let Some(Violation::Email) = is_unique_constraint_violation(&e) {
+                EmailChangeError::DuplicateEmail {
+                    email: new_email.clone(),
+                }
+            } else {
+                anyhow::anyhow!(e)
+                    .context(format!("failed to change email of user {user_id}"))
+                    .into()
+            }
+        })?;
+        sqlx::query!("DELETE FROM email_changes WHERE user_id = $1", user_id)
+            .execute(&mut *tx)
+            .await
+            .with_context(|| format!("failed to delete email change of user {user_id}"))?;
+
+        tx.commit()
+            .await
+            .context("failed to commit Postgres transaction")?;
+
+        Ok(self.user_from_row(row)?)
+    }
+
+    fn stream_email_changes(
+        &self,
+    ) -> impl Stream<Item = Result<(PendingEmailChange, EmailChangeTokenHash), EmailChangeError>> + Send
+    {
+        let repo = self.clone();
+        sqlx::query!(
+            r#"SELECT user_id, new_email, token_hash, requested_at, expires_at FROM email_changes
+            ORDER BY requested_at, user_id"#
+        )
+        .fetch(&self.db_pool)
+        .map(move |row| {
+            let row = row.context("failed to fetch email change from Postgres")?;
+            let user_id = row.user_id;
+            let new_email = repo
+                .open_email(&row.new_email)
+                .with_context(|| format!("failed to decrypt new email of user {user_id}"))?;
+            let new_email = EmailAddress::from_persisted(&new_email)
+                .with_context(|| format!("invalid new email stored for user {user_id}"))?;
+            Ok((
+                PendingEmailChange::new(user_id, new_email, row.requested_at, row.expires_at),
+                EmailChangeTokenHash::new(&row.token_hash),
+            ))
+        })
+    }
+
+    async fn import_email_change(
+        &self,
+        change: &PendingEmailChange,
+        token_hash: &EmailChangeTokenHash,
+    ) -> Result<(), EmailChangeError> {
+        let user_id = change.user_id();
+        let (new_email, _) = self.seal_email(change.new_email())?;
+        let mut conn = self.connection().await?;
+        sqlx::query!(
+            r#"INSERT INTO email_changes (user_id, new_email, token_hash, requested_at, expires_at)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (user_id) DO UPDATE
+            SET new_email = $2, token_hash = $3, requested_at = $4, expires_at = $5"#,
+            user_id,
+            new_email,
+            token_hash.as_str(),
+            change.requested_at(),
+            change.expires_at(),
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| {
+            if is_foreign_key_violation(&e) {
+                EmailChangeError::UserNotFound { id: *user_id }
+            } else {
+                anyhow::anyhow!(e)
+                    .context(format!("failed to import email change of user {user_id}"))
+                    .into()
+            }
+        })?;
+        Ok(())
+    }
+
+    async fn delete_email_change(&self, user_id: &Uuid) -> Result<(), EmailChangeError> {
+        let mut conn = self.connection().await?;
+        let result = sqlx::query!("DELETE FROM email_changes WHERE user_id = $1", user_id)
+            .execute(&mut *conn)
+            .await
+            .with_context(|| format!("failed to delete email change of user {user_id}"))?;
+        if result.rows_affected() == 0 {
+            return Err(EmailChangeError::NotPending { user_id: *user_id });
+        }
+        Ok(())
+    }
+
+    async fn delete_expired_email_changes(
+        &self,
+        now: &DateTime<Utc>,
+    ) -> Result<u64, EmailChangeError> {
+        let mut conn = self.connection().await?;
+        let result = sqlx::query!("DELETE FROM email_changes WHERE expires_at <= $1", now)
+            .execute(&mut *conn)
+            .await
+            .context("failed to delete expired email changes")?;
+        Ok(result.rows_affected())
+    }
+
+    async fn save_notification(&self, notification: &InboxNotification) -> Result<(), InboxError> {
+        let user_id = notification.user_id();
+        let mut conn = self.connection().await?;
+        sqlx::query!(
+            "INSERT INTO inbox_notifications (id, user_id, kind, created_at, read_at)
+            VALUES ($1, $2, $3, $4, $5)",
+            notification.id(),
+            user_id,
+            notification.kind().as_str(),
+            notification.created_at(),
+            notification.read_at(),
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| {
+            if is_foreign_key_violation(&e) {
+                InboxError::UserNotFound { id: *user_id }
+            } else {
+                anyhow::anyhow!(e)
+                    .context(format!("failed to save notification of user {user_id}"))
+                    .into()
+            }
+        })?;
+        Ok(())
+    }
+
+    async fn list_notifications(
+        &self,
+        user_id: &Uuid,
+        query: &InboxQuery,
+    ) -> Result<Vec<InboxNotification>, InboxError> {
+        let mut conn = self.connection().await?;
+        let rows = sqlx::query_as!(
+            InboxNotificationRow,
+            r#"SELECT id, user_id, kind, created_at, read_at
+            FROM inbox_notifications
+            WHERE user_id = $1 AND (NOT $2 OR read_at IS NULL)
+            ORDER BY created_at DESC, id
+            LIMIT $3"#,
+            user_id,
+            query.unread_only(),
+            i64::from(query.limit())
+        )
+        .fetch_all(&mut *conn)
+        .await
+        .with_context(|| format!("failed to fetch notifications of user {user_id}"))?;
+        Ok(rows
+            .into_iter()
+            .map(InboxNotification::try_from)
+            .collect::<anyhow::Result<_>>()?)
+    }
+
+    fn stream_notifications(
+        &self,
+    ) -> impl Stream<Item = Result<InboxNotification, InboxError>> + Send {
+        sqlx::query_as!(
+            InboxNotificationRow,
+            r#"SELECT id, user_id, kind, created_at, read_at FROM inbox_notifications
+            ORDER BY created_at, id"#
+        )
+        .fetch(&self.db_pool)
+        .map(|row| {
+            let row = row.context("failed to fetch notification from Postgres")?;
+            Ok(InboxNotification::try_from(row)?)
+        })
+    }
+
+    async fn count_unread_notifications(&self, user_id: &Uuid) -> Result<u64, InboxError> {
+        let mut conn = self.connection().await?;
+        let count = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) AS "count!" FROM inbox_notifications
+            WHERE user_id = $1 AND read_at IS NULL"#,
+            user_id
+        )
+        .fetch_one(&mut *conn)
+        .await
+        .with_context(|| format!("failed to count unread notifications of user {user_id}"))?;
+        Ok(u64::try_from(count).context("negative count of unread notifications")?)
+    }
+
+    async fn mark_notifications_read(
+        &self,
+        user_id: &Uuid,
+        notification_id: Option<&Uuid>,
+        read_at: &DateTime<Utc>,
+    ) -> Result<u64, InboxError> {
+        let mut conn = self.connection().await?;
+        let Some(notification_id) = notification_id else {
+            let result = sqlx::query!(
+                "UPDATE inbox_notifications SET read_at = $2
+                WHERE user_id = $1 AND read_at IS NULL",
+                user_id,
+                read_at
+            )
+            .execute(&mut *conn)
+            .await
+            .with_context(|| format!("failed to mark notifications of user {user_id} read"))?;
+            return Ok(result.rows_affected());
+        };
+        let was_unread = sqlx::query_scalar!(
+            r#"UPDATE inbox_notifications AS updated SET read_at = COALESCE(updated.read_at, $3)
+            FROM inbox_notifications AS previous
+            WHERE updated.id = $1 AND updated.user_id = $2 AND previous.id = updated.id
+            RETURNING previous.read_at IS NULL AS "was_unread!""#,
+            notification_id,
+            user_id,
+            read_at
+        )
+        .fetch_optional(&mut *conn)
+        .await
+        .with_context(|| format!("failed to mark notification {notification_id} read"))?
+        .ok_or(InboxError::NotFound {
+            id: *notification_id,
+        })?;
+        Ok(u64::from(was_unread))
+    }
+
+    async fn delete_notifications_before(&self, cutoff: &DateTime<Utc>) -> Result<u64, InboxError> {
+        let mut conn = self.connection().await?;
+        let result = sqlx::query!(
+            "DELETE FROM inbox_notifications WHERE created_at < $1",
+            cutoff
+        )
+        .execute(&mut *conn)
+        .await
+        .context("failed to delete old notifications")?;
+        Ok(result.rows_affected())
+    }
+
+    async fn search_users(&self, query: &UserSearchQuery) -> Result<Vec<User>, SearchError> {
+        let mut conn = self.connection().await?;
+        let text = escape_like(query.text());
+        // Usernames starting with the text come first, then the shorter ones.
+        let rows = sqlx::query_as!(
+            UserRow,
+            r#"SELECT id, username, email, created_at, email_verified FROM users
+            WHERE erased_at IS NULL AND username ILIKE $1
+            ORDER BY username NOT ILIKE $2, length(username), username
+            LIMIT $3"#,
+            format!("%{text}%"),
+            format!("{text}%"),
+            i64::from(query.limit())
+        )
+        .fetch_all(&mut *conn)
+        .await
+        .context("failed to search users")?;
+        Ok(rows
+            .into_iter()
+            .map(|row| self.user_from_row(row))
+            .collect::<anyhow::Result<_>>()?)
+    }
+}
+
+/// Escapes the wildcards of a `LIKE` pattern in `text`, so that it only matches itself.
+fn escape_like(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+fn activity_type(kind: &ActivityKind) -> &'static str {
+    match kind {
+        ActivityKind::SignedUp => "signed_up",
+        ActivityKind::AcceptedTerms { .. } => "accepted_terms",
+    }
+}
+
+/// A row of the `user_activities` table.
+struct ActivityRow {
+    seq: i64,
+    user_id: Uuid,
+    activity_type: String,
+    terms_version: Option<String>,
+    occurred_at: DateTime<Utc>,
+}
+
+impl TryFrom<ActivityRow> for Activity {
+    type Error = anyhow::Error;
+
+    fn try_from(row: ActivityRow) -> Result<Self, Self::Error> {
+        let kind = match (row.activity_type.as_str(), row.terms_version) {
+            ("signed_up", _) => ActivityKind::SignedUp,
+            ("accepted_terms", Some(version)) => ActivityKind::AcceptedTerms {
+                version: TermsVersion::new(&version).with_context(|| {
+                    format!("invalid terms version stored for activity {}", row.seq)
+                })?,
+            },
+            (other, _) => anyhow::bail!(
+                "unknown or incomplete activity type '{other}' stored for activity {}",
+                row.seq
+            ),
+        };
+        Ok(Activity::new(row.user_id, kind, row.occurred_at))
+    }
+}
+
+fn event_type(event: &NotificationEvent) -> &'static str {
+    match event {
+        NotificationEvent::UserCreated { .. } => "user_created",
+    }
+}
+
+/// A row of the `notification_dead_letters` table.
+struct DeadLetterRow {
+    id: Uuid,
+    event_type: String,
+    user_id: Uuid,
+    failure_reason: String,
+    retry_count: i32,
+    created_at: DateTime<Utc>,
+    last_failed_at: DateTime<Utc>,
+}
+
+impl TryFrom<DeadLetterRow> for DeadLetter {
+    type Error = anyhow::Error;
+
+    fn try_from(row: DeadLetterRow) -> Result<Self, Self::Error> {
+        let event = match row.event_type.as_str() {
+            "user_created" => NotificationEvent::UserCreated {
+                user_id: row.user_id,
+            },
+            other => anyhow::bail!(
+                "unknown event type '{other}' stored for dead letter {}",
+                row.id
+            ),
+        };
+        let retry_count = u32::try_from(row.retry_count)
+            .with_context(|| format!("invalid retry count stored for dead letter {}", row.id))?;
+        Ok(DeadLetter::new(
+            row.id,
+            event,
+            row.failure_reason,
+            retry_count,
+            row.created_at,
+            row.last_failed_at,
+        ))
+    }
+}
+
+/// A row of the `inbox_notifications` table.
+struct InboxNotificationRow {
+    id: Uuid,
+    user_id: Uuid,
+    kind: String,
+    created_at: DateTime<Utc>,
+    read_at: Option<DateTime<Utc>>,
+}
+
+impl TryFrom<InboxNotificationRow> for InboxNotification {
+    type Error = anyhow::Error;
+
+    fn try_from(row: InboxNotificationRow) -> Result<Self, Self::Error> {
+        let kind = InboxKind::parse(&row.kind).with_context(|| {
+            format!(
+                "unknown kind '{}' stored for notification {}",
+                row.kind, row.id
+            )
+        })?;
+        Ok(InboxNotification::new(
+            row.id,
+            row.user_id,
+            kind,
+            row.created_at,
+            row.read_at,
+        ))
+    }
+}
+
+/// A row of the `users` table.
+struct UserRow {
+    id: Uuid,
+    username: String,
+    email: String,
+    created_at: DateTime<Utc>,
+    email_verified: bool,
 }
 
 const UNIQUE_CONSTRAINT_VIOLATION_CODE: &str = "23505";
+const FOREIGN_KEY_VIOLATION_CODE: &str = "23503";
+
+/// The unique constraints of the `users` table on emails, as stored and as indexed.
+const EMAIL_CONSTRAINTS: &[&str] = &["users_email_key", "users_email_index_key"];
+/// The unique constraints of the `users` table on usernames, as given and as skeletons.
+const USERNAME_CONSTRAINTS: &[&str] = &["users_username_key", "users_username_skeleton_key"];
+
+/// Which unique constraint of the `users` table a user violates.
 #[derive(Debug, Clone, Copy)]
 enum Violation {
     Email,
     Username,
 }
 
-#[allow(clippy::collapsible_if)]
+/// The unique constraint of the `users` table `err` is the violation of, if any.
 fn is_unique_constraint_violation(err: &sqlx::Error) -> Option<Violation> {
-    if let sqlx::Error::Database(db_err) = err {
-        if let Some(code) = db_err.code() {
-            dbg!(&code);
-            if code == UNIQUE_CONSTRAINT_VIOLATION_CODE {
-                dbg!(db_err);
-                if db_err.message().contains("email") {
-                    return Some(Violation::Email);
-                }
-                return Some(Violation::Username);
-            }
-        }
+    let sqlx::Error::Database(db_err) = err else {
+        return None;
+    };
+    if db_err.code().as_deref() != Some(UNIQUE_CONSTRAINT_VIOLATION_CODE) {
+        return None;
     }
+    match db_err.constraint() {
+        Some(constraint) if EMAIL_CONSTRAINTS.contains(&constraint) => Some(Violation::Email),
+        Some(constraint) if USERNAME_CONSTRAINTS.contains(&constraint) => Some(Violation::Username),
+        _ => None,
+    }
+}
+
+/// The form email change tokens are stored in, from which they can't be recovered.
+fn token_hash(token: &EmailChangeToken) -> String {
+    hex::encode(Sha256::digest(token.as_str().as_bytes()))
+}
 
-    None
+fn is_foreign_key_violation(err: &sqlx::Error) -> bool {
+    matches!(err, sqlx::Error::Database(db_err) if db_err.code().as_deref() == Some(FOREIGN_KEY_VIOLATION_CODE))
 }
@@ -0,0 +1,357 @@
+use std::{
+    fmt::Debug,
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Utc};
+use futures::Stream;
+use sha2::{Digest, Sha256};
+
+use crate::domain::crowdsrc::{
+    models::activity::{Activity, ActivityError, ActivityPage, ActivityQuery},
+    models::dead_letter::{DeadLetter, DeadLetterError, NotificationEvent},
+    models::email_change::{
+        EmailChangeError, EmailChangeToken, EmailChangeTokenHash, PendingEmailChange,
+    },
+    models::inbox::{InboxError, InboxNotification, InboxQuery},
+    models::search::{SearchError, UserSearchQuery},
+    models::terms::{AcceptTermsError, TermsAcceptance, TermsVersion},
+    models::user::{
+        CreateUserError, CreateUserOutcome, CreateUserRequest, CreateUsersError, EraseUserError,
+        GetUserError, ListUsersError, User,
+    },
+    ports::UserRepository,
+};
+
+/// A [UserRepository] decorator that warns about calls taking longer than `threshold`, within
+/// the span of the request that made them.
+///
+/// The warning names the call and a digest of its parameters, so that repeated slow calls with
+/// the same parameters can be told apart without logging personal data. Streams are not timed,
+/// since how long they take depends on their consumer.
+#[derive(Debug, Clone)]
+pub struct TimedRepository<R>
+where
+    R: UserRepository,
+{
+    inner: R,
+    threshold: Duration,
+}
+
+impl<R> TimedRepository<R>
+where
+    R: UserRepository,
+{
+    pub fn new(inner: R, threshold: Duration) -> Self {
+        Self { inner, threshold }
+    }
+
+    async fn timed<T>(
+        &self,
+        query: &'static str,
+        params: impl Debug,
+        call: impl Future<Output = T>,
+    ) -> T {
+        let started_at = Instant::now();
+        let result = call.await;
+        let elapsed = started_at.elapsed();
+        if elapsed > self.threshold {
+            tracing::warn!(
+                query,
+                params_digest = params_digest(&params),
+                elapsed_ms = elapsed.as_millis() as u64,
+                threshold_ms = self.threshold.as_millis() as u64,
+                "slow repository call"
+            );
+        }
+        result
+    }
+}
+
+/// A short digest of the [Debug] representation of `params`, equal for equal parameters.
+fn params_digest(params: &impl Debug) -> String {
+    let digest = Sha256::digest(format!("{params:?}").as_bytes());
+    hex::encode(&digest[..8])
+}
+
+impl<R> UserRepository for TimedRepository<R>
+where
+    R: UserRepository,
+{
+    async fn create_user(&self, req: &CreateUserRequest) -> Result<User, CreateUserError> {
+        self.timed("create_user", req, self.inner.create_user(req))
+            .await
+    }
+
+    async fn create_users(
+        &self,
+        reqs: &[CreateUserRequest],
+    ) -> Result<Vec<CreateUserOutcome>, CreateUsersError> {
+        self.timed("create_users", reqs, self.inner.create_users(reqs))
+            .await
+    }
+
+    fn stream_users(&self) -> impl Stream<Item = Result<User, ListUsersError>> + Send {
+        self.inner.stream_users()
+    }
+
+    async fn get_user(&self, id: &uuid::Uuid) -> Result<User, GetUserError> {
+        self.timed("get_user", id, self.inner.get_user(id)).await
+    }
+
+    async fn erase_user(&self, id: &uuid::Uuid) -> Result<User, EraseUserError> {
+        self.timed("erase_user", id, self.inner.erase_user(id))
+            .await
+    }
+
+    async fn accept_terms(
+        &self,
+        user_id: &uuid::Uuid,
+        version: &TermsVersion,
+    ) -> Result<TermsAcceptance, AcceptTermsError> {
+        self.timed(
+            "accept_terms",
+            (user_id, version),
+            self.inner.accept_terms(user_id, version),
+        )
+        .await
+    }
+
+    async fn list_terms_acceptances(
+        &self,
+        user_id: &uuid::Uuid,
+    ) -> Result<Vec<TermsAcceptance>, GetUserError> {
+        self.timed(
+            "list_terms_acceptances",
+            user_id,
+            self.inner.list_terms_acceptances(user_id),
+        )
+        .await
+    }
+
+    async fn save_dead_letter(
+        &self,
+        event: &NotificationEvent,
+        failure_reason: &str,
+    ) -> Result<DeadLetter, DeadLetterError> {
+        self.timed(
+            "save_dead_letter",
+            (event, failure_reason),
+            self.inner.save_dead_letter(event, failure_reason),
+        )
+        .await
+    }
+
+    async fn list_dead_letters(&self) -> Result<Vec<DeadLetter>, DeadLetterError> {
+        self.timed("list_dead_letters", (), self.inner.list_dead_letters())
+            .await
+    }
+
+    async fn get_dead_letter(&self, id: &uuid::Uuid) -> Result<DeadLetter, DeadLetterError> {
+        self.timed("get_dead_letter", id, self.inner.get_dead_letter(id))
+            .await
+    }
+
+    async fn record_dead_letter_retry(
+        &self,
+        id: &uuid::Uuid,
+        failure_reason: &str,
+    ) -> Result<DeadLetter, DeadLetterError> {
+        self.timed(
+            "record_dead_letter_retry",
+            (id, failure_reason),
+            self.inner.record_dead_letter_retry(id, failure_reason),
+        )
+        .await
+    }
+
+    async fn delete_dead_letter(&self, id: &uuid::Uuid) -> Result<(), DeadLetterError> {
+        self.timed("delete_dead_letter", id, self.inner.delete_dead_letter(id))
+            .await
+    }
+
+    async fn import_user(
+        &self,
+        user: &User,
+        terms_acceptances: &[TermsAcceptance],
+    ) -> Result<(), CreateUserError> {
+        self.timed(
+            "import_user",
+            (user, terms_acceptances),
+            self.inner.import_user(user, terms_acceptances),
+        )
+        .await
+    }
+
+    async fn record_activity(&self, activity: &Activity) -> Result<(), ActivityError> {
+        self.timed(
+            "record_activity",
+            activity,
+            self.inner.record_activity(activity),
+        )
+        .await
+    }
+
+    async fn list_activity(
+        &self,
+        user_id: &uuid::Uuid,
+        query: &ActivityQuery,
+    ) -> Result<ActivityPage, ActivityError> {
+        self.timed(
+            "list_activity",
+            (user_id, query),
+            self.inner.list_activity(user_id, query),
+        )
+        .await
+    }
+
+    async fn save_email_change(
+        &self,
+        change: &PendingEmailChange,
+        token: &EmailChangeToken,
+    ) -> Result<(), EmailChangeError> {
+        self.timed(
+            "save_email_change",
+            change,
+            self.inner.save_email_change(change, token),
+        )
+        .await
+    }
+
+    async fn confirm_email_change(
+        &self,
+        user_id: &uuid::Uuid,
+        token: &EmailChangeToken,
+    ) -> Result<User, EmailChangeError> {
+        self.timed(
+            "confirm_email_change",
+            user_id,
+            self.inner.confirm_email_change(user_id, token),
+        )
+        .await
+    }
+
+    fn stream_email_changes(
+        &self,
+    ) -> impl Stream<Item = Result<(PendingEmailChange, EmailChangeTokenHash), EmailChangeError>> + Send
+    {
+        self.inner.stream_email_changes()
+    }
+
+    async fn import_email_change(
+        &self,
+        change: &PendingEmailChange,
+        token_hash: &EmailChangeTokenHash,
+    ) -> Result<(), EmailChangeError> {
+        self.timed(
+            "import_email_change",
+            change,
+            self.inner.import_email_change(change, token_hash),
+        )
+        .await
+    }
+
+    async fn delete_email_change(&self, user_id: &uuid::Uuid) -> Result<(), EmailChangeError> {
+        self.timed(
+            "delete_email_change",
+            user_id,
+            self.inner.delete_email_change(user_id),
+        )
+        .await
+    }
+
+    async fn delete_expired_email_changes(
+        &self,
+        now: &DateTime<Utc>,
+    ) -> Result<u64, EmailChangeError> {
+        self.timed(
+            "delete_expired_email_changes",
+            now,
+            self.inner.delete_expired_email_changes(now),
+        )
+        .await
+    }
+
+    async fn save_notification(&self, notification: &InboxNotification) -> Result<(), InboxError> {
+        self.timed(
+            "save_notification",
+            (notification.user_id(), notification.kind()),
+            self.inner.save_notification(notification),
+        )
+        .await
+    }
+
+    async fn list_notifications(
+        &self,
+        user_id: &uuid::Uuid,
+        query: &InboxQuery,
+    ) -> Result<Vec<InboxNotification>, InboxError> {
+        self.timed(
+            "list_notifications",
+            (user_id, query),
+            self.inner.list_notifications(user_id, query),
+        )
+        .await
+    }
+
+    fn stream_notifications(
+        &self,
+    ) -> impl Stream<Item = Result<InboxNotification, InboxError>> + Send {
+        self.inner.stream_notifications()
+    }
+
+    async fn count_unread_notifications(&self, user_id: &uuid::Uuid) -> Result<u64, InboxError> {
+        self.timed(
+            "count_unread_notifications",
+            user_id,
+            self.inner.count_unread_notifications(user_id),
+        )
+        .await
+    }
+
+    async fn mark_notifications_read(
+        &self,
+        user_id: &uuid::Uuid,
+        notification_id: Option<&uuid::Uuid>,
+        read_at: &DateTime<Utc>,
+    ) -> Result<u64, InboxError> {
+        self.timed(
+            "mark_notifications_read",
+            (user_id, notification_id),
+            self.inner
+                .mark_notifications_read(user_id, notification_id, read_at),
+        )
+        .await
+    }
+
+    async fn delete_notifications_before(&self, cutoff: &DateTime<Utc>) -> Result<u64, InboxError> {
+        self.timed(
+            "delete_notifications_before",
+            cutoff,
+            self.inner.delete_notifications_before(cutoff),
+        )
+        .await
+    }
+
+    async fn search_users(&self, query: &UserSearchQuery) -> Result<Vec<User>, SearchError> {
+        self.timed("search_users", query, self.inner.search_users(query))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn params_digest_is_stable_and_hides_params() {
+        let email = "kristoffer@example.com";
+
+        let digest = params_digest(&email);
+
+        assert_eq!(digest, params_digest(&email));
+        assert_ne!(digest, params_digest(&"someone@example.com"));
+        assert_eq!(digest.len(), 16);
+        assert!(!digest.contains("kristoffer"));
+    }
+}
@@ -0,0 +1,352 @@
+//! Module `scheduler` runs jobs, such as purging expired data, whenever their
+//! [CronExpression] matches.
+//!
+//! The [Schedule]s are kept in a [ScheduleStore] shared by all instances, which lets one
+//! instance only claim each run, so jobs run once however many replicas are up. Jobs run while
+//! holding a [DistributedLock], so that a job outlasting the interval between its runs is not
+//! run again, on any instance, before it completes.
+
+use std::{pin::Pin, sync::Arc, time::Duration};
+
+use chrono::{DateTime, Utc};
+
+use crate::domain::crowdsrc::{
+    models::schedule::{CronExpression, Schedule, ScheduleError},
+    ports::{DistributedLock, ScheduleStore},
+};
+
+type JobFuture = Pin<Box<dyn Future<Output = anyhow::Result<()>> + Send>>;
+
+/// A registered job.
+#[derive(Clone)]
+struct Job {
+    name: String,
+    run: Arc<dyn Fn() -> JobFuture + Send + Sync>,
+}
+
+/// Runs registered jobs when they are due, checking every `tick`.
+#[derive(Clone)]
+pub struct Scheduler<S: ScheduleStore, L: DistributedLock> {
+    store: S,
+    lock: L,
+    tick: Duration,
+    jobs: Vec<Job>,
+}
+
+impl<S: ScheduleStore, L: DistributedLock> Scheduler<S, L> {
+    pub fn new(store: S, lock: L, tick: Duration) -> Self {
+        Self {
+            store,
+            lock,
+            tick,
+            jobs: Vec::new(),
+        }
+    }
+
+    /// Registers `job` to run whenever `cron` matches, under `name`, returning its [Schedule].
+    ///
+    /// Re-registering a job with an unchanged `cron`, such as on restart, keeps its next run.
+    ///
+    /// # Errors
+    ///
+    /// - Propagates any [ScheduleError] returned by the [ScheduleStore].
+    pub async fn register<F, Fut>(
+        &mut self,
+        name: &str,
+        cron: &CronExpression,
+        job: F,
+    ) -> Result<Schedule, ScheduleError>
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = anyhow::Result<()>> + Send + 'static,
+    {
+        let schedule = self.store.register(name, cron, Utc::now()).await?;
+        tracing::info!(
+            job = name,
+            cron = %cron,
+            next_run_at = %schedule.next_run_at(),
+            "registered scheduled job"
+        );
+        self.jobs.retain(|registered| registered.name != name);
+        self.jobs.push(Job {
+            name: name.to_string(),
+            run: Arc::new(move || Box::pin(job())),
+        });
+        Ok(schedule)
+    }
+
+    /// Runs, concurrently, the jobs due at `now` whose runs this instance claims, returning the
+    /// names of those that ran.
+    ///
+    /// A job still running, on any instance, is skipped until it completes. A failing job is
+    /// logged, and retried at its next run.
+    pub async fn run_due(&self, now: DateTime<Utc>) -> Vec<String> {
+        let runs = self.jobs.iter().map(|job| async move {
+            let run = async {
+                match self.store.claim(&job.name, now).await {
+                    Ok(true) => {}
+                    Ok(false) => return false,
+                    Err(e) => {
+                        tracing::warn!(job = job.name, "failed to claim scheduled job: {e:#}");
+                        return false;
+                    }
+                }
+                match (job.run)().await {
+                    Ok(()) => tracing::info!(job = job.name, "ran scheduled job"),
+                    Err(e) => tracing::error!(job = job.name, "scheduled job failed: {e:#}"),
+                }
+                true
+            };
+            match self.lock.try_with_lock(&lock_name(&job.name), run).await {
+                Ok(Some(true)) => Some(job.name.clone()),
+                Ok(Some(false) | None) => None,
+                Err(e) => {
+                    tracing::warn!(job = job.name, "failed to lock scheduled job: {e:#}");
+                    None
+                }
+            }
+        });
+        futures::future::join_all(runs)
+            .await
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+
+    /// Runs the jobs whenever they are due, forever.
+    pub async fn run(self) {
+        let mut interval = tokio::time::interval(self.tick);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            self.run_due(Utc::now()).await;
+        }
+    }
+}
+
+/// The name of the [DistributedLock] held while the job named `name` runs.
+fn lock_name(name: &str) -> String {
+    format!("scheduler:{name}")
+}
+
+impl<S: ScheduleStore, L: DistributedLock> std::fmt::Debug for Scheduler<S, L> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Scheduler")
+            .field("tick", &self.tick)
+            .field(
+                "jobs",
+                &self.jobs.iter().map(|job| &job.name).collect::<Vec<_>>(),
+            )
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::{HashMap, HashSet},
+        sync::{
+            Mutex,
+            atomic::{AtomicU32, Ordering},
+        },
+    };
+
+    use anyhow::anyhow;
+
+    use crate::domain::crowdsrc::models::lock::LockError;
+
+    use super::*;
+
+    /// A [ScheduleStore] keeping the [Schedule]s in memory.
+    #[derive(Clone, Default)]
+    struct InMemoryScheduleStore {
+        schedules: Arc<Mutex<HashMap<String, Schedule>>>,
+    }
+
+    impl ScheduleStore for InMemoryScheduleStore {
+        async fn register(
+            &self,
+            name: &str,
+            cron: &CronExpression,
+            now: DateTime<Utc>,
+        ) -> Result<Schedule, ScheduleError> {
+            let mut schedules = self.schedules.lock().unwrap();
+            let schedule = schedules
+                .entry(name.to_string())
+                .and_modify(|schedule| {
+                    if schedule.cron() != cron {
+                        *schedule =
+                            Schedule::new(name, cron.clone(), cron.next_after(now).unwrap(), None);
+                    }
+                })
+                .or_insert_with(|| {
+                    Schedule::new(name, cron.clone(), cron.next_after(now).unwrap(), None)
+                });
+            Ok(schedule.clone())
+        }
+
+        async fn claim(&self, name: &str, now: DateTime<Utc>) -> Result<bool, ScheduleError> {
+            let mut schedules = self.schedules.lock().unwrap();
+            let schedule = schedules.get_mut(name).ok_or(ScheduleError::NotFound {
+                name: name.to_string(),
+            })?;
+            if *schedule.next_run_at() > now {
+                return Ok(false);
+            }
+            *schedule = Schedule::new(
+                name,
+                schedule.cron().clone(),
+                schedule.cron().next_after(now).unwrap(),
+                Some(now),
+            );
+            Ok(true)
+        }
+
+        async fn list(&self) -> Result<Vec<Schedule>, ScheduleError> {
+            Ok(self.schedules.lock().unwrap().values().cloned().collect())
+        }
+    }
+
+    /// A [DistributedLock] over locks held in memory.
+    #[derive(Clone, Default)]
+    struct InMemoryLock {
+        held: Arc<Mutex<HashSet<String>>>,
+    }
+
+    impl DistributedLock for InMemoryLock {
+        async fn with_lock<T, W>(&self, _name: &str, _work: W) -> Result<T, LockError>
+        where
+            T: Send,
+            W: Future<Output = T> + Send,
+        {
+            unimplemented!()
+        }
+
+        async fn try_with_lock<T, W>(&self, name: &str, work: W) -> Result<Option<T>, LockError>
+        where
+            T: Send,
+            W: Future<Output = T> + Send,
+        {
+            if !self.held.lock().unwrap().insert(name.to_string()) {
+                return Ok(None);
+            }
+            let output = work.await;
+            self.held.lock().unwrap().remove(name);
+            Ok(Some(output))
+        }
+    }
+
+    fn counting_job(runs: &Arc<AtomicU32>) -> impl Fn() -> JobFuture + Send + Sync + 'static {
+        let runs = runs.clone();
+        move || {
+            let runs = runs.clone();
+            Box::pin(async move {
+                runs.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_due_runs_each_due_job_once() {
+        let store = InMemoryScheduleStore::default();
+        let mut scheduler = Scheduler::new(store, InMemoryLock::default(), Duration::from_secs(1));
+        let runs = Arc::new(AtomicU32::new(0));
+        let schedule = scheduler
+            .register(
+                "every_minute",
+                &CronExpression::parse("* * * * *").unwrap(),
+                counting_job(&runs),
+            )
+            .await
+            .unwrap();
+        let due = *schedule.next_run_at();
+
+        let early = scheduler.run_due(due - chrono::TimeDelta::seconds(1)).await;
+        let on_time = scheduler.run_due(due).await;
+        let again = scheduler.run_due(due).await;
+
+        assert!(early.is_empty());
+        assert_eq!(on_time, vec!["every_minute".to_string()]);
+        assert!(again.is_empty());
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_instances_sharing_a_store_run_job_once() {
+        let store = InMemoryScheduleStore::default();
+        let cron = CronExpression::parse("0 * * * *").unwrap();
+        let lock = InMemoryLock::default();
+        let runs = Arc::new(AtomicU32::new(0));
+        let mut first = Scheduler::new(store.clone(), lock.clone(), Duration::from_secs(1));
+        let mut second = Scheduler::new(store.clone(), lock.clone(), Duration::from_secs(1));
+        let schedule = first
+            .register("hourly", &cron, counting_job(&runs))
+            .await
+            .unwrap();
+        second
+            .register("hourly", &cron, counting_job(&runs))
+            .await
+            .unwrap();
+        let due = *schedule.next_run_at();
+
+        first.run_due(due).await;
+        second.run_due(due).await;
+
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_failing_job_is_retried_at_next_run() {
+        let store = InMemoryScheduleStore::default();
+        let mut scheduler = Scheduler::new(
+            store.clone(),
+            InMemoryLock::default(),
+            Duration::from_secs(1),
+        );
+        let schedule = scheduler
+            .register(
+                "failing",
+                &CronExpression::parse("* * * * *").unwrap(),
+                || async { Err(anyhow!("boom")) },
+            )
+            .await
+            .unwrap();
+        let due = *schedule.next_run_at();
+
+        let ran = scheduler.run_due(due).await;
+
+        assert_eq!(ran, vec!["failing".to_string()]);
+        let schedules = store.list().await.unwrap();
+        assert_eq!(
+            *schedules[0].next_run_at(),
+            due + chrono::TimeDelta::minutes(1)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_due_skips_job_still_running() {
+        let store = InMemoryScheduleStore::default();
+        let lock = InMemoryLock::default();
+        let mut scheduler = Scheduler::new(store, lock.clone(), Duration::from_secs(1));
+        let runs = Arc::new(AtomicU32::new(0));
+        let schedule = scheduler
+            .register(
+                "slow",
+                &CronExpression::parse("* * * * *").unwrap(),
+                counting_job(&runs),
+            )
+            .await
+            .unwrap();
+        let due = *schedule.next_run_at();
+        lock.held.lock().unwrap().insert(lock_name("slow"));
+
+        let while_running = scheduler.run_due(due).await;
+        lock.held.lock().unwrap().clear();
+        let once_done = scheduler.run_due(due).await;
+
+        assert!(while_running.is_empty());
+        assert_eq!(once_done, vec!["slow".to_string()]);
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+    }
+}
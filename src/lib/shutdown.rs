@@ -0,0 +1,31 @@
+//! Module `shutdown` notices when the process is asked to stop, so that the server can drain
+//! in-flight requests instead of being killed mid-request.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// How often to check whether a stop was requested.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+static REQUESTED: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn request_shutdown(_signal: libc::c_int) {
+    REQUESTED.store(true, Ordering::SeqCst);
+}
+
+/// Completes once the process receives `SIGTERM` or `SIGINT`.
+///
+/// Replaces the default handlers of those signals, which terminate the process immediately.
+pub async fn signal() {
+    let handler = request_shutdown as extern "C" fn(libc::c_int) as libc::sighandler_t;
+    // SAFETY: the handler only stores to an atomic, which is async-signal-safe.
+    unsafe {
+        libc::signal(libc::SIGTERM, handler);
+        libc::signal(libc::SIGINT, handler);
+    }
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+    while !REQUESTED.load(Ordering::SeqCst) {
+        interval.tick().await;
+    }
+    tracing::info!("shutdown requested, draining connections");
+}
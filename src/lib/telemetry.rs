@@ -0,0 +1,476 @@
+//! Module `telemetry` writes the events recorded with [tracing] to stdout, as human-readable
+//! lines or as one JSON object per line for log collectors.
+
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fmt,
+    io::Write,
+    str::FromStr,
+    sync::{
+        Arc, Mutex, OnceLock, RwLock,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use anyhow::Context;
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde_json::{Map, Value};
+use tracing::{
+    Event, Level, Metadata, Subscriber,
+    field::{Field, Visit},
+    level_filters::LevelFilter,
+    span,
+};
+
+use crate::configuration::{LogFormat, LogSettings};
+
+/// The level logged at if [LogSettings::level] is not set.
+const DEFAULT_LEVEL: LevelFilter = LevelFilter::INFO;
+
+/// The levels of the installed [LogSubscriber], changed by [set_level].
+static LEVELS: OnceLock<Arc<RwLock<LevelFilters>>> = OnceLock::new();
+
+thread_local! {
+    /// The ids of the spans entered on this thread, innermost last.
+    static ENTERED: RefCell<Vec<u64>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Writes events as configured by `settings` from now on.
+///
+/// # Errors
+///
+/// Fails if a level in `settings` is invalid, or if logging was already initialized.
+pub fn init_logging(settings: &LogSettings) -> anyhow::Result<()> {
+    let filter = Arc::new(RwLock::new(LevelFilters::from_settings(settings)?));
+    let subscriber = LogSubscriber {
+        format: settings.format,
+        filter: filter.clone(),
+        next_id: AtomicU64::new(1),
+        spans: Mutex::new(HashMap::new()),
+    };
+    tracing::subscriber::set_global_default(subscriber)
+        .context("logging is already initialized")?;
+    let _ = LEVELS.set(filter);
+    Ok(())
+}
+
+/// Logs at `level` from now on, except for the targets with a level of their own, or at the
+/// configured level again if `None`. Has no effect before [init_logging].
+///
+/// # Errors
+///
+/// Fails if `level` is invalid.
+pub fn set_level(level: Option<&str>) -> anyhow::Result<()> {
+    let level = level.map(parse_level).transpose()?;
+    update_levels(|filter| filter.overridden = level);
+    Ok(())
+}
+
+/// Logs at the levels of `settings` from now on, keeping a level set by [set_level]. The
+/// format can't be changed once logging is initialized. Has no effect before [init_logging].
+///
+/// # Errors
+///
+/// Fails if a level in `settings` is invalid.
+pub fn reload(settings: &LogSettings) -> anyhow::Result<()> {
+    let reloaded = LevelFilters::from_settings(settings)?;
+    update_levels(|filter| {
+        filter.configured = reloaded.configured;
+        filter.targets = reloaded.targets;
+    });
+    Ok(())
+}
+
+fn update_levels(update: impl FnOnce(&mut LevelFilters)) {
+    let Some(filter) = LEVELS.get() else {
+        return;
+    };
+    update(&mut filter.write().expect("level lock poisoned"));
+    // Callsites cache whether they are enabled, so they must be asked again.
+    tracing::callsite::rebuild_interest_cache();
+}
+
+/// The levels logged at, by target.
+#[derive(Debug, Clone, PartialEq)]
+struct LevelFilters {
+    /// The level of the configuration file.
+    configured: LevelFilter,
+    /// The level set by [set_level], taking precedence over `configured`.
+    overridden: Option<LevelFilter>,
+    /// The levels of targets, the longest target first, so that the most specific one matches.
+    targets: Vec<(String, LevelFilter)>,
+}
+
+impl LevelFilters {
+    fn from_settings(settings: &LogSettings) -> anyhow::Result<Self> {
+        let configured = match &settings.level {
+            Some(level) => parse_level(level)?,
+            None => DEFAULT_LEVEL,
+        };
+        let mut targets = settings
+            .targets
+            .iter()
+            .map(|(target, level)| Ok((target.clone(), parse_level(level)?)))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        targets.sort_by_key(|(target, _)| std::cmp::Reverse(target.len()));
+        Ok(Self {
+            configured,
+            overridden: None,
+            targets,
+        })
+    }
+
+    /// The level of the targets without a level of their own.
+    fn default_level(&self) -> LevelFilter {
+        self.overridden.unwrap_or(self.configured)
+    }
+
+    /// The level of `target`, or of the module it is in.
+    fn level(&self, target: &str) -> LevelFilter {
+        self.targets
+            .iter()
+            .find(|(prefix, _)| {
+                target
+                    .strip_prefix(prefix.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+            })
+            .map_or(self.default_level(), |(_, level)| *level)
+    }
+
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        *metadata.level() <= self.level(metadata.target())
+    }
+
+    fn max_level(&self) -> LevelFilter {
+        self.targets
+            .iter()
+            .map(|(_, level)| *level)
+            .fold(self.default_level(), LevelFilter::max)
+    }
+}
+
+fn parse_level(level: &str) -> anyhow::Result<LevelFilter> {
+    LevelFilter::from_str(level).with_context(|| format!("invalid log level '{level}'"))
+}
+
+#[derive(Debug)]
+struct SpanData {
+    name: &'static str,
+    fields: Map<String, Value>,
+    references: usize,
+}
+
+/// A [Subscriber] writing the enabled events to stdout, with the spans they occurred in.
+struct LogSubscriber {
+    format: LogFormat,
+    filter: Arc<RwLock<LevelFilters>>,
+    next_id: AtomicU64,
+    spans: Mutex<HashMap<u64, SpanData>>,
+}
+
+impl LogSubscriber {
+    fn filter(&self) -> std::sync::RwLockReadGuard<'_, LevelFilters> {
+        self.filter.read().expect("level lock poisoned")
+    }
+
+    fn spans(&self) -> std::sync::MutexGuard<'_, HashMap<u64, SpanData>> {
+        self.spans.lock().expect("span lock poisoned")
+    }
+}
+
+impl Subscriber for LogSubscriber {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        self.filter().enabled(metadata)
+    }
+
+    fn max_level_hint(&self) -> Option<LevelFilter> {
+        Some(self.filter().max_level())
+    }
+
+    fn new_span(&self, attributes: &span::Attributes<'_>) -> span::Id {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let mut fields = Map::new();
+        attributes.record(&mut JsonVisitor(&mut fields));
+        self.spans().insert(
+            id,
+            SpanData {
+                name: attributes.metadata().name(),
+                fields,
+                references: 1,
+            },
+        );
+        span::Id::from_u64(id)
+    }
+
+    fn record(&self, span: &span::Id, values: &span::Record<'_>) {
+        if let Some(data) = self.spans().get_mut(&span.into_u64()) {
+            values.record(&mut JsonVisitor(&mut data.fields));
+        }
+    }
+
+    fn record_follows_from(&self, _span: &span::Id, _follows: &span::Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut fields = Map::new();
+        event.record(&mut JsonVisitor(&mut fields));
+        let spans = {
+            let spans = self.spans();
+            ENTERED.with_borrow(|entered| {
+                entered
+                    .iter()
+                    .filter_map(|id| spans.get(id))
+                    .map(|data| (data.name, data.fields.clone()))
+                    .collect()
+            })
+        };
+        let line = LogLine {
+            timestamp: Utc::now(),
+            level: *event.metadata().level(),
+            target: event.metadata().target(),
+            fields,
+            spans,
+        };
+        let line = match self.format {
+            LogFormat::Pretty => line.to_pretty(),
+            LogFormat::Json => line.to_json(),
+        };
+        // Logging must never take the process down, so failed writes are dropped.
+        let _ = writeln!(std::io::stdout().lock(), "{line}");
+    }
+
+    fn enter(&self, span: &span::Id) {
+        ENTERED.with_borrow_mut(|entered| entered.push(span.into_u64()));
+    }
+
+    fn exit(&self, span: &span::Id) {
+        let id = span.into_u64();
+        ENTERED.with_borrow_mut(|entered| {
+            if let Some(index) = entered.iter().rposition(|entered| *entered == id) {
+                entered.remove(index);
+            }
+        });
+    }
+
+    fn clone_span(&self, span: &span::Id) -> span::Id {
+        if let Some(data) = self.spans().get_mut(&span.into_u64()) {
+            data.references += 1;
+        }
+        span.clone()
+    }
+
+    fn try_close(&self, span: span::Id) -> bool {
+        let mut spans = self.spans();
+        let id = span.into_u64();
+        let Some(data) = spans.get_mut(&id) else {
+            return false;
+        };
+        data.references -= 1;
+        if data.references > 0 {
+            return false;
+        }
+        spans.remove(&id);
+        true
+    }
+}
+
+/// Collects the recorded fields as JSON values.
+struct JsonVisitor<'a>(&'a mut Map<String, Value>);
+
+impl Visit for JsonVisitor<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), format!("{value:?}").into());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+}
+
+/// An event, as written to the log.
+struct LogLine<'a> {
+    timestamp: DateTime<Utc>,
+    level: Level,
+    target: &'a str,
+    fields: Map<String, Value>,
+    /// The names and fields of the spans the event occurred in, outermost first.
+    spans: Vec<(&'static str, Map<String, Value>)>,
+}
+
+impl LogLine<'_> {
+    fn timestamp(&self) -> String {
+        self.timestamp.to_rfc3339_opts(SecondsFormat::Millis, true)
+    }
+
+    fn to_json(&self) -> String {
+        let mut fields = self.fields.clone();
+        let message = fields.remove("message").unwrap_or_default();
+        let spans: Vec<Value> = self
+            .spans
+            .iter()
+            .map(|(name, fields)| {
+                let mut span = fields.clone();
+                span.insert("name".to_string(), (*name).into());
+                Value::Object(span)
+            })
+            .collect();
+        serde_json::json!({
+            "timestamp": self.timestamp(),
+            "level": self.level.as_str(),
+            "target": self.target,
+            "message": message,
+            "fields": fields,
+            "spans": spans,
+        })
+        .to_string()
+    }
+
+    fn to_pretty(&self) -> String {
+        let mut line = format!("{} {:>5} ", self.timestamp(), self.level.as_str());
+        for (name, fields) in &self.spans {
+            line.push_str(name);
+            if !fields.is_empty() {
+                line.push('{');
+                line.push_str(&format_fields(fields, None));
+                line.push('}');
+            }
+            line.push(':');
+        }
+        line.push_str(self.target);
+        line.push(':');
+        if let Some(message) = self.fields.get("message") {
+            line.push(' ');
+            line.push_str(&format_value(message));
+        }
+        let fields = format_fields(&self.fields, Some("message"));
+        if !fields.is_empty() {
+            line.push(' ');
+            line.push_str(&fields);
+        }
+        line
+    }
+}
+
+fn format_fields(fields: &Map<String, Value>, skip: Option<&str>) -> String {
+    fields
+        .iter()
+        .filter(|(name, _)| Some(name.as_str()) != skip)
+        .map(|(name, value)| format!("{name}={}", format_value(value)))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn format_value(value: &Value) -> String {
+    match value {
+        Value::String(value) => value.clone(),
+        value => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line() -> LogLine<'static> {
+        let fields = serde_json::json!({ "message": "listening", "port": 3000 });
+        let span = serde_json::json!({ "method": "GET" });
+        LogLine {
+            timestamp: DateTime::from_timestamp(0, 0).unwrap(),
+            level: Level::INFO,
+            target: "crowdsource::bootstrap",
+            fields: fields.as_object().unwrap().clone(),
+            spans: vec![("http_request", span.as_object().unwrap().clone())],
+        }
+    }
+
+    #[test]
+    fn level_of_most_specific_target_applies() {
+        let settings = LogSettings {
+            format: LogFormat::Pretty,
+            level: Some("warn".to_string()),
+            targets: HashMap::from([
+                ("crowdsource".to_string(), "info".to_string()),
+                ("crowdsource::outbound".to_string(), "debug".to_string()),
+            ]),
+        };
+
+        let filters = LevelFilters::from_settings(&settings).unwrap();
+
+        assert_eq!(
+            filters.level("crowdsource::outbound::sqlx"),
+            LevelFilter::DEBUG
+        );
+        assert_eq!(filters.level("crowdsource::bootstrap"), LevelFilter::INFO);
+        assert_eq!(filters.level("crowdsourced"), LevelFilter::WARN);
+        assert_eq!(filters.level("sqlx::query"), LevelFilter::WARN);
+        assert_eq!(filters.max_level(), LevelFilter::DEBUG);
+    }
+
+    #[test]
+    fn overridden_level_applies_to_targets_without_a_level_of_their_own() {
+        let settings = LogSettings {
+            format: LogFormat::Pretty,
+            level: Some("info".to_string()),
+            targets: HashMap::from([("sqlx".to_string(), "warn".to_string())]),
+        };
+        let mut filters = LevelFilters::from_settings(&settings).unwrap();
+
+        filters.overridden = Some(LevelFilter::DEBUG);
+
+        assert_eq!(filters.level("crowdsource::bootstrap"), LevelFilter::DEBUG);
+        assert_eq!(filters.level("sqlx::query"), LevelFilter::WARN);
+    }
+
+    #[test]
+    fn invalid_level_is_rejected() {
+        let settings = LogSettings {
+            level: Some("loud".to_string()),
+            ..LogSettings::default()
+        };
+
+        assert!(LevelFilters::from_settings(&settings).is_err());
+    }
+
+    #[test]
+    fn json_line_holds_message_fields_and_spans() {
+        let json: Value = serde_json::from_str(&line().to_json()).unwrap();
+
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "timestamp": "1970-01-01T00:00:00.000Z",
+                "level": "INFO",
+                "target": "crowdsource::bootstrap",
+                "message": "listening",
+                "fields": { "port": 3000 },
+                "spans": [{ "name": "http_request", "method": "GET" }],
+            })
+        );
+    }
+
+    #[test]
+    fn pretty_line_is_human_readable() {
+        assert_eq!(
+            line().to_pretty(),
+            "1970-01-01T00:00:00.000Z  INFO http_request{method=GET}:crowdsource::bootstrap: \
+             listening port=3000"
+        );
+    }
+}
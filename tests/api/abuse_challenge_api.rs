@@ -0,0 +1,78 @@
+use crowdsource::inbound::http::ChallengedRoute;
+use sha2::{Digest, Sha256};
+
+use crate::helpers::{TestApp, TestAppOptions, spawn_app_with};
+
+const USER_BODY: &str = r#"{
+    "email_address":"user@example.com",
+    "username":"user",
+    "accepted_terms_version":"2026-01-30"
+}"#;
+
+async fn spawn_app_with_challenged_signup() -> TestApp {
+    spawn_app_with(TestAppOptions {
+        challenged_routes: &[ChallengedRoute::Signup],
+        ..Default::default()
+    })
+    .await
+}
+
+async fn solve_challenge(app: &TestApp) -> String {
+    let challenge: serde_json::Value = app.get_challenge().await.json().await.unwrap();
+    assert_eq!(challenge["data"]["type"], "proof_of_work");
+    let difficulty = challenge["data"]["difficulty"].as_u64().unwrap() as u32;
+    let challenge = challenge["data"]["challenge"].as_str().unwrap();
+    (0u64..)
+        .map(|nonce| format!("{challenge}:{nonce}"))
+        .find(|solution| {
+            let hash = Sha256::digest(solution.as_bytes());
+            u32::from_be_bytes(hash[..4].try_into().unwrap()).leading_zeros() >= difficulty
+        })
+        .unwrap()
+}
+
+#[tokio::test]
+async fn signup_with_solved_challenge_succeeds() {
+    // Arrange
+    let app = spawn_app_with_challenged_signup().await;
+    let solution = solve_challenge(&app).await;
+
+    // Act
+    let response = app
+        .post_users_with_challenge_solution(USER_BODY.into(), &solution)
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 201);
+}
+
+#[tokio::test]
+async fn signup_without_challenge_solution_returns_403() {
+    // Arrange
+    let app = spawn_app_with_challenged_signup().await;
+
+    // Act
+    let response = app.post_users(USER_BODY.into()).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 403);
+    let saved = sqlx::query!("SELECT id FROM users;")
+        .fetch_all(&app.db_pool)
+        .await
+        .unwrap();
+    assert!(saved.is_empty());
+}
+
+#[tokio::test]
+async fn signup_with_forged_challenge_returns_403() {
+    // Arrange
+    let app = spawn_app_with_challenged_signup().await;
+
+    // Act
+    let response = app
+        .post_users_with_challenge_solution(USER_BODY.into(), "4102444800.abc.forged:0")
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 403);
+}
@@ -0,0 +1,117 @@
+use crate::helpers::{TestApp, spawn_app};
+
+async fn create_user(app: &TestApp) -> String {
+    let body = r#"{
+        "email_address":"user@example.com",
+        "username":"user",
+        "accepted_terms_version":"2026-01-30"
+    }"#;
+    let created: serde_json::Value = app.post_users(body.into()).await.json().await.unwrap();
+    created["data"]["id"].as_str().unwrap().to_string()
+}
+
+async fn accept_terms(app: &TestApp, id: &str) {
+    let response = app
+        .post_user_terms_acceptance(id, r#"{"terms_version":"2026-01-30"}"#.into())
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn user_activity_lists_signup_and_terms_acceptances_newest_first() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = create_user(&app).await;
+    accept_terms(&app, &id).await;
+
+    // Act
+    let response = app.get_user_activity(&id, "").await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    let activities = body["data"]["activities"].as_array().unwrap();
+    assert_eq!(activities.len(), 2);
+    assert_eq!(activities[0]["type"], "accepted_terms");
+    assert_eq!(activities[0]["terms_version"], "2026-01-30");
+    assert_eq!(activities[1]["type"], "signed_up");
+    assert_eq!(activities[1]["user_id"], id.as_str());
+    assert!(body["data"]["next_cursor"].is_null());
+}
+
+#[tokio::test]
+async fn user_activity_is_paginated_by_cursor() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = create_user(&app).await;
+    accept_terms(&app, &id).await;
+    accept_terms(&app, &id).await;
+
+    // Act
+    let first: serde_json::Value = app
+        .get_user_activity(&id, "?limit=2")
+        .await
+        .json()
+        .await
+        .unwrap();
+    let cursor = first["data"]["next_cursor"].as_str().unwrap();
+    let second: serde_json::Value = app
+        .get_user_activity(&id, &format!("?limit=2&after={cursor}"))
+        .await
+        .json()
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(first["data"]["activities"].as_array().unwrap().len(), 2);
+    let activities = second["data"]["activities"].as_array().unwrap();
+    assert_eq!(activities.len(), 1);
+    assert_eq!(activities[0]["type"], "signed_up");
+    assert!(second["data"]["next_cursor"].is_null());
+}
+
+#[tokio::test]
+async fn user_activity_returns_404_for_unknown_user() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app
+        .get_user_activity("b2d5b8c2-8c1f-4f22-9a43-2a1f5e5b8c3d", "")
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 404);
+}
+
+#[tokio::test]
+async fn user_activity_returns_422_for_invalid_cursor() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = create_user(&app).await;
+
+    // Act
+    let response = app.get_user_activity(&id, "?after=abc").await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 422);
+}
+
+#[tokio::test]
+async fn user_activity_is_only_available_to_the_user_or_an_admin() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = create_user(&app).await;
+    let path = format!("/api/users/{id}/activity");
+    let other_token = app.user_token(&uuid::Uuid::new_v4().to_string());
+
+    // Act
+    let anonymous = app.get_as(&path, None).await;
+    let other_user = app.get_as(&path, Some(&other_token)).await;
+    let admin = app.get_as(&path, Some(&app.admin_token())).await;
+
+    // Assert
+    assert_eq!(anonymous.status().as_u16(), 401);
+    assert_eq!(other_user.status().as_u16(), 403);
+    assert_eq!(admin.status().as_u16(), 200);
+}
@@ -0,0 +1,85 @@
+use crate::helpers::spawn_app;
+
+#[tokio::test]
+async fn users_page_lists_users() {
+    // Arrange
+    let app = spawn_app().await;
+    let body = r#"{
+        "email_address":"user@example.com",
+        "username":"<user>",
+        "accepted_terms_version":"2026-01-30"
+    }"#;
+    app.post_users(body.into()).await;
+
+    // Act
+    let response = app.get_admin_page("/users").await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(
+        response.headers()["content-type"],
+        "text/html; charset=utf-8"
+    );
+    let html = response.text().await.unwrap();
+    assert!(html.contains("&lt;user&gt;"));
+    assert!(html.contains("user@example.com"));
+}
+
+#[tokio::test]
+async fn overview_page_counts_users_and_dead_letters() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app.get_admin_page("").await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let html = response.text().await.unwrap();
+    assert!(html.contains("0 users"));
+    assert!(html.contains("0 undelivered notifications"));
+}
+
+#[tokio::test]
+async fn dead_letters_page_shows_empty_queue() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app.get_admin_page("/dead-letters").await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let html = response.text().await.unwrap();
+    assert!(html.contains("All notifications have been delivered."));
+}
+
+#[tokio::test]
+async fn admin_pages_are_only_available_to_admins() {
+    // Arrange
+    let app = spawn_app().await;
+    let user_token = app.user_token(&uuid::Uuid::new_v4().to_string());
+
+    // Act
+    let anonymous = app.get_as("/admin/users", None).await;
+    let user = app.get_as("/admin/users", Some(&user_token)).await;
+    let bearer_admin = app.get_as("/admin/users", Some(&app.admin_token())).await;
+    let anonymous_redrive = app
+        .post_as(
+            "/admin/dead-letters/b2d5b8c2-8c1f-4f22-9a43-2a1f5e5b8c3d/redrive",
+            None,
+        )
+        .await;
+
+    // Assert
+    assert_eq!(anonymous.status().as_u16(), 401);
+    assert!(
+        anonymous.headers()["www-authenticate"]
+            .to_str()
+            .unwrap()
+            .starts_with("Basic ")
+    );
+    assert_eq!(user.status().as_u16(), 403);
+    assert_eq!(bearer_admin.status().as_u16(), 200);
+    assert_eq!(anonymous_redrive.status().as_u16(), 401);
+}
@@ -0,0 +1,257 @@
+use std::time::Duration;
+
+use chrono::{TimeDelta, Utc};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::helpers::{TestApp, spawn_app};
+
+async fn create_user(app: &TestApp) -> String {
+    let body = r#"{
+        "email_address":"user@example.com",
+        "username":"user",
+        "accepted_terms_version":"2026-01-30"
+    }"#;
+    let created: serde_json::Value = app.post_users(body.into()).await.json().await.unwrap();
+    created["data"]["id"].as_str().unwrap().to_string()
+}
+
+async fn stored_events(app: &TestApp) -> Vec<(String, String, Option<i64>)> {
+    sqlx::query!(
+        "SELECT task_id, kind, duration_ms FROM analytics_events ORDER BY occurred_at, kind"
+    )
+    .fetch_all(&app.db_pool)
+    .await
+    .unwrap()
+    .into_iter()
+    .map(|row| (row.task_id, row.kind, row.duration_ms))
+    .collect()
+}
+
+/// Waits until `count` events are stored, as they are written asynchronously, failing after
+/// a few seconds.
+async fn wait_for_events(app: &TestApp, count: usize) -> Vec<(String, String, Option<i64>)> {
+    for _ in 0..100 {
+        let events = stored_events(app).await;
+        if events.len() >= count {
+            return events;
+        }
+        tokio::time::sleep(Duration::from_millis(30)).await;
+    }
+    panic!("expected {count} stored events");
+}
+
+#[tokio::test]
+async fn recorded_events_are_written() {
+    // Arrange
+    let app = spawn_app().await;
+    let user_id = create_user(&app).await;
+    let viewed_at = Utc::now() - TimeDelta::minutes(2);
+
+    // Act
+    let response = app
+        .post_analytics_events(
+            &user_id,
+            json!({
+                "events": [
+                    { "type": "task_viewed", "task_id": "task-1", "occurred_at": viewed_at },
+                    {
+                        "type": "time_on_task",
+                        "task_id": "task-1",
+                        "occurred_at": viewed_at + TimeDelta::seconds(40),
+                        "duration_ms": 40000
+                    },
+                    { "type": "task_skipped", "task_id": "task-2", "occurred_at": Utc::now() }
+                ]
+            }),
+        )
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 202);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["data"]["accepted"], 3);
+    let events = wait_for_events(&app, 3).await;
+    assert_eq!(
+        events,
+        vec![
+            ("task-1".to_string(), "task_viewed".to_string(), None),
+            (
+                "task-1".to_string(),
+                "time_on_task".to_string(),
+                Some(40000)
+            ),
+            ("task-2".to_string(), "task_skipped".to_string(), None),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn invalid_events_are_rejected_whole_batch() {
+    // Arrange
+    let app = spawn_app().await;
+    let user_id = create_user(&app).await;
+    let valid = json!({ "type": "task_viewed", "task_id": "task-1", "occurred_at": Utc::now() });
+    let test_cases = [
+        (
+            json!({ "type": "task_liked", "task_id": "task-1", "occurred_at": Utc::now() }),
+            "unknown type",
+        ),
+        (
+            json!({ "type": "time_on_task", "task_id": "task-1", "occurred_at": Utc::now() }),
+            "missing duration",
+        ),
+        (
+            json!({
+                "type": "task_viewed",
+                "task_id": "task-1",
+                "occurred_at": Utc::now() + TimeDelta::hours(1)
+            }),
+            "occurred in the future",
+        ),
+        (
+            json!({ "type": "task_viewed", "task_id": "", "occurred_at": Utc::now() }),
+            "blank task id",
+        ),
+    ];
+
+    for (invalid, description) in test_cases {
+        // Act
+        let response = app
+            .post_analytics_events(&user_id, json!({ "events": [valid, invalid] }))
+            .await;
+
+        // Assert
+        assert_eq!(
+            response.status().as_u16(),
+            422,
+            "The API did not fail with 422 Unprocessable Entity when the event had {description}."
+        );
+    }
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(stored_events(&app).await.is_empty());
+}
+
+#[tokio::test]
+async fn invalid_event_is_pointed_out() {
+    // Arrange
+    let app = spawn_app().await;
+    let user_id = create_user(&app).await;
+
+    // Act
+    let response = app
+        .post_analytics_events(
+            &user_id,
+            json!({
+                "events": [
+                    { "type": "task_viewed", "task_id": "task-1", "occurred_at": Utc::now() },
+                    {
+                        "type": "time_on_task",
+                        "task_id": "task-1",
+                        "occurred_at": Utc::now(),
+                        "duration_ms": 0
+                    }
+                ]
+            }),
+        )
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 422);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert!(
+        body["data"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("events[1]")
+    );
+}
+
+#[tokio::test]
+async fn too_many_events_are_rejected() {
+    // Arrange
+    let app = spawn_app().await;
+    let user_id = create_user(&app).await;
+    let events: Vec<serde_json::Value> = (0..101)
+        .map(|_| json!({ "type": "task_viewed", "task_id": "task-1", "occurred_at": Utc::now() }))
+        .collect();
+
+    // Act
+    let response = app
+        .post_analytics_events(&user_id, json!({ "events": events }))
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 422);
+}
+
+#[tokio::test]
+async fn events_of_unknown_user_return_404() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app
+        .post_analytics_events(
+            &Uuid::new_v4().to_string(),
+            json!({
+                "events": [{ "type": "task_viewed", "task_id": "task-1", "occurred_at": Utc::now() }]
+            }),
+        )
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 404);
+}
+
+#[tokio::test]
+async fn events_are_recorded_for_the_authenticated_user() {
+    // Arrange
+    let app = spawn_app().await;
+    let user_id = create_user(&app).await;
+    let other_user_id = Uuid::new_v4();
+    let body = json!({
+        "user_id": other_user_id,
+        "events": [{ "type": "task_viewed", "task_id": "task-1", "occurred_at": Utc::now() }]
+    });
+
+    // Act
+    let anonymous = app.post_analytics_events_as(body.clone(), None).await;
+    let admin = app
+        .post_analytics_events_as(body.clone(), Some(&app.admin_token()))
+        .await;
+    let user = app.post_analytics_events(&user_id, body).await;
+
+    // Assert
+    assert_eq!(anonymous.status().as_u16(), 401);
+    assert_eq!(admin.status().as_u16(), 403);
+    assert_eq!(user.status().as_u16(), 202);
+    wait_for_events(&app, 1).await;
+    let user_ids: Vec<Uuid> = sqlx::query_scalar!("SELECT user_id FROM analytics_events")
+        .fetch_all(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(user_ids, vec![user_id.parse::<Uuid>().unwrap()]);
+}
+
+#[tokio::test]
+async fn events_are_deleted_on_erasure() {
+    // Arrange
+    let app = spawn_app().await;
+    let user_id = create_user(&app).await;
+    app.post_analytics_events(
+        &user_id,
+        json!({
+            "events": [{ "type": "task_viewed", "task_id": "task-1", "occurred_at": Utc::now() }]
+        }),
+    )
+    .await;
+    wait_for_events(&app, 1).await;
+
+    // Act
+    let response = app.post_user_erasure(&user_id).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(stored_events(&app).await.is_empty());
+}
@@ -0,0 +1,49 @@
+use axum::routing::{get, post};
+
+use crate::helpers::{TestApp, TestAppOptions, spawn_app_with};
+
+async fn spawn_app_with_extension() -> TestApp {
+    spawn_app_with(TestAppOptions {
+        api_extension: Some(
+            axum::Router::new()
+                .route("/hello", get(|| async { "hello" }))
+                .route("/echo", post(|body: String| async move { body })),
+        ),
+        ..TestAppOptions::default()
+    })
+    .await
+}
+
+#[tokio::test]
+async fn extension_routes_are_served_under_api() {
+    // Arrange
+    let app = spawn_app_with_extension().await;
+
+    // Act
+    let extension = app.get("/api/hello").await;
+    let built_in = app.get("/api/features").await;
+
+    // Assert
+    assert_eq!(extension.status().as_u16(), 200);
+    assert_eq!(extension.text().await.unwrap(), "hello");
+    assert_eq!(built_in.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn extension_writes_are_refused_during_maintenance() {
+    // Arrange
+    let app = spawn_app_with_extension().await;
+    app.put_maintenance(true).await;
+
+    // Act
+    let response = app
+        .api_client
+        .post(app.url("/api/echo"))
+        .body("hello")
+        .send()
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 503);
+}
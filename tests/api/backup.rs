@@ -0,0 +1,91 @@
+use crowdsource::{backup, outbound::sqlx_user_repository::SqlxUserRepository};
+
+use crate::helpers::{TestApp, fresh_user_repository, spawn_app};
+
+async fn create_user(app: &TestApp, name: &str) -> String {
+    let body = format!(
+        r#"{{
+            "email_address":"{name}@example.com",
+            "username":"{name}",
+            "accepted_terms_version":"2026-01-30"
+        }}"#
+    );
+    let created: serde_json::Value = app.post_users(body).await.json().await.unwrap();
+    created["data"]["id"].as_str().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn restored_backup_holds_the_same_data() {
+    // Arrange
+    let app = spawn_app().await;
+    let alice = create_user(&app, "alice").await;
+    let bob = create_user(&app, "bob").await;
+    app.post_user_terms_acceptance(&alice, r#"{"terms_version":"2026-01-30"}"#.into())
+        .await;
+    app.post_user_erasure(&bob).await;
+    sqlx::query!("UPDATE users SET email_verified = true WHERE username = 'alice'")
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+    app.post_user_email_change(&alice, r#"{"email":"alice.new@example.com"}"#.into())
+        .await;
+    app.post_user_notifications_read(&alice).await;
+    let mut archive = Vec::new();
+    let backed_up = backup::backup(&SqlxUserRepository::new(app.db_pool.clone()), &mut archive)
+        .await
+        .unwrap();
+    let target = fresh_user_repository().await;
+
+    // Act
+    let restored = backup::restore(&target, archive.as_slice()).await.unwrap();
+
+    // Assert
+    assert_eq!(restored, backed_up);
+    assert_eq!(restored.users, 2);
+    assert_eq!(restored.terms_acceptances, 3);
+    assert_eq!(restored.activities, 3);
+    assert_eq!(restored.notifications, 2);
+    assert_eq!(restored.email_changes, 1);
+    let mut round_trip = Vec::new();
+    backup::backup(&target, &mut round_trip).await.unwrap();
+    let without_header = |archive: &[u8]| {
+        String::from_utf8(archive.to_vec())
+            .unwrap()
+            .lines()
+            .skip(1)
+            .map(str::to_string)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(without_header(&round_trip), without_header(&archive));
+    assert!(without_header(&archive)[0].contains(r#""email_verified":true"#));
+}
+
+#[tokio::test]
+async fn restore_refuses_a_repository_holding_users() {
+    // Arrange
+    let app = spawn_app().await;
+    create_user(&app, "alice").await;
+    let repo = SqlxUserRepository::new(app.db_pool.clone());
+    let mut archive = Vec::new();
+    backup::backup(&repo, &mut archive).await.unwrap();
+
+    // Act
+    let result = backup::restore(&repo, archive.as_slice()).await;
+
+    // Assert
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn restore_rejects_unsupported_format_versions() {
+    // Arrange
+    let target = fresh_user_repository().await;
+    let archive = r#"{"type":"header","format_version":999,"created_at":"2026-10-15T00:00:00Z"}"#;
+
+    // Act
+    let result = backup::restore(&target, archive.as_bytes()).await;
+
+    // Assert
+    let err = result.unwrap_err();
+    assert!(err.to_string().contains("format version 999"), "{err:#}");
+}
@@ -0,0 +1,89 @@
+use crowdsource::{bootstrap, configuration::get_configuration, migrations};
+use uuid::Uuid;
+
+use crate::helpers::{configure_database, create_database};
+
+fn outcome<'a>(results: &'a [bootstrap::CheckResult], name: &str) -> &'a anyhow::Result<()> {
+    &results
+        .iter()
+        .find(|result| result.name == name)
+        .unwrap_or_else(|| panic!("no {name} check"))
+        .outcome
+}
+
+#[tokio::test]
+async fn checks_pass_for_migrated_database() {
+    // Arrange
+    let mut settings = get_configuration().unwrap();
+    settings.database.database_name = Uuid::new_v4().to_string();
+    configure_database(&settings.database).await;
+
+    // Act
+    let results = bootstrap::check(&settings).await;
+
+    // Assert
+    assert!(
+        results.iter().all(bootstrap::CheckResult::is_ok),
+        "{results:?}"
+    );
+}
+
+#[tokio::test]
+async fn check_reports_pending_migrations() {
+    // Arrange
+    let mut settings = get_configuration().unwrap();
+    settings.database.database_name = Uuid::new_v4().to_string();
+    create_database(&settings.database).await;
+
+    // Act
+    let results = bootstrap::check(&settings).await;
+
+    // Assert
+    let error = outcome(&results, "migrations").as_ref().unwrap_err();
+    assert!(error.to_string().contains("create user table"), "{error}");
+}
+
+#[tokio::test]
+async fn check_reports_unreachable_database() {
+    // Arrange
+    let mut settings = get_configuration().unwrap();
+    settings.database.database_name = Uuid::new_v4().to_string();
+
+    // Act
+    let results = bootstrap::check(&settings).await;
+
+    // Assert
+    assert!(outcome(&results, "configuration").is_ok());
+    assert!(outcome(&results, "database").is_err());
+    assert!(results.iter().all(|result| result.name != "migrations"));
+}
+
+#[tokio::test]
+async fn check_reports_invalid_configuration() {
+    // Arrange
+    let mut settings = get_configuration().unwrap();
+    settings.terms_version = " ".to_string();
+    settings.static_dir = Some("does/not/exist".to_string());
+
+    // Act
+    let results = bootstrap::check(&settings).await;
+
+    // Assert
+    assert!(outcome(&results, "configuration").is_err());
+}
+
+#[tokio::test]
+async fn migrations_applied_concurrently_are_applied_once() {
+    // Arrange
+    let mut settings = get_configuration().unwrap();
+    settings.database.database_name = Uuid::new_v4().to_string();
+    let db_pool = create_database(&settings.database).await;
+
+    // Act
+    let (first, second) = tokio::join!(migrations::run(&db_pool), migrations::run(&db_pool));
+
+    // Assert
+    first.unwrap();
+    second.unwrap();
+    assert!(migrations::pending(&db_pool).await.unwrap().is_empty());
+}
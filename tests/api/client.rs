@@ -0,0 +1,78 @@
+use crowdsource::client::{
+    ClientError, CrowdsourceClient,
+    models::{AcceptTermsHttpRequestBody, CreateUserHttpRequestBody, ListNotificationsParams},
+};
+use uuid::Uuid;
+
+use crate::helpers::spawn_app;
+
+fn create_user_body() -> CreateUserHttpRequestBody {
+    CreateUserHttpRequestBody {
+        username: "user".to_string(),
+        email_address: "user@example.com".to_string(),
+        accepted_terms_version: "2026-01-30".to_string(),
+    }
+}
+
+#[tokio::test]
+async fn client_round_trips_requests_and_responses() {
+    // Arrange
+    let app = spawn_app().await;
+    let client = CrowdsourceClient::new(&app.url(""));
+
+    // Act
+    let created = client.create_user(&create_user_body(), None).await.unwrap();
+    let user_id: Uuid = created.id.parse().unwrap();
+    let client = client.with_access_token(&app.user_token(&created.id));
+    let user = client.get_user(user_id).await.unwrap();
+    let accepted = client
+        .accept_terms(
+            user_id,
+            &AcceptTermsHttpRequestBody {
+                terms_version: "2026-01-30".to_string(),
+            },
+        )
+        .await
+        .unwrap();
+    let export = client.export_user_data(user_id).await.unwrap();
+    let inbox = client
+        .list_notifications(
+            user_id,
+            &ListNotificationsParams {
+                limit: Some(10),
+                unread_only: Some(true),
+            },
+        )
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(user.username, created.username);
+    assert_eq!(user.created_at, created.created_at);
+    assert_eq!(accepted.user_id, created.id);
+    assert_eq!(export.user.username, "user");
+    assert_eq!(export.user.email_address, "user@example.com");
+    assert_eq!(inbox.unread_count, inbox.notifications.len() as u64);
+}
+
+#[tokio::test]
+async fn client_returns_api_errors() {
+    // Arrange
+    let app = spawn_app().await;
+    let client = CrowdsourceClient::new(&app.url("")).with_access_token(&app.admin_token());
+    client.create_user(&create_user_body(), None).await.unwrap();
+
+    // Act
+    let duplicate = client.create_user(&create_user_body(), None).await;
+    let unknown = client.erase_user(Uuid::new_v4()).await;
+
+    // Assert
+    assert!(matches!(
+        duplicate,
+        Err(ClientError::Api { status, .. }) if status.as_u16() == 422
+    ));
+    assert!(matches!(
+        unknown,
+        Err(ClientError::Api { status, .. }) if status.as_u16() == 404
+    ));
+}
@@ -0,0 +1,55 @@
+use std::time::Duration;
+
+use crowdsource::configuration::watch_configuration;
+use tokio::sync::watch;
+
+#[tokio::test]
+async fn modified_configuration_file_is_published() {
+    // Arrange
+    let path = std::env::temp_dir().join(format!("{}.yaml", uuid::Uuid::new_v4()));
+    let original = std::fs::read_to_string("configuration.yaml").unwrap();
+    std::fs::write(&path, &original).unwrap();
+    let settings = crowdsource::configuration::get_configuration_from(&path).unwrap();
+    let (updates, mut changes) = watch::channel(settings);
+    tokio::spawn(watch_configuration(
+        path.clone(),
+        updates,
+        Duration::from_millis(10),
+    ));
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    // Act
+    std::fs::write(&path, format!("{original}\nmax_concurrent_requests: 7\n")).unwrap();
+
+    // Assert
+    tokio::time::timeout(Duration::from_secs(5), changes.changed())
+        .await
+        .expect("configuration was not reloaded")
+        .unwrap();
+    assert_eq!(changes.borrow().max_concurrent_requests, Some(7));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn invalid_configuration_file_is_skipped() {
+    // Arrange
+    let path = std::env::temp_dir().join(format!("{}.yaml", uuid::Uuid::new_v4()));
+    let original = std::fs::read_to_string("configuration.yaml").unwrap();
+    std::fs::write(&path, &original).unwrap();
+    let settings = crowdsource::configuration::get_configuration_from(&path).unwrap();
+    let (updates, changes) = watch::channel(settings);
+    tokio::spawn(watch_configuration(
+        path.clone(),
+        updates,
+        Duration::from_millis(10),
+    ));
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    // Act
+    std::fs::write(&path, "application_port: not a port\n").unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    // Assert
+    assert!(!changes.has_changed().unwrap());
+    std::fs::remove_file(&path).unwrap();
+}
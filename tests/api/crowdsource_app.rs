@@ -0,0 +1,108 @@
+use std::{collections::HashMap, sync::Arc};
+
+use crowdsource::{
+    CrowdsourceApp,
+    configuration::get_configuration,
+    domain::crowdsrc::{
+        models::{access_token::Principal, user::EmailAddress},
+        observed_service::{ObservedCrowdSrcService, ServiceMetrics},
+    },
+    outbound::collecting_user_notifier::CollectingUserNotifier,
+};
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use crate::helpers::{ACCESS_TOKEN_KEY, access_token, create_database};
+
+#[tokio::test]
+async fn embedded_app_uses_overridden_components() {
+    // Arrange
+    let mut settings = get_configuration().unwrap();
+    settings.database.database_name = Uuid::new_v4().to_string();
+    settings.application_port = 0;
+    settings.auto_migrate = true;
+    create_database(&settings.database).await;
+    let user_email_map = Arc::new(RwLock::new(HashMap::new()));
+    let notifier = CollectingUserNotifier::new(user_email_map.clone());
+    let server = CrowdsourceApp::new(settings)
+        .with_user_notifier(move |_, _| Ok(notifier))
+        .build()
+        .await
+        .unwrap();
+    let address = server.local_addr().unwrap();
+    tokio::spawn(server.run());
+
+    // Act
+    let response = reqwest::Client::new()
+        .post(format!("http://{address}/api/users"))
+        .header("Content-Type", "application/json")
+        .body(
+            r#"{
+            "email_address":"embedded@example.com",
+            "username":"embedded",
+            "accepted_terms_version":"2026-01-30"
+        }"#,
+        )
+        .send()
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 201);
+    let email = EmailAddress::new("embedded@example.com").unwrap();
+    assert!(user_email_map.read().await.contains_key(&email));
+}
+
+#[tokio::test]
+async fn embedded_app_serves_decorated_service() {
+    // Arrange
+    let mut settings = get_configuration().unwrap();
+    settings.database.database_name = Uuid::new_v4().to_string();
+    settings.application_port = 0;
+    settings.auto_migrate = true;
+    settings.access_token_secret = Some(String::from_utf8(ACCESS_TOKEN_KEY.to_vec()).unwrap());
+    create_database(&settings.database).await;
+    let metrics = ServiceMetrics::new();
+    let observer = metrics.clone();
+    let server = CrowdsourceApp::new(settings)
+        .with_user_notifier(|_, _| Ok(CollectingUserNotifier::new(Default::default())))
+        .with_service_decorator(move |service| ObservedCrowdSrcService::new(service, observer))
+        .build()
+        .await
+        .unwrap();
+    let address = server.local_addr().unwrap();
+    tokio::spawn(server.run());
+
+    // Act
+    let response = reqwest::Client::new()
+        .get(format!(
+            "http://{address}/api/users/{}/activity",
+            Uuid::new_v4()
+        ))
+        .bearer_auth(access_token(Principal::Admin))
+        .send()
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 404);
+    let snapshot = metrics.snapshot();
+    assert_eq!(snapshot["list_user_activity"].calls, 1);
+    assert_eq!(snapshot["list_user_activity"].failures, 1);
+}
+
+#[tokio::test]
+async fn embedded_app_fails_to_build_when_checks_fail() {
+    // Arrange
+    let mut settings = get_configuration().unwrap();
+    settings.database.database_name = Uuid::new_v4().to_string();
+    settings.application_port = 0;
+    create_database(&settings.database).await;
+
+    // Act
+    let result = CrowdsourceApp::new(settings).build().await;
+
+    // Assert
+    let error = result.err().expect("expected the startup checks to fail");
+    assert!(error.to_string().contains("migrations"), "{error:#}");
+}
@@ -0,0 +1,150 @@
+use std::sync::atomic::Ordering;
+
+use crowdsource::domain::crowdsrc::models::user::EmailAddress;
+
+use crate::helpers::{TestApp, spawn_app};
+
+async fn create_user_with_failing_notifier(app: &TestApp) -> serde_json::Value {
+    app.notifier_failing.store(true, Ordering::SeqCst);
+    let body = r#"{
+        "email_address":"user@example.com",
+        "username":"user",
+        "accepted_terms_version":"2026-01-30"
+    }"#;
+    let response = app.post_users(body.into()).await;
+    assert_eq!(response.status().as_u16(), 201);
+    response.json().await.unwrap()
+}
+
+#[tokio::test]
+async fn failed_notification_is_saved_as_dead_letter() {
+    // Arrange
+    let app = spawn_app().await;
+    let created = create_user_with_failing_notifier(&app).await;
+
+    // Act
+    let response = app.get_dead_letters().await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let actual: serde_json::Value = response.json().await.unwrap();
+    let dead_letters = actual["data"].as_array().unwrap();
+    assert_eq!(dead_letters.len(), 1);
+    assert_eq!(dead_letters[0]["event_type"], "user_created");
+    assert_eq!(dead_letters[0]["user_id"], created["data"]["id"]);
+    assert_eq!(dead_letters[0]["retry_count"], 0);
+    assert_eq!(
+        dead_letters[0]["failure_reason"],
+        "notifier is switched off"
+    );
+}
+
+#[tokio::test]
+async fn redrive_delivers_dead_letter() {
+    // Arrange
+    let app = spawn_app().await;
+    create_user_with_failing_notifier(&app).await;
+    let dead_letters: serde_json::Value = app.get_dead_letters().await.json().await.unwrap();
+    let id = dead_letters["data"][0]["id"].as_str().unwrap();
+    app.notifier_failing.store(false, Ordering::SeqCst);
+
+    // Act
+    let response = app.post_dead_letter_redrive(id).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let actual: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(actual["data"]["status"], "delivered");
+    let email = EmailAddress::new("user@example.com").unwrap();
+    assert!(app.user_email_map.read().await.contains_key(&email));
+    let remaining = sqlx::query_scalar!("SELECT COUNT(*) FROM notification_dead_letters;")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(remaining, Some(0));
+}
+
+#[tokio::test]
+async fn redrive_keeps_dead_letter_if_delivery_fails_again() {
+    // Arrange
+    let app = spawn_app().await;
+    create_user_with_failing_notifier(&app).await;
+    let dead_letters: serde_json::Value = app.get_dead_letters().await.json().await.unwrap();
+    let id = dead_letters["data"][0]["id"].as_str().unwrap();
+
+    // Act
+    let response = app.post_dead_letter_redrive(id).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let actual: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(actual["data"]["status"], "failed");
+    assert_eq!(actual["data"]["dead_letter"]["id"], id);
+    assert_eq!(actual["data"]["dead_letter"]["retry_count"], 1);
+}
+
+#[tokio::test]
+async fn redrive_discards_dead_letter_of_erased_user() {
+    // Arrange
+    let app = spawn_app().await;
+    let created = create_user_with_failing_notifier(&app).await;
+    let dead_letters: serde_json::Value = app.get_dead_letters().await.json().await.unwrap();
+    let id = dead_letters["data"][0]["id"].as_str().unwrap();
+    app.post_user_erasure(created["data"]["id"].as_str().unwrap())
+        .await;
+    app.notifier_failing.store(false, Ordering::SeqCst);
+
+    // Act
+    let response = app.post_dead_letter_redrive(id).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let actual: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(actual["data"]["status"], "discarded");
+    assert!(app.user_email_map.read().await.is_empty());
+    let remaining = sqlx::query_scalar!("SELECT COUNT(*) FROM notification_dead_letters;")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(remaining, Some(0));
+}
+
+#[tokio::test]
+async fn redrive_returns_404_for_unknown_dead_letter() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app
+        .post_dead_letter_redrive("b2d5b8c2-8c1f-4f22-9a43-2a1f5e5b8c3d")
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 404);
+    let actual_msg: serde_json::Value = response.json().await.unwrap();
+    insta::assert_json_snapshot!(actual_msg);
+}
+
+#[tokio::test]
+async fn dead_letters_are_only_available_to_admins() {
+    // Arrange
+    let app = spawn_app().await;
+    let created = create_user_with_failing_notifier(&app).await;
+    let user_token = app.user_token(created["data"]["id"].as_str().unwrap());
+    let id = "b2d5b8c2-8c1f-4f22-9a43-2a1f5e5b8c3d";
+    let redrive = format!("/api/admin/dead-letters/{id}/redrive");
+
+    // Act
+    let anonymous_list = app.get_as("/api/admin/dead-letters", None).await;
+    let user_list = app
+        .get_as("/api/admin/dead-letters", Some(&user_token))
+        .await;
+    let anonymous_redrive = app.post_as(&redrive, None).await;
+    let user_redrive = app.post_as(&redrive, Some(&user_token)).await;
+
+    // Assert
+    assert_eq!(anonymous_list.status().as_u16(), 401);
+    assert_eq!(user_list.status().as_u16(), 403);
+    assert_eq!(anonymous_redrive.status().as_u16(), 401);
+    assert_eq!(user_redrive.status().as_u16(), 403);
+}
@@ -0,0 +1,54 @@
+use std::{collections::HashMap, sync::Arc};
+
+use crowdsource::{
+    dev_seed,
+    domain::crowdsrc::{models::terms::TermsVersion, ports::CrowdSrcService, service::Service},
+    outbound::{
+        collecting_user_notifier::CollectingUserNotifier, sqlx_user_repository::SqlxUserRepository,
+    },
+};
+use futures::TryStreamExt;
+use tokio::sync::RwLock;
+
+use crate::helpers::spawn_app;
+
+#[tokio::test]
+async fn seed_creates_users_through_service() {
+    // Arrange
+    let app = spawn_app().await;
+    let terms = TermsVersion::new("2026-01-30").unwrap();
+    let service = Service::new(
+        SqlxUserRepository::new(app.db_pool.clone()),
+        CollectingUserNotifier::new(Arc::new(RwLock::new(HashMap::new()))),
+        terms.clone(),
+    );
+
+    // Act
+    let summary = dev_seed::seed(&service, &terms).await.unwrap();
+
+    // Assert
+    assert_eq!(summary.created, 6);
+    assert_eq!(summary.erased, 1);
+    let users: Vec<_> = service.stream_users().try_collect().await.unwrap();
+    assert_eq!(users.len(), 6);
+}
+
+#[tokio::test]
+async fn seeding_again_skips_existing_users() {
+    // Arrange
+    let app = spawn_app().await;
+    let terms = TermsVersion::new("2026-01-30").unwrap();
+    let service = Service::new(
+        SqlxUserRepository::new(app.db_pool.clone()),
+        CollectingUserNotifier::new(Arc::new(RwLock::new(HashMap::new()))),
+        terms.clone(),
+    );
+    dev_seed::seed(&service, &terms).await.unwrap();
+
+    // Act
+    let summary = dev_seed::seed(&service, &terms).await.unwrap();
+
+    // Assert
+    assert_eq!(summary.existing, 5);
+    assert_eq!(summary.created, 1);
+}
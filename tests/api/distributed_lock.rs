@@ -0,0 +1,104 @@
+use std::time::Duration;
+
+use crowdsource::{
+    domain::crowdsrc::ports::DistributedLock, outbound::sqlx_advisory_lock::SqlxAdvisoryLock,
+};
+use tokio::sync::oneshot;
+
+use crate::helpers::spawn_app;
+
+#[tokio::test]
+async fn try_with_lock_skips_work_while_lock_is_held() {
+    // Arrange
+    let app = spawn_app().await;
+    let first = SqlxAdvisoryLock::new(app.db_pool.clone());
+    let second = SqlxAdvisoryLock::new(app.db_pool.clone());
+    let (locked, wait_locked) = oneshot::channel();
+    let (release, wait_release) = oneshot::channel::<()>();
+    let holder = tokio::spawn(async move {
+        first
+            .with_lock("job", async move {
+                locked.send(()).unwrap();
+                wait_release.await.unwrap();
+            })
+            .await
+            .unwrap();
+    });
+    wait_locked.await.unwrap();
+
+    // Act
+    let while_held = second.try_with_lock("job", async { "ran" }).await.unwrap();
+    let other_lock = second
+        .try_with_lock("other", async { "ran" })
+        .await
+        .unwrap();
+    release.send(()).unwrap();
+    holder.await.unwrap();
+    let once_released = second.try_with_lock("job", async { "ran" }).await.unwrap();
+
+    // Assert
+    assert_eq!(while_held, None);
+    assert_eq!(other_lock, Some("ran"));
+    assert_eq!(once_released, Some("ran"));
+}
+
+#[tokio::test]
+async fn with_lock_waits_for_lock_to_be_released() {
+    // Arrange
+    let app = spawn_app().await;
+    let first = SqlxAdvisoryLock::new(app.db_pool.clone());
+    let second = SqlxAdvisoryLock::new(app.db_pool.clone());
+    let (locked, wait_locked) = oneshot::channel();
+    let holder = tokio::spawn(async move {
+        first
+            .with_lock("job", async move {
+                locked.send(()).unwrap();
+                tokio::time::sleep(Duration::from_millis(200)).await;
+                tokio::time::Instant::now()
+            })
+            .await
+            .unwrap()
+    });
+    wait_locked.await.unwrap();
+
+    // Act
+    let started = second
+        .with_lock("job", async { tokio::time::Instant::now() })
+        .await
+        .unwrap();
+
+    // Assert
+    let released = holder.await.unwrap();
+    assert!(started >= released);
+}
+
+#[tokio::test]
+async fn lock_is_released_when_work_is_dropped() {
+    // Arrange
+    let app = spawn_app().await;
+    let lock = SqlxAdvisoryLock::new(app.db_pool.clone());
+    let (locked, wait_locked) = oneshot::channel();
+    let holder = tokio::spawn({
+        let lock = lock.clone();
+        async move {
+            lock.with_lock("job", async move {
+                locked.send(()).unwrap();
+                std::future::pending::<()>().await;
+            })
+            .await
+        }
+    });
+    wait_locked.await.unwrap();
+
+    // Act
+    holder.abort();
+    let _ = holder.await;
+    let after_drop = tokio::time::timeout(
+        Duration::from_secs(5),
+        lock.with_lock("job", async { "ran" }),
+    )
+    .await;
+
+    // Assert
+    assert_eq!(after_drop.unwrap().unwrap(), "ran");
+}
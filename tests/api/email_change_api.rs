@@ -0,0 +1,303 @@
+use std::sync::atomic::Ordering;
+
+use crowdsource::domain::crowdsrc::models::user::EmailAddress;
+use uuid::Uuid;
+
+use crate::helpers::{TestApp, spawn_app};
+
+async fn create_user(app: &TestApp, username: &str, email: &str) -> String {
+    let body = format!(
+        r#"{{
+        "email_address":"{email}",
+        "username":"{username}",
+        "accepted_terms_version":"2026-01-30"
+    }}"#
+    );
+    let created: serde_json::Value = app.post_users(body).await.json().await.unwrap();
+    created["data"]["id"].as_str().unwrap().to_string()
+}
+
+async fn request_email_change(app: &TestApp, id: &str, email: &str) -> reqwest::Response {
+    app.post_user_email_change(id, format!(r#"{{"email":"{email}"}}"#))
+        .await
+}
+
+async fn confirm_email_change(app: &TestApp, id: &str, token: &str) -> reqwest::Response {
+    app.post_user_email_change_confirmation(id, format!(r#"{{"token":"{token}"}}"#))
+        .await
+}
+
+/// The token sent to `email`, as collected by the notifier.
+async fn token_sent_to(app: &TestApp, email: &str) -> String {
+    app.user_email_map
+        .read()
+        .await
+        .get(&EmailAddress::new(email).unwrap())
+        .cloned()
+        .expect("no token was sent")
+}
+
+async fn stored_email(app: &TestApp, id: &str) -> String {
+    sqlx::query_scalar!(
+        "SELECT email FROM users WHERE id = $1",
+        Uuid::parse_str(id).unwrap()
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn email_change_takes_effect_once_confirmed() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = create_user(&app, "user", "user@example.com").await;
+
+    // Act
+    let requested = request_email_change(&app, &id, "new@example.com").await;
+    let email_before_confirmation = stored_email(&app, &id).await;
+    let token = token_sent_to(&app, "new@example.com").await;
+    let confirmed = confirm_email_change(&app, &id, &token).await;
+
+    // Assert
+    assert_eq!(requested.status().as_u16(), 202);
+    let body: serde_json::Value = requested.json().await.unwrap();
+    assert_eq!(body["data"]["new_email"], "new@example.com");
+    assert_eq!(email_before_confirmation, "user@example.com");
+    assert_eq!(confirmed.status().as_u16(), 200);
+    let body: serde_json::Value = confirmed.json().await.unwrap();
+    assert_eq!(body["data"]["email"], "new@example.com");
+    assert_eq!(stored_email(&app, &id).await, "new@example.com");
+}
+
+#[tokio::test]
+async fn confirmed_email_change_verifies_email() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = create_user(&app, "user", "user@example.com").await;
+    let export_before: serde_json::Value =
+        app.get_user_data_export(&id).await.json().await.unwrap();
+    request_email_change(&app, &id, "new@example.com").await;
+    let token = token_sent_to(&app, "new@example.com").await;
+
+    // Act
+    confirm_email_change(&app, &id, &token).await;
+
+    // Assert
+    let export: serde_json::Value = app.get_user_data_export(&id).await.json().await.unwrap();
+    assert_eq!(export_before["data"]["user"]["email_verified"], false);
+    assert_eq!(export["data"]["user"]["email_verified"], true);
+}
+
+#[tokio::test]
+async fn email_change_can_only_be_confirmed_once() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = create_user(&app, "user", "user@example.com").await;
+    request_email_change(&app, &id, "new@example.com").await;
+    let token = token_sent_to(&app, "new@example.com").await;
+    confirm_email_change(&app, &id, &token).await;
+
+    // Act
+    let response = confirm_email_change(&app, &id, &token).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 404);
+}
+
+#[tokio::test]
+async fn email_change_returns_403_for_invalid_token() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = create_user(&app, "user", "user@example.com").await;
+    request_email_change(&app, &id, "new@example.com").await;
+
+    // Act
+    let response = confirm_email_change(&app, &id, "not-the-token").await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 403);
+    assert_eq!(stored_email(&app, &id).await, "user@example.com");
+}
+
+#[tokio::test]
+async fn expired_email_change_is_discarded() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = create_user(&app, "user", "user@example.com").await;
+    request_email_change(&app, &id, "new@example.com").await;
+    let token = token_sent_to(&app, "new@example.com").await;
+    sqlx::query!(
+        "UPDATE email_changes SET expires_at = NOW() - INTERVAL '1 minute' WHERE user_id = $1",
+        Uuid::parse_str(&id).unwrap()
+    )
+    .execute(&app.db_pool)
+    .await
+    .unwrap();
+
+    // Act
+    let expired = confirm_email_change(&app, &id, &token).await;
+    let retried = confirm_email_change(&app, &id, &token).await;
+
+    // Assert
+    assert_eq!(expired.status().as_u16(), 409);
+    assert_eq!(retried.status().as_u16(), 404);
+    assert_eq!(stored_email(&app, &id).await, "user@example.com");
+}
+
+#[tokio::test]
+async fn cancelled_email_change_can_not_be_confirmed() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = create_user(&app, "user", "user@example.com").await;
+    request_email_change(&app, &id, "new@example.com").await;
+    let token = token_sent_to(&app, "new@example.com").await;
+
+    // Act
+    let cancelled = app.delete_user_email_change(&id).await;
+    let confirmed = confirm_email_change(&app, &id, &token).await;
+    let cancelled_again = app.delete_user_email_change(&id).await;
+
+    // Assert
+    assert_eq!(cancelled.status().as_u16(), 200);
+    assert_eq!(confirmed.status().as_u16(), 404);
+    assert_eq!(cancelled_again.status().as_u16(), 404);
+    assert_eq!(stored_email(&app, &id).await, "user@example.com");
+}
+
+#[tokio::test]
+async fn new_request_replaces_pending_email_change() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = create_user(&app, "user", "user@example.com").await;
+    request_email_change(&app, &id, "first@example.com").await;
+    let first_token = token_sent_to(&app, "first@example.com").await;
+    request_email_change(&app, &id, "second@example.com").await;
+
+    // Act
+    let with_first_token = confirm_email_change(&app, &id, &first_token).await;
+    let second_token = token_sent_to(&app, "second@example.com").await;
+    let with_second_token = confirm_email_change(&app, &id, &second_token).await;
+
+    // Assert
+    assert_eq!(with_first_token.status().as_u16(), 403);
+    assert_eq!(with_second_token.status().as_u16(), 200);
+    assert_eq!(stored_email(&app, &id).await, "second@example.com");
+}
+
+#[tokio::test]
+async fn email_change_returns_422_for_email_of_other_user() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = create_user(&app, "user", "user@example.com").await;
+    create_user(&app, "other", "other@example.com").await;
+
+    // Act
+    let response = request_email_change(&app, &id, "other@Example.COM").await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 422);
+}
+
+#[tokio::test]
+async fn email_change_returns_422_for_email_taken_before_confirmation() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = create_user(&app, "user", "user@example.com").await;
+    request_email_change(&app, &id, "new@example.com").await;
+    let token = token_sent_to(&app, "new@example.com").await;
+    create_user(&app, "other", "new@example.com").await;
+
+    // Act
+    let response = confirm_email_change(&app, &id, &token).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 422);
+    assert_eq!(stored_email(&app, &id).await, "user@example.com");
+}
+
+#[tokio::test]
+async fn email_change_returns_422_for_invalid_email() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = create_user(&app, "user", "user@example.com").await;
+
+    // Act
+    let response = request_email_change(&app, &id, "not-an-email").await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 422);
+}
+
+#[tokio::test]
+async fn email_change_returns_404_for_unknown_user() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = request_email_change(
+        &app,
+        "b2d5b8c2-8c1f-4f22-9a43-2a1f5e5b8c3d",
+        "new@example.com",
+    )
+    .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 404);
+}
+
+#[tokio::test]
+async fn email_change_is_discarded_when_token_can_not_be_sent() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = create_user(&app, "user", "user@example.com").await;
+    app.notifier_failing.store(true, Ordering::SeqCst);
+
+    // Act
+    let response = request_email_change(&app, &id, "new@example.com").await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 202);
+    app.notifier_failing.store(false, Ordering::SeqCst);
+    assert_eq!(
+        app.delete_user_email_change(&id).await.status().as_u16(),
+        404
+    );
+}
+
+#[tokio::test]
+async fn only_the_user_changes_their_email() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = create_user(&app, "user", "user@example.com").await;
+    let other = create_user(&app, "other", "other@example.com").await;
+    let other_token = app.user_token(&other);
+    let body = r#"{"email":"attacker@example.com"}"#;
+
+    // Act
+    let anonymous = app.post_user_email_change_as(&id, body.into(), None).await;
+    let other_user = app
+        .post_user_email_change_as(&id, body.into(), Some(&other_token))
+        .await;
+    let admin = app
+        .post_user_email_change_as(&id, body.into(), Some(&app.admin_token()))
+        .await;
+    let anonymous_cancel = app.delete_user_email_change_as(&id, None).await;
+    let other_user_cancel = app
+        .delete_user_email_change_as(&id, Some(&other_token))
+        .await;
+
+    // Assert
+    assert_eq!(anonymous.status().as_u16(), 401);
+    assert_eq!(other_user.status().as_u16(), 403);
+    assert_eq!(admin.status().as_u16(), 403);
+    assert_eq!(anonymous_cancel.status().as_u16(), 401);
+    assert_eq!(other_user_cancel.status().as_u16(), 403);
+    assert!(
+        app.user_email_map
+            .read()
+            .await
+            .get(&EmailAddress::new("attacker@example.com").unwrap())
+            .is_none()
+    );
+}
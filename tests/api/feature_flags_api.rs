@@ -0,0 +1,60 @@
+use std::collections::HashMap;
+
+use crowdsource::configuration::FeatureFlagSettings;
+
+use crate::helpers::{TestAppOptions, spawn_app_with};
+
+fn feature_flags() -> HashMap<String, FeatureFlagSettings> {
+    HashMap::from([
+        (
+            "bulk_import".to_string(),
+            FeatureFlagSettings {
+                enabled: true,
+                tenants: HashMap::from([("acme".to_string(), false)]),
+            },
+        ),
+        (
+            "new_editor".to_string(),
+            FeatureFlagSettings {
+                enabled: false,
+                tenants: HashMap::from([("acme".to_string(), true)]),
+            },
+        ),
+    ])
+}
+
+#[tokio::test]
+async fn features_lists_globally_enabled_flags() {
+    // Arrange
+    let app = spawn_app_with(TestAppOptions {
+        feature_flags: feature_flags(),
+        ..Default::default()
+    })
+    .await;
+
+    // Act
+    let response = app.get_features(None).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["data"], serde_json::json!(["bulk_import"]));
+}
+
+#[tokio::test]
+async fn features_applies_tenant_overrides() {
+    // Arrange
+    let app = spawn_app_with(TestAppOptions {
+        feature_flags: feature_flags(),
+        ..Default::default()
+    })
+    .await;
+
+    // Act
+    let response = app.get_features(Some("acme")).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["data"], serde_json::json!(["new_editor"]));
+}
@@ -1,22 +1,82 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    path::Path,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
 
 use crowdsource::{
-    configuration::{DatabaseSettings, get_configuration},
-    domain::crowdsrc::{models::user::EmailAddress, service::Service},
-    inbound::http::HttpServer,
+    configuration::{DatabaseSettings, FeatureFlagSettings, get_configuration},
+    domain::crowdsrc::{
+        analytics_buffer::{AnalyticsBuffer, BufferPolicy},
+        models::{
+            access_token::{AccessToken, Principal},
+            email_change::{EmailChangeToken, PendingEmailChange},
+            runtime_config::RuntimeConfig,
+            signed_url::SigningKey,
+            terms::TermsVersion,
+            throttle::ThrottlePolicy,
+            user::{EmailAddress, NotifyUserError, User, UserNamePolicy},
+        },
+        ports::UserNotifier,
+        service::Service,
+    },
+    inbound::http::{ChallengedRoute, HttpServer, RouteLimits, RuntimeConfigControl},
+    migrations,
     outbound::{
-        collecting_user_notifier::CollectingUserNotifier, sqlx_user_repository::SqlxUserRepository,
+        collecting_user_notifier::CollectingUserNotifier, config_feature_flags::ConfigFeatureFlags,
+        in_memory_submission_throttle::InMemorySubmissionThrottle,
+        proof_of_work_challenge::ProofOfWorkChallenge, sqlx_analytics_sink::SqlxAnalyticsSink,
+        sqlx_maintenance_switch::SqlxMaintenanceSwitch,
+        sqlx_runtime_config_store::SqlxRuntimeConfigStore,
+        sqlx_transaction::SqlxTransactionManager, sqlx_user_repository::SqlxUserRepository,
     },
 };
 use sqlx::{Connection, Executor, PgConnection, PgPool};
-use tokio::sync::RwLock;
+use tokio::sync::{RwLock, watch};
 use uuid::Uuid;
 
+/// A [CollectingUserNotifier] that fails while `failing` is set.
+#[derive(Clone)]
+struct SwitchableUserNotifier {
+    inner: CollectingUserNotifier,
+    failing: Arc<AtomicBool>,
+}
+
+impl UserNotifier for SwitchableUserNotifier {
+    async fn user_created(&self, user: &User) -> Result<(), NotifyUserError> {
+        if self.failing.load(Ordering::SeqCst) {
+            return Err(anyhow::anyhow!("notifier is switched off").into());
+        }
+        self.inner.user_created(user).await
+    }
+
+    async fn email_change_requested(
+        &self,
+        user: &User,
+        change: &PendingEmailChange,
+        token: &EmailChangeToken,
+    ) -> Result<(), NotifyUserError> {
+        if self.failing.load(Ordering::SeqCst) {
+            return Err(anyhow::anyhow!("notifier is switched off").into());
+        }
+        self.inner.email_change_requested(user, change, token).await
+    }
+}
+
 pub struct TestApp {
-    user_email_map: Arc<RwLock<HashMap<EmailAddress, String>>>,
+    pub user_email_map: Arc<RwLock<HashMap<EmailAddress, String>>>,
+    /// Makes all notifications fail while set.
+    pub notifier_failing: Arc<AtomicBool>,
     address: String,
     pub db_pool: PgPool,
     pub api_client: reqwest::Client,
+    /// The runtime config in effect, as seen by the components applying it.
+    pub runtime_config: watch::Receiver<RuntimeConfig>,
 }
 
 impl TestApp {
@@ -24,6 +84,16 @@ impl TestApp {
         format!("http://{}{}", dbg!(&self.address), path)
     }
 
+    /// An access token for an admin, which the helpers authenticate with unless told otherwise.
+    pub fn admin_token(&self) -> String {
+        access_token(Principal::Admin)
+    }
+
+    /// An access token for the user with id `id`.
+    pub fn user_token(&self, id: &str) -> String {
+        access_token(Principal::User(id.parse().unwrap()))
+    }
+
     pub async fn post_users(&self, body: String) -> reqwest::Response {
         self.api_client
             .post(self.url("/api/users"))
@@ -33,18 +103,491 @@ impl TestApp {
             .await
             .expect("Failed to execute request")
     }
+
+    pub async fn get_maintenance(&self) -> reqwest::Response {
+        self.get_as("/api/admin/maintenance", Some(&self.admin_token()))
+            .await
+    }
+
+    pub async fn put_maintenance(&self, enabled: bool) -> reqwest::Response {
+        self.put_maintenance_as(enabled, Some(&self.admin_token()))
+            .await
+    }
+
+    /// Switches maintenance mode, authenticated with `token`, if any.
+    pub async fn put_maintenance_as(
+        &self,
+        enabled: bool,
+        token: Option<&str>,
+    ) -> reqwest::Response {
+        authenticated(
+            self.api_client.put(self.url("/api/admin/maintenance")),
+            token,
+        )
+        .header("Content-Type", "application/json")
+        .body(format!(r#"{{"enabled":{enabled}}}"#))
+        .send()
+        .await
+        .expect("Failed to execute request")
+    }
+
+    pub async fn get_runtime_config(&self) -> reqwest::Response {
+        self.get_as("/api/admin/config", Some(&self.admin_token()))
+            .await
+    }
+
+    pub async fn patch_runtime_config(&self, body: String) -> reqwest::Response {
+        self.patch_runtime_config_as(body, Some(&self.admin_token()))
+            .await
+    }
+
+    /// Tunes the runtime config, authenticated with `token`, if any.
+    pub async fn patch_runtime_config_as(
+        &self,
+        body: String,
+        token: Option<&str>,
+    ) -> reqwest::Response {
+        authenticated(self.api_client.patch(self.url("/api/admin/config")), token)
+            .header("Content-Type", "application/json")
+            .body(body)
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
+    pub async fn post_users_with_challenge_solution(
+        &self,
+        body: String,
+        solution: &str,
+    ) -> reqwest::Response {
+        self.api_client
+            .post(self.url("/api/users"))
+            .header("Content-Type", "application/json")
+            .header("X-Challenge-Solution", solution)
+            .body(body)
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
+    pub async fn get_challenge(&self) -> reqwest::Response {
+        self.api_client
+            .get(self.url("/api/challenge"))
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
+    pub async fn get_features(&self, tenant: Option<&str>) -> reqwest::Response {
+        let path = match tenant {
+            Some(tenant) => format!("/api/features?tenant={tenant}"),
+            None => "/api/features".to_string(),
+        };
+        self.api_client
+            .get(self.url(&path))
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
+    pub async fn post_users_with_language(
+        &self,
+        body: String,
+        accept_language: &str,
+    ) -> reqwest::Response {
+        self.api_client
+            .post(self.url("/api/users"))
+            .header("Content-Type", "application/json")
+            .header("Accept-Language", accept_language)
+            .body(body)
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
+    pub async fn get_user_data_export(&self, id: &str) -> reqwest::Response {
+        self.get_user_data_export_as(id, Some(&self.admin_token()))
+            .await
+    }
+
+    /// Gets the data export of user `id`, authenticated with `token`, if any.
+    pub async fn get_user_data_export_as(
+        &self,
+        id: &str,
+        token: Option<&str>,
+    ) -> reqwest::Response {
+        authenticated(
+            self.api_client
+                .get(self.url(&format!("/api/users/{id}/data-export"))),
+            token,
+        )
+        .send()
+        .await
+        .expect("Failed to execute request")
+    }
+
+    pub async fn post_user_data_export_share(&self, id: &str) -> reqwest::Response {
+        self.post_as(
+            &format!("/api/users/{id}/data-export/share"),
+            Some(&self.user_token(id)),
+        )
+        .await
+    }
+
+    /// Gets `path_and_query`, such as a shared link, as is.
+    pub async fn get(&self, path_and_query: &str) -> reqwest::Response {
+        self.get_as(path_and_query, None).await
+    }
+
+    /// Gets `path_and_query`, authenticated with `token`, if any.
+    pub async fn get_as(&self, path_and_query: &str, token: Option<&str>) -> reqwest::Response {
+        authenticated(self.api_client.get(self.url(path_and_query)), token)
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
+    /// Posts to `path` without a body, authenticated with `token`, if any.
+    pub async fn post_as(&self, path: &str, token: Option<&str>) -> reqwest::Response {
+        authenticated(self.api_client.post(self.url(path)), token)
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
+    pub async fn get_user(&self, id: &str) -> reqwest::Response {
+        self.get_as(&format!("/api/users/{id}"), Some(&self.admin_token()))
+            .await
+    }
+
+    pub async fn get_user_activity(&self, id: &str, query: &str) -> reqwest::Response {
+        self.get_as(
+            &format!("/api/users/{id}/activity{query}"),
+            Some(&self.user_token(id)),
+        )
+        .await
+    }
+
+    pub async fn post_user_terms_acceptance(&self, id: &str, body: String) -> reqwest::Response {
+        self.post_user_terms_acceptance_as(id, body, Some(&self.user_token(id)))
+            .await
+    }
+
+    /// Accepts the terms of service for user `id`, authenticated with `token`, if any.
+    pub async fn post_user_terms_acceptance_as(
+        &self,
+        id: &str,
+        body: String,
+        token: Option<&str>,
+    ) -> reqwest::Response {
+        authenticated(
+            self.api_client
+                .post(self.url(&format!("/api/users/{id}/terms-acceptance")))
+                .header("Content-Type", "application/json")
+                .body(body),
+            token,
+        )
+        .send()
+        .await
+        .expect("Failed to execute request")
+    }
+
+    pub async fn get_dead_letters(&self) -> reqwest::Response {
+        self.get_as("/api/admin/dead-letters", Some(&self.admin_token()))
+            .await
+    }
+
+    pub async fn post_dead_letter_redrive(&self, id: &str) -> reqwest::Response {
+        self.post_as(
+            &format!("/api/admin/dead-letters/{id}/redrive"),
+            Some(&self.admin_token()),
+        )
+        .await
+    }
+
+    pub async fn get_users_export_csv(&self, query: &str) -> reqwest::Response {
+        self.get_as(
+            &format!("/api/admin/users/export.csv{query}"),
+            Some(&self.admin_token()),
+        )
+        .await
+    }
+
+    pub async fn get_admin_user_search(&self, query: &str) -> reqwest::Response {
+        self.get_as(
+            &format!("/api/admin/users/search{query}"),
+            Some(&self.admin_token()),
+        )
+        .await
+    }
+
+    pub async fn post_analytics_events(
+        &self,
+        user_id: &str,
+        body: serde_json::Value,
+    ) -> reqwest::Response {
+        self.post_analytics_events_as(body, Some(&self.user_token(user_id)))
+            .await
+    }
+
+    /// Records analytics events, authenticated with `token`, if any.
+    pub async fn post_analytics_events_as(
+        &self,
+        body: serde_json::Value,
+        token: Option<&str>,
+    ) -> reqwest::Response {
+        authenticated(
+            self.api_client
+                .post(self.url("/api/analytics/events"))
+                .json(&body),
+            token,
+        )
+        .send()
+        .await
+        .expect("Failed to execute request")
+    }
+
+    /// Gets an admin page, signed in as a browser would be with the admin token as password.
+    pub async fn get_admin_page(&self, path: &str) -> reqwest::Response {
+        self.api_client
+            .get(self.url(&format!("/admin{path}")))
+            .basic_auth("admin", Some(self.admin_token()))
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
+    pub async fn get_with_accept(&self, path: &str, accept: &str) -> reqwest::Response {
+        self.api_client
+            .get(self.url(path))
+            .header("Accept", accept)
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
+    pub async fn post_user_erasure(&self, id: &str) -> reqwest::Response {
+        self.post_user_erasure_as(id, Some(&self.admin_token()))
+            .await
+    }
+
+    /// Erases user `id`, authenticated with `token`, if any.
+    pub async fn post_user_erasure_as(&self, id: &str, token: Option<&str>) -> reqwest::Response {
+        authenticated(
+            self.api_client
+                .post(self.url(&format!("/api/users/{id}/erasure"))),
+            token,
+        )
+        .send()
+        .await
+        .expect("Failed to execute request")
+    }
+
+    pub async fn post_user_email_change(&self, id: &str, body: String) -> reqwest::Response {
+        self.post_user_email_change_as(id, body, Some(&self.user_token(id)))
+            .await
+    }
+
+    /// Requests an email change for user `id`, authenticated with `token`, if any.
+    pub async fn post_user_email_change_as(
+        &self,
+        id: &str,
+        body: String,
+        token: Option<&str>,
+    ) -> reqwest::Response {
+        authenticated(
+            self.api_client
+                .post(self.url(&format!("/api/users/{id}/email-change")))
+                .header("Content-Type", "application/json")
+                .body(body),
+            token,
+        )
+        .send()
+        .await
+        .expect("Failed to execute request")
+    }
+
+    pub async fn post_user_email_change_confirmation(
+        &self,
+        id: &str,
+        body: String,
+    ) -> reqwest::Response {
+        self.api_client
+            .post(self.url(&format!("/api/users/{id}/email-change/confirmation")))
+            .header("Content-Type", "application/json")
+            .body(body)
+            .send()
+            .await
+            .expect("Failed to execute request")
+    }
+
+    pub async fn get_user_notifications(&self, id: &str, query: &str) -> reqwest::Response {
+        self.get_as(
+            &format!("/api/users/{id}/notifications{query}"),
+            Some(&self.user_token(id)),
+        )
+        .await
+    }
+
+    pub async fn get_user_unread_notification_count(&self, id: &str) -> reqwest::Response {
+        self.get_as(
+            &format!("/api/users/{id}/notifications/unread-count"),
+            Some(&self.user_token(id)),
+        )
+        .await
+    }
+
+    pub async fn post_user_notification_read(
+        &self,
+        id: &str,
+        notification_id: &str,
+    ) -> reqwest::Response {
+        self.post_as(
+            &format!("/api/users/{id}/notifications/{notification_id}/read"),
+            Some(&self.user_token(id)),
+        )
+        .await
+    }
+
+    pub async fn post_user_notifications_read(&self, id: &str) -> reqwest::Response {
+        self.post_as(
+            &format!("/api/users/{id}/notifications/read"),
+            Some(&self.user_token(id)),
+        )
+        .await
+    }
+
+    pub async fn delete_user_email_change(&self, id: &str) -> reqwest::Response {
+        self.delete_user_email_change_as(id, Some(&self.user_token(id)))
+            .await
+    }
+
+    /// Cancels the email change of user `id`, authenticated with `token`, if any.
+    pub async fn delete_user_email_change_as(
+        &self,
+        id: &str,
+        token: Option<&str>,
+    ) -> reqwest::Response {
+        authenticated(
+            self.api_client
+                .delete(self.url(&format!("/api/users/{id}/email-change"))),
+            token,
+        )
+        .send()
+        .await
+        .expect("Failed to execute request")
+    }
+}
+
+/// Options for [spawn_app_with], the defaults being those of [spawn_app].
+#[derive(Default)]
+pub struct TestAppOptions<'a> {
+    pub static_dir: Option<&'a Path>,
+    pub challenged_routes: &'a [ChallengedRoute],
+    /// Defaults to more signups than any test makes.
+    pub throttle_policy: Option<ThrottlePolicy>,
+    pub throttle_allowlist: &'a [IpAddr],
+    pub feature_flags: HashMap<String, FeatureFlagSettings>,
+    pub maintenance_mode: bool,
+    /// Routes served under `/api` alongside the built-in ones.
+    pub api_extension: Option<axum::Router>,
+}
+
+/// The key proof of work challenges of a [TestApp] are signed with.
+pub const CHALLENGE_KEY: &[u8] = b"test challenge key";
+
+/// The key shared links of a [TestApp] are signed with.
+pub const SIGNED_URL_KEY: &[u8] = b"test signed url key";
+
+/// The key access tokens of a [TestApp] are signed with.
+pub const ACCESS_TOKEN_KEY: &[u8] = b"test access token key";
+
+/// An access token for `principal`, signed with [ACCESS_TOKEN_KEY].
+pub fn access_token(principal: Principal) -> String {
+    AccessToken::issue(
+        &SigningKey::new(ACCESS_TOKEN_KEY),
+        principal,
+        chrono::Utc::now() + chrono::TimeDelta::hours(1),
+    )
+    .to_string()
+}
+
+/// `request`, authenticated with `token`, if any.
+fn authenticated(request: reqwest::RequestBuilder, token: Option<&str>) -> reqwest::RequestBuilder {
+    match token {
+        Some(token) => request.bearer_auth(token),
+        None => request,
+    }
 }
 
 pub async fn spawn_app() -> TestApp {
+    spawn_app_with(TestAppOptions::default()).await
+}
+
+pub async fn spawn_app_with(options: TestAppOptions<'_>) -> TestApp {
     let mut configuration = get_configuration().expect("Failed to read configuration");
     configuration.database.database_name = Uuid::new_v4().to_string();
     let db_pool = configure_database(&configuration.database).await;
     let user_email_map = Arc::new(RwLock::new(HashMap::new()));
     let user_repo = SqlxUserRepository::new(db_pool.clone());
-    let user_notifier = CollectingUserNotifier::new(user_email_map.clone());
-    let crwdsrc_service = Service::new(user_repo, user_notifier);
-    let config = crowdsource::inbound::http::HttpServerConfig { port: "0" };
-    let server = HttpServer::new(crwdsrc_service, config).await.unwrap();
+    let notifier_failing = Arc::new(AtomicBool::new(false));
+    let user_notifier = SwitchableUserNotifier {
+        inner: CollectingUserNotifier::new(user_email_map.clone()),
+        failing: notifier_failing.clone(),
+    };
+    let terms_version = TermsVersion::new(&configuration.terms_version).unwrap();
+    let crwdsrc_service = Service::new(user_repo, user_notifier, terms_version).with_analytics(
+        AnalyticsBuffer::spawn(
+            SqlxAnalyticsSink::new(db_pool.clone()),
+            BufferPolicy {
+                capacity: 1000,
+                max_batch: 100,
+                flush_interval: Duration::from_millis(20),
+            },
+        ),
+    );
+    let config = crowdsource::inbound::http::HttpServerConfig {
+        port: "0",
+        static_dir: options.static_dir,
+        challenged_routes: options.challenged_routes,
+        throttle_allowlist: options.throttle_allowlist,
+        api_limits: RouteLimits::default(),
+        admin_limits: RouteLimits::default(),
+        maintenance_mode: options.maintenance_mode,
+        maintenance_retry_after: Duration::from_secs(120),
+        signed_url_key: SIGNED_URL_KEY,
+        access_token_key: ACCESS_TOKEN_KEY,
+        username_policy: UserNamePolicy::default(),
+        reuse_port: false,
+        drain_timeout: Duration::from_secs(5),
+    };
+    let transaction_manager = SqlxTransactionManager::new(db_pool.clone());
+    let abuse_challenge = ProofOfWorkChallenge::new(CHALLENGE_KEY, 4, Duration::from_secs(60));
+    let submission_throttle =
+        InMemorySubmissionThrottle::new(options.throttle_policy.unwrap_or(ThrottlePolicy {
+            max_submissions: 1000,
+            window: Duration::from_secs(60),
+        }));
+    let (runtime_config_updates, runtime_config) = watch::channel(RuntimeConfig::default());
+    let server = HttpServer::new(
+        crwdsrc_service,
+        transaction_manager,
+        abuse_challenge,
+        submission_throttle,
+        ConfigFeatureFlags::new(options.feature_flags),
+        SqlxMaintenanceSwitch::new(db_pool.clone()),
+        RuntimeConfigControl {
+            store: SqlxRuntimeConfigStore::new(db_pool.clone()),
+            updates: runtime_config_updates,
+        },
+        config,
+    )
+    .await
+    .unwrap();
+    let server = match options.api_extension {
+        Some(extension) => server.with_extension(extension),
+        None => server,
+    };
     let address = server.local_addr().unwrap();
     tokio::spawn(async move { server.run().await });
     let api_client = reqwest::Client::builder()
@@ -54,12 +597,29 @@ pub async fn spawn_app() -> TestApp {
     TestApp {
         address: address.to_string(),
         user_email_map,
+        notifier_failing,
         db_pool,
         api_client,
+        runtime_config,
     }
 }
+/// A repository on a new, migrated database of its own.
+pub async fn fresh_user_repository() -> SqlxUserRepository {
+    let mut configuration = get_configuration().expect("Failed to read configuration");
+    configuration.database.database_name = Uuid::new_v4().to_string();
+    SqlxUserRepository::new(configure_database(&configuration.database).await)
+}
+
 pub async fn configure_database(config: &DatabaseSettings) -> PgPool {
-    // Create database
+    let connection_pool = create_database(config).await;
+    migrations::run(&connection_pool)
+        .await
+        .expect("Failed to migrate the database");
+    connection_pool
+}
+
+/// Creates the database of `config`, without migrating it.
+pub async fn create_database(config: &DatabaseSettings) -> PgPool {
     let maintenance_settings = DatabaseSettings {
         database_name: "postgres".to_string(),
         username: "postgres".to_string(),
@@ -74,13 +634,7 @@ pub async fn configure_database(config: &DatabaseSettings) -> PgPool {
         .await
         .expect("Failed to create database");
 
-    // Migrate database
-    let connection_pool = PgPool::connect_with(config.connection_options())
+    PgPool::connect_with(config.connection_options())
         .await
-        .expect("Failed to connect to Postgres.");
-    sqlx::migrate!("./migrations")
-        .run(&connection_pool)
-        .await
-        .expect("Failed to migrate the database");
-    connection_pool
+        .expect("Failed to connect to Postgres.")
 }
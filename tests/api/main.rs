@@ -1,2 +1,28 @@
+mod abuse_challenge_api;
+mod activity_api;
+mod admin_pages;
+mod analytics_api;
+mod api_extension;
+mod backup;
+mod bootstrap;
+#[cfg(feature = "client-models")]
+mod client;
+mod configuration_reload;
+mod crowdsource_app;
+mod dead_letter_api;
+mod dev_seed;
+mod distributed_lock;
+mod email_change_api;
+mod feature_flags_api;
 pub mod helpers;
+mod maintenance_api;
+mod notifications_api;
+mod runtime_config_api;
+mod schedule_store;
+mod shared_links;
+mod static_files;
+mod throttle_api;
 mod user_api;
+mod user_repository;
+mod user_search_api;
+mod users_export_api;
@@ -0,0 +1,89 @@
+use crate::helpers::{TestAppOptions, spawn_app, spawn_app_with};
+
+const USER_BODY: &str = r#"{
+    "email_address":"user@example.com",
+    "username":"user",
+    "accepted_terms_version":"2026-01-30"
+}"#;
+
+#[tokio::test]
+async fn writes_are_refused_with_503_during_maintenance() {
+    // Arrange
+    let app = spawn_app().await;
+    app.put_maintenance(true).await;
+
+    // Act
+    let response = app.post_users(USER_BODY.into()).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 503);
+    assert_eq!(response.headers()["retry-after"], "120");
+}
+
+#[tokio::test]
+async fn reads_are_served_during_maintenance() {
+    // Arrange
+    let app = spawn_app().await;
+    app.put_maintenance(true).await;
+
+    // Act
+    let response = app.get_admin_page("/users").await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn writes_are_accepted_once_maintenance_is_switched_off() {
+    // Arrange
+    let app = spawn_app().await;
+    app.put_maintenance(true).await;
+
+    // Act
+    let switched = app.put_maintenance(false).await;
+    let response = app.post_users(USER_BODY.into()).await;
+
+    // Assert
+    assert_eq!(switched.status().as_u16(), 200);
+    assert_eq!(response.status().as_u16(), 201);
+}
+
+#[tokio::test]
+async fn maintenance_forced_in_configuration_stays_on() {
+    // Arrange
+    let app = spawn_app_with(TestAppOptions {
+        maintenance_mode: true,
+        ..Default::default()
+    })
+    .await;
+
+    // Act
+    app.put_maintenance(false).await;
+    let maintenance = app.get_maintenance().await;
+    let response = app.post_users(USER_BODY.into()).await;
+
+    // Assert
+    let maintenance: serde_json::Value = maintenance.json().await.unwrap();
+    assert_eq!(
+        maintenance["data"],
+        serde_json::json!({"enabled": true, "forced": true})
+    );
+    assert_eq!(response.status().as_u16(), 503);
+}
+
+#[tokio::test]
+async fn only_admins_switch_maintenance_mode() {
+    // Arrange
+    let app = spawn_app().await;
+    let user_token = app.user_token(&uuid::Uuid::new_v4().to_string());
+
+    // Act
+    let anonymous = app.put_maintenance_as(true, None).await;
+    let user = app.put_maintenance_as(true, Some(&user_token)).await;
+    let response = app.post_users(USER_BODY.into()).await;
+
+    // Assert
+    assert_eq!(anonymous.status().as_u16(), 401);
+    assert_eq!(user.status().as_u16(), 403);
+    assert_eq!(response.status().as_u16(), 201);
+}
@@ -0,0 +1,260 @@
+use chrono::{TimeDelta, Utc};
+use crowdsource::{
+    domain::crowdsrc::{
+        models::inbox::{InboxKind, InboxNotification, NOTIFICATION_RETENTION},
+        ports::UserRepository,
+    },
+    outbound::sqlx_user_repository::SqlxUserRepository,
+};
+use uuid::Uuid;
+
+use crate::helpers::{TestApp, spawn_app};
+
+async fn create_user(app: &TestApp, username: &str, email: &str) -> String {
+    let body = format!(
+        r#"{{
+        "email_address":"{email}",
+        "username":"{username}",
+        "accepted_terms_version":"2026-01-30"
+    }}"#
+    );
+    let created: serde_json::Value = app.post_users(body).await.json().await.unwrap();
+    created["data"]["id"].as_str().unwrap().to_string()
+}
+
+async fn notifications(app: &TestApp, id: &str, query: &str) -> serde_json::Value {
+    let response = app.get_user_notifications(id, query).await;
+    assert_eq!(response.status().as_u16(), 200);
+    response.json().await.unwrap()
+}
+
+async fn stored_notifications(app: &TestApp, id: &str) -> i64 {
+    sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "count!" FROM inbox_notifications WHERE user_id = $1"#,
+        Uuid::parse_str(id).unwrap()
+    )
+    .fetch_one(&app.db_pool)
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn domain_events_fill_inbox() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = create_user(&app, "user", "user@example.com").await;
+
+    // Act
+    app.post_user_email_change(&id, r#"{"email":"new@example.com"}"#.into())
+        .await;
+    let inbox = notifications(&app, &id, "").await;
+
+    // Assert
+    let listed = inbox["data"]["notifications"].as_array().unwrap();
+    assert_eq!(listed.len(), 2);
+    assert_eq!(listed[0]["kind"], "email_change_requested");
+    assert_eq!(listed[1]["kind"], "welcome");
+    assert_eq!(listed[1]["message"], "Welcome! Your account is ready.");
+    assert!(listed[1]["read_at"].is_null());
+    assert_eq!(inbox["data"]["unread_count"], 2);
+}
+
+#[tokio::test]
+async fn notification_messages_follow_request_language() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = create_user(&app, "user", "user@example.com").await;
+
+    // Act
+    let response = app
+        .api_client
+        .get(app.url(&format!("/api/users/{id}/notifications")))
+        .bearer_auth(app.user_token(&id))
+        .header("Accept-Language", "sv-SE")
+        .send()
+        .await
+        .expect("Failed to execute request");
+
+    // Assert
+    let inbox: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(
+        inbox["data"]["notifications"][0]["message"],
+        "Välkommen! Ditt konto är klart."
+    );
+}
+
+#[tokio::test]
+async fn marked_notification_is_read() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = create_user(&app, "user", "user@example.com").await;
+    app.post_user_email_change(&id, r#"{"email":"new@example.com"}"#.into())
+        .await;
+    let inbox = notifications(&app, &id, "").await;
+    let welcome_id = inbox["data"]["notifications"][1]["id"].as_str().unwrap();
+
+    // Act
+    let marked = app.post_user_notification_read(&id, welcome_id).await;
+    let marked_again = app.post_user_notification_read(&id, welcome_id).await;
+    let count = app.get_user_unread_notification_count(&id).await;
+    let unread = notifications(&app, &id, "?unread_only=true").await;
+
+    // Assert
+    assert_eq!(marked.status().as_u16(), 204);
+    assert_eq!(marked_again.status().as_u16(), 204);
+    let count: serde_json::Value = count.json().await.unwrap();
+    assert_eq!(count["data"]["unread_count"], 1);
+    let unread = unread["data"]["notifications"].as_array().unwrap();
+    assert_eq!(unread.len(), 1);
+    assert_eq!(unread[0]["kind"], "email_change_requested");
+    let all = notifications(&app, &id, "").await;
+    assert!(all["data"]["notifications"][1]["read_at"].is_string());
+}
+
+#[tokio::test]
+async fn mark_read_returns_404_for_notification_of_other_user() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = create_user(&app, "user", "user@example.com").await;
+    let other = create_user(&app, "other", "other@example.com").await;
+    let inbox = notifications(&app, &id, "").await;
+    let welcome_id = inbox["data"]["notifications"][0]["id"].as_str().unwrap();
+
+    // Act
+    let by_other = app.post_user_notification_read(&other, welcome_id).await;
+    let unknown = app
+        .post_user_notification_read(&id, &Uuid::new_v4().to_string())
+        .await;
+
+    // Assert
+    assert_eq!(by_other.status().as_u16(), 404);
+    assert_eq!(unknown.status().as_u16(), 404);
+    let inbox = notifications(&app, &id, "").await;
+    assert_eq!(inbox["data"]["unread_count"], 1);
+}
+
+#[tokio::test]
+async fn only_the_user_reads_their_notifications() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = create_user(&app, "user", "user@example.com").await;
+    let inbox = notifications(&app, &id, "").await;
+    let welcome_id = inbox["data"]["notifications"][0]["id"].as_str().unwrap();
+    let other_token = app.user_token(&Uuid::new_v4().to_string());
+
+    // Act
+    let anonymous = app
+        .get_as(&format!("/api/users/{id}/notifications"), None)
+        .await;
+    let other_user = app
+        .get_as(
+            &format!("/api/users/{id}/notifications/unread-count"),
+            Some(&other_token),
+        )
+        .await;
+    let admin = app
+        .get_as(
+            &format!("/api/users/{id}/notifications"),
+            Some(&app.admin_token()),
+        )
+        .await;
+    let marked_by_other_user = app
+        .post_as(
+            &format!("/api/users/{id}/notifications/{welcome_id}/read"),
+            Some(&other_token),
+        )
+        .await;
+    let all_marked_anonymously = app
+        .post_as(&format!("/api/users/{id}/notifications/read"), None)
+        .await;
+
+    // Assert
+    assert_eq!(anonymous.status().as_u16(), 401);
+    assert_eq!(other_user.status().as_u16(), 403);
+    assert_eq!(admin.status().as_u16(), 403);
+    assert_eq!(marked_by_other_user.status().as_u16(), 403);
+    assert_eq!(all_marked_anonymously.status().as_u16(), 401);
+    let inbox = notifications(&app, &id, "").await;
+    assert_eq!(inbox["data"]["unread_count"], 1);
+}
+
+#[tokio::test]
+async fn all_notifications_are_marked_read() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = create_user(&app, "user", "user@example.com").await;
+    app.post_user_email_change(&id, r#"{"email":"new@example.com"}"#.into())
+        .await;
+
+    // Act
+    let marked = app.post_user_notifications_read(&id).await;
+    let marked_again = app.post_user_notifications_read(&id).await;
+
+    // Assert
+    let marked: serde_json::Value = marked.json().await.unwrap();
+    assert_eq!(marked["data"]["marked_read"], 2);
+    let marked_again: serde_json::Value = marked_again.json().await.unwrap();
+    assert_eq!(marked_again["data"]["marked_read"], 0);
+    let inbox = notifications(&app, &id, "").await;
+    assert_eq!(inbox["data"]["unread_count"], 0);
+}
+
+#[tokio::test]
+async fn notifications_return_404_for_unknown_user() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = "b2d5b8c2-8c1f-4f22-9a43-2a1f5e5b8c3d";
+
+    // Act
+    let listed = app.get_user_notifications(id, "").await;
+    let count = app.get_user_unread_notification_count(id).await;
+    let marked = app.post_user_notifications_read(id).await;
+
+    // Assert
+    assert_eq!(listed.status().as_u16(), 404);
+    assert_eq!(count.status().as_u16(), 404);
+    assert_eq!(marked.status().as_u16(), 404);
+}
+
+#[tokio::test]
+async fn notifications_past_retention_are_pruned() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = create_user(&app, "user", "user@example.com").await;
+    let repo = SqlxUserRepository::new(app.db_pool.clone());
+    let old = InboxNotification::new(
+        Uuid::new_v4(),
+        Uuid::parse_str(&id).unwrap(),
+        InboxKind::EmailChangeRequested,
+        Utc::now() - NOTIFICATION_RETENTION - TimeDelta::days(1),
+        None,
+    );
+    repo.save_notification(&old).await.unwrap();
+
+    // Act
+    let purged = repo
+        .delete_notifications_before(&(Utc::now() - NOTIFICATION_RETENTION))
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(purged, 1);
+    let inbox = notifications(&app, &id, "").await;
+    let listed = inbox["data"]["notifications"].as_array().unwrap();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0]["kind"], "welcome");
+}
+
+#[tokio::test]
+async fn notifications_are_deleted_on_erasure() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = create_user(&app, "user", "user@example.com").await;
+
+    // Act
+    let response = app.post_user_erasure(&id).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(stored_notifications(&app, &id).await, 0);
+}
@@ -0,0 +1,118 @@
+use crowdsource::domain::crowdsrc::models::runtime_config::LogLevel;
+
+use crate::helpers::spawn_app;
+
+#[tokio::test]
+async fn runtime_config_keeps_configured_values_by_default() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app.get_runtime_config().await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(
+        body["data"],
+        serde_json::json!({ "log_level": null, "max_submissions": null })
+    );
+}
+
+#[tokio::test]
+async fn patched_settings_are_persisted_and_published() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app
+        .patch_runtime_config(r#"{"log_level":"debug","max_submissions":5}"#.into())
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(
+        body["data"],
+        serde_json::json!({ "log_level": "debug", "max_submissions": 5 })
+    );
+    let published = app.runtime_config.borrow().clone();
+    assert_eq!(published.log_level, Some(LogLevel::new("debug").unwrap()));
+    assert_eq!(published.max_submissions, Some(5));
+    let persisted: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM runtime_config")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(persisted, 2);
+}
+
+#[tokio::test]
+async fn missing_settings_are_kept_and_null_settings_are_reset() {
+    // Arrange
+    let app = spawn_app().await;
+    app.patch_runtime_config(r#"{"log_level":"debug","max_submissions":5}"#.into())
+        .await;
+
+    // Act
+    let response = app
+        .patch_runtime_config(r#"{"log_level":null}"#.into())
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(
+        body["data"],
+        serde_json::json!({ "log_level": null, "max_submissions": 5 })
+    );
+}
+
+#[tokio::test]
+async fn settings_outside_the_safelist_are_rejected_with_422() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app
+        .patch_runtime_config(r#"{"database_url":"postgres://elsewhere"}"#.into())
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 422);
+    assert!(!app.runtime_config.has_changed().unwrap());
+}
+
+#[tokio::test]
+async fn invalid_log_level_is_rejected_with_422() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app
+        .patch_runtime_config(r#"{"log_level":"loud"}"#.into())
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 422);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["data"]["message"], "log level 'loud' is invalid");
+}
+
+#[tokio::test]
+async fn only_admins_tune_the_runtime_config() {
+    // Arrange
+    let app = spawn_app().await;
+    let user_token = app.user_token(&uuid::Uuid::new_v4().to_string());
+    let body = r#"{"max_submissions":5}"#;
+
+    // Act
+    let anonymous = app.patch_runtime_config_as(body.into(), None).await;
+    let user = app
+        .patch_runtime_config_as(body.into(), Some(&user_token))
+        .await;
+
+    // Assert
+    assert_eq!(anonymous.status().as_u16(), 401);
+    assert_eq!(user.status().as_u16(), 403);
+    assert_eq!(app.runtime_config.borrow().max_submissions, None);
+}
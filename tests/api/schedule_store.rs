@@ -0,0 +1,94 @@
+use chrono::{DateTime, TimeDelta, Utc};
+use crowdsource::{
+    domain::crowdsrc::{
+        models::schedule::{CronExpression, ScheduleError},
+        ports::ScheduleStore,
+    },
+    outbound::sqlx_schedule_store::SqlxScheduleStore,
+};
+
+use crate::helpers::spawn_app;
+
+fn at(time: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(time).unwrap().to_utc()
+}
+
+fn hourly() -> CronExpression {
+    CronExpression::parse("0 * * * *").unwrap()
+}
+
+#[tokio::test]
+async fn register_keeps_next_run_unless_cron_changes() {
+    // Arrange
+    let app = spawn_app().await;
+    let store = SqlxScheduleStore::new(app.db_pool.clone());
+    store
+        .register("job", &hourly(), at("2026-10-15T10:30:00Z"))
+        .await
+        .unwrap();
+
+    // Act
+    let unchanged = store
+        .register("job", &hourly(), at("2026-10-15T12:30:00Z"))
+        .await
+        .unwrap();
+    let changed = store
+        .register(
+            "job",
+            &CronExpression::parse("0 0 * * *").unwrap(),
+            at("2026-10-15T12:30:00Z"),
+        )
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(*unchanged.next_run_at(), at("2026-10-15T11:00:00Z"));
+    assert_eq!(*changed.next_run_at(), at("2026-10-16T00:00:00Z"));
+    assert_eq!(changed.cron().as_str(), "0 0 * * *");
+}
+
+#[tokio::test]
+async fn claim_succeeds_once_per_due_run() {
+    // Arrange
+    let app = spawn_app().await;
+    let first = SqlxScheduleStore::new(app.db_pool.clone());
+    let second = SqlxScheduleStore::new(app.db_pool.clone());
+    first
+        .register("job", &hourly(), at("2026-10-15T10:30:00Z"))
+        .await
+        .unwrap();
+    let due = at("2026-10-15T11:00:00Z");
+
+    // Act
+    let early = first
+        .claim("job", due - TimeDelta::minutes(1))
+        .await
+        .unwrap();
+    let (first_claim, second_claim) =
+        tokio::join!(first.claim("job", due), second.claim("job", due));
+
+    // Assert
+    assert!(!early);
+    assert!(first_claim.unwrap() ^ second_claim.unwrap());
+    let schedules = first.list().await.unwrap();
+    assert_eq!(schedules.len(), 1);
+    assert_eq!(*schedules[0].next_run_at(), at("2026-10-15T12:00:00Z"));
+    assert_eq!(schedules[0].last_run_at(), Some(&due));
+}
+
+#[tokio::test]
+async fn claim_fails_for_unknown_schedule() {
+    // Arrange
+    let app = spawn_app().await;
+    let store = SqlxScheduleStore::new(app.db_pool.clone());
+
+    // Act
+    let actual = store.claim("unknown", Utc::now()).await;
+
+    // Assert
+    assert!(
+        matches!(actual, Err(ScheduleError::NotFound { ref name }) if name == "unknown"),
+        "expected NotFound, but got {:?}",
+        actual
+    );
+}
@@ -0,0 +1,88 @@
+use crate::helpers::{TestApp, spawn_app};
+
+async fn create_user(app: &TestApp) -> String {
+    let body = r#"{
+        "email_address":"user@example.com",
+        "username":"user",
+        "accepted_terms_version":"2026-01-30"
+    }"#;
+    let created: serde_json::Value = app.post_users(body.into()).await.json().await.unwrap();
+    created["data"]["id"].as_str().unwrap().to_string()
+}
+
+async fn share_export(app: &TestApp, id: &str) -> String {
+    let shared: serde_json::Value = app
+        .post_user_data_export_share(id)
+        .await
+        .json()
+        .await
+        .unwrap();
+    shared["data"]["url"].as_str().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn shared_export_link_returns_the_export() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = create_user(&app).await;
+    let url = share_export(&app, &id).await;
+
+    // Act
+    let response = app.get(&url).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let actual: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(actual["data"]["user"]["id"], id.as_str());
+}
+
+#[tokio::test]
+async fn shared_export_link_for_another_user_returns_403() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = create_user(&app).await;
+    let url = share_export(&app, &id).await;
+    let other_id = "b2d5b8c2-8c1f-4f22-9a43-2a1f5e5b8c3d";
+
+    // Act
+    let response = app.get(&url.replace(&id, other_id)).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 403);
+}
+
+#[tokio::test]
+async fn unsigned_shared_export_returns_403() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = create_user(&app).await;
+
+    // Act
+    let response = app
+        .get(&format!("/api/shared/users/{id}/data-export"))
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 403);
+}
+
+#[tokio::test]
+async fn only_the_user_shares_their_export() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = create_user(&app).await;
+    let share = format!("/api/users/{id}/data-export/share");
+    let other_user_token = app.user_token("b2d5b8c2-8c1f-4f22-9a43-2a1f5e5b8c3d");
+
+    // Act
+    let anonymous = app.post_as(&share, None).await;
+    let other_user = app.post_as(&share, Some(&other_user_token)).await;
+    let admin = app.post_as(&share, Some(&app.admin_token())).await;
+    let owner = app.post_as(&share, Some(&app.user_token(&id))).await;
+
+    // Assert
+    assert_eq!(anonymous.status().as_u16(), 401);
+    assert_eq!(other_user.status().as_u16(), 403);
+    assert_eq!(admin.status().as_u16(), 403);
+    assert_eq!(owner.status().as_u16(), 201);
+}
@@ -0,0 +1,86 @@
+use std::path::PathBuf;
+
+use uuid::Uuid;
+
+use crate::helpers::{TestApp, TestAppOptions, spawn_app, spawn_app_with};
+
+async fn spawn_app_with_frontend() -> TestApp {
+    let dir: PathBuf = std::env::temp_dir().join(Uuid::new_v4().to_string());
+    std::fs::create_dir_all(dir.join("assets")).unwrap();
+    std::fs::write(dir.join("index.html"), "<html>frontend</html>").unwrap();
+    std::fs::write(dir.join("assets/app.js"), "console.log('app');").unwrap();
+    spawn_app_with(TestAppOptions {
+        static_dir: Some(&dir),
+        ..Default::default()
+    })
+    .await
+}
+
+#[tokio::test]
+async fn static_file_is_served_with_content_type() {
+    // Arrange
+    let app = spawn_app_with_frontend().await;
+
+    // Act
+    let response = app.get_with_accept("/assets/app.js", "*/*").await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(
+        response.headers()["content-type"],
+        "text/javascript; charset=utf-8"
+    );
+    assert_eq!(response.text().await.unwrap(), "console.log('app');");
+}
+
+#[tokio::test]
+async fn unknown_html_route_falls_back_to_index() {
+    // Arrange
+    let app = spawn_app_with_frontend().await;
+
+    // Act
+    let response = app
+        .get_with_accept("/projects/42", "text/html,application/xhtml+xml")
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(response.text().await.unwrap(), "<html>frontend</html>");
+}
+
+#[tokio::test]
+async fn missing_asset_returns_404() {
+    // Arrange
+    let app = spawn_app_with_frontend().await;
+
+    // Act
+    let response = app.get_with_accept("/assets/missing.js", "*/*").await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 404);
+}
+
+#[tokio::test]
+async fn api_routes_take_precedence_over_static_files() {
+    // Arrange
+    let app = spawn_app_with_frontend().await;
+
+    // Act
+    let response = app.get_with_accept("/api", "text/html").await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(response.text().await.unwrap(), "The crowdsource API");
+}
+
+#[tokio::test]
+async fn static_files_are_not_served_unless_configured() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app.get_with_accept("/projects/42", "text/html").await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 404);
+}
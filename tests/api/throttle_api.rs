@@ -0,0 +1,68 @@
+use std::time::Duration;
+
+use crowdsource::domain::crowdsrc::models::throttle::ThrottlePolicy;
+
+use crate::helpers::{TestApp, TestAppOptions, spawn_app_with};
+
+fn user_body(n: u32) -> String {
+    format!(
+        r#"{{
+            "email_address":"user{n}@example.com",
+            "username":"user{n}",
+            "accepted_terms_version":"2026-01-30"
+        }}"#
+    )
+}
+
+const POLICY: ThrottlePolicy = ThrottlePolicy {
+    max_submissions: 2,
+    window: Duration::from_secs(60),
+};
+
+async fn sign_up_three_times(app: &TestApp) -> Vec<reqwest::Response> {
+    let mut responses = Vec::new();
+    for n in 0..3 {
+        responses.push(app.post_users(user_body(n)).await);
+    }
+    responses
+}
+
+#[tokio::test]
+async fn signups_beyond_allowance_return_429() {
+    // Arrange
+    let app = spawn_app_with(TestAppOptions {
+        throttle_policy: Some(POLICY),
+        ..Default::default()
+    })
+    .await;
+
+    // Act
+    let responses = sign_up_three_times(&app).await;
+
+    // Assert
+    assert_eq!(responses[0].status().as_u16(), 201);
+    assert_eq!(responses[1].status().as_u16(), 201);
+    assert_eq!(responses[2].status().as_u16(), 429);
+    assert!(responses[2].headers().contains_key("retry-after"));
+}
+
+#[tokio::test]
+async fn allowlisted_addresses_are_not_throttled() {
+    // Arrange
+    let app = spawn_app_with(TestAppOptions {
+        throttle_policy: Some(POLICY),
+        throttle_allowlist: &["127.0.0.1".parse().unwrap()],
+        ..Default::default()
+    })
+    .await;
+
+    // Act
+    let responses = sign_up_three_times(&app).await;
+
+    // Assert
+    assert!(
+        responses
+            .iter()
+            .all(|response| response.status().as_u16() == 201)
+    );
+}
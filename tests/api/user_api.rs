@@ -1,3 +1,7 @@
+use chrono::{DateTime, TimeDelta, Utc};
+use crowdsource::domain::crowdsrc::models::user::EmailAddress;
+use uuid::Uuid;
+
 use crate::helpers::spawn_app;
 
 #[tokio::test]
@@ -6,7 +10,8 @@ async fn add_user_returns_201_for_valid_data() {
     let app = spawn_app().await;
     let body = r#"{
         "email_address":"user@example.com",
-        "username":"user"
+        "username":"user",
+        "accepted_terms_version":"2026-01-30"
     }"#;
 
     // Act
@@ -14,15 +19,99 @@ async fn add_user_returns_201_for_valid_data() {
 
     // Assert
     let response_status = response.status().as_u16();
-    let response_text = response.text().await.unwrap();
-    println!("{}", response_text);
+    let location = response.headers()[reqwest::header::LOCATION].clone();
+    let body: serde_json::Value = response.json().await.unwrap();
     assert_eq!(response_status, 201);
-    let saved = sqlx::query!("SELECT email, username FROM users;")
+    let saved = sqlx::query!("SELECT id, email, username, created_at FROM users;")
         .fetch_one(&app.db_pool)
         .await
         .unwrap();
     assert_eq!(saved.email, "user@example.com");
     assert_eq!(saved.username, "user");
+    assert_eq!(location, format!("/api/users/{}", saved.id).as_str());
+    assert_eq!(body["data"]["id"], saved.id.to_string());
+    assert_eq!(body["data"]["username"], "user");
+    assert_eq!(body["data"]["email_address"], "user@example.com");
+    let created_at: DateTime<Utc> =
+        serde_json::from_value(body["data"]["created_at"].clone()).unwrap();
+    assert!((created_at - saved.created_at).abs() < TimeDelta::milliseconds(1));
+    let email = EmailAddress::new("user@example.com").unwrap();
+    assert!(app.user_email_map.read().await.contains_key(&email));
+}
+
+#[tokio::test]
+async fn get_user_returns_created_user() {
+    // Arrange
+    let app = spawn_app().await;
+    let body = r#"{
+        "email_address":"user@example.com",
+        "username":"user",
+        "accepted_terms_version":"2026-01-30"
+    }"#;
+    let created = app.post_users(body.into()).await;
+    let location = created.headers()[reqwest::header::LOCATION]
+        .to_str()
+        .unwrap()
+        .to_string();
+    let created: serde_json::Value = created.json().await.unwrap();
+    let token = app.user_token(created["data"]["id"].as_str().unwrap());
+
+    // Act
+    let response = app.get_as(&location, Some(&token)).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["data"]["id"], created["data"]["id"]);
+    assert_eq!(body["data"]["username"], "user");
+    assert_eq!(body["data"]["email_address"], "user@example.com");
+    assert_eq!(body["data"]["email_verified"], false);
+    assert_eq!(body["data"]["created_at"], created["data"]["created_at"]);
+}
+
+#[tokio::test]
+async fn get_user_is_only_allowed_for_the_user_or_an_admin() {
+    // Arrange
+    let app = spawn_app().await;
+    let body = r#"{
+        "email_address":"user@example.com",
+        "username":"user",
+        "accepted_terms_version":"2026-01-30"
+    }"#;
+    let created: serde_json::Value = app.post_users(body.into()).await.json().await.unwrap();
+    let path = format!("/api/users/{}", created["data"]["id"].as_str().unwrap());
+    let other_token = app.user_token(&Uuid::new_v4().to_string());
+
+    // Act
+    let anonymous = app.get_as(&path, None).await;
+    let other_user = app.get_as(&path, Some(&other_token)).await;
+    let admin = app.get_as(&path, Some(&app.admin_token())).await;
+
+    // Assert
+    assert_eq!(anonymous.status().as_u16(), 401);
+    assert_eq!(other_user.status().as_u16(), 403);
+    assert!(
+        !other_user
+            .text()
+            .await
+            .unwrap()
+            .contains("user@example.com")
+    );
+    assert_eq!(admin.status().as_u16(), 200);
+    let body: serde_json::Value = admin.json().await.unwrap();
+    assert_eq!(body["data"]["email_address"], "user@example.com");
+}
+
+#[tokio::test]
+async fn get_user_returns_404_for_unknown_user() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app.get_user(&Uuid::new_v4().to_string()).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 404);
 }
 
 #[tokio::test]
@@ -38,24 +127,51 @@ async fn add_user_returns_422_for_invalid_data() {
         (
             r#"{
                 "email_address":"user-example.com",
-                "username":"user"
+                "username":"user",
+                "accepted_terms_version":"2026-01-30"
             }"#,
             "invalid email",
         ),
         (
             r#"{
                 "email_address":"user@example.com",
-                "username":""
+                "username":"",
+                "accepted_terms_version":"2026-01-30"
             }"#,
             "empty username",
         ),
         (
             r#"{
                 "email_address":"user@example.com",
-                "username":"with whitespace"
+                "username":"with whitespace",
+                "accepted_terms_version":"2026-01-30"
             }"#,
             "username with whitespace",
         ),
+        (
+            r#"{
+                "email_address":"user@example.com",
+                "username":"zero\u200Bwidth",
+                "accepted_terms_version":"2026-01-30"
+            }"#,
+            "username with zero width space",
+        ),
+        (
+            r#"{
+                "email_address":"user@example.com",
+                "username":"erased-user",
+                "accepted_terms_version":"2026-01-30"
+            }"#,
+            "reserved username",
+        ),
+        (
+            r#"{
+                "email_address":"user@erased.invalid",
+                "username":"user",
+                "accepted_terms_version":"2026-01-30"
+            }"#,
+            "reserved email domain",
+        ),
     ];
     for (invalid_body, error_message) in test_cases {
         // Act
@@ -82,7 +198,8 @@ async fn add_user_fails_with_500_if_there_is_a_fatal_database_error() {
     let app = spawn_app().await;
     let body = r#"{
         "email_address":"user@example.com",
-        "username":"user"
+        "username":"user",
+        "accepted_terms_version":"2026-01-30"
     }"#;
     // Sabotage the database
     sqlx::query!("ALTER TABLE users DROP COLUMN email;")
@@ -106,21 +223,24 @@ async fn add_user_returns_422_for_existing_data() {
     let app = spawn_app().await;
     let body = r#"{
         "email_address":"user@example.com",
-        "username":"user"
+        "username":"user",
+        "accepted_terms_version":"2026-01-30"
     }"#;
     app.post_users(body.into()).await;
     let test_cases = vec![
         (
             r#"{
          "email_address":"user@example.com",
-         "username":"user1"
+         "username":"user1",
+         "accepted_terms_version":"2026-01-30"
      }"#,
             "duplicate email",
         ),
         (
             r#"{
          "email_address":"user1@example.com",
-         "username":"user"
+         "username":"user",
+         "accepted_terms_version":"2026-01-30"
      }"#,
             "duplicate username",
         ),
@@ -144,3 +264,333 @@ async fn add_user_returns_422_for_existing_data() {
         );
     }
 }
+
+#[tokio::test]
+async fn export_user_data_returns_200_with_all_user_data() {
+    // Arrange
+    let app = spawn_app().await;
+    let body = r#"{
+        "email_address":"user@example.com",
+        "username":"user",
+        "accepted_terms_version":"2026-01-30"
+    }"#;
+    let created: serde_json::Value = app.post_users(body.into()).await.json().await.unwrap();
+    let id = created["data"]["id"].as_str().unwrap();
+
+    // Act
+    let response = app.get_user_data_export(id).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let actual: serde_json::Value = response.json().await.unwrap();
+    let user = &actual["data"]["user"];
+    assert_eq!(actual["data"]["format_version"], 1);
+    assert_eq!(user["id"], id);
+    assert_eq!(user["username"], "user");
+    assert_eq!(user["email_address"], "user@example.com");
+    assert!(user["created_at"].is_string());
+}
+
+#[tokio::test]
+async fn export_user_data_returns_404_for_unknown_user() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app
+        .get_user_data_export("b2d5b8c2-8c1f-4f22-9a43-2a1f5e5b8c3d")
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 404);
+    let actual_msg: serde_json::Value = response.json().await.unwrap();
+    insta::assert_json_snapshot!(actual_msg);
+}
+
+#[tokio::test]
+async fn erase_user_anonymizes_personal_data() {
+    // Arrange
+    let app = spawn_app().await;
+    let body = r#"{
+        "email_address":"user@example.com",
+        "username":"user",
+        "accepted_terms_version":"2026-01-30"
+    }"#;
+    let created: serde_json::Value = app.post_users(body.into()).await.json().await.unwrap();
+    let id = created["data"]["id"].as_str().unwrap();
+
+    // Act
+    let response = app.post_user_erasure(id).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let saved = sqlx::query!("SELECT email, username, erased_at FROM users;")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    assert_ne!(saved.email, "user@example.com");
+    assert_ne!(saved.username, "user");
+    assert!(saved.erased_at.is_some());
+
+    // The email and username are free to be used again
+    let response = app.post_users(body.into()).await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    // Erasing again succeeds
+    let response = app.post_user_erasure(id).await;
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn erase_user_returns_404_for_unknown_user() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app
+        .post_user_erasure("b2d5b8c2-8c1f-4f22-9a43-2a1f5e5b8c3d")
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 404);
+}
+
+#[tokio::test]
+async fn erasure_and_export_are_only_allowed_for_the_user_or_an_admin() {
+    // Arrange
+    let app = spawn_app().await;
+    let body = r#"{
+        "email_address":"user@example.com",
+        "username":"user",
+        "accepted_terms_version":"2026-01-30"
+    }"#;
+    let created: serde_json::Value = app.post_users(body.into()).await.json().await.unwrap();
+    let id = created["data"]["id"].as_str().unwrap();
+    let other_user_token = app.user_token(&Uuid::new_v4().to_string());
+
+    // Act
+    let anonymous_export = app.get_user_data_export_as(id, None).await;
+    let anonymous_erasure = app.post_user_erasure_as(id, None).await;
+    let tampered_erasure = app.post_user_erasure_as(id, Some("admin.0.00")).await;
+    let other_export = app
+        .get_user_data_export_as(id, Some(&other_user_token))
+        .await;
+    let other_erasure = app.post_user_erasure_as(id, Some(&other_user_token)).await;
+    let own_export = app
+        .get_user_data_export_as(id, Some(&app.user_token(id)))
+        .await;
+    let own_erasure = app
+        .post_user_erasure_as(id, Some(&app.user_token(id)))
+        .await;
+
+    // Assert
+    assert_eq!(anonymous_export.status().as_u16(), 401);
+    assert_eq!(
+        anonymous_export.headers()["www-authenticate"]
+            .to_str()
+            .unwrap(),
+        "Bearer"
+    );
+    assert_eq!(anonymous_erasure.status().as_u16(), 401);
+    assert_eq!(tampered_erasure.status().as_u16(), 401);
+    assert_eq!(other_export.status().as_u16(), 403);
+    assert_eq!(other_erasure.status().as_u16(), 403);
+    assert_eq!(own_export.status().as_u16(), 200);
+    assert_eq!(own_erasure.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn export_user_data_returns_422_for_invalid_id() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app.get_user_data_export("not-a-uuid").await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 422);
+}
+
+#[tokio::test]
+async fn add_user_returns_409_for_stale_terms_version() {
+    // Arrange
+    let app = spawn_app().await;
+    let body = r#"{
+        "email_address":"user@example.com",
+        "username":"user",
+        "accepted_terms_version":"2025-01-01"
+    }"#;
+
+    // Act
+    let response = app.post_users(body.into()).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 409);
+    let actual_msg: serde_json::Value = response.json().await.unwrap();
+    insta::assert_json_snapshot!(actual_msg);
+    let count = sqlx::query_scalar!("SELECT COUNT(*) FROM users;")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(count, Some(0));
+}
+
+#[tokio::test]
+async fn accept_terms_records_acceptance() {
+    // Arrange
+    let app = spawn_app().await;
+    let body = r#"{
+        "email_address":"user@example.com",
+        "username":"user",
+        "accepted_terms_version":"2026-01-30"
+    }"#;
+    let created: serde_json::Value = app.post_users(body.into()).await.json().await.unwrap();
+    let id = created["data"]["id"].as_str().unwrap();
+
+    // Act
+    let response = app
+        .post_user_terms_acceptance(id, r#"{"terms_version":"2026-01-30"}"#.into())
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let export: serde_json::Value = app.get_user_data_export(id).await.json().await.unwrap();
+    let acceptances = export["data"]["terms_acceptances"].as_array().unwrap();
+    assert_eq!(acceptances.len(), 2);
+    assert!(
+        acceptances
+            .iter()
+            .all(|acceptance| acceptance["terms_version"] == "2026-01-30")
+    );
+}
+
+#[tokio::test]
+async fn only_the_user_accepts_their_terms() {
+    // Arrange
+    let app = spawn_app().await;
+    let body = r#"{
+        "email_address":"user@example.com",
+        "username":"user",
+        "accepted_terms_version":"2026-01-30"
+    }"#;
+    let created: serde_json::Value = app.post_users(body.into()).await.json().await.unwrap();
+    let id = created["data"]["id"].as_str().unwrap();
+    let acceptance = r#"{"terms_version":"2026-01-30"}"#;
+    let other_token = app.user_token(&Uuid::new_v4().to_string());
+
+    // Act
+    let anonymous = app
+        .post_user_terms_acceptance_as(id, acceptance.into(), None)
+        .await;
+    let other_user = app
+        .post_user_terms_acceptance_as(id, acceptance.into(), Some(&other_token))
+        .await;
+    let admin = app
+        .post_user_terms_acceptance_as(id, acceptance.into(), Some(&app.admin_token()))
+        .await;
+
+    // Assert
+    assert_eq!(anonymous.status().as_u16(), 401);
+    assert_eq!(other_user.status().as_u16(), 403);
+    assert_eq!(admin.status().as_u16(), 403);
+    let export: serde_json::Value = app.get_user_data_export(id).await.json().await.unwrap();
+    assert_eq!(
+        export["data"]["terms_acceptances"]
+            .as_array()
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[tokio::test]
+async fn writes_for_a_user_require_the_current_terms() {
+    // Arrange
+    let app = spawn_app().await;
+    let body = r#"{
+        "email_address":"user@example.com",
+        "username":"user",
+        "accepted_terms_version":"2026-01-30"
+    }"#;
+    let created: serde_json::Value = app.post_users(body.into()).await.json().await.unwrap();
+    let id = created["data"]["id"].as_str().unwrap();
+    // The terms were updated since the user accepted them
+    sqlx::query!("UPDATE terms_acceptances SET terms_version = '2025-01-01';")
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+    let email_change = r#"{"email":"new@example.com"}"#;
+
+    // Act
+    let stale = app.post_user_email_change(id, email_change.into()).await;
+    app.post_user_terms_acceptance(id, r#"{"terms_version":"2026-01-30"}"#.into())
+        .await;
+    let accepted = app.post_user_email_change(id, email_change.into()).await;
+
+    // Assert
+    assert_eq!(stale.status().as_u16(), 409);
+    assert_eq!(accepted.status().as_u16(), 202);
+}
+
+#[tokio::test]
+async fn accept_terms_returns_409_for_stale_terms_version() {
+    // Arrange
+    let app = spawn_app().await;
+    let body = r#"{
+        "email_address":"user@example.com",
+        "username":"user",
+        "accepted_terms_version":"2026-01-30"
+    }"#;
+    let created: serde_json::Value = app.post_users(body.into()).await.json().await.unwrap();
+    let id = created["data"]["id"].as_str().unwrap();
+
+    // Act
+    let response = app
+        .post_user_terms_acceptance(id, r#"{"terms_version":"2025-01-01"}"#.into())
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 409);
+}
+
+#[tokio::test]
+async fn accept_terms_returns_404_for_unknown_user() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app
+        .post_user_terms_acceptance(
+            "b2d5b8c2-8c1f-4f22-9a43-2a1f5e5b8c3d",
+            r#"{"terms_version":"2026-01-30"}"#.into(),
+        )
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 404);
+}
+
+#[tokio::test]
+async fn add_user_returns_errors_in_the_accepted_language() {
+    // Arrange
+    let app = spawn_app().await;
+    let body = r#"{
+        "email_address":"user@example.com",
+        "username":"",
+        "accepted_terms_version":"2026-01-30"
+    }"#;
+
+    // Act
+    let response = app
+        .post_users_with_language(body.into(), "sv-SE,sv;q=0.9,en;q=0.8")
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 422);
+    assert_eq!(response.headers()["Content-Language"], "sv");
+    let actual_msg: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(
+        actual_msg["data"]["message"],
+        "användarnamnet får inte vara tomt"
+    );
+}
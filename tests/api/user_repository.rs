@@ -0,0 +1,619 @@
+use std::{collections::HashMap, time::Duration};
+
+use chrono::{TimeDelta, Utc};
+use crowdsource::{
+    domain::crowdsrc::{
+        models::{
+            email_change::{EmailChangeToken, PendingEmailChange},
+            terms::TermsVersion,
+            user::{
+                CreateUserError, CreateUserOutcome, CreateUserRequest, EmailAddress,
+                EmailCanonicalization, UserName,
+            },
+        },
+        ports::{
+            Transaction, TransactionManager, UserRepository, testing::user_repository_contract,
+        },
+    },
+    outbound::{
+        field_cipher::FieldCipher,
+        retry::RetryPolicy,
+        retrying_repository::RetryingRepository,
+        sqlx_transaction::SqlxTransactionManager,
+        sqlx_user_repository::{ReencryptionSummary, SqlxUserRepository},
+        timed_repository::TimedRepository,
+    },
+};
+use futures::TryStreamExt;
+
+use crate::helpers::{fresh_user_repository, spawn_app};
+
+fn create_user_request(username: &str, email: &str) -> CreateUserRequest {
+    CreateUserRequest::new(
+        UserName::new(username).unwrap(),
+        EmailAddress::new(email).unwrap(),
+        TermsVersion::new("2026-01-30").unwrap(),
+    )
+}
+
+/// A cipher sealing under `current_key_id`, either `old` or `new`.
+fn field_cipher(current_key_id: &str) -> FieldCipher {
+    let keys = HashMap::from([
+        ("old".to_string(), vec![1; 32]),
+        ("new".to_string(), vec![2; 32]),
+    ]);
+    FieldCipher::new(keys, current_key_id, b"test index key").unwrap()
+}
+
+#[tokio::test]
+async fn sqlx_user_repository_satisfies_contract() {
+    user_repository_contract(fresh_user_repository).await;
+}
+
+#[tokio::test]
+async fn confusable_rejecting_sqlx_user_repository_satisfies_contract() {
+    user_repository_contract(async || {
+        fresh_user_repository()
+            .await
+            .with_confusable_usernames_rejected(true)
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn encrypting_sqlx_user_repository_satisfies_contract() {
+    user_repository_contract(async || {
+        fresh_user_repository()
+            .await
+            .with_cipher(field_cipher("new"))
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn decorated_sqlx_user_repository_satisfies_contract() {
+    user_repository_contract(async || {
+        RetryingRepository::new(
+            TimedRepository::new(fresh_user_repository().await, Duration::from_secs(1)),
+            RetryPolicy::new(2, Duration::from_millis(10)),
+        )
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn create_users_persists_all_users_in_batch() {
+    // Arrange
+    let app = spawn_app().await;
+    let repo = SqlxUserRepository::new(app.db_pool.clone());
+    let reqs = vec![
+        create_user_request("user1", "user1@example.com"),
+        create_user_request("user2", "user2@example.com"),
+    ];
+
+    // Act
+    let outcomes = repo.create_users(&reqs).await.unwrap();
+
+    // Assert
+    assert_eq!(outcomes.len(), 2);
+    assert!(outcomes.iter().all(CreateUserOutcome::is_created));
+    let saved = sqlx::query!("SELECT email, username FROM users ORDER BY username;")
+        .fetch_all(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(saved.len(), 2);
+    assert_eq!(saved[0].username, "user1");
+    assert_eq!(saved[1].email, "user2@example.com");
+    let acceptances = sqlx::query_scalar!("SELECT COUNT(*) FROM terms_acceptances;")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(acceptances, Some(2));
+}
+
+#[tokio::test]
+async fn create_users_reports_duplicates_per_item() {
+    // Arrange
+    let app = spawn_app().await;
+    let repo = SqlxUserRepository::new(app.db_pool.clone());
+    repo.create_users(&[create_user_request("user", "user@example.com")])
+        .await
+        .unwrap();
+    let reqs = vec![
+        create_user_request("user1", "user@example.com"),
+        create_user_request("user", "user1@example.com"),
+        create_user_request("user2", "user2@example.com"),
+        create_user_request("user3", "user2@example.com"),
+        create_user_request("user2", "user3@example.com"),
+    ];
+
+    // Act
+    let outcomes = repo.create_users(&reqs).await.unwrap();
+
+    // Assert
+    assert!(
+        matches!(&outcomes[0], CreateUserOutcome::DuplicateEmail { email } if email.as_str() == "user@example.com"),
+        "expected duplicate email, got {:?}",
+        outcomes[0]
+    );
+    assert!(
+        matches!(&outcomes[1], CreateUserOutcome::DuplicateUserName { username } if username.to_string() == "user"),
+        "expected duplicate username, got {:?}",
+        outcomes[1]
+    );
+    assert!(outcomes[2].is_created(), "got {:?}", outcomes[2]);
+    assert!(
+        matches!(&outcomes[3], CreateUserOutcome::DuplicateEmail { .. }),
+        "expected duplicate email within batch, got {:?}",
+        outcomes[3]
+    );
+    assert!(
+        matches!(&outcomes[4], CreateUserOutcome::DuplicateUserName { .. }),
+        "expected duplicate username within batch, got {:?}",
+        outcomes[4]
+    );
+    let count = sqlx::query_scalar!("SELECT COUNT(*) FROM users;")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(count, Some(2));
+    let acceptances = sqlx::query_scalar!("SELECT COUNT(*) FROM terms_acceptances;")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(acceptances, Some(2));
+}
+
+#[tokio::test]
+async fn create_users_reports_the_collision_that_rejected_each_item() {
+    // Arrange
+    let app = spawn_app().await;
+    let repo = SqlxUserRepository::new(app.db_pool.clone());
+    repo.create_users(&[create_user_request("user", "user@example.com")])
+        .await
+        .unwrap();
+    let reqs = vec![
+        // Rejected for its username, before the next request takes its email
+        create_user_request("user", "shared@example.com"),
+        create_user_request("user1", "shared@example.com"),
+        create_user_request("user2", "user@example.com"),
+    ];
+
+    // Act
+    let outcomes = repo.create_users(&reqs).await.unwrap();
+
+    // Assert
+    assert!(
+        matches!(&outcomes[0], CreateUserOutcome::DuplicateUserName { username } if username.to_string() == "user"),
+        "expected duplicate username, got {:?}",
+        outcomes[0]
+    );
+    assert!(outcomes[1].is_created(), "got {:?}", outcomes[1]);
+    assert!(
+        matches!(&outcomes[2], CreateUserOutcome::DuplicateEmail { email } if email.as_str() == "user@example.com"),
+        "expected duplicate email, got {:?}",
+        outcomes[2]
+    );
+}
+
+#[tokio::test]
+async fn stream_users_yields_all_users_oldest_first() {
+    // Arrange
+    let app = spawn_app().await;
+    let repo = SqlxUserRepository::new(app.db_pool.clone());
+    for (username, email) in [
+        ("user2", "user2@example.com"),
+        ("user1", "user1@example.com"),
+    ] {
+        repo.create_users(&[create_user_request(username, email)])
+            .await
+            .unwrap();
+    }
+
+    // Act
+    let users: Vec<_> = repo.stream_users().try_collect().await.unwrap();
+
+    // Assert
+    let usernames: Vec<_> = users
+        .iter()
+        .map(|user| user.username().to_string())
+        .collect();
+    assert_eq!(usernames, vec!["user2", "user1"]);
+}
+
+#[tokio::test]
+async fn stream_users_yields_nothing_for_empty_table() {
+    // Arrange
+    let app = spawn_app().await;
+    let repo = SqlxUserRepository::new(app.db_pool.clone());
+
+    // Act
+    let users: Vec<_> = repo.stream_users().try_collect().await.unwrap();
+
+    // Assert
+    assert!(users.is_empty());
+}
+
+#[tokio::test]
+async fn writes_in_committed_transaction_are_persisted() {
+    // Arrange
+    let app = spawn_app().await;
+    let repo = SqlxUserRepository::new(app.db_pool.clone());
+    let tx = SqlxTransactionManager::new(app.db_pool.clone())
+        .begin()
+        .await
+        .unwrap();
+
+    // Act
+    tx.scope(async {
+        repo.create_user(&create_user_request("user1", "user1@example.com"))
+            .await
+            .unwrap();
+        repo.create_user(&create_user_request("user2", "user2@example.com"))
+            .await
+            .unwrap();
+    })
+    .await;
+    tx.commit().await.unwrap();
+
+    // Assert
+    let users: Vec<_> = repo.stream_users().try_collect().await.unwrap();
+    assert_eq!(users.len(), 2);
+}
+
+#[tokio::test]
+async fn writes_in_rolled_back_transaction_are_discarded() {
+    // Arrange
+    let app = spawn_app().await;
+    let repo = SqlxUserRepository::new(app.db_pool.clone());
+    let tx = SqlxTransactionManager::new(app.db_pool.clone())
+        .begin()
+        .await
+        .unwrap();
+
+    // Act
+    let user = tx
+        .scope(async {
+            let user = repo
+                .create_user(&create_user_request("user1", "user1@example.com"))
+                .await
+                .unwrap();
+            // Reads within the transaction see its own writes.
+            repo.get_user(user.id()).await.unwrap()
+        })
+        .await;
+    tx.rollback().await.unwrap();
+
+    // Assert
+    assert!(repo.get_user(user.id()).await.is_err());
+    let users: Vec<_> = repo.stream_users().try_collect().await.unwrap();
+    assert!(users.is_empty());
+}
+
+#[tokio::test]
+async fn encrypted_emails_are_not_stored_in_plain_text() {
+    // Arrange
+    let app = spawn_app().await;
+    let repo = SqlxUserRepository::new(app.db_pool.clone()).with_cipher(field_cipher("new"));
+
+    // Act
+    let user = repo
+        .create_user(&create_user_request("user1", "user1@example.com"))
+        .await
+        .unwrap();
+    repo.create_users(&[create_user_request("user2", "user2@example.com")])
+        .await
+        .unwrap();
+
+    // Assert
+    let stored = sqlx::query_scalar!("SELECT email FROM users;")
+        .fetch_all(&app.db_pool)
+        .await
+        .unwrap();
+    assert!(stored.iter().all(|email| email.starts_with("enc:v1:new:")));
+    let fetched = repo.get_user(user.id()).await.unwrap();
+    assert_eq!(fetched.email().as_str(), "user1@example.com");
+    let users: Vec<_> = repo.stream_users().try_collect().await.unwrap();
+    assert_eq!(users[1].email().as_str(), "user2@example.com");
+}
+
+#[tokio::test]
+async fn encrypted_duplicate_emails_are_reported() {
+    // Arrange
+    let app = spawn_app().await;
+    let repo = SqlxUserRepository::new(app.db_pool.clone()).with_cipher(field_cipher("new"));
+    repo.create_user(&create_user_request("user", "user@example.com"))
+        .await
+        .unwrap();
+
+    // Act
+    let single = repo
+        .create_user(&create_user_request("user1", "user@example.com"))
+        .await;
+    let outcomes = repo
+        .create_users(&[
+            create_user_request("user2", "user@example.com"),
+            create_user_request("user", "user3@example.com"),
+        ])
+        .await
+        .unwrap();
+
+    // Assert
+    assert!(
+        matches!(&single, Err(CreateUserError::DuplicateEmail { email }) if email.as_str() == "user@example.com"),
+        "expected duplicate email, got {single:?}"
+    );
+    assert!(matches!(
+        &outcomes[0],
+        CreateUserOutcome::DuplicateEmail { .. }
+    ));
+    assert!(matches!(
+        &outcomes[1],
+        CreateUserOutcome::DuplicateUserName { .. }
+    ));
+}
+
+#[tokio::test]
+async fn reencrypt_users_reseals_plain_and_retired_emails() {
+    // Arrange
+    let app = spawn_app().await;
+    let plain = SqlxUserRepository::new(app.db_pool.clone());
+    plain
+        .create_user(&create_user_request("user1", "user1@example.com"))
+        .await
+        .unwrap();
+    let old = SqlxUserRepository::new(app.db_pool.clone()).with_cipher(field_cipher("old"));
+    old.create_user(&create_user_request("user2", "user2@example.com"))
+        .await
+        .unwrap();
+    let repo = SqlxUserRepository::new(app.db_pool.clone()).with_cipher(field_cipher("new"));
+    repo.create_user(&create_user_request("user3", "user3@example.com"))
+        .await
+        .unwrap();
+
+    // Act
+    let summary = repo.reencrypt_users(2).await.unwrap();
+
+    // Assert
+    assert_eq!(
+        summary,
+        ReencryptionSummary {
+            users: 3,
+            resealed: 2,
+            reindexed: 1,
+            duplicates: 0,
+            confusables: 0,
+        }
+    );
+    let stored = sqlx::query_scalar!("SELECT email FROM users;")
+        .fetch_all(&app.db_pool)
+        .await
+        .unwrap();
+    assert!(stored.iter().all(|email| email.starts_with("enc:v1:new:")));
+    let users: Vec<_> = repo.stream_users().try_collect().await.unwrap();
+    let emails: Vec<_> = users.iter().map(|user| user.email().as_str()).collect();
+    assert_eq!(
+        emails,
+        vec![
+            "user1@example.com",
+            "user2@example.com",
+            "user3@example.com"
+        ]
+    );
+    let duplicate = repo
+        .create_user(&create_user_request("user4", "user1@example.com"))
+        .await;
+    assert!(matches!(
+        duplicate,
+        Err(CreateUserError::DuplicateEmail { .. })
+    ));
+    assert_eq!(repo.reencrypt_users(2).await.unwrap().resealed, 0);
+}
+
+const FOLD_ALIASES: EmailCanonicalization = EmailCanonicalization {
+    fold_plus_aliases: true,
+    fold_gmail_dots: true,
+};
+
+#[tokio::test]
+async fn emails_with_same_canonical_form_are_reported_as_duplicates() {
+    // Arrange
+    let app = spawn_app().await;
+    let repo =
+        SqlxUserRepository::new(app.db_pool.clone()).with_email_canonicalization(FOLD_ALIASES);
+    repo.create_user(&create_user_request("user", "first.last@gmail.com"))
+        .await
+        .unwrap();
+
+    // Act
+    let single = repo
+        .create_user(&create_user_request("user1", "FirstLast+1@gmail.com"))
+        .await;
+    let outcomes = repo
+        .create_users(&[
+            create_user_request("user2", "first.last+2@googlemail.com"),
+            create_user_request("user3", "other+1@example.com"),
+            create_user_request("user4", "other+2@EXAMPLE.com"),
+        ])
+        .await
+        .unwrap();
+
+    // Assert
+    assert!(
+        matches!(&single, Err(CreateUserError::DuplicateEmail { email }) if email.as_str() == "FirstLast+1@gmail.com"),
+        "expected duplicate email, got {single:?}"
+    );
+    assert!(matches!(
+        &outcomes[0],
+        CreateUserOutcome::DuplicateEmail { .. }
+    ));
+    assert!(outcomes[1].is_created());
+    assert!(matches!(
+        &outcomes[2],
+        CreateUserOutcome::DuplicateEmail { .. }
+    ));
+    // The email is stored as given.
+    let stored = sqlx::query_scalar!("SELECT email FROM users WHERE username = 'user3';")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(stored, "other+1@example.com");
+}
+
+#[tokio::test]
+async fn reencrypt_users_reports_users_made_duplicates_by_canonicalization() {
+    // Arrange
+    let app = spawn_app().await;
+    let repo = SqlxUserRepository::new(app.db_pool.clone());
+    repo.create_user(&create_user_request("user1", "user+1@example.com"))
+        .await
+        .unwrap();
+    repo.create_user(&create_user_request("user2", "user+2@example.com"))
+        .await
+        .unwrap();
+    let repo = repo.with_email_canonicalization(FOLD_ALIASES);
+
+    // Act
+    let summary = repo.reencrypt_users(10).await.unwrap();
+
+    // Assert
+    assert_eq!(summary.users, 2);
+    assert_eq!(summary.reindexed, 1);
+    assert_eq!(summary.duplicates, 1);
+    let duplicate = repo
+        .create_user(&create_user_request("user3", "user@example.com"))
+        .await;
+    assert!(matches!(
+        duplicate,
+        Err(CreateUserError::DuplicateEmail { .. })
+    ));
+}
+
+#[tokio::test]
+async fn confusable_usernames_are_reported_as_duplicates() {
+    // Arrange
+    let app = spawn_app().await;
+    let repo =
+        SqlxUserRepository::new(app.db_pool.clone()).with_confusable_usernames_rejected(true);
+    repo.create_user(&create_user_request("admin", "admin@example.com"))
+        .await
+        .unwrap();
+
+    // Act
+    let single = repo
+        .create_user(&create_user_request("\u{430}dmin", "user1@example.com"))
+        .await;
+    let outcomes = repo
+        .create_users(&[
+            create_user_request("adrnin", "user2@example.com"),
+            create_user_request("paypal", "user3@example.com"),
+            create_user_request("paypaI", "user4@example.com"),
+        ])
+        .await
+        .unwrap();
+
+    // Assert
+    assert!(
+        matches!(&single, Err(CreateUserError::DuplicateUserName { username }) if username.to_string() == "\u{430}dmin"),
+        "expected duplicate username, got {single:?}"
+    );
+    assert!(matches!(
+        &outcomes[0],
+        CreateUserOutcome::DuplicateUserName { .. }
+    ));
+    assert!(outcomes[1].is_created());
+    assert!(matches!(
+        &outcomes[2],
+        CreateUserOutcome::DuplicateUserName { .. }
+    ));
+}
+
+#[tokio::test]
+async fn erased_usernames_can_be_taken_by_lookalikes() {
+    // Arrange
+    let app = spawn_app().await;
+    let repo =
+        SqlxUserRepository::new(app.db_pool.clone()).with_confusable_usernames_rejected(true);
+    let user = repo
+        .create_user(&create_user_request("admin", "admin@example.com"))
+        .await
+        .unwrap();
+    repo.erase_user(user.id()).await.unwrap();
+
+    // Act
+    let lookalike = repo
+        .create_user(&create_user_request("\u{430}dmin", "user@example.com"))
+        .await;
+
+    // Assert
+    assert!(lookalike.is_ok(), "got {lookalike:?}");
+}
+
+#[tokio::test]
+async fn reencrypt_users_reports_confusable_usernames() {
+    // Arrange
+    let app = spawn_app().await;
+    let repo = SqlxUserRepository::new(app.db_pool.clone());
+    repo.create_user(&create_user_request("admin", "user1@example.com"))
+        .await
+        .unwrap();
+    repo.create_user(&create_user_request("\u{430}dmin", "user2@example.com"))
+        .await
+        .unwrap();
+    let repo = repo.with_confusable_usernames_rejected(true);
+
+    // Act
+    let summary = repo.reencrypt_users(10).await.unwrap();
+    let rerun = repo.reencrypt_users(10).await.unwrap();
+
+    // Assert
+    assert_eq!(summary.users, 2);
+    assert_eq!(summary.reindexed, 1);
+    assert_eq!(summary.confusables, 1);
+    assert_eq!(rerun.reindexed, 0);
+    let lookalike = repo
+        .create_user(&create_user_request("adrnin", "user3@example.com"))
+        .await;
+    assert!(matches!(
+        lookalike,
+        Err(CreateUserError::DuplicateUserName { .. })
+    ));
+}
+
+#[tokio::test]
+async fn delete_expired_email_changes_keeps_pending_ones() {
+    // Arrange
+    let app = spawn_app().await;
+    let repo = SqlxUserRepository::new(app.db_pool.clone());
+    let expired = repo
+        .create_user(&create_user_request("expired", "expired@example.com"))
+        .await
+        .unwrap();
+    let pending = repo
+        .create_user(&create_user_request("pending", "pending@example.com"))
+        .await
+        .unwrap();
+    let now = Utc::now();
+    for (user, requested_at) in [
+        (&expired, now - TimeDelta::days(2)),
+        (&pending, now - TimeDelta::hours(1)),
+    ] {
+        let change = PendingEmailChange::new(
+            *user.id(),
+            EmailAddress::new(&format!("new-{}", user.email())).unwrap(),
+            requested_at,
+            requested_at + TimeDelta::days(1),
+        );
+        repo.save_email_change(&change, &EmailChangeToken::generate())
+            .await
+            .unwrap();
+    }
+
+    // Act
+    let deleted = repo.delete_expired_email_changes(&now).await.unwrap();
+
+    // Assert
+    assert_eq!(deleted, 1);
+    assert!(repo.delete_email_change(expired.id()).await.is_err());
+    assert!(repo.delete_email_change(pending.id()).await.is_ok());
+}
@@ -0,0 +1,186 @@
+use crowdsource::{
+    domain::crowdsrc::{
+        indexed_service::IndexedCrowdSrcService,
+        models::{
+            search::{SearchError, UserSearchQuery},
+            terms::TermsVersion,
+            user::User,
+        },
+        ports::{CrowdSrcService, SearchIndex},
+        service::Service,
+    },
+    outbound::{
+        collecting_user_notifier::CollectingUserNotifier, sqlx_user_repository::SqlxUserRepository,
+    },
+};
+use uuid::Uuid;
+
+use crate::helpers::{TestApp, spawn_app};
+
+/// Stands in for a [SearchIndex] lagging behind the users, finding `hits` whatever is searched
+/// for.
+#[derive(Debug, Clone)]
+struct StaleSearchIndex {
+    hits: Vec<Uuid>,
+}
+
+impl SearchIndex for StaleSearchIndex {
+    async fn index_users(&self, _users: &[User]) -> Result<(), SearchError> {
+        Ok(())
+    }
+
+    async fn remove_user(&self, _id: &Uuid) -> Result<(), SearchError> {
+        Ok(())
+    }
+
+    async fn clear(&self) -> Result<(), SearchError> {
+        Ok(())
+    }
+
+    async fn search_users(&self, _query: &UserSearchQuery) -> Result<Vec<Uuid>, SearchError> {
+        Ok(self.hits.clone())
+    }
+}
+
+async fn create_user(app: &TestApp, username: &str, email: &str) -> String {
+    let body = format!(
+        r#"{{
+        "email_address":"{email}",
+        "username":"{username}",
+        "accepted_terms_version":"2026-01-30"
+    }}"#
+    );
+    let created: serde_json::Value = app.post_users(body).await.json().await.unwrap();
+    created["data"]["id"].as_str().unwrap().to_string()
+}
+
+async fn found_usernames(app: &TestApp, query: &str) -> Vec<String> {
+    let response = app.get_admin_user_search(query).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let found: serde_json::Value = response.json().await.unwrap();
+    found["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|user| user["username"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn search_finds_matching_usernames_prefix_matches_first() {
+    // Arrange
+    let app = spawn_app().await;
+    create_user(&app, "the_annotator", "the@example.com").await;
+    create_user(&app, "anna", "anna@example.com").await;
+    create_user(&app, "bob", "bob@example.com").await;
+
+    // Act
+    let found = found_usernames(&app, "?q=ANN").await;
+
+    // Assert
+    assert_eq!(found, vec!["anna", "the_annotator"]);
+}
+
+#[tokio::test]
+async fn search_is_limited() {
+    // Arrange
+    let app = spawn_app().await;
+    create_user(&app, "anna", "anna@example.com").await;
+    create_user(&app, "annie", "annie@example.com").await;
+
+    // Act
+    let found = found_usernames(&app, "?q=ann&limit=1").await;
+
+    // Assert
+    assert_eq!(found, vec!["anna"]);
+}
+
+#[tokio::test]
+async fn search_treats_wildcards_literally() {
+    // Arrange
+    let app = spawn_app().await;
+    create_user(&app, "anna", "anna@example.com").await;
+
+    // Act
+    let found = found_usernames(&app, "?q=%25").await;
+
+    // Assert
+    assert!(found.is_empty());
+}
+
+#[tokio::test]
+async fn search_leaves_out_erased_users() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = create_user(&app, "anna", "anna@example.com").await;
+    app.post_user_erasure(&id).await;
+
+    // Act
+    let found = found_usernames(&app, "?q=e").await;
+
+    // Assert
+    assert!(found.is_empty());
+}
+
+#[tokio::test]
+async fn search_returns_422_for_blank_query() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app.get_admin_user_search("?q=%20%20").await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 422);
+}
+
+#[tokio::test]
+async fn search_is_only_available_to_admins() {
+    // Arrange
+    let app = spawn_app().await;
+    let user_token = app.user_token(&Uuid::new_v4().to_string());
+
+    // Act
+    let anonymous = app.get_as("/api/admin/users/search?q=anna", None).await;
+    let user = app
+        .get_as("/api/admin/users/search?q=anna", Some(&user_token))
+        .await;
+
+    // Assert
+    assert_eq!(anonymous.status().as_u16(), 401);
+    assert_eq!(user.status().as_u16(), 403);
+}
+
+#[tokio::test]
+async fn indexed_search_drops_hits_of_missing_or_erased_users() {
+    // Arrange
+    let app = spawn_app().await;
+    let anna = create_user(&app, "anna", "anna@example.com").await;
+    let erased = create_user(&app, "annie", "annie@example.com").await;
+    app.post_user_erasure(&erased).await;
+    let index = StaleSearchIndex {
+        hits: vec![
+            Uuid::parse_str(&erased).unwrap(),
+            Uuid::new_v4(),
+            Uuid::parse_str(&anna).unwrap(),
+        ],
+    };
+    let service = IndexedCrowdSrcService::new(
+        Service::new(
+            SqlxUserRepository::new(app.db_pool.clone()),
+            CollectingUserNotifier::new(Default::default()),
+            TermsVersion::new("2026-01-30").unwrap(),
+        ),
+        Some(index),
+    );
+
+    // Act
+    let found = service
+        .search_users(&UserSearchQuery::new("ann", 10).unwrap())
+        .await
+        .unwrap();
+
+    // Assert
+    let found: Vec<String> = found.iter().map(|user| user.id().to_string()).collect();
+    assert_eq!(found, vec![anna]);
+}
@@ -0,0 +1,102 @@
+use crate::helpers::{TestApp, spawn_app};
+
+async fn create_user(app: &TestApp, username: &str, email: &str) {
+    let body = format!(
+        r#"{{
+        "email_address":"{email}",
+        "username":"{username}",
+        "accepted_terms_version":"2026-01-30"
+    }}"#
+    );
+    let response = app.post_users(body).await;
+    assert_eq!(response.status().as_u16(), 201);
+}
+
+#[tokio::test]
+async fn users_export_lists_users_as_csv() {
+    // Arrange
+    let app = spawn_app().await;
+    create_user(&app, "first", "first@example.com").await;
+    create_user(&app, "second", "second@example.com").await;
+
+    // Act
+    let response = app.get_users_export_csv("").await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(
+        response.headers()["content-type"],
+        "text/csv; charset=utf-8"
+    );
+    let csv = response.text().await.unwrap();
+    let lines: Vec<&str> = csv.split_terminator("\r\n").collect();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0], "id,username,email,created_at");
+    assert!(lines[1].contains(",first,first@example.com,"));
+    assert!(lines[2].contains(",second,second@example.com,"));
+}
+
+#[tokio::test]
+async fn users_export_selects_columns() {
+    // Arrange
+    let app = spawn_app().await;
+    create_user(&app, "first", "first@example.com").await;
+
+    // Act
+    let response = app.get_users_export_csv("?columns=email,username").await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(
+        response.text().await.unwrap(),
+        "email,username\r\nfirst@example.com,first\r\n"
+    );
+}
+
+#[tokio::test]
+async fn users_export_filters_by_creation_time() {
+    // Arrange
+    let app = spawn_app().await;
+    create_user(&app, "first", "first@example.com").await;
+
+    // Act
+    let before = app
+        .get_users_export_csv("?columns=username&created_before=2000-01-01T00:00:00Z")
+        .await;
+    let after = app
+        .get_users_export_csv("?columns=username&created_after=2000-01-01T00:00:00Z")
+        .await;
+
+    // Assert
+    assert_eq!(before.text().await.unwrap(), "username\r\n");
+    assert_eq!(after.text().await.unwrap(), "username\r\nfirst\r\n");
+}
+
+#[tokio::test]
+async fn users_export_returns_422_for_unknown_column() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app.get_users_export_csv("?columns=id,password").await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 422);
+}
+
+#[tokio::test]
+async fn users_export_is_only_available_to_admins() {
+    // Arrange
+    let app = spawn_app().await;
+    let user_token = app.user_token(&uuid::Uuid::new_v4().to_string());
+
+    // Act
+    let anonymous = app.get_as("/api/admin/users/export.csv", None).await;
+    let user = app
+        .get_as("/api/admin/users/export.csv", Some(&user_token))
+        .await;
+
+    // Assert
+    assert_eq!(anonymous.status().as_u16(), 401);
+    assert_eq!(user.status().as_u16(), 403);
+}